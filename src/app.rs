@@ -1,10 +1,36 @@
-use crate::logic::{SharedState, pinger_task};
-use crate::model::{AppState, DisplaySettings, HostInfo, HostStatus, PingMode};
-use crate::ui::system_tools::{SystemToolsState, ui_system_tools_window};
+use crate::constants::{
+    APP_ICON_PNG, SESSION_COMPARISON_DELAY_SEC, SESSION_TOAST_SEC, SHUTDOWN_TIMEOUT_MS,
+    UPDATE_CHECK_INTERVAL_SEC,
+};
+use crate::logic::agent::AgentLink;
+use crate::logic::daemon::{DaemonClient, DaemonConfig};
+use crate::logic::diagnostics::MtuDiscovery;
+use crate::logic::live_trace::LiveTrace;
+use crate::logic::presenter::Severity;
+use crate::logic::profiles::ProfileChange;
+use crate::logic::summary::Summary;
+use crate::logic::updates::{UpdateCheck, UpdateChecker};
+use crate::logic::watchdog::Watchdog;
+use crate::logic::{SharedState, agent, applog, badge, baseline, daemon, host_order};
+use crate::model::{
+    AppSettings, AppState, Connectivity, HostLayout, HostSort, HostStatus, PlotSettings,
+    RecentFileKind, SessionBaseline, StatusColors, Thresholds,
+};
+use crate::ui::bufferbloat::BufferbloatState;
+use crate::ui::dashboard::DashboardState;
+use crate::ui::duplicate_host::DuplicateHostState;
+use crate::ui::host_group::{GroupSummary, SILENCE_DURATIONS_MIN, silence_label};
+use crate::ui::host_import::HostImportState;
+use crate::ui::log_viewer::LogViewerState;
+use crate::ui::menu_bar::MenuAction;
+use crate::ui::profiles::ProfilesState;
+use crate::ui::subnet_scan::SubnetScanState;
+use crate::ui::system_tools::{SystemToolsState, ToolsTab, ui_system_tools_window};
 use eframe::egui;
 use eframe::egui::Color32;
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tr::tr;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -27,12 +53,65 @@ pub struct EguiPinger {
     pub input_address: String,
     pub(crate) editing_host: Option<String>,
     pub(crate) deleting_host: Option<String>,
+    /// Host being duplicated in the duplicate dialog.
+    pub(crate) duplicating: Option<DuplicateHostState>,
     pub(crate) help_window_open: bool,
     pub(crate) selected_help_tab: HelpTab,
     pub(crate) viewing_route: Option<String>,
     pub viewing_log: Option<String>,
+    /// Host shown in the detailed plot window, if open.
+    pub(crate) viewing_plot: Option<String>,
+    pub(crate) compare_open: bool,
+    /// Addresses of the hosts in the comparison plot.
+    pub(crate) compare_hosts: Vec<String>,
+    pub(crate) pairs_open: bool,
+    /// Text the host list is filtered by.
+    pub(crate) host_filter: String,
+    /// Tag the host list is filtered by.
+    pub(crate) tag_filter: Option<String>,
+    pub(crate) log_viewer: LogViewerState,
     pub(crate) system_tools_open: bool,
     pub(crate) system_tools: SystemToolsState,
+    pub(crate) deleted_hosts_open: bool,
+    pub(crate) host_import_open: bool,
+    pub(crate) host_import: HostImportState,
+    pub(crate) subnet_scan_open: bool,
+    pub(crate) subnet_scan: SubnetScanState,
+    /// Welcome window offering the first-run setup.
+    pub(crate) first_run_open: bool,
+    pub(crate) action_log_open: bool,
+    pub(crate) settings_open: bool,
+    pub(crate) quiet_hours_open: bool,
+    pub(crate) annotations_open: bool,
+    pub(crate) profiles: ProfilesState,
+    pub(crate) updates: UpdateChecker,
+    pub(crate) kiosk_unlock_open: bool,
+    /// Wall dashboard, while it is shown.
+    pub(crate) dashboard: Option<DashboardState>,
+    /// Live trace shown in the trace window, if open.
+    pub(crate) live_trace: Option<LiveTrace>,
+    /// Bufferbloat check window, if open.
+    pub(crate) bufferbloat: Option<BufferbloatState>,
+    /// Path MTU discovery shown in the MTU window, if open.
+    pub(crate) mtu_discovery: Option<MtuDiscovery>,
+    /// Keeps the background pinger running; `None` when no pinger was started.
+    pub(crate) watchdog: Option<Watchdog>,
+    /// Link to the background daemon while this window shows its results.
+    pub(crate) daemon: Option<DaemonClient>,
+    /// Links to the remote agents whose hosts are shown.
+    pub(crate) agent_links: Vec<AgentLink>,
+    /// Last title sent to the window, to only send changes.
+    pub(crate) window_title: String,
+    /// Count last drawn on the window icon; 0 for the plain icon.
+    pub(crate) badge_count: usize,
+    /// Decoded application icon the badge is drawn on.
+    pub(crate) app_icon: Option<egui::IconData>,
+    /// Unix timestamp this session started at.
+    pub(crate) session_start: u64,
+    /// Baselines of the previous session until this one is compared with them.
+    pub(crate) previous_baselines: Option<HashMap<String, SessionBaseline>>,
+    /// Result of the comparison with the previous session and when it was shown first.
+    pub(crate) session_toast: Option<(Vec<String>, Instant)>,
 }
 
 /// Helper for application-specific colors adapted for light/dark themes.
@@ -42,13 +121,26 @@ pub struct EguiPinger {
 /// status changes, and grid styles.
 pub struct PingVisuals {
     pub is_dark: bool,
+    /// Dimensions of the history plots.
+    pub plot: PlotSettings,
+    /// Latency thresholds of what belongs to no single host.
+    pub thresholds: Thresholds,
+    /// Status colors of the chosen color theme.
+    pub colors: StatusColors,
 }
 
 impl PingVisuals {
-    /// Creates a new `PingVisuals` based on the current UI theme.
-    pub fn from_ctx(ctx: &egui::Context) -> Self {
+    /// Creates a new `PingVisuals` based on the current UI theme and the plot,
+    /// threshold and color settings.
+    pub fn from_ctx(ctx: &egui::Context, settings: &AppSettings) -> Self {
+        let is_dark = ctx.style().visuals.dark_mode;
         Self {
-            is_dark: ctx.style().visuals.dark_mode,
+            is_dark,
+            plot: settings.plot.clone(),
+            thresholds: settings.default_thresholds,
+            colors: settings
+                .color_theme
+                .colors(is_dark, &settings.custom_colors),
         }
     }
 
@@ -61,25 +153,45 @@ impl PingVisuals {
         }
     }
 
-    /// Returns a theme-aware color representing the given latency range.
-    pub fn latency_color(&self, rtt: f64) -> Color32 {
-        if rtt.is_nan() {
-            Color32::from_rgb(213, 94, 0) // Vermilion
-        } else if rtt > 300.0 {
-            Color32::from_rgb(204, 121, 167) // Reddish purple
-        } else if rtt > 150.0 {
-            if self.is_dark {
-                Color32::from_rgb(240, 228, 66) // Yellow
-            } else {
-                Color32::from_rgb(230, 159, 0) // Orange
-            }
-        } else if self.is_dark {
-            Color32::from_rgb(86, 180, 233) // Sky Blue
+    /// Returns the color of annotation markers in charts.
+    pub fn annotation_color(&self) -> Color32 {
+        if self.is_dark {
+            Color32::from_rgb(0, 158, 115) // Bluish green
+        } else {
+            Color32::from_rgb(0, 128, 90)
+        }
+    }
+
+    /// Returns the fill of spans without measurements in charts.
+    pub fn pause_color(&self) -> Color32 {
+        if self.is_dark {
+            Color32::from_rgba_unmultiplied(160, 160, 160, 60)
         } else {
-            Color32::from_rgb(0, 114, 178) // Blue
+            Color32::from_rgba_unmultiplied(110, 110, 110, 60)
         }
     }
 
+    /// Returns a theme-aware color representing the given latency range of the
+    /// default thresholds.
+    pub fn latency_color(&self, rtt: f64) -> Color32 {
+        self.host_latency_color(rtt, &self.thresholds)
+    }
+
+    /// Returns a theme-aware color representing the latency range of a host's
+    /// thresholds.
+    pub fn host_latency_color(&self, rtt: f64, thresholds: &Thresholds) -> Color32 {
+        let [r, g, b] = if rtt.is_nan() {
+            self.colors.loss
+        } else if rtt > thresholds.latency_bad {
+            self.colors.bad
+        } else if rtt > thresholds.latency_warn {
+            self.colors.warn
+        } else {
+            self.colors.ok
+        };
+        Color32::from_rgb(r, g, b)
+    }
+
     /// Returns an optional alert color if a value exceeds thresholds.
     pub fn value_color(
         &self,
@@ -88,38 +200,28 @@ impl PingVisuals {
         bad_th: f64,
         higher_is_better: bool,
     ) -> Option<Color32> {
-        if value.is_nan() {
-            return None;
-        }
-        let is_bad = if higher_is_better {
-            value < bad_th
-        } else {
-            value > bad_th
-        };
-        let is_warn = if higher_is_better {
-            value < warn_th
-        } else {
-            value > warn_th
-        };
+        self.severity_color(Severity::of(value, warn_th, bad_th, higher_is_better))
+    }
 
-        let bad_c = Color32::from_rgb(213, 94, 0); // Vermilion
-        let warn_c = if self.is_dark {
-            Color32::from_rgb(240, 228, 66)
-        } else {
-            Color32::from_rgb(230, 159, 0)
+    /// Returns the alert color of a severity, `None` for normal values.
+    pub fn severity_color(&self, severity: Severity) -> Option<Color32> {
+        let [r, g, b] = match severity {
+            Severity::Normal => return None,
+            Severity::Warn => self.colors.warn,
+            Severity::Bad => self.colors.loss,
         };
-
-        if is_bad {
-            Some(bad_c)
-        } else if is_warn {
-            Some(warn_c)
-        } else {
-            None
-        }
+        Some(Color32::from_rgb(r, g, b))
     }
 
-    /// Returns a color representing the combined availability and latency state.
-    pub fn status_color(&self, is_stopped: bool, alive: bool, latency: f64) -> Color32 {
+    /// Returns a color representing the combined availability and latency state,
+    /// the latency judged by `thresholds`.
+    pub fn status_color(
+        &self,
+        is_stopped: bool,
+        alive: bool,
+        latency: f64,
+        thresholds: &Thresholds,
+    ) -> Color32 {
         if is_stopped {
             if self.is_dark {
                 Color32::from_gray(128)
@@ -129,7 +231,7 @@ impl PingVisuals {
         } else if !alive {
             self.latency_color(f64::NAN)
         } else {
-            self.latency_color(latency)
+            self.host_latency_color(latency, thresholds)
         }
     }
 }
@@ -148,35 +250,216 @@ impl EguiPinger {
             None => AppState::default(),
         }));
 
-        let state_clone = state.clone();
-        std::thread::spawn(move || {
-            tokio::runtime::Builder::new_multi_thread()
-                .enable_all()
-                .build()
-                .unwrap()
-                .block_on(pinger_task(state_clone));
-        });
+        let daemon_state = state.clone();
+        let (previous_baselines, daemon) = {
+            let mut state = state.lock().expect("State mutex poisoned");
+            // Without a choice the language set up at startup follows the system locale
+            if !state.settings.language.is_empty() {
+                crate::logic::i18n::set_language(&state.settings.language);
+            }
+            // The results of the daemon wait for this lock, so they follow the restored history
+            let daemon = daemon::read_token().and_then(|token| {
+                DaemonClient::connect(daemon_state, state.settings.daemon_port, &token)
+            });
+            state.daemon_attached = daemon.is_some();
+            if state.settings.persistent_history
+                && let Some(known) = crate::logic::profiles::known_addresses(&state, cc.storage)
+            {
+                let known: Vec<&str> = known.iter().map(String::as_str).collect();
+                crate::logic::storage::prune(&known);
+            }
+            if state.settings.persistent_history || daemon.is_some() {
+                crate::logic::storage::restore_statuses(&mut state);
+            }
+            (state.baselines.clone(), daemon)
+        };
 
-        let app = Self {
-            state,
-            input_name: String::new(),
-            input_address: String::new(),
-            editing_host: None,
-            deleting_host: None,
-            help_window_open: false,
-            selected_help_tab: HelpTab::default(),
-            viewing_route: None,
-            viewing_log: None,
-            system_tools_open: false,
-            system_tools: SystemToolsState::default(),
+        // An attached window leaves the pinging to the daemon
+        let watchdog = daemon.is_none().then(|| Watchdog::start(state.clone()));
+
+        let now_ts = chrono::Utc::now().timestamp() as u64;
+        let (check_updates, offer_setup) = {
+            let mut state = state.lock().expect("State mutex poisoned");
+            state.purge_expired_deleted_hosts(now_ts);
+            let due = now_ts.saturating_sub(state.settings.last_update_check)
+                >= UPDATE_CHECK_INTERVAL_SEC;
+            let check_updates = if state.settings.check_for_updates && due {
+                state.settings.last_update_check = now_ts;
+                Some(state.settings.proxy.clone())
+            } else {
+                None
+            };
+            let offer_setup = state.hosts.is_empty() && !state.settings.setup_offered;
+            (check_updates, offer_setup)
         };
 
+        let mut app = Self::from_state(state);
+        app.watchdog = watchdog;
+        app.daemon = daemon;
+        app.previous_baselines = Some(previous_baselines);
+        app.first_run_open = offer_setup;
+        if let Some(proxy) = check_updates {
+            app.updates.start_check(false, proxy);
+        }
+
         // Add startup markers for hosts with logging enabled
         app.add_marker_to_all_active_logs(true);
 
         app
     }
 
+    /// Stops the pinger and waits for the results of the probes in flight.
+    fn stop_pinger(&mut self) {
+        if let Some(watchdog) = &mut self.watchdog {
+            watchdog.shutdown(Duration::from_millis(SHUTDOWN_TIMEOUT_MS));
+        }
+    }
+
+    /// Sends changed hosts and settings to the background daemon, and pings from
+    /// this window again when the daemon went away.
+    fn follow_daemon(&mut self) {
+        let Some(daemon) = &mut self.daemon else {
+            return;
+        };
+        if daemon.is_connected() {
+            daemon.sync_config(&self.state.lock().expect("State mutex poisoned"));
+            return;
+        }
+        applog::warn(
+            "daemon",
+            "The background daemon went away, pinging from this window",
+        );
+        self.daemon = None;
+        self.state
+            .lock()
+            .expect("State mutex poisoned")
+            .daemon_attached = false;
+        self.watchdog = Some(Watchdog::start(self.state.clone()));
+    }
+
+    /// Carries out the profile change picked in the UI, which needs the storage
+    /// the profiles are kept in.
+    fn apply_profile_change(&mut self, frame: &mut eframe::Frame) {
+        self.profiles
+            .follow_network(&self.state.lock().expect("State mutex poisoned"));
+        let Some(change) = self.profiles.pending.take() else {
+            return;
+        };
+        let Some(storage) = frame.storage_mut() else {
+            self.profiles.error = Some(tr!("Profiles need a persistent storage"));
+            self.profiles.open = true;
+            return;
+        };
+        let mut state = self.state.lock().expect("State mutex poisoned");
+        match change.apply(&mut state, storage) {
+            Ok(()) => {
+                self.profiles.error = None;
+                if state.settings.persistent_history {
+                    crate::logic::storage::restore_statuses(&mut state);
+                }
+                applog::info("profiles", format!("Active profile: \"{}\"", state.profile));
+                // Windows of hosts of the previous profile
+                self.editing_host = None;
+                self.deleting_host = None;
+                self.duplicating = None;
+                self.viewing_route = None;
+                self.viewing_log = None;
+                self.viewing_plot = None;
+                self.compare_hosts.clear();
+                self.tag_filter = None;
+            }
+            Err(e) => {
+                self.profiles.error = Some(e);
+                self.profiles.open = true;
+            }
+        }
+    }
+
+    /// Requests screenshots queued by incidents and saves the ones that arrived.
+    ///
+    /// One screenshot is requested per frame; egui delivers it with the input
+    /// of a following frame.
+    fn take_incident_screenshots(&self, ctx: &egui::Context) {
+        let shots: Vec<(String, Arc<egui::ColorImage>)> = ctx.input(|i| {
+            i.events
+                .iter()
+                .filter_map(|e| match e {
+                    egui::Event::Screenshot {
+                        user_data, image, ..
+                    } => user_data
+                        .data
+                        .as_ref()
+                        .and_then(|d| d.downcast_ref::<String>())
+                        .map(|stem| (stem.clone(), image.clone())),
+                    _ => None,
+                })
+                .collect()
+        });
+        for (stem, image) in shots {
+            crate::logic::screenshot::save(image, stem);
+        }
+
+        let next = self
+            .state
+            .lock()
+            .expect("State mutex poisoned")
+            .pending_screenshots
+            .pop_front();
+        if let Some(stem) = next {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(egui::UserData::new(stem)));
+        }
+    }
+
+    /// Compares the first minutes of this session with the previous one and shows
+    /// the hosts that got worse for a while.
+    fn compare_with_previous_session(&mut self, ctx: &egui::Context) {
+        let due = chrono::Utc::now().timestamp() as u64
+            >= self.session_start + SESSION_COMPARISON_DELAY_SEC;
+        if due && let Some(baselines) = self.previous_baselines.take() {
+            let state = self.state.lock().expect("State mutex poisoned");
+            let now = chrono::Utc::now().timestamp() as u64;
+            let messages = baseline::compare(&state, &baselines, self.session_start, now);
+            for message in &messages {
+                applog::info("baseline", message.clone());
+            }
+            if state.settings.compare_sessions && !messages.is_empty() {
+                self.session_toast = Some((messages, Instant::now()));
+            }
+        }
+
+        if let Some((messages, shown)) = &self.session_toast {
+            let closed = crate::ui::toast::render_toast(ctx, messages);
+            if closed || shown.elapsed() >= Duration::from_secs(SESSION_TOAST_SEC) {
+                self.session_toast = None;
+            }
+        }
+    }
+
+    /// Shows the host count, the hosts that are down and the probe rate in the window
+    /// title, and the down hosts in a badge on the window icon.
+    fn update_window_title(&mut self, ctx: &egui::Context) {
+        let (summary, badge) = {
+            let state = self.state.lock().expect("State mutex poisoned");
+            (Summary::compute(&state), state.settings.taskbar_badge)
+        };
+        let title = summary.title();
+        if title != self.window_title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.window_title = title;
+        }
+
+        let count = if badge { summary.down } else { 0 };
+        if count != self.badge_count {
+            let icon = self.app_icon.get_or_insert_with(|| {
+                eframe::icon_data::from_png_bytes(APP_ICON_PNG).unwrap_or_default()
+            });
+            ctx.send_viewport_cmd(egui::ViewportCommand::Icon(Some(Arc::new(
+                badge::badge_icon(icon, count),
+            ))));
+            self.badge_count = count;
+        }
+    }
+
     fn add_marker_to_all_active_logs(&self, is_start: bool) {
         let mut state = self.state.lock().expect("State mutex poisoned");
         let msg = if is_start {
@@ -217,84 +500,242 @@ impl EguiPinger {
             input_address: String::new(),
             editing_host: None,
             deleting_host: None,
+            duplicating: None,
             help_window_open: false,
             selected_help_tab: HelpTab::default(),
             viewing_route: None,
+            live_trace: None,
+            bufferbloat: None,
+            mtu_discovery: None,
             viewing_log: None,
+            viewing_plot: None,
+            compare_open: false,
+            compare_hosts: Vec::new(),
+            pairs_open: false,
+            host_filter: String::new(),
+            tag_filter: None,
+            log_viewer: LogViewerState::default(),
             system_tools_open: false,
             system_tools: SystemToolsState::default(),
+            deleted_hosts_open: false,
+            host_import_open: false,
+            host_import: HostImportState::default(),
+            subnet_scan_open: false,
+            subnet_scan: SubnetScanState::default(),
+            first_run_open: false,
+            action_log_open: false,
+            settings_open: false,
+            quiet_hours_open: false,
+            annotations_open: false,
+            profiles: ProfilesState::default(),
+            updates: UpdateChecker::default(),
+            kiosk_unlock_open: false,
+            dashboard: None,
+            watchdog: None,
+            daemon: None,
+            agent_links: Vec::new(),
+            window_title: String::new(),
+            badge_count: 0,
+            app_icon: None,
+            session_start: chrono::Utc::now().timestamp() as u64,
+            previous_baselines: None,
+            session_toast: None,
+        }
+    }
+
+    /// Shows the hosts full screen for a wall display, without editing controls.
+    pub fn enter_dashboard(&mut self, ctx: &egui::Context) {
+        if self.dashboard.is_none() {
+            let settings = self
+                .state
+                .lock()
+                .expect("State mutex poisoned")
+                .settings
+                .dashboard
+                .clone();
+            self.dashboard = Some(DashboardState::enter(ctx, &settings));
+        }
+    }
+
+    fn leave_dashboard(&mut self, ctx: &egui::Context) {
+        if let Some(dashboard) = self.dashboard.take() {
+            dashboard.leave(ctx);
+        }
+    }
+
+    /// Carries out an action picked in the menu bar.
+    fn apply_menu_action(&mut self, ctx: &egui::Context, action: MenuAction) {
+        match action {
+            MenuAction::OpenTools(tab) => {
+                self.system_tools.selected_tab = tab;
+                self.system_tools_open = true;
+            }
+            MenuAction::OpenRecent(file) => match file.kind {
+                RecentFileKind::Capture => {
+                    self.system_tools.import_path = file.path;
+                    self.system_tools.import_address = file.address;
+                    self.system_tools.selected_tab = ToolsTab::Import;
+                    self.system_tools_open = true;
+                }
+                RecentFileKind::HostList => {
+                    let mut state = self.state.lock().expect("State mutex poisoned");
+                    state.settings.shared_host_list = file.path.clone();
+                    state.settings.remember_file(file);
+                    self.settings_open = true;
+                }
+            },
+            MenuAction::ImportHosts => self.host_import_open = true,
+            MenuAction::ScanNetwork => {
+                if self.subnet_scan.range.is_empty() {
+                    self.subnet_scan.range =
+                        crate::logic::subnet_scan::local_subnet().unwrap_or_default();
+                }
+                self.subnet_scan_open = true;
+            }
+            MenuAction::ClearRecent => {
+                let mut state = self.state.lock().expect("State mutex poisoned");
+                state.settings.recent_files.clear();
+            }
+            MenuAction::Settings => self.settings_open = true,
+            MenuAction::Profiles => self.profiles.open = true,
+            MenuAction::SwitchProfile(name) => {
+                self.profiles.pending = Some(ProfileChange::Switch(name));
+            }
+            MenuAction::QuietHours => self.quiet_hours_open = true,
+            MenuAction::Annotations => self.annotations_open = true,
+            MenuAction::Compare => self.compare_open = true,
+            MenuAction::Pairs => self.pairs_open = true,
+            MenuAction::Dashboard => self.enter_dashboard(ctx),
+            MenuAction::DeletedHosts => self.deleted_hosts_open = true,
+            MenuAction::ActionLog => self.action_log_open = true,
+            MenuAction::Help => self.help_window_open = true,
+            MenuAction::CheckUpdates => {
+                let proxy = {
+                    let state = self.state.lock().expect("State mutex poisoned");
+                    state.settings.proxy.clone()
+                };
+                self.updates.start_check(true, proxy);
+            }
+            MenuAction::Quit => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
         }
     }
 
     /// The main UI decomposition function that orchestrates all sub-windows and the host list.
     pub fn ui_layout(&mut self, ctx: &egui::Context) {
+        if self.dashboard.is_some() && ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.leave_dashboard(ctx);
+        }
+        let kiosk_locked = self
+            .state
+            .lock()
+            .expect("State mutex poisoned")
+            .settings
+            .kiosk_locked;
+        // The dashboard shows the same read-only view as the locked kiosk mode
+        let locked = kiosk_locked || self.dashboard.is_some();
+        if locked {
+            // Close everything that can change hosts or settings
+            self.editing_host = None;
+            self.deleting_host = None;
+            self.duplicating = None;
+            self.viewing_log = None;
+            self.settings_open = false;
+            self.quiet_hours_open = false;
+            self.annotations_open = false;
+            self.profiles.open = false;
+            self.system_tools_open = false;
+            self.deleted_hosts_open = false;
+            self.host_import_open = false;
+            self.subnet_scan_open = false;
+            self.first_run_open = false;
+            self.action_log_open = false;
+        }
+        if !kiosk_locked {
+            self.kiosk_unlock_open = false;
+        }
+
+        if !locked {
+            let (recent, has_deleted, has_actions, profiles, active_profile) = {
+                let state = self.state.lock().expect("State mutex poisoned");
+                (
+                    state.settings.recent_files.clone(),
+                    !state.deleted_hosts.is_empty(),
+                    !state.action_log.is_empty(),
+                    state.profile_names(),
+                    state.profile.clone(),
+                )
+            };
+            let action = egui::TopBottomPanel::top("menu_bar")
+                .show(ctx, |ui| {
+                    crate::ui::menu_bar::render_menu_bar(
+                        ui,
+                        &recent,
+                        has_deleted,
+                        has_actions,
+                        &profiles,
+                        &active_profile,
+                    )
+                })
+                .inner;
+            if let Some(action) = action {
+                self.apply_menu_action(ctx, action);
+            }
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             egui::ScrollArea::vertical().show(ui, |ui| {
                 egui::ScrollArea::horizontal().show(ui, |ui| {
                     ui.horizontal(|ui| {
-                        let name_field_id = ui.make_persistent_id("name_field");
-                        let addr_field_id = ui.make_persistent_id("addr_field");
-
-                        let rs1 = ui.add(
-                            egui::TextEdit::singleline(&mut self.input_name)
-                                .id(name_field_id)
-                                .char_limit(256)
-                                .hint_text(tr!("Host name"))
-                                .desired_width(256.0),
-                        );
+                        if !locked {
+                            let name_field_id = ui.make_persistent_id("name_field");
+                            let addr_field_id = ui.make_persistent_id("addr_field");
 
-                        let rs2 = ui.add(
-                            egui::TextEdit::singleline(&mut self.input_address)
-                                .id(addr_field_id)
-                                .char_limit(256)
-                                .hint_text(tr!("Host address"))
-                                .desired_width(256.0),
-                        );
+                            let rs1 = ui.add(
+                                egui::TextEdit::singleline(&mut self.input_name)
+                                    .id(name_field_id)
+                                    .char_limit(256)
+                                    .hint_text(tr!("Host name"))
+                                    .desired_width(256.0),
+                            );
 
-                        // When "Add" button is clicked or Enter is pressed in the second field,
-                        // add host to the list
-                        if (ui.button(tr!("Add")).clicked()
-                            || (rs2.lost_focus()
-                                && rs2.ctx.input(|i| i.key_pressed(egui::Key::Enter))))
-                            && !self.input_address.trim().is_empty()
-                        {
-                            let name = self.input_name.trim().to_string();
-                            let address = self.input_address.trim().to_lowercase();
+                            let rs2 = ui.add(
+                                egui::TextEdit::singleline(&mut self.input_address)
+                                    .id(addr_field_id)
+                                    .char_limit(256)
+                                    .hint_text(tr!("Host address or URL"))
+                                    .desired_width(256.0),
+                            )
+                            .on_hover_text(tr!(
+                                "An IP address or host name, or an http(s)://, dns:// or udp:// URL. Add @interface or @local-address to ping from there, e.g. 8.8.8.8@wlan0 next to 8.8.8.8@eth0 to compare two links."
+                            ));
 
-                            let mut state = self.state.lock().expect("State mutex poisoned");
-                            if !state.hosts.iter().any(|h| h.address == address) {
-                                state
-                                    .statuses
-                                    .insert(address.clone(), HostStatus::default());
-                                let mut host_info = HostInfo {
-                                    name,
-                                    address,
-                                    mode: PingMode::NotFast,
-                                    display: DisplaySettings::default(),
-                                    packet_size: 16,
-                                    random_padding: false,
-                                    log_to_file: false,
-                                    log_file_path: String::new(),
-                                    is_stopped: false,
-                                };
-                                if host_info.is_local() {
-                                    host_info.mode = PingMode::Fast;
-                                }
-                                state.hosts.push(host_info);
-                            }
+                            // When "Add" button is clicked or Enter is pressed in the second field,
+                            // add host to the list
+                            if (ui.button(tr!("Add")).clicked()
+                                || (rs2.lost_focus()
+                                    && rs2.ctx.input(|i| i.key_pressed(egui::Key::Enter))))
+                                && !self.input_address.trim().is_empty()
+                            {
+                                self.state
+                                    .lock()
+                                    .expect("State mutex poisoned")
+                                    .add_host(&self.input_name, &self.input_address);
 
-                            self.input_name.clear();
-                            self.input_address.clear();
+                                self.input_name.clear();
+                                self.input_address.clear();
 
-                            ui.memory_mut(|mem| mem.request_focus(name_field_id));
-                        }
+                                ui.memory_mut(|mem| mem.request_focus(name_field_id));
+                            }
 
-                        // When Enter is pressed in the first field, move focus to the second field
-                        if rs1.lost_focus() && rs1.ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
-                            ui.memory_mut(|mem| mem.request_focus(addr_field_id));
+                            // When Enter is pressed in the first field, move focus to the second field
+                            if rs1.lost_focus()
+                                && rs1.ctx.input(|i| i.key_pressed(egui::Key::Enter))
+                            {
+                                ui.memory_mut(|mem| mem.request_focus(addr_field_id));
+                            }
                         }
 
-                        // Theme toggle and tools button (right-aligned)
+                        // Theme toggle and unlock button (right-aligned)
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                             let mut theme = ui.ctx().options(|o| o.theme_preference);
                             let old_theme = theme;
@@ -302,8 +743,18 @@ impl EguiPinger {
                             if theme != old_theme {
                                 ui.ctx().options_mut(|o| o.theme_preference = theme);
                             }
-                            if ui.button("🔧").on_hover_text(tr!("System Tools")).clicked() {
-                                self.system_tools_open = !self.system_tools_open;
+                            if kiosk_locked
+                                && ui.button("🔒").on_hover_text(tr!("Unlock")).clicked()
+                            {
+                                self.kiosk_unlock_open = true;
+                            }
+                            if self.dashboard.is_some()
+                                && ui
+                                    .button("⛶")
+                                    .on_hover_text(tr!("Leave the wall dashboard (Esc)"))
+                                    .clicked()
+                            {
+                                self.leave_dashboard(ctx);
                             }
                         });
                     });
@@ -312,33 +763,202 @@ impl EguiPinger {
 
                     // Clone only the Arc to decouple MutexGuard from self
                     let state_arc = self.state.clone();
-                    let visuals = PingVisuals::from_ctx(ctx);
+                    let visuals = PingVisuals::from_ctx(
+                        ctx,
+                        &state_arc.lock().expect("State mutex poisoned").settings,
+                    );
+                    let summary =
+                        Summary::compute(&state_arc.lock().expect("State mutex poisoned"));
+                    crate::ui::summary_bar::render_summary_bar(ui, &visuals, &summary);
+                    {
+                        let state = state_arc.lock().expect("State mutex poisoned");
+                        if state.settings.public_ip_check {
+                            crate::ui::public_ip::render_public_ip_panel(ui, &state.public_ip);
+                        }
+                    }
+                    let (sort, sort_reversed, layout) = {
+                        let mut state = state_arc.lock().expect("State mutex poisoned");
+                        let tags = host_order::all_tags(&state.hosts);
+                        if let Some(tag) = &self.tag_filter
+                            && !tags.contains(tag)
+                        {
+                            self.tag_filter = None;
+                        }
+                        let settings = &mut state.settings;
+                        if self.dashboard.is_none() {
+                            crate::ui::host_filter::render_host_filter_bar(
+                                ui,
+                                &mut self.host_filter,
+                                &tags,
+                                &mut self.tag_filter,
+                                &mut settings.host_sort,
+                                &mut settings.host_sort_reversed,
+                                &mut settings.host_layout,
+                            );
+                        }
+                        (
+                            settings.host_sort,
+                            settings.host_sort_reversed,
+                            settings.host_layout,
+                        )
+                    };
+                    let reorderable = sort == HostSort::Manual
+                        && self.host_filter.trim().is_empty()
+                        && self.tag_filter.is_none();
+
+                    crate::ui::watchdog::render_watchdog_banner(
+                        ui,
+                        self.watchdog.as_ref().and_then(Watchdog::notice),
+                    );
+                    let behind_portal = {
+                        let mut state = self.state.lock().expect("State mutex poisoned");
+                        crate::ui::alert_banner::render_alert_banner(ui, &state);
+                        let budget_warning = crate::logic::budget::check(&state);
+                        if let Some(mode) = crate::ui::probe_budget::render_probe_budget_banner(
+                            ui,
+                            budget_warning.as_ref(),
+                            !locked,
+                        ) {
+                            crate::logic::budget::apply_suggestion(&mut state, mode);
+                        }
+                        matches!(state.connectivity, Connectivity::CaptivePortal { .. })
+                    };
+
                     let default_host_status = HostStatus::default();
                     let mut moved = None;
                     let mut toggled_stop = None;
+                    let mut silence = None;
+                    let now_ts = chrono::Utc::now().timestamp() as u64;
 
                     {
                         let state = state_arc.lock().expect("State mutex poisoned");
 
+                        // Ungrouped hosts first, then groups in order of first appearance
+                        let mut groups: Vec<(&str, Vec<usize>)> = vec![("", Vec::new())];
                         for (idx, host_info) in state.hosts.iter().enumerate() {
-                            let status = state
-                                .statuses
-                                .get(&host_info.address)
-                                .unwrap_or(&default_host_status);
+                            let group = host_info.group.trim();
+                            match groups.iter_mut().find(|(g, _)| *g == group) {
+                                Some((_, members)) => members.push(idx),
+                                None => groups.push((group, vec![idx])),
+                            }
+                        }
+                        // Filtered and sorted within each group; groups without
+                        // matching hosts are hidden
+                        for (_, members) in &mut groups {
+                            members.retain(|&i| {
+                                let host = &state.hosts[i];
+                                host_order::has_tag(host, self.tag_filter.as_deref())
+                                    && host_order::matches_filter(
+                                        host,
+                                        state.statuses.get(&host.address),
+                                        &self.host_filter,
+                                    )
+                            });
+                            host_order::sort_hosts(members, &state, sort, sort_reversed);
+                        }
+                        groups.retain(|(group, members)| group.is_empty() || !members.is_empty());
 
-                            crate::ui::host_row::render_host_row(
-                                ui,
+                        let mut render_rows = |ui: &mut egui::Ui, members: &[usize]| {
+                            if layout == HostLayout::Grid {
+                                let hosts = members.iter().map(|&idx| {
+                                    let host_info = &state.hosts[idx];
+                                    let status = state
+                                        .statuses
+                                        .get(&host_info.address)
+                                        .unwrap_or(&default_host_status);
+                                    (host_info, status)
+                                });
+                                crate::ui::host_card::render_host_grid(
+                                    ui,
+                                    &visuals,
+                                    hosts,
+                                    &mut self.viewing_plot,
+                                );
+                                return;
+                            }
+                            for &idx in members {
+                                let host_info = &state.hosts[idx];
+                                let status = state
+                                    .statuses
+                                    .get(&host_info.address)
+                                    .unwrap_or(&default_host_status);
+
+                                crate::ui::host_row::render_host_row(
+                                    ui,
+                                    &visuals,
+                                    host_info,
+                                    status,
+                                    &state.annotations,
+                                    idx,
+                                    &mut self.deleting_host,
+                                    &mut self.editing_host,
+                                    &mut self.viewing_route,
+                                    &mut self.viewing_log,
+                                    &mut self.viewing_plot,
+                                    &mut toggled_stop,
+                                    &mut moved,
+                                    &mut self.tag_filter,
+                                    reorderable,
+                                    behind_portal,
+                                    locked,
+                                );
+                            }
+                        };
+
+                        let scroll_sec = state.settings.dashboard.scroll_sec;
+                        let scroll_to = self
+                            .dashboard
+                            .as_mut()
+                            .and_then(|d| d.scroll_target(groups.len(), scroll_sec));
+                        for (i, (group, members)) in groups.iter().enumerate() {
+                            if scroll_to == Some(i) {
+                                ui.scroll_to_cursor(Some(egui::Align::TOP));
+                            }
+                            if group.is_empty() {
+                                render_rows(ui, members);
+                                continue;
+                            }
+                            let summary = GroupSummary::compute(members.iter().map(|&i| {
+                                let h = &state.hosts[i];
+                                (
+                                    h,
+                                    state
+                                        .statuses
+                                        .get(&h.address)
+                                        .unwrap_or(&default_host_status),
+                                )
+                            }));
+                            let silenced_for = state
+                                .silenced_until(group, now_ts)
+                                .map(|until| until - now_ts);
+                            let res = egui::CollapsingHeader::new(summary.header(
+                                group,
                                 &visuals,
-                                host_info,
-                                status,
-                                idx,
-                                &mut self.deleting_host,
-                                &mut self.editing_host,
-                                &mut self.viewing_route,
-                                &mut self.viewing_log,
-                                &mut toggled_stop,
-                                &mut moved,
-                            );
+                                silenced_for,
+                            ))
+                            .id_salt(("host_group", *group))
+                            .default_open(true)
+                            .show(ui, |ui| render_rows(ui, members));
+                            if !locked {
+                                res.header_response.context_menu(|ui| {
+                                    ui.label(tr!("Silence alerts for"));
+                                    for minutes in SILENCE_DURATIONS_MIN {
+                                        if ui.button(silence_label(minutes)).clicked() {
+                                            silence = Some((
+                                                group.to_string(),
+                                                Some(now_ts + minutes * 60),
+                                            ));
+                                            ui.close();
+                                        }
+                                    }
+                                    if silenced_for.is_some()
+                                        && ui.button(tr!("End silence")).clicked()
+                                    {
+                                        silence = Some((group.to_string(), None));
+                                        ui.close();
+                                    }
+                                });
+                            }
                         }
                     } // End of state MutexGuard scope
 
@@ -347,10 +967,19 @@ impl EguiPinger {
                         && from != to
                     {
                         let mut state = self.state.lock().expect("State mutex poisoned");
-                        let item = state.hosts.remove(from);
+                        // A host dropped onto a row of another group joins that group
+                        let group = state.hosts[to].group.clone();
+                        let mut item = state.hosts.remove(from);
+                        item.group = group;
                         state.hosts.insert(to, item);
                     }
 
+                    if let Some((group, until)) = silence {
+                        let mut state = self.state.lock().expect("State mutex poisoned");
+                        state.purge_expired_silences(now_ts);
+                        state.set_group_silence(&group, until, now_ts);
+                    }
+
                     if let Some(idx) = toggled_stop {
                         let mut state = self.state.lock().expect("State mutex poisoned");
                         if let Some(host) = state.hosts.get_mut(idx) {
@@ -404,13 +1033,33 @@ impl EguiPinger {
                                     name,
                                     address
                                 ));
+                                ui.add_space(8.0);
+                                let mut state = self.state.lock().expect("State mutex poisoned");
+                                ui.horizontal(|ui| {
+                                    ui.checkbox(
+                                        &mut state.settings.retain_deleted_hosts,
+                                        tr!("Keep history for"),
+                                    );
+                                    ui.add(
+                                        egui::DragValue::new(
+                                            &mut state.settings.deleted_host_retention_days,
+                                        )
+                                        .range(1..=3650)
+                                        .suffix(tr!(" days")),
+                                    );
+                                });
                                 ui.add_space(12.0);
                                 ui.horizontal(|ui| {
                                     if ui.button(tr!("Delete")).clicked() {
-                                        let mut state =
-                                            self.state.lock().expect("State mutex poisoned");
-                                        state.hosts.retain(|h| h.address != address);
-                                        state.statuses.remove(&address);
+                                        let retain_days = state
+                                            .settings
+                                            .retain_deleted_hosts
+                                            .then_some(state.settings.deleted_host_retention_days);
+                                        state.delete_host(
+                                            &address,
+                                            retain_days,
+                                            chrono::Utc::now().timestamp() as u64,
+                                        );
                                         self.deleting_host = None;
                                     }
                                     if ui.button(tr!("Cancel")).clicked() {
@@ -423,15 +1072,34 @@ impl EguiPinger {
                     // Host settings dialog
                     if self.editing_host.is_some() {
                         let mut state = self.state.lock().expect("State mutex poisoned");
+                        let mut interfaces: Vec<String> =
+                            state.interfaces.keys().cloned().collect();
+                        interfaces.sort();
                         if crate::ui::host_settings::render_host_settings_window(
                             ctx,
                             &mut state.hosts,
                             &mut self.editing_host,
+                            &mut self.live_trace,
+                            &mut self.bufferbloat,
+                            &mut self.mtu_discovery,
+                            &mut self.duplicating,
+                            &interfaces,
                         ) {
                             self.help_window_open = true;
                         }
                     }
 
+                    // --- Duplicate Host Window ---
+                    if self.duplicating.is_some() {
+                        let mut state = self.state.lock().expect("State mutex poisoned");
+                        crate::ui::duplicate_host::render_duplicate_host_window(
+                            ctx,
+                            &mut self.duplicating,
+                            &mut state,
+                            &mut self.editing_host,
+                        );
+                    }
+
                     // Traceroute viewer dialog
                     if self.viewing_route.is_some() {
                         let mut state = self.state.lock().expect("State mutex poisoned");
@@ -443,6 +1111,63 @@ impl EguiPinger {
                         );
                     }
 
+                    // Detailed plot window
+                    if self.viewing_plot.is_some() {
+                        let state = self.state.lock().expect("State mutex poisoned");
+                        crate::ui::plot_window::render_plot_window(
+                            ctx,
+                            &visuals,
+                            &state,
+                            &mut self.viewing_plot,
+                        );
+                    }
+
+                    // Host comparison window
+                    if self.compare_open {
+                        let state = self.state.lock().expect("State mutex poisoned");
+                        crate::ui::compare_window::render_compare_window(
+                            ctx,
+                            &visuals,
+                            &state,
+                            &mut self.compare_hosts,
+                            &mut self.compare_open,
+                        );
+                    }
+
+                    // Paired hosts window
+                    if self.pairs_open {
+                        let state = self.state.lock().expect("State mutex poisoned");
+                        crate::ui::pair_window::render_pair_window(
+                            ctx,
+                            &visuals,
+                            &state,
+                            &mut self.pairs_open,
+                        );
+                    }
+
+                    // Live trace window
+                    crate::ui::trace_view::render_trace_window(ctx, &visuals, &mut self.live_trace);
+
+                    // Path MTU window
+                    crate::ui::mtu::render_mtu_window(ctx, &visuals, &mut self.mtu_discovery);
+
+                    // Bufferbloat check window
+                    if self.bufferbloat.is_some() {
+                        let proxy = self
+                            .state
+                            .lock()
+                            .expect("State mutex poisoned")
+                            .settings
+                            .proxy
+                            .clone();
+                        crate::ui::bufferbloat::render_bufferbloat_window(
+                            ctx,
+                            &visuals,
+                            &mut self.bufferbloat,
+                            &proxy,
+                        );
+                    }
+
                     // Help window
                     if self.help_window_open {
                         crate::ui::help::render_help_window(
@@ -458,6 +1183,141 @@ impl EguiPinger {
                             ctx,
                             &mut self.system_tools_open,
                             &mut self.system_tools,
+                            &self.state,
+                        );
+                    }
+
+                    // --- Settings Window ---
+                    if self.settings_open {
+                        let mut kiosk_lock = None;
+                        let mut guard = self.state.lock().expect("State mutex poisoned");
+                        let state = &mut *guard;
+                        crate::ui::settings::render_settings_window(
+                            ctx,
+                            &mut state.settings,
+                            &state.shared_list,
+                            &state.dhcp_leases,
+                            state.stream_error.as_deref(),
+                            state.daemon_attached,
+                            state.agent_error.as_deref(),
+                            &state.remote_agent_status,
+                            state.api_error.as_deref(),
+                            (state.mqtt_connected, state.mqtt_error.as_deref()),
+                            &mut self.updates,
+                            &mut self.quiet_hours_open,
+                            &mut kiosk_lock,
+                            &mut self.settings_open,
+                        );
+                        drop(guard);
+                        if let Some(password) = kiosk_lock {
+                            crate::ui::kiosk::lock(ctx, &self.state, password);
+                        }
+                    }
+
+                    // --- Quiet Hours Window ---
+                    if self.quiet_hours_open {
+                        let mut state = self.state.lock().expect("State mutex poisoned");
+                        crate::ui::quiet_hours::render_quiet_hours_window(
+                            ctx,
+                            &mut state,
+                            &mut self.quiet_hours_open,
+                        );
+                    }
+
+                    // --- Annotations Window ---
+                    if self.annotations_open {
+                        let mut state = self.state.lock().expect("State mutex poisoned");
+                        crate::ui::annotations::render_annotations_window(
+                            ctx,
+                            &mut state,
+                            &mut self.annotations_open,
+                        );
+                    }
+
+                    // --- Profiles Window ---
+                    if self.profiles.open {
+                        let mut state = self.state.lock().expect("State mutex poisoned");
+                        crate::ui::profiles::render_profiles_window(
+                            ctx,
+                            &mut self.profiles,
+                            &mut state,
+                        );
+                    }
+
+                    // --- Kiosk Unlock Window ---
+                    if self.kiosk_unlock_open {
+                        crate::ui::kiosk::render_unlock_window(
+                            ctx,
+                            &self.state,
+                            &mut self.kiosk_unlock_open,
+                        );
+                    }
+
+                    // --- Software Update Dialog ---
+                    if self.updates.poll()
+                        && !self.updates.manual
+                        && !matches!(self.updates.result, Some(UpdateCheck::Available(_)))
+                    {
+                        // Automatic checks stay silent unless there is something to show
+                        self.updates.result = None;
+                    }
+                    let proxy = self
+                        .state
+                        .lock()
+                        .expect("State mutex poisoned")
+                        .settings
+                        .proxy
+                        .clone();
+                    crate::ui::update_dialog::render_update_dialog(ctx, &mut self.updates, &proxy);
+
+                    // --- Deleted Hosts Window ---
+                    if self.deleted_hosts_open {
+                        let mut state = self.state.lock().expect("State mutex poisoned");
+                        crate::ui::deleted_hosts::render_deleted_hosts_window(
+                            ctx,
+                            &mut state,
+                            &mut self.deleted_hosts_open,
+                        );
+                    }
+
+                    // --- Host Import Window ---
+                    if self.host_import_open {
+                        let mut state = self.state.lock().expect("State mutex poisoned");
+                        crate::ui::host_import::render_host_import_window(
+                            ctx,
+                            &mut self.host_import,
+                            &mut state,
+                            &mut self.host_import_open,
+                        );
+                    }
+
+                    // --- Network Scan Window ---
+                    if self.subnet_scan_open || self.subnet_scan.scan.is_some() {
+                        let mut state = self.state.lock().expect("State mutex poisoned");
+                        crate::ui::subnet_scan::render_subnet_scan_window(
+                            ctx,
+                            &mut self.subnet_scan,
+                            &mut state,
+                            &mut self.subnet_scan_open,
+                        );
+                    }
+
+                    // --- First Run Window ---
+                    if self.first_run_open {
+                        crate::ui::first_run::render_first_run_window(
+                            ctx,
+                            &self.state,
+                            &mut self.first_run_open,
+                        );
+                    }
+
+                    // --- Action Log Window ---
+                    if self.action_log_open {
+                        let mut state = self.state.lock().expect("State mutex poisoned");
+                        crate::ui::action_log::render_action_log_window(
+                            ctx,
+                            &mut state,
+                            &mut self.action_log_open,
                         );
                     }
 
@@ -469,6 +1329,7 @@ impl EguiPinger {
                             &visuals,
                             &mut state,
                             &mut self.viewing_log,
+                            &mut self.log_viewer,
                         );
                     }
                 })
@@ -479,17 +1340,53 @@ impl EguiPinger {
 
 impl eframe::App for EguiPinger {
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        let state = self.state.lock().expect("State mutex poisoned");
+        let mut state = self.state.lock().expect("State mutex poisoned");
+        state.purge_expired_deleted_hosts(chrono::Utc::now().timestamp() as u64);
+        baseline::record_baselines(
+            &mut state,
+            self.session_start,
+            chrono::Utc::now().timestamp() as u64,
+        );
         let serialized = serde_json::to_string_pretty(&*state).unwrap_or_default();
         storage.set_string(eframe::APP_KEY, serialized);
+        if let Err(e) = daemon::write_config(&DaemonConfig::of(&state)) {
+            applog::warn(
+                "daemon",
+                format!("Failed to save the hosts for the background daemon: {}", e),
+            );
+        }
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.stop_pinger();
+        if self
+            .state
+            .lock()
+            .expect("State mutex poisoned")
+            .settings
+            .persistent_history
+        {
+            crate::logic::storage::sync();
+        }
         self.add_marker_to_all_active_logs(false);
     }
 
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // The last save follows the close request, so the probes in flight are
+        // applied first
+        if ctx.input(|i| i.viewport().close_requested()) {
+            self.stop_pinger();
+        }
+        if let Some(watchdog) = &mut self.watchdog {
+            watchdog.check();
+        }
+        self.follow_daemon();
+        agent::follow_links(&self.state, &mut self.agent_links);
         self.ui_layout(ctx);
+        self.apply_profile_change(frame);
+        self.take_incident_screenshots(ctx);
+        self.update_window_title(ctx);
+        self.compare_with_previous_session(ctx);
         ctx.request_repaint_after(Duration::from_millis(1000));
     }
 }
@@ -0,0 +1,100 @@
+//! Background daemon that keeps pinging the hosts of egui_pinger and recording
+//! their history while no window is open.
+//!
+//! Usage: `egui_pinger_daemon`. It pings the hosts with the settings the window
+//! saved last; a window started while it runs attaches to it and shows its
+//! results instead of pinging itself. Stops on Ctrl+C or SIGTERM.
+
+use egui_pinger::constants::SHUTDOWN_TIMEOUT_MS;
+use egui_pinger::logic::daemon;
+use egui_pinger::logic::watchdog::Watchdog;
+use egui_pinger::logic::{i18n, storage};
+use egui_pinger::model::AppState;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Completes when the daemon is asked to stop.
+async fn stop_requested() {
+    #[cfg(unix)]
+    if let Ok(mut term) = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+    {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = term.recv() => {}
+        }
+        return;
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+fn main() -> ExitCode {
+    i18n::set_language("");
+    let config = match daemon::load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!(
+                "Cannot read the hosts: {}. Start egui_pinger once to save them.",
+                e
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut app_state = AppState {
+        headless: true,
+        ..Default::default()
+    };
+    daemon::apply_config(&mut app_state, config);
+    storage::restore_statuses(&mut app_state);
+    let port = app_state.settings.daemon_port;
+    let state = Arc::new(Mutex::new(app_state));
+
+    let token = match daemon::random_token() {
+        Ok(token) => token,
+        Err(e) => {
+            eprintln!("Cannot create the secret windows attach with: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let _server = match daemon::start(state.clone(), port, token.clone()) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!(
+                "Cannot listen on port {}: {}. Is the daemon already running?",
+                port, e
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    // Written only once listening, so a second daemon leaves the first one's secret alone
+    if let Err(e) = daemon::write_token(&token) {
+        eprintln!("Cannot write the secret windows attach with: {}", e);
+        return ExitCode::FAILURE;
+    }
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start runtime: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut watchdog = Watchdog::start(state.clone());
+    runtime.block_on(async {
+        let stop = stop_requested();
+        tokio::pin!(stop);
+        loop {
+            tokio::select! {
+                _ = &mut stop => break,
+                _ = tokio::time::sleep(Duration::from_secs(1)) => watchdog.check(),
+            }
+        }
+    });
+    watchdog.shutdown(Duration::from_millis(SHUTDOWN_TIMEOUT_MS));
+    storage::sync();
+    let _ = std::fs::remove_file(daemon::token_path());
+    ExitCode::SUCCESS
+}
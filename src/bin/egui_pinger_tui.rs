@@ -0,0 +1,97 @@
+//! Terminal client showing the statistics stream of a running egui_pinger.
+//!
+//! Usage: `egui_pinger_tui [address:port]` (default `127.0.0.1:7170`). To watch a
+//! machine remotely, forward the port first: `ssh -L 7170:localhost:7170 home`.
+//! `q` or `Esc` quits.
+
+use egui_pinger::constants::DEFAULT_STREAM_PORT;
+use egui_pinger::logic::{i18n, tui};
+use egui_pinger::model::StatsSnapshot;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use std::io::{BufRead, BufReader};
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Sender};
+use std::time::Duration;
+use tr::tr;
+
+/// News from the connection thread.
+enum Update {
+    Snapshot(Box<StatsSnapshot>),
+    /// The connection is lost or cannot be made; the reason is shown until it is back.
+    Status(String),
+}
+
+/// Reads the snapshots of the stream, reconnecting until the client quits.
+fn follow_stream(address: String, updates: Sender<Update>) {
+    loop {
+        let status = match TcpStream::connect(&address) {
+            Ok(stream) => {
+                for line in BufReader::new(stream).lines() {
+                    let Ok(line) = line else {
+                        break;
+                    };
+                    let Ok(snapshot) = serde_json::from_str::<StatsSnapshot>(&line) else {
+                        continue;
+                    };
+                    if updates.send(Update::Snapshot(Box::new(snapshot))).is_err() {
+                        return;
+                    }
+                }
+                tr!("Connection to {address} lost, reconnecting…").replace("{address}", &address)
+            }
+            Err(e) => tr!("Cannot connect to {address}: {error}. Is the statistics stream enabled in the settings? Retrying…")
+                .replace("{address}", &address)
+                .replace("{error}", &e.to_string()),
+        };
+        if updates.send(Update::Status(status)).is_err() {
+            return;
+        }
+        std::thread::sleep(Duration::from_secs(2));
+    }
+}
+
+fn main() -> std::io::Result<()> {
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| format!("127.0.0.1:{}", DEFAULT_STREAM_PORT));
+    i18n::set_language("");
+    let color = std::env::var_os("NO_COLOR").is_none();
+    let (sender, updates) = mpsc::channel();
+    std::thread::spawn(move || follow_stream(address, sender));
+
+    let mut terminal = ratatui::init();
+    let mut snapshot = StatsSnapshot::default();
+    let mut status = Some(tr!("Connecting…"));
+    let result = loop {
+        for update in updates.try_iter() {
+            match update {
+                Update::Snapshot(next) => {
+                    snapshot = *next;
+                    status = None;
+                }
+                Update::Status(next) => status = Some(next),
+            }
+        }
+        if let Err(e) = terminal.draw(|frame| tui::draw(frame, &snapshot, status.as_deref(), color))
+        {
+            break Err(e);
+        }
+        match event::poll(Duration::from_millis(250)) {
+            Ok(false) => {}
+            Ok(true) => match event::read() {
+                Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                    let ctrl_c = key.modifiers.contains(KeyModifiers::CONTROL)
+                        && key.code == KeyCode::Char('c');
+                    if ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                        break Ok(());
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => break Err(e),
+            },
+            Err(e) => break Err(e),
+        }
+    };
+    ratatui::restore();
+    result
+}
@@ -1,14 +1,75 @@
+/// PNG of the application icon.
+pub const APP_ICON_PNG: &[u8] =
+    include_bytes!("../assets/linux/com.github.vlisivka.EguiPinger.png");
+
 /// Size of the sliding window for RTT and jitter history.
 pub const HISTORY_WINDOW_SIZE: usize = 300;
 
 /// Maximum number of events stored per host in memory.
 pub const MAX_EVENTS_PER_HOST: usize = 100_000;
 
+/// Maximum number of finished outages kept per host in memory.
+pub const MAX_OUTAGES_PER_HOST: usize = 1_000;
+
 /// Maximum events displayed in the UI log viewer.
 pub const MAX_UI_EVENTS: usize = 10_000;
 
-/// RTT threshold (ms) for the warning line on the chart.
-pub const RTT_WARNING_THRESHOLD_MS: f64 = 150.0;
+/// Time (seconds) over which the summary bar totals the packet loss.
+pub const SUMMARY_LOSS_WINDOW_SEC: u64 = 300;
+
+/// Loss (%) of the public DNS hosts above which the Internet counts as degraded.
+pub const INTERNET_DEGRADED_LOSS_PCT: f64 = 2.0;
+
+/// Anycast addresses of the public DNS resolvers that tell about the health of
+/// the Internet connection as a whole.
+pub const PUBLIC_DNS_ADDRESSES: &[&str] = &[
+    "8.8.8.8",
+    "8.8.4.4",
+    "1.1.1.1",
+    "1.0.0.1",
+    "9.9.9.9",
+    "149.112.112.112",
+    "208.67.222.222",
+    "208.67.220.220",
+    "2001:4860:4860::8888",
+    "2001:4860:4860::8844",
+    "2606:4700:4700::1111",
+    "2606:4700:4700::1001",
+    "2620:fe::fe",
+];
+
+/// Upper edges of the RTT rows of the heatmap (ms); a last row takes anything slower.
+pub const HEATMAP_RTT_BUCKETS_MS: [f64; 7] = [5.0, 10.0, 20.0, 50.0, 100.0, 150.0, 300.0];
+
+/// Consecutive samples counted into one column of the heatmap.
+pub const HEATMAP_SAMPLES_PER_COLUMN: usize = 5;
+
+/// Most buckets of the latency histogram; slower replies go into the last one.
+pub const MAX_HISTOGRAM_BUCKETS: usize = 1_000;
+
+/// Default width of a latency histogram bucket (ms).
+pub const DEFAULT_HISTOGRAM_BUCKET_MS: f64 = 2.0;
+
+/// Minutes shown by the mini-charts when they are switched to a shared time axis.
+pub const DEFAULT_PLOT_TIME_SPAN_MIN: u32 = 5;
+
+/// A gap between samples longer than this many ping intervals (plus the slack
+/// below) is shown as a pause in measuring rather than as part of the history.
+pub const PAUSE_GAP_INTERVALS: u64 = 2;
+
+/// Slack added to the pause gap for slow probes and scheduling delays (seconds).
+pub const PAUSE_GAP_SLACK_SEC: u64 = 10;
+
+/// A pinger loop round that took longer than this is taken as the machine having
+/// slept or been suspended (seconds).
+pub const SLEEP_GAP_MIN_SEC: u64 = 15;
+
+/// Failed probes up to this long after the machine woke up are not counted as
+/// lost, as Wi-Fi and DHCP take a while to come back (seconds).
+pub const SLEEP_RESUME_GRACE_SEC: u64 = 20;
+
+/// Number of sleep gaps remembered for marking the probes around them.
+pub const MAX_SLEEP_GAPS: usize = 16;
 
 /// Number of consecutive failures/successes to confirm state change.
 pub const STATE_CONFIRMATION_STREAK: u32 = 3;
@@ -28,5 +89,364 @@ pub const STATS_SNAPSHOT_INTERVAL: u32 = 300;
 /// RFC 3550 smoothing divisor for RTP jitter calculation.
 pub const RTP_JITTER_SMOOTHING_DIVISOR: f64 = 16.0;
 
+/// Weight of the newest RTT in the exponentially weighted moving average.
+pub const EWMA_ALPHA: f64 = 0.1;
+
+/// Number of replies in each of the two windows compared for the latency trend.
+pub const TREND_WINDOW: usize = 30;
+
+/// Relative change of the mean RTT between the trend windows shown as an arrow.
+pub const TREND_MIN_CHANGE: f64 = 0.1;
+
+/// Burst ratio from which loss is shown as bursty (1.0 is random loss).
+pub const BURST_RATIO_WARN: f64 = 1.5;
+
+/// Burst ratio from which loss is shown as severely bursty.
+pub const BURST_RATIO_BAD: f64 = 3.0;
+
 /// Windows creation flag to hide the console window.
 pub const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// Maximum number of application log records kept in memory.
+pub const MAX_APP_LOG_RECORDS: usize = 5_000;
+
+/// Number of application log records included in a diagnostic bundle.
+pub const BUNDLE_APP_LOG_RECORDS: usize = 2_000;
+
+/// Number of most recent events per host included in a diagnostic bundle.
+pub const BUNDLE_EVENTS_PER_HOST: usize = 1_000;
+
+/// Default number of days the history of a deleted host is retained.
+pub const DEFAULT_DELETED_HOST_RETENTION_DAYS: u32 = 30;
+
+/// Maximum number of events kept for a deleted host.
+pub const MAX_RETAINED_EVENTS_PER_HOST: usize = 10_000;
+
+/// Minimum number of successful samples per window for trend-based alerts.
+pub const MIN_TREND_SAMPLES: usize = 5;
+
+/// Timeout for outgoing HTTP requests (seconds).
+pub const HTTP_TIMEOUT_SEC: u64 = 15;
+
+/// GitHub API endpoint describing the latest release.
+pub const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/vlisivka/egui_pinger/releases/latest";
+
+/// Minimum interval between automatic update checks (seconds).
+pub const UPDATE_CHECK_INTERVAL_SEC: u64 = 86_400;
+
+/// Default endpoint for captive portal detection; returns HTTP 204 when the Internet is reachable.
+pub const CAPTIVE_PORTAL_CHECK_URL: &str = "http://connectivitycheck.gstatic.com/generate_204";
+
+/// Interval between captive portal checks (seconds).
+pub const CONNECTIVITY_CHECK_INTERVAL_SEC: u64 = 60;
+
+/// Interval between detections of the current network for the automatic profile switching (seconds).
+pub const NETINFO_CHECK_INTERVAL_SEC: u64 = 30;
+
+/// HTTPS service answering with the public IP address of the caller in plain text.
+pub const PUBLIC_IP_URL: &str = "https://api.ipify.org";
+
+/// Default services of the public IP check: IPv4 and IPv6 echo services and a STUN server.
+pub const DEFAULT_PUBLIC_IP_SERVICES: [&str; 3] = [
+    PUBLIC_IP_URL,
+    "https://api6.ipify.org",
+    "stun:stun.l.google.com:19302",
+];
+
+/// Interval between lookups of the public IP addresses (seconds).
+pub const PUBLIC_IP_CHECK_INTERVAL_SEC: u64 = 60;
+
+/// Time to wait for the answer of a STUN server (ms).
+pub const STUN_TIMEOUT_MS: u64 = 2_000;
+
+/// Most time between the replies of two paired hosts compared with each other (seconds).
+pub const PAIR_MATCH_SEC: u64 = 2;
+
+/// Interval between checks of the link state of network interfaces (seconds).
+pub const INTERFACE_CHECK_INTERVAL_SEC: u64 = 3;
+
+/// Default size of the simulated jitter buffer (ms).
+pub const DEFAULT_JITTER_BUFFER_MS: u32 = 60;
+
+/// Period of recorded pings replayed through the jitter buffer simulation (seconds).
+pub const JITTER_SIMULATION_WINDOW_SEC: u64 = 300;
+
+/// Number of ping results kept per host in the on-disk history (a week at one ping per second).
+pub const HISTORY_CAPACITY_RECORDS: u32 = 604_800;
+
+/// Number of closed per-minute statistics buckets kept per host (one day).
+pub const MAX_MINUTE_ROLLUPS: usize = 1_440;
+
+/// Number of closed per-hour statistics buckets kept per host (30 days).
+pub const MAX_HOUR_ROLLUPS: usize = 720;
+
+/// Interval between checks of the shared host list file for changes (seconds).
+pub const SHARED_HOST_LIST_CHECK_INTERVAL_SEC: u64 = 10;
+
+/// Time after the start until this session is compared with the previous one (seconds).
+pub const SESSION_COMPARISON_DELAY_SEC: u64 = 180;
+
+/// Replies a session needs for its baseline or its comparison with the previous one.
+pub const SESSION_BASELINE_MIN_SAMPLES: u32 = 20;
+
+/// Increase of the median RTT over the previous session reported at startup (percent).
+pub const SESSION_RTT_INCREASE_PCT: f64 = 30.0;
+
+/// Smallest increase of the median RTT reported at startup, to skip 1 ms → 2 ms (ms).
+pub const SESSION_RTT_INCREASE_MIN_MS: f64 = 5.0;
+
+/// Increase of the loss over the previous session reported at startup (percentage points).
+pub const SESSION_LOSS_INCREASE_PP: f64 = 2.0;
+
+/// Time the comparison with the previous session stays on screen (seconds).
+pub const SESSION_TOAST_SEC: u64 = 30;
+
+/// Interval between checks of the DHCP lease file for changes (seconds).
+pub const DHCP_LEASES_CHECK_INTERVAL_SEC: u64 = 60;
+
+/// Lease file of dnsmasq on Debian-based systems, the most common DHCP server.
+pub const DEFAULT_DHCP_LEASE_FILE: &str = "/var/lib/misc/dnsmasq.leases";
+
+/// Time to wait for the answer of a DNS latency probe (ms).
+pub const DNS_PROBE_TIMEOUT_MS: u64 = 2_000;
+
+/// Time to wait for the answer of a UDP probe (ms).
+pub const UDP_PROBE_TIMEOUT_MS: u64 = 2_000;
+
+/// Interval between SNMP polls of the interface counters of a host (seconds).
+pub const SNMP_POLL_INTERVAL_SEC: u64 = 60;
+
+/// Time to wait for the answer to an SNMP request (ms).
+pub const SNMP_TIMEOUT_MS: u64 = 2_000;
+
+/// Rows asked for in one SNMP GetBulk request.
+pub const SNMP_MAX_REPETITIONS: u32 = 20;
+
+/// Most GetBulk requests for one column, bounding the walk of a huge interface table.
+pub const SNMP_MAX_REQUESTS: usize = 50;
+
+/// Time a custom probe command may run before it is killed and counted as failed (seconds).
+pub const EXEC_PROBE_TIMEOUT_SEC: u64 = 10;
+
+/// Time an on down / on up action command may run before it is killed (seconds).
+pub const ACTION_TIMEOUT_SEC: u64 = 60;
+
+/// Number of executed actions kept in the action log.
+pub const MAX_ACTION_LOG: usize = 200;
+
+/// Default number of probes per second the scheduler is expected to sustain.
+pub const DEFAULT_PROBE_BUDGET_PER_SEC: u32 = 50;
+
+/// Default number of probes allowed in flight at once.
+pub const DEFAULT_MAX_CONCURRENT_PROBES: u32 = 256;
+
+/// Default number of probes per second allowed on each network interface.
+pub const DEFAULT_PROBE_RATE_PER_INTERFACE: u32 = 200;
+
+/// Probes an interface may send at once after a quiet spell, in seconds of its rate.
+pub const PROBE_RATE_BURST_SEC: f64 = 0.1;
+
+/// Longest a probe waits for its turn on a busy interface before it is put back
+/// into the schedule (milliseconds).
+pub const PROBE_RATE_MAX_DELAY_MS: u64 = 1_000;
+
+/// Default time a host must stay down before the escalation sound plays (minutes).
+pub const DEFAULT_SOUND_ESCALATION_MIN: u32 = 5;
+
+/// Interval between probe rounds of the live trace window (ms).
+pub const LIVE_TRACE_INTERVAL_MS: u64 = 1_000;
+
+/// Time to wait for the answer of a live trace probe (ms).
+pub const LIVE_TRACE_TIMEOUT_MS: u64 = 1_000;
+
+/// Highest TTL tried when measuring the number of hops to a target.
+pub const LIVE_TRACE_MAX_TTL: u8 = 30;
+
+/// Smallest IPv4 MTU tried by the path MTU discovery (the RFC 791 minimum).
+pub const MTU_MIN_V4: usize = 576;
+
+/// Smallest IPv6 MTU tried by the path MTU discovery (the RFC 8200 minimum).
+pub const MTU_MIN_V6: usize = 1280;
+
+/// Largest MTU tried by the path MTU discovery (Ethernet).
+pub const MTU_MAX: usize = 1500;
+
+/// Time to wait for the answer of a path MTU probe (ms).
+pub const MTU_PROBE_TIMEOUT_MS: u64 = 1_000;
+
+/// Attempts per packet size before it is taken as too large.
+pub const MTU_PROBE_TRIES: usize = 2;
+
+/// Duration of the idle phase of the bufferbloat check (seconds).
+pub const BUFFERBLOAT_IDLE_SEC: u64 = 5;
+
+/// Duration of the loaded phase of the bufferbloat check (seconds).
+pub const BUFFERBLOAT_LOAD_SEC: u64 = 10;
+
+/// Interval between the latency probes of the bufferbloat check (ms).
+pub const BUFFERBLOAT_PROBE_INTERVAL_MS: u64 = 200;
+
+/// Parallel streams of full-size pings that load the link.
+pub const BUFFERBLOAT_PING_STREAMS: usize = 32;
+
+/// Payload of the pings that load the link, a full Ethernet frame (bytes).
+pub const BUFFERBLOAT_PING_PAYLOAD: usize = 1472;
+
+/// Server the upload load of the bufferbloat check is sent to.
+pub const BUFFERBLOAT_UPLOAD_URL: &str = "https://speed.cloudflare.com/__up";
+
+/// Parallel uploads that load the link.
+pub const BUFFERBLOAT_UPLOAD_STREAMS: usize = 4;
+
+/// Size of each upload request (bytes).
+pub const BUFFERBLOAT_UPLOAD_BYTES: usize = 2 * 1024 * 1024;
+
+/// Most addresses a network scan sweeps (a /20).
+pub const SCAN_MAX_ADDRESSES: usize = 4_096;
+
+/// Echo requests of a network scan in flight at once.
+pub const SCAN_CONCURRENCY: usize = 64;
+
+/// Time to wait for the answer of a network scan probe (ms).
+pub const SCAN_TIMEOUT_MS: u64 = 1_000;
+
+/// Time to wait for the NetBIOS name of a device on the local network (ms).
+pub const LAN_NAME_TIMEOUT_MS: u64 = 500;
+
+/// Fraction of an alert threshold from which the host row bar turns amber.
+pub const THRESHOLD_WARNING_FRACTION: f64 = 0.8;
+
+/// Interval of the housekeeping round of the pinger loop, which picks up new and
+/// changed hosts and runs the periodic checks; probes are timed exactly
+/// in between (milliseconds).
+pub const SCHEDULER_ROUND_MS: u64 = 500;
+
+/// Longest the pinger waits for the probes in flight when the application quits,
+/// and again for their results to be applied (milliseconds).
+pub const SHUTDOWN_GRACE_MS: u64 = 2_000;
+
+/// Longest quitting waits for the pinger to stop (milliseconds).
+pub const SHUTDOWN_TIMEOUT_MS: u64 = 5_000;
+
+/// Most probe results taken from the queue at once.
+pub const APPLY_BATCH_MAX: usize = 1024;
+
+/// Most probe results applied under one lock of the state; the lock is released
+/// between chunks, so the UI waits for at most this many.
+pub const APPLY_LOCK_CHUNK: usize = 32;
+
+/// Time without a pinger heartbeat after which the watchdog restarts it (seconds).
+pub const PINGER_STALL_TIMEOUT_SEC: u64 = 10;
+
+/// How long the warning about a restarted pinger stays visible (seconds).
+pub const WATCHDOG_NOTICE_SEC: u64 = 120;
+
+/// Default interval after which host names are resolved again (seconds).
+pub const DEFAULT_DNS_REFRESH_SEC: u64 = 300;
+
+/// How long a changed resolved address stays highlighted in the host row (seconds).
+pub const ADDRESS_CHANGE_HIGHLIGHT_SEC: u64 = 3_600;
+
+/// Range of the per-host ping reply timeout offered in the host settings (ms).
+pub const MIN_PROBE_TIMEOUT_MS: u32 = 100;
+pub const MAX_PROBE_TIMEOUT_MS: u32 = 10_000;
+
+/// Most confirmation pings offered in the host settings.
+pub const MAX_CONFIRM_PROBES: u8 = 5;
+
+/// Named DSCP code points offered in the host settings.
+pub const DSCP_PRESETS: [(&str, u8); 8] = [
+    ("CS0 / BE", 0),
+    ("CS1", 8),
+    ("AF11", 10),
+    ("AF21", 18),
+    ("AF31", 26),
+    ("AF41", 34),
+    ("EF", 46),
+    ("CS6", 48),
+];
+
+/// Default time a host has to be down before an alert email is sent (minutes).
+pub const DEFAULT_EMAIL_DOWN_MIN: u32 = 5;
+
+/// Timeout for connecting to and talking with the SMTP server (seconds).
+pub const SMTP_TIMEOUT_SEC: u64 = 30;
+
+/// Number of recent samples drawn in the chart of an alert email.
+pub const EMAIL_CHART_SAMPLES: usize = 60;
+
+/// Service name under which secrets are stored in the keyring of the operating system.
+pub const KEYRING_SERVICE: &str = "egui_pinger";
+
+/// Number of files kept in the recent files menu.
+pub const MAX_RECENT_FILES: usize = 8;
+
+/// Default localhost port of the statistics stream for the terminal client.
+pub const DEFAULT_STREAM_PORT: u16 = 7170;
+
+/// Interval between two statistics snapshots sent to stream clients.
+pub const STREAM_INTERVAL_MS: u64 = 1_000;
+
+/// Default localhost port of the background daemon the window attaches to.
+pub const DEFAULT_DAEMON_PORT: u16 = 7171;
+
+/// How long the window waits for the background daemon to answer at startup.
+pub const DAEMON_CONNECT_TIMEOUT_MS: u64 = 500;
+
+/// Interval between checks whether the hosts or settings changed and have to be
+/// sent to the background daemon.
+pub const DAEMON_CONFIG_SYNC_SEC: u64 = 2;
+
+/// Default port a remote agent serves its results to viewers on.
+pub const DEFAULT_AGENT_PORT: u16 = 7172;
+
+/// How long the daemon or an agent waits for a window or viewer to answer its challenge.
+pub const AGENT_AUTH_TIMEOUT_SEC: u64 = 5;
+
+/// Interval between checks whether the hosts of an agent changed and have to be
+/// sent to its viewers.
+pub const AGENT_HOSTS_SYNC_SEC: u64 = 2;
+
+/// Wait before a viewer connects to a remote agent again after losing it.
+pub const AGENT_RETRY_SEC: u64 = 10;
+
+/// Default port of the JSON API.
+pub const DEFAULT_API_PORT: u16 = 7173;
+
+/// Largest request the JSON API reads, headers and body together.
+pub const API_MAX_REQUEST_BYTES: usize = 65_536;
+
+/// Period of history the JSON API returns when the request names no start.
+pub const API_DEFAULT_HISTORY_SEC: u64 = 3_600;
+
+/// Default port of an MQTT broker without TLS.
+pub const DEFAULT_MQTT_PORT: u16 = 1883;
+
+/// Default port of an MQTT broker with TLS.
+pub const DEFAULT_MQTT_TLS_PORT: u16 = 8883;
+
+/// Default interval between publications of the latency metrics over MQTT.
+pub const DEFAULT_MQTT_INTERVAL_SEC: u32 = 30;
+
+/// Keep-alive interval announced to the MQTT broker; a ping is sent after half
+/// of it without other packets.
+pub const MQTT_KEEP_ALIVE_SEC: u16 = 60;
+
+/// Timeout of connecting to the MQTT broker and of its answer.
+pub const MQTT_TIMEOUT_SEC: u64 = 10;
+
+/// Wait before connecting to the MQTT broker again after losing it.
+pub const MQTT_RETRY_SEC: u64 = 10;
+
+/// Zoom factor of the user interface in the wall dashboard.
+pub const DEFAULT_DASHBOARD_ZOOM: f32 = 1.5;
+
+/// Seconds each group is shown before the wall dashboard scrolls to the next.
+pub const DEFAULT_DASHBOARD_SCROLL_SEC: u32 = 15;
+
+/// Width (points) of a host card in the grid layout.
+pub const GRID_CARD_WIDTH: f32 = 200.0;
+
+/// Samples in the sparkline of a host card.
+pub const GRID_SPARKLINE_SAMPLES: usize = 60;
@@ -0,0 +1,177 @@
+//! User commands run when a host goes down or comes back up.
+
+use crate::constants::{ACTION_TIMEOUT_SEC, MAX_ACTION_LOG};
+use crate::logic::{SharedState, applog, exec_probe};
+use crate::model::{ActionEvent, ActionRecord, LogEntry};
+use std::time::{Duration, Instant};
+use tr::tr;
+
+/// A command to run for a state change, with the measurements passed to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingAction {
+    pub event: ActionEvent,
+    pub command: String,
+    pub name: String,
+    pub address: String,
+    /// Unix timestamp of the state change.
+    pub timestamp: u64,
+    pub loss_pct: f64,
+    /// Mean RTT in ms.
+    pub rtt_ms: f64,
+    /// Length of the incident, for `Up`.
+    pub downtime_sec: Option<u64>,
+}
+
+impl PendingAction {
+    /// Environment variables describing the state change.
+    pub fn env(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("EGUI_PINGER_EVENT", self.event.as_str().to_string()),
+            ("EGUI_PINGER_NAME", self.name.clone()),
+            ("EGUI_PINGER_ADDRESS", self.address.clone()),
+            ("EGUI_PINGER_LOSS", format!("{:.1}", self.loss_pct)),
+            ("EGUI_PINGER_RTT", format!("{:.1}", self.rtt_ms)),
+            (
+                "EGUI_PINGER_DOWNTIME",
+                self.downtime_sec.map(|d| d.to_string()).unwrap_or_default(),
+            ),
+        ]
+    }
+}
+
+/// Runs a command through the system shell, returning its exit code.
+async fn execute(
+    command: &str,
+    env: &[(&'static str, String)],
+    timeout: Duration,
+) -> Result<i32, String> {
+    exec_probe::run_shell(command, env, timeout)
+        .await?
+        .status
+        .code()
+        .ok_or_else(|| "terminated by signal".to_string())
+}
+
+/// Runs the action on its own thread and records the result in the action log.
+pub fn spawn(state: SharedState, action: PendingAction) {
+    std::thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(rt) => rt,
+            Err(e) => {
+                applog::error("actions", format!("Failed to start action runtime: {}", e));
+                return;
+            }
+        };
+        let started = Instant::now();
+        let result = runtime.block_on(execute(
+            &action.command,
+            &action.env(),
+            Duration::from_secs(ACTION_TIMEOUT_SEC),
+        ));
+        let record = ActionRecord {
+            timestamp: action.timestamp,
+            address: action.address.clone(),
+            event: action.event,
+            command: action.command.clone(),
+            result,
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+        record_action(&state, record);
+    });
+}
+
+/// Adds an executed action to the action log and to the event log of its host.
+pub fn record_action(state: &SharedState, record: ActionRecord) {
+    let outcome = match &record.result {
+        Ok(code) => tr!("exit code {code}").replace("{code}", &code.to_string()),
+        Err(e) => e.clone(),
+    };
+    let message = tr!("Action {event} ran: {outcome}")
+        .replace("{event}", &record.event.label())
+        .replace("{outcome}", &outcome);
+    if record.result == Ok(0) {
+        applog::info("actions", format!("{}: {}", record.address, message));
+    } else {
+        applog::warn("actions", format!("{}: {}", record.address, message));
+    }
+
+    let mut state = state.lock().expect("Failed to lock state for action log");
+    if let Some(status) = state.statuses.get_mut(&record.address) {
+        status.events.push_back(LogEntry::Marker {
+            timestamp: record.timestamp,
+            message,
+        });
+        status.trim_events();
+    }
+    state.action_log.push_back(record);
+    while state.action_log.len() > MAX_ACTION_LOG {
+        state.action_log.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{AppState, HostStatus};
+    use std::sync::{Arc, Mutex};
+
+    fn action(command: &str) -> PendingAction {
+        PendingAction {
+            event: ActionEvent::Up,
+            command: command.to_string(),
+            name: "Gateway".to_string(),
+            address: "192.168.1.1".to_string(),
+            timestamp: 1_700_000_000,
+            loss_pct: 12.5,
+            rtt_ms: 3.25,
+            downtime_sec: Some(42),
+        }
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn test_execute_passes_environment() {
+        let a = action(
+            r#"[ "$EGUI_PINGER_EVENT $EGUI_PINGER_NAME $EGUI_PINGER_LOSS $EGUI_PINGER_DOWNTIME" = "up Gateway 12.5 42" ]"#,
+        );
+        assert_eq!(
+            execute(&a.command, &a.env(), Duration::from_secs(5)).await,
+            Ok(0)
+        );
+        assert_eq!(execute("exit 3", &[], Duration::from_secs(5)).await, Ok(3));
+        assert_eq!(
+            execute("sleep 5", &[], Duration::from_millis(200)).await,
+            Err("timeout".to_string())
+        );
+    }
+
+    #[test]
+    fn test_record_action_logs_and_caps() {
+        let mut app = AppState::default();
+        app.statuses
+            .insert("192.168.1.1".to_string(), HostStatus::default());
+        let state: SharedState = Arc::new(Mutex::new(app));
+        for _ in 0..MAX_ACTION_LOG + 1 {
+            record_action(
+                &state,
+                ActionRecord {
+                    timestamp: 1,
+                    address: "192.168.1.1".to_string(),
+                    event: ActionEvent::Down,
+                    command: "true".to_string(),
+                    result: Ok(0),
+                    duration_ms: 1,
+                },
+            );
+        }
+        let s = state.lock().unwrap();
+        assert_eq!(s.action_log.len(), MAX_ACTION_LOG);
+        assert!(matches!(
+            s.statuses["192.168.1.1"].events.back(),
+            Some(LogEntry::Marker { message, .. }) if message == "Action on down ran: exit code 0"
+        ));
+    }
+}
@@ -0,0 +1,489 @@
+//! Remote agents: an egui_pinger serving its results to windows on other
+//! machines, and the links such a viewer shows the agent's hosts through.
+//!
+//! The agent listens on all interfaces once enabled in the settings. A viewer
+//! proves it knows the agent's token by signing a random challenge with it
+//! (HMAC-SHA256), and the agent signs one of the viewer's back, so the token
+//! never crosses the network and neither side talks to an impostor; the results are not
+//! encrypted, so an agent on the internet belongs behind a VPN or an SSH tunnel.
+//! The viewer shows the agent's hosts as passive hosts tagged with the agent's
+//! name, e.g. `agent://office/192.168.1.1`.
+
+use crate::constants::{AGENT_AUTH_TIMEOUT_SEC, AGENT_HOSTS_SYNC_SEC, AGENT_RETRY_SEC};
+use crate::logic::daemon::{self, Message};
+use crate::logic::listener::{self, Listener};
+use crate::logic::pinger::{self, ProbeResult};
+use crate::logic::{SharedState, applog};
+use crate::model::{AppState, HostInfo, RemoteAgent, RemoteAgentStatus};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, Shutdown, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::time::{Duration, Instant};
+
+/// Address a host of an agent is shown under by the viewer.
+pub fn remote_address(agent: &str, address: &str) -> String {
+    format!("agent://{}/{}", agent, address)
+}
+
+/// The fields of a host a viewer shows; its commands, files, probe settings and
+/// credentials stay on the agent.
+fn display_fields(host: &HostInfo) -> HostInfo {
+    HostInfo {
+        name: host.name.clone(),
+        address: host.address.clone(),
+        group: host.group.clone(),
+        notes: host.notes.clone(),
+        tags: host.tags.clone(),
+        mode: host.mode,
+        display: host.display.clone(),
+        thresholds: host.thresholds,
+        ..Default::default()
+    }
+}
+
+/// The local hosts of the agent as sent to its viewers.
+fn shared_hosts(state: &AppState) -> Vec<HostInfo> {
+    state
+        .hosts
+        .iter()
+        .filter(|h| h.agent.is_empty())
+        .map(display_fields)
+        .collect()
+}
+
+/// A host of an agent as the viewer shows it: passive, tagged with the agent, and
+/// without commands, files or alerts of its own.
+fn mirror(agent: &str, host: &HostInfo) -> HostInfo {
+    let mut tags = host.tags.clone();
+    if !tags.iter().any(|t| t == agent) {
+        tags.push(agent.to_string());
+    }
+    HostInfo {
+        address: remote_address(agent, &host.address),
+        tags,
+        passive: true,
+        agent: agent.to_string(),
+        ..display_fields(host)
+    }
+}
+
+/// Adds the new hosts of an agent and removes the ones it no longer has; hosts
+/// shown already keep the changes made to them in the viewer.
+pub fn merge_hosts(state: &mut AppState, agent: &str, hosts: &[HostInfo]) {
+    let wanted: Vec<HostInfo> = hosts
+        .iter()
+        .filter(|h| h.agent.is_empty())
+        .map(|h| mirror(agent, h))
+        .collect();
+    let gone: Vec<String> = state
+        .hosts
+        .iter()
+        .filter(|h| h.agent == agent && !wanted.iter().any(|w| w.address == h.address))
+        .map(|h| h.address.clone())
+        .collect();
+    for address in &gone {
+        state.statuses.remove(address);
+    }
+    state.hosts.retain(|h| !gone.contains(&h.address));
+    for host in wanted {
+        if !state.hosts.iter().any(|h| h.address == host.address) {
+            state.statuses.entry(host.address.clone()).or_default();
+            state.hosts.push(host);
+        }
+    }
+}
+
+/// The agent's listener, started for the port and token in its key.
+pub type AgentServer = Listener<(u16, String)>;
+
+/// Starts listening on all interfaces.
+pub fn start(state: SharedState, port: u16, token: &str) -> std::io::Result<AgentServer> {
+    let key = token.to_string();
+    Listener::start(
+        "agent",
+        Ipv4Addr::UNSPECIFIED,
+        port,
+        (port, token.to_string()),
+        move |viewer, peer, stop| {
+            if let Err(e) = serve_viewer(state.clone(), viewer, &key, stop) {
+                applog::warn("agent", format!("Viewer {}: {}", peer, e));
+            }
+        },
+    )
+}
+
+/// Checks the viewer's answer to a challenge, then sends it the hosts and the
+/// results until it goes away or the agent stops.
+fn serve_viewer(
+    state: SharedState,
+    mut viewer: TcpStream,
+    token: &str,
+    stop: Arc<AtomicBool>,
+) -> Result<(), String> {
+    daemon::challenge(&mut viewer, token)?;
+    applog::info(
+        "agent",
+        format!(
+            "Viewer {} connected",
+            viewer
+                .peer_addr()
+                .map(|a| a.to_string())
+                .unwrap_or_default()
+        ),
+    );
+
+    let queue = daemon::subscribe();
+    let mut sent_hosts = None;
+    let mut last_sync: Option<Instant> = None;
+    while !stop.load(Ordering::Relaxed) {
+        if last_sync.is_none_or(|at| at.elapsed() >= Duration::from_secs(AGENT_HOSTS_SYNC_SEC)) {
+            last_sync = Some(Instant::now());
+            let hosts = shared_hosts(&state.lock().expect("Failed to lock state for agent hosts"));
+            let message = Message::Hosts { hosts };
+            let line = daemon::to_line(&message);
+            if line != sent_hosts {
+                send(&mut viewer, &message)?;
+                sent_hosts = line;
+            }
+        }
+        match queue.recv_timeout(Duration::from_millis(500)) {
+            Ok(line) => viewer
+                .write_all(line.as_bytes())
+                .map_err(|e| e.to_string())?,
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    Ok(())
+}
+
+fn send(stream: &mut TcpStream, message: &Message) -> Result<(), String> {
+    let line = daemon::to_line(message).ok_or("cannot encode a message")?;
+    stream.write_all(line.as_bytes()).map_err(|e| e.to_string())
+}
+
+/// Starts, restarts or stops the agent to follow the settings.
+pub fn follow_settings(state: &SharedState, server: &mut Option<AgentServer>) {
+    let settings = state
+        .lock()
+        .expect("Failed to lock state for agent")
+        .settings
+        .agent
+        .clone();
+    let wanted = settings
+        .enabled
+        .then(|| (settings.port, settings.token.clone()));
+    if let Some(error) = listener::follow("agent", server, wanted, settings.port, || {
+        start(state.clone(), settings.port, &settings.token)
+    }) {
+        state
+            .lock()
+            .expect("Failed to lock state for agent")
+            .agent_error = error;
+    }
+}
+
+/// A viewer's link to a remote agent, reconnecting until it is dropped.
+pub struct AgentLink {
+    pub agent: RemoteAgent,
+    stop: Arc<AtomicBool>,
+    /// Connection in use, shut down to stop the link at once.
+    connection: Arc<Mutex<Option<TcpStream>>>,
+}
+
+impl AgentLink {
+    /// Connects to the agent in a background thread.
+    pub fn start(state: SharedState, agent: RemoteAgent) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let connection = Arc::new(Mutex::new(None));
+        let link = Self {
+            agent: agent.clone(),
+            stop: stop.clone(),
+            connection: connection.clone(),
+        };
+        std::thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                set_status(&state, &agent.name, RemoteAgentStatus::Connecting);
+                let error = match run_link(&state, &agent, &connection) {
+                    Ok(()) => "connection closed".to_string(),
+                    Err(e) => e,
+                };
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                applog::warn(
+                    "agent",
+                    format!("Agent {} ({}): {}", agent.name, agent.address, error),
+                );
+                set_status(&state, &agent.name, RemoteAgentStatus::Failed(error));
+                let retry_at = Instant::now() + Duration::from_secs(AGENT_RETRY_SEC);
+                while Instant::now() < retry_at && !stop.load(Ordering::Relaxed) {
+                    std::thread::sleep(Duration::from_millis(200));
+                }
+            }
+        });
+        link
+    }
+}
+
+impl Drop for AgentLink {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Ok(connection) = self.connection.lock()
+            && let Some(stream) = connection.as_ref()
+        {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+fn set_status(state: &SharedState, agent: &str, status: RemoteAgentStatus) {
+    state
+        .lock()
+        .expect("Failed to lock state for agent status")
+        .remote_agent_status
+        .insert(agent.to_string(), status);
+}
+
+/// Answers the agent's challenge, then applies its hosts and results until the
+/// connection ends.
+fn run_link(
+    state: &SharedState,
+    agent: &RemoteAgent,
+    connection: &Mutex<Option<TcpStream>>,
+) -> Result<(), String> {
+    let mut stream = TcpStream::connect(agent.address.trim()).map_err(|e| e.to_string())?;
+    stream
+        .set_read_timeout(Some(Duration::from_secs(AGENT_AUTH_TIMEOUT_SEC)))
+        .map_err(|e| e.to_string())?;
+    if let Ok(mut connection) = connection.lock() {
+        *connection = stream.try_clone().ok();
+    }
+    let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|e| e.to_string())?;
+    let Some((_, auth, nonce)) = daemon::answer(&line, &agent.token) else {
+        return Err("not an egui_pinger agent".to_string());
+    };
+    send(&mut stream, &auth)?;
+    let mut reply = String::new();
+    let _ = reader.read_line(&mut reply);
+    if !daemon::verify_reply(&reply, &agent.token, &nonce) {
+        return Err("wrong token".to_string());
+    }
+    stream.set_read_timeout(None).map_err(|e| e.to_string())?;
+
+    let mut connected = false;
+    for line in reader.lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        match serde_json::from_str(&line) {
+            Ok(Message::Hosts { hosts }) => {
+                let mut state_lock = state.lock().expect("Failed to lock state for agent hosts");
+                merge_hosts(&mut state_lock, &agent.name, &hosts);
+                if !connected {
+                    connected = true;
+                    applog::info("agent", format!("Connected to agent {}", agent.name));
+                    state_lock
+                        .remote_agent_status
+                        .insert(agent.name.clone(), RemoteAgentStatus::Connected);
+                }
+            }
+            Ok(Message::Result(result)) => {
+                let mut result = ProbeResult::from(*result);
+                result.address = remote_address(&agent.name, &result.address);
+                // Effects follow the viewer's own settings of the host
+                result.host_info = state
+                    .lock()
+                    .expect("Failed to lock state for agent results")
+                    .hosts
+                    .iter()
+                    .find(|h| h.address == result.address)
+                    .cloned();
+                if result.host_info.is_some() {
+                    pinger::apply_probe_result(state, &result);
+                }
+            }
+            _ => {}
+        }
+    }
+    if connected {
+        Ok(())
+    } else {
+        Err("wrong token".to_string())
+    }
+}
+
+/// Starts and stops the links to follow the settings, and drops the hosts of
+/// agents removed from the settings.
+pub fn follow_links(state: &SharedState, links: &mut Vec<AgentLink>) {
+    let agents = {
+        let mut state_lock = state.lock().expect("Failed to lock state for agents");
+        let names: HashSet<String> = state_lock
+            .settings
+            .remote_agents
+            .iter()
+            .map(|a| a.name.clone())
+            .collect();
+        let gone: Vec<String> = state_lock
+            .hosts
+            .iter()
+            .filter(|h| !h.agent.is_empty() && !names.contains(&h.agent))
+            .map(|h| h.address.clone())
+            .collect();
+        for address in &gone {
+            state_lock.statuses.remove(address);
+        }
+        state_lock.hosts.retain(|h| !gone.contains(&h.address));
+        state_lock
+            .remote_agent_status
+            .retain(|name, _| names.contains(name));
+        state_lock.settings.remote_agents.clone()
+    };
+    let wanted: Vec<RemoteAgent> = agents
+        .into_iter()
+        .filter(|a| a.enabled && !a.name.trim().is_empty() && !a.address.trim().is_empty())
+        .collect();
+    links.retain(|link| wanted.contains(&link.agent));
+    for agent in wanted {
+        if !links.iter().any(|link| link.agent == agent) {
+            links.push(AgentLink::start(state.clone(), agent));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::HostStatus;
+
+    fn wait_for(done: impl Fn() -> bool) {
+        for _ in 0..250 {
+            if done() {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        panic!("timed out");
+    }
+
+    #[test]
+    fn test_viewer_shows_hosts_and_results_of_agent() {
+        let host = HostInfo {
+            name: "Printer".to_string(),
+            address: "agent-test.invalid".to_string(),
+            on_down_command: "rm -rf ~".to_string(),
+            ..Default::default()
+        };
+        let mut agent_state = AppState::default();
+        agent_state.hosts.push(host.clone());
+        agent_state
+            .statuses
+            .insert(host.address.clone(), HostStatus::default());
+        let agent_state = Arc::new(Mutex::new(agent_state));
+        let server = start(agent_state, 0, "secret").unwrap();
+        let address = format!("127.0.0.1:{}", server.local_port.unwrap());
+
+        let viewer = Arc::new(Mutex::new(AppState::default()));
+        let remote = RemoteAgent {
+            name: "office".to_string(),
+            address: address.clone(),
+            token: "wrong".to_string(),
+            enabled: true,
+        };
+        let rejected = AgentLink::start(viewer.clone(), remote.clone());
+        wait_for(|| {
+            matches!(
+                viewer.lock().unwrap().remote_agent_status.get("office"),
+                Some(RemoteAgentStatus::Failed(_))
+            )
+        });
+        drop(rejected);
+        assert!(viewer.lock().unwrap().hosts.is_empty());
+
+        let _link = AgentLink::start(
+            viewer.clone(),
+            RemoteAgent {
+                token: "secret".to_string(),
+                ..remote
+            },
+        );
+        let shown = remote_address("office", "agent-test.invalid");
+        wait_for(|| viewer.lock().unwrap().statuses.contains_key(&shown));
+        {
+            let state = viewer.lock().unwrap();
+            let mirrored = &state.hosts[0];
+            assert_eq!(mirrored.address, shown);
+            assert!(mirrored.passive);
+            assert_eq!(mirrored.tags, vec!["office".to_string()]);
+            assert!(mirrored.on_down_command.is_empty());
+        }
+
+        daemon::publish(&[ProbeResult::new(
+            "agent-test.invalid",
+            true,
+            7.0,
+            false,
+            Some(host),
+        )]);
+        wait_for(|| viewer.lock().unwrap().statuses[&shown].sent == 1);
+    }
+
+    #[test]
+    fn test_viewers_get_no_commands_or_credentials() {
+        let mut state = AppState::default();
+        let mut host = HostInfo {
+            name: "Switch".to_string(),
+            address: "10.0.0.9".to_string(),
+            on_up_command: "notify-send up".to_string(),
+            probe_command: "check-switch".to_string(),
+            ..Default::default()
+        };
+        host.snmp.community = "s3cret".to_string();
+        state.hosts.push(host.clone());
+        let hosts = daemon::to_line(&Message::Hosts {
+            hosts: shared_hosts(&state),
+        })
+        .unwrap();
+        let result = daemon::to_line(&Message::Result(Box::new(
+            (&ProbeResult::new("10.0.0.9", true, 3.0, false, Some(host))).into(),
+        )))
+        .unwrap();
+        for line in [hosts, result] {
+            assert!(line.contains("10.0.0.9"));
+            assert!(!line.contains("notify-send"), "{}", line);
+            assert!(!line.contains("check-switch"), "{}", line);
+            assert!(!line.contains("s3cret"), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_removed_agent_hosts_are_dropped() {
+        let mut state = AppState::default();
+        let hosts = vec![
+            HostInfo {
+                address: "10.0.0.1".to_string(),
+                ..Default::default()
+            },
+            HostInfo {
+                address: "10.0.0.2".to_string(),
+                ..Default::default()
+            },
+        ];
+        merge_hosts(&mut state, "home", &hosts);
+        state.hosts[0].name = "Renamed here".to_string();
+        merge_hosts(&mut state, "home", &hosts[..1]);
+        assert_eq!(state.hosts.len(), 1);
+        assert_eq!(state.hosts[0].name, "Renamed here");
+        assert!(
+            !state
+                .statuses
+                .contains_key(&remote_address("home", "10.0.0.2"))
+        );
+
+        let state = Arc::new(Mutex::new(state));
+        follow_links(&state, &mut Vec::new());
+        assert!(state.lock().unwrap().hosts.is_empty());
+    }
+}
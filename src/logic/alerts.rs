@@ -0,0 +1,302 @@
+use crate::constants::MIN_TREND_SAMPLES;
+use crate::model::status::calculate_percentile;
+use crate::model::{ActiveAlert, AlertRule, HostInfo, HostStatus, LogEntry};
+use tr::tr;
+
+/// Best possible MOS score of the E-model.
+const MAX_MOS: f64 = 4.5;
+
+/// Collects successful RTTs from the event log with timestamps in `[from, to)`.
+fn rtts_between(status: &HostStatus, from: u64, to: u64) -> Vec<f64> {
+    status
+        .events
+        .iter()
+        .rev()
+        .take_while(|e| e.timestamp() >= from)
+        .filter_map(|e| match e {
+            LogEntry::Ping {
+                timestamp,
+                rtt: Some(rtt),
+                ..
+            } if *timestamp < to => Some(*rtt as f64),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Checks a single rule against the host's current data.
+///
+/// Returns a description of the violation, or `None` if the rule is satisfied
+/// (or there is not enough data to judge).
+pub fn check_rule(rule: &AlertRule, status: &HostStatus, now: u64) -> Option<String> {
+    match rule {
+        AlertRule::LatencyTrend {
+            increase_pct,
+            window_min,
+        } => {
+            let window = *window_min as u64 * 60;
+            let recent = rtts_between(status, now.saturating_sub(window), now + 1);
+            let previous = rtts_between(
+                status,
+                now.saturating_sub(2 * window),
+                now.saturating_sub(window),
+            );
+            if recent.len() < MIN_TREND_SAMPLES || previous.len() < MIN_TREND_SAMPLES {
+                return None;
+            }
+            let recent_med = calculate_percentile(recent, 50.0);
+            let previous_med = calculate_percentile(previous, 50.0);
+            if previous_med <= 0.0 {
+                return None;
+            }
+            let change = (recent_med - previous_med) / previous_med * 100.0;
+            (change > *increase_pct).then(|| {
+                format!(
+                    "{} ({:.1} → {:.1} {}, +{:.0}%)",
+                    rule.label(),
+                    previous_med,
+                    recent_med,
+                    tr!("ms"),
+                    change
+                )
+            })
+        }
+        AlertRule::LossAbove { pct } => {
+            if status.history.len() < MIN_TREND_SAMPLES {
+                return None;
+            }
+            let loss = 100.0 - status.availability;
+            (loss > *pct).then(|| format!("{} ({:.1}%)", rule.label(), loss))
+        }
+        AlertRule::ConsecutiveFailures { count } => (!status.streak_success
+            && status.streak >= *count)
+            .then(|| format!("{} ({})", rule.label(), status.streak)),
+        AlertRule::LatencyAbove { ms } => {
+            let valid = status.history.iter().filter(|s| !s.is_lost()).count();
+            (valid >= MIN_TREND_SAMPLES && status.median > *ms)
+                .then(|| format!("{} ({:.1} {})", rule.label(), status.median, tr!("ms")))
+        }
+        AlertRule::MosBelow { mos } => (status.history.len() >= MIN_TREND_SAMPLES
+            && status.mos < *mos)
+            .then(|| format!("{} ({:.2})", rule.label(), status.mos)),
+    }
+}
+
+/// How close the host is to violating a rule, as a fraction of the threshold
+/// (1.0 = at the threshold).
+///
+/// Returns `None` for rules without a fixed threshold (latency trend) or when
+/// there is not enough data yet.
+pub fn rule_proximity(rule: &AlertRule, status: &HostStatus) -> Option<f64> {
+    match rule {
+        AlertRule::LatencyTrend { .. } => None,
+        AlertRule::LossAbove { pct } => (status.history.len() >= MIN_TREND_SAMPLES && *pct > 0.0)
+            .then(|| (100.0 - status.availability) / pct),
+        AlertRule::ConsecutiveFailures { count } => Some(if status.streak_success || *count == 0 {
+            0.0
+        } else {
+            status.streak as f64 / *count as f64
+        }),
+        AlertRule::LatencyAbove { ms } => {
+            let valid = status.history.iter().filter(|s| !s.is_lost()).count();
+            (valid >= MIN_TREND_SAMPLES && *ms > 0.0).then(|| status.median / ms)
+        }
+        AlertRule::MosBelow { mos } => (status.history.len() >= MIN_TREND_SAMPLES
+            && *mos < MAX_MOS)
+            .then(|| ((MAX_MOS - status.mos) / (MAX_MOS - mos)).max(0.0)),
+    }
+}
+
+/// Returns the rule the host is closest to violating, with its proximity.
+pub fn closest_threshold<'a>(
+    host: &'a HostInfo,
+    status: &HostStatus,
+) -> Option<(&'a AlertRule, f64)> {
+    host.alert_rules
+        .iter()
+        .filter_map(|rule| rule_proximity(rule, status).map(|p| (rule, p)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// Evaluates all alert rules of a host and updates its active alerts.
+///
+/// Returns `LogEntry::Alert` events for alerts that were raised or cleared;
+/// they are also appended to the host's event log.
+pub fn evaluate_host_alerts(host: &HostInfo, status: &mut HostStatus, now: u64) -> Vec<LogEntry> {
+    let mut changes = Vec::new();
+
+    // Alerts whose rules were removed from the host are dropped silently
+    status
+        .active_alerts
+        .retain(|a| host.alert_rules.contains(&a.rule));
+
+    for rule in &host.alert_rules {
+        let violation = check_rule(rule, status, now);
+        let active_idx = status.active_alerts.iter().position(|a| a.rule == *rule);
+
+        match (violation, active_idx) {
+            (Some(message), None) => {
+                status.active_alerts.push(ActiveAlert {
+                    rule: rule.clone(),
+                    message: message.clone(),
+                    since: now,
+                });
+                changes.push(LogEntry::Alert {
+                    timestamp: now,
+                    raised: true,
+                    message,
+                });
+            }
+            (None, Some(idx)) => {
+                let alert = status.active_alerts.remove(idx);
+                changes.push(LogEntry::Alert {
+                    timestamp: now,
+                    raised: false,
+                    message: alert.rule.label(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    for ev in &changes {
+        status.events.push_back(ev.clone());
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_pings(status: &mut HostStatus, from: u64, to: u64, rtt: f32) {
+        for ts in from..to {
+            status.events.push_back(LogEntry::Ping {
+                timestamp: ts,
+                seq: ts as u32,
+                rtt: Some(rtt),
+                bytes: 16,
+            });
+        }
+    }
+
+    fn trend_host() -> HostInfo {
+        HostInfo {
+            address: "1.1.1.1".to_string(),
+            alert_rules: vec![AlertRule::LatencyTrend {
+                increase_pct: 50.0,
+                window_min: 1,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_trend_not_raised_for_stable_latency() {
+        let mut status = HostStatus::default();
+        push_pings(&mut status, 1000, 1120, 20.0);
+        let changes = evaluate_host_alerts(&trend_host(), &mut status, 1119);
+        assert!(changes.is_empty());
+        assert!(status.active_alerts.is_empty());
+    }
+
+    #[test]
+    fn test_trend_raised_and_cleared() {
+        let host = trend_host();
+        let mut status = HostStatus::default();
+        push_pings(&mut status, 1000, 1060, 20.0);
+        push_pings(&mut status, 1060, 1120, 40.0);
+
+        let changes = evaluate_host_alerts(&host, &mut status, 1119);
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(changes[0], LogEntry::Alert { raised: true, .. }));
+        assert_eq!(status.active_alerts.len(), 1);
+
+        // Still violated: no duplicate alert
+        assert!(evaluate_host_alerts(&host, &mut status, 1119).is_empty());
+
+        // Latency stabilizes at the new level: the next window compares 40 vs 40
+        push_pings(&mut status, 1120, 1180, 40.0);
+        let changes = evaluate_host_alerts(&host, &mut status, 1179);
+        assert!(matches!(changes[0], LogEntry::Alert { raised: false, .. }));
+        assert!(status.active_alerts.is_empty());
+    }
+
+    #[test]
+    fn test_trend_requires_enough_samples() {
+        let mut status = HostStatus::default();
+        push_pings(&mut status, 1000, 1003, 10.0);
+        push_pings(&mut status, 1060, 1063, 100.0);
+        let rule = &trend_host().alert_rules[0];
+        assert_eq!(check_rule(rule, &status, 1100), None);
+    }
+
+    #[test]
+    fn test_threshold_rules() {
+        let mut status = HostStatus::default();
+        for _ in 0..10 {
+            status.add_sample(150.0, true);
+        }
+        let latency = AlertRule::LatencyAbove { ms: 100.0 };
+        assert!(check_rule(&latency, &status, 0).is_some());
+        assert!(check_rule(&AlertRule::LatencyAbove { ms: 200.0 }, &status, 0).is_none());
+
+        let failures = AlertRule::ConsecutiveFailures { count: 3 };
+        let loss = AlertRule::LossAbove { pct: 20.0 };
+        for _ in 0..2 {
+            status.add_sample(f64::NAN, false);
+        }
+        assert!(check_rule(&failures, &status, 0).is_none());
+        assert!(check_rule(&loss, &status, 0).is_none());
+        status.add_sample(f64::NAN, false);
+        assert!(check_rule(&failures, &status, 0).is_some());
+        assert!(check_rule(&loss, &status, 0).is_some());
+
+        status.add_sample(20.0, true);
+        assert!(check_rule(&failures, &status, 0).is_none());
+    }
+
+    #[test]
+    fn test_removed_rule_drops_alert() {
+        let mut host = trend_host();
+        let mut status = HostStatus::default();
+        push_pings(&mut status, 1000, 1060, 20.0);
+        push_pings(&mut status, 1060, 1120, 40.0);
+        evaluate_host_alerts(&host, &mut status, 1119);
+        assert_eq!(status.active_alerts.len(), 1);
+
+        host.alert_rules.clear();
+        evaluate_host_alerts(&host, &mut status, 1119);
+        assert!(status.active_alerts.is_empty());
+    }
+
+    #[test]
+    fn test_threshold_proximity() {
+        let mut status = HostStatus::default();
+        let latency = AlertRule::LatencyAbove { ms: 100.0 };
+        assert_eq!(rule_proximity(&latency, &status), None);
+        for _ in 0..10 {
+            status.add_sample(80.0, true);
+        }
+        assert_eq!(rule_proximity(&latency, &status), Some(0.8));
+
+        let failures = AlertRule::ConsecutiveFailures { count: 4 };
+        assert_eq!(rule_proximity(&failures, &status), Some(0.0));
+        status.add_sample(f64::NAN, false);
+        assert_eq!(rule_proximity(&failures, &status), Some(0.25));
+
+        let host = HostInfo {
+            alert_rules: vec![
+                failures,
+                latency.clone(),
+                AlertRule::LatencyTrend {
+                    increase_pct: 50.0,
+                    window_min: 10,
+                },
+            ],
+            ..Default::default()
+        };
+        let (rule, p) = closest_threshold(&host, &status).unwrap();
+        assert_eq!((rule, p), (&latency, 0.8));
+    }
+}
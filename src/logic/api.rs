@@ -0,0 +1,578 @@
+//! JSON API over HTTP for scripts and home-automation systems: the statistics,
+//! the history of a host, and adding and removing hosts.
+//!
+//! Listens on localhost unless opened to the network in the settings; with a
+//! token set, every request needs `Authorization: Bearer <token>`. Without one,
+//! only requests with a `Host` of localhost are answered.
+//!
+//! - `GET /api/status`: statistics of all hosts
+//! - `GET /api/hosts`: the configured hosts: address, name, group, tags and whether enabled
+//! - `POST /api/hosts` with `{"address": "…", "name": "…"}`: adds a host
+//! - `GET /api/hosts/<address>`: statistics of a host
+//! - `GET /api/hosts/<address>/history?from=<unix time>`: its pings, by default of the last hour
+//! - `DELETE /api/hosts/<address>`: removes a host
+//!
+//! Addresses in paths are percent-encoded, e.g. `https%3A%2F%2Fexample.com`.
+
+use crate::constants::{API_DEFAULT_HISTORY_SEC, API_MAX_REQUEST_BYTES};
+use crate::logic::listener::{self, Listener};
+use crate::logic::{SharedState, applog, storage};
+use crate::model::snapshot::HostSnapshot;
+use crate::model::{HostInfo, StatsSnapshot};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpStream};
+use std::time::Duration;
+
+/// A parsed HTTP request.
+#[derive(Debug, Default)]
+pub(crate) struct Request {
+    pub method: String,
+    /// Path without the query, still percent-encoded.
+    pub path: String,
+    pub query: String,
+    pub authorization: Option<String>,
+    /// The `Host` header, naming the server the client thinks it talks to.
+    pub host: Option<String>,
+    /// Media type of the body, without its parameters.
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+/// Body of `POST /api/hosts`.
+#[derive(Debug, Deserialize)]
+struct NewHost {
+    address: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    group: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Decodes `%XX` escapes; `None` if the result is not UTF-8.
+fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(byte) = input
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Compares without stopping at the first difference, so the time taken does not
+/// tell how much of a guessed token was right.
+fn same_token(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Whether the `Host` header names this machine, so the request did not come from
+/// a page that had its own domain resolve to 127.0.0.1 (DNS rebinding).
+fn is_local_host(host: &str) -> bool {
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => name,
+        _ => host,
+    };
+    matches!(
+        name.to_ascii_lowercase().as_str(),
+        "localhost" | "127.0.0.1" | "[::1]"
+    )
+}
+
+/// The fields of a host the API shows; its commands, files and credentials stay in
+/// the application.
+fn host_fields(host: &HostInfo) -> Value {
+    json!({
+        "address": host.address,
+        "name": host.name,
+        "group": host.group,
+        "tags": host.tags,
+        "enabled": !host.is_stopped,
+    })
+}
+
+fn error(status: u16, message: &str) -> (u16, Value) {
+    (status, json!({ "error": message }))
+}
+
+/// Answers a request with a status code and a JSON body.
+pub(crate) fn handle(state: &SharedState, request: &Request) -> (u16, Value) {
+    let token = state
+        .lock()
+        .expect("Failed to lock state for the API")
+        .settings
+        .api
+        .token
+        .clone();
+    let writes = request.method != "GET";
+    if token.is_empty() && writes {
+        return error(403, "set a token to add or remove hosts");
+    }
+    if token.is_empty() && !request.host.as_deref().is_some_and(is_local_host) {
+        return error(403, "without a token only localhost is answered");
+    }
+    if !token.is_empty() {
+        let given = request
+            .authorization
+            .as_deref()
+            .and_then(|a| a.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        if !same_token(given.trim(), &token) {
+            return error(401, "missing or wrong token");
+        }
+    }
+    // A page in a browser can post a form or plain text to localhost, but not JSON
+    if request.method == "POST" && request.content_type.as_deref() != Some("application/json") {
+        return error(415, "the body must be application/json");
+    }
+
+    let segments: Vec<&str> = request.path.trim_end_matches('/').split('/').collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["", "api", "status"]) => {
+            let state_lock = state.lock().expect("Failed to lock state for the API");
+            (200, json!(StatsSnapshot::capture(&state_lock)))
+        }
+        ("GET", ["", "api", "hosts"]) => {
+            let state_lock = state.lock().expect("Failed to lock state for the API");
+            let hosts: Vec<Value> = state_lock.hosts.iter().map(host_fields).collect();
+            (200, json!(hosts))
+        }
+        ("POST", ["", "api", "hosts"]) => add_host(state, &request.body),
+        (method, ["", "api", "hosts", address, rest @ ..]) => {
+            let Some(address) = percent_decode(address) else {
+                return error(400, "invalid address");
+            };
+            match (method, rest) {
+                ("GET", []) => host_status(state, &address),
+                ("GET", ["history"]) => host_history(state, &address, &request.query),
+                ("DELETE", []) => delete_host(state, &address),
+                (_, [] | ["history"]) => error(405, "method not allowed"),
+                _ => error(404, "not found"),
+            }
+        }
+        (_, ["", "api", "status" | "hosts"]) => error(405, "method not allowed"),
+        _ => error(404, "not found"),
+    }
+}
+
+fn add_host(state: &SharedState, body: &[u8]) -> (u16, Value) {
+    let new_host: NewHost = match serde_json::from_slice(body) {
+        Ok(h) => h,
+        Err(e) => return error(400, &e.to_string()),
+    };
+    if new_host.address.trim().is_empty() {
+        return error(400, "the address is empty");
+    }
+    let mut state_lock = state.lock().expect("Failed to lock state for the API");
+    let Some(host) = state_lock.add_host(&new_host.name, &new_host.address) else {
+        return error(409, "the host is monitored already");
+    };
+    let address = host.address.clone();
+    let host = state_lock
+        .hosts
+        .iter_mut()
+        .find(|h| h.address == address)
+        .expect("added above");
+    host.group = new_host.group.trim().to_string();
+    host.tags = new_host.tags;
+    applog::info("api", format!("Added host {}", address));
+    (201, host_fields(host))
+}
+
+fn host_status(state: &SharedState, address: &str) -> (u16, Value) {
+    let state_lock = state.lock().expect("Failed to lock state for the API");
+    match (
+        state_lock.hosts.iter().find(|h| h.address == address),
+        state_lock.statuses.get(address),
+    ) {
+        (Some(host), Some(status)) => (200, json!(HostSnapshot::capture(host, status))),
+        _ => error(404, "no such host"),
+    }
+}
+
+/// Pings of a host since `from` in the query: from the persistent history if it
+/// is kept, otherwise from the samples in memory.
+fn host_history(state: &SharedState, address: &str, query: &str) -> (u16, Value) {
+    let now = chrono::Utc::now().timestamp() as u64;
+    let from = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("from="))
+        .and_then(|from| from.parse::<u64>().ok())
+        .unwrap_or(now.saturating_sub(API_DEFAULT_HISTORY_SEC));
+    let (persistent, in_memory) = {
+        let state_lock = state.lock().expect("Failed to lock state for the API");
+        let Some(status) = state_lock
+            .statuses
+            .get(address)
+            .filter(|_| state_lock.hosts.iter().any(|h| h.address == address))
+        else {
+            return error(404, "no such host");
+        };
+        let in_memory: Vec<(u64, Option<f64>)> = status
+            .history
+            .iter()
+            .filter(|s| s.timestamp >= from && !s.is_no_data())
+            .map(|s| (s.timestamp, s.rtt))
+            .collect();
+        (state_lock.settings.persistent_history, in_memory)
+    };
+    let samples = if persistent {
+        match storage::query(address, from) {
+            Ok(records) => records
+                .iter()
+                .map(|r| (r.timestamp, r.rtt.map(f64::from)))
+                .collect(),
+            Err(e) => return error(500, &e.to_string()),
+        }
+    } else {
+        in_memory
+    };
+    let pings: Vec<Value> = samples
+        .into_iter()
+        .map(|(timestamp, rtt)| json!({ "timestamp": timestamp, "rtt_ms": rtt }))
+        .collect();
+    (200, json!(pings))
+}
+
+fn delete_host(state: &SharedState, address: &str) -> (u16, Value) {
+    let mut state_lock = state.lock().expect("Failed to lock state for the API");
+    if !state_lock.hosts.iter().any(|h| h.address == address) {
+        return error(404, "no such host");
+    }
+    let retain_days = state_lock
+        .settings
+        .retain_deleted_hosts
+        .then_some(state_lock.settings.deleted_host_retention_days);
+    state_lock.delete_host(address, retain_days, chrono::Utc::now().timestamp() as u64);
+    applog::info("api", format!("Removed host {}", address));
+    (204, Value::Null)
+}
+
+/// Reads the request line, the headers and the body of one request.
+fn read_request(stream: &TcpStream) -> Result<Request, (u16, &'static str)> {
+    let mut reader = BufReader::new(stream.take(API_MAX_REQUEST_BYTES as u64));
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .map_err(|_| (400, "unreadable request"))?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err((400, "malformed request line"));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query.to_string(),
+        ..Default::default()
+    };
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        if reader
+            .read_line(&mut line)
+            .map_err(|_| (400, "unreadable headers"))?
+            == 0
+        {
+            return Err((413, "request too large"));
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-length" => {
+                    content_length = value.trim().parse().map_err(|_| (400, "bad length"))?
+                }
+                "authorization" => request.authorization = Some(value.trim().to_string()),
+                "host" => request.host = Some(value.trim().to_string()),
+                "content-type" => {
+                    let media_type = value.split(';').next().unwrap_or_default();
+                    request.content_type = Some(media_type.trim().to_ascii_lowercase());
+                }
+                _ => {}
+            }
+        }
+    }
+    if content_length > API_MAX_REQUEST_BYTES {
+        return Err((413, "request too large"));
+    }
+    request.body = vec![0; content_length];
+    reader
+        .read_exact(&mut request.body)
+        .map_err(|_| (400, "incomplete body"))?;
+    Ok(request)
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        _ => "Internal Server Error",
+    }
+}
+
+fn serve_request(state: &SharedState, mut stream: TcpStream) {
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
+    let _ = stream.set_write_timeout(Some(Duration::from_secs(10)));
+    let (status, body) = match read_request(&stream) {
+        Ok(request) => handle(state, &request),
+        Err((status, message)) => error(status, message),
+    };
+    let body = if status == 204 {
+        String::new()
+    } else {
+        body.to_string()
+    };
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes());
+}
+
+/// A running API listener for its port, interfaces and token; dropping it stops
+/// the listener.
+pub type ApiServer = Listener<(u16, bool, String)>;
+
+/// Starts listening in a background thread, on all interfaces if `listen_all`,
+/// which needs a token.
+pub fn start(
+    state: SharedState,
+    port: u16,
+    listen_all: bool,
+    token: &str,
+) -> std::io::Result<ApiServer> {
+    if listen_all && token.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "a token is required to accept requests from other machines",
+        ));
+    }
+    let ip = if listen_all {
+        Ipv4Addr::UNSPECIFIED
+    } else {
+        Ipv4Addr::LOCALHOST
+    };
+    let key = (port, listen_all, token.to_string());
+    Listener::start("api", ip, port, key, move |client, _, _| {
+        serve_request(&state, client)
+    })
+}
+
+/// Starts, restarts or stops the server to follow the settings, keeping the
+/// reason a start failed in `AppState::api_error` for the settings window.
+pub fn follow_settings(state: &SharedState, server: &mut Option<ApiServer>) {
+    let settings = state
+        .lock()
+        .expect("Failed to lock state for the API")
+        .settings
+        .api
+        .clone();
+    let wanted = settings
+        .enabled
+        .then(|| (settings.port, settings.listen_all, settings.token.clone()));
+    let restart = || {
+        start(
+            state.clone(),
+            settings.port,
+            settings.listen_all,
+            &settings.token,
+        )
+    };
+    if let Some(error) = listener::follow("api", server, wanted, settings.port, restart) {
+        state
+            .lock()
+            .expect("Failed to lock state for the API")
+            .api_error = error;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::AppState;
+    use std::sync::{Arc, Mutex};
+
+    fn request(method: &str, target: &str, body: &str) -> Request {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: query.to_string(),
+            authorization: Some("Bearer secret".to_string()),
+            host: Some("localhost:7173".to_string()),
+            content_type: Some("application/json".to_string()),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_hosts_are_added_listed_and_removed() {
+        let state = Arc::new(Mutex::new(AppState::default()));
+        state.lock().unwrap().settings.api.token = "secret".to_string();
+        let unauthorized = Request {
+            authorization: None,
+            ..request("GET", "/api/hosts", "")
+        };
+        assert_eq!(handle(&state, &unauthorized).0, 401);
+        let form = Request {
+            content_type: Some("text/plain".to_string()),
+            ..request("POST", "/api/hosts", r#"{"address": "192.0.2.7"}"#)
+        };
+        assert_eq!(handle(&state, &form).0, 415);
+
+        let body =
+            r#"{"address": " https://example.com/health ", "name": "Site", "tags": ["web"]}"#;
+        let (status, host) = handle(&state, &request("POST", "/api/hosts", body));
+        assert_eq!(status, 201);
+        assert_eq!(host["address"], "https://example.com/health");
+        assert_eq!(host["tags"], json!(["web"]));
+        assert_eq!(handle(&state, &request("POST", "/api/hosts", body)).0, 409);
+        assert_eq!(handle(&state, &request("POST", "/api/hosts", "{}")).0, 400);
+
+        let encoded = "/api/hosts/https%3A%2F%2Fexample.com%2Fhealth";
+        let (status, snapshot) = handle(&state, &request("GET", encoded, ""));
+        assert_eq!((status, snapshot["name"].as_str()), (200, Some("Site")));
+        state
+            .lock()
+            .unwrap()
+            .statuses
+            .get_mut("https://example.com/health")
+            .unwrap()
+            .add_sample(20.0, true);
+        let (status, pings) = handle(
+            &state,
+            &request("GET", &format!("{}/history?from=0", encoded), ""),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(pings[0]["rtt_ms"], 20.0);
+
+        assert_eq!(handle(&state, &request("DELETE", encoded, "")).0, 204);
+        assert_eq!(handle(&state, &request("GET", encoded, "")).0, 404);
+        assert_eq!(handle(&state, &request("PUT", "/api/hosts", "")).0, 405);
+        assert_eq!(
+            handle(&state, &request("GET", "/api/status", "")).1["hosts"],
+            json!([])
+        );
+
+        // Without a token the API is read-only
+        state.lock().unwrap().settings.api.token.clear();
+        assert_eq!(handle(&state, &request("GET", "/api/hosts", "")).0, 200);
+        assert_eq!(handle(&state, &request("POST", "/api/hosts", body)).0, 403);
+        assert_eq!(handle(&state, &request("DELETE", encoded, "")).0, 403);
+        // A page on another domain resolving to 127.0.0.1 gets nothing
+        let rebound = Request {
+            host: Some("attacker.example:7173".to_string()),
+            ..request("GET", "/api/hosts", "")
+        };
+        assert_eq!(handle(&state, &rebound).0, 403);
+        let no_host = Request {
+            host: None,
+            ..request("GET", "/api/status", "")
+        };
+        assert_eq!(handle(&state, &no_host).0, 403);
+    }
+
+    #[test]
+    fn test_hosts_show_no_commands_or_credentials() {
+        let state = Arc::new(Mutex::new(AppState::default()));
+        state.lock().unwrap().hosts.push(HostInfo {
+            address: "192.0.2.7".to_string(),
+            name: "Router".to_string(),
+            probe_command: "check --password hunter2".to_string(),
+            on_down_command: "notify-down".to_string(),
+            on_up_command: "notify-up".to_string(),
+            notes: "admin password in the drawer".to_string(),
+            log_file_path: "/home/me/router.log".to_string(),
+            snmp: crate::model::SnmpSettings {
+                community: "private-community".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let (status, hosts) = handle(&state, &request("GET", "/api/hosts", ""));
+        assert_eq!(status, 200);
+        assert_eq!(hosts[0]["name"], "Router");
+        assert_eq!(hosts[0]["enabled"], true);
+        let text = hosts.to_string();
+        for secret in [
+            "hunter2",
+            "notify-down",
+            "notify-up",
+            "drawer",
+            "router.log",
+            "private-community",
+        ] {
+            assert!(!text.contains(secret), "{} in {}", secret, text);
+        }
+    }
+
+    #[test]
+    fn test_other_machines_need_a_token() {
+        let state = Arc::new(Mutex::new(AppState::default()));
+        let refused = start(state.clone(), 0, true, "").err().unwrap();
+        assert_eq!(refused.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(start(state, 0, true, "secret").is_ok());
+    }
+
+    #[test]
+    fn test_request_is_read_from_the_socket() {
+        let state = Arc::new(Mutex::new(AppState::default()));
+        state.lock().unwrap().settings.api.token = "secret".to_string();
+        let server = start(state, 0, false, "secret").unwrap();
+        let mut client = TcpStream::connect(("127.0.0.1", server.local_port.unwrap())).unwrap();
+        let body = r#"{"address": "192.0.2.7"}"#;
+        write!(
+            client,
+            "POST /api/hosts HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer secret\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        assert!(
+            response.starts_with("HTTP/1.1 201 Created\r\n"),
+            "{}",
+            response
+        );
+        assert!(
+            response.contains(r#""address":"192.0.2.7""#),
+            "{}",
+            response
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use crate::constants::MAX_APP_LOG_RECORDS;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{LazyLock, Mutex};
+
+/// Severity of an application log record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+/// A single structured record of the application's own diagnostic log.
+///
+/// Unlike [`crate::model::LogEntry`], which describes what happened to a monitored
+/// host, these records describe what happened inside the application itself
+/// (failed spawns, socket errors, etc.) and are meant for bug reports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppLogRecord {
+    pub timestamp: u64,
+    pub level: LogLevel,
+    /// Subsystem that produced the record (e.g. "pinger", "tracer").
+    pub target: String,
+    pub message: String,
+}
+
+static APP_LOG: LazyLock<Mutex<VecDeque<AppLogRecord>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::new()));
+
+/// Appends a record to the in-memory application log and mirrors it to stderr.
+pub fn log(level: LogLevel, target: &str, message: impl Into<String>) {
+    let record = AppLogRecord {
+        timestamp: chrono::Utc::now().timestamp() as u64,
+        level,
+        target: target.to_string(),
+        message: message.into(),
+    };
+    eprintln!("[{:?}] {}: {}", record.level, record.target, record.message);
+
+    if let Ok(mut log) = APP_LOG.lock() {
+        log.push_back(record);
+        while log.len() > MAX_APP_LOG_RECORDS {
+            log.pop_front();
+        }
+    }
+}
+
+pub fn info(target: &str, message: impl Into<String>) {
+    log(LogLevel::Info, target, message);
+}
+
+pub fn warn(target: &str, message: impl Into<String>) {
+    log(LogLevel::Warn, target, message);
+}
+
+pub fn error(target: &str, message: impl Into<String>) {
+    log(LogLevel::Error, target, message);
+}
+
+/// Returns a copy of the most recent `limit` records, oldest first.
+pub fn recent(limit: usize) -> Vec<AppLogRecord> {
+    match APP_LOG.lock() {
+        Ok(log) => {
+            let start = log.len().saturating_sub(limit);
+            log.range(start..).cloned().collect()
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_are_kept_in_order() {
+        info("test_order", "first");
+        warn("test_order", "second");
+        let ours: Vec<AppLogRecord> = recent(MAX_APP_LOG_RECORDS)
+            .into_iter()
+            .filter(|r| r.target == "test_order")
+            .collect();
+        assert_eq!(ours.len(), 2);
+        assert_eq!(ours[0].message, "first");
+        assert_eq!(ours[1].level, LogLevel::Warn);
+    }
+}
@@ -0,0 +1,111 @@
+//! Window icon with the number of hosts that are down, shown on the taskbar.
+
+use eframe::egui::IconData;
+
+/// 3×5 pixel digits, one row per entry with the leftmost pixel in bit 2.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+const PLUS: [u8; 5] = [0b000, 0b010, 0b111, 0b010, 0b000];
+
+const BADGE_COLOR: [u8; 4] = [213, 43, 30, 255];
+const TEXT_COLOR: [u8; 4] = [255, 255, 255, 255];
+
+/// The icon with `count` in a red circle in its top right corner; counts above 9
+/// show as "9+". A count of 0 returns the icon unchanged.
+pub fn badge_icon(base: &IconData, count: usize) -> IconData {
+    let mut icon = base.clone();
+    if count == 0 {
+        return icon;
+    }
+    let (w, h) = (icon.width as i64, icon.height as i64);
+    let diameter = w.min(h) * 11 / 20;
+    let radius = diameter as f64 / 2.0;
+    let (cx, cy) = (w as f64 - radius, radius);
+    let mut put = |x: i64, y: i64, color: [u8; 4]| {
+        if (0..w).contains(&x) && (0..h).contains(&y) {
+            let i = ((y * w + x) * 4) as usize;
+            icon.rgba[i..i + 4].copy_from_slice(&color);
+        }
+    };
+    for y in 0..diameter {
+        for x in (w - diameter)..w {
+            let (dx, dy) = (x as f64 + 0.5 - cx, y as f64 + 0.5 - cy);
+            if dx * dx + dy * dy <= radius * radius {
+                put(x, y, BADGE_COLOR);
+            }
+        }
+    }
+
+    let glyphs: Vec<[u8; 5]> = if count > 9 {
+        vec![DIGITS[9], PLUS]
+    } else {
+        vec![DIGITS[count]]
+    };
+    // Text of 5 rows fills about 60% of the circle
+    let scale = (diameter * 3 / 25).max(1);
+    let text_w = (glyphs.len() as i64 * 4 - 1) * scale;
+    let (left, top) = (
+        (cx - text_w as f64 / 2.0).round() as i64,
+        (cy - (5 * scale) as f64 / 2.0).round() as i64,
+    );
+    for (g, glyph) in glyphs.iter().enumerate() {
+        for (row, bits) in glyph.iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+                let x0 = left + (g as i64 * 4 + col) * scale;
+                let y0 = top + row as i64 * scale;
+                for y in y0..y0 + scale {
+                    for x in x0..x0 + scale {
+                        put(x, y, TEXT_COLOR);
+                    }
+                }
+            }
+        }
+    }
+    icon
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_badge_icon() {
+        let base = IconData {
+            rgba: vec![0; 64 * 64 * 4],
+            width: 64,
+            height: 64,
+        };
+        assert_eq!(badge_icon(&base, 0), base);
+
+        let pixel = |icon: &IconData, x: usize, y: usize| {
+            let i = (y * 64 + x) * 4;
+            [
+                icon.rgba[i],
+                icon.rgba[i + 1],
+                icon.rgba[i + 2],
+                icon.rgba[i + 3],
+            ]
+        };
+        let one = badge_icon(&base, 1);
+        // Inside the circle, left of the digit; the lower left corner is untouched
+        assert_eq!(pixel(&one, 64 - 30, 17), BADGE_COLOR);
+        assert_eq!(pixel(&one, 2, 60), [0; 4]);
+        // The foot of the "1" is white
+        assert_eq!(pixel(&one, 64 - 18, 17 + 8), TEXT_COLOR);
+
+        assert_ne!(badge_icon(&base, 12), one);
+    }
+}
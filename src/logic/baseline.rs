@@ -0,0 +1,186 @@
+//! Comparison of the first minutes of a session with the previous session.
+//!
+//! When the application saves its state, the median RTT and loss of each host in
+//! this session are kept as its baseline. A few minutes after the next start, hosts
+//! that became clearly slower or lossier are pointed out, e.g. "Latency to gateway
+//! is 40% higher than yesterday".
+
+use crate::constants::{
+    SESSION_BASELINE_MIN_SAMPLES, SESSION_LOSS_INCREASE_PP, SESSION_RTT_INCREASE_MIN_MS,
+    SESSION_RTT_INCREASE_PCT,
+};
+use crate::model::{AppState, Sample, SessionBaseline};
+use chrono::{Local, TimeZone};
+use std::collections::HashMap;
+use tr::tr;
+
+/// Median RTT and loss of `samples`, if they contain enough replies.
+pub fn baseline_of<'a>(
+    samples: impl IntoIterator<Item = &'a Sample>,
+    timestamp: u64,
+) -> Option<SessionBaseline> {
+    let mut rtts = Vec::new();
+    let mut sent = 0u32;
+    for sample in samples {
+        sent += 1;
+        if let Some(rtt) = sample.rtt {
+            rtts.push(rtt);
+        }
+    }
+    if (rtts.len() as u32) < SESSION_BASELINE_MIN_SAMPLES {
+        return None;
+    }
+    rtts.sort_by(f64::total_cmp);
+    Some(SessionBaseline {
+        timestamp,
+        median: rtts[rtts.len() / 2],
+        loss_pct: (sent - rtts.len() as u32) as f64 / sent as f64 * 100.0,
+        samples: sent,
+    })
+}
+
+/// Stores the baselines of the hosts from their samples since `session_start`.
+///
+/// Hosts without enough samples in this session keep their older baseline.
+pub fn record_baselines(state: &mut AppState, session_start: u64, now: u64) {
+    for host in &state.hosts {
+        let Some(status) = state.statuses.get(&host.address) else {
+            continue;
+        };
+        let session = status
+            .history
+            .iter()
+            .filter(|s| s.timestamp >= session_start);
+        if let Some(baseline) = baseline_of(session, now) {
+            state.baselines.insert(host.address.clone(), baseline);
+        }
+    }
+    let known: Vec<&String> = state.hosts.iter().map(|h| &h.address).collect();
+    state
+        .baselines
+        .retain(|address, _| known.contains(&address));
+}
+
+/// When a baseline was taken, relative to `now`: "yesterday", "earlier today" or a date.
+fn when(timestamp: u64, now: u64) -> String {
+    let day = |ts: u64| {
+        Local
+            .timestamp_opt(ts as i64, 0)
+            .single()
+            .map(|t| t.date_naive())
+    };
+    match (day(timestamp), day(now)) {
+        (Some(then), Some(today)) if then == today => tr!("earlier today"),
+        (Some(then), Some(today)) if then.succ_opt() == Some(today) => tr!("yesterday"),
+        (Some(then), _) => tr!("on {date}").replace("{date}", &then.format("%Y-%m-%d").to_string()),
+        _ => tr!("in the previous session"),
+    }
+}
+
+/// Messages about hosts whose samples since `session_start` are clearly worse than
+/// their `baselines` from the previous session.
+pub fn compare(
+    state: &AppState,
+    baselines: &HashMap<String, SessionBaseline>,
+    session_start: u64,
+    now: u64,
+) -> Vec<String> {
+    let mut messages = Vec::new();
+    for host in &state.hosts {
+        let (Some(before), Some(status)) = (
+            baselines.get(&host.address),
+            state.statuses.get(&host.address),
+        ) else {
+            continue;
+        };
+        let session = status
+            .history
+            .iter()
+            .filter(|s| s.timestamp >= session_start);
+        let Some(current) = baseline_of(session, now) else {
+            continue;
+        };
+        let name = if host.name.is_empty() {
+            &host.address
+        } else {
+            &host.name
+        };
+        let increase = current.median - before.median;
+        if before.median > 0.0
+            && increase >= SESSION_RTT_INCREASE_MIN_MS
+            && increase / before.median * 100.0 >= SESSION_RTT_INCREASE_PCT
+        {
+            messages.push(
+                tr!("Latency to {host} is {pct}% higher than {when}")
+                    .replace("{host}", name)
+                    .replace("{pct}", &format!("{:.0}", increase / before.median * 100.0))
+                    .replace("{when}", &when(before.timestamp, now)),
+            );
+        }
+        if current.loss_pct - before.loss_pct >= SESSION_LOSS_INCREASE_PP {
+            messages.push(
+                tr!("Packet loss to {host} is {now}%, {before}% {when}")
+                    .replace("{host}", name)
+                    .replace("{now}", &format!("{:.1}", current.loss_pct))
+                    .replace("{before}", &format!("{:.1}", before.loss_pct))
+                    .replace("{when}", &when(before.timestamp, now)),
+            );
+        }
+    }
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{HostInfo, HostStatus};
+
+    /// 40 samples from `start`, every `lost_every`-th lost (none for 0).
+    fn status(start: u64, rtt: f64, lost_every: usize) -> HostStatus {
+        HostStatus {
+            history: (0..40)
+                .map(|i| Sample {
+                    timestamp: start + i as u64,
+                    rtt: (lost_every == 0 || i % lost_every != 0).then_some(rtt),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_baselines_and_comparison() {
+        let mut state = AppState::default();
+        for (address, name) in [("10.0.0.1", "gateway"), ("8.8.8.8", "")] {
+            state.hosts.push(HostInfo {
+                name: name.to_string(),
+                address: address.to_string(),
+                ..Default::default()
+            });
+        }
+        // Previous session: 20 ms without loss, and too few samples of 8.8.8.8
+        state
+            .statuses
+            .insert("10.0.0.1".to_string(), status(1_000, 20.0, 0));
+        let mut short = status(1_000, 20.0, 0);
+        short.history.truncate(5);
+        state.statuses.insert("8.8.8.8".to_string(), short);
+        record_baselines(&mut state, 1_000, 2_000);
+        assert_eq!(state.baselines.len(), 1);
+        assert_eq!(state.baselines["10.0.0.1"].median, 20.0);
+
+        // This session: 28 ms with every 10th probe lost
+        let baselines = state.baselines.clone();
+        state
+            .statuses
+            .insert("10.0.0.1".to_string(), status(90_000, 28.0, 10));
+        let messages = compare(&state, &baselines, 90_000, 90_100);
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].starts_with("Latency to gateway is 40% higher than "));
+        assert!(messages[1].starts_with("Packet loss to gateway is 10.0%, 0.0% "));
+
+        // Older samples restored from the persisted history do not count
+        assert!(compare(&state, &baselines, 95_000, 95_100).is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+use crate::model::{AppState, PingMode};
+
+/// Probe load of the configured hosts compared with the probe budget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetWarning {
+    /// Probes per second needed to keep every host on its interval.
+    pub required: f64,
+    /// Configured budget in probes per second.
+    pub budget: f64,
+    /// Shortest interval all hosts could use while staying within the budget,
+    /// or `None` if even the slowest mode does not fit.
+    pub suggested_mode: Option<PingMode>,
+    /// Number of hosts that would have to be slowed down to `suggested_mode`.
+    pub affected_hosts: usize,
+}
+
+/// Probes per second needed by the active hosts if none pings faster than `cap`.
+///
+/// Traceroute hops are pinged in the background at the slow rate and cannot be adjusted.
+fn required_rate(state: &AppState, cap: Option<PingMode>) -> f64 {
+    let min_secs = cap.map(|c| c.interval_secs()).unwrap_or(0);
+    let hosts: f64 = state
+        .hosts
+        .iter()
+        .filter(|h| !h.is_stopped && !h.passive)
+        .map(|h| 1.0 / h.mode.interval_secs().max(min_secs) as f64)
+        .sum();
+    let hops = state
+        .statuses
+        .iter()
+        .filter(|(addr, s)| s.is_trace_hop && !state.hosts.iter().any(|h| &h.address == *addr))
+        .count();
+    hosts + hops as f64 / PingMode::Slow.interval_secs() as f64
+}
+
+/// Probes per second the active hosts and traceroute hops need on their intervals.
+pub fn probe_rate(state: &AppState) -> f64 {
+    required_rate(state, None)
+}
+
+/// Returns a warning if the hosts need more probes per second than the budget allows.
+pub fn check(state: &AppState) -> Option<BudgetWarning> {
+    let budget = state.settings.probe_budget_per_sec as f64;
+    let required = required_rate(state, None);
+    if budget <= 0.0 || required <= budget {
+        return None;
+    }
+    let suggested_mode = PingMode::ALL
+        .into_iter()
+        .find(|&mode| required_rate(state, Some(mode)) <= budget);
+    let affected_hosts = suggested_mode
+        .map(|mode| {
+            state
+                .hosts
+                .iter()
+                .filter(|h| !h.is_stopped && !h.passive)
+                .filter(|h| h.mode.interval_secs() < mode.interval_secs())
+                .count()
+        })
+        .unwrap_or(0);
+    Some(BudgetWarning {
+        required,
+        budget,
+        suggested_mode,
+        affected_hosts,
+    })
+}
+
+/// Slows down every host that pings more often than `mode`, except hosts of the shared list.
+pub fn apply_suggestion(state: &mut AppState, mode: PingMode) {
+    for h in &mut state.hosts {
+        if !h.shared && h.mode.interval_secs() < mode.interval_secs() {
+            h.mode = mode;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::HostInfo;
+
+    fn state_with_hosts(count: usize, mode: PingMode, budget: u32) -> AppState {
+        let mut state = AppState::default();
+        state.settings.probe_budget_per_sec = budget;
+        for i in 0..count {
+            state.hosts.push(HostInfo {
+                address: format!("10.0.0.{}", i),
+                mode,
+                ..Default::default()
+            });
+        }
+        state
+    }
+
+    #[test]
+    fn test_within_budget() {
+        assert_eq!(check(&state_with_hosts(10, PingMode::VeryFast, 50)), None);
+        // A budget of 0 disables the check
+        assert_eq!(check(&state_with_hosts(100, PingMode::VeryFast, 0)), None);
+    }
+
+    #[test]
+    fn test_suggests_slowest_needed_interval() {
+        let mut state = state_with_hosts(100, PingMode::VeryFast, 30);
+        state.hosts[0].mode = PingMode::Slow;
+        state.hosts[1].is_stopped = true;
+
+        let warning = check(&state).unwrap();
+        assert!((warning.required - (98.0 + 1.0 / 60.0)).abs() < 1e-9);
+        // 98 hosts at 2 s need 49/s, at 5 s 19.6/s
+        assert_eq!(warning.suggested_mode, Some(PingMode::NotFast));
+        assert_eq!(warning.affected_hosts, 98);
+
+        apply_suggestion(&mut state, PingMode::NotFast);
+        assert_eq!(state.hosts[0].mode, PingMode::Slow);
+        assert_eq!(state.hosts[2].mode, PingMode::NotFast);
+        assert_eq!(check(&state), None);
+    }
+
+    #[test]
+    fn test_no_suggestion_when_nothing_fits() {
+        let warning = check(&state_with_hosts(400, PingMode::Slow, 1)).unwrap();
+        assert_eq!(warning.suggested_mode, None);
+    }
+}
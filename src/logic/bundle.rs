@@ -0,0 +1,428 @@
+use crate::constants::{BUNDLE_APP_LOG_RECORDS, BUNDLE_EVENTS_PER_HOST};
+use crate::logic::applog;
+use crate::model::snapshot::StatsSnapshot;
+use crate::model::{AppState, HostInfo, LogEntry};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Replaces host names and addresses with stable pseudonyms.
+///
+/// The same input always maps to the same pseudonym within one anonymizer,
+/// so relations between hosts and hops stay visible in the anonymized data.
+#[derive(Default)]
+pub struct Anonymizer {
+    addresses: HashMap<String, String>,
+    groups: HashMap<String, String>,
+}
+
+impl Anonymizer {
+    /// Returns the pseudonym for an address (or hop), allocating a new one if needed.
+    pub fn address(&mut self, address: &str) -> String {
+        if address == "Local Interface" {
+            return address.to_string();
+        }
+        let next = self.addresses.len() + 1;
+        self.addresses
+            .entry(address.to_string())
+            .or_insert_with(|| format!("host-{}.invalid", next))
+            .clone()
+    }
+
+    /// Returns the pseudonym for a host's display name, derived from its address.
+    pub fn name(&mut self, address: &str) -> String {
+        let pseudo = self.address(address);
+        let n = pseudo
+            .trim_start_matches("host-")
+            .trim_end_matches(".invalid");
+        format!("Host {}", n)
+    }
+
+    /// Returns the pseudonym for a group name; the empty (ungrouped) group stays empty.
+    pub fn group(&mut self, group: &str) -> String {
+        if group.is_empty() {
+            return String::new();
+        }
+        let next = self.groups.len() + 1;
+        self.groups
+            .entry(group.to_string())
+            .or_insert_with(|| format!("Group {}", next))
+            .clone()
+    }
+
+    /// Anonymizes a host configuration, dropping fields that may reveal the network
+    /// or its secrets.
+    pub fn host(&mut self, host: &mut HostInfo) {
+        host.name = self.name(&host.address);
+        host.group = self.group(&host.group);
+        host.address = self.address(&host.address);
+        if !host.paired_with.is_empty() {
+            host.paired_with = self.address(&host.paired_with);
+        }
+        host.notes.clear();
+        host.tags.clear();
+        host.log_file_path.clear();
+        host.expected_dns_answer.clear();
+        host.udp_payload.clear();
+        host.snmp.community.clear();
+        host.clear_commands();
+    }
+
+    /// Anonymizes a log entry: hops and failure points get pseudonyms, while
+    /// free-text messages and hints, which may quote addresses, are dropped.
+    pub fn entry(&mut self, entry: &mut LogEntry) {
+        match entry {
+            LogEntry::RouteUpdate { path, .. } => {
+                *path = path.iter().map(|a| self.address(a)).collect();
+            }
+            LogEntry::Incident { node, hint, .. } => {
+                *node = node.as_deref().map(|n| self.address(n));
+                *hint = None;
+            }
+            LogEntry::Marker { message, .. } | LogEntry::Alert { message, .. } => {
+                *message = "(hidden)".to_string();
+            }
+            LogEntry::Ping { .. } | LogEntry::Statistics { .. } => {}
+        }
+    }
+
+    /// Anonymizes all hosts and hops of a statistics snapshot.
+    pub fn snapshot(&mut self, snapshot: &mut StatsSnapshot) {
+        for h in &mut snapshot.hosts {
+            h.name = self.name(&h.address);
+            h.address = self.address(&h.address);
+            h.failure_point = h.failure_point.as_deref().map(|fp| self.address(fp));
+            h.traceroute_path = h.traceroute_path.iter().map(|a| self.address(a)).collect();
+        }
+    }
+}
+
+/// Collects environment information relevant to bug reports.
+pub fn environment_info() -> String {
+    let mut lines = vec![
+        format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+        format!("OS: {} ({})", std::env::consts::OS, std::env::consts::ARCH),
+        format!(
+            "Locale: {}",
+            sys_locale::get_locale().unwrap_or_else(|| "unknown".to_string())
+        ),
+        "ICMP backend: ping-async".to_string(),
+    ];
+
+    if cfg!(target_os = "linux") {
+        // Unprivileged ICMP sockets are only allowed for groups in this range.
+        let range = std::fs::read_to_string("/proc/sys/net/ipv4/ping_group_range")
+            .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "))
+            .unwrap_or_else(|_| "unavailable".to_string());
+        lines.push(format!("ping_group_range: {}", range));
+    }
+
+    let trace_tool = if cfg!(windows) {
+        "tracert"
+    } else {
+        "traceroute"
+    };
+    lines.push(format!(
+        "{}: {}",
+        trace_tool,
+        if find_in_path(trace_tool) {
+            "found"
+        } else {
+            "not found"
+        }
+    ));
+
+    lines.join("\n")
+}
+
+/// Checks whether an executable with the given name exists in `PATH`.
+fn find_in_path(program: &str) -> bool {
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&path)
+        .any(|dir| dir.join(program).is_file() || dir.join(format!("{}.exe", program)).is_file())
+}
+
+/// Builds the list of files (name, contents) that make up a diagnostic bundle.
+pub fn build_bundle_entries(state: &AppState, anonymize: bool) -> Vec<(String, Vec<u8>)> {
+    let mut anon = Anonymizer::default();
+    let mut entries = Vec::new();
+
+    entries.push((
+        "environment.txt".to_string(),
+        environment_info().into_bytes(),
+    ));
+
+    // The messages of the application log name hosts, servers and files freely,
+    // so an anonymized bundle goes without it
+    if !anonymize {
+        let app_log: String = applog::recent(BUNDLE_APP_LOG_RECORDS)
+            .iter()
+            .filter_map(|r| serde_json::to_string(r).ok())
+            .map(|l| l + "\n")
+            .collect();
+        entries.push(("app_log.jsonl".to_string(), app_log.into_bytes()));
+    }
+
+    let hosts: Vec<HostInfo> = state
+        .hosts
+        .iter()
+        .map(|h| {
+            let mut h = h.clone();
+            if anonymize {
+                anon.host(&mut h);
+            }
+            h
+        })
+        .collect();
+    entries.push((
+        "config.json".to_string(),
+        serde_json::to_vec_pretty(&hosts).unwrap_or_default(),
+    ));
+
+    let mut snapshot = StatsSnapshot::capture(state);
+    if anonymize {
+        anon.snapshot(&mut snapshot);
+    }
+    entries.push((
+        "stats.json".to_string(),
+        serde_json::to_vec_pretty(&snapshot).unwrap_or_default(),
+    ));
+
+    for (idx, host) in state.hosts.iter().enumerate() {
+        let Some(status) = state.statuses.get(&host.address) else {
+            continue;
+        };
+        let address = if anonymize {
+            anon.address(&host.address)
+        } else {
+            host.address.clone()
+        };
+        let start = status.events.len().saturating_sub(BUNDLE_EVENTS_PER_HOST);
+        let mut text = String::new();
+        for entry in status.events.range(start..) {
+            let mut entry = entry.clone();
+            if anonymize {
+                anon.entry(&mut entry);
+            }
+            text.push_str(&entry.format(&address, Some(&host.display)));
+            text.push('\n');
+        }
+        entries.push((format!("logs/{:03}.log", idx + 1), text.into_bytes()));
+    }
+
+    entries
+}
+
+/// Creates a diagnostic bundle (ZIP archive) in `dir` and returns its path.
+pub fn create_diagnostic_bundle(
+    state: &AppState,
+    anonymize: bool,
+    dir: &Path,
+) -> std::io::Result<PathBuf> {
+    let entries = build_bundle_entries(state, anonymize);
+    let file_name = format!(
+        "egui_pinger-diagnostics-{}.zip",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let path = dir.join(file_name);
+    let file = std::fs::File::create(&path)?;
+    write_zip(std::io::BufWriter::new(file), &entries)?;
+    applog::info(
+        "bundle",
+        format!("Diagnostic bundle written to {}", path.display()),
+    );
+    Ok(path)
+}
+
+/// Writes entries as an uncompressed (stored) ZIP archive.
+///
+/// Bundles are small text files, so compression is not worth an extra dependency.
+pub fn write_zip(mut out: impl Write, entries: &[(String, Vec<u8>)]) -> std::io::Result<()> {
+    let (dos_time, dos_date) = dos_datetime(chrono::Local::now());
+    let mut central = Vec::new();
+    let mut offset: u32 = 0;
+
+    for (name, data) in entries {
+        let crc = crc32fast::hash(data);
+        let size = data.len() as u32;
+        let name_len = name.len() as u16;
+
+        let mut header = Vec::with_capacity(30 + name.len());
+        header.extend_from_slice(&0x04034b50u32.to_le_bytes());
+        header.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        header.extend_from_slice(&0u16.to_le_bytes()); // flags
+        header.extend_from_slice(&0u16.to_le_bytes()); // method: stored
+        header.extend_from_slice(&dos_time.to_le_bytes());
+        header.extend_from_slice(&dos_date.to_le_bytes());
+        header.extend_from_slice(&crc.to_le_bytes());
+        header.extend_from_slice(&size.to_le_bytes());
+        header.extend_from_slice(&size.to_le_bytes());
+        header.extend_from_slice(&name_len.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); // extra length
+        header.extend_from_slice(name.as_bytes());
+        out.write_all(&header)?;
+        out.write_all(data)?;
+
+        central.extend_from_slice(&0x02014b50u32.to_le_bytes());
+        central.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        central.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        central.extend_from_slice(&0u16.to_le_bytes()); // flags
+        central.extend_from_slice(&0u16.to_le_bytes()); // method
+        central.extend_from_slice(&dos_time.to_le_bytes());
+        central.extend_from_slice(&dos_date.to_le_bytes());
+        central.extend_from_slice(&crc.to_le_bytes());
+        central.extend_from_slice(&size.to_le_bytes());
+        central.extend_from_slice(&size.to_le_bytes());
+        central.extend_from_slice(&name_len.to_le_bytes());
+        central.extend_from_slice(&[0u8; 12]); // extra, comment, disk, attributes
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+
+        offset += header.len() as u32 + size;
+    }
+
+    out.write_all(&central)?;
+    let count = entries.len() as u16;
+    let mut end = Vec::with_capacity(22);
+    end.extend_from_slice(&0x06054b50u32.to_le_bytes());
+    end.extend_from_slice(&[0u8; 4]); // disk numbers
+    end.extend_from_slice(&count.to_le_bytes());
+    end.extend_from_slice(&count.to_le_bytes());
+    end.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    end.extend_from_slice(&offset.to_le_bytes());
+    end.extend_from_slice(&0u16.to_le_bytes()); // comment length
+    out.write_all(&end)?;
+    out.flush()
+}
+
+/// Converts a local time into MS-DOS (time, date) fields used by ZIP headers.
+fn dos_datetime(dt: chrono::DateTime<chrono::Local>) -> (u16, u16) {
+    use chrono::{Datelike, Timelike};
+    let time = ((dt.hour() << 11) | (dt.minute() << 5) | (dt.second() / 2)) as u16;
+    let year = dt.year().clamp(1980, 2107) as u32 - 1980;
+    let date = ((year << 9) | (dt.month() << 5) | dt.day()) as u16;
+    (time, date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{HostStatus, LogEntry};
+
+    fn state_with_host() -> AppState {
+        let mut state = AppState::default();
+        state.hosts.push(HostInfo {
+            name: "Office router".to_string(),
+            address: "192.168.7.1".to_string(),
+            notes: "Rack 3, office 192.168.7.0/24".to_string(),
+            tags: vec!["office-lan".to_string()],
+            on_down_command: "notify-send router-down".to_string(),
+            snmp: crate::model::SnmpSettings {
+                community: "s3cret-community".to_string(),
+                ..Default::default()
+            },
+            mode: crate::model::PingMode::Fast,
+            display: Default::default(),
+            packet_size: 16,
+            random_padding: false,
+            log_to_file: false,
+            log_file_path: "/home/user/router.log".to_string(),
+            is_stopped: false,
+            ..Default::default()
+        });
+        let mut status = HostStatus::default();
+        status.add_sample(12.0, true);
+        status.traceroute_path = vec!["10.0.0.1".to_string(), "192.168.7.1".to_string()];
+        status.events.push_back(LogEntry::Ping {
+            timestamp: 0,
+            seq: 1,
+            rtt: Some(12.0),
+            bytes: 16,
+        });
+        status.events.push_back(LogEntry::Incident {
+            timestamp: 1,
+            is_break: true,
+            streak: 3,
+            downtime_sec: None,
+            node: Some("10.0.0.1".to_string()),
+            hint: Some("Gateway 10.0.0.1 does not answer".to_string()),
+        });
+        status.events.push_back(LogEntry::Marker {
+            timestamp: 2,
+            message: "Address changed from 192.168.7.9 to 192.168.7.1".to_string(),
+        });
+        status.events.push_back(LogEntry::Alert {
+            timestamp: 3,
+            raised: true,
+            message: "Office router: loss above 5%".to_string(),
+        });
+        state.statuses.insert("192.168.7.1".to_string(), status);
+        state
+    }
+
+    #[test]
+    fn test_anonymizer_is_consistent() {
+        let mut anon = Anonymizer::default();
+        let a = anon.address("8.8.8.8");
+        let b = anon.address("1.1.1.1");
+        assert_ne!(a, b);
+        assert_eq!(anon.address("8.8.8.8"), a);
+        assert_eq!(anon.name("1.1.1.1"), "Host 2");
+        assert_eq!(anon.address("Local Interface"), "Local Interface");
+    }
+
+    #[test]
+    fn test_anonymized_bundle_hides_addresses() {
+        let state = state_with_host();
+        applog::info("test", "Cannot reach 192.168.7.1");
+        let entries = build_bundle_entries(&state, true);
+        assert!(entries.iter().all(|(name, _)| name != "app_log.jsonl"));
+        for (name, data) in &entries {
+            if name == "environment.txt" {
+                continue;
+            }
+            let text = String::from_utf8_lossy(data);
+            assert!(!text.contains("192.168.7"), "{} leaks address", name);
+            assert!(!text.contains("10.0.0.1"), "{} leaks hop", name);
+            assert!(!text.contains("Office router"), "{} leaks name", name);
+            assert!(!text.contains("router.log"), "{} leaks path", name);
+            assert!(!text.contains("office-lan"), "{} leaks tags", name);
+            assert!(!text.contains("router-down"), "{} leaks commands", name);
+            assert!(!text.contains("s3cret"), "{} leaks community", name);
+        }
+    }
+
+    #[test]
+    fn test_plain_bundle_contains_expected_files() {
+        let state = state_with_host();
+        let entries = build_bundle_entries(&state, false);
+        let names: Vec<&str> = entries.iter().map(|(n, _)| n.as_str()).collect();
+        assert!(names.contains(&"environment.txt"));
+        assert!(names.contains(&"config.json"));
+        assert!(names.contains(&"stats.json"));
+        assert!(names.contains(&"logs/001.log"));
+        let stats = &entries.iter().find(|(n, _)| n == "stats.json").unwrap().1;
+        assert!(String::from_utf8_lossy(stats).contains("192.168.7.1"));
+    }
+
+    #[test]
+    fn test_zip_layout() {
+        let entries = vec![
+            ("a.txt".to_string(), b"hello".to_vec()),
+            ("b.txt".to_string(), b"world!".to_vec()),
+        ];
+        let mut buf = Vec::new();
+        write_zip(&mut buf, &entries).unwrap();
+
+        assert_eq!(&buf[0..4], &0x04034b50u32.to_le_bytes());
+        let end = &buf[buf.len() - 22..];
+        assert_eq!(&end[0..4], &0x06054b50u32.to_le_bytes());
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 2);
+        let cd_offset = u32::from_le_bytes([end[16], end[17], end[18], end[19]]) as usize;
+        assert_eq!(&buf[cd_offset..cd_offset + 4], &0x02014b50u32.to_le_bytes());
+        // Stored data is readable right after the first local header
+        assert_eq!(&buf[30 + 5..30 + 10], b"hello");
+    }
+}
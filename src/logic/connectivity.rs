@@ -0,0 +1,161 @@
+use crate::constants::CONNECTIVITY_CHECK_INTERVAL_SEC;
+use crate::logic::{SharedState, applog, http};
+use crate::model::{Connectivity, ProxySettings};
+use std::io::Read;
+use std::time::{Duration, Instant};
+
+/// Requests the check endpoint without following redirects and classifies the answer.
+///
+/// HTTP 204 means online. A redirect, or a 200 with a page instead of the empty
+/// answer, means the request was intercepted; a redirect target is reported as
+/// the portal's login page. Other answers (e.g. an error of the endpoint) prove
+/// neither and leave the connectivity unknown.
+pub fn check_connectivity(proxy: &ProxySettings, url: &str) -> Connectivity {
+    let agent = http::builder(proxy, url).redirects(0).build();
+    match agent.get(url).call() {
+        Ok(response) if response.status() == 204 => Connectivity::Online,
+        Ok(response) if (300..400).contains(&response.status()) => Connectivity::CaptivePortal {
+            login_url: response.header("Location").map(str::to_string),
+        },
+        Ok(response) if response.status() == 200 => {
+            let mut first = [0u8; 1];
+            match response.into_reader().read(&mut first) {
+                Ok(1..) => Connectivity::CaptivePortal { login_url: None },
+                _ => Connectivity::Unknown,
+            }
+        }
+        Ok(_) | Err(ureq::Error::Status(_, _)) => Connectivity::Unknown,
+        Err(ureq::Error::Transport(_)) => Connectivity::Offline,
+    }
+}
+
+/// Starts a background captive portal check if it is enabled and due.
+///
+/// Called from the pinger loop; the blocking HTTP request runs on tokio's blocking pool.
+pub fn spawn_check_if_due(state: &SharedState, last_check: &mut Option<Instant>, now: Instant) {
+    if last_check.is_some_and(|t| {
+        now.duration_since(t) < Duration::from_secs(CONNECTIVITY_CHECK_INTERVAL_SEC)
+    }) {
+        return;
+    }
+    *last_check = Some(now);
+
+    let (enabled, proxy, url) = {
+        let mut state_lock = state
+            .lock()
+            .expect("Failed to lock state for connectivity check");
+        let settings = &state_lock.settings;
+        let params = (
+            settings.captive_portal_check,
+            settings.proxy.clone(),
+            settings.captive_portal_url.trim().to_string(),
+        );
+        if !params.0 || params.2.is_empty() {
+            state_lock.connectivity = Connectivity::Unknown;
+        }
+        params
+    };
+    if !enabled || url.is_empty() {
+        return;
+    }
+
+    let state = state.clone();
+    tokio::task::spawn_blocking(move || {
+        let result = check_connectivity(&proxy, &url);
+        let mut state_lock = state
+            .lock()
+            .expect("Failed to lock state for connectivity check");
+        if state_lock.connectivity != result {
+            match &result {
+                Connectivity::CaptivePortal { .. } => {
+                    applog::warn("connectivity", "Captive portal detected")
+                }
+                Connectivity::Offline => applog::warn("connectivity", "Connectivity check failed"),
+                Connectivity::Online => {
+                    applog::info("connectivity", "Internet connectivity confirmed")
+                }
+                Connectivity::Unknown => applog::info(
+                    "connectivity",
+                    "Unexpected answer of the connectivity check endpoint",
+                ),
+            }
+            state_lock.connectivity = result;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::net::TcpListener;
+
+    /// Serves a single canned HTTP response on a local port and returns its URL.
+    fn serve_once(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/generate_204", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        url
+    }
+
+    fn direct() -> ProxySettings {
+        ProxySettings {
+            mode: crate::model::ProxyMode::Direct,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_no_content_means_online() {
+        let url = serve_once("HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n");
+        assert_eq!(check_connectivity(&direct(), &url), Connectivity::Online);
+    }
+
+    #[test]
+    fn test_redirect_means_captive_portal() {
+        let url = serve_once(
+            "HTTP/1.1 302 Found\r\nLocation: http://portal.example/login\r\nContent-Length: 0\r\n\r\n",
+        );
+        assert_eq!(
+            check_connectivity(&direct(), &url),
+            Connectivity::CaptivePortal {
+                login_url: Some("http://portal.example/login".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn test_login_page_means_captive_portal() {
+        let url = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 13\r\n\r\n<html></html>");
+        assert_eq!(
+            check_connectivity(&direct(), &url),
+            Connectivity::CaptivePortal { login_url: None }
+        );
+    }
+
+    #[test]
+    fn test_other_answers_are_inconclusive() {
+        let url = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n");
+        assert_eq!(check_connectivity(&direct(), &url), Connectivity::Unknown);
+        let url = serve_once("HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\n\r\n");
+        assert_eq!(check_connectivity(&direct(), &url), Connectivity::Unknown);
+    }
+
+    #[test]
+    fn test_unreachable_means_offline() {
+        // Bind and drop to get a port nobody listens on
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let url = format!("http://127.0.0.1:{}/generate_204", port);
+        assert_eq!(check_connectivity(&direct(), &url), Connectivity::Offline);
+    }
+}
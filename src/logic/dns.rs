@@ -0,0 +1,375 @@
+use crate::constants::DNS_PROBE_TIMEOUT_MS;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+/// Strips the brackets of an IPv6 literal like `[::1]`.
+pub fn strip_brackets(address: &str) -> &str {
+    address
+        .strip_prefix('[')
+        .and_then(|a| a.strip_suffix(']'))
+        .unwrap_or(address)
+}
+
+/// Resolves a host name into all of its addresses using the system resolver.
+pub async fn resolve(address: &str) -> Vec<IpAddr> {
+    match tokio::net::lookup_host(format!("{}:0", address)).await {
+        Ok(addrs) => addrs.map(|a| a.ip()).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Expected DNS answer of a host: an IP address, which must be among the
+/// answers, or a regular expression that at least one of the answers must match.
+///
+/// Parsed once when the setting changes, not on every lookup.
+#[derive(Debug, Clone)]
+pub enum ExpectedAnswer {
+    Address(IpAddr),
+    Pattern(regex::Regex),
+}
+
+impl ExpectedAnswer {
+    pub fn parse(expected: &str) -> Result<Self, String> {
+        match strip_brackets(expected).parse::<IpAddr>() {
+            Ok(ip) => Ok(Self::Address(ip)),
+            Err(_) => regex::Regex::new(expected)
+                .map(Self::Pattern)
+                .map_err(|_| format!("invalid pattern {}", expected)),
+        }
+    }
+
+    /// Validates a DNS answer, returning the address to ping on success or a
+    /// description of the mismatch.
+    pub fn check(&self, answers: &[IpAddr]) -> Result<IpAddr, String> {
+        if answers.is_empty() {
+            return Err("no answer".to_string());
+        }
+        let (found, expected) = match self {
+            Self::Address(ip) => (answers.iter().find(|a| *a == ip), ip.to_string()),
+            Self::Pattern(re) => (
+                answers.iter().find(|a| re.is_match(&a.to_string())),
+                re.as_str().to_string(),
+            ),
+        };
+        found.copied().ok_or_else(|| {
+            let received: Vec<_> = answers.iter().map(|a| a.to_string()).collect();
+            format!("got {}, expected {}", received.join(", "), expected)
+        })
+    }
+}
+
+/// Target of a DNS latency probe, written as `dns://resolver/name`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DnsProbeTarget {
+    /// Resolver to query; `None` (as in `dns:///name`) uses the system resolver.
+    pub resolver: Option<SocketAddr>,
+    pub name: String,
+}
+
+/// Parses a `dns://resolver[:port]/name` address. IPv6 resolvers are written in brackets.
+pub fn parse_probe_target(address: &str) -> Option<DnsProbeTarget> {
+    let rest = address
+        .get(..6)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("dns://"))
+        .map(|_| &address[6..])?;
+    let (resolver, name) = rest.split_once('/')?;
+    let name = name.trim_end_matches('.');
+    if name.is_empty() {
+        return None;
+    }
+    let resolver = if resolver.is_empty() {
+        None
+    } else if let Ok(addr) = resolver.parse::<SocketAddr>() {
+        Some(addr)
+    } else {
+        Some(SocketAddr::new(strip_brackets(resolver).parse().ok()?, 53))
+    };
+    Some(DnsProbeTarget {
+        resolver,
+        name: name.to_string(),
+    })
+}
+
+/// DNS record types queried.
+const QTYPE_A: u8 = 1;
+const QTYPE_PTR: u8 = 12;
+
+/// Builds a recursive query for the record of type `qtype` of `name`.
+fn build_query(id: u16, name: &str, qtype: u8) -> Result<Vec<u8>, String> {
+    let mut packet = Vec::with_capacity(18 + name.len());
+    packet.extend_from_slice(&id.to_be_bytes());
+    // Flags: standard query, recursion desired; one question
+    packet.extend_from_slice(&[0x01, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0]);
+    for label in name.split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(format!("invalid name {}", name));
+        }
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    // Root label, QTYPE, QCLASS IN
+    packet.extend_from_slice(&[0, 0, qtype, 0, 1]);
+    Ok(packet)
+}
+
+/// Reads a possibly compressed name at `pos` of a DNS message, returning it and
+/// the position after it.
+fn read_name(packet: &[u8], mut pos: usize) -> Option<(String, usize)> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    // Bounds the pointers followed, against loops in malformed messages
+    for _ in 0..64 {
+        let len = *packet.get(pos)? as usize;
+        if len == 0 {
+            return Some((labels.join("."), end.unwrap_or(pos + 1)));
+        }
+        if len & 0xc0 == 0xc0 {
+            let target = ((len & 0x3f) << 8) | *packet.get(pos + 1)? as usize;
+            end.get_or_insert(pos + 2);
+            pos = target;
+            continue;
+        }
+        let label = packet.get(pos + 1..pos + 1 + len)?;
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        pos += 1 + len;
+    }
+    None
+}
+
+/// Finds the first PTR record in the answer section of a response.
+fn parse_ptr_answer(response: &[u8]) -> Option<String> {
+    let answers = u16::from_be_bytes([*response.get(6)?, *response.get(7)?]);
+    // Skip the question: name, QTYPE and QCLASS
+    let (_, mut pos) = read_name(response, 12)?;
+    pos += 4;
+    for _ in 0..answers {
+        let (_, after_name) = read_name(response, pos)?;
+        let rtype =
+            u16::from_be_bytes([*response.get(after_name)?, *response.get(after_name + 1)?]);
+        let rdlen = u16::from_be_bytes([
+            *response.get(after_name + 8)?,
+            *response.get(after_name + 9)?,
+        ]);
+        let rdata = after_name + 10;
+        if rtype == QTYPE_PTR as u16 {
+            return read_name(response, rdata).map(|(name, _)| name);
+        }
+        pos = rdata + rdlen as usize;
+    }
+    None
+}
+
+/// Name of the reverse lookup of an address, e.g. `4.3.2.1.in-addr.arpa`.
+fn reverse_name(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, d] = v4.octets();
+            format!("{}.{}.{}.{}.in-addr.arpa", d, c, b, a)
+        }
+        IpAddr::V6(v6) => {
+            let mut nibbles: Vec<String> = v6
+                .octets()
+                .iter()
+                .flat_map(|byte| [byte >> 4, byte & 0x0f])
+                .map(|n| format!("{:x}", n))
+                .collect();
+            nibbles.reverse();
+            format!("{}.ip6.arpa", nibbles.join("."))
+        }
+    }
+}
+
+/// Reads the `nameserver` addresses of a resolv.conf.
+pub fn parse_resolv_conf(text: &str) -> Vec<IpAddr> {
+    text.lines()
+        .filter_map(|line| line.trim().strip_prefix("nameserver"))
+        .filter_map(|rest| rest.trim().split('%').next()?.parse::<IpAddr>().ok())
+        .collect()
+}
+
+/// The resolver the system uses, from `/etc/resolv.conf`; `None` where there is
+/// no such file (Windows).
+pub fn system_nameserver() -> Option<SocketAddr> {
+    let text = std::fs::read_to_string("/etc/resolv.conf").ok()?;
+    let ip = *parse_resolv_conf(&text).first()?;
+    Some(SocketAddr::new(ip, 53))
+}
+
+/// Looks up the name of an address (its PTR record) at `resolver`.
+pub async fn reverse_lookup(ip: IpAddr, resolver: SocketAddr) -> Option<String> {
+    let id: u16 = rand::random();
+    let query = build_query(id, &reverse_name(ip), QTYPE_PTR).ok()?;
+    let (response, _) = exchange(resolver, id, &query).await.ok()?;
+    check_response(id, &response).ok()?;
+    parse_ptr_answer(&response)
+        .map(|name| name.trim_end_matches('.').to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// Sends a query to `resolver` and waits for the answer with the same id,
+/// returning it and the RTT in ms.
+async fn exchange(resolver: SocketAddr, id: u16, query: &[u8]) -> Result<(Vec<u8>, f64), String> {
+    let timeout = Duration::from_millis(DNS_PROBE_TIMEOUT_MS);
+    let bind: SocketAddr = if resolver.is_ipv4() {
+        "0.0.0.0:0".parse().expect("valid address")
+    } else {
+        "[::]:0".parse().expect("valid address")
+    };
+    let socket = tokio::net::UdpSocket::bind(bind)
+        .await
+        .map_err(|e| e.to_string())?;
+    socket.connect(resolver).await.map_err(|e| e.to_string())?;
+
+    let start = Instant::now();
+    socket.send(query).await.map_err(|e| e.to_string())?;
+    let mut buf = [0u8; 1500];
+    loop {
+        let remaining = timeout.saturating_sub(start.elapsed());
+        let len = tokio::time::timeout(remaining, socket.recv(&mut buf))
+            .await
+            .map_err(|_| "timeout".to_string())?
+            .map_err(|e| e.to_string())?;
+        // Ignore stray datagrams, e.g. late answers to an earlier query
+        if len >= 2 && buf[..2] == id.to_be_bytes() {
+            let rtt = start.elapsed().as_secs_f64() * 1000.0;
+            return Ok((buf[..len].to_vec(), rtt));
+        }
+    }
+}
+
+/// Checks that `response` answers the query `id` successfully.
+fn check_response(id: u16, response: &[u8]) -> Result<(), String> {
+    if response.len() < 12 || response[..2] != id.to_be_bytes() || response[2] & 0x80 == 0 {
+        return Err("malformed response".to_string());
+    }
+    match response[3] & 0x0f {
+        0 => Ok(()),
+        2 => Err("SERVFAIL".to_string()),
+        3 => Err("NXDOMAIN".to_string()),
+        5 => Err("REFUSED".to_string()),
+        rcode => Err(format!("RCODE {}", rcode)),
+    }
+}
+
+/// Measures how long the resolver takes to answer a lookup of the target name, in ms.
+///
+/// Without an explicit resolver the system resolver is timed, which may answer
+/// from a local cache.
+pub async fn probe(target: &DnsProbeTarget) -> Result<f64, String> {
+    let timeout = Duration::from_millis(DNS_PROBE_TIMEOUT_MS);
+    let Some(resolver) = target.resolver else {
+        let start = Instant::now();
+        let answers = tokio::time::timeout(timeout, resolve(&target.name))
+            .await
+            .map_err(|_| "timeout".to_string())?;
+        if answers.is_empty() {
+            return Err("no answer".to_string());
+        }
+        return Ok(start.elapsed().as_secs_f64() * 1000.0);
+    };
+
+    let id: u16 = rand::random();
+    let query = build_query(id, &target.name, QTYPE_A)?;
+    let (response, rtt) = exchange(resolver, id, &query).await?;
+    check_response(id, &response).map(|_| rtt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ips(list: &[&str]) -> Vec<IpAddr> {
+        list.iter().map(|s| s.parse().unwrap()).collect()
+    }
+
+    fn check_dns_answer(expected: &str, answers: &[IpAddr]) -> Result<IpAddr, String> {
+        ExpectedAnswer::parse(expected)?.check(answers)
+    }
+
+    #[test]
+    fn test_expected_ip() {
+        let answers = ips(&["93.184.216.34", "2606:2800:220:1::248"]);
+        assert_eq!(
+            check_dns_answer("2606:2800:220:1::248", &answers),
+            Ok(answers[1])
+        );
+        assert!(check_dns_answer("10.0.0.1", &answers).is_err());
+        assert!(check_dns_answer("10.0.0.1", &[]).is_err());
+    }
+
+    #[test]
+    fn test_expected_regex() {
+        let answers = ips(&["192.168.1.1", "10.1.2.3"]);
+        assert_eq!(check_dns_answer(r"^10\.", &answers), Ok(answers[1]));
+        let err = check_dns_answer(r"^172\.16\.", &answers).unwrap_err();
+        assert!(err.contains("192.168.1.1, 10.1.2.3"));
+        assert!(check_dns_answer("(", &answers).is_err());
+    }
+
+    #[test]
+    fn test_parse_probe_target() {
+        assert_eq!(
+            parse_probe_target("dns://1.1.1.1/example.com."),
+            Some(DnsProbeTarget {
+                resolver: Some("1.1.1.1:53".parse().unwrap()),
+                name: "example.com".to_string()
+            })
+        );
+        assert_eq!(
+            parse_probe_target("DNS://[::1]:5353/example.com")
+                .unwrap()
+                .resolver,
+            Some("[::1]:5353".parse().unwrap())
+        );
+        assert_eq!(
+            parse_probe_target("dns:///example.com").unwrap().resolver,
+            None
+        );
+        assert_eq!(parse_probe_target("dns://1.1.1.1/"), None);
+        assert_eq!(parse_probe_target("example.com"), None);
+    }
+
+    #[test]
+    fn test_reverse_names_and_ptr_answer() {
+        assert_eq!(
+            reverse_name("192.168.1.23".parse().unwrap()),
+            "23.1.168.192.in-addr.arpa"
+        );
+        assert!(
+            reverse_name("2001:db8::1".parse().unwrap())
+                .starts_with("1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.")
+        );
+
+        let mut response = build_query(7, "23.1.168.192.in-addr.arpa", QTYPE_PTR).unwrap();
+        response[7] = 1;
+        // Answer: pointer to the question name, PTR, IN, TTL, then "nas" + pointer to "lan"
+        response.extend_from_slice(&[0xc0, 12, 0, 12, 0, 1, 0, 0, 0, 60, 0, 9]);
+        response.extend_from_slice(&[3, b'n', b'a', b's', 3, b'l', b'a', b'n', 0]);
+        assert_eq!(parse_ptr_answer(&response), Some("nas.lan".to_string()));
+        assert_eq!(
+            parse_resolv_conf("# generated\nsearch lan\nnameserver 127.0.0.53\n"),
+            ips(&["127.0.0.53"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_probe_local_resolver() {
+        let server = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let target = DnsProbeTarget {
+            resolver: Some(server.local_addr().unwrap()),
+            name: "example.com".to_string(),
+        };
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            for rcode in [0u8, 3] {
+                let (len, peer) = server.recv_from(&mut buf).await.unwrap();
+                let mut answer = buf[..len].to_vec();
+                answer[2] |= 0x80;
+                answer[3] = 0x80 | rcode;
+                server.send_to(&answer, peer).await.unwrap();
+            }
+        });
+        assert!(probe(&target).await.unwrap() >= 0.0);
+        assert_eq!(probe(&target).await, Err("NXDOMAIN".to_string()));
+    }
+}
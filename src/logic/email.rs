@@ -0,0 +1,492 @@
+//! Alert emails over SMTP for hosts that stay down, with a chart of their recent history.
+
+use crate::constants::SMTP_TIMEOUT_SEC;
+use crate::logic::{applog, keyring, presenter};
+use crate::model::{EmailSettings, SmtpSecurity};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+use tr::tr;
+
+/// A host that has been down for longer than the configured time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DownReport {
+    pub host: String,
+    pub address: String,
+    /// Unix timestamp when the incident opened.
+    pub down_since: u64,
+    /// Unix timestamp of the report.
+    pub timestamp: u64,
+    pub loss_pct: f64,
+    /// Mean RTT in ms.
+    pub rtt_ms: f64,
+    /// Recent RTTs in ms, oldest first; `None` for lost probes.
+    pub samples: Vec<Option<f64>>,
+}
+
+impl DownReport {
+    pub fn subject(&self) -> String {
+        tr!("[egui_pinger] {host} is down for {duration}")
+            .replace("{host}", &self.host)
+            .replace("{duration}", &self.downtime())
+    }
+
+    fn downtime(&self) -> String {
+        presenter::format_duration(self.timestamp.saturating_sub(self.down_since))
+    }
+
+    fn facts(&self) -> Vec<(String, String)> {
+        let since = chrono::DateTime::from_timestamp(self.down_since as i64, 0)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        vec![
+            (tr!("Host"), format!("{} ({})", self.host, self.address)),
+            (tr!("Down since"), since),
+            (tr!("Downtime"), self.downtime()),
+            (tr!("Loss"), format!("{:.1} %", self.loss_pct)),
+            (tr!("Mean RTT"), format!("{:.1} ms", self.rtt_ms)),
+        ]
+    }
+
+    /// Plain-text body with an ASCII chart.
+    pub fn text_body(&self) -> String {
+        let mut body = String::new();
+        for (label, value) in self.facts() {
+            body.push_str(&format!("{}: {}\n", label, value));
+        }
+        body.push('\n');
+        body.push_str(&ascii_chart(&self.samples));
+        body
+    }
+
+    /// HTML body with a bar chart made of table cells.
+    pub fn html_body(&self) -> String {
+        let mut rows = String::new();
+        for (label, value) in self.facts() {
+            rows.push_str(&format!(
+                "<tr><td><b>{}</b></td><td>{}</td></tr>",
+                html_escape(&label),
+                html_escape(&value)
+            ));
+        }
+        format!(
+            "<html><body><table>{}</table><br>{}</body></html>",
+            rows,
+            html_chart(&self.samples)
+        )
+    }
+}
+
+const CHART_ROWS: usize = 6;
+
+/// Bar chart of the samples in `CHART_ROWS` lines of text; lost probes are marked `x`.
+pub fn ascii_chart(samples: &[Option<f64>]) -> String {
+    let max = samples.iter().flatten().fold(0.0_f64, |a, &b| a.max(b));
+    let mut chart = String::new();
+    for row in (0..CHART_ROWS).rev() {
+        let label = if row == CHART_ROWS - 1 {
+            format!("{:>7.1} ms |", max)
+        } else {
+            format!("{:>10} |", "")
+        };
+        chart.push_str(&label);
+        for sample in samples {
+            let c = match sample {
+                None if row == 0 => 'x',
+                None => ' ',
+                Some(rtt) if max > 0.0 && rtt / max * CHART_ROWS as f64 > row as f64 => '#',
+                Some(_) if row == 0 => '_',
+                Some(_) => ' ',
+            };
+            chart.push(c);
+        }
+        chart.push('\n');
+    }
+    chart.push_str(&format!("{:>10} +{}\n", "", "-".repeat(samples.len())));
+    chart
+}
+
+/// Bar chart of the samples as table cells; lost probes are full-height red bars.
+pub fn html_chart(samples: &[Option<f64>]) -> String {
+    let max = samples.iter().flatten().fold(0.0_f64, |a, &b| a.max(b));
+    let height = 60.0;
+    let mut cells = String::new();
+    for sample in samples {
+        let (bar, color) = match sample {
+            None => (height, "#d9534f"),
+            Some(rtt) if max > 0.0 => ((rtt / max * height).max(1.0), "#5cb85c"),
+            Some(_) => (1.0, "#5cb85c"),
+        };
+        cells.push_str(&format!(
+            "<td style=\"vertical-align:bottom;padding:0 1px 0 0\">\
+             <div style=\"width:4px;height:{:.0}px;background:{}\"></div></td>",
+            bar, color
+        ));
+    }
+    format!(
+        "<table cellspacing=\"0\" cellpadding=\"0\" style=\"height:{:.0}px\"><tr>{}</tr></table>\
+         <small>{}: {:.1} ms</small>",
+        height,
+        cells,
+        html_escape(&tr!("Highest RTT")),
+        max
+    )
+}
+
+/// Escapes text for HTML element content and attribute values.
+pub fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Encodes a header value as RFC 2047 if it is not plain ASCII.
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        value.to_string()
+    } else {
+        format!("=?UTF-8?B?{}?=", BASE64.encode(value))
+    }
+}
+
+/// Base64 in lines of 76 characters.
+fn base64_lines(data: &str) -> String {
+    let encoded = BASE64.encode(data);
+    let mut lines = String::new();
+    for chunk in encoded.as_bytes().chunks(76) {
+        lines.push_str(std::str::from_utf8(chunk).unwrap_or_default());
+        lines.push_str("\r\n");
+    }
+    lines
+}
+
+/// A MIME message with a plain-text and an HTML alternative.
+pub fn compose(settings: &EmailSettings, report: &DownReport) -> String {
+    let boundary = format!("egui_pinger_{:016x}", rand::random::<u64>());
+    let date = chrono::DateTime::from_timestamp(report.timestamp as i64, 0)
+        .unwrap_or_default()
+        .to_rfc2822();
+    let mut message = String::new();
+    message.push_str(&format!("From: {}\r\n", settings.sender()));
+    message.push_str(&format!("To: {}\r\n", settings.recipient_list().join(", ")));
+    message.push_str(&format!(
+        "Subject: {}\r\n",
+        encode_header(&report.subject())
+    ));
+    message.push_str(&format!("Date: {}\r\n", date));
+    message.push_str("MIME-Version: 1.0\r\n");
+    message.push_str(&format!(
+        "Content-Type: multipart/alternative; boundary=\"{}\"\r\n\r\n",
+        boundary
+    ));
+    for (mime, body) in [
+        ("text/plain", report.text_body()),
+        ("text/html", report.html_body()),
+    ] {
+        message.push_str(&format!("--{}\r\n", boundary));
+        message.push_str(&format!("Content-Type: {}; charset=utf-8\r\n", mime));
+        message.push_str("Content-Transfer-Encoding: base64\r\n\r\n");
+        message.push_str(&base64_lines(&body));
+    }
+    message.push_str(&format!("--{}--\r\n", boundary));
+    message
+}
+
+/// A conversation with an SMTP server over a plain or TLS stream.
+struct Smtp<S: Read + Write> {
+    io: BufReader<S>,
+}
+
+impl<S: Read + Write> Smtp<S> {
+    fn new(stream: S) -> Self {
+        Self {
+            io: BufReader::new(stream),
+        }
+    }
+
+    /// Reads a (multi-line) reply and checks its code.
+    fn expect(&mut self, codes: &[u16]) -> Result<String, String> {
+        let mut text = String::new();
+        loop {
+            let mut line = String::new();
+            if self.io.read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+                return Err("connection closed by the server".to_string());
+            }
+            let line = line.trim_end();
+            let code: u16 = line.get(..3).and_then(|c| c.parse().ok()).unwrap_or(0);
+            text.push_str(line.get(4..).unwrap_or_default());
+            text.push('\n');
+            if line.as_bytes().get(3) != Some(&b'-') {
+                return if codes.contains(&code) {
+                    Ok(text)
+                } else {
+                    Err(line.to_string())
+                };
+            }
+        }
+    }
+
+    fn command(&mut self, line: &str, codes: &[u16]) -> Result<String, String> {
+        let stream = self.io.get_mut();
+        stream
+            .write_all(format!("{}\r\n", line).as_bytes())
+            .and_then(|_| stream.flush())
+            .map_err(|e| e.to_string())?;
+        self.expect(codes)
+    }
+
+    fn ehlo(&mut self) -> Result<String, String> {
+        self.command("EHLO localhost", &[250])
+    }
+
+    /// Authenticates if a login name is set and transfers the message.
+    fn deliver(
+        &mut self,
+        settings: &EmailSettings,
+        password: Option<&str>,
+        message: &str,
+    ) -> Result<(), String> {
+        let username = settings.username.trim();
+        if !username.is_empty() {
+            let credentials = format!("\0{}\0{}", username, password.unwrap_or_default());
+            self.command(
+                &format!("AUTH PLAIN {}", BASE64.encode(credentials)),
+                &[235],
+            )?;
+        }
+        self.command(&format!("MAIL FROM:<{}>", settings.sender()), &[250])?;
+        for recipient in settings.recipient_list() {
+            self.command(&format!("RCPT TO:<{}>", recipient), &[250, 251])?;
+        }
+        self.command("DATA", &[354])?;
+        // Lines starting with a dot are doubled (RFC 5321, 4.5.2)
+        let mut data = message.replace("\r\n.", "\r\n..");
+        if data.starts_with('.') {
+            data.insert(0, '.');
+        }
+        data.push('.');
+        self.command(&data, &[250])?;
+        let _ = self.command("QUIT", &[221]);
+        Ok(())
+    }
+
+    fn into_inner(self) -> S {
+        self.io.into_inner()
+    }
+}
+
+fn connect(server: &str, port: u16) -> Result<TcpStream, String> {
+    let timeout = Duration::from_secs(SMTP_TIMEOUT_SEC);
+    let mut last_error = format!("cannot resolve {}", server);
+    for addr in (server, port)
+        .to_socket_addrs()
+        .map_err(|e| e.to_string())?
+    {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => {
+                stream
+                    .set_read_timeout(Some(timeout))
+                    .and_then(|_| stream.set_write_timeout(Some(timeout)))
+                    .map_err(|e| e.to_string())?;
+                return Ok(stream);
+            }
+            Err(e) => last_error = e.to_string(),
+        }
+    }
+    Err(last_error)
+}
+
+/// Wraps `stream` in TLS, verifying the certificate of `server` against the
+/// public certificate authorities.
+pub(crate) fn tls(
+    server: &str,
+    stream: TcpStream,
+) -> Result<rustls::StreamOwned<rustls::ClientConnection, TcpStream>, String> {
+    let roots = rustls::RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .map_err(|e| e.to_string())?
+    .with_root_certificates(roots)
+    .with_no_client_auth();
+    let name =
+        rustls::pki_types::ServerName::try_from(server.to_string()).map_err(|e| e.to_string())?;
+    let connection =
+        rustls::ClientConnection::new(Arc::new(config), name).map_err(|e| e.to_string())?;
+    Ok(rustls::StreamOwned::new(connection, stream))
+}
+
+/// Sends a composed message to the recipients of `settings`.
+pub fn send(settings: &EmailSettings, password: Option<&str>, message: &str) -> Result<(), String> {
+    if settings.recipient_list().is_empty() {
+        return Err(tr!("No recipients configured"));
+    }
+    if settings.security == SmtpSecurity::None && !settings.username.trim().is_empty() {
+        return Err(tr!(
+            "The password is not sent over an unencrypted connection"
+        ));
+    }
+    let server = settings.server.trim();
+    let stream = connect(server, settings.port)?;
+    match settings.security {
+        SmtpSecurity::Tls => {
+            let mut smtp = Smtp::new(tls(server, stream)?);
+            smtp.expect(&[220])?;
+            smtp.ehlo()?;
+            smtp.deliver(settings, password, message)
+        }
+        SmtpSecurity::StartTls => {
+            let mut smtp = Smtp::new(stream);
+            smtp.expect(&[220])?;
+            smtp.ehlo()?;
+            smtp.command("STARTTLS", &[220])?;
+            let mut smtp = Smtp::new(tls(server, smtp.into_inner())?);
+            smtp.ehlo()?;
+            smtp.deliver(settings, password, message)
+        }
+        SmtpSecurity::None => {
+            let mut smtp = Smtp::new(stream);
+            smtp.expect(&[220])?;
+            smtp.ehlo()?;
+            smtp.deliver(settings, password, message)
+        }
+    }
+}
+
+/// Sends the report on a background thread, with the password from the keyring.
+pub fn spawn(settings: EmailSettings, report: DownReport) {
+    std::thread::spawn(move || {
+        let password = if settings.username.trim().is_empty() {
+            Ok(None)
+        } else {
+            keyring::load(&settings.keyring_account())
+        };
+        let result = password.and_then(|password| {
+            send(&settings, password.as_deref(), &compose(&settings, &report))
+        });
+        match result {
+            Ok(()) => applog::info(
+                "email",
+                format!("Sent \"{}\" to {}", report.subject(), settings.recipients),
+            ),
+            Err(e) => applog::warn("email", format!("Failed to send email: {}", e)),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn report() -> DownReport {
+        DownReport {
+            host: "Gateway".to_string(),
+            address: "192.168.1.1".to_string(),
+            down_since: 1_700_000_000,
+            timestamp: 1_700_000_000 + 330,
+            loss_pct: 25.0,
+            rtt_ms: 10.0,
+            samples: vec![Some(10.0), Some(20.0), None, Some(5.0)],
+        }
+    }
+
+    #[test]
+    fn test_ascii_chart() {
+        let chart = ascii_chart(&report().samples);
+        let lines: Vec<&str> = chart.lines().collect();
+        assert_eq!(lines.len(), CHART_ROWS + 1);
+        assert_eq!(lines[0], "   20.0 ms | #  ");
+        assert_eq!(lines[CHART_ROWS - 1], "           |##x#");
+        assert!(report().subject().ends_with("Gateway is down for 5m 30s"));
+    }
+
+    /// A scripted server: replies to read, and everything the client wrote.
+    struct Script {
+        replies: Cursor<Vec<u8>>,
+        written: Vec<u8>,
+    }
+
+    impl Read for Script {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.replies.read(buf)
+        }
+    }
+
+    impl Write for Script {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.written.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_smtp_conversation() {
+        let replies = "220 mail.example ESMTP\r\n250-mail.example\r\n250 AUTH PLAIN\r\n\
+                       235 ok\r\n250 ok\r\n250 ok\r\n251 forwarded\r\n354 go\r\n250 queued\r\n221 bye\r\n";
+        let mut smtp = Smtp::new(Script {
+            replies: Cursor::new(replies.as_bytes().to_vec()),
+            written: Vec::new(),
+        });
+        let settings = EmailSettings {
+            username: "me@example.com".to_string(),
+            recipients: "a@example.com; b@example.com".to_string(),
+            ..Default::default()
+        };
+        smtp.expect(&[220]).unwrap();
+        assert!(smtp.ehlo().unwrap().contains("AUTH PLAIN"));
+        smtp.deliver(&settings, Some("secret"), "Subject: x\r\n\r\n.hidden\r\n")
+            .unwrap();
+
+        let written = String::from_utf8(smtp.into_inner().written).unwrap();
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(
+            lines[1],
+            format!("AUTH PLAIN {}", BASE64.encode("\0me@example.com\0secret"))
+        );
+        assert_eq!(lines[2], "MAIL FROM:<me@example.com>");
+        assert_eq!(lines[4], "RCPT TO:<b@example.com>");
+        assert_eq!(&lines[6..], ["Subject: x", "", "..hidden", ".", "QUIT"]);
+    }
+
+    #[test]
+    fn test_no_login_without_encryption() {
+        let settings = EmailSettings {
+            server: "192.0.2.1".to_string(),
+            security: SmtpSecurity::None,
+            username: "me@example.com".to_string(),
+            recipients: "a@example.com".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            send(&settings, Some("secret"), "Subject: x\r\n\r\n"),
+            Err(tr!(
+                "The password is not sent over an unencrypted connection"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_smtp_rejection() {
+        let mut smtp = Smtp::new(Script {
+            replies: Cursor::new(b"554 no service\r\n".to_vec()),
+            written: Vec::new(),
+        });
+        assert_eq!(smtp.expect(&[220]), Err("554 no service".to_string()));
+    }
+}
@@ -0,0 +1,89 @@
+#[cfg(windows)]
+use crate::constants::CREATE_NO_WINDOW;
+use crate::constants::EXEC_PROBE_TIMEOUT_SEC;
+use std::time::{Duration, Instant};
+
+/// Extracts the latency reported by a probe command: the first number on stdout.
+pub fn parse_latency(stdout: &str) -> Option<f64> {
+    stdout
+        .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .filter(|t| t.chars().any(|c| c.is_ascii_digit()))
+        .find_map(|t| t.parse::<f64>().ok())
+}
+
+/// Runs a command through the system shell with the variables `env`, killing it
+/// after `timeout`.
+pub(crate) async fn run_shell(
+    command: &str,
+    env: &[(&str, String)],
+    timeout: Duration,
+) -> Result<std::process::Output, String> {
+    #[cfg(windows)]
+    let mut cmd = {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.arg("/C").arg(command).creation_flags(CREATE_NO_WINDOW);
+        cmd
+    };
+    #[cfg(not(windows))]
+    let mut cmd = {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    cmd.envs(env.iter().map(|(k, v)| (k, v)))
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true);
+
+    tokio::time::timeout(timeout, cmd.output())
+        .await
+        .map_err(|_| "timeout".to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Runs a probe command through the system shell.
+///
+/// Exit code 0 means the host is up. The first number printed on stdout is the
+/// latency in ms; without one, the run time of the command is used. The host
+/// address is passed in the `EGUI_PINGER_ADDRESS` environment variable.
+pub async fn probe(command: &str, address: &str) -> Result<f64, String> {
+    let start = Instant::now();
+    let output = run_shell(
+        command,
+        &[("EGUI_PINGER_ADDRESS", address.to_string())],
+        Duration::from_secs(EXEC_PROBE_TIMEOUT_SEC),
+    )
+    .await?;
+    let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+
+    if !output.status.success() {
+        return Err(match output.status.code() {
+            Some(code) => format!("exit code {}", code),
+            None => "terminated by signal".to_string(),
+        });
+    }
+    Ok(parse_latency(&String::from_utf8_lossy(&output.stdout)).unwrap_or(elapsed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_latency() {
+        assert_eq!(parse_latency("12.5\n"), Some(12.5));
+        assert_eq!(parse_latency("OK latency=7 ms, 3 rows"), Some(7.0));
+        assert_eq!(parse_latency("OK."), None);
+        assert_eq!(parse_latency(""), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_probe_exit_code_and_output() {
+        assert_eq!(probe("echo 42.5", "h").await, Ok(42.5));
+        assert_eq!(
+            probe("test \"$EGUI_PINGER_ADDRESS\" = db1 && echo 3", "db1").await,
+            Ok(3.0)
+        );
+        assert_eq!(probe("exit 2", "h").await, Err("exit code 2".to_string()));
+    }
+}
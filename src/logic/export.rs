@@ -0,0 +1,302 @@
+use crate::logic::bundle::Anonymizer;
+use crate::logic::{applog, trace_context};
+use crate::model::snapshot::StatsSnapshot;
+use crate::model::{AppState, LogEntry, OutageLog};
+use std::path::{Path, PathBuf};
+
+/// Quotes a CSV field if it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Per-host statistics as CSV, one row per host.
+pub fn stats_csv(snapshot: &StatsSnapshot) -> String {
+    let mut out = String::from(
+        "name,address,alive,sent,lost,loss_pct,latency_ms,mean_ms,median_ms,p95_ms,jitter_ms,mos,availability_pct\n",
+    );
+    for h in &snapshot.hosts {
+        let loss = if h.sent == 0 {
+            0.0
+        } else {
+            h.lost as f64 / h.sent as f64 * 100.0
+        };
+        out.push_str(&format!(
+            "{},{},{},{},{},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2}\n",
+            csv_field(&h.name),
+            csv_field(&h.address),
+            h.alive,
+            h.sent,
+            h.lost,
+            loss,
+            h.latency,
+            h.mean,
+            h.median,
+            h.p95,
+            h.rtp_jitter,
+            h.mos,
+            h.availability,
+        ));
+    }
+    out
+}
+
+/// Raw ping results from the in-memory event logs as CSV; lost packets have an empty RTT.
+///
+/// With correlation tagging, a `traceparent` column holds the tag of each probe; it is
+/// left out of anonymized exports since the tag is derived from the address.
+pub fn pings_csv(state: &AppState, mut anon: Option<&mut Anonymizer>) -> String {
+    let trace_id = state
+        .settings
+        .correlation
+        .active_trace_id()
+        .filter(|_| anon.is_none());
+    let mut out = String::from(if trace_id.is_some() {
+        "address,timestamp,seq,rtt_ms,traceparent\n"
+    } else {
+        "address,timestamp,seq,rtt_ms\n"
+    });
+    for host in &state.hosts {
+        let Some(status) = state.statuses.get(&host.address) else {
+            continue;
+        };
+        let address = match anon.as_deref_mut() {
+            Some(a) => a.address(&host.address),
+            None => host.address.clone(),
+        };
+        let field = csv_field(&address);
+        for entry in &status.events {
+            if let LogEntry::Ping {
+                timestamp,
+                seq,
+                rtt,
+                ..
+            } = entry
+            {
+                let rtt = rtt.map(|r| format!("{:.3}", r)).unwrap_or_default();
+                let line = format!("{},{},{},{}", field, timestamp, seq, rtt);
+                out.push_str(&match trace_id {
+                    Some(id) => format!(
+                        "{},{}\n",
+                        line,
+                        trace_context::traceparent(id, &address, *timestamp)
+                    ),
+                    None => format!("{}\n", line),
+                });
+            }
+        }
+    }
+    out
+}
+
+/// Formats a Unix timestamp as RFC 3339 in local time.
+fn local_time(ts: u64) -> String {
+    chrono::DateTime::from_timestamp(ts as i64, 0)
+        .map(|t| t.with_timezone(&chrono::Local).to_rfc3339())
+        .unwrap_or_default()
+}
+
+/// Finished outages of one host as CSV rows, without a header.
+fn outage_rows(out: &mut String, address: &str, log: &OutageLog) {
+    let field = csv_field(address);
+    for event in log.finished() {
+        out.push_str(&format!(
+            "{},{},{},{},{}\n",
+            field,
+            local_time(event.start),
+            local_time(event.end),
+            event.duration(),
+            event.lost
+        ));
+    }
+}
+
+const OUTAGES_HEADER: &str = "address,start,end,duration_sec,packets_lost\n";
+
+/// Finished outages of one host as CSV.
+pub fn host_outages_csv(address: &str, log: &OutageLog) -> String {
+    let mut out = String::from(OUTAGES_HEADER);
+    outage_rows(&mut out, address, log);
+    out
+}
+
+/// Finished outages of all hosts as CSV.
+pub fn outages_csv(state: &AppState, mut anon: Option<&mut Anonymizer>) -> String {
+    let mut out = String::from(OUTAGES_HEADER);
+    for host in &state.hosts {
+        let Some(status) = state.statuses.get(&host.address) else {
+            continue;
+        };
+        let address = match anon.as_deref_mut() {
+            Some(a) => a.address(&host.address),
+            None => host.address.clone(),
+        };
+        outage_rows(&mut out, &address, &status.outages);
+    }
+    out
+}
+
+/// Notes on the timeline as CSV.
+pub fn annotations_csv(state: &AppState) -> String {
+    let mut out = String::from("timestamp,time,text\n");
+    for note in &state.annotations {
+        out.push_str(&format!(
+            "{},{},{}\n",
+            note.timestamp,
+            local_time(note.timestamp),
+            csv_field(&note.text)
+        ));
+    }
+    out
+}
+
+/// Builds the export files (name, content); pseudonyms are shared across all files.
+///
+/// Annotations are free text that may name hosts, so anonymized exports leave them out.
+pub fn build_export_files(state: &AppState, anonymize: bool) -> Vec<(String, String)> {
+    let mut anon = Anonymizer::default();
+    let mut snapshot = StatsSnapshot::capture(state);
+    if anonymize {
+        anon.snapshot(&mut snapshot);
+    }
+    let mut files = vec![
+        ("stats.csv".to_string(), stats_csv(&snapshot)),
+        (
+            "stats.json".to_string(),
+            serde_json::to_string_pretty(&snapshot).unwrap_or_default(),
+        ),
+        (
+            "pings.csv".to_string(),
+            pings_csv(state, anonymize.then_some(&mut anon)),
+        ),
+        (
+            "outages.csv".to_string(),
+            outages_csv(state, anonymize.then_some(&mut anon)),
+        ),
+    ];
+    if !anonymize {
+        files.push(("annotations.csv".to_string(), annotations_csv(state)));
+    }
+    files
+}
+
+/// Writes the statistics export into a new timestamped directory under `dir`.
+pub fn export_statistics(
+    state: &AppState,
+    anonymize: bool,
+    dir: &Path,
+) -> std::io::Result<PathBuf> {
+    let path = dir.join(format!(
+        "egui_pinger-export-{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::create_dir_all(&path)?;
+    for (name, content) in build_export_files(state, anonymize) {
+        std::fs::write(path.join(name), content)?;
+    }
+    applog::info(
+        "export",
+        format!("Statistics exported to {}", path.display()),
+    );
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{HostInfo, HostStatus};
+
+    fn state_with_hosts() -> AppState {
+        let mut state = AppState::default();
+        for (name, address) in [("Office, router", "192.168.7.1"), ("VPN", "10.8.0.1")] {
+            state.hosts.push(HostInfo {
+                name: name.to_string(),
+                address: address.to_string(),
+                ..Default::default()
+            });
+            let mut status = HostStatus::default();
+            status.add_sample(12.0, true);
+            status.traceroute_path = vec!["10.8.0.1".to_string(), address.to_string()];
+            status.events.push_back(LogEntry::Ping {
+                timestamp: 100,
+                seq: 1,
+                rtt: Some(12.0),
+                bytes: 16,
+            });
+            status.events.push_back(LogEntry::Ping {
+                timestamp: 101,
+                seq: 2,
+                rtt: None,
+                bytes: 16,
+            });
+            state.statuses.insert(address.to_string(), status);
+        }
+        let vpn = state.statuses.get_mut("10.8.0.1").unwrap();
+        vpn.outages.begin(1_000, 3);
+        vpn.outages.add_lost();
+        vpn.outages.end(1_030);
+        state.annotate(1_010, "Rebooted router, then modem");
+        state
+    }
+
+    #[test]
+    fn test_plain_export_quotes_fields() {
+        let files = build_export_files(&state_with_hosts(), false);
+        let stats = &files[0].1;
+        assert!(stats.contains("\"Office, router\",192.168.7.1,"));
+        let pings = &files[2].1;
+        assert!(pings.contains("192.168.7.1,100,1,12.000\n"));
+        assert!(pings.contains("192.168.7.1,101,2,\n"));
+        let outages = &files[3].1;
+        assert_eq!(outages.lines().count(), 2);
+        assert!(outages.lines().nth(1).unwrap().starts_with("10.8.0.1,"));
+        assert!(outages.ends_with(",30,4\n"));
+        assert!(files[4].1.ends_with("\"Rebooted router, then modem\"\n"));
+    }
+
+    #[test]
+    fn test_export_carries_trace_context() {
+        let mut state = state_with_hosts();
+        state.settings.correlation.enabled = true;
+        state.settings.correlation.trace_id = trace_context::new_trace_id();
+        let tag = trace_context::traceparent(&state.settings.correlation.trace_id, "10.8.0.1", 100);
+
+        let files = build_export_files(&state, false);
+        assert!(
+            files[2]
+                .1
+                .starts_with("address,timestamp,seq,rtt_ms,traceparent\n")
+        );
+        assert!(
+            files[2]
+                .1
+                .contains(&format!("10.8.0.1,100,1,12.000,{}\n", tag))
+        );
+        let json: StatsSnapshot = serde_json::from_str(&files[1].1).unwrap();
+        assert_eq!(
+            json.trace_id,
+            Some(state.settings.correlation.trace_id.clone())
+        );
+
+        let files = build_export_files(&state, true);
+        assert!(!files[2].1.contains("traceparent"));
+    }
+
+    #[test]
+    fn test_anonymized_export_uses_consistent_pseudonyms() {
+        let files = build_export_files(&state_with_hosts(), true);
+        for (name, content) in &files {
+            for secret in ["192.168.7.1", "10.8.0.1", "Office", "VPN"] {
+                assert!(!content.contains(secret), "{} leaks {}", name, secret);
+            }
+        }
+        // The VPN gateway is both a host and a hop of the other host: one pseudonym everywhere
+        let json: StatsSnapshot = serde_json::from_str(&files[1].1).unwrap();
+        let vpn = &json.hosts[1].address;
+        assert_eq!(&json.hosts[0].traceroute_path[0], vpn);
+        assert!(files[2].1.contains(&format!("{},100,1,", vpn)));
+    }
+}
@@ -0,0 +1,292 @@
+//! Importing hosts from a host list file into the local list.
+//!
+//! New hosts are appended in the order of the file and existing hosts keep their
+//! position. A host that already exists with different settings becomes a
+//! conflict the user resolves before anything is changed.
+
+use crate::logic::host_list::parse_host_list;
+use crate::model::{AppState, HostInfo};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use tr::tr;
+
+/// How a conflicting host is resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Resolution {
+    #[default]
+    KeepLocal,
+    TakeImported,
+    /// Take the imported value of the fields marked in the conflict.
+    Merge,
+}
+
+/// A setting that differs between the local host and the imported one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldConflict {
+    /// Name of the setting as stored in the host list.
+    pub field: String,
+    pub local: Value,
+    pub imported: Value,
+    /// Take the imported value when merging field by field.
+    pub take_imported: bool,
+}
+
+/// An imported host that already exists locally with different settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostConflict {
+    pub address: String,
+    /// Local name of the host.
+    pub name: String,
+    pub fields: Vec<FieldConflict>,
+    pub resolution: Resolution,
+}
+
+/// What an import would do, with the conflicts still to be resolved.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportPlan {
+    /// New hosts in the order of the file.
+    pub added: Vec<HostInfo>,
+    pub conflicts: Vec<HostConflict>,
+    /// Hosts that already exist with the same settings.
+    pub unchanged: usize,
+    /// Hosts that exist as hosts of the shared list and are managed there.
+    pub skipped_shared: usize,
+}
+
+/// What an applied import changed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportSummary {
+    /// Addresses of the added hosts.
+    pub added: Vec<String>,
+    /// Addresses of the updated hosts with the settings that changed.
+    pub updated: Vec<(String, Vec<String>)>,
+    /// Conflicting hosts left as they were.
+    pub kept: usize,
+    pub unchanged: usize,
+    pub skipped_shared: usize,
+}
+
+impl ImportSummary {
+    /// One line for the counts, then one line per added or updated host.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            tr!("{added} added, {updated} updated, {kept} kept local, {unchanged} unchanged")
+                .replace("{added}", &self.added.len().to_string())
+                .replace("{updated}", &self.updated.len().to_string())
+                .replace("{kept}", &self.kept.to_string())
+                .replace("{unchanged}", &self.unchanged.to_string()),
+        ];
+        if self.skipped_shared > 0 {
+            lines.push(
+                tr!("{count} hosts skipped because they come from the shared host list")
+                    .replace("{count}", &self.skipped_shared.to_string()),
+            );
+        }
+        lines.extend(self.added.iter().map(|a| format!("+ {}", a)));
+        lines.extend(
+            self.updated
+                .iter()
+                .map(|(a, fields)| format!("~ {}: {}", a, fields.join(", "))),
+        );
+        lines
+    }
+}
+
+/// Settings of a host by name, without those that are not settings of the host.
+fn fields_of(host: &HostInfo) -> Map<String, Value> {
+    let Ok(Value::Object(mut fields)) = serde_json::to_value(host) else {
+        return Map::new();
+    };
+    fields.remove("address");
+    fields.remove("shared");
+    fields
+}
+
+/// Plans the import of a host list in either format of [`parse_host_list`].
+///
+/// A plain text list only names hosts, so only a non-empty name is compared for
+/// it; the other settings of existing hosts are never touched.
+pub fn plan_import(state: &AppState, text: &str) -> Result<ImportPlan, String> {
+    let names_only = !text.trim_start().starts_with('[');
+    let mut plan = ImportPlan::default();
+    let mut seen = HashSet::new();
+    for mut host in parse_host_list(text)? {
+        host.address = HostInfo::normalize_address(&host.address);
+        host.shared = false;
+        if host.address.is_empty() || !seen.insert(host.address.clone()) {
+            continue;
+        }
+        let Some(local) = state.hosts.iter().find(|h| h.address == host.address) else {
+            plan.added.push(host);
+            continue;
+        };
+        if local.shared {
+            plan.skipped_shared += 1;
+            continue;
+        }
+        let local_fields = fields_of(local);
+        let fields: Vec<FieldConflict> = fields_of(&host)
+            .into_iter()
+            .filter(|(field, _)| !names_only || (field == "name" && !host.name.is_empty()))
+            .filter_map(|(field, imported)| {
+                let local = local_fields.get(&field).cloned().unwrap_or(Value::Null);
+                (local != imported).then_some(FieldConflict {
+                    field,
+                    local,
+                    imported,
+                    take_imported: true,
+                })
+            })
+            .collect();
+        if fields.is_empty() {
+            plan.unchanged += 1;
+        } else {
+            plan.conflicts.push(HostConflict {
+                address: host.address,
+                name: local.name.clone(),
+                fields,
+                resolution: Resolution::default(),
+            });
+        }
+    }
+    Ok(plan)
+}
+
+/// Carries out a planned import with the chosen resolutions.
+pub fn apply_import(state: &mut AppState, plan: ImportPlan) -> ImportSummary {
+    let mut summary = ImportSummary {
+        unchanged: plan.unchanged,
+        skipped_shared: plan.skipped_shared,
+        ..Default::default()
+    };
+    for host in plan.added {
+        // The list may have changed since the import was planned
+        if state.hosts.iter().any(|h| h.address == host.address) {
+            continue;
+        }
+        state.statuses.entry(host.address.clone()).or_default();
+        summary.added.push(host.address.clone());
+        state.hosts.push(host);
+    }
+    for conflict in plan.conflicts {
+        let taken: Vec<&FieldConflict> = conflict
+            .fields
+            .iter()
+            .filter(|f| match conflict.resolution {
+                Resolution::KeepLocal => false,
+                Resolution::TakeImported => true,
+                Resolution::Merge => f.take_imported,
+            })
+            .collect();
+        let Some(local) = state
+            .hosts
+            .iter_mut()
+            .find(|h| h.address == conflict.address)
+        else {
+            continue;
+        };
+        if taken.is_empty() {
+            summary.kept += 1;
+            continue;
+        }
+        let mut fields = match serde_json::to_value(&*local) {
+            Ok(Value::Object(fields)) => fields,
+            _ => continue,
+        };
+        for field in &taken {
+            fields.insert(field.field.clone(), field.imported.clone());
+        }
+        match serde_json::from_value(Value::Object(fields)) {
+            Ok(merged) => {
+                *local = merged;
+                summary.updated.push((
+                    conflict.address,
+                    taken.iter().map(|f| f.field.clone()).collect(),
+                ));
+            }
+            Err(_) => summary.kept += 1,
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::PingMode;
+
+    fn host(address: &str, name: &str) -> HostInfo {
+        HostInfo {
+            name: name.to_string(),
+            address: address.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_plan_and_apply_import() {
+        let mut state = AppState::default();
+        state.hosts.push(host("10.0.0.1", "Router"));
+        state.hosts.push(host("10.0.0.2", "NAS"));
+        state.hosts.push(host("10.0.0.3", "Printer"));
+
+        let mut changed = host("10.0.0.2", "Storage");
+        changed.mode = PingMode::Slow;
+        let imported = vec![
+            host("10.0.0.9", "New"),
+            changed,
+            host("10.0.0.1", "Router"),
+            host("10.0.0.3", "Office printer"),
+            host("10.0.0.8", "Second new"),
+        ];
+        let text = serde_json::to_string(&imported).unwrap();
+        let mut plan = plan_import(&state, &text).unwrap();
+        assert_eq!(plan.added.len(), 2);
+        assert_eq!(plan.unchanged, 1);
+        assert_eq!(plan.conflicts.len(), 2);
+        let fields: Vec<&str> = plan.conflicts[0]
+            .fields
+            .iter()
+            .map(|f| f.field.as_str())
+            .collect();
+        assert_eq!(fields, vec!["mode", "name"]);
+
+        plan.conflicts[0].resolution = Resolution::Merge;
+        plan.conflicts[0].fields[1].take_imported = false;
+        let summary = apply_import(&mut state, plan);
+
+        let order: Vec<&str> = state.hosts.iter().map(|h| h.address.as_str()).collect();
+        assert_eq!(
+            order,
+            vec!["10.0.0.1", "10.0.0.2", "10.0.0.3", "10.0.0.9", "10.0.0.8"]
+        );
+        assert_eq!(state.hosts[1].name, "NAS");
+        assert_eq!(state.hosts[1].mode, PingMode::Slow);
+        assert_eq!(state.hosts[2].name, "Printer");
+        assert!(state.statuses.contains_key("10.0.0.9"));
+        assert_eq!(
+            summary.updated,
+            vec![("10.0.0.2".to_string(), vec!["mode".to_string()])]
+        );
+        assert_eq!(summary.kept, 1);
+        assert_eq!(
+            summary.lines()[0],
+            "2 added, 1 updated, 1 kept local, 1 unchanged"
+        );
+    }
+
+    #[test]
+    fn test_text_list_compares_names_only() {
+        let mut state = AppState::default();
+        let mut local = host("10.0.0.1", "Router");
+        local.mode = PingMode::Slow;
+        state.hosts.push(local);
+        state.hosts.push(host("10.0.0.2", "NAS"));
+
+        let plan = plan_import(&state, "10.0.0.1\n10.0.0.2 Storage\n").unwrap();
+        assert_eq!(plan.unchanged, 1);
+        assert_eq!(plan.conflicts.len(), 1);
+        assert_eq!(plan.conflicts[0].fields.len(), 1);
+        assert_eq!(plan.conflicts[0].fields[0].imported, Value::from("Storage"));
+    }
+}
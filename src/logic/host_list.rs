@@ -0,0 +1,232 @@
+use crate::constants::SHARED_HOST_LIST_CHECK_INTERVAL_SEC;
+use crate::logic::{SharedState, applog};
+use crate::model::{AppState, HostInfo};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// Parses a shared host list.
+///
+/// Two formats are accepted: a JSON array of host configurations (as stored by
+/// the application), or plain text with one `address [name]` per line, where
+/// empty lines and lines starting with `#` are ignored.
+pub fn parse_host_list(text: &str) -> Result<Vec<HostInfo>, String> {
+    if text.trim_start().starts_with('[') {
+        return serde_json::from_str(text).map_err(|e| e.to_string());
+    }
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(|line| {
+            let (address, name) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            HostInfo {
+                name: name.trim().to_string(),
+                address: HostInfo::normalize_address(address),
+                ..Default::default()
+            }
+        })
+        .collect())
+}
+
+/// Merges the hosts of the shared list into the local list.
+///
+/// Shared hosts are added or updated from the file, keeping only whether they are
+/// stopped on this machine, and removed when they disappear from it. A local host
+/// with the same address takes precedence over the shared one.
+pub fn merge_shared_hosts(state: &mut AppState, shared: Vec<HostInfo>, now: u64) {
+    let mut seen = HashSet::new();
+    for mut host in shared {
+        if !seen.insert(host.address.clone()) {
+            continue;
+        }
+        host.shared = true;
+        // Never run commands from a file other people can edit
+        host.clear_commands();
+        match state.hosts.iter_mut().find(|h| h.address == host.address) {
+            Some(local) if local.shared => {
+                host.is_stopped = local.is_stopped;
+                *local = host;
+            }
+            Some(_) => {}
+            None => {
+                state.statuses.entry(host.address.clone()).or_default();
+                state.hosts.push(host);
+            }
+        }
+    }
+
+    let removed: Vec<String> = state
+        .hosts
+        .iter()
+        .filter(|h| h.shared && !seen.contains(&h.address))
+        .map(|h| h.address.clone())
+        .collect();
+    let retain_days = state
+        .settings
+        .retain_deleted_hosts
+        .then_some(state.settings.deleted_host_retention_days);
+    for address in removed {
+        state.delete_host(&address, retain_days, now);
+    }
+}
+
+/// Turns shared hosts into ordinary local ones, e.g. when team mode is disabled.
+fn release_shared_hosts(state: &mut AppState) {
+    for host in state.hosts.iter_mut().filter(|h| h.shared) {
+        host.shared = false;
+    }
+}
+
+/// Reloads the shared host list if it is configured and has changed since the last merge.
+///
+/// Called from the pinger loop; file access runs on tokio's blocking pool because
+/// the file may live on a slow network drive.
+pub fn spawn_sync_if_due(state: &SharedState, last_check: &mut Option<Instant>, now: Instant) {
+    if last_check.is_some_and(|t| {
+        now.duration_since(t) < Duration::from_secs(SHARED_HOST_LIST_CHECK_INTERVAL_SEC)
+    }) {
+        return;
+    }
+    *last_check = Some(now);
+
+    let (path, known) = {
+        let mut state_lock = state
+            .lock()
+            .expect("Failed to lock state for host list sync");
+        let path = state_lock.settings.shared_host_list.trim().to_string();
+        if path != state_lock.shared_list.path {
+            if path.is_empty() {
+                release_shared_hosts(&mut state_lock);
+            }
+            state_lock.shared_list = Default::default();
+            state_lock.shared_list.path = path.clone();
+        }
+        (path, state_lock.shared_list.modified)
+    };
+    if path.is_empty() {
+        return;
+    }
+
+    let state = state.clone();
+    tokio::task::spawn_blocking(move || {
+        let result = std::fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .map_err(|e| e.to_string())
+            .and_then(|modified| {
+                if Some(modified) == known {
+                    return Ok(None);
+                }
+                let text = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+                Ok(Some((modified, parse_host_list(&text)?)))
+            });
+
+        let mut state_lock = state
+            .lock()
+            .expect("Failed to lock state for host list sync");
+        // The setting may have changed while the file was read
+        if state_lock.shared_list.path != path {
+            return;
+        }
+        match result {
+            Ok(None) => {}
+            Ok(Some((modified, hosts))) => {
+                applog::info(
+                    "host_list",
+                    format!("Loaded {} hosts from {}", hosts.len(), path),
+                );
+                state_lock.shared_list.modified = Some(modified);
+                state_lock.shared_list.hosts = hosts.len();
+                state_lock.shared_list.error = None;
+                merge_shared_hosts(
+                    &mut state_lock,
+                    hosts,
+                    chrono::Utc::now().timestamp() as u64,
+                );
+            }
+            Err(e) => {
+                if state_lock.shared_list.error.as_ref() != Some(&e) {
+                    applog::warn(
+                        "host_list",
+                        format!("Failed to read shared host list {}: {}", path, e),
+                    );
+                }
+                state_lock.shared_list.error = Some(e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(address: &str, name: &str) -> HostInfo {
+        HostInfo {
+            name: name.to_string(),
+            address: address.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_text_and_json() {
+        let hosts = parse_host_list(
+            "# Team hosts\n8.8.8.8 Google DNS\n\nGW.Example.com\nhttps://example.com/Health",
+        )
+        .unwrap();
+        let addresses: Vec<&str> = hosts.iter().map(|h| h.address.as_str()).collect();
+        assert_eq!(
+            addresses,
+            vec!["8.8.8.8", "gw.example.com", "https://example.com/Health"]
+        );
+        assert_eq!(hosts[0].name, "Google DNS");
+
+        let json = serde_json::to_string(&vec![host("1.1.1.1", "CF")]).unwrap();
+        assert_eq!(parse_host_list(&json).unwrap()[0].name, "CF");
+        assert!(parse_host_list("[ broken").is_err());
+    }
+
+    #[test]
+    fn test_merge_adds_updates_and_removes() {
+        let mut state = AppState::default();
+        state.settings.retain_deleted_hosts = false;
+        state.hosts.push(host("10.0.0.1", "Local"));
+
+        merge_shared_hosts(
+            &mut state,
+            vec![host("10.0.0.1", "Shared"), host("10.0.0.2", "Old")],
+            0,
+        );
+        assert_eq!(state.hosts.len(), 2);
+        // The local host is not taken over by the shared list
+        assert_eq!(state.hosts[0].name, "Local");
+        assert!(!state.hosts[0].shared);
+        assert!(state.hosts[1].shared);
+        assert!(state.statuses.contains_key("10.0.0.2"));
+
+        state.hosts[1].is_stopped = true;
+        merge_shared_hosts(&mut state, vec![host("10.0.0.2", "New")], 0);
+        assert_eq!(state.hosts[1].name, "New");
+        assert!(state.hosts[1].is_stopped);
+
+        merge_shared_hosts(&mut state, Vec::new(), 0);
+        assert_eq!(state.hosts.len(), 1);
+        assert!(!state.statuses.contains_key("10.0.0.2"));
+    }
+
+    #[test]
+    fn test_shared_hosts_run_no_commands() {
+        let mut state = AppState::default();
+        let shared = HostInfo {
+            probe_command: "probe".to_string(),
+            on_down_command: "down".to_string(),
+            on_up_command: "up".to_string(),
+            ..host("10.0.0.3", "Scripted")
+        };
+        merge_shared_hosts(&mut state, vec![shared], 0);
+        let merged = &state.hosts[0];
+        assert!(merged.probe_command.is_empty());
+        assert!(merged.on_down_command.is_empty());
+        assert!(merged.on_up_command.is_empty());
+    }
+}
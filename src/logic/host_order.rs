@@ -0,0 +1,183 @@
+//! Sorting and filtering of the host list shown in the main window.
+//!
+//! Only the displayed order changes; the stored order of the hosts stays the manual
+//! one set by drag and drop.
+
+use crate::model::{AppState, HostInfo, HostSort, HostStatus};
+use std::cmp::Ordering;
+
+/// Whether a host matches the filter text: a case-insensitive substring of its
+/// name, address, group, notes, tags, resolved IP or DHCP name. An empty filter
+/// matches all.
+pub fn matches_filter(host: &HostInfo, status: Option<&HostStatus>, filter: &str) -> bool {
+    let filter = filter.trim().to_lowercase();
+    if filter.is_empty() {
+        return true;
+    }
+    let status_fields = status
+        .into_iter()
+        .flat_map(|s| [s.resolved_ip.as_deref(), s.dhcp_name.as_deref()])
+        .flatten();
+    [
+        host.name.as_str(),
+        host.address.as_str(),
+        host.group.as_str(),
+        host.notes.as_str(),
+    ]
+    .into_iter()
+    .chain(host.tags.iter().map(String::as_str))
+    .chain(status_fields)
+    .any(|field| field.to_lowercase().contains(&filter))
+}
+
+/// Whether a host has the tag, or any host when no tag is chosen.
+pub fn has_tag(host: &HostInfo, tag: Option<&str>) -> bool {
+    tag.is_none_or(|tag| host.tags.iter().any(|t| t == tag))
+}
+
+/// All tags of the hosts, sorted and without duplicates.
+pub fn all_tags(hosts: &[HostInfo]) -> Vec<String> {
+    let mut tags: Vec<String> = hosts.iter().flat_map(|h| h.tags.iter().cloned()).collect();
+    tags.sort_by_key(|t| t.to_lowercase());
+    tags.dedup();
+    tags
+}
+
+/// Parses comma-separated tags, dropping empty ones and duplicates.
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// Value a host is sorted by; `None` for hosts without one (stopped or not yet
+/// answered), which always come last.
+fn sort_value(sort: HostSort, host: &HostInfo, status: &HostStatus) -> Option<f64> {
+    let measured = !host.is_stopped && status.sent > 0;
+    match sort {
+        HostSort::Manual | HostSort::Name => None,
+        HostSort::Latency => (measured && status.alive).then_some(status.latency),
+        HostSort::Loss => measured.then_some(100.0 - status.availability),
+        // Best first
+        HostSort::Mos => (measured && status.alive).then_some(-status.mos),
+        HostSort::Status => measured.then_some(if status.alive { 1.0 } else { 0.0 }),
+    }
+}
+
+/// Sorts the host indices in place. The sort is stable, so hosts with equal values
+/// keep their manual order; `reversed` turns the order around except that hosts
+/// without a value stay last.
+pub fn sort_hosts(indices: &mut [usize], state: &AppState, sort: HostSort, reversed: bool) {
+    let default_status = HostStatus::default();
+    let status_of = |host: &HostInfo| state.statuses.get(&host.address).unwrap_or(&default_status);
+    let name_of = |host: &HostInfo| {
+        if host.name.is_empty() {
+            host.address.to_lowercase()
+        } else {
+            host.name.to_lowercase()
+        }
+    };
+    indices.sort_by(|&a, &b| {
+        let (a, b) = (&state.hosts[a], &state.hosts[b]);
+        let order = match sort {
+            HostSort::Manual => Ordering::Equal,
+            HostSort::Name => name_of(a).cmp(&name_of(b)),
+            _ => {
+                match (
+                    sort_value(sort, a, status_of(a)),
+                    sort_value(sort, b, status_of(b)),
+                ) {
+                    (Some(x), Some(y)) => x.total_cmp(&y),
+                    (Some(_), None) => return Ordering::Less,
+                    (None, Some(_)) => return Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                }
+            }
+        };
+        if reversed { order.reverse() } else { order }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state_with(hosts: &[(&str, &str, bool, u32, f64)]) -> AppState {
+        let mut state = AppState::default();
+        for &(address, name, alive, sent, latency) in hosts {
+            state.hosts.push(HostInfo {
+                address: address.to_string(),
+                name: name.to_string(),
+                ..Default::default()
+            });
+            state.statuses.insert(
+                address.to_string(),
+                HostStatus {
+                    alive,
+                    sent,
+                    latency,
+                    ..Default::default()
+                },
+            );
+        }
+        state
+    }
+
+    #[test]
+    fn test_sort_hosts() {
+        let state = state_with(&[
+            ("10.0.0.1", "router", true, 5, 2.0),
+            ("10.0.0.2", "", false, 5, 0.0),
+            ("10.0.0.3", "Camera", true, 5, 40.0),
+            ("10.0.0.4", "New", false, 0, 0.0),
+            ("10.0.0.5", "Backup", true, 5, 2.0),
+        ]);
+        let sorted = |sort, reversed| {
+            let mut indices: Vec<usize> = (0..state.hosts.len()).collect();
+            sort_hosts(&mut indices, &state, sort, reversed);
+            indices
+        };
+        assert_eq!(sorted(HostSort::Manual, false), vec![0, 1, 2, 3, 4]);
+        assert_eq!(sorted(HostSort::Name, false), vec![1, 4, 2, 3, 0]);
+        assert_eq!(sorted(HostSort::Latency, false), vec![0, 4, 2, 1, 3]);
+        assert_eq!(sorted(HostSort::Latency, true), vec![2, 0, 4, 1, 3]);
+        assert_eq!(sorted(HostSort::Status, false), vec![1, 0, 2, 4, 3]);
+    }
+
+    #[test]
+    fn test_matches_filter() {
+        let mut state = state_with(&[("nas.local", "Storage", true, 5, 1.0)]);
+        state.hosts[0].group = "Office".to_string();
+        state.hosts[0].notes = "Rack 3, port 12".to_string();
+        let status = state.statuses.get_mut("nas.local").unwrap();
+        status.resolved_ip = Some("192.168.1.50".to_string());
+        let (host, status) = (&state.hosts[0], state.statuses.get("nas.local"));
+        for filter in ["", "stor", "NAS", "office", "rack 3", "1.50"] {
+            assert!(matches_filter(host, status, filter), "{}", filter);
+        }
+        assert!(!matches_filter(host, status, "printer"));
+    }
+
+    #[test]
+    fn test_tags() {
+        assert_eq!(
+            parse_tags(" prod, vpn,,prod ,home "),
+            vec!["prod", "vpn", "home"]
+        );
+        let mut state = state_with(&[
+            ("10.0.0.1", "a", true, 1, 1.0),
+            ("10.0.0.2", "b", true, 1, 1.0),
+        ]);
+        state.hosts[0].tags = parse_tags("vpn, prod");
+        state.hosts[1].tags = parse_tags("home, vpn");
+        assert_eq!(all_tags(&state.hosts), vec!["home", "prod", "vpn"]);
+        assert!(has_tag(&state.hosts[0], Some("prod")));
+        assert!(!has_tag(&state.hosts[1], Some("prod")));
+        assert!(has_tag(&state.hosts[1], None));
+        assert!(matches_filter(&state.hosts[1], None, "HOME"));
+    }
+}
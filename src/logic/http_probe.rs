@@ -0,0 +1,93 @@
+use crate::logic::http;
+use crate::model::{HttpMethod, ProxySettings};
+use std::time::Instant;
+
+/// Response of an HTTP(S) probe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HttpProbeResult {
+    pub status: u16,
+    /// Time from sending the request until the response headers arrived.
+    pub ttfb_ms: f64,
+}
+
+/// Returns `true` if `status` is accepted by `expected`: a comma-separated list of
+/// codes ("200"), ranges ("200-299") and classes ("2xx"). Empty accepts 200-399.
+pub fn status_matches(expected: &str, status: u16) -> bool {
+    if expected.trim().is_empty() {
+        return (200..400).contains(&status);
+    }
+    expected.split(',').map(str::trim).any(|item| {
+        if let Some((from, to)) = item.split_once('-') {
+            matches!(
+                (from.trim().parse::<u16>(), to.trim().parse::<u16>()),
+                (Ok(from), Ok(to)) if (from..=to).contains(&status)
+            )
+        } else if let Some(class) = item.strip_suffix("xx").or(item.strip_suffix("XX")) {
+            class.parse::<u16>().is_ok_and(|c| status / 100 == c)
+        } else {
+            item.parse::<u16>().is_ok_and(|code| code == status)
+        }
+    })
+}
+
+/// Sends a request to `url` and measures the time to the first byte of the response.
+///
+/// Redirects are not followed, so a 3xx answer is measured and judged on its own.
+/// Transport errors (DNS, connection, TLS, timeout) are returned as `Err`.
+pub fn probe(
+    url: &str,
+    method: HttpMethod,
+    proxy: &ProxySettings,
+) -> Result<HttpProbeResult, String> {
+    let agent = http::builder(proxy, url).redirects(0).build();
+    let start = Instant::now();
+    let result = agent.request(method.as_str(), url).call();
+    let ttfb_ms = start.elapsed().as_secs_f64() * 1000.0;
+    let status = match result {
+        Ok(response) => response.status(),
+        Err(ureq::Error::Status(code, _)) => code,
+        Err(e) => return Err(e.to_string()),
+    };
+    Ok(HttpProbeResult { status, ttfb_ms })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_status_matches() {
+        assert!(status_matches("", 200));
+        assert!(status_matches("", 301));
+        assert!(!status_matches("", 404));
+        assert!(status_matches("200, 204", 204));
+        assert!(!status_matches("200,204", 301));
+        assert!(status_matches("400-404", 401));
+        assert!(status_matches("5xx", 503));
+        assert!(!status_matches("5xx", 404));
+        assert!(!status_matches("garbage", 200));
+    }
+
+    #[test]
+    fn test_probe_reports_status_without_following_redirects() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/health", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf);
+            let _ = stream.write_all(
+                b"HTTP/1.1 302 Found\r\nLocation: /elsewhere\r\nContent-Length: 0\r\n\r\n",
+            );
+        });
+        let proxy = ProxySettings {
+            mode: crate::model::ProxyMode::Direct,
+            ..Default::default()
+        };
+        let result = probe(&url, HttpMethod::Head, &proxy).unwrap();
+        assert_eq!(result.status, 302);
+        assert!(result.ttfb_ms >= 0.0);
+    }
+}
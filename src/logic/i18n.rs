@@ -0,0 +1,94 @@
+//! Runtime selection of the user interface language.
+//!
+//! `tr!` looks the translator up on every call, so replacing it switches the
+//! language of the next frame without a restart.
+
+use std::borrow::Cow;
+use tr::MoTranslator;
+
+/// Languages with a catalog, as code and native name (not translated on purpose).
+pub const LANGUAGES: [(&str, &str); 2] = [("en", "English"), ("uk", "Українська")];
+
+/// Language code of the system locale, e.g. "uk" for `uk_UA.UTF-8`.
+pub fn system_language() -> String {
+    let locale = sys_locale::get_locale()
+        .or_else(|| std::env::var("LANG").ok())
+        .or_else(|| std::env::var("LC_ALL").ok())
+        .or_else(|| std::env::var("LC_MESSAGES").ok())
+        .unwrap_or_else(|| "en".to_string());
+    language_code(&locale)
+}
+
+/// The language part of a locale name ("uk_UA.UTF-8", "uk-UA" → "uk").
+pub fn language_code(locale: &str) -> String {
+    locale
+        .split(['_', '-', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// The compiled gettext catalog of `code`, if there is one.
+#[cfg(any(feature = "embed-locales", target_os = "windows"))]
+fn catalog(code: &str) -> Option<Vec<u8>> {
+    match code {
+        "uk" => Some(include_bytes!("../../locales/uk/LC_MESSAGES/egui_pinger.mo").to_vec()),
+        _ => None,
+    }
+}
+
+/// The compiled gettext catalog of `code`, if there is one.
+#[cfg(not(any(feature = "embed-locales", target_os = "windows")))]
+fn catalog(code: &str) -> Option<Vec<u8>> {
+    std::fs::read(format!("./locales/{}/LC_MESSAGES/egui_pinger.mo", code)).ok()
+}
+
+/// Shows the source strings, i.e. English.
+struct Untranslated;
+
+impl tr::Translator for Untranslated {
+    fn translate<'a>(&'a self, string: &'a str, _context: Option<&'a str>) -> Cow<'a, str> {
+        Cow::Borrowed(string)
+    }
+
+    fn ntranslate<'a>(
+        &'a self,
+        n: u64,
+        singular: &'a str,
+        plural: &'a str,
+        _context: Option<&'a str>,
+    ) -> Cow<'a, str> {
+        Cow::Borrowed(if n == 1 { singular } else { plural })
+    }
+}
+
+/// Switches the user interface to `language` (a code of [`LANGUAGES`]); empty follows
+/// the system locale. Languages without a catalog fall back to English.
+pub fn set_language(language: &str) {
+    let code = if language.is_empty() {
+        system_language()
+    } else {
+        language_code(language)
+    };
+    match catalog(&code).map(MoTranslator::from_vec_u8) {
+        Some(Ok(translator)) => tr::set_translator!(translator),
+        Some(Err(_)) => {
+            super::applog::warn("i18n", format!("Invalid catalog of language {}", code));
+            tr::set_translator!(Untranslated);
+        }
+        None => tr::set_translator!(Untranslated),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_code() {
+        assert_eq!(language_code("uk_UA.UTF-8"), "uk");
+        assert_eq!(language_code("uk-UA"), "uk");
+        assert_eq!(language_code("EN"), "en");
+        assert_eq!(language_code("C"), "c");
+    }
+}
@@ -0,0 +1,462 @@
+use crate::logic::{SharedState, applog, pinger, storage};
+use crate::model::LogEntry;
+use mos::PingResult;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Output format of an external ping log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImportFormat {
+    /// Detected from the first recognized line.
+    #[default]
+    Auto,
+    /// Standard `ping` output, optionally with `-D` timestamps.
+    Ping,
+    /// `fping -l`, `fping -e` or `fping -C` output, optionally with `-D` timestamps.
+    Fping,
+    /// `rrdtool fetch` output of a Smokeping RRD file.
+    Smokeping,
+}
+
+impl ImportFormat {
+    pub const ALL: [ImportFormat; 4] = [
+        ImportFormat::Auto,
+        ImportFormat::Ping,
+        ImportFormat::Fping,
+        ImportFormat::Smokeping,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImportFormat::Auto => "Auto",
+            ImportFormat::Ping => "ping",
+            ImportFormat::Fping => "fping",
+            ImportFormat::Smokeping => "Smokeping (rrdtool fetch)",
+        }
+    }
+}
+
+/// One ping parsed from an external log.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImportedSample {
+    /// Unix timestamp, if the log records one.
+    pub timestamp: Option<u64>,
+    /// RTT in ms, `None` for a lost packet.
+    pub rtt: Option<f64>,
+}
+
+/// Splits a leading `[1700000000.123456]` timestamp (as printed by `ping -D`
+/// and `fping -D`) from the rest of the line.
+fn split_timestamp(line: &str) -> (Option<u64>, &str) {
+    let trimmed = line.trim_start();
+    if let Some(rest) = trimmed.strip_prefix('[')
+        && let Some((ts, rest)) = rest.split_once(']')
+        && let Ok(ts) = ts.trim().parse::<f64>()
+    {
+        return (Some(ts as u64), rest);
+    }
+    (None, line)
+}
+
+/// Parses a line of standard `ping` output.
+fn parse_ping_line(line: &str) -> Vec<ImportedSample> {
+    let (timestamp, rest) = split_timestamp(line);
+    let rtt = match mos::parse_line(rest) {
+        PingResult::Success(rtt) => Some(rtt),
+        PingResult::Timeout => None,
+        PingResult::Unknown => return Vec::new(),
+    };
+    vec![ImportedSample { timestamp, rtt }]
+}
+
+/// Parses a line of `fping` output.
+fn parse_fping_line(line: &str) -> Vec<ImportedSample> {
+    let (timestamp, rest) = split_timestamp(line);
+    let sample = |rtt| vec![ImportedSample { timestamp, rtt }];
+    let ms_value = |s: &str| {
+        s.split_whitespace()
+            .next()
+            .and_then(|v| v.parse::<f64>().ok())
+    };
+
+    // `fping -e`: "8.8.8.8 is alive (12.3 ms)" / "8.8.8.8 is unreachable"
+    if let Some((_, after)) = rest.split_once(" is alive (") {
+        return ms_value(after)
+            .map(|rtt| sample(Some(rtt)))
+            .unwrap_or_default();
+    }
+    if rest.contains(" is unreachable") {
+        return sample(None);
+    }
+
+    let Some((_, results)) = rest.split_once(" : ") else {
+        return Vec::new();
+    };
+    // `fping -l`: "8.8.8.8 : [3], 64 bytes, 12.3 ms (12.1 avg, 0% loss)"
+    if let Some((_, after)) = results.split_once("bytes,") {
+        return ms_value(after)
+            .map(|rtt| sample(Some(rtt)))
+            .unwrap_or_default();
+    }
+    if results.contains("timed out") {
+        return sample(None);
+    }
+    // `fping -C`: "8.8.8.8 : 12.3 13.1 - 14.0", "-" marks a lost packet
+    let values: Vec<&str> = results.split_whitespace().collect();
+    if values.is_empty() || !values.iter().all(|v| *v == "-" || v.parse::<f64>().is_ok()) {
+        return Vec::new();
+    }
+    values
+        .iter()
+        .map(|v| ImportedSample {
+            timestamp,
+            rtt: v.parse().ok(),
+        })
+        .collect()
+}
+
+/// Column positions of a Smokeping RRD as named in the `rrdtool fetch` header.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct SmokepingColumns {
+    loss: usize,
+    pings: Vec<usize>,
+}
+
+impl SmokepingColumns {
+    fn from_header(line: &str) -> Option<Self> {
+        let names: Vec<&str> = line.split_whitespace().collect();
+        let loss = names.iter().position(|n| *n == "loss")?;
+        let pings: Vec<usize> = names
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| {
+                n.strip_prefix("ping")
+                    .is_some_and(|i| i.parse::<u32>().is_ok())
+            })
+            .map(|(i, _)| i)
+            .collect();
+        (!pings.is_empty()).then_some(Self { loss, pings })
+    }
+
+    /// Parses a data row: "1700000000: nan 1.0e+00 1.2e-02 ...". Smokeping stores
+    /// RTTs in seconds, sorted, with `nan` in place of lost pings.
+    fn parse_row(&self, line: &str) -> Vec<ImportedSample> {
+        let Some((ts, values)) = line.split_once(':') else {
+            return Vec::new();
+        };
+        let Ok(timestamp) = ts.trim().parse::<u64>() else {
+            return Vec::new();
+        };
+        let values: Vec<f64> = values
+            .split_whitespace()
+            .map(|v| v.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        // A row without a loss count has no data (the host was not probed)
+        if values.get(self.loss).is_none_or(|l| l.is_nan()) {
+            return Vec::new();
+        }
+        self.pings
+            .iter()
+            .filter_map(|&i| values.get(i))
+            .map(|v| ImportedSample {
+                timestamp: Some(timestamp),
+                rtt: (!v.is_nan()).then_some(v * 1000.0),
+            })
+            .collect()
+    }
+}
+
+/// Line-by-line parser of an external ping log.
+#[derive(Debug, Clone, Default)]
+pub struct LogParser {
+    format: ImportFormat,
+    smokeping: Option<SmokepingColumns>,
+}
+
+impl LogParser {
+    pub fn new(format: ImportFormat) -> Self {
+        Self {
+            format,
+            smokeping: None,
+        }
+    }
+
+    /// Format in use; stays `Auto` until a line has been recognized.
+    pub fn format(&self) -> ImportFormat {
+        self.format
+    }
+
+    /// Parses one line, returning the pings it reports (usually one, several for
+    /// `fping -C` summaries and Smokeping rows, none for headers and noise).
+    pub fn parse_line(&mut self, line: &str) -> Vec<ImportedSample> {
+        match self.format {
+            ImportFormat::Auto => {
+                if let Some(columns) = SmokepingColumns::from_header(line) {
+                    self.format = ImportFormat::Smokeping;
+                    self.smokeping = Some(columns);
+                    return Vec::new();
+                }
+                let samples = parse_fping_line(line);
+                if !samples.is_empty() {
+                    self.format = ImportFormat::Fping;
+                    return samples;
+                }
+                let samples = parse_ping_line(line);
+                if !samples.is_empty() {
+                    self.format = ImportFormat::Ping;
+                }
+                samples
+            }
+            ImportFormat::Ping => parse_ping_line(line),
+            ImportFormat::Fping => parse_fping_line(line),
+            ImportFormat::Smokeping => match &self.smokeping {
+                Some(columns) => columns.parse_row(line),
+                None => {
+                    self.smokeping = SmokepingColumns::from_header(line);
+                    Vec::new()
+                }
+            },
+        }
+    }
+}
+
+/// Reads all pings from a log file.
+pub fn read_file(path: &Path, format: ImportFormat) -> Result<Vec<ImportedSample>, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut parser = LogParser::new(format);
+    let mut samples = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        samples.extend(parser.parse_line(&line));
+    }
+    Ok(samples)
+}
+
+/// Adds imported pings to the statistics of `address`.
+///
+/// Samples without a timestamp are assumed to be one second apart and to end now,
+/// like the output of a plain `ping`. Incidents and alerts are not evaluated, as
+/// they describe the present state of the host.
+pub fn add_samples(state: &SharedState, address: &str, samples: &[ImportedSample]) -> usize {
+    let mut state_lock = state.lock().expect("Failed to lock state for import");
+    let keep_history = state_lock.settings.persistent_history;
+    let bytes = state_lock
+        .hosts
+        .iter()
+        .find(|h| h.address == address)
+        .map(|h| h.packet_size as u16)
+        .unwrap_or(16);
+    let Some(status) = state_lock.statuses.get_mut(address) else {
+        return 0;
+    };
+
+    let now_ts = chrono::Utc::now().timestamp() as u64;
+    let count = samples.len() as u64;
+    for (i, sample) in samples.iter().enumerate() {
+        let timestamp = sample
+            .timestamp
+            .unwrap_or_else(|| now_ts.saturating_sub(count - 1 - i as u64));
+        status.add_sample(sample.rtt.unwrap_or(f64::NAN), sample.rtt.is_some());
+        status.rollups.add(timestamp, sample.rtt);
+        if keep_history {
+            storage::record(address, timestamp, sample.rtt.map(|r| r as f32));
+        }
+        status.events.push_back(LogEntry::Ping {
+            timestamp,
+            seq: status.sent,
+            rtt: sample.rtt.map(|r| r as f32),
+            bytes,
+        });
+    }
+    status.events.push_back(LogEntry::Marker {
+        timestamp: now_ts,
+        message: format!("Imported {} pings", samples.len()),
+    });
+    status.trim_events();
+    samples.len()
+}
+
+/// Follows a growing log file like `tail -f`, feeding new pings into a host's
+/// statistics as if the pinger had received them.
+pub struct LogFollower {
+    pub path: PathBuf,
+    pub address: String,
+    stop: Arc<AtomicBool>,
+}
+
+impl LogFollower {
+    /// Starts following `path` from its current end in a background thread.
+    pub fn start(state: SharedState, path: PathBuf, address: String, format: ImportFormat) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let follower = Self {
+            path: path.clone(),
+            address: address.clone(),
+            stop: stop.clone(),
+        };
+        std::thread::spawn(move || {
+            applog::info(
+                "import",
+                format!("Following {} for {}", path.display(), address),
+            );
+            if let Err(e) = follow(&state, &path, &address, format, &stop) {
+                applog::warn(
+                    "import",
+                    format!("Stopped following {}: {}", path.display(), e),
+                );
+            }
+        });
+        follower
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.stop.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for LogFollower {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn follow(
+    state: &SharedState,
+    path: &Path,
+    address: &str,
+    format: ImportFormat,
+    stop: &AtomicBool,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut pos = reader.seek(SeekFrom::End(0))?;
+    let mut parser = LogParser::new(format);
+    let mut line = String::new();
+
+    while !stop.load(Ordering::Relaxed) {
+        // Start over if the file was truncated or replaced by a shorter one
+        if std::fs::metadata(path)?.len() < pos {
+            reader = BufReader::new(File::open(path)?);
+            pos = 0;
+            line.clear();
+        }
+        let read = reader.read_line(&mut line)?;
+        if read == 0 {
+            std::thread::sleep(Duration::from_millis(500));
+            continue;
+        }
+        pos += read as u64;
+        // Wait for the rest of a partially written line
+        if !line.ends_with('\n') {
+            continue;
+        }
+        let samples = parser.parse_line(&line);
+        line.clear();
+        if samples.is_empty() {
+            continue;
+        }
+        let host_info = {
+            let state_lock = state.lock().expect("Failed to lock state for import");
+            state_lock
+                .hosts
+                .iter()
+                .find(|h| h.address == address)
+                .cloned()
+        };
+        let Some(host_info) = host_info else {
+            // The host was deleted
+            break;
+        };
+        for sample in samples {
+            pinger::process_ping_result(
+                state,
+                address,
+                sample.rtt.is_some(),
+                sample.rtt.unwrap_or(f64::NAN),
+                false,
+                Some(&host_info),
+            );
+        }
+    }
+    stop.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_all(format: ImportFormat, text: &str) -> (ImportFormat, Vec<ImportedSample>) {
+        let mut parser = LogParser::new(format);
+        let samples = text.lines().flat_map(|l| parser.parse_line(l)).collect();
+        (parser.format(), samples)
+    }
+
+    #[test]
+    fn test_parse_ping_output() {
+        let text = "PING 8.8.8.8 (8.8.8.8) 56(84) bytes of data.\n\
+            [1700000000.123456] 64 bytes from 8.8.8.8: icmp_seq=1 ttl=117 time=12.3 ms\n\
+            From 192.168.1.1 icmp_seq=2 Destination Host Unreachable\n\
+            --- 8.8.8.8 ping statistics ---";
+        let (format, samples) = parse_all(ImportFormat::Auto, text);
+        assert_eq!(format, ImportFormat::Ping);
+        assert_eq!(
+            samples,
+            vec![
+                ImportedSample {
+                    timestamp: Some(1_700_000_000),
+                    rtt: Some(12.3)
+                },
+                ImportedSample {
+                    timestamp: None,
+                    rtt: None
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_fping_output() {
+        let text = "[1700000000.5] 8.8.8.8 : [0], 64 bytes, 12.3 ms (12.3 avg, 0% loss)\n\
+            [1700000001.5] 8.8.8.8 : [1], timed out (12.3 avg, 50% loss)\n\
+            8.8.8.8 : 10.0 - 14.5\n\
+            1.1.1.1 is alive (3.25 ms)\n\
+            10.0.0.1 is unreachable";
+        let (format, samples) = parse_all(ImportFormat::Auto, text);
+        assert_eq!(format, ImportFormat::Fping);
+        let rtts: Vec<Option<f64>> = samples.iter().map(|s| s.rtt).collect();
+        assert_eq!(
+            rtts,
+            vec![
+                Some(12.3),
+                None,
+                Some(10.0),
+                None,
+                Some(14.5),
+                Some(3.25),
+                None
+            ]
+        );
+        assert_eq!(samples[1].timestamp, Some(1_700_000_001));
+    }
+
+    #[test]
+    fn test_parse_smokeping_fetch() {
+        let text = "        uptime  loss  median  ping1  ping2  ping3\n\
+            \n\
+            1700000000: nan 1.0000000000e+00 1.2000000000e-02 1.1000000000e-02 1.2000000000e-02 nan\n\
+            1700000300: nan nan nan nan nan nan";
+        let (format, samples) = parse_all(ImportFormat::Auto, text);
+        assert_eq!(format, ImportFormat::Smokeping);
+        assert_eq!(samples.len(), 3);
+        assert!(samples.iter().all(|s| s.timestamp == Some(1_700_000_000)));
+        assert!((samples[0].rtt.unwrap() - 11.0).abs() < 1e-9);
+        assert_eq!(samples[2].rtt, None);
+    }
+}
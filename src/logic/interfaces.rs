@@ -0,0 +1,345 @@
+//! Link state of the network interfaces, for hosts reached through a specific one.
+//!
+//! Linux reads `/sys/class/net`, macOS parses `ifconfig` and Windows asks
+//! `Get-NetAdapter` through PowerShell.
+
+use crate::constants::INTERFACE_CHECK_INTERVAL_SEC;
+use crate::logic::{SharedState, applog};
+use crate::model::{AppState, LogEntry};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+use tr::tr;
+
+/// Link state by interface name, as printed by `ifconfig` on macOS and BSD.
+///
+/// An interface is up if its flags contain `UP` and it does not report `status: inactive`.
+pub fn parse_ifconfig(output: &str) -> HashMap<String, bool> {
+    let mut links = HashMap::new();
+    let mut current: Option<String> = None;
+    for line in output.lines() {
+        if !line.starts_with(char::is_whitespace)
+            && let Some((name, rest)) = line.split_once(':')
+        {
+            let up = rest
+                .split_once('<')
+                .and_then(|(_, flags)| flags.split_once('>'))
+                .is_some_and(|(flags, _)| flags.split(',').any(|f| f == "UP"));
+            links.insert(name.to_string(), up);
+            current = Some(name.to_string());
+        } else if line.trim() == "status: inactive"
+            && let Some(name) = &current
+        {
+            links.insert(name.clone(), false);
+        }
+    }
+    links
+}
+
+/// Link state by interface name from `<name>\t<status>` lines of `Get-NetAdapter`.
+pub fn parse_adapter_list(output: &str) -> HashMap<String, bool> {
+    output
+        .lines()
+        .filter_map(|line| line.trim_end().rsplit_once('\t'))
+        .map(|(name, status)| (name.to_string(), status.trim() == "Up"))
+        .collect()
+}
+
+/// Addresses from the `inet` / `inet6` entries of `ip -o addr` or `ifconfig`
+/// output, without prefix lengths and scope ids.
+pub fn parse_addresses(output: &str) -> Vec<IpAddr> {
+    let mut addresses = Vec::new();
+    for line in output.lines() {
+        let mut words = line.split_whitespace();
+        while let Some(word) = words.next() {
+            if (word == "inet" || word == "inet6")
+                && let Some(address) = words.next()
+                && let Some(ip) = parse_address(address)
+            {
+                addresses.push(ip);
+            }
+        }
+    }
+    addresses
+}
+
+/// An address like `192.168.1.5/24`, `fe80::1%en0` or `addr:10.0.0.2`.
+fn parse_address(text: &str) -> Option<IpAddr> {
+    let text = text.strip_prefix("addr:").unwrap_or(text);
+    let text = text.split(['/', '%']).next()?;
+    text.parse().ok()
+}
+
+/// Addresses assigned to the interface `name`.
+#[cfg(unix)]
+pub fn addresses(name: &str) -> Vec<IpAddr> {
+    let output = if cfg!(target_os = "linux") {
+        std::process::Command::new("ip")
+            .args(["-o", "addr", "show", "dev", name])
+            .output()
+    } else {
+        std::process::Command::new("ifconfig").arg(name).output()
+    };
+    output
+        .map(|o| parse_addresses(&String::from_utf8_lossy(&o.stdout)))
+        .unwrap_or_default()
+}
+
+/// Addresses assigned to the interface `name`.
+#[cfg(windows)]
+pub fn addresses(name: &str) -> Vec<IpAddr> {
+    use crate::constants::CREATE_NO_WINDOW;
+    use std::os::windows::process::CommandExt;
+    let command = format!(
+        "(Get-NetIPAddress -InterfaceAlias '{}').IPAddress",
+        name.replace('\'', "''")
+    );
+    std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &command])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .lines()
+                .filter_map(|line| parse_address(line.trim()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Local address to send probes to `target` from: `source` itself if it is an IP
+/// address, otherwise an address of the interface named `source` in the family
+/// of the target.
+pub async fn source_address(source: &str, target: IpAddr) -> Result<IpAddr, String> {
+    if let Ok(ip) = crate::logic::dns::strip_brackets(source).parse::<IpAddr>() {
+        return Ok(ip);
+    }
+    let name = source.to_string();
+    let assigned = tokio::task::spawn_blocking(move || addresses(&name))
+        .await
+        .unwrap_or_default();
+    assigned
+        .into_iter()
+        .filter(|ip| ip.is_ipv4() == target.is_ipv4())
+        .min_by_key(|ip| matches!(ip, IpAddr::V6(v6) if v6.is_unicast_link_local()))
+        .ok_or_else(|| {
+            format!(
+                "no {} address on {}",
+                if target.is_ipv4() { "IPv4" } else { "IPv6" },
+                source
+            )
+        })
+}
+
+/// Link state of all interfaces of the system.
+#[cfg(target_os = "linux")]
+pub fn scan() -> HashMap<String, bool> {
+    let Ok(dir) = std::fs::read_dir("/sys/class/net") else {
+        return HashMap::new();
+    };
+    dir.flatten()
+        .map(|entry| {
+            let path = entry.path();
+            let read = |file: &str| {
+                std::fs::read_to_string(path.join(file))
+                    .map(|s| s.trim().to_string())
+                    .unwrap_or_default()
+            };
+            let up = match read("operstate").as_str() {
+                "up" => true,
+                // Loopback and tunnels do not report an operational state
+                "unknown" => {
+                    let flags = read("flags");
+                    u32::from_str_radix(flags.trim_start_matches("0x"), 16)
+                        .is_ok_and(|f| f & 1 != 0)
+                        && read("carrier") != "0"
+                }
+                _ => false,
+            };
+            (entry.file_name().to_string_lossy().into_owned(), up)
+        })
+        .collect()
+}
+
+/// Link state of all interfaces of the system.
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn scan() -> HashMap<String, bool> {
+    std::process::Command::new("ifconfig")
+        .output()
+        .map(|o| parse_ifconfig(&String::from_utf8_lossy(&o.stdout)))
+        .unwrap_or_default()
+}
+
+/// Link state of all interfaces of the system.
+#[cfg(windows)]
+pub fn scan() -> HashMap<String, bool> {
+    use crate::constants::CREATE_NO_WINDOW;
+    use std::os::windows::process::CommandExt;
+    std::process::Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "Get-NetAdapter | ForEach-Object { $_.Name + \"`t\" + $_.Status }",
+        ])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map(|o| parse_adapter_list(&String::from_utf8_lossy(&o.stdout)))
+        .unwrap_or_default()
+}
+
+/// Updates the hosts bound to an interface from a scan.
+///
+/// An interface missing from the scan (e.g. an unplugged USB adapter) counts as down.
+/// Every change is recorded in the event log of the affected hosts.
+pub fn apply(state: &mut AppState, links: HashMap<String, bool>, now: u64) {
+    let AppState {
+        hosts, statuses, ..
+    } = state;
+    for host in hosts.iter() {
+        let Some(status) = statuses.get_mut(&host.address) else {
+            continue;
+        };
+        let name = host.bound_interface();
+        let down = !name.is_empty() && links.get(name) != Some(&true);
+        if status.interface_down == down {
+            continue;
+        }
+        status.interface_down = down;
+        if name.is_empty() {
+            continue;
+        }
+        let message = if down {
+            tr!("Interface {name} down, probes paused")
+        } else {
+            tr!("Interface {name} up, probes resumed")
+        }
+        .replace("{name}", name);
+        applog::info("interfaces", format!("{}: {}", host.address, message));
+        status.events.push_back(LogEntry::Marker {
+            timestamp: now,
+            message,
+        });
+        status.trim_events();
+    }
+    state.interfaces = links;
+}
+
+/// Starts a background scan of the interfaces if one is due.
+///
+/// The first scan always runs so the host settings can offer the interfaces;
+/// later ones only while a host is bound to an interface.
+pub fn spawn_check_if_due(state: &SharedState, last_check: &mut Option<Instant>, now: Instant) {
+    if last_check
+        .is_some_and(|t| now.duration_since(t) < Duration::from_secs(INTERFACE_CHECK_INTERVAL_SEC))
+    {
+        return;
+    }
+    let needed = last_check.is_none() || {
+        let state_lock = state.lock().expect("Failed to lock state for interfaces");
+        state_lock
+            .hosts
+            .iter()
+            .any(|h| !h.bound_interface().is_empty())
+            || state_lock.statuses.values().any(|s| s.interface_down)
+    };
+    *last_check = Some(now);
+    if !needed {
+        return;
+    }
+
+    let state = state.clone();
+    tokio::task::spawn_blocking(move || {
+        let links = scan();
+        let now_ts = chrono::Utc::now().timestamp() as u64;
+        let mut state_lock = state.lock().expect("Failed to lock state for interfaces");
+        apply(&mut state_lock, links, now_ts);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{HostInfo, HostStatus};
+
+    #[test]
+    fn test_parse_ifconfig() {
+        let output = "lo0: flags=8049<UP,LOOPBACK,RUNNING,MULTICAST> mtu 16384\n\
+                      \tinet 127.0.0.1 netmask 0xff000000\n\
+                      en0: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500\n\
+                      \tstatus: active\n\
+                      en1: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500\n\
+                      \tstatus: inactive\n\
+                      utun3: flags=8050<POINTOPOINT,RUNNING,MULTICAST> mtu 1380\n";
+        let links = parse_ifconfig(output);
+        assert_eq!(links.get("lo0"), Some(&true));
+        assert_eq!(links.get("en0"), Some(&true));
+        assert_eq!(links.get("en1"), Some(&false));
+        assert_eq!(links.get("utun3"), Some(&false));
+
+        let links = parse_adapter_list("Ethernet\tUp\r\nWi-Fi 2\tDisconnected\r\n");
+        assert_eq!(links.get("Ethernet"), Some(&true));
+        assert_eq!(links.get("Wi-Fi 2"), Some(&false));
+    }
+
+    #[test]
+    fn test_parse_addresses() {
+        let ip = "3: wlan0    inet 192.168.1.5/24 brd 192.168.1.255 scope global dynamic wlan0\\       valid_lft 85000sec preferred_lft 85000sec\n\
+                  3: wlan0    inet6 fe80::1c2d:3e4f:5a6b:7c8d/64 scope link \\       valid_lft forever preferred_lft forever\n";
+        assert_eq!(
+            parse_addresses(ip),
+            vec![
+                "192.168.1.5".parse::<IpAddr>().unwrap(),
+                "fe80::1c2d:3e4f:5a6b:7c8d".parse().unwrap()
+            ]
+        );
+        let ifconfig = "en0: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500\n\
+                        \tinet6 fe80::8b6:1a2b:3c4d:5e6f%en0 prefixlen 64 secured scopeid 0x6\n\
+                        \tinet 10.0.0.7 netmask 0xffffff00 broadcast 10.0.0.255\n";
+        assert_eq!(
+            parse_addresses(ifconfig),
+            vec![
+                "fe80::8b6:1a2b:3c4d:5e6f".parse::<IpAddr>().unwrap(),
+                "10.0.0.7".parse().unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_interface_down_pauses_and_resumes_host() {
+        let mut state = AppState::default();
+        for (address, interface) in [
+            ("10.0.0.1", "wlan0"),
+            ("10.0.0.2", ""),
+            ("10.0.0.3@wlan0", ""),
+        ] {
+            state.hosts.push(HostInfo {
+                address: address.to_string(),
+                interface: interface.to_string(),
+                ..Default::default()
+            });
+            state
+                .statuses
+                .insert(address.to_string(), HostStatus::default());
+        }
+
+        apply(&mut state, HashMap::from([("wlan0".to_string(), true)]), 1);
+        assert!(!state.statuses["10.0.0.1"].interface_down);
+        assert!(state.statuses["10.0.0.1"].events.is_empty());
+
+        // Unplugged: the interface disappears from the scan
+        apply(&mut state, HashMap::new(), 2);
+        assert!(state.statuses["10.0.0.1"].interface_down);
+        assert!(!state.statuses["10.0.0.2"].interface_down);
+        // An address bound with @ pauses with its interface
+        assert!(state.statuses["10.0.0.3@wlan0"].interface_down);
+        assert!(matches!(
+            state.statuses["10.0.0.1"].events.back(),
+            Some(LogEntry::Marker { timestamp: 2, message }) if message.contains("wlan0 down")
+        ));
+
+        apply(&mut state, HashMap::from([("wlan0".to_string(), true)]), 3);
+        assert!(!state.statuses["10.0.0.1"].interface_down);
+        assert_eq!(state.statuses["10.0.0.1"].events.len(), 2);
+    }
+}
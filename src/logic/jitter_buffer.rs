@@ -0,0 +1,123 @@
+use crate::model::LogEntry;
+
+/// Outcome of replaying recorded pings through a fixed-size jitter buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct JitterBufferResult {
+    /// Number of replayed packets (including timeouts).
+    pub packets: usize,
+    /// Packets that arrived after their playout deadline.
+    pub late: usize,
+    /// Packets that never arrived.
+    pub lost: usize,
+    /// Audible interruptions: runs of consecutive late or lost packets.
+    pub glitches: usize,
+}
+
+/// Replays the pings recorded since `from` through a jitter buffer of `buffer_ms`.
+///
+/// One-way delay is approximated as half the RTT. Playout is scheduled at the
+/// lowest delay in the window plus the buffer size, which is the best a fixed
+/// buffer could do; every packet with a larger delay would be discarded as late.
+pub fn simulate<'a>(
+    events: impl IntoIterator<Item = &'a LogEntry>,
+    from: u64,
+    buffer_ms: f64,
+) -> JitterBufferResult {
+    let delays: Vec<Option<f64>> = events
+        .into_iter()
+        .filter_map(|e| match e {
+            LogEntry::Ping { timestamp, rtt, .. } if *timestamp >= from => {
+                Some(rtt.map(|r| r as f64 / 2.0))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let Some(base) = delays.iter().flatten().copied().reduce(f64::min) else {
+        return JitterBufferResult {
+            packets: delays.len(),
+            lost: delays.len(),
+            glitches: usize::from(!delays.is_empty()),
+            ..Default::default()
+        };
+    };
+
+    let mut result = JitterBufferResult {
+        packets: delays.len(),
+        ..Default::default()
+    };
+    let mut in_glitch = false;
+    for delay in delays {
+        let missed = match delay {
+            None => {
+                result.lost += 1;
+                true
+            }
+            Some(d) if d > base + buffer_ms => {
+                result.late += 1;
+                true
+            }
+            Some(_) => false,
+        };
+        if missed && !in_glitch {
+            result.glitches += 1;
+        }
+        in_glitch = missed;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pings(rtts: &[Option<f32>]) -> Vec<LogEntry> {
+        rtts.iter()
+            .enumerate()
+            .map(|(i, rtt)| LogEntry::Ping {
+                timestamp: 100 + i as u64,
+                seq: i as u32,
+                rtt: *rtt,
+                bytes: 16,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_late_and_lost_packets_form_glitches() {
+        // One-way delays: 10, 10, 50, 60, 10, lost, 10
+        let events = pings(&[
+            Some(20.0),
+            Some(20.0),
+            Some(100.0),
+            Some(120.0),
+            Some(20.0),
+            None,
+            Some(20.0),
+        ]);
+        let r = simulate(&events, 0, 30.0);
+        assert_eq!(r.packets, 7);
+        assert_eq!(r.late, 2);
+        assert_eq!(r.lost, 1);
+        assert_eq!(r.glitches, 2);
+
+        // A larger buffer absorbs the delay spike
+        let r = simulate(&events, 0, 60.0);
+        assert_eq!((r.late, r.glitches), (0, 1));
+    }
+
+    #[test]
+    fn test_window_start_is_respected() {
+        let events = pings(&[Some(200.0), Some(20.0), Some(22.0)]);
+        assert_eq!(simulate(&events, 101, 5.0).late, 0);
+        assert_eq!(simulate(&events, 0, 5.0).late, 1);
+    }
+
+    #[test]
+    fn test_only_timeouts() {
+        let events = pings(&[None, None]);
+        let r = simulate(&events, 0, 40.0);
+        assert_eq!((r.lost, r.glitches), (2, 1));
+        assert_eq!(simulate(&[], 0, 40.0), JitterBufferResult::default());
+    }
+}
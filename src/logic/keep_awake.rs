@@ -0,0 +1,74 @@
+//! Keeps the display on while the wall dashboard is shown, by holding a helper
+//! process of the system that inhibits the screen saver and sleep.
+
+use crate::logic::applog;
+use std::process::{Child, Command, Stdio};
+
+/// The helper command: systemd-inhibit on Linux, caffeinate on macOS and
+/// `SetThreadExecutionState` through PowerShell on Windows. Each lasts until it
+/// is killed, its standard input is closed or this process exits.
+fn inhibit_command() -> Command {
+    let pid = std::process::id().to_string();
+    if cfg!(windows) {
+        let script = format!(
+            "$t = Add-Type -Name Power -Namespace EguiPinger -PassThru -MemberDefinition '[DllImport(\"kernel32.dll\")] public static extern uint SetThreadExecutionState(uint f);'; \
+             [void]$t::SetThreadExecutionState(0x80000003); Wait-Process -Id {}",
+            pid
+        );
+        let mut cmd = Command::new("powershell");
+        cmd.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+        #[cfg(windows)]
+        {
+            use crate::constants::CREATE_NO_WINDOW;
+            use std::os::windows::process::CommandExt;
+            cmd.creation_flags(CREATE_NO_WINDOW);
+        }
+        cmd
+    } else if cfg!(target_os = "macos") {
+        let mut cmd = Command::new("caffeinate");
+        cmd.args(["-d", "-i", "-w", &pid]);
+        cmd
+    } else {
+        let mut cmd = Command::new("systemd-inhibit");
+        cmd.args([
+            "--what=idle:sleep",
+            "--who=egui_pinger",
+            "--why=Wall dashboard",
+            "cat",
+        ]);
+        cmd
+    }
+}
+
+/// Keeps the screen awake until dropped.
+pub struct KeepAwake {
+    child: Option<Child>,
+}
+
+impl KeepAwake {
+    pub fn start() -> Self {
+        let child = inhibit_command()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        match child {
+            Ok(child) => Self { child: Some(child) },
+            Err(e) => {
+                applog::warn("dashboard", format!("Cannot keep the screen awake: {}", e));
+                Self { child: None }
+            }
+        }
+    }
+}
+
+impl Drop for KeepAwake {
+    fn drop(&mut self) {
+        if let Some(child) = &mut self.child {
+            // Closing the input also ends what systemd-inhibit runs
+            drop(child.stdin.take());
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Secrets in the keyring of the operating system, through its command-line tools.
+//!
+//! Linux uses `secret-tool` (libsecret, e.g. GNOME Keyring or KWallet), macOS the
+//! `security` tool of the login keychain and Windows the Credential Locker via PowerShell.
+
+#[cfg(windows)]
+use crate::constants::CREATE_NO_WINDOW;
+use crate::constants::KEYRING_SERVICE;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Runs a tool, passing `input` on stdin; returns the exit code and stdout.
+fn run(mut cmd: Command, input: Option<&str>) -> Result<(i32, String), String> {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.unwrap_or_default().as_bytes())
+            .map_err(|e| e.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    Ok((
+        output.status.code().unwrap_or(-1),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+    ))
+}
+
+fn check((code, _): (i32, String)) -> Result<(), String> {
+    if code == 0 {
+        Ok(())
+    } else {
+        Err(format!("keyring tool failed with exit code {}", code))
+    }
+}
+
+/// A PowerShell command working on the Credential Locker in `$v`.
+#[cfg(windows)]
+fn powershell(script: &str, account: &str) -> Command {
+    let mut cmd = Command::new("powershell");
+    cmd.args(["-NoProfile", "-NonInteractive", "-Command"]).arg(format!(
+        "[void][Windows.Security.Credentials.PasswordVault,Windows.Security.Credentials,ContentType=WindowsRuntime]; \
+         $v = New-Object Windows.Security.Credentials.PasswordVault; \
+         $s = '{}'; $a = '{}'; {}",
+        KEYRING_SERVICE,
+        account.replace('\'', "''"),
+        script
+    ));
+    cmd
+}
+
+/// Stores `secret` for `account`, replacing a previous one.
+pub fn store(account: &str, secret: &str) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut cmd = Command::new("secret-tool");
+        cmd.args(["store", "--label", KEYRING_SERVICE]).args([
+            "service",
+            KEYRING_SERVICE,
+            "account",
+            account,
+        ]);
+        check(run(cmd, Some(secret))?)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        // A trailing -w asks for the password (and its confirmation) on stdin,
+        // keeping it out of the process list
+        let mut cmd = Command::new("security");
+        cmd.args(["add-generic-password", "-U", "-s", KEYRING_SERVICE])
+            .args(["-a", account, "-w"]);
+        check(run(cmd, Some(&format!("{0}\n{0}\n", secret)))?)
+    }
+    #[cfg(windows)]
+    {
+        let cmd = powershell(
+            "$p = [Console]::In.ReadToEnd(); \
+             $v.Add((New-Object Windows.Security.Credentials.PasswordCredential($s, $a, $p)))",
+            account,
+        );
+        check(run(cmd, Some(secret))?)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    {
+        let _ = (account, secret);
+        Err("no keyring available on this system".to_string())
+    }
+}
+
+/// The secret stored for `account`, `None` if there is none.
+pub fn load(account: &str) -> Result<Option<String>, String> {
+    #[cfg(target_os = "linux")]
+    let found = {
+        let mut cmd = Command::new("secret-tool");
+        cmd.args(["lookup", "service", KEYRING_SERVICE, "account", account]);
+        // Exits with 1 when nothing matches
+        match run(cmd, None)? {
+            (0, secret) => Some(secret),
+            (1, secret) if secret.is_empty() => None,
+            result => return check(result).map(|_| None),
+        }
+    };
+    #[cfg(target_os = "macos")]
+    let found = {
+        let mut cmd = Command::new("security");
+        cmd.args(["find-generic-password", "-s", KEYRING_SERVICE])
+            .args(["-a", account, "-w"]);
+        // errSecItemNotFound
+        match run(cmd, None)? {
+            (0, secret) => Some(secret.strip_suffix('\n').unwrap_or(&secret).to_string()),
+            (44, _) => None,
+            result => return check(result).map(|_| None),
+        }
+    };
+    #[cfg(windows)]
+    let found = {
+        let cmd = powershell(
+            "try { $c = $v.Retrieve($s, $a) } catch { exit 0 }; \
+             $c.RetrievePassword(); [Console]::Out.Write($c.Password)",
+            account,
+        );
+        match run(cmd, None)? {
+            (0, secret) => Some(secret),
+            result => return check(result).map(|_| None),
+        }
+    };
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    let found = {
+        let _ = account;
+        None
+    };
+    Ok(found.filter(|s: &String| !s.is_empty()))
+}
+
+/// Removes the secret of `account`; succeeds if there was none.
+pub fn delete(account: &str) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut cmd = Command::new("secret-tool");
+        cmd.args(["clear", "service", KEYRING_SERVICE, "account", account]);
+        check(run(cmd, None)?)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let mut cmd = Command::new("security");
+        cmd.args(["delete-generic-password", "-s", KEYRING_SERVICE])
+            .args(["-a", account]);
+        match run(cmd, None)? {
+            (44, _) => Ok(()),
+            result => check(result),
+        }
+    }
+    #[cfg(windows)]
+    {
+        let cmd = powershell(
+            "try { $v.Remove($v.Retrieve($s, $a)) } catch { exit 0 }",
+            account,
+        );
+        check(run(cmd, None)?)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    {
+        let _ = account;
+        Ok(())
+    }
+}
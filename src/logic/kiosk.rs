@@ -0,0 +1,89 @@
+use ring::rand::SecureRandom;
+use ring::{digest, pbkdf2, rand};
+use std::num::NonZeroU32;
+
+const ITERATIONS: u32 = 100_000;
+const SALT_LEN: usize = 16;
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn iterations() -> NonZeroU32 {
+    NonZeroU32::new(ITERATIONS).expect("iteration count is not zero")
+}
+
+/// Hashes a kiosk password with a random salt (PBKDF2-HMAC-SHA256), as `salt$hash` in hex.
+///
+/// An empty password yields an empty string, which unlocks without asking.
+pub fn hash_password(password: &str) -> String {
+    if password.is_empty() {
+        return String::new();
+    }
+    let mut salt = [0u8; SALT_LEN];
+    rand::SystemRandom::new()
+        .fill(&mut salt)
+        .expect("system random generator failed");
+    let mut hash = [0u8; digest::SHA256_OUTPUT_LEN];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations(),
+        &salt,
+        password.as_bytes(),
+        &mut hash,
+    );
+    format!("{}${}", to_hex(&salt), to_hex(&hash))
+}
+
+/// Checks a password against a hash produced by [`hash_password`].
+pub fn verify_password(stored: &str, password: &str) -> bool {
+    if stored.is_empty() {
+        return true;
+    }
+    let Some((salt, hash)) = stored.split_once('$') else {
+        return false;
+    };
+    let (Some(salt), Some(hash)) = (from_hex(salt), from_hex(hash)) else {
+        return false;
+    };
+    pbkdf2::verify(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations(),
+        &salt,
+        password.as_bytes(),
+        &hash,
+    )
+    .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_roundtrip() {
+        let stored = hash_password("wall display");
+        assert!(verify_password(&stored, "wall display"));
+        assert!(!verify_password(&stored, "wrong"));
+        // Salted: the same password hashes differently each time
+        assert_ne!(stored, hash_password("wall display"));
+    }
+
+    #[test]
+    fn test_empty_and_corrupt_hashes() {
+        assert_eq!(hash_password(""), "");
+        assert!(verify_password("", "anything"));
+        assert!(!verify_password("nonsense", ""));
+        assert!(!verify_password("zz$zz", ""));
+    }
+}
@@ -0,0 +1,340 @@
+//! Friendly names of devices on the local network: the ARP table of the system,
+//! MAC vendors, and names from reverse DNS, mDNS and NetBIOS.
+
+use crate::constants::LAN_NAME_TIMEOUT_MS;
+use crate::logic::dns;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+/// A neighbor from the ARP table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArpEntry {
+    pub ip: Ipv4Addr,
+    /// Lowercase, colon-separated MAC address.
+    pub mac: String,
+}
+
+/// Normalizes a MAC address like `0:1A:2b:3:4:5` or `00-1a-2b-03-04-05` to
+/// `00:1a:2b:03:04:05`; `None` for anything else and for the all-zero and
+/// broadcast addresses of incomplete entries.
+pub fn normalize_mac(text: &str) -> Option<String> {
+    let parts: Vec<&str> = text.split([':', '-']).collect();
+    if parts.len() != 6 {
+        return None;
+    }
+    let bytes = parts
+        .iter()
+        .map(|p| u8::from_str_radix(p, 16).ok().filter(|_| p.len() <= 2))
+        .collect::<Option<Vec<u8>>>()?;
+    if bytes.iter().all(|&b| b == 0) || bytes.iter().all(|&b| b == 0xff) {
+        return None;
+    }
+    Some(
+        bytes
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<_>>()
+            .join(":"),
+    )
+}
+
+/// Adds an entry unless its address is not a unicast one.
+fn push_entry(entries: &mut Vec<ArpEntry>, ip: &str, mac: &str) {
+    let (Ok(ip), Some(mac)) = (ip.parse::<Ipv4Addr>(), normalize_mac(mac)) else {
+        return;
+    };
+    if ip.is_multicast() || ip.is_broadcast() || entries.iter().any(|e| e.ip == ip) {
+        return;
+    }
+    entries.push(ArpEntry { ip, mac });
+}
+
+/// Parses `/proc/net/arp` of Linux.
+pub fn parse_proc_net_arp(text: &str) -> Vec<ArpEntry> {
+    let mut entries = Vec::new();
+    for line in text.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        // IP address, HW type, flags (0x0 for incomplete), HW address, mask, device
+        if let [ip, _, flags, mac, ..] = fields[..]
+            && flags != "0x0"
+        {
+            push_entry(&mut entries, ip, mac);
+        }
+    }
+    entries
+}
+
+/// Parses the output of `arp -a` of macOS/BSD (`? (192.168.1.1) at 0:1a:... on en0`)
+/// and Windows (`  192.168.1.1   00-1a-...   dynamic`).
+pub fn parse_arp_a(text: &str) -> Vec<ArpEntry> {
+    let mut entries = Vec::new();
+    for line in text.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields[..] {
+            [_, ip, "at", mac, ..] => {
+                push_entry(&mut entries, ip.trim_matches(['(', ')']), mac);
+            }
+            [ip, mac, ..] => push_entry(&mut entries, ip, mac),
+            _ => {}
+        }
+    }
+    entries
+}
+
+/// Neighbors the system currently knows.
+pub fn read_arp_table() -> Vec<ArpEntry> {
+    if cfg!(target_os = "linux") {
+        return std::fs::read_to_string("/proc/net/arp")
+            .map(|text| parse_proc_net_arp(&text))
+            .unwrap_or_default();
+    }
+    let mut cmd = std::process::Command::new("arp");
+    cmd.arg("-a");
+    #[cfg(windows)]
+    {
+        use crate::constants::CREATE_NO_WINDOW;
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    cmd.output()
+        .map(|o| parse_arp_a(&String::from_utf8_lossy(&o.stdout)))
+        .unwrap_or_default()
+}
+
+/// Vendor databases installed by other tools: IEEE's oui.txt, Wireshark's manuf
+/// and nmap's prefixes.
+const VENDOR_FILES: &[&str] = &[
+    "/usr/share/ieee-data/oui.txt",
+    "/usr/share/misc/oui.txt",
+    "/usr/share/wireshark/manuf",
+    "/usr/share/nmap/nmap-mac-prefixes",
+    "/usr/local/share/nmap/nmap-mac-prefixes",
+    "/opt/homebrew/share/nmap/nmap-mac-prefixes",
+];
+
+/// Well-known prefixes used when no vendor database is installed.
+const BUILTIN_VENDORS: &[(&str, &str)] = &[
+    ("b827eb", "Raspberry Pi"),
+    ("dca632", "Raspberry Pi"),
+    ("e45f01", "Raspberry Pi"),
+    ("000c29", "VMware"),
+    ("005056", "VMware"),
+    ("080027", "VirtualBox"),
+    ("00155d", "Hyper-V"),
+    ("001c42", "Parallels"),
+    ("525400", "QEMU/KVM"),
+];
+
+/// MAC prefix (first three bytes, lowercase hex) to vendor name.
+#[derive(Debug, Clone, Default)]
+pub struct VendorTable(HashMap<String, String>);
+
+impl VendorTable {
+    /// Parses a vendor database in the oui.txt (`00-1A-11   (hex)  Google, Inc.`),
+    /// manuf (`00:1A:11<TAB>Google<TAB>Google, Inc.`) or nmap (`001A11 Google`)
+    /// format. Longer prefixes of manuf (`/28`, `/36`) are skipped.
+    pub fn parse(text: &str) -> Self {
+        let mut table = HashMap::new();
+        for line in text.lines() {
+            let line = line.trim();
+            let Some((prefix, rest)) = line.split_once(char::is_whitespace) else {
+                continue;
+            };
+            let hex: String = prefix.chars().filter(|c| *c != ':' && *c != '-').collect();
+            if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                continue;
+            }
+            let rest = rest.trim();
+            let name = match rest.strip_prefix("(hex)") {
+                Some(name) => name.trim(),
+                // manuf: short name, then the full name after a tab
+                None => rest.split('\t').next_back().unwrap_or(rest).trim(),
+            };
+            if !name.is_empty() {
+                table
+                    .entry(hex.to_lowercase())
+                    .or_insert_with(|| name.to_string());
+            }
+        }
+        Self(table)
+    }
+
+    /// The first installed vendor database, or the built-in prefixes.
+    pub fn load() -> Self {
+        VENDOR_FILES
+            .iter()
+            .filter_map(|path| std::fs::read_to_string(path).ok())
+            .map(|text| Self::parse(&text))
+            .find(|table| !table.0.is_empty())
+            .unwrap_or_else(|| {
+                Self(
+                    BUILTIN_VENDORS
+                        .iter()
+                        .map(|(p, n)| (p.to_string(), n.to_string()))
+                        .collect(),
+                )
+            })
+    }
+
+    /// Vendor of a normalized MAC address.
+    pub fn vendor(&self, mac: &str) -> Option<String> {
+        let prefix: String = mac.chars().filter(|c| *c != ':').take(6).collect();
+        self.0.get(&prefix).cloned()
+    }
+}
+
+/// Whether a MAC address is locally administered, like the private (randomized)
+/// addresses of phones, so it has no vendor.
+pub fn is_private_mac(mac: &str) -> bool {
+    mac.get(..2)
+        .and_then(|b| u8::from_str_radix(b, 16).ok())
+        .is_some_and(|b| b & 0x02 != 0)
+}
+
+/// NetBIOS node status request for the name `*`.
+fn netbios_query(id: u16) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(50);
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&[0, 0, 0, 1, 0, 0, 0, 0, 0, 0]);
+    // "*" padded with zeros to 16 bytes, each nibble encoded as a letter
+    packet.push(0x20);
+    for byte in std::iter::once(b'*').chain(std::iter::repeat_n(0u8, 15)) {
+        packet.push(b'A' + (byte >> 4));
+        packet.push(b'A' + (byte & 0x0f));
+    }
+    // Root, NBSTAT, IN
+    packet.extend_from_slice(&[0, 0, 0x21, 0, 1]);
+    packet
+}
+
+/// Finds the unique workstation name (suffix 0x00) in a node status response.
+fn parse_netbios_response(response: &[u8]) -> Option<String> {
+    // Header, then the encoded name of the answer
+    let name_len = *response.get(12)? as usize;
+    let count_at = 12 + 1 + name_len + 1 + 10;
+    let count = *response.get(count_at)? as usize;
+    (0..count).find_map(|i| {
+        let entry = response.get(count_at + 1 + i * 18..count_at + 1 + (i + 1) * 18)?;
+        let group = entry[16] & 0x80 != 0;
+        (entry[15] == 0 && !group)
+            .then(|| String::from_utf8_lossy(&entry[..15]).trim().to_string())
+            .filter(|name| !name.is_empty())
+    })
+}
+
+/// Asks a Windows machine or Samba server for its NetBIOS name.
+pub async fn netbios_name(ip: Ipv4Addr) -> Option<String> {
+    let socket = tokio::net::UdpSocket::bind("0.0.0.0:0").await.ok()?;
+    socket.connect((ip, 137)).await.ok()?;
+    let id: u16 = rand::random();
+    socket.send(&netbios_query(id)).await.ok()?;
+    let mut buf = [0u8; 1024];
+    let timeout = Duration::from_millis(LAN_NAME_TIMEOUT_MS);
+    let len = tokio::time::timeout(timeout, socket.recv(&mut buf))
+        .await
+        .ok()?
+        .ok()?;
+    let response = &buf[..len];
+    (response.get(..2)? == id.to_be_bytes()).then_some(())?;
+    parse_netbios_response(response)
+}
+
+/// Name of a device on the local network: its reverse DNS name at the system
+/// resolver, else the `.local` name its mDNS responder gives for its address
+/// (asked directly on port 5353), else its NetBIOS name.
+pub async fn device_name(ip: Ipv4Addr, resolver: Option<SocketAddr>) -> Option<String> {
+    if let Some(resolver) = resolver
+        && let Some(name) = dns::reverse_lookup(IpAddr::V4(ip), resolver).await
+    {
+        return Some(name);
+    }
+    let mdns = SocketAddr::new(IpAddr::V4(ip), 5353);
+    if let Some(name) = dns::reverse_lookup(IpAddr::V4(ip), mdns).await {
+        return Some(name);
+    }
+    netbios_name(ip).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_arp_tables() {
+        let linux = "IP address       HW type     Flags       HW address            Mask     Device\n\
+                     192.168.1.1      0x1         0x2         a0:b1:c2:d3:e4:f5     *        eth0\n\
+                     192.168.1.9      0x1         0x0         00:00:00:00:00:00     *        eth0\n";
+        assert_eq!(
+            parse_proc_net_arp(linux),
+            vec![ArpEntry {
+                ip: Ipv4Addr::new(192, 168, 1, 1),
+                mac: "a0:b1:c2:d3:e4:f5".to_string()
+            }]
+        );
+
+        let macos = "? (192.168.1.20) at b8:27:eb:1:2:3 on en0 ifscope [ethernet]\n\
+                     ? (192.168.1.30) at (incomplete) on en0 ifscope [ethernet]\n\
+                     ? (224.0.0.251) at 1:0:5e:0:0:fb on en0 ifscope permanent [ethernet]\n";
+        let entries = parse_arp_a(macos);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].mac, "b8:27:eb:01:02:03");
+
+        let windows = "\nInterface: 192.168.1.5 --- 0xb\n  Internet Address      Physical Address      Type\n  \
+                       192.168.1.1           a0-b1-c2-d3-e4-f5     dynamic\n  \
+                       192.168.1.255         ff-ff-ff-ff-ff-ff     static\n";
+        let entries = parse_arp_a(windows);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].ip, Ipv4Addr::new(192, 168, 1, 1));
+    }
+
+    #[test]
+    fn test_vendor_table() {
+        let oui = "OUI/MA-L                                                    Organization\n\
+                   B8-27-EB   (hex)\t\tRaspberry Pi Foundation\n\
+                   B827EB     (base 16)\t\tRaspberry Pi Foundation\n";
+        let manuf =
+            "00:1A:11\tGoogle\tGoogle, Inc.\n00:1B:C5:00:00/36\tConverging\tConverging Systems\n";
+        let nmap = "001132 Synology Incorporated\n";
+        assert_eq!(
+            VendorTable::parse(oui).vendor("b8:27:eb:01:02:03"),
+            Some("Raspberry Pi Foundation".to_string())
+        );
+        let table = VendorTable::parse(manuf);
+        assert_eq!(
+            table.vendor("00:1a:11:00:00:01"),
+            Some("Google, Inc.".to_string())
+        );
+        assert_eq!(table.0.len(), 1);
+        assert_eq!(
+            VendorTable::parse(nmap).vendor("00:11:32:aa:bb:cc"),
+            Some("Synology Incorporated".to_string())
+        );
+        assert_eq!(VendorTable::default().vendor("00:11:32:aa:bb:cc"), None);
+        assert!(is_private_mac("da:a1:19:00:00:01"));
+        assert!(!is_private_mac("00:11:32:aa:bb:cc"));
+    }
+
+    #[test]
+    fn test_netbios_response() {
+        let mut response = netbios_query(9);
+        response[2] = 0x84;
+        // Answer instead of question: drop QDCOUNT, set ANCOUNT
+        response[5] = 0;
+        response[7] = 1;
+        response.truncate(12 + 34);
+        response.extend_from_slice(&[0, 0x21, 0, 1, 0, 0, 0, 0, 0, 56, 2]);
+        let mut entry = |name: &str, suffix: u8, flags: u8| {
+            let mut padded = format!("{:<15}", name).into_bytes();
+            padded.extend_from_slice(&[suffix, flags, 0]);
+            response.extend_from_slice(&padded);
+        };
+        entry("WORKGROUP", 0, 0x80);
+        entry("OFFICE-PC", 0, 0x04);
+        assert_eq!(
+            parse_netbios_response(&response),
+            Some("OFFICE-PC".to_string())
+        );
+        assert_eq!(netbios_query(1).len(), 50);
+    }
+}
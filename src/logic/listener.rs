@@ -0,0 +1,160 @@
+//! TCP listeners of the servers (statistics stream, background daemon, remote
+//! agent, JSON API): the accept loop in a background thread and the following
+//! of the settings.
+
+use crate::logic::applog;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// A running listener; dropping it stops the listener and, through the flag
+/// their handlers get, all connections.
+pub struct Listener<K> {
+    /// Settings the listener was started for; it is restarted when they change.
+    pub key: K,
+    /// Port actually listened on (differs from the requested one only for port 0).
+    pub local_port: Option<u16>,
+    stop: Arc<AtomicBool>,
+}
+
+impl<K> Listener<K> {
+    /// Listens on `ip:port` in a background thread and runs `serve` in a thread of
+    /// its own for every connection, with a flag that is set once the listener
+    /// stops. `target` names the server in the application log.
+    pub fn start(
+        target: &'static str,
+        ip: Ipv4Addr,
+        port: u16,
+        key: K,
+        serve: impl Fn(TcpStream, SocketAddr, Arc<AtomicBool>) + Send + Sync + 'static,
+    ) -> std::io::Result<Self> {
+        let listener = TcpListener::bind((IpAddr::V4(ip), port))?;
+        listener.set_nonblocking(true)?;
+        let local_port = listener.local_addr().ok().map(|a| a.port());
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = stop.clone();
+        let serve = Arc::new(serve);
+        std::thread::spawn(move || {
+            while !stop_flag.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((client, peer)) => {
+                        let _ = client.set_nonblocking(false);
+                        let serve = serve.clone();
+                        let stop = stop_flag.clone();
+                        std::thread::spawn(move || serve(client, peer, stop));
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        std::thread::sleep(Duration::from_millis(100));
+                    }
+                    Err(e) => {
+                        applog::warn(target, format!("Accept failed: {}", e));
+                        std::thread::sleep(Duration::from_secs(1));
+                    }
+                }
+            }
+        });
+        applog::info(target, format!("Listening on {}:{}", ip, port));
+        Ok(Self {
+            key,
+            local_port,
+            stop,
+        })
+    }
+
+    /// Stand-in for a listener that could not start.
+    fn failed(key: K) -> Self {
+        Self {
+            key,
+            local_port: None,
+            stop: Arc::new(AtomicBool::new(true)),
+        }
+    }
+}
+
+impl<K> Drop for Listener<K> {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Starts, restarts or stops a listener to follow the settings: `wanted` is the
+/// key of the settings it should run with, `None` while it is disabled.
+///
+/// A port that cannot be bound is not retried until the settings change. Returns
+/// the reason it failed when it was (re)started or stopped, for the settings
+/// window, and `None` when nothing changed.
+pub fn follow<K: PartialEq>(
+    target: &'static str,
+    listener: &mut Option<Listener<K>>,
+    wanted: Option<K>,
+    port: u16,
+    start: impl FnOnce() -> std::io::Result<Listener<K>>,
+) -> Option<Option<String>> {
+    let Some(wanted) = wanted else {
+        if listener.take().is_some() {
+            applog::info(target, "Stopped");
+        }
+        return Some(None);
+    };
+    if listener.as_ref().is_some_and(|l| l.key == wanted) {
+        return None;
+    }
+    *listener = None;
+    match start() {
+        Ok(started) => {
+            *listener = Some(started);
+            Some(None)
+        }
+        Err(e) => {
+            applog::warn(target, format!("Cannot listen on port {}: {}", port, e));
+            *listener = Some(Listener::failed(wanted));
+            Some(Some(e.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+
+    #[test]
+    fn test_listener_follows_the_settings() {
+        let serve = |mut client: TcpStream, _: SocketAddr, _: Arc<AtomicBool>| {
+            let _ = client.write_all(b"hi");
+        };
+        let start = || Listener::start("test", Ipv4Addr::LOCALHOST, 0, 1, serve);
+        let mut listener = None;
+        assert_eq!(follow("test", &mut listener, Some(1), 0, start), Some(None));
+        let port = listener.as_ref().and_then(|l| l.local_port).unwrap();
+        let mut reply = String::new();
+        TcpStream::connect((Ipv4Addr::LOCALHOST, port))
+            .unwrap()
+            .read_to_string(&mut reply)
+            .unwrap();
+        assert_eq!(reply, "hi");
+
+        // Unchanged settings leave it running
+        assert_eq!(
+            follow("test", &mut listener, Some(1), 0, || unreachable!()),
+            None
+        );
+        // A port in use is reported once, not retried
+        let taken = || Listener::start("test", Ipv4Addr::LOCALHOST, port, 2, serve);
+        let mut other = None;
+        assert!(matches!(
+            follow("test", &mut other, Some(2), port, taken),
+            Some(Some(_))
+        ));
+        assert_eq!(
+            follow("test", &mut other, Some(2), port, || unreachable!()),
+            None
+        );
+        assert_eq!(
+            follow("test", &mut listener, None, 0, || unreachable!()),
+            Some(None)
+        );
+        assert!(listener.is_none());
+    }
+}
@@ -0,0 +1,267 @@
+use crate::constants::{LIVE_TRACE_INTERVAL_MS, LIVE_TRACE_MAX_TTL, LIVE_TRACE_TIMEOUT_MS};
+use crate::logic::{applog, dns, tracer};
+use ping_async::{IcmpEchoRequestor, IcmpEchoStatus};
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Live latency and loss statistics of one hop.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HopStats {
+    pub address: String,
+    pub sent: u32,
+    pub lost: u32,
+    /// RTT of the last probe in ms, `None` if it was lost.
+    pub last: Option<f64>,
+    pub best: f64,
+    pub worst: f64,
+    sum: f64,
+}
+
+impl HopStats {
+    pub fn new(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Records a probe result; `rtt` is `None` for a lost probe.
+    pub fn record(&mut self, rtt: Option<f64>) {
+        self.sent += 1;
+        self.last = rtt;
+        match rtt {
+            Some(rtt) => {
+                let received = self.sent - self.lost;
+                self.best = if received == 1 {
+                    rtt
+                } else {
+                    self.best.min(rtt)
+                };
+                self.worst = self.worst.max(rtt);
+                self.sum += rtt;
+            }
+            None => self.lost += 1,
+        }
+    }
+
+    pub fn loss_pct(&self) -> f64 {
+        if self.sent == 0 {
+            0.0
+        } else {
+            self.lost as f64 / self.sent as f64 * 100.0
+        }
+    }
+
+    /// Average RTT in ms of the answered probes.
+    pub fn avg(&self) -> Option<f64> {
+        let received = self.sent - self.lost;
+        (received > 0).then(|| self.sum / received as f64)
+    }
+}
+
+/// Returns the hop that adds the most average latency compared with the
+/// previous answering hop, together with the added latency in ms.
+pub fn worst_hop(hops: &[HopStats]) -> Option<(usize, f64)> {
+    let mut prev = 0.0;
+    let mut worst: Option<(usize, f64)> = None;
+    for (i, hop) in hops.iter().enumerate() {
+        let Some(avg) = hop.avg() else {
+            continue;
+        };
+        let added = avg - prev;
+        if added > 0.0 && worst.is_none_or(|(_, w)| added > w) {
+            worst = Some((i, added));
+        }
+        prev = avg;
+    }
+    worst
+}
+
+/// State of a live trace shared with the UI.
+#[derive(Debug, Clone, Default)]
+pub struct TraceProgress {
+    /// The path is still being discovered.
+    pub discovering: bool,
+    /// Number of hops to the target found by TTL probing, if it answered.
+    pub distance: Option<u8>,
+    pub hops: Vec<HopStats>,
+    pub error: Option<String>,
+}
+
+/// Probes every hop of the route to a host once per interval until stopped.
+pub struct LiveTrace {
+    pub target: String,
+    progress: Arc<Mutex<TraceProgress>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl LiveTrace {
+    /// Starts tracing `target` on a background thread.
+    pub fn start(target: String) -> Self {
+        let progress = Arc::new(Mutex::new(TraceProgress {
+            discovering: true,
+            ..Default::default()
+        }));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (thread_target, thread_progress, thread_stop) =
+            (target.clone(), progress.clone(), stop.clone());
+        std::thread::spawn(move || {
+            match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(rt) => rt.block_on(run(thread_target, thread_progress, thread_stop)),
+                Err(e) => applog::error("trace", format!("Failed to start trace runtime: {}", e)),
+            }
+        });
+        Self {
+            target,
+            progress,
+            stop,
+        }
+    }
+
+    /// Returns a copy of the current progress.
+    pub fn progress(&self) -> TraceProgress {
+        self.progress.lock().map(|p| p.clone()).unwrap_or_default()
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for LiveTrace {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Sends one echo request, returning the RTT in ms or `None` if it was lost.
+pub(crate) async fn probe(requestor: &IcmpEchoRequestor) -> Option<f64> {
+    match requestor.send().await {
+        Ok(reply) if reply.status() == IcmpEchoStatus::Success => {
+            Some(reply.round_trip_time().as_secs_f64() * 1000.0)
+        }
+        _ => None,
+    }
+}
+
+/// Finds the number of hops to `ip` by sending echo requests with increasing TTL.
+///
+/// Routers answer expired requests with ICMP Time Exceeded, which the ping
+/// backend reports without the router's address, so hop addresses come from
+/// the system traceroute instead.
+async fn hop_distance(ip: IpAddr) -> Option<u8> {
+    let timeout = Some(Duration::from_millis(LIVE_TRACE_TIMEOUT_MS));
+    let mut probes = tokio::task::JoinSet::new();
+    for ttl in 1..=LIVE_TRACE_MAX_TTL {
+        // A TTL the backend refuses only leaves out that hop
+        let Ok(requestor) = IcmpEchoRequestor::new(ip, None, Some(ttl), timeout) else {
+            continue;
+        };
+        probes.spawn(async move { probe(&requestor).await.map(|_| ttl) });
+    }
+    let mut distance = None;
+    while let Some(result) = probes.join_next().await {
+        if let Ok(Some(ttl)) = result {
+            distance = Some(distance.map_or(ttl, |d: u8| d.min(ttl)));
+        }
+    }
+    distance
+}
+
+async fn run(target: String, progress: Arc<Mutex<TraceProgress>>, stop: Arc<AtomicBool>) {
+    let set_error = |e: String| {
+        if let Ok(mut p) = progress.lock() {
+            p.discovering = false;
+            p.error = Some(e);
+        }
+    };
+
+    let ip = match dns::strip_brackets(&target).parse::<IpAddr>() {
+        Ok(ip) => Some(ip),
+        Err(_) => dns::resolve(&target).await.first().copied(),
+    };
+    let Some(ip) = ip else {
+        set_error(format!("Cannot resolve {}", target));
+        return;
+    };
+
+    let ip_text = ip.to_string();
+    let (mut path, distance) = tokio::join!(tracer::run_traceroute(&ip_text), hop_distance(ip));
+    if path.last() != Some(&ip_text) {
+        path.push(ip_text);
+    }
+    let timeout = Some(Duration::from_millis(LIVE_TRACE_TIMEOUT_MS));
+    let requestors: Vec<Option<IcmpEchoRequestor>> = path
+        .iter()
+        .map(|hop| {
+            let hop_ip = hop.parse::<IpAddr>().ok()?;
+            IcmpEchoRequestor::new(hop_ip, None, None, timeout).ok()
+        })
+        .collect();
+    if requestors.iter().all(Option::is_none) {
+        set_error("Cannot send ICMP requests".to_string());
+        return;
+    }
+    if let Ok(mut p) = progress.lock() {
+        p.discovering = false;
+        p.distance = distance;
+        p.hops = path.iter().map(|hop| HopStats::new(hop)).collect();
+    }
+
+    while !stop.load(Ordering::Relaxed) {
+        let started = Instant::now();
+        let results = futures::future::join_all(requestors.iter().map(|r| async move {
+            match r {
+                Some(r) => probe(r).await,
+                None => None,
+            }
+        }))
+        .await;
+        if let Ok(mut p) = progress.lock() {
+            for (hop, rtt) in p.hops.iter_mut().zip(results) {
+                hop.record(rtt);
+            }
+        }
+        let interval = Duration::from_millis(LIVE_TRACE_INTERVAL_MS);
+        tokio::time::sleep(interval.saturating_sub(started.elapsed())).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hop_stats() {
+        let mut hop = HopStats::new("10.0.0.1");
+        assert_eq!(hop.avg(), None);
+        hop.record(None);
+        hop.record(Some(20.0));
+        hop.record(Some(10.0));
+        hop.record(Some(30.0));
+        assert_eq!((hop.sent, hop.lost), (4, 1));
+        assert_eq!(hop.loss_pct(), 25.0);
+        assert_eq!(hop.avg(), Some(20.0));
+        assert_eq!((hop.best, hop.worst), (10.0, 30.0));
+        assert_eq!(hop.last, Some(30.0));
+    }
+
+    #[test]
+    fn test_worst_hop_skips_silent_hops() {
+        let mut hops: Vec<HopStats> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|a| HopStats::new(a))
+            .collect();
+        hops[0].record(Some(1.0));
+        hops[1].record(None);
+        hops[2].record(Some(40.0));
+        hops[3].record(Some(42.0));
+        assert_eq!(worst_hop(&hops), Some((2, 39.0)));
+        assert_eq!(worst_hop(&[]), None);
+    }
+}
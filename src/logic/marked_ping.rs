@@ -0,0 +1,243 @@
+//! ICMP echo with a DSCP marking, a custom payload or the don't-fragment flag,
+//! which `ping_async` cannot set.
+
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+const ECHO_REQUEST_V4: u8 = 8;
+const ECHO_REPLY_V4: u8 = 0;
+const ECHO_REQUEST_V6: u8 = 128;
+const ECHO_REPLY_V6: u8 = 129;
+
+/// Internet checksum (RFC 1071).
+fn checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data
+        .chunks(2)
+        .map(|c| u16::from_be_bytes([c[0], *c.get(1).unwrap_or(&0)]) as u32)
+        .sum();
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// Builds an echo request. The kernel fills in the ICMPv6 checksum itself.
+fn echo_request(ipv6: bool, identifier: u16, seq: u16, payload: &[u8]) -> Vec<u8> {
+    let mut packet = vec![0u8; 8];
+    packet.extend_from_slice(payload);
+    packet[0] = if ipv6 {
+        ECHO_REQUEST_V6
+    } else {
+        ECHO_REQUEST_V4
+    };
+    packet[4..6].copy_from_slice(&identifier.to_be_bytes());
+    packet[6..8].copy_from_slice(&seq.to_be_bytes());
+    if !ipv6 {
+        let sum = checksum(&packet);
+        packet[2..4].copy_from_slice(&sum.to_be_bytes());
+    }
+    packet
+}
+
+/// Whether `data` is the reply to the request with `seq`.
+///
+/// Some systems (macOS) deliver ICMPv4 with the IP header in front.
+fn is_echo_reply(data: &[u8], ipv6: bool, seq: u16) -> bool {
+    let icmp = if !ipv6 && data.first().is_some_and(|b| b >> 4 == 4) {
+        let header_len = ((data[0] & 0x0f) as usize) * 4;
+        data.get(header_len..).unwrap_or_default()
+    } else {
+        data
+    };
+    let reply = if ipv6 { ECHO_REPLY_V6 } else { ECHO_REPLY_V4 };
+    icmp.len() >= 8 && icmp[0] == reply && icmp[6..8] == seq.to_be_bytes()
+}
+
+/// Opens an ICMP datagram socket to `ip` with `dscp` and `ttl` set.
+#[cfg(unix)]
+fn open_socket(ip: IpAddr, ttl: Option<u8>, dscp: u8) -> io::Result<socket2::Socket> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let tos = (dscp as u32) << 2;
+    let socket = match ip {
+        IpAddr::V4(_) => {
+            let s = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::ICMPV4))?;
+            s.set_tos_v4(tos)?;
+            if let Some(ttl) = ttl {
+                s.set_ttl_v4(ttl as u32)?;
+            }
+            s
+        }
+        IpAddr::V6(_) => {
+            let s = Socket::new(Domain::IPV6, Type::DGRAM, Some(Protocol::ICMPV6))?;
+            s.set_tclass_v6(tos)?;
+            if let Some(ttl) = ttl {
+                s.set_unicast_hops_v6(ttl as u32)?;
+            }
+            s
+        }
+    };
+    Ok(socket)
+}
+
+/// Sets the don't-fragment flag, so a packet too large for a link on the path is
+/// dropped instead of split. Linux also stops using its cached path MTU, which
+/// would split large packets before they are sent.
+#[cfg(unix)]
+fn set_dont_fragment(socket: &socket2::Socket, ipv6: bool) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    let (level, name, value) = if ipv6 {
+        (
+            libc::IPPROTO_IPV6,
+            libc::IPV6_MTU_DISCOVER,
+            libc::IPV6_PMTUDISC_PROBE,
+        )
+    } else {
+        (
+            libc::IPPROTO_IP,
+            libc::IP_MTU_DISCOVER,
+            libc::IP_PMTUDISC_PROBE,
+        )
+    };
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    let (level, name, value) = if ipv6 {
+        (libc::IPPROTO_IPV6, libc::IPV6_DONTFRAG, 1)
+    } else {
+        (libc::IPPROTO_IP, libc::IP_DONTFRAG, 1)
+    };
+    let value: libc::c_int = value;
+    // SAFETY: the option value is a c_int that outlives the call, and its size is passed
+    let result = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            level,
+            name,
+            (&value as *const libc::c_int).cast(),
+            std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Sends one echo request carrying `payload` on `socket` and waits for the reply.
+#[cfg(unix)]
+async fn exchange(
+    socket: socket2::Socket,
+    ip: IpAddr,
+    payload: &[u8],
+    timeout: Duration,
+) -> io::Result<Option<Duration>> {
+    socket.set_nonblocking(true)?;
+    let socket = tokio::net::UdpSocket::from_std(socket.into())?;
+    socket.connect(SocketAddr::new(ip, 0)).await?;
+
+    let seq: u16 = rand::random();
+    let packet = echo_request(ip.is_ipv6(), rand::random(), seq, payload);
+    let start = Instant::now();
+    socket.send(&packet).await?;
+
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut buf = vec![0u8; packet.len() + 128];
+    loop {
+        match tokio::time::timeout_at(deadline, socket.recv(&mut buf)).await {
+            Err(_) => return Ok(None),
+            Ok(Err(e)) => return Err(e),
+            Ok(Ok(n)) if is_echo_reply(&buf[..n], ip.is_ipv6(), seq) => {
+                return Ok(Some(start.elapsed()));
+            }
+            Ok(Ok(_)) => continue,
+        }
+    }
+}
+
+/// Sends one echo request carrying `payload` with `dscp` in the ToS / traffic class
+/// field, from the local address `source` if given.
+///
+/// Returns the round-trip time, or `None` when no reply came within `timeout`.
+#[cfg(unix)]
+pub async fn send(
+    ip: IpAddr,
+    source: Option<IpAddr>,
+    ttl: Option<u8>,
+    dscp: u8,
+    payload: &[u8],
+    timeout: Duration,
+) -> io::Result<Option<Duration>> {
+    let socket = open_socket(ip, ttl, dscp)?;
+    if let Some(source) = source {
+        socket.bind(&SocketAddr::new(source, 0).into())?;
+    }
+    exchange(socket, ip, payload, timeout).await
+}
+
+/// Sends one echo request carrying `payload` that must not be fragmented.
+///
+/// Returns `None` when no reply came within `timeout`; a packet larger than the
+/// MTU of the outgoing interface fails with `EMSGSIZE` instead.
+#[cfg(unix)]
+pub async fn send_unfragmented(
+    ip: IpAddr,
+    payload: &[u8],
+    timeout: Duration,
+) -> io::Result<Option<Duration>> {
+    let socket = open_socket(ip, None, 0)?;
+    set_dont_fragment(&socket, ip.is_ipv6())?;
+    exchange(socket, ip, payload, timeout).await
+}
+
+/// Windows has no unprivileged ICMP sockets to mark.
+#[cfg(windows)]
+pub async fn send(
+    _ip: IpAddr,
+    _source: Option<IpAddr>,
+    _ttl: Option<u8>,
+    _dscp: u8,
+    _payload: &[u8],
+    _timeout: Duration,
+) -> io::Result<Option<Duration>> {
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_echo_request_checksum() {
+        let packet = echo_request(false, 0x1234, 7, &[0; 32]);
+        assert_eq!(packet[0], ECHO_REQUEST_V4);
+        // A packet including its checksum sums to zero
+        assert_eq!(checksum(&packet), 0);
+        assert_eq!(&packet[6..8], &[0, 7]);
+
+        let packet = echo_request(true, 0x1234, 7, &[0; 32]);
+        assert_eq!(packet[0], ECHO_REQUEST_V6);
+        assert_eq!(&packet[2..4], &[0, 0]);
+    }
+
+    #[test]
+    fn test_is_echo_reply() {
+        let mut reply = echo_request(false, 1, 42, b"payload");
+        reply[0] = ECHO_REPLY_V4;
+        assert!(is_echo_reply(&reply, false, 42));
+        assert!(!is_echo_reply(&reply, false, 43));
+
+        // Same reply behind a 20-byte IPv4 header
+        let mut with_header = vec![0x45];
+        with_header.extend_from_slice(&[0u8; 19]);
+        with_header.extend_from_slice(&reply);
+        assert!(is_echo_reply(&with_header, false, 42));
+
+        let mut reply = echo_request(true, 1, 42, b"payload");
+        reply[0] = ECHO_REPLY_V6;
+        assert!(is_echo_reply(&reply, true, 42));
+        assert!(!is_echo_reply(&reply[..6], true, 42));
+    }
+}
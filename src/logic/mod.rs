@@ -1,4 +1,65 @@
+pub mod actions;
+pub mod agent;
+pub mod alerts;
+pub mod api;
+pub mod applog;
+pub mod badge;
+pub mod baseline;
+pub mod budget;
+pub mod bundle;
+pub mod connectivity;
+pub mod daemon;
+pub mod dhcp;
+pub mod diagnostics;
+pub mod dns;
+pub mod email;
+pub mod exec_probe;
+pub mod export;
+pub mod host_import;
+pub mod host_list;
+pub mod host_order;
+pub mod http;
+pub mod http_probe;
+pub mod i18n;
+pub mod import;
+pub mod interfaces;
+pub mod jitter_buffer;
+pub mod keep_awake;
+pub mod keyring;
+pub mod kiosk;
+pub mod lan_discovery;
+pub mod listener;
+pub mod live_trace;
+pub mod marked_ping;
+pub mod mqtt;
+pub mod netconfig;
+pub mod netinfo;
+pub mod notify;
+pub mod pairs;
 pub mod pinger;
+pub mod presenter;
+pub mod probe_limit;
+pub mod profiles;
+pub mod report;
+pub mod root_cause;
+pub mod scheduler;
+pub mod screenshot;
+pub mod shutdown;
+#[cfg(test)]
+pub(crate) mod simulation;
+pub mod sla;
+pub mod snmp;
+pub mod sound;
+pub mod storage;
+pub mod stream;
+pub mod subnet_scan;
+pub mod summary;
+pub mod trace_context;
 pub mod tracer;
+pub mod tui;
+pub mod udp_probe;
+pub mod updates;
+pub mod watchdog;
+pub mod webhook;
 
 pub use pinger::{SharedState, pinger_task};
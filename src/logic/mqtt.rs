@@ -0,0 +1,748 @@
+//! Publishing of the host states and latency to an MQTT broker (MQTT 3.1.1,
+//! QoS 0), with Home Assistant discovery payloads.
+//!
+//! Every host has a retained JSON state at `<prefix>/<host>/state`, published at
+//! once when the host goes up or down and otherwise every few seconds set in the
+//! settings. `<prefix>/status` is `online` while connected and `offline` (the
+//! broker's last will) otherwise. Hosts are named in topics by their address with
+//! everything but letters and digits replaced by `_`; of hosts that end up with the
+//! same name only the first is published.
+//!
+//! The password is only sent over TLS.
+
+use crate::constants::{MQTT_KEEP_ALIVE_SEC, MQTT_RETRY_SEC, MQTT_TIMEOUT_SEC};
+use crate::logic::{SharedState, applog, email, keyring};
+use crate::model::MqttSettings;
+use crate::model::snapshot::HostSnapshot;
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const PINGREQ: u8 = 0xc0;
+const DISCONNECT: u8 = 0xe0;
+const RETAIN: u8 = 0x01;
+
+/// Appends the variable-length "remaining length" of a packet.
+fn encode_length(buf: &mut Vec<u8>, mut length: usize) {
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        buf.push(byte);
+        if length == 0 {
+            break;
+        }
+    }
+}
+
+/// Appends a string with its 16-bit length.
+fn encode_str(buf: &mut Vec<u8>, value: &[u8]) {
+    buf.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    buf.extend_from_slice(value);
+}
+
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut buf = vec![header];
+    encode_length(&mut buf, body.len());
+    buf.extend_from_slice(body);
+    buf
+}
+
+/// CONNECT with a clean session and `will` as the retained last will.
+fn connect_packet(
+    client_id: &str,
+    will: (&str, &str),
+    username: &str,
+    password: Option<&str>,
+) -> Vec<u8> {
+    let mut flags = 0x02 | 0x04 | 0x20;
+    if !username.is_empty() {
+        flags |= 0x80;
+        if password.is_some() {
+            flags |= 0x40;
+        }
+    }
+    let mut body = Vec::new();
+    encode_str(&mut body, b"MQTT");
+    body.push(4);
+    body.push(flags);
+    body.extend_from_slice(&MQTT_KEEP_ALIVE_SEC.to_be_bytes());
+    encode_str(&mut body, client_id.as_bytes());
+    encode_str(&mut body, will.0.as_bytes());
+    encode_str(&mut body, will.1.as_bytes());
+    if !username.is_empty() {
+        encode_str(&mut body, username.as_bytes());
+        if let Some(password) = password {
+            encode_str(&mut body, password.as_bytes());
+        }
+    }
+    packet(CONNECT, &body)
+}
+
+/// PUBLISH with QoS 0 and the retain flag, so new subscribers get the last value.
+fn publish_packet(topic: &str, payload: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    encode_str(&mut body, topic.as_bytes());
+    body.extend_from_slice(payload);
+    packet(PUBLISH | RETAIN, &body)
+}
+
+/// Takes the first packet off `buffer` once it is complete, returning its first
+/// byte and its body.
+fn take_packet(buffer: &mut Vec<u8>) -> std::io::Result<Option<(u8, Vec<u8>)>> {
+    let mut length = 0usize;
+    let mut start = None;
+    for (i, byte) in buffer.iter().skip(1).take(4).enumerate() {
+        length |= usize::from(byte & 0x7f) << (i * 7);
+        if byte & 0x80 == 0 {
+            start = Some(i + 2);
+            break;
+        }
+    }
+    let Some(start) = start else {
+        if buffer.len() > 4 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "malformed packet length",
+            ));
+        }
+        return Ok(None);
+    };
+    if buffer.len() < start + length {
+        return Ok(None);
+    }
+    let body = buffer[start..start + length].to_vec();
+    let header = buffer[0];
+    buffer.drain(..start + length);
+    Ok(Some((header, body)))
+}
+
+/// Reads a packet, returning its first byte and its body.
+///
+/// Bytes of a packet that is not complete when the read times out stay in
+/// `buffer` for the next call, so a slow packet does not break the stream.
+fn read_packet(stream: &mut impl Read, buffer: &mut Vec<u8>) -> std::io::Result<(u8, Vec<u8>)> {
+    let mut chunk = [0u8; 4096];
+    loop {
+        if let Some(packet) = take_packet(buffer)? {
+            return Ok(packet);
+        }
+        match stream.read(&mut chunk)? {
+            0 => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+            n => buffer.extend_from_slice(&chunk[..n]),
+        }
+    }
+}
+
+/// Connection to the broker, plain or with TLS.
+enum Connection {
+    Plain(TcpStream),
+    Tls(Box<rustls::StreamOwned<rustls::ClientConnection, TcpStream>>),
+}
+
+impl Connection {
+    fn set_read_timeout(&self, timeout: Duration) -> std::io::Result<()> {
+        match self {
+            Connection::Plain(stream) => stream.set_read_timeout(Some(timeout)),
+            Connection::Tls(stream) => stream.sock.set_read_timeout(Some(timeout)),
+        }
+    }
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Connection::Plain(stream) => stream.read(buf),
+            Connection::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Connection::Plain(stream) => stream.write(buf),
+            Connection::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Connection::Plain(stream) => stream.flush(),
+            Connection::Tls(stream) => stream.flush(),
+        }
+    }
+}
+
+/// Name of a host in topics and discovery ids.
+pub fn topic_id(address: &str) -> String {
+    address
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Pairs the hosts with their topic ids. Hosts whose id is taken by an earlier
+/// host are left out; their addresses are returned separately.
+fn with_topic_ids(hosts: Vec<HostSnapshot>) -> (Vec<(String, HostSnapshot)>, Vec<String>) {
+    let mut named: Vec<(String, HostSnapshot)> = Vec::new();
+    let mut left_out = Vec::new();
+    for host in hosts {
+        let id = topic_id(&host.address);
+        if named.iter().any(|(taken, _)| *taken == id) {
+            left_out.push(host.address);
+        } else {
+            named.push((id, host));
+        }
+    }
+    (named, left_out)
+}
+
+fn trim_prefix(prefix: &str) -> &str {
+    prefix.trim().trim_matches('/')
+}
+
+/// State payload of a host.
+pub fn state_payload(host: &HostSnapshot) -> Value {
+    let up = host.alive && !host.is_stopped;
+    json!({
+        "name": host.name,
+        "address": host.address,
+        "up": up,
+        "latency_ms": up.then_some(host.latency),
+        "mean_ms": host.mean,
+        "jitter_ms": host.rtp_jitter,
+        "loss_pct": 100.0 - host.availability,
+        "availability": host.availability,
+        "mos": host.mos,
+    })
+}
+
+/// Home Assistant discovery topics and payloads of a host: its connectivity,
+/// latency and packet loss as entities of one device.
+pub fn discovery_payloads(settings: &MqttSettings, host: &HostSnapshot) -> Vec<(String, Value)> {
+    let prefix = trim_prefix(&settings.topic_prefix);
+    let node = topic_id(prefix);
+    let id = topic_id(&host.address);
+    let device = json!({
+        "identifiers": [format!("{}_{}", node, id)],
+        "name": if host.name.is_empty() { &host.address } else { &host.name },
+        "model": host.address,
+        "manufacturer": "egui_pinger",
+    });
+    let entity = |component: &str, key: &str, name: &str, extra: Value| {
+        let mut payload = json!({
+            "name": name,
+            "unique_id": format!("{}_{}_{}", node, id, key),
+            "object_id": format!("{}_{}", id, key),
+            "state_topic": format!("{}/{}/state", prefix, id),
+            "availability_topic": format!("{}/status", prefix),
+            "device": device,
+        });
+        if let (Some(payload), Value::Object(extra)) = (payload.as_object_mut(), extra) {
+            payload.extend(extra);
+        }
+        (
+            format!(
+                "{}/{}/{}/{}_{}/config",
+                trim_prefix(&settings.discovery_prefix),
+                component,
+                node,
+                id,
+                key
+            ),
+            payload,
+        )
+    };
+    vec![
+        entity(
+            "binary_sensor",
+            "connectivity",
+            "Connectivity",
+            json!({
+                "device_class": "connectivity",
+                "value_template": "{{ 'ON' if value_json.up else 'OFF' }}",
+            }),
+        ),
+        entity(
+            "sensor",
+            "latency",
+            "Latency",
+            json!({
+                "unit_of_measurement": "ms",
+                "state_class": "measurement",
+                "value_template": "{{ value_json.mean_ms | round(1) }}",
+            }),
+        ),
+        entity(
+            "sensor",
+            "loss",
+            "Packet loss",
+            json!({
+                "unit_of_measurement": "%",
+                "state_class": "measurement",
+                "value_template": "{{ value_json.loss_pct | round(1) }}",
+            }),
+        ),
+    ]
+}
+
+/// A connection to the broker that remembers what it published.
+struct Session<'a> {
+    settings: &'a MqttSettings,
+    stream: Connection,
+    /// Start of a packet from the broker that has not arrived completely.
+    incoming: Vec<u8>,
+    last_sent: Instant,
+    /// Last published snapshot and up state of the hosts by topic id.
+    published: HashMap<String, (HostSnapshot, bool)>,
+    /// Addresses left out because their topic id is taken, warned about once.
+    collisions: HashSet<String>,
+    last_metrics: Option<Instant>,
+}
+
+impl Session<'_> {
+    fn send(&mut self, packet: &[u8]) -> Result<(), String> {
+        self.stream
+            .write_all(packet)
+            .and_then(|_| self.stream.flush())
+            .map_err(|e| e.to_string())?;
+        self.last_sent = Instant::now();
+        Ok(())
+    }
+
+    fn publish(&mut self, topic: &str, payload: &[u8]) -> Result<(), String> {
+        self.send(&publish_packet(topic, payload))
+    }
+
+    /// Publishes the changes of the hosts: discovery for new ones, the state of
+    /// the ones that went up or down or all when the metrics are due, and removal
+    /// of the ones that are gone.
+    fn update(&mut self, hosts: Vec<HostSnapshot>) -> Result<(), String> {
+        let prefix = trim_prefix(&self.settings.topic_prefix).to_string();
+        let metrics_due = self.last_metrics.is_none_or(|t| {
+            t.elapsed() >= Duration::from_secs(u64::from(self.settings.interval_sec.max(1)))
+        });
+        if metrics_due {
+            self.last_metrics = Some(Instant::now());
+        }
+        let (hosts, left_out) = with_topic_ids(hosts);
+        for address in left_out {
+            if self.collisions.insert(address.clone()) {
+                applog::warn(
+                    "mqtt",
+                    format!(
+                        "{} is not published: another host is named {} in the topics too",
+                        address,
+                        topic_id(&address)
+                    ),
+                );
+            }
+        }
+        let ids: Vec<String> = hosts.iter().map(|(id, _)| id.clone()).collect();
+        for (id, host) in hosts {
+            let payload = state_payload(&host);
+            let up = payload["up"].as_bool().unwrap_or_default();
+            let (renamed, changed) = match self.published.get(&id) {
+                Some((previous, was_up)) => (previous.name != host.name, *was_up != up),
+                None => (true, true),
+            };
+            if self.settings.discovery && renamed {
+                for (topic, config) in discovery_payloads(self.settings, &host) {
+                    self.publish(&topic, config.to_string().as_bytes())?;
+                }
+            }
+            if metrics_due || changed {
+                self.publish(
+                    &format!("{}/{}/state", prefix, id),
+                    payload.to_string().as_bytes(),
+                )?;
+            }
+            self.published.insert(id, (host, up));
+        }
+        let gone: Vec<(String, HostSnapshot)> = self
+            .published
+            .iter()
+            .filter(|(id, _)| !ids.contains(id))
+            .map(|(id, (host, _))| (id.clone(), host.clone()))
+            .collect();
+        for (id, host) in gone {
+            // Empty retained messages clear the topics and remove the entities
+            if self.settings.discovery {
+                for (topic, _) in discovery_payloads(self.settings, &host) {
+                    self.publish(&topic, b"")?;
+                }
+            }
+            self.publish(&format!("{}/{}/state", prefix, id), b"")?;
+            self.published.remove(&id);
+        }
+        if self.last_sent.elapsed() >= Duration::from_secs(u64::from(MQTT_KEEP_ALIVE_SEC) / 2) {
+            self.send(&[PINGREQ, 0])?;
+        }
+        Ok(())
+    }
+}
+
+fn snapshots(state: &SharedState) -> Vec<HostSnapshot> {
+    let state_lock = state.lock().expect("Failed to lock state for MQTT");
+    let default_status = Default::default();
+    state_lock
+        .hosts
+        .iter()
+        .map(|h| {
+            HostSnapshot::capture(
+                h,
+                state_lock
+                    .statuses
+                    .get(&h.address)
+                    .unwrap_or(&default_status),
+            )
+        })
+        .collect()
+}
+
+/// Connects and publishes until the connection fails or `stop` is set.
+fn run_session(
+    state: &SharedState,
+    settings: &MqttSettings,
+    stop: &AtomicBool,
+) -> Result<(), String> {
+    let password = if settings.username.trim().is_empty() {
+        None
+    } else if !settings.tls {
+        return Err("the password is not sent without TLS".to_string());
+    } else {
+        keyring::load(&settings.keyring_account())?
+    };
+    let timeout = Duration::from_secs(MQTT_TIMEOUT_SEC);
+    let address = (settings.broker.trim(), settings.port)
+        .to_socket_addrs()
+        .map_err(|e| e.to_string())?
+        .next()
+        .ok_or("the broker's name does not resolve")?;
+    let tcp = TcpStream::connect_timeout(&address, timeout).map_err(|e| e.to_string())?;
+    tcp.set_write_timeout(Some(timeout))
+        .and_then(|_| tcp.set_read_timeout(Some(timeout)))
+        .map_err(|e| e.to_string())?;
+    let mut stream = if settings.tls {
+        Connection::Tls(Box::new(email::tls(settings.broker.trim(), tcp)?))
+    } else {
+        Connection::Plain(tcp)
+    };
+
+    let prefix = trim_prefix(&settings.topic_prefix);
+    let status_topic = format!("{}/status", prefix);
+    let client_id = format!("{}-{:08x}", topic_id(prefix), rand::random::<u32>());
+    stream
+        .write_all(&connect_packet(
+            &client_id,
+            (&status_topic, "offline"),
+            settings.username.trim(),
+            password.as_deref(),
+        ))
+        .and_then(|_| stream.flush())
+        .map_err(|e| e.to_string())?;
+    let mut incoming = Vec::new();
+    match read_packet(&mut stream, &mut incoming).map_err(|e| e.to_string())? {
+        (CONNACK, body) if body.get(1) == Some(&0) => {}
+        (CONNACK, body) => {
+            return Err(match body.get(1) {
+                Some(1) => "the broker does not support MQTT 3.1.1".to_string(),
+                Some(4) => "wrong user name or password".to_string(),
+                Some(5) => "not authorized".to_string(),
+                code => format!("refused with code {:?}", code),
+            });
+        }
+        _ => return Err("not an MQTT broker".to_string()),
+    }
+
+    // Waiting for the broker's packets paces the loop
+    stream
+        .set_read_timeout(Duration::from_secs(1))
+        .map_err(|e| e.to_string())?;
+    let mut session = Session {
+        settings,
+        stream,
+        incoming,
+        last_sent: Instant::now(),
+        published: HashMap::new(),
+        collisions: HashSet::new(),
+        last_metrics: None,
+    };
+    session.publish(&status_topic, b"online")?;
+    {
+        let mut state_lock = state.lock().expect("Failed to lock state for MQTT");
+        state_lock.mqtt_connected = true;
+        state_lock.mqtt_error = None;
+    }
+    applog::info(
+        "mqtt",
+        format!("Connected to {}:{}", settings.broker, settings.port),
+    );
+    while !stop.load(Ordering::Relaxed) {
+        session.update(snapshots(state))?;
+        match read_packet(&mut session.stream, &mut session.incoming) {
+            Ok(_) => {}
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                return Err("the broker closed the connection".to_string());
+            }
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    // A clean disconnect does not trigger the last will
+    session.publish(&status_topic, b"offline")?;
+    session.send(&[DISCONNECT, 0])
+}
+
+/// Publisher connected to the broker of the settings, reconnecting until dropped.
+pub struct MqttPublisher {
+    pub settings: MqttSettings,
+    stop: Arc<AtomicBool>,
+}
+
+impl MqttPublisher {
+    pub fn start(state: SharedState, settings: MqttSettings) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let publisher = Self {
+            settings: settings.clone(),
+            stop: stop.clone(),
+        };
+        std::thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                let result = run_session(&state, &settings, &stop);
+                let mut state_lock = state.lock().expect("Failed to lock state for MQTT");
+                state_lock.mqtt_connected = false;
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                let error = result.err().unwrap_or_else(|| "disconnected".to_string());
+                applog::warn(
+                    "mqtt",
+                    format!("Broker {}:{}: {}", settings.broker, settings.port, error),
+                );
+                state_lock.mqtt_error = Some(error);
+                drop(state_lock);
+                let retry_at = Instant::now() + Duration::from_secs(MQTT_RETRY_SEC);
+                while Instant::now() < retry_at && !stop.load(Ordering::Relaxed) {
+                    std::thread::sleep(Duration::from_millis(200));
+                }
+            }
+        });
+        publisher
+    }
+}
+
+impl Drop for MqttPublisher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Starts, restarts or stops the publisher to follow the settings.
+pub fn follow_settings(state: &SharedState, publisher: &mut Option<MqttPublisher>) {
+    let settings = state
+        .lock()
+        .expect("Failed to lock state for MQTT")
+        .settings
+        .mqtt
+        .clone();
+    if !settings.enabled || settings.broker.trim().is_empty() {
+        if publisher.take().is_some() {
+            applog::info("mqtt", "Stopped");
+        }
+        let mut state_lock = state.lock().expect("Failed to lock state for MQTT");
+        state_lock.mqtt_error = None;
+        state_lock.mqtt_connected = false;
+        return;
+    }
+    if publisher.as_ref().is_some_and(|p| p.settings == settings) {
+        return;
+    }
+    *publisher = Some(MqttPublisher::start(state.clone(), settings));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{AppState, HostInfo, HostStatus};
+    use std::net::TcpListener;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_remaining_length_encoding() {
+        for (length, encoded) in [
+            (0, vec![0x00]),
+            (127, vec![0x7f]),
+            (128, vec![0x80, 0x01]),
+            (16_383, vec![0xff, 0x7f]),
+            (2_097_152, vec![0x80, 0x80, 0x80, 0x01]),
+        ] {
+            let mut buf = Vec::new();
+            encode_length(&mut buf, length);
+            assert_eq!(buf, encoded);
+            let mut packet = vec![PUBLISH];
+            packet.extend(buf);
+            packet.resize(packet.len() + length, 0);
+            let (_, body) = read_packet(&mut packet.as_slice(), &mut Vec::new()).unwrap();
+            assert_eq!(body.len(), length);
+        }
+    }
+
+    /// Hands out one chunk per read; `None` is a read that times out.
+    struct Slow(std::collections::VecDeque<Option<Vec<u8>>>);
+
+    impl Read for Slow {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.0.pop_front() {
+                Some(Some(chunk)) => {
+                    buf[..chunk.len()].copy_from_slice(&chunk);
+                    Ok(chunk.len())
+                }
+                Some(None) => Err(std::io::ErrorKind::WouldBlock.into()),
+                None => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_packet_split_by_a_timeout_is_kept() {
+        let mut broker = Slow(
+            [
+                Some(vec![PUBLISH, 3, 0]),
+                None,
+                Some(vec![1, b'a', 0xd0]),
+                None,
+                Some(vec![0]),
+            ]
+            .into(),
+        );
+        let mut buffer = Vec::new();
+        let timed_out = read_packet(&mut broker, &mut buffer).unwrap_err();
+        assert_eq!(timed_out.kind(), std::io::ErrorKind::WouldBlock);
+        assert_eq!(
+            read_packet(&mut broker, &mut buffer).unwrap(),
+            (PUBLISH, vec![0, 1, b'a'])
+        );
+        assert!(read_packet(&mut broker, &mut buffer).is_err());
+        assert_eq!(
+            read_packet(&mut broker, &mut buffer).unwrap(),
+            (0xd0, vec![])
+        );
+    }
+
+    #[test]
+    fn test_hosts_with_the_same_topic_id_are_detected() {
+        let host = |address: &str| {
+            let info = HostInfo {
+                address: address.to_string(),
+                ..Default::default()
+            };
+            HostSnapshot::capture(&info, &HostStatus::default())
+        };
+        let (named, left_out) = with_topic_ids(vec![host("a.b"), host("a-b"), host("c")]);
+        let ids: Vec<&str> = named.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["a_b", "c"]);
+        assert_eq!(named[0].1.address, "a.b");
+        assert_eq!(left_out, ["a-b"]);
+    }
+
+    #[test]
+    fn test_password_needs_tls() {
+        let state = Arc::new(Mutex::new(AppState::default()));
+        let settings = MqttSettings {
+            broker: "192.0.2.1".to_string(),
+            username: "pinger".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            run_session(&state, &settings, &AtomicBool::new(false)),
+            Err("the password is not sent without TLS".to_string())
+        );
+    }
+
+    #[test]
+    fn test_states_and_discovery_are_published() {
+        let broker = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = broker.local_addr().unwrap().port();
+        let mut app_state = AppState::default();
+        app_state.settings.mqtt = MqttSettings {
+            enabled: true,
+            broker: "127.0.0.1".to_string(),
+            port,
+            topic_prefix: "/pinger/".to_string(),
+            ..Default::default()
+        };
+        let mut status = HostStatus {
+            alive: true,
+            ..Default::default()
+        };
+        status.add_sample(20.0, true);
+        app_state.hosts.push(HostInfo {
+            name: "Router".to_string(),
+            address: "192.168.1.1".to_string(),
+            ..Default::default()
+        });
+        app_state.statuses.insert("192.168.1.1".to_string(), status);
+        let state = Arc::new(Mutex::new(app_state));
+        let mut publisher = None;
+        follow_settings(&state, &mut publisher);
+
+        let (mut client, _) = broker.accept().unwrap();
+        let mut buffer = Vec::new();
+        let (header, body) = read_packet(&mut client, &mut buffer).unwrap();
+        assert_eq!(header, CONNECT);
+        assert_eq!(&body[..7], b"\x00\x04MQTT\x04");
+        client.write_all(&[CONNACK, 2, 0, 0]).unwrap();
+
+        let mut messages = HashMap::new();
+        while messages.len() < 5 {
+            let (header, body) = read_packet(&mut client, &mut buffer).unwrap();
+            assert_eq!(header, PUBLISH | RETAIN);
+            let length = usize::from(u16::from_be_bytes([body[0], body[1]]));
+            let topic = String::from_utf8(body[2..2 + length].to_vec()).unwrap();
+            messages.insert(topic, body[2 + length..].to_vec());
+        }
+        assert_eq!(messages["pinger/status"], b"online");
+        let state_json: Value =
+            serde_json::from_slice(&messages["pinger/192_168_1_1/state"]).unwrap();
+        assert_eq!(
+            (state_json["up"].as_bool(), state_json["mean_ms"].as_f64()),
+            (Some(true), Some(20.0))
+        );
+        let discovery: Value = serde_json::from_slice(
+            &messages["homeassistant/binary_sensor/pinger/192_168_1_1_connectivity/config"],
+        )
+        .unwrap();
+        assert_eq!(discovery["state_topic"], "pinger/192_168_1_1/state");
+        assert_eq!(discovery["device"]["name"], "Router");
+        assert!(state.lock().unwrap().mqtt_connected);
+
+        state.lock().unwrap().settings.mqtt.enabled = false;
+        follow_settings(&state, &mut publisher);
+        assert!(publisher.is_none());
+        let mut rest = buffer;
+        client.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest.last(), Some(&0));
+        assert_eq!(rest[rest.len() - 2], DISCONNECT);
+    }
+}
@@ -0,0 +1,216 @@
+//! Detection of the default gateway and the DNS servers of the system, used to
+//! set up a useful host list on the first run.
+
+use crate::logic::{SharedState, applog, dns};
+use crate::model::{HostInfo, HostStatus, PingMode};
+use std::net::{IpAddr, Ipv4Addr};
+use tr::tr;
+
+/// Public resolvers added by the first-run setup, with their names.
+pub const PUBLIC_RESOLVERS: [(&str, &str); 2] =
+    [("1.1.1.1", "Cloudflare DNS"), ("8.8.8.8", "Google DNS")];
+
+/// Parses `/proc/net/route` of Linux: the gateway of the default route, stored as
+/// a little-endian hex number.
+pub fn parse_proc_net_route(text: &str) -> Option<IpAddr> {
+    text.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        // Interface, destination, gateway, flags (0x2 = via a gateway), ...
+        let [_, destination, gateway, flags, ..] = fields[..] else {
+            return None;
+        };
+        let flags = u32::from_str_radix(flags, 16).ok()?;
+        if destination != "00000000" || flags & 0x2 == 0 {
+            return None;
+        }
+        let gateway = u32::from_str_radix(gateway, 16).ok()?;
+        Some(IpAddr::V4(Ipv4Addr::from(gateway.swap_bytes())))
+    })
+}
+
+/// Parses `route -n get default` of macOS/BSD (`    gateway: 192.168.1.1`).
+pub fn parse_route_get(text: &str) -> Option<IpAddr> {
+    text.lines()
+        .filter_map(|line| line.trim().strip_prefix("gateway:"))
+        .find_map(|gateway| gateway.trim().parse().ok())
+}
+
+/// Parses `route print -4 0.0.0.0` of Windows: the gateway column of the
+/// `0.0.0.0  0.0.0.0  <gateway>  <interface>  <metric>` rows, lowest metric first.
+pub fn parse_route_print(text: &str) -> Option<IpAddr> {
+    text.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields[..] {
+                ["0.0.0.0", "0.0.0.0", gateway, _, metric] => {
+                    Some((metric.parse::<u32>().ok()?, gateway.parse::<IpAddr>().ok()?))
+                }
+                _ => None,
+            }
+        })
+        .min_by_key(|(metric, _)| *metric)
+        .map(|(_, gateway)| gateway)
+}
+
+/// Runs a command and returns its standard output.
+pub(crate) fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let mut cmd = std::process::Command::new(program);
+    cmd.args(args);
+    #[cfg(windows)]
+    {
+        use crate::constants::CREATE_NO_WINDOW;
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = cmd.output().ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The gateway of the default IPv4 route.
+pub fn default_gateway() -> Option<IpAddr> {
+    if cfg!(target_os = "linux") {
+        parse_proc_net_route(&std::fs::read_to_string("/proc/net/route").ok()?)
+    } else if cfg!(windows) {
+        parse_route_print(&command_output("route", &["print", "-4", "0.0.0.0"])?)
+    } else {
+        parse_route_get(&command_output("route", &["-n", "get", "default"])?)
+    }
+}
+
+/// The DNS servers the system got from DHCP or its configuration. A local stub
+/// resolver (systemd-resolved, dnsmasq) is looked through where its upstream
+/// servers are known, and left out otherwise.
+pub fn dns_servers() -> Vec<IpAddr> {
+    let servers = if cfg!(windows) {
+        command_output(
+            "powershell",
+            &[
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                "Get-DnsClientServerAddress -AddressFamily IPv4 | ForEach-Object { $_.ServerAddresses }",
+            ],
+        )
+        .map(|text| text.lines().filter_map(|l| l.trim().parse().ok()).collect())
+        .unwrap_or_default()
+    } else {
+        ["/run/systemd/resolve/resolv.conf", "/etc/resolv.conf"]
+            .iter()
+            .filter_map(|path| std::fs::read_to_string(path).ok())
+            .map(|text| dns::parse_resolv_conf(&text))
+            .find(|servers| servers.iter().any(|ip| !ip.is_loopback()))
+            .unwrap_or_default()
+    };
+    let mut unique: Vec<IpAddr> = Vec::new();
+    for ip in servers {
+        if !ip.is_loopback() && !unique.contains(&ip) {
+            unique.push(ip);
+        }
+    }
+    unique
+}
+
+/// Hosts of the first-run setup: the gateway, the DNS servers that are not the
+/// gateway, and the public resolvers, with local hosts pinged more often.
+pub fn setup_hosts(gateway: Option<IpAddr>, dns_servers: &[IpAddr]) -> Vec<HostInfo> {
+    let mut hosts: Vec<HostInfo> = Vec::new();
+    let mut add = |address: String, name: String| {
+        if hosts.iter().any(|h| h.address == address) {
+            return;
+        }
+        let mut host = HostInfo {
+            name,
+            address,
+            mode: PingMode::NotFast,
+            ..Default::default()
+        };
+        if host.is_local() {
+            host.mode = PingMode::Fast;
+        }
+        hosts.push(host);
+    };
+    if let Some(gateway) = gateway {
+        add(gateway.to_string(), tr!("Gateway"));
+    }
+    for ip in dns_servers {
+        add(ip.to_string(), tr!("DNS server"));
+    }
+    for (address, name) in PUBLIC_RESOLVERS {
+        add(address.to_string(), name.to_string());
+    }
+    hosts
+}
+
+/// Detects the gateway and DNS servers on a background thread and adds them and
+/// the public resolvers to the host list, skipping hosts already in it.
+pub fn spawn_first_run_setup(state: &SharedState) {
+    let state = state.clone();
+    std::thread::spawn(move || {
+        let gateway = default_gateway();
+        let servers = dns_servers();
+        applog::info(
+            "setup",
+            format!(
+                "Detected gateway {:?} and DNS servers {:?}",
+                gateway.map(|ip| ip.to_string()),
+                servers.iter().map(|ip| ip.to_string()).collect::<Vec<_>>()
+            ),
+        );
+        let mut state = state.lock().expect("Failed to lock state for setup");
+        for mut host in setup_hosts(gateway, &servers) {
+            if state.hosts.iter().any(|h| h.address == host.address) {
+                continue;
+            }
+            host.thresholds = state.settings.default_thresholds;
+            state
+                .statuses
+                .insert(host.address.clone(), HostStatus::default());
+            state.hosts.push(host);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_default_routes() {
+        let linux = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\n\
+                     wlan0\t0001A8C0\t00000000\t0001\t0\t0\t600\t00FFFFFF\n\
+                     wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\n";
+        assert_eq!(
+            parse_proc_net_route(linux),
+            Some("192.168.1.1".parse().unwrap())
+        );
+
+        let macos = "   route to: default\ndestination: default\n       mask: default\n    gateway: 10.0.0.1\n  interface: en0\n";
+        assert_eq!(parse_route_get(macos), Some("10.0.0.1".parse().unwrap()));
+
+        let windows = "IPv4 Route Table\n===\nActive Routes:\nNetwork Destination        Netmask          Gateway       Interface  Metric\n          \
+                       0.0.0.0          0.0.0.0      10.8.0.1      10.8.0.6     50\n          \
+                       0.0.0.0          0.0.0.0  192.168.1.254    192.168.1.20     25\n";
+        assert_eq!(
+            parse_route_print(windows),
+            Some("192.168.1.254".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_setup_hosts() {
+        let gateway = "192.168.1.1".parse().ok();
+        let servers: Vec<IpAddr> = ["192.168.1.1", "9.9.9.9", "8.8.8.8"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let hosts = setup_hosts(gateway, &servers);
+        let addresses: Vec<&str> = hosts.iter().map(|h| h.address.as_str()).collect();
+        assert_eq!(
+            addresses,
+            vec!["192.168.1.1", "9.9.9.9", "8.8.8.8", "1.1.1.1"]
+        );
+        assert_eq!(hosts[0].mode, PingMode::Fast);
+        assert_eq!(hosts[1].mode, PingMode::NotFast);
+        assert_eq!(hosts[0].name, "Gateway");
+    }
+}
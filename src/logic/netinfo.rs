@@ -0,0 +1,432 @@
+//! Detection of the network the machine is on (default gateway and its MAC
+//! address, Wi-Fi name, public IP), used to activate the profile of the network
+//! and to follow changes of the public IP addresses.
+
+use crate::constants::{
+    NETINFO_CHECK_INTERVAL_SEC, PUBLIC_IP_CHECK_INTERVAL_SEC, PUBLIC_IP_URL, STUN_TIMEOUT_MS,
+};
+use crate::logic::{SharedState, applog, http, lan_discovery, netconfig};
+use crate::model::{AppState, NetworkInfo, ProxySettings};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+use tr::tr;
+
+/// Magic cookie of STUN messages (RFC 5389).
+const STUN_MAGIC_COOKIE: u32 = 0x2112_a442;
+const STUN_BINDING_REQUEST: u16 = 0x0001;
+const STUN_BINDING_RESPONSE: u16 = 0x0101;
+const STUN_MAPPED_ADDRESS: u16 = 0x0001;
+const STUN_XOR_MAPPED_ADDRESS: u16 = 0x0020;
+
+/// Parses `nmcli -t -f active,ssid dev wifi` of Linux (`yes:Home WiFi`).
+pub fn parse_nmcli(text: &str) -> Option<String> {
+    text.lines()
+        .filter_map(|line| line.strip_prefix("yes:"))
+        // Colons in the name are escaped in the terse output
+        .map(|ssid| ssid.replace("\\:", ":"))
+        .find(|ssid| !ssid.is_empty())
+}
+
+/// Parses `networksetup -getairportnetwork en0` of macOS
+/// (`Current Wi-Fi Network: Home WiFi`).
+pub fn parse_airport_network(text: &str) -> Option<String> {
+    text.lines()
+        .find_map(|line| line.strip_prefix("Current Wi-Fi Network:"))
+        .map(|ssid| ssid.trim().to_string())
+        .filter(|ssid| !ssid.is_empty())
+}
+
+/// Parses `netsh wlan show interfaces` of Windows (`    SSID   : Home WiFi`),
+/// skipping the `BSSID` line.
+pub fn parse_netsh_wlan(text: &str) -> Option<String> {
+    text.lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(key, _)| key.trim() == "SSID")
+        .map(|(_, ssid)| ssid.trim().to_string())
+        .filter(|ssid| !ssid.is_empty())
+}
+
+/// Name of the Wi-Fi network the machine is connected to.
+pub fn wifi_ssid() -> Option<String> {
+    if cfg!(target_os = "linux") {
+        netconfig::command_output("iwgetid", &["-r"])
+            .map(|ssid| ssid.trim().to_string())
+            .filter(|ssid| !ssid.is_empty())
+            .or_else(|| {
+                parse_nmcli(&netconfig::command_output(
+                    "nmcli",
+                    &["-t", "-f", "active,ssid", "dev", "wifi"],
+                )?)
+            })
+    } else if cfg!(windows) {
+        parse_netsh_wlan(&netconfig::command_output(
+            "netsh",
+            &["wlan", "show", "interfaces"],
+        )?)
+    } else {
+        parse_airport_network(&netconfig::command_output(
+            "networksetup",
+            &["-getairportnetwork", "en0"],
+        )?)
+    }
+}
+
+/// A STUN Binding Request with the given transaction id.
+pub fn stun_request(transaction: [u8; 12]) -> Vec<u8> {
+    let mut request = Vec::with_capacity(20);
+    request.extend_from_slice(&STUN_BINDING_REQUEST.to_be_bytes());
+    // No attributes
+    request.extend_from_slice(&0u16.to_be_bytes());
+    request.extend_from_slice(&STUN_MAGIC_COOKIE.to_be_bytes());
+    request.extend_from_slice(&transaction);
+    request
+}
+
+/// The address the server saw the request come from, from a Binding Response.
+pub fn parse_stun_response(data: &[u8], transaction: [u8; 12]) -> Result<IpAddr, String> {
+    let header = data
+        .get(..20)
+        .ok_or_else(|| "short STUN response".to_string())?;
+    if u16::from_be_bytes([header[0], header[1]]) != STUN_BINDING_RESPONSE
+        || header[4..8] != STUN_MAGIC_COOKIE.to_be_bytes()
+        || header[8..20] != transaction
+    {
+        return Err("unexpected STUN response".to_string());
+    }
+    let len = u16::from_be_bytes([header[2], header[3]]) as usize;
+    let mut attributes = data
+        .get(20..20 + len)
+        .ok_or_else(|| "short STUN response".to_string())?;
+    let mut mapped = None;
+    while attributes.len() >= 4 {
+        let kind = u16::from_be_bytes([attributes[0], attributes[1]]);
+        let len = u16::from_be_bytes([attributes[2], attributes[3]]) as usize;
+        let Some(value) = attributes.get(4..4 + len) else {
+            break;
+        };
+        // Family, port, address
+        let address = match (kind, value.get(1), value.get(4..)) {
+            (STUN_XOR_MAPPED_ADDRESS, Some(1), Some(ip)) if ip.len() == 4 => {
+                let ip = u32::from_be_bytes([ip[0], ip[1], ip[2], ip[3]]) ^ STUN_MAGIC_COOKIE;
+                Some(IpAddr::V4(Ipv4Addr::from(ip)))
+            }
+            (STUN_XOR_MAPPED_ADDRESS, Some(2), Some(ip)) if ip.len() == 16 => {
+                let mut key = STUN_MAGIC_COOKIE.to_be_bytes().to_vec();
+                key.extend_from_slice(&transaction);
+                let bytes: [u8; 16] = std::array::from_fn(|i| ip[i] ^ key[i]);
+                Some(IpAddr::V6(Ipv6Addr::from(bytes)))
+            }
+            (STUN_MAPPED_ADDRESS, Some(1), Some(ip)) if ip.len() == 4 => {
+                Some(IpAddr::V4(Ipv4Addr::new(ip[0], ip[1], ip[2], ip[3])))
+            }
+            (STUN_MAPPED_ADDRESS, Some(2), Some(ip)) if ip.len() == 16 => {
+                let bytes: [u8; 16] = ip.try_into().unwrap_or_default();
+                Some(IpAddr::V6(Ipv6Addr::from(bytes)))
+            }
+            _ => None,
+        };
+        // The XOR variant is preferred, some NATs rewrite plain addresses
+        if let (STUN_XOR_MAPPED_ADDRESS, Some(ip)) = (kind, address) {
+            return Ok(ip);
+        }
+        mapped = mapped.or(address);
+        // Attributes are padded to 4 bytes
+        attributes = attributes
+            .get(4 + len.div_ceil(4) * 4..)
+            .unwrap_or_default();
+    }
+    mapped.ok_or_else(|| "no mapped address in the STUN response".to_string())
+}
+
+/// The public IP address as seen by the STUN server at `server` (`host:port`).
+fn stun_lookup(server: &str) -> Result<IpAddr, String> {
+    let address: SocketAddr = server
+        .to_socket_addrs()
+        .map_err(|e| e.to_string())?
+        .next()
+        .ok_or_else(|| "cannot resolve host".to_string())?;
+    let local: SocketAddr = if address.is_ipv6() {
+        "[::]:0".parse().expect("valid address")
+    } else {
+        "0.0.0.0:0".parse().expect("valid address")
+    };
+    let socket = UdpSocket::bind(local).map_err(|e| e.to_string())?;
+    socket
+        .set_read_timeout(Some(Duration::from_millis(STUN_TIMEOUT_MS)))
+        .map_err(|e| e.to_string())?;
+    socket.connect(address).map_err(|e| e.to_string())?;
+    let transaction: [u8; 12] = rand::random();
+    socket
+        .send(&stun_request(transaction))
+        .map_err(|e| e.to_string())?;
+    let mut buf = [0u8; 512];
+    let n = socket.recv(&mut buf).map_err(|e| e.to_string())?;
+    parse_stun_response(&buf[..n], transaction)
+}
+
+/// The public IP address as told by `service`: an HTTPS URL answering with the
+/// address in plain text, or `stun:host:port`.
+pub fn lookup(service: &str, proxy: &ProxySettings) -> Result<IpAddr, String> {
+    if let Some(server) = service.strip_prefix("stun:") {
+        return stun_lookup(server);
+    }
+    let text = http::agent(proxy, service)
+        .get(service)
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+    text.trim()
+        .parse()
+        .map_err(|_| format!("unexpected answer: {}", text.trim()))
+}
+
+/// Detects the current network; the public IP only if `with_public_ip`.
+pub fn detect(proxy: &ProxySettings, with_public_ip: bool) -> NetworkInfo {
+    let gateway = netconfig::default_gateway();
+    let gateway_mac = gateway.and_then(|gateway| {
+        lan_discovery::read_arp_table()
+            .into_iter()
+            .find(|entry| IpAddr::V4(entry.ip) == gateway)
+            .map(|entry| entry.mac)
+    });
+    let public_ip = if with_public_ip {
+        lookup(PUBLIC_IP_URL, proxy)
+            .inspect_err(|e| applog::warn("netinfo", format!("Public IP lookup failed: {}", e)))
+            .ok()
+    } else {
+        None
+    };
+    NetworkInfo {
+        gateway,
+        gateway_mac,
+        ssid: wifi_ssid(),
+        public_ip,
+    }
+}
+
+/// Detects the network if a detection is due and profiles are switched by it.
+pub fn spawn_detect_if_due(state: &SharedState, last_check: &mut Option<Instant>, now: Instant) {
+    if last_check
+        .is_some_and(|t| now.duration_since(t) < Duration::from_secs(NETINFO_CHECK_INTERVAL_SEC))
+    {
+        return;
+    }
+    *last_check = Some(now);
+    let (proxy, with_public_ip) = {
+        let mut state_lock = state.lock().expect("Failed to lock state for netinfo");
+        if !state_lock.auto_profile {
+            state_lock.network = None;
+            return;
+        }
+        (
+            state_lock.settings.proxy.clone(),
+            state_lock
+                .profile_networks
+                .values()
+                .any(|n| !n.public_ip.trim().is_empty()),
+        )
+    };
+
+    let state = state.clone();
+    tokio::task::spawn_blocking(move || {
+        let network = detect(&proxy, with_public_ip);
+        let mut state_lock = state.lock().expect("Failed to lock state for netinfo");
+        if state_lock.network.as_ref() != Some(&network) {
+            applog::info("netinfo", format!("Network: {:?}", network));
+        }
+        state_lock.network = Some(network);
+    });
+}
+
+/// Asks every service for the public IP and keeps the first IPv4 and IPv6 address.
+pub fn public_ips(
+    services: &[String],
+    proxy: &ProxySettings,
+) -> Result<(Option<IpAddr>, Option<IpAddr>), String> {
+    let (mut v4, mut v6) = (None, None);
+    let mut errors = Vec::new();
+    for service in services {
+        match lookup(service, proxy) {
+            Ok(ip @ IpAddr::V4(_)) => v4 = v4.or(Some(ip)),
+            Ok(ip @ IpAddr::V6(_)) => v6 = v6.or(Some(ip)),
+            Err(e) => errors.push(format!("{}: {}", service, e)),
+        }
+    }
+    if v4.is_none() && v6.is_none() {
+        return Err(errors.join("; "));
+    }
+    Ok((v4, v6))
+}
+
+/// Stores the result of a public IP lookup, noting changes of the addresses on the
+/// timeline. A failed lookup keeps the known addresses, so that a reconnect with
+/// the same address is not taken for a change.
+pub fn apply_public_ips(
+    state: &mut AppState,
+    result: Result<(Option<IpAddr>, Option<IpAddr>), String>,
+    now: u64,
+) {
+    state.public_ip.checked_at = Some(now);
+    let (v4, v6) = match result {
+        Ok(ips) => ips,
+        Err(e) => {
+            state.public_ip.error = Some(e);
+            return;
+        }
+    };
+    state.public_ip.error = None;
+    for (family, old, new) in [
+        ("IPv4", state.public_ip.v4, v4),
+        ("IPv6", state.public_ip.v6, v6),
+    ] {
+        if let (Some(old), Some(new)) = (old, new)
+            && old != new
+        {
+            let note = tr!("Public {family} changed from {old} to {new}")
+                .replace("{family}", family)
+                .replace("{old}", &old.to_string())
+                .replace("{new}", &new.to_string());
+            applog::info("netinfo", &note);
+            state.annotate(now, &note);
+        }
+    }
+    state.public_ip.v4 = v4.or(state.public_ip.v4);
+    state.public_ip.v6 = v6.or(state.public_ip.v6);
+}
+
+/// Looks up the public IP addresses if the check is enabled and due.
+pub fn spawn_public_ip_check_if_due(
+    state: &SharedState,
+    last_check: &mut Option<Instant>,
+    now: Instant,
+) {
+    if last_check
+        .is_some_and(|t| now.duration_since(t) < Duration::from_secs(PUBLIC_IP_CHECK_INTERVAL_SEC))
+    {
+        return;
+    }
+    *last_check = Some(now);
+    let (services, proxy) = {
+        let mut state_lock = state.lock().expect("Failed to lock state for public IP");
+        if !state_lock.settings.public_ip_check {
+            state_lock.public_ip = Default::default();
+            return;
+        }
+        let services: Vec<String> = state_lock
+            .settings
+            .public_ip_services
+            .lines()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect();
+        (services, state_lock.settings.proxy.clone())
+    };
+    if services.is_empty() {
+        return;
+    }
+
+    let state = state.clone();
+    tokio::task::spawn_blocking(move || {
+        let result = public_ips(&services, &proxy);
+        let now_ts = chrono::Utc::now().timestamp() as u64;
+        let mut state_lock = state.lock().expect("Failed to lock state for public IP");
+        apply_public_ips(&mut state_lock, result, now_ts);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ProfileNetwork;
+
+    #[test]
+    fn test_parse_ssid() {
+        assert_eq!(
+            parse_nmcli("no:Neighbor\nyes:Home\\:5G\n"),
+            Some("Home:5G".to_string())
+        );
+        assert_eq!(parse_nmcli("no:Neighbor\n"), None);
+        assert_eq!(
+            parse_airport_network("Current Wi-Fi Network: Office Guest\n"),
+            Some("Office Guest".to_string())
+        );
+        assert_eq!(
+            parse_airport_network("You are not associated with an AirPort network.\n"),
+            None
+        );
+        let netsh = "    Name                   : Wi-Fi\n\
+                     \x20   SSID                   : Cafe\n\
+                     \x20   BSSID                  : 00:11:22:33:44:55\n";
+        assert_eq!(parse_netsh_wlan(netsh), Some("Cafe".to_string()));
+    }
+
+    #[test]
+    fn test_parse_stun_response() {
+        let transaction = [7u8; 12];
+        let request = stun_request(transaction);
+        assert_eq!(request.len(), 20);
+        assert_eq!(&request[..4], &[0x00, 0x01, 0x00, 0x00]);
+
+        // XOR-MAPPED-ADDRESS of 203.0.113.7:54321, after a padded SOFTWARE attribute
+        let mut response = vec![0x01, 0x01, 0x00, 20, 0x21, 0x12, 0xa4, 0x42];
+        response.extend_from_slice(&transaction);
+        response.extend_from_slice(&[0x80, 0x22, 0x00, 0x03, b'a', b'b', b'c', 0x00]);
+        let port = 54321u16 ^ 0x2112;
+        let ip = u32::from(Ipv4Addr::new(203, 0, 113, 7)) ^ STUN_MAGIC_COOKIE;
+        response.extend_from_slice(&[0x00, 0x20, 0x00, 0x08, 0x00, 0x01]);
+        response.extend_from_slice(&port.to_be_bytes());
+        response.extend_from_slice(&ip.to_be_bytes());
+        assert_eq!(
+            parse_stun_response(&response, transaction),
+            Ok("203.0.113.7".parse().unwrap())
+        );
+        assert!(parse_stun_response(&response, [8u8; 12]).is_err());
+        assert!(parse_stun_response(&response[..30], transaction).is_err());
+    }
+
+    #[test]
+    fn test_public_ip_changes_are_annotated() {
+        let mut state = AppState::default();
+        let ip = |text: &str| Some(text.parse::<IpAddr>().unwrap());
+        apply_public_ips(&mut state, Ok((ip("203.0.113.7"), None)), 100);
+        assert!(state.annotations.is_empty());
+
+        // A failed lookup keeps the address
+        apply_public_ips(&mut state, Err("offline".to_string()), 160);
+        assert_eq!(state.public_ip.v4, ip("203.0.113.7"));
+        assert_eq!(state.public_ip.error.as_deref(), Some("offline"));
+
+        apply_public_ips(&mut state, Ok((ip("198.51.100.2"), ip("2001:db8::1"))), 220);
+        assert_eq!(state.annotations.len(), 1);
+        assert_eq!(state.annotations[0].timestamp, 220);
+        assert!(state.annotations[0].text.contains("198.51.100.2"));
+        assert_eq!(state.public_ip.v6, ip("2001:db8::1"));
+        assert_eq!(state.public_ip.error, None);
+    }
+
+    #[test]
+    fn test_profile_network_matches() {
+        let network = NetworkInfo {
+            gateway: None,
+            gateway_mac: Some("00:1a:2b:3c:4d:5e".to_string()),
+            ssid: Some("Home".to_string()),
+            public_ip: None,
+        };
+        let profile = |gateway_mac: &str, ssid: &str| ProfileNetwork {
+            gateway_mac: gateway_mac.to_string(),
+            ssid: ssid.to_string(),
+            public_ip: String::new(),
+        };
+        assert!(profile("00-1A-2B-3C-4D-5E", "").matches(&network));
+        assert!(profile("00:1a:2b:3c:4d:5e", "Home").matches(&network));
+        assert!(!profile("00:1a:2b:3c:4d:5e", "Office").matches(&network));
+        assert!(!profile("", "").matches(&network));
+        let public = ProfileNetwork {
+            public_ip: "203.0.113.7".to_string(),
+            ..Default::default()
+        };
+        assert!(!public.matches(&network));
+    }
+}
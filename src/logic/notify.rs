@@ -0,0 +1,18 @@
+use crate::logic::applog;
+
+/// Shows a desktop notification without blocking the caller.
+///
+/// Delivery goes through the platform notification service (D-Bus on Linux),
+/// which may be slow or missing, so it runs on a separate thread.
+pub fn desktop_notification(summary: String, body: String) {
+    std::thread::spawn(move || {
+        if let Err(e) = notify_rust::Notification::new()
+            .appname("egui_pinger")
+            .summary(&summary)
+            .body(&body)
+            .show()
+        {
+            applog::warn("notify", format!("Failed to show notification: {}", e));
+        }
+    });
+}
@@ -0,0 +1,156 @@
+//! Comparison of paired hosts, e.g. the same destination through and around a VPN
+//! tunnel: how much latency and loss the first host has over the second.
+
+use crate::constants::PAIR_MATCH_SEC;
+use crate::model::status::calculate_percentile;
+use crate::model::{HostInfo, HostStatus};
+
+/// Difference of a host over the host it is paired with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairDelta {
+    /// Median RTT overhead over the replies received at about the same time (ms)
+    pub latency_ms: f64,
+    /// Difference of the 95th percentiles of RTT (ms)
+    pub p95_ms: f64,
+    /// Difference of the RTP jitter (ms)
+    pub jitter_ms: f64,
+    /// Difference of the loss in the history window (percentage points)
+    pub loss_pct: f64,
+    /// Replies of the two hosts matched in time
+    pub matched: usize,
+}
+
+/// The hosts linked to another host of the list, as (host, paired host) addresses.
+pub fn pairs(hosts: &[HostInfo]) -> Vec<(String, String)> {
+    hosts
+        .iter()
+        .filter(|h| {
+            !h.paired_with.is_empty()
+                && h.paired_with != h.address
+                && hosts.iter().any(|o| o.address == h.paired_with)
+        })
+        .map(|h| (h.address.clone(), h.paired_with.clone()))
+        .collect()
+}
+
+/// RTT of `a` minus RTT of `b` over time as `[timestamp, ms]` points, pairing each
+/// reply of `a` with the closest reply of `b` at most [`PAIR_MATCH_SEC`] away.
+pub fn delta_series(a: &HostStatus, b: &HostStatus) -> Vec<[f64; 2]> {
+    let replies = |status: &HostStatus| -> Vec<(u64, f64)> {
+        status
+            .history
+            .iter()
+            .filter_map(|s| s.rtt.map(|rtt| (s.timestamp, rtt)))
+            .collect()
+    };
+    let (a, b) = (replies(a), replies(b));
+    let mut series = Vec::new();
+    let mut j = 0;
+    for &(timestamp, rtt) in &a {
+        while j + 1 < b.len() && b[j + 1].0.abs_diff(timestamp) <= b[j].0.abs_diff(timestamp) {
+            j += 1;
+        }
+        if let Some(&(other_timestamp, other_rtt)) = b.get(j)
+            && other_timestamp.abs_diff(timestamp) <= PAIR_MATCH_SEC
+        {
+            series.push([timestamp as f64, rtt - other_rtt]);
+        }
+    }
+    series
+}
+
+/// How much latency, jitter and loss `a` has over `b`; `None` until both were probed.
+///
+/// The latency overhead comes from replies received at about the same time, so a
+/// change of the path that affects both hosts cancels out. Without such replies
+/// it falls back to the difference of the medians.
+pub fn compare(a: &HostStatus, b: &HostStatus) -> Option<PairDelta> {
+    if a.sent == 0 || b.sent == 0 {
+        return None;
+    }
+    let series = delta_series(a, b);
+    let latency_ms = if series.is_empty() {
+        a.median - b.median
+    } else {
+        calculate_percentile(series.iter().map(|p| p[1]), 50.0)
+    };
+    Some(PairDelta {
+        latency_ms,
+        p95_ms: a.p95 - b.p95,
+        jitter_ms: a.rtp_jitter - b.rtp_jitter,
+        // Availability is the share of the window answered
+        loss_pct: b.availability - a.availability,
+        matched: series.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Sample, SampleOutcome};
+
+    fn status(samples: &[(u64, Option<f64>)]) -> HostStatus {
+        let mut status = HostStatus::default();
+        for (i, &(timestamp, rtt)) in samples.iter().enumerate() {
+            status.record_sample(Sample {
+                timestamp,
+                seq: i as u32 + 1,
+                outcome: if rtt.is_some() {
+                    SampleOutcome::Reply
+                } else {
+                    SampleOutcome::Timeout
+                },
+                rtt,
+                maintenance: false,
+            });
+        }
+        status
+    }
+
+    #[test]
+    fn test_tunnel_overhead() {
+        let vpn = status(&[
+            (100, Some(40.0)),
+            (101, Some(42.0)),
+            (103, None),
+            (104, Some(45.0)),
+            (120, Some(90.0)),
+        ]);
+        let direct = status(&[
+            (100, Some(10.0)),
+            (102, Some(11.0)),
+            (103, Some(12.0)),
+            (104, Some(14.0)),
+            (110, Some(10.0)),
+        ]);
+        // The reply at 120 has no counterpart close enough
+        assert_eq!(
+            delta_series(&vpn, &direct),
+            vec![[100.0, 30.0], [101.0, 31.0], [104.0, 31.0]]
+        );
+        let delta = compare(&vpn, &direct).unwrap();
+        assert_eq!(delta.latency_ms, 31.0);
+        assert_eq!(delta.matched, 3);
+        assert!((delta.loss_pct - 20.0).abs() < 1e-9);
+        assert_eq!(compare(&vpn, &HostStatus::default()), None);
+    }
+
+    #[test]
+    fn test_pairs_need_both_hosts() {
+        let host = |address: &str, paired_with: &str| HostInfo {
+            address: address.to_string(),
+            paired_with: paired_with.to_string(),
+            ..Default::default()
+        };
+        let hosts = [
+            host("8.8.8.8@tun0", "8.8.8.8@eth0"),
+            host("8.8.8.8@eth0", ""),
+            host("1.1.1.1@tun0", "1.1.1.1"),
+            host("9.9.9.9", "9.9.9.9"),
+        ];
+        assert_eq!(
+            pairs(&hosts),
+            vec![("8.8.8.8@tun0".to_string(), "8.8.8.8@eth0".to_string())]
+        );
+    }
+}
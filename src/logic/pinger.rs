@@ -1,16 +1,34 @@
 use crate::constants::{
-    HOP_DATA_FRESHNESS_SEC, STATE_CONFIRMATION_STREAK, STATS_SNAPSHOT_INTERVAL,
-    TRACEROUTE_INTERVAL_SEC, TRACEROUTE_MIN_COOLDOWN_SEC,
+    APPLY_BATCH_MAX, APPLY_LOCK_CHUNK, EMAIL_CHART_SAMPLES, HOP_DATA_FRESHNESS_SEC,
+    SCHEDULER_ROUND_MS, SHUTDOWN_GRACE_MS, SLEEP_GAP_MIN_SEC, STATE_CONFIRMATION_STREAK,
+    STATS_SNAPSHOT_INTERVAL, TRACEROUTE_INTERVAL_SEC, TRACEROUTE_MIN_COOLDOWN_SEC,
 };
+use crate::logic::actions::PendingAction;
+use crate::logic::email::DownReport;
+use crate::logic::scheduler::Schedule;
+use crate::logic::shutdown::CancellationToken;
 use crate::logic::tracer::run_traceroute;
-use crate::model::{AppState, HostInfo, LogEntry, PingMode};
-use ping_async::{IcmpEchoRequestor, IcmpEchoStatus};
+use crate::logic::webhook::{WebhookEvent, WebhookMessage};
+use crate::logic::{
+    actions, agent, alerts, api, applog, connectivity, daemon, dhcp, dns, email, exec_probe,
+    host_list, http_probe, interfaces, marked_ping, mqtt, netinfo, notify, presenter, probe_limit,
+    root_cause, screenshot, snmp, sound, storage, stream, trace_context, udp_probe, webhook,
+};
+use crate::model::{
+    ActionEvent, AddressFamily, AppState, EmailSettings, HostInfo, HostStatus, LogEntry, PingMode,
+    ProxySettings, Sample, SampleOutcome, SoundEvent, SoundSettings, WebhookTarget,
+};
+use ping_async::{IcmpEchoRequestor, IcmpEchoStatus, PING_DEFAULT_TIMEOUT};
 use rand::RngExt;
 use std::collections::HashMap;
 use std::net::IpAddr;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
+use tr::tr;
 
+/// State shared by the UI and the pinger. Probe results reach it through a
+/// queue and are applied in batches, with file writes and notifications done
+/// after it is unlocked.
 pub type SharedState = Arc<Mutex<AppState>>;
 
 /// Returns a randomized ping interval for the given mode.
@@ -29,6 +47,19 @@ pub fn compute_interval(mode: PingMode, rng: &mut impl rand::Rng) -> Duration {
     Duration::from_secs_f64(base + jitter)
 }
 
+/// Returns the mode to ping a host in, given its configured mode and current status.
+///
+/// A failing host with an adaptive interval is pinged as fast as possible until it
+/// answers again, so the outage and the recovery are seen with 1 s resolution.
+pub fn adaptive_mode(mode: PingMode, adaptive: bool, status: &HostStatus) -> PingMode {
+    let failing = status.sent > 0 && !status.alive;
+    if adaptive && failing {
+        PingMode::VeryFast
+    } else {
+        mode
+    }
+}
+
 /// Generates a randomized ICMP payload for the given host config.
 /// Generates a randomized ICMP payload.
 pub fn generate_payload(host: Option<&HostInfo>) -> Vec<u8> {
@@ -60,7 +91,7 @@ fn check_and_spawn_traceroutes(
 
         for address in target_addrs {
             if let Some(host_info) = state_lock.hosts.iter().find(|h| h.address == address)
-                && host_info.is_stopped
+                && (host_info.is_stopped || host_info.passive || !host_info.is_icmp())
             {
                 continue;
             }
@@ -98,7 +129,7 @@ fn check_and_spawn_traceroutes(
         last_trace_times.insert(target_addr.clone(), now);
 
         tokio::spawn(async move {
-            let hops = run_traceroute(&addr_c).await;
+            let hops = run_traceroute(HostInfo::split_source(&addr_c).0).await;
             let mut state_lock = state_c.lock().expect("Failed to lock state after trace");
 
             if let Some(status) = state_lock.statuses.get_mut(&addr_c) {
@@ -226,77 +257,100 @@ fn update_diagnostic_modes(state: &SharedState) {
     }
 }
 
-/// Collects the list of addresses that need pinging in the current cycle.
+/// Whether a host is left out of probing: stopped and passive hosts, and hosts
+/// paused until their interface is back.
+fn is_paused(status: &HostStatus, host_info: Option<&HostInfo>) -> bool {
+    host_info.is_some_and(|h| h.is_stopped || h.passive) || status.interface_down
+}
+
+/// Effective probing mode of a host:
+/// - If diagnostic_mode is ON -> Fast (2s)
+/// - Else if it's a user target -> host.mode
+/// - Else (pure hop) -> Slow (60s)
+fn effective_mode(status: &HostStatus, host_info: Option<&HostInfo>) -> PingMode {
+    if status.diagnostic_mode {
+        PingMode::Fast
+    } else if let Some(h) = host_info {
+        adaptive_mode(h.mode, h.adaptive_interval, status)
+    } else {
+        PingMode::Slow
+    }
+}
+
+/// Brings the schedule in line with the hosts: new hosts and hosts that are no
+/// longer paused are probed at once, removed and paused ones are taken off.
+fn sync_schedule(state: &SharedState, schedule: &mut Schedule, now: Instant) {
+    let state_lock = state
+        .lock()
+        .expect("Failed to lock state for reading hosts");
+    let target_configs: HashMap<&str, &HostInfo> = state_lock
+        .hosts
+        .iter()
+        .map(|h| (h.address.as_str(), h))
+        .collect();
+
+    schedule.retain(|addr| state_lock.statuses.contains_key(addr));
+    for (addr, status) in &state_lock.statuses {
+        let host_info = target_configs.get(addr.as_str()).copied();
+        if is_paused(status, host_info) {
+            schedule.remove(addr);
+            continue;
+        }
+        // If the new mode's interval is shorter than the remaining wait time,
+        // jump the queue and ping now, rather than waiting the full Slow interval.
+        // This handles switching from Slow to Fast correctly without race conditions.
+        let mode = effective_mode(status, host_info);
+        let max_wait = Duration::from_secs(mode.interval_secs() + 2);
+        if schedule
+            .deadline(addr)
+            .is_none_or(|next| now + max_wait < next)
+        {
+            schedule.set(addr, now);
+        }
+    }
+}
+
+/// Takes the hosts due at `now` off the schedule and schedules their next probe,
+/// returned with the time it may be sent on its interface.
+///
+/// The next probe is timed from the deadline rather than from the wake-up, so
+/// a late wake-up does not shift the schedule of the host. A host whose
+/// interface is booked up is put back into the schedule for when it frees up.
 fn collect_ping_targets(
     state: &SharedState,
-    next_pings: &mut HashMap<String, Instant>,
+    schedule: &mut Schedule,
     now: Instant,
-) -> Vec<(String, PingMode, Option<HostInfo>)> {
+) -> Vec<(String, Instant, Option<HostInfo>)> {
+    let due = schedule.pop_due(now);
+    if due.is_empty() {
+        return Vec::new();
+    }
     let state_lock = state
         .lock()
         .expect("Failed to lock state for reading hosts");
 
     let mut rng = rand::rng();
 
-    // Build a lookup for target hosts to know their desired mode and config
-    let target_configs: HashMap<String, HostInfo> = state_lock
-        .hosts
-        .iter()
-        .map(|h| (h.address.clone(), h.clone()))
-        .collect();
-
-    state_lock
-        .statuses
-        .iter()
-        .filter_map(|(addr, status)| {
-            let next = next_pings.entry(addr.clone()).or_insert(now);
-            let host_info = target_configs.get(addr);
-
-            if let Some(h) = host_info
-                && h.is_stopped
-            {
+    due.into_iter()
+        .filter_map(|(addr, deadline)| {
+            // Removed and paused hosts stay off until the next sync adds them again
+            let status = state_lock.statuses.get(&addr)?;
+            let host_info = state_lock.hosts.iter().find(|h| h.address == addr);
+            if is_paused(status, host_info) {
                 return None;
             }
-
-            // Effective mode logic:
-            // - If diagnostic_mode is ON -> Fast (2s)
-            // - Else if it's a user target -> host.mode
-            // - Else (pure hop) -> Slow (60s)
-            let mode = if status.diagnostic_mode {
-                PingMode::Fast
-            } else if let Some(h) = host_info {
-                h.mode
-            } else {
-                PingMode::Slow
+            let interface = host_info.map_or("", |h| h.bound_interface());
+            let send_at = match probe_limit::take_token(interface, now) {
+                Ok(at) => at,
+                Err(retry_at) => {
+                    schedule.set(&addr, retry_at);
+                    return None;
+                }
             };
-
-            let interval = compute_interval(mode, &mut rng);
-
-            // If the new mode's interval is shorter than the remaining wait time,
-            // jump the queue and ping now/soon, rather than waiting the full Slow interval.
-            // This handles switching from Slow to Fast correctly without race conditions.
-            let max_wait = Duration::from_secs(
-                match mode {
-                    PingMode::VeryFast => 1,
-                    PingMode::Fast => 2,
-                    PingMode::NotFast => 5,
-                    PingMode::Normal => 10,
-                    PingMode::NotSlow => 30,
-                    PingMode::Slow => 60,
-                    PingMode::VerySlow => 300,
-                } + 2,
-            );
-
-            if now + max_wait < *next {
-                *next = now;
-            }
-
-            if *next <= now {
-                *next = now + interval;
-                Some((addr.clone(), mode, host_info.cloned()))
-            } else {
-                None
-            }
+            let mode = effective_mode(status, host_info);
+            let next = (deadline + compute_interval(mode, &mut rng)).max(now);
+            schedule.set(&addr, next);
+            Some((addr, send_at, host_info.cloned()))
         })
         .collect()
 }
@@ -449,8 +503,75 @@ pub(crate) fn deduce_failure_points(state: &SharedState, now: Instant) {
     }
 }
 
-/// Processes a single ping result: updates stats, creates log entries, detects incidents.
-fn process_ping_result(
+/// Outcome of one probe, measured by a probe task and applied to the state later.
+#[derive(Debug, Clone)]
+pub(crate) struct ProbeResult {
+    pub address: String,
+    /// Unix time in seconds when the probe finished
+    pub timestamp: u64,
+    pub alive: bool,
+    pub rtt_ms: f64,
+    pub dns_error: bool,
+    /// Status code of an HTTP(S) probe response
+    pub http_status: Option<u16>,
+    /// Reason an HTTP(S), DNS or command probe failed
+    pub probe_error: Option<String>,
+    pub host_info: Option<HostInfo>,
+    /// Set for the per-family pings of a dual-stack host
+    pub family: Option<AddressFamily>,
+    /// Address that was pinged, if known
+    pub ip: Option<IpAddr>,
+    /// The probe went unanswered, but a confirmation ping right after it was answered
+    pub loss_unconfirmed: bool,
+}
+
+impl ProbeResult {
+    /// A result measured now.
+    pub fn new(
+        address: &str,
+        alive: bool,
+        rtt_ms: f64,
+        dns_error: bool,
+        host_info: Option<HostInfo>,
+    ) -> Self {
+        Self {
+            address: address.to_string(),
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            alive,
+            rtt_ms,
+            dns_error,
+            http_status: None,
+            probe_error: None,
+            host_info,
+            family: None,
+            ip: None,
+            loss_unconfirmed: false,
+        }
+    }
+
+    /// The history sample of this result.
+    fn sample(&self, seq: u32) -> Sample {
+        Sample {
+            timestamp: self.timestamp,
+            seq,
+            outcome: if self.alive {
+                SampleOutcome::Reply
+            } else if self.dns_error {
+                SampleOutcome::DnsError
+            } else {
+                SampleOutcome::Timeout
+            },
+            rtt: self.alive.then_some(self.rtt_ms),
+            maintenance: false,
+        }
+    }
+}
+
+/// Queue of probe results waiting to be applied by [`apply_results`].
+type ResultSender = tokio::sync::mpsc::UnboundedSender<ProbeResult>;
+
+/// Processes a single ping result measured now: updates stats, creates log entries, detects incidents.
+pub(crate) fn process_ping_result(
     state: &SharedState,
     address: &str,
     alive: bool,
@@ -458,14 +579,178 @@ fn process_ping_result(
     dns_error: bool,
     host_info: Option<&HostInfo>,
 ) {
-    let mut state_lock = state
-        .lock()
-        .expect("Failed to lock state for updating status");
+    apply_probe_result(
+        state,
+        &ProbeResult::new(address, alive, rtt_ms, dns_error, host_info.cloned()),
+    );
+}
+
+/// Applies a probe result with the time it was measured at.
+pub(crate) fn apply_probe_result(state: &SharedState, result: &ProbeResult) {
+    let effects = update_status(
+        &mut state
+            .lock()
+            .expect("Failed to lock state for updating status"),
+        result,
+    );
+    effects.run(state, result);
+}
+
+/// Side effects of a probe result, carried out once the state is unlocked so
+/// that file writes and outgoing requests never hold up the UI.
+#[derive(Default)]
+struct ResultEffects {
+    /// Sample for the persistent history as (timestamp, RTT).
+    history: Option<(u64, Option<f32>)>,
+    /// Entries for the log file of the host, formatted once the state is unlocked.
+    log_entries: Vec<LogEntry>,
+    /// Correlation trace the log lines are tagged with.
+    trace_id: Option<String>,
+    /// Sounds of the alerts, with the sound settings.
+    sounds: Option<(SoundSettings, Vec<SoundEvent>)>,
+    action: Option<PendingAction>,
+    webhooks: Option<(ProxySettings, Vec<WebhookTarget>, Vec<WebhookMessage>)>,
+    email: Option<(EmailSettings, DownReport)>,
+}
+
+impl ResultEffects {
+    fn play_sounds(&mut self) {
+        if let Some((settings, events)) = self.sounds.take() {
+            for event in events {
+                sound::trigger(&settings, event);
+            }
+        }
+    }
+
+    fn run(mut self, state: &SharedState, result: &ProbeResult) {
+        self.play_sounds();
+        if let Some((timestamp, rtt)) = self.history {
+            storage::record(&result.address, timestamp, rtt);
+        }
+        if let Some(h) = result.host_info.as_ref()
+            && !self.log_entries.is_empty()
+        {
+            let address = result.address.as_str();
+            let lines: Vec<String> = self
+                .log_entries
+                .iter()
+                .map(|e| {
+                    trace_context::tag_line(
+                        e.format(address, Some(&h.display)),
+                        self.trace_id.as_deref(),
+                        address,
+                        e.timestamp(),
+                    )
+                })
+                .collect();
+            h.append_to_log(&lines);
+        }
+        if let Some(action) = self.action {
+            actions::spawn(state.clone(), action);
+        }
+        if let Some((proxy, webhooks, messages)) = self.webhooks {
+            webhook::send(proxy, webhooks, messages);
+        }
+        if let Some((email, report)) = self.email {
+            email::spawn(email, report);
+        }
+    }
+}
+
+/// Updates the statistics of the host with a probe result; returns what is left
+/// to do once the state is unlocked.
+fn update_status(state_lock: &mut AppState, result: &ProbeResult) -> ResultEffects {
+    let address = result.address.as_str();
+    let (alive, rtt_ms, dns_error) = (result.alive, result.rtt_ms, result.dns_error);
+    let host_info = result.host_info.as_ref();
+    // A loss overruled by a confirmation ping counts as lost but leaves the host up
+    let up = alive || result.loss_unconfirmed;
+    // Per-family results only feed the family's statistics
+    if let Some(family) = result.family {
+        if let Some(status) = state_lock.statuses.get_mut(address) {
+            let family_status = status.dual_stack.get_or_insert_default().get_mut(family);
+            family_status.dns_error = dns_error;
+            family_status.resolved_ip = result.ip.map(|ip| ip.to_string());
+            family_status.record_sample(result.sample(family_status.sent + 1));
+        }
+        return ResultEffects::default();
+    }
+    // Probes in flight when the interface went down are not counted as lost
+    if !alive
+        && state_lock
+            .statuses
+            .get(address)
+            .is_some_and(|s| s.interface_down)
+    {
+        return ResultEffects::default();
+    }
+    // Probes unanswered while the machine was asleep only mark the history
+    if !alive && state_lock.is_sleep_gap(result.timestamp) {
+        if let Some(status) = state_lock.statuses.get_mut(address) {
+            status.record_sample(Sample {
+                outcome: SampleOutcome::NoData,
+                ..result.sample(status.sent + 1)
+            });
+        }
+        return ResultEffects::default();
+    }
+    let keep_history = state_lock.settings.persistent_history;
+    let mut history = None;
+    let mut log_entries = Vec::new();
+    let trace_id = state_lock
+        .settings
+        .correlation
+        .active_trace_id()
+        .map(str::to_string);
+    let proxy = state_lock.settings.proxy.clone();
+    let webhooks = state_lock.settings.webhooks.clone();
+    let mut webhook_messages = Vec::new();
+    let email = state_lock.settings.email.clone();
+    let mut email_report = None;
+    let maintenance = state_lock.is_host_in_maintenance(address, result.timestamp);
+    // Silenced hosts, quiet hours and maintenance windows still record alerts but
+    // neither notify nor play sounds
+    let silenced = state_lock.is_host_silenced(address, result.timestamp)
+        || state_lock.is_host_quiet(address, result.timestamp)
+        || maintenance;
+    // An attached window notifies and plays the sounds for the background daemon
+    let alerts_here = state_lock.attached_windows == 0;
+    let notifications = state_lock.settings.desktop_notifications && alerts_here && !silenced;
+    let screenshots = state_lock.settings.incident_screenshots && !state_lock.headless;
+    let sounds = state_lock.settings.sounds.clone();
+    let mut screenshot = None;
+    let mut sound_events = Vec::new();
+    let mut action = None;
+    let mut pending_action = None;
+    // Events for the webhooks with their detail (downtime or alert message)
+    let mut webhook_events: Vec<(WebhookEvent, String)> = Vec::new();
+    // The surroundings are only needed if this failure opens an incident
+    let evidence = (!up
+        && host_info.is_some()
+        && state_lock
+            .statuses
+            .get(address)
+            .is_some_and(HostStatus::next_loss_opens_incident))
+    .then(|| root_cause::survey(state_lock, address, dns_error));
     if let Some(status) = state_lock.statuses.get_mut(address) {
         status.dns_error = dns_error;
-        status.add_sample(rtt_ms, alive);
+        status.http_status = result.http_status;
+        status.probe_error = result.probe_error.clone();
+        let now_ts = result.timestamp;
+        let sample = Sample {
+            maintenance,
+            ..result.sample(status.sent + 1)
+        };
+        if result.loss_unconfirmed {
+            status.record_unconfirmed_loss(sample);
+        } else {
+            status.record_sample(sample);
+        }
 
-        let now_ts = chrono::Utc::now().timestamp() as u64;
+        status.rollups.add(now_ts, alive.then_some(rtt_ms));
+        if keep_history {
+            history = Some((now_ts, alive.then_some(rtt_ms as f32)));
+        }
         let mut extra_events: Vec<LogEntry> = Vec::new();
 
         // 1. Log the ping result
@@ -478,19 +763,33 @@ fn process_ping_result(
         status.events.push_back(entry.clone());
 
         // 2. Incident Detection (Loss/Restoration)
-        if !alive && status.streak == STATE_CONFIRMATION_STREAK && status.incident_start.is_none() {
+        if !up && status.streak == STATE_CONFIRMATION_STREAK && status.incident_start.is_none() {
             // Just became "down" officially after 3 failures
             status.incident_start = Some(now_ts);
+            // The outage itself started with the first probe of the streak
+            let first_lost = status
+                .history
+                .iter()
+                .rev()
+                .nth(status.streak as usize - 1)
+                .map_or(now_ts, |s| s.timestamp);
+            status.outages.begin(first_lost, status.streak);
+            status.state_since = Some(first_lost);
             let ev = LogEntry::Incident {
                 timestamp: now_ts,
                 is_break: true,
                 streak: status.streak,
                 downtime_sec: None,
                 node: status.failure_point.clone(),
+                hint: evidence.as_ref().and_then(root_cause::diagnose),
             };
             status.events.push_back(ev.clone());
             extra_events.push(ev);
-        } else if alive && status.incident_start.is_some() {
+            screenshot = Some(screenshot::file_stem(address, now_ts, true));
+            sound_events.push(SoundEvent::Down);
+            webhook_events.push((WebhookEvent::Down, String::new()));
+            action = host_info.map(|h| (ActionEvent::Down, h.on_down_command.clone(), None));
+        } else if up && status.incident_start.is_some() {
             // Just restored from being officially "down"
             let downtime = status.incident_start.map(|s| now_ts.saturating_sub(s));
             let ev = LogEntry::Incident {
@@ -499,12 +798,142 @@ fn process_ping_result(
                 streak: status.streak,
                 downtime_sec: downtime,
                 node: None,
+                hint: None,
             };
             status.events.push_back(ev.clone());
             extra_events.push(ev);
             status.incident_start = None;
+            status.state_since = Some(now_ts);
+            status.outages.end(now_ts);
+            status.incident_escalated = false;
+            status.incident_emailed = false;
+            screenshot = Some(screenshot::file_stem(address, now_ts, false));
+            sound_events.push(SoundEvent::Recovery);
+            webhook_events.push((
+                WebhookEvent::Up,
+                presenter::format_duration(downtime.unwrap_or(0)),
+            ));
+            action = host_info.map(|h| (ActionEvent::Up, h.on_up_command.clone(), downtime));
+        } else if let Some(start) = status.incident_start
+            && !status.incident_escalated
+            && sounds.escalation_min > 0
+            && now_ts.saturating_sub(start) >= sounds.escalation_min as u64 * 60
+        {
+            status.incident_escalated = true;
+            sound_events.push(SoundEvent::Escalation);
+            webhook_events.push((
+                WebhookEvent::Escalation,
+                presenter::format_duration(now_ts.saturating_sub(start)),
+            ));
+        }
+        if !up && status.streak > STATE_CONFIRMATION_STREAK {
+            status.outages.add_lost();
+        }
+        if up && status.state_since.is_none() {
+            status.state_since = Some(now_ts);
+        }
+        if let Some(start) = status.incident_start
+            && let Some(h) = host_info
+            && email.enabled
+            && !status.incident_emailed
+            && now_ts.saturating_sub(start) >= email.down_min as u64 * 60
+        {
+            status.incident_emailed = true;
+            email_report = Some(DownReport {
+                host: if h.name.is_empty() {
+                    h.address.clone()
+                } else {
+                    h.name.clone()
+                },
+                address: address.to_string(),
+                down_since: start,
+                timestamp: now_ts,
+                loss_pct: status.lost as f64 / status.sent.max(1) as f64 * 100.0,
+                rtt_ms: status.mean,
+                samples: status
+                    .history
+                    .iter()
+                    .rev()
+                    .take(EMAIL_CHART_SAMPLES)
+                    .rev()
+                    .map(|s| s.rtt)
+                    .collect(),
+            });
+        }
+        status.prev_alive = Some(up);
+        let action = action.filter(|(_, command, _)| !command.trim().is_empty());
+        pending_action = action.map(|(event, command, downtime_sec)| PendingAction {
+            event,
+            command,
+            name: host_info.map(|h| h.name.clone()).unwrap_or_default(),
+            address: address.to_string(),
+            timestamp: now_ts,
+            loss_pct: status.lost as f64 / status.sent.max(1) as f64 * 100.0,
+            rtt_ms: status.mean,
+            downtime_sec,
+        });
+
+        // 2b. Alert rules
+        if let Some(h) = host_info {
+            let changes = alerts::evaluate_host_alerts(h, status, now_ts);
+            if changes
+                .iter()
+                .any(|ev| matches!(ev, LogEntry::Alert { raised: true, .. }))
+            {
+                sound_events.push(SoundEvent::Degraded);
+            }
+            for ev in &changes {
+                if let LogEntry::Alert {
+                    raised: true,
+                    message,
+                    ..
+                } = ev
+                {
+                    webhook_events.push((WebhookEvent::Degraded, message.clone()));
+                }
+            }
+            if notifications {
+                for ev in &changes {
+                    if let LogEntry::Alert {
+                        raised: true,
+                        message,
+                        ..
+                    } = ev
+                    {
+                        let host = if h.name.is_empty() {
+                            &h.address
+                        } else {
+                            &h.name
+                        };
+                        notify::desktop_notification(
+                            format!("{}: {}", tr!("Alert"), host),
+                            message.clone(),
+                        );
+                    }
+                }
+            }
+            extra_events.extend(changes);
+        }
+
+        if let Some(h) = host_info.filter(|_| !silenced) {
+            let host = if h.name.is_empty() {
+                &h.address
+            } else {
+                &h.name
+            };
+            let loss_pct = status.lost as f64 / status.sent.max(1) as f64 * 100.0;
+            webhook_messages.extend(webhook_events.drain(..).map(|(event, detail)| {
+                WebhookMessage {
+                    event,
+                    host: host.clone(),
+                    address: address.to_string(),
+                    message: webhook::describe(event, host, &detail),
+                    timestamp: now_ts,
+                    loss_pct,
+                    rtt_ms: status.mean,
+                }
+            }));
         }
-        status.prev_alive = Some(alive);
 
         // 3. Statistics every 300 pings (only if alive or periodic check)
         status.log_pings_since_stats += 1;
@@ -537,83 +966,667 @@ fn process_ping_result(
         status.trim_events();
 
         // 4. File Logging
-        if let Some(h) = host_info {
-            let mut lines = vec![entry.format(address, Some(&h.display))];
-            for ev in extra_events {
-                lines.push(ev.format(address, Some(&h.display)));
+        if host_info.is_some_and(|h| h.log_to_file) {
+            log_entries.push(entry);
+            log_entries.extend(extra_events);
+        }
+    }
+    if screenshots
+        && host_info.is_some()
+        && let Some(stem) = screenshot
+    {
+        state_lock.pending_screenshots.push_back(stem);
+    }
+    let sounds = (host_info.is_some() && alerts_here && !silenced && !sound_events.is_empty())
+        .then_some((sounds, sound_events));
+    ResultEffects {
+        history,
+        log_entries,
+        trace_id,
+        sounds,
+        action: pending_action,
+        webhooks: Some((proxy, webhooks, webhook_messages)),
+        email: email_report
+            .filter(|_| !silenced)
+            .map(|report| (email, report)),
+    }
+}
+
+/// Expected DNS answer of a host as written in its settings, and its parsed form.
+type ParsedAnswer = (String, Result<dns::ExpectedAnswer, String>);
+
+/// The parsed expected DNS answer of the host with `address`, parsing `expected`
+/// only when it differs from the text parsed before. `None` if no answer is expected.
+fn parsed_answer(
+    cache: &mut HashMap<String, ParsedAnswer>,
+    address: &str,
+    expected: &str,
+) -> Option<Result<dns::ExpectedAnswer, String>> {
+    if expected.is_empty() {
+        cache.remove(address);
+        return None;
+    }
+    let entry = cache
+        .entry(address.to_string())
+        .or_insert_with(|| (String::new(), Err(String::new())));
+    if entry.0 != expected {
+        *entry = (expected.to_string(), dns::ExpectedAnswer::parse(expected));
+    }
+    Some(entry.1.clone())
+}
+
+/// Echo requestor of a host together with the address it was created for.
+#[derive(Clone)]
+struct CachedRequestor {
+    requestor: IcmpEchoRequestor,
+    ip: IpAddr,
+    resolved_at: Instant,
+    /// TTL and reply timeout the requestor was created with.
+    ttl: Option<u8>,
+    timeout: Option<Duration>,
+    /// Local address the pings are sent from, if bound to one.
+    source: Option<IpAddr>,
+}
+
+/// Records the address a host name resolved to, flagging changes in the event log.
+fn set_resolved_ip(state: &SharedState, address: &str, ip: IpAddr) {
+    let ip = ip.to_string();
+    let mut state_lock = state.lock().expect("Failed to lock state for DNS update");
+    let Some(status) = state_lock.statuses.get_mut(address) else {
+        return;
+    };
+    if status.resolved_ip.as_ref() == Some(&ip) {
+        return;
+    }
+    if let Some(previous) = status.resolved_ip.replace(ip.clone()) {
+        let now_ts = chrono::Utc::now().timestamp() as u64;
+        applog::info(
+            "dns",
+            format!("{} now resolves to {} instead of {}", address, ip, previous),
+        );
+        status.events.push_back(LogEntry::Marker {
+            timestamp: now_ts,
+            message: tr!("Address changed from {old} to {new}")
+                .replace("{old}", &previous)
+                .replace("{new}", &ip),
+        });
+        status.trim_events();
+        status.address_change = Some((previous, now_ts));
+        // The route to the new address is likely different
+        status.manual_trace_requested = true;
+    }
+}
+
+/// Records the result of the expected DNS answer check, logging changes.
+fn set_dns_mismatch(state: &SharedState, address: &str, mismatch: Option<String>) {
+    let mut state_lock = state.lock().expect("Failed to lock state for DNS check");
+    if let Some(status) = state_lock.statuses.get_mut(address)
+        && status.dns_mismatch != mismatch
+    {
+        match &mismatch {
+            Some(m) => applog::warn(
+                "dns",
+                format!("Unexpected DNS answer for {}: {}", address, m),
+            ),
+            None => applog::info("dns", format!("DNS answer for {} matches again", address)),
+        }
+        status.dns_mismatch = mismatch;
+    }
+}
+
+/// Probes an HTTP(S) host and records the response like a ping result,
+/// with time to first byte as latency.
+async fn probe_http(proxy: ProxySettings, results: &ResultSender, host: &HostInfo) {
+    let (url, method) = (host.address.clone(), host.http_method);
+    let result = tokio::task::spawn_blocking(move || http_probe::probe(&url, method, &proxy))
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()));
+
+    let (alive, rtt_ms, http_status, error) = match result {
+        Ok(r) if http_probe::status_matches(&host.http_expected_status, r.status) => {
+            (true, r.ttfb_ms, Some(r.status), None)
+        }
+        Ok(r) => (false, f64::NAN, Some(r.status), None),
+        Err(e) => (false, f64::NAN, None, Some(e)),
+    };
+    let _ = results.send(ProbeResult {
+        http_status,
+        probe_error: error,
+        ..ProbeResult::new(&host.address, alive, rtt_ms, false, Some(host.clone()))
+    });
+}
+
+/// Times a DNS lookup against the host's resolver and records it like a ping result.
+async fn probe_dns(results: &ResultSender, host: &HostInfo) {
+    let result = match dns::parse_probe_target(&host.address) {
+        Some(target) => dns::probe(&target).await,
+        None => Err("invalid address, expected dns://resolver/name".to_string()),
+    };
+    record_probe_result(results, host, result);
+}
+
+/// Sends the host's datagram to its `udp://host:port` address and records the
+/// reply time like a ping result.
+async fn probe_udp(results: &ResultSender, host: &HostInfo) {
+    let result = match (
+        udp_probe::parse_target(&host.address),
+        udp_probe::parse_payload(&host.udp_payload),
+    ) {
+        (Some(target), Ok(payload)) => udp_probe::probe(&target, &payload).await,
+        (None, _) => Err("invalid address, expected udp://host:port".to_string()),
+        (_, Err(e)) => Err(e),
+    };
+    record_probe_result(results, host, result);
+}
+
+/// Runs the host's probe command and records its outcome like a ping result.
+async fn probe_exec(results: &ResultSender, host: &HostInfo) {
+    let result = exec_probe::probe(&host.probe_command, &host.address).await;
+    record_probe_result(results, host, result);
+}
+
+/// Returns cached requestors for the IPv4 and IPv6 address of a host name,
+/// resolving the name again when the cache is missing or too old.
+async fn family_requestors(
+    address: &str,
+    requestors: &tokio::sync::Mutex<HashMap<String, CachedRequestor>>,
+    dns_refresh: Option<Duration>,
+    timeout: Option<Duration>,
+) -> Vec<(AddressFamily, Option<CachedRequestor>)> {
+    let key = |family: AddressFamily| format!("{}#{}", address, family.label());
+    {
+        let reqs = requestors.lock().await;
+        let cached: Vec<_> = AddressFamily::ALL
+            .iter()
+            .map(|&f| (f, reqs.get(&key(f)).cloned()))
+            .collect();
+        let fresh = cached.iter().all(|(_, c)| {
+            c.as_ref().is_some_and(|c| {
+                c.timeout == timeout && dns_refresh.is_none_or(|r| c.resolved_at.elapsed() < r)
+            })
+        });
+        if fresh {
+            return cached;
+        }
+    }
+
+    let (target, source) = HostInfo::split_source(address);
+    let answers = dns::resolve(target).await;
+    let mut families = Vec::new();
+    for family in AddressFamily::ALL {
+        let mut cached = None;
+        if let Some(&ip) = answers.iter().find(|ip| family.matches(ip)) {
+            let source_ip = match source {
+                Some(source) => interfaces::source_address(source, ip).await.ok(),
+                None => None,
+            };
+            if source.is_none() || source_ip.is_some() {
+                cached = IcmpEchoRequestor::new(ip, source_ip, None, timeout)
+                    .ok()
+                    .map(|requestor| CachedRequestor {
+                        requestor,
+                        ip,
+                        resolved_at: Instant::now(),
+                        ttl: None,
+                        timeout,
+                        source: source_ip,
+                    });
             }
-            h.append_to_log(&lines);
         }
+        families.push((family, cached));
+    }
+    let mut reqs = requestors.lock().await;
+    for (family, cached) in &families {
+        match cached {
+            Some(c) => reqs.insert(key(*family), c.clone()),
+            None => reqs.remove(&key(*family)),
+        };
+    }
+    families
+}
+
+/// Pings the IPv4 and the IPv6 address of a dual-stack host in parallel.
+///
+/// A family without an address counts as a failed lookup for that family.
+async fn probe_families(
+    address: String,
+    host: HostInfo,
+    requestors: Arc<tokio::sync::Mutex<HashMap<String, CachedRequestor>>>,
+    dns_refresh: Option<Duration>,
+    results: ResultSender,
+) {
+    let families = family_requestors(&address, &requestors, dns_refresh, host.icmp_timeout()).await;
+    let pings = families.into_iter().map(|(family, cached)| async move {
+        let rtt = match &cached {
+            Some(c) => match c.requestor.send().await {
+                Ok(reply) if reply.status() == IcmpEchoStatus::Success => {
+                    Some(reply.round_trip_time().as_secs_f64() * 1000.0)
+                }
+                _ => None,
+            },
+            None => None,
+        };
+        (family, cached.map(|c| c.ip), rtt)
+    });
+    for (family, ip, rtt) in futures::future::join_all(pings).await {
+        let _ = results.send(ProbeResult {
+            family: Some(family),
+            ip,
+            ..ProbeResult::new(
+                &address,
+                rtt.is_some(),
+                rtt.unwrap_or(f64::NAN),
+                ip.is_none(),
+                Some(host.clone()),
+            )
+        });
+    }
+}
+
+/// Records the latency or failure reason of a non-ICMP probe.
+fn record_probe_result(results: &ResultSender, host: &HostInfo, result: Result<f64, String>) {
+    let (alive, rtt_ms, error) = match result {
+        Ok(rtt) => (true, rtt, None),
+        Err(e) => (false, f64::NAN, Some(e)),
+    };
+    let _ = results.send(ProbeResult {
+        probe_error: error,
+        ..ProbeResult::new(&host.address, alive, rtt_ms, false, Some(host.clone()))
+    });
+}
+
+/// Applies a result measured by the background daemon to the statistics of an
+/// attached window, with the window's own settings of the host, and plays its
+/// sounds. The daemon already recorded the history, wrote the logs and ran the
+/// actions, webhooks and e-mail of the result.
+pub(crate) fn apply_daemon_result(state: &SharedState, mut result: ProbeResult) {
+    let mut effects = {
+        let mut state_lock = state
+            .lock()
+            .expect("Failed to lock state for a result of the daemon");
+        result.host_info = state_lock
+            .hosts
+            .iter()
+            .find(|h| h.address == result.address)
+            .cloned();
+        update_status(&mut state_lock, &result)
+    };
+    effects.play_sounds();
+}
+
+/// Applies queued probe results in the order the probes finished.
+///
+/// Probe tasks hand their measurements to this task instead of applying them,
+/// so the results keep the time they were measured at even while the UI holds
+/// the state lock. Only a lookup of a host name still takes the lock in the probe
+/// task, to record the resolved address or a DNS mismatch. The results that piled
+/// up meanwhile are applied in chunks of [`APPLY_LOCK_CHUNK`], releasing the lock
+/// in between so that the UI can draw; log lines, sounds and the other effects
+/// wait until the lock is released.
+async fn apply_results(
+    state: SharedState,
+    mut results: tokio::sync::mpsc::UnboundedReceiver<ProbeResult>,
+) {
+    let mut batch = Vec::new();
+    while results.recv_many(&mut batch, APPLY_BATCH_MAX).await > 0 {
+        batch.sort_by_key(|r| r.timestamp);
+        let mut effects: Vec<ResultEffects> = Vec::with_capacity(batch.len());
+        for chunk in batch.chunks(APPLY_LOCK_CHUNK) {
+            let mut state_lock = state
+                .lock()
+                .expect("Failed to lock state for updating status");
+            effects.extend(
+                chunk
+                    .iter()
+                    .map(|result| update_status(&mut state_lock, result)),
+            );
+        }
+        daemon::publish(&batch);
+        for (result, effects) in batch.drain(..).zip(effects) {
+            effects.run(&state, &result);
+        }
+    }
+}
+
+/// Lets the probes in flight finish for up to [`SHUTDOWN_GRACE_MS`] and applies
+/// their results, so that quitting loses no measurement; slower probes are cancelled.
+async fn finish_probes(
+    mut probes: tokio::task::JoinSet<()>,
+    results: ResultSender,
+    applier: tokio::task::JoinHandle<()>,
+) {
+    let grace = Duration::from_millis(SHUTDOWN_GRACE_MS);
+    let all_done = async { while probes.join_next().await.is_some() {} };
+    if tokio::time::timeout(grace, all_done).await.is_err() {
+        applog::warn(
+            "pinger",
+            format!(
+                "Cancelled {} probes still running at shutdown",
+                probes.len()
+            ),
+        );
+        probes.shutdown().await;
+    }
+    // The applying task ends once the queue is closed and applied
+    drop(results);
+    if tokio::time::timeout(grace, applier).await.is_err() {
+        applog::warn(
+            "pinger",
+            "Results of some probes could not be applied at shutdown",
+        );
     }
 }
 
+/// Records a sleep of the machine when the loop round since `last_round` (wall
+/// clock and monotonic time) took longer than [`SLEEP_GAP_MIN_SEC`].
+///
+/// The monotonic clock stops during suspend on some systems and the wall clock
+/// on none, so the longer of both is taken.
+pub(crate) fn detect_sleep_gap(
+    state: &SharedState,
+    last_round: &mut Option<(u64, Instant)>,
+    wall_now: u64,
+    now: Instant,
+) {
+    let Some((wall_before, before)) = last_round.replace((wall_now, now)) else {
+        return;
+    };
+    let elapsed = wall_now
+        .saturating_sub(wall_before)
+        .max(now.duration_since(before).as_secs());
+    if elapsed < SLEEP_GAP_MIN_SEC {
+        return;
+    }
+    let start = wall_before;
+    let end = wall_now.max(start + elapsed);
+    applog::info(
+        "pinger",
+        format!(
+            "Machine was asleep for {} s, probes around it are not counted",
+            elapsed
+        ),
+    );
+    state
+        .lock()
+        .expect("Failed to lock state for recording a sleep gap")
+        .record_sleep_gap(start, end);
+}
+
 /// Background task that pings all configured hosts at regular intervals.
-pub async fn pinger_task(state: SharedState) {
-    // Map of address -> next scheduled ping time
-    let mut next_pings: HashMap<String, Instant> = HashMap::new();
+///
+/// Runs until the state asks for a different `generation`, i.e. the watchdog
+/// started a replacement, or until `cancel` is cancelled when the application quits.
+pub async fn pinger_task(state: SharedState, generation: u64, cancel: CancellationToken) {
+    // Deadlines of the next ping of each address
+    let mut schedule = Schedule::default();
+    // Time of the next housekeeping round (heartbeat, periodic checks, schedule sync)
+    let mut next_round = Instant::now();
     // Cache of ping-async requestors
-    let requestors: Arc<tokio::sync::Mutex<HashMap<String, IcmpEchoRequestor>>> =
+    let requestors: Arc<tokio::sync::Mutex<HashMap<String, CachedRequestor>>> =
         Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+    // Expected DNS answers of the hosts, parsed when the setting changes
+    let mut expected_answers: HashMap<String, ParsedAnswer> = HashMap::new();
 
     // Map to keep track of when we last ran traceroute per target
     let mut last_trace_times: HashMap<String, Instant> = HashMap::new();
+    // Time of the last captive portal check
+    let mut last_connectivity_check: Option<Instant> = None;
+    // Time of the last scan of the network interfaces
+    let mut last_interface_check: Option<Instant> = None;
+    // Time of the last check of the shared host list file
+    let mut last_host_list_check: Option<Instant> = None;
+    // Time of the last check of the DHCP lease file
+    let mut last_dhcp_check: Option<Instant> = None;
+    // Time of the last SNMP poll of the interface counters
+    let mut last_snmp_poll: Option<Instant> = None;
+    // Time of the last detection of the current network
+    let mut last_network_check: Option<Instant> = None;
+    // Time of the last lookup of the public IP addresses
+    let mut last_public_ip_check: Option<Instant> = None;
+    // Wall clock and monotonic time of the previous round, to notice sleeps
+    let mut last_round: Option<(u64, Instant)> = None;
+    // Statistics stream for the terminal client, stopped with this loop
+    let mut stream_server: Option<stream::StreamServer> = None;
+    // Remote agent serving the results to viewers elsewhere, stopped with this loop
+    let mut agent_server: Option<agent::AgentServer> = None;
+    let mut api_server: Option<api::ApiServer> = None;
+    let mut mqtt_publisher: Option<mqtt::MqttPublisher> = None;
+    // Probe results are applied by a single task, in order
+    let (results, results_rx) = tokio::sync::mpsc::unbounded_channel();
+    let applier = tokio::spawn(apply_results(state.clone(), results_rx));
+    // Probes in flight, waited for when the application quits
+    let mut probes = tokio::task::JoinSet::new();
 
     loop {
+        let wake = schedule
+            .next_deadline()
+            .map_or(next_round, |deadline| deadline.min(next_round));
+        tokio::select! {
+            _ = tokio::time::sleep_until(wake.into()) => {}
+            _ = cancel.cancelled() => {
+                finish_probes(probes, results, applier).await;
+                applog::info("pinger", "Pinger stopped");
+                return;
+            }
+        }
+        while probes.try_join_next().is_some() {}
         let now = Instant::now();
+        let (dns_refresh, trace_id, proxy) = {
+            let mut state_lock = state.lock().expect("Failed to lock state for heartbeat");
+            if state_lock.pinger_generation != generation {
+                applog::info("pinger", format!("Pinger loop {} replaced", generation));
+                return;
+            }
+            state_lock.pinger_heartbeat = Some(now);
+            (
+                Some(Duration::from_secs(state_lock.settings.dns_refresh_sec))
+                    .filter(|d| !d.is_zero()),
+                state_lock
+                    .settings
+                    .correlation
+                    .active_trace_id()
+                    .map(str::to_string),
+                state_lock.settings.proxy.clone(),
+            )
+        };
 
-        check_and_spawn_traceroutes(&state, &mut last_trace_times, now);
-        update_diagnostic_modes(&state);
-        let addresses_to_ping = collect_ping_targets(&state, &mut next_pings, now);
-        deduce_failure_points(&state, now);
+        if now >= next_round {
+            next_round = now + Duration::from_millis(SCHEDULER_ROUND_MS);
+            detect_sleep_gap(
+                &state,
+                &mut last_round,
+                chrono::Utc::now().timestamp() as u64,
+                now,
+            );
+            check_and_spawn_traceroutes(&state, &mut last_trace_times, now);
+            connectivity::spawn_check_if_due(&state, &mut last_connectivity_check, now);
+            host_list::spawn_sync_if_due(&state, &mut last_host_list_check, now);
+            dhcp::spawn_sync_if_due(&state, &mut last_dhcp_check, now);
+            interfaces::spawn_check_if_due(&state, &mut last_interface_check, now);
+            snmp::spawn_poll_if_due(&state, &mut last_snmp_poll, now);
+            netinfo::spawn_detect_if_due(&state, &mut last_network_check, now);
+            netinfo::spawn_public_ip_check_if_due(&state, &mut last_public_ip_check, now);
+            stream::follow_settings(&state, &mut stream_server);
+            agent::follow_settings(&state, &mut agent_server);
+            api::follow_settings(&state, &mut api_server);
+            mqtt::follow_settings(&state, &mut mqtt_publisher);
+            probe_limit::follow_settings(&state);
+            update_diagnostic_modes(&state);
+            sync_schedule(&state, &mut schedule, now);
+            deduce_failure_points(&state, now);
+        }
+        let addresses_to_ping = collect_ping_targets(&state, &mut schedule, now);
 
-        for (address, _mode, host_info) in addresses_to_ping {
+        for (address, send_at, host_info) in addresses_to_ping {
+            let expected_answer = host_info.as_ref().and_then(|h| {
+                parsed_answer(
+                    &mut expected_answers,
+                    &address,
+                    h.expected_dns_answer.trim(),
+                )
+            });
             let state = state.clone();
             let requestors_clone = requestors.clone();
-            let _payload = generate_payload(host_info.as_ref());
+            let results = results.clone();
+            let mut payload = generate_payload(host_info.as_ref());
+            let trace_id = trace_id.clone();
+            let proxy = proxy.clone();
+            let cancel = cancel.clone();
+
+            probes.spawn(async move {
+                // Probes still waiting for their turn are dropped when quitting
+                tokio::select! {
+                    _ = tokio::time::sleep_until(send_at.into()) => {}
+                    _ = cancel.cancelled() => return,
+                }
+                let _slot = probe_limit::acquire_slot().await;
+                if let Some(h) = host_info.as_ref().filter(|h| h.is_exec()) {
+                    probe_exec(&results, h).await;
+                    return;
+                }
+                if let Some(h) = host_info.as_ref().filter(|h| h.is_http()) {
+                    probe_http(proxy, &results, h).await;
+                    return;
+                }
+                if let Some(h) = host_info.as_ref().filter(|h| h.is_dns()) {
+                    probe_dns(&results, h).await;
+                    return;
+                }
+                if let Some(h) = host_info.as_ref().filter(|h| h.is_udp()) {
+                    probe_udp(&results, h).await;
+                    return;
+                }
 
-            tokio::spawn(async move {
-                let existing_requestor = {
+                let (target, source) = HostInfo::split_source(&address);
+                let is_name = dns::strip_brackets(target).parse::<IpAddr>().is_err();
+                if let Some(h) = host_info.as_ref().filter(|h| h.dual_stack && is_name) {
+                    tokio::spawn(probe_families(
+                        address.clone(),
+                        h.clone(),
+                        requestors_clone.clone(),
+                        dns_refresh,
+                        results.clone(),
+                    ));
+                }
+                let (ttl, dscp, timeout) = host_info
+                    .as_ref()
+                    .map_or((None, 0, None), |h| (h.ttl, h.dscp, h.icmp_timeout()));
+                let confirm_probes = host_info.as_ref().map_or(0, |h| h.confirm_probes);
+                let cached = {
                     let reqs = requestors_clone.lock().await;
-                    reqs.get(&address).cloned()
+                    reqs.get(&address)
+                        .filter(|c| c.ttl == ttl && c.timeout == timeout)
+                        .cloned()
+                };
+
+                // Hosts with an expected DNS answer are re-resolved on every ping
+                let checked_ip = match expected_answer.filter(|_| is_name) {
+                    Some(expected) => {
+                        let answers = dns::resolve(target).await;
+                        match expected.and_then(|e| e.check(&answers)) {
+                            Ok(ip) => {
+                                set_dns_mismatch(&state, &address, None);
+                                Some(ip)
+                            }
+                            Err(mismatch) => {
+                                requestors_clone.lock().await.remove(&address);
+                                set_dns_mismatch(&state, &address, Some(mismatch));
+                                let _ = results.send(ProbeResult::new(
+                                    &address,
+                                    false,
+                                    f64::NAN,
+                                    true,
+                                    host_info,
+                                ));
+                                return;
+                            }
+                        }
+                    }
+                    None => None,
                 };
 
-                let requestor_opt = if let Some(r) = existing_requestor {
-                    Some(r)
+                // Other names are resolved again once the cached answer is too old
+                let existing_requestor = cached.filter(|c| {
+                    checked_ip.is_none_or(|ip| ip == c.ip)
+                        && !(is_name && dns_refresh.is_some_and(|r| c.resolved_at.elapsed() >= r))
+                });
+
+                let requestor_opt = if let Some(c) = existing_requestor {
+                    Some((c.requestor, c.ip, c.source))
                 } else {
                     // Resolve the address outside of the lock
-                    let clean_address = if address.starts_with('[') && address.ends_with(']') {
-                        &address[1..address.len() - 1]
-                    } else {
-                        &address
-                    };
+                    let clean_address = dns::strip_brackets(target);
 
-                    let ip = if let Ok(ip) = clean_address.parse::<IpAddr>() {
+                    let ip = if checked_ip.is_some() {
+                        checked_ip
+                    } else if let Ok(ip) = clean_address.parse::<IpAddr>() {
                         Some(ip)
                     } else {
                         // Try DNS resolution
-                        let lookup_str = format!("{}:0", address);
-                        if let Ok(mut addrs) = tokio::net::lookup_host(&lookup_str).await {
-                            addrs.next().map(|a| a.ip())
-                        } else {
-                            None
-                        }
+                        dns::resolve(target).await.first().copied()
                     };
 
                     if let Some(target_ip) = ip {
-                        match IcmpEchoRequestor::new(target_ip, None, None, None) {
+                        if is_name {
+                            set_resolved_ip(&state, &address, target_ip);
+                        }
+                        // An address like `8.8.8.8@wlan0` pings from that interface
+                        let source_ip = match source {
+                            Some(source) => {
+                                match interfaces::source_address(source, target_ip).await {
+                                    Ok(ip) => Some(ip),
+                                    Err(e) => {
+                                        let _ = results.send(ProbeResult {
+                                            probe_error: Some(e),
+                                            ..ProbeResult::new(
+                                                &address,
+                                                false,
+                                                f64::NAN,
+                                                false,
+                                                host_info,
+                                            )
+                                        });
+                                        return;
+                                    }
+                                }
+                            }
+                            None => None,
+                        };
+                        match IcmpEchoRequestor::new(target_ip, source_ip, ttl, timeout) {
                             Ok(r) => {
                                 // Re-acquire the lock to insert
                                 let mut reqs = requestors_clone.lock().await;
                                 // Double check in case another task inserted it
-                                if let Some(existing) = reqs.get(&address) {
-                                    Some(existing.clone())
+                                if let Some(existing) = reqs.get(&address).filter(|c| {
+                                    c.ip == target_ip
+                                        && c.ttl == ttl
+                                        && c.timeout == timeout
+                                        && c.source == source_ip
+                                }) {
+                                    Some((existing.requestor.clone(), target_ip, source_ip))
                                 } else {
-                                    reqs.insert(address.clone(), r.clone());
-                                    Some(r)
+                                    reqs.insert(
+                                        address.clone(),
+                                        CachedRequestor {
+                                            requestor: r.clone(),
+                                            ip: target_ip,
+                                            resolved_at: Instant::now(),
+                                            ttl,
+                                            timeout,
+                                            source: source_ip,
+                                        },
+                                    );
+                                    Some((r, target_ip, source_ip))
                                 }
                             }
                             Err(e) => {
-                                eprintln!("Failed to create ICMP requestor for {}: {}", address, e);
+                                applog::error(
+                                    "pinger",
+                                    format!(
+                                        "Failed to create ICMP requestor for {}: {}",
+                                        address, e
+                                    ),
+                                );
                                 None
                             }
                         }
@@ -622,17 +1635,47 @@ pub async fn pinger_task(state: SharedState) {
                     }
                 };
 
-                if let Some(r) = requestor_opt {
-                    let result = r.send().await;
-
-                    let (alive, rtt_ms) = match result {
-                        Ok(reply) => {
-                            if reply.status() == IcmpEchoStatus::Success {
-                                (true, reply.round_trip_time().as_secs_f64() * 1000.0)
-                            } else {
-                                (false, f64::NAN)
+                if let Some((r, ip, source_ip)) = requestor_opt {
+                    // ping_async can neither mark its packets nor set their payload,
+                    // so marked and tagged probes use their own socket
+                    let sent_at = chrono::Utc::now().timestamp() as u64;
+                    if let Some(id) = &trace_id {
+                        let tag = trace_context::traceparent(id, &address, sent_at);
+                        trace_context::embed(&mut payload, &tag);
+                    }
+                    let own_socket = (dscp != 0 || trace_id.is_some()) && cfg!(unix);
+                    let ping = async || {
+                        if own_socket {
+                            let timeout = timeout.unwrap_or(PING_DEFAULT_TIMEOUT);
+                            marked_ping::send(ip, source_ip, ttl, dscp, &payload, timeout)
+                                .await
+                                .map_err(|e| e.to_string())
+                        } else {
+                            r.send()
+                                .await
+                                .map(|reply| {
+                                    (reply.status() == IcmpEchoStatus::Success)
+                                        .then(|| reply.round_trip_time())
+                                })
+                                .map_err(|e| e.to_string())
+                        }
+                    };
+                    let result = ping().await;
+                    // A single dropped packet should not make the host look down,
+                    // so a missed reply is checked with a few quick pings
+                    let mut loss_unconfirmed = false;
+                    if !matches!(result, Ok(Some(_))) {
+                        for _ in 0..confirm_probes {
+                            if matches!(ping().await, Ok(Some(_))) {
+                                loss_unconfirmed = true;
+                                break;
                             }
                         }
+                    }
+
+                    let (alive, rtt_ms) = match result {
+                        Ok(Some(rtt)) => (true, rtt.as_secs_f64() * 1000.0),
+                        Ok(None) => (false, f64::NAN),
                         Err(_) => {
                             // Invalidate cache on err (e.g., interface change)
                             let mut reqs = requestors_clone.lock().await;
@@ -641,21 +1684,21 @@ pub async fn pinger_task(state: SharedState) {
                         }
                     };
 
-                    process_ping_result(&state, &address, alive, rtt_ms, false, host_info.as_ref());
+                    let mut result = ProbeResult {
+                        loss_unconfirmed,
+                        ..ProbeResult::new(&address, alive, rtt_ms, false, host_info)
+                    };
+                    if own_socket && trace_id.is_some() {
+                        // Logged with the send time that the tag in the packet encodes
+                        result.timestamp = sent_at;
+                    }
+                    let _ = results.send(result);
                 } else {
-                    process_ping_result(
-                        &state,
-                        &address,
-                        false,
-                        f64::NAN,
-                        true,
-                        host_info.as_ref(),
-                    );
+                    let _ =
+                        results.send(ProbeResult::new(&address, false, f64::NAN, true, host_info));
                 }
             });
         }
-
-        tokio::time::sleep(Duration::from_millis(100)).await;
     }
 }
 
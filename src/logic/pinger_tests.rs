@@ -13,6 +13,7 @@ fn test_host(mode: PingMode, packet_size: usize, random_padding: bool) -> HostIn
         log_to_file: false,
         log_file_path: String::new(),
         is_stopped: false,
+        ..Default::default()
     }
 }
 
@@ -89,6 +90,27 @@ fn test_jitter_has_sufficient_entropy() {
     );
 }
 
+#[test]
+fn test_adaptive_mode_speeds_up_failing_host() {
+    let mut status = HostStatus::default();
+    // Not pinged yet
+    assert_eq!(adaptive_mode(PingMode::Slow, true, &status), PingMode::Slow);
+
+    status.sent = 5;
+    status.alive = false;
+    assert_eq!(
+        adaptive_mode(PingMode::Slow, true, &status),
+        PingMode::VeryFast
+    );
+    assert_eq!(
+        adaptive_mode(PingMode::Slow, false, &status),
+        PingMode::Slow
+    );
+
+    status.alive = true;
+    assert_eq!(adaptive_mode(PingMode::Slow, true, &status), PingMode::Slow);
+}
+
 // --- Payload tests ---
 
 #[test]
@@ -470,6 +492,45 @@ fn test_failure_deduction_stale_data() {
     );
 }
 
+#[test]
+fn test_schedule_follows_hosts() {
+    let state = Arc::new(Mutex::new(AppState::default()));
+    {
+        let mut sl = state.lock().unwrap();
+        for (address, is_stopped) in [("10.0.0.1", false), ("10.0.0.2", true)] {
+            sl.hosts.push(HostInfo {
+                address: address.to_string(),
+                mode: PingMode::Normal,
+                is_stopped,
+                ..Default::default()
+            });
+            sl.statuses
+                .insert(address.to_string(), HostStatus::default());
+        }
+    }
+    let now = Instant::now();
+    let mut schedule = Schedule::default();
+    sync_schedule(&state, &mut schedule, now);
+    assert_eq!(schedule.deadline("10.0.0.2"), None);
+
+    // A new host is probed at once, the next probe one interval after the deadline
+    let due = collect_ping_targets(&state, &mut schedule, now + Duration::from_millis(20));
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].0, "10.0.0.1");
+    assert!(due[0].1 <= now + Duration::from_millis(20));
+    let next = schedule.deadline("10.0.0.1").unwrap();
+    assert!(next >= now + Duration::from_secs(9) && next <= now + Duration::from_secs(11));
+    assert!(collect_ping_targets(&state, &mut schedule, now + Duration::from_secs(1)).is_empty());
+
+    // Switching to a faster mode jumps the queue
+    state.lock().unwrap().hosts[0].mode = PingMode::VeryFast;
+    sync_schedule(&state, &mut schedule, now + Duration::from_secs(1));
+    assert_eq!(
+        schedule.deadline("10.0.0.1"),
+        Some(now + Duration::from_secs(1))
+    );
+}
+
 // --- Incident detection tests ---
 
 #[tokio::test]
@@ -502,6 +563,77 @@ async fn test_incident_detection_timeout_streak() {
     }
 }
 
+#[test]
+fn test_confirmed_reply_keeps_host_up() {
+    let state: SharedState = Arc::new(Mutex::new(AppState::default()));
+    let address = "1.2.3.4";
+    state
+        .lock()
+        .unwrap()
+        .statuses
+        .insert(address.to_string(), HostStatus::default());
+
+    // Every scheduled ping is lost, but a confirmation ping is answered each time
+    for _ in 0..=STATE_CONFIRMATION_STREAK {
+        apply_probe_result(
+            &state,
+            &ProbeResult {
+                loss_unconfirmed: true,
+                ..ProbeResult::new(address, false, f64::NAN, false, None)
+            },
+        );
+    }
+
+    let sl = state.lock().unwrap();
+    let status = sl.statuses.get(address).unwrap();
+    assert!(status.alive);
+    assert!(status.incident_start.is_none());
+    assert_eq!(status.lost, STATE_CONFIRMATION_STREAK + 1);
+    assert_eq!(status.unconfirmed_losses, STATE_CONFIRMATION_STREAK + 1);
+    assert_eq!(status.availability, 0.0);
+}
+
+#[tokio::test]
+async fn test_probes_around_sleep_are_not_lost() {
+    let state = Arc::new(Mutex::new(AppState::default()));
+    let address = "1.2.3.4".to_string();
+    state
+        .lock()
+        .unwrap()
+        .statuses
+        .insert(address.clone(), HostStatus::default());
+
+    // Ten minutes pass between two rounds of the loop while the monotonic clock stood still
+    let now = Instant::now();
+    let wall = chrono::Utc::now().timestamp() as u64 - 5;
+    let mut last_round = None;
+    detect_sleep_gap(&state, &mut last_round, wall - 600, now);
+    detect_sleep_gap(&state, &mut last_round, wall, now);
+    assert_eq!(state.lock().unwrap().sleep_gaps, [(wall - 600, wall)]);
+    detect_sleep_gap(&state, &mut last_round, wall + 1, now);
+    assert_eq!(state.lock().unwrap().sleep_gaps.len(), 1);
+
+    // The probes timing out on resume neither count as lost nor open an incident
+    for _ in 0..STATE_CONFIRMATION_STREAK {
+        process_ping_result(&state, &address, false, f64::NAN, false, None);
+    }
+    process_ping_result(&state, &address, true, 10.0, false, None);
+
+    let sl = state.lock().unwrap();
+    let status = &sl.statuses[&address];
+    assert_eq!((status.sent, status.lost), (1, 0));
+    assert_eq!(status.availability, 100.0);
+    assert_eq!(status.history.len(), 4);
+    assert!(status.history[0].is_no_data() && !status.history[0].is_lost());
+    assert!(
+        !status
+            .events
+            .iter()
+            .any(|e| matches!(e, LogEntry::Incident { .. }))
+    );
+    assert_eq!(sl.annotations.len(), 1);
+}
+
 #[tokio::test]
 async fn test_incident_restoration() {
     let state = Arc::new(Mutex::new(AppState::default()));
@@ -545,6 +677,40 @@ async fn test_incident_restoration() {
     }
 }
 
+#[tokio::test]
+async fn test_outage_event_counts_lost_probes() {
+    let state = Arc::new(Mutex::new(AppState::default()));
+    let address = "1.2.3.4".to_string();
+    state
+        .lock()
+        .unwrap()
+        .statuses
+        .insert(address.clone(), HostStatus::default());
+
+    // Two losses without an outage, then one lasting two probes beyond the confirmation
+    for alive in [false, false, true] {
+        process_ping_result(&state, &address, alive, 10.0, false, None);
+    }
+    for _ in 0..STATE_CONFIRMATION_STREAK + 2 {
+        process_ping_result(&state, &address, false, f64::NAN, false, None);
+    }
+    assert_eq!(
+        state.lock().unwrap().statuses[&address]
+            .outages
+            .current()
+            .map(|c| c.1),
+        Some(STATE_CONFIRMATION_STREAK + 2)
+    );
+    process_ping_result(&state, &address, true, 10.0, false, None);
+
+    let sl = state.lock().unwrap();
+    let outages = sl.statuses[&address].outages.finished();
+    assert_eq!(outages.len(), 1);
+    assert_eq!(outages[0].lost, STATE_CONFIRMATION_STREAK + 2);
+    assert!(outages[0].start <= outages[0].end);
+    assert_eq!(sl.statuses[&address].outages.current(), None);
+}
+
 // --- Traceroute update tests ---
 
 #[test]
@@ -754,3 +920,118 @@ fn test_failure_deduction_multiple_targets_same_node() {
         Some(common_hop.clone())
     );
 }
+
+// --- DNS re-resolution tests ---
+
+#[test]
+fn test_resolved_ip_change_is_flagged() {
+    let state: SharedState = Arc::new(Mutex::new(AppState::default()));
+    state
+        .lock()
+        .unwrap()
+        .statuses
+        .insert("example.com".to_string(), HostStatus::default());
+
+    set_resolved_ip(&state, "example.com", "192.0.2.1".parse().unwrap());
+    set_resolved_ip(&state, "example.com", "192.0.2.1".parse().unwrap());
+    {
+        let sl = state.lock().unwrap();
+        let s = sl.statuses.get("example.com").unwrap();
+        assert_eq!(s.resolved_ip.as_deref(), Some("192.0.2.1"));
+        // The first answer is not a change
+        assert!(s.address_change.is_none());
+        assert!(s.events.is_empty());
+    }
+
+    set_resolved_ip(&state, "example.com", "192.0.2.2".parse().unwrap());
+    let sl = state.lock().unwrap();
+    let s = sl.statuses.get("example.com").unwrap();
+    assert_eq!(s.resolved_ip.as_deref(), Some("192.0.2.2"));
+    assert_eq!(
+        s.address_change.as_ref().map(|(old, _)| old.as_str()),
+        Some("192.0.2.1")
+    );
+    assert!(s.manual_trace_requested);
+    assert!(matches!(
+        s.events.back(),
+        Some(LogEntry::Marker { message, .. }) if message == "Address changed from 192.0.2.1 to 192.0.2.2"
+    ));
+}
+
+// --- Result queue tests ---
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_queued_results_keep_their_timestamps() {
+    let state: SharedState = Arc::new(Mutex::new(AppState::default()));
+    let address = "1.2.3.4";
+    state
+        .lock()
+        .unwrap()
+        .statuses
+        .insert(address.to_string(), HostStatus::default());
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    {
+        // Results arrive while the UI holds the lock, the later probe first
+        let _ui = state.lock().unwrap();
+        let late = ProbeResult {
+            timestamp: 1_000_005,
+            ..ProbeResult::new(address, true, 20.0, false, None)
+        };
+        let early = ProbeResult {
+            timestamp: 1_000_001,
+            ..ProbeResult::new(address, false, f64::NAN, false, None)
+        };
+        tx.send(late).unwrap();
+        tx.send(early).unwrap();
+    }
+    drop(tx);
+    apply_results(state.clone(), rx).await;
+
+    let sl = state.lock().unwrap();
+    let status = sl.statuses.get(address).unwrap();
+    let times: Vec<u64> = status.history.iter().map(|s| s.timestamp).collect();
+    assert_eq!(times, vec![1_000_001, 1_000_005]);
+    assert_eq!(status.history[1].rtt, Some(20.0));
+    assert_eq!(
+        status.events.front().map(|e| e.timestamp()),
+        Some(1_000_001)
+    );
+    assert!(status.alive);
+}
+
+#[test]
+fn test_family_results_are_kept_apart() {
+    let state: SharedState = Arc::new(Mutex::new(AppState::default()));
+    let address = "example.com";
+    state
+        .lock()
+        .unwrap()
+        .statuses
+        .insert(address.to_string(), HostStatus::default());
+
+    apply_probe_result(
+        &state,
+        &ProbeResult {
+            family: Some(AddressFamily::V4),
+            ip: Some("192.0.2.1".parse().unwrap()),
+            ..ProbeResult::new(address, true, 12.0, false, None)
+        },
+    );
+    apply_probe_result(
+        &state,
+        &ProbeResult {
+            family: Some(AddressFamily::V6),
+            ..ProbeResult::new(address, false, f64::NAN, true, None)
+        },
+    );
+
+    let sl = state.lock().unwrap();
+    let status = sl.statuses.get(address).unwrap();
+    assert_eq!(status.sent, 0);
+    let families = status.dual_stack.as_ref().unwrap();
+    assert_eq!(families.v4.history[0].rtt, Some(12.0));
+    assert_eq!(families.v4.resolved_ip.as_deref(), Some("192.0.2.1"));
+    assert_eq!(families.v6.history[0].outcome, SampleOutcome::DnsError);
+    assert_eq!(families.v6.lost, 1);
+}
@@ -0,0 +1,834 @@
+//! Text and severity of the statistics shown for a host, independent of the GUI.
+
+use crate::constants::{
+    BURST_RATIO_BAD, BURST_RATIO_WARN, HEATMAP_RTT_BUCKETS_MS, MAX_HISTOGRAM_BUCKETS,
+    PAUSE_GAP_INTERVALS, PAUSE_GAP_SLACK_SEC, RTP_JITTER_SMOOTHING_DIVISOR, TREND_MIN_CHANGE,
+};
+use crate::model::{Annotation, DisplaySettings, HostStatus, Sample, Thresholds};
+use std::collections::VecDeque;
+use tr::tr;
+
+/// How a value compares to its thresholds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Normal,
+    Warn,
+    Bad,
+}
+
+impl Severity {
+    /// Compares `value` to the warn and bad thresholds; NaN is always normal.
+    pub fn of(value: f64, warn: f64, bad: f64, higher_is_better: bool) -> Self {
+        let exceeds = |th: f64| {
+            if higher_is_better {
+                value < th
+            } else {
+                value > th
+            }
+        };
+        if value.is_nan() {
+            Severity::Normal
+        } else if exceeds(bad) {
+            Severity::Bad
+        } else if exceeds(warn) {
+            Severity::Warn
+        } else {
+            Severity::Normal
+        }
+    }
+}
+
+/// Which statistic a [`StatItem`] shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatKind {
+    Mean,
+    Median,
+    Jitter,
+    MeanJitter,
+    MedianJitter,
+    Mos,
+    Availability,
+    Outliers,
+    Streak,
+    Uptime,
+    StdDev,
+    P95,
+    MinMax,
+    Loss,
+    Ewma,
+    LossBursts,
+    Snmp,
+}
+
+/// A statistic shown after the host label.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatItem {
+    pub kind: StatKind,
+    pub text: String,
+    pub tooltip: String,
+    pub severity: Severity,
+}
+
+/// Packet loss of `status` in percent.
+pub fn loss_pct(status: &HostStatus) -> f64 {
+    (status.lost as f64 / status.sent.max(1) as f64) * 100.0
+}
+
+/// Arrow for a relative latency change: ↑ degrading, ↓ improving, empty while
+/// the change is below [`TREND_MIN_CHANGE`].
+pub fn trend_arrow(trend: f64) -> &'static str {
+    if trend >= TREND_MIN_CHANGE {
+        "↑"
+    } else if trend <= -TREND_MIN_CHANGE {
+        "↓"
+    } else {
+        ""
+    }
+}
+
+/// Formats a Unix timestamp as local date and time.
+pub fn format_time(ts: u64) -> String {
+    chrono::DateTime::from_timestamp(ts as i64, 0)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+/// Formats a duration in seconds as e.g. `9s`, `1m 05s`, `1h 02m 05s` or `3d 04h 12m`.
+pub fn format_duration(secs: u64) -> String {
+    match (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60) {
+        (0, 0, 0, s) => format!("{}s", s),
+        (0, 0, m, s) => format!("{}m {:02}s", m, s),
+        (0, h, m, s) => format!("{}h {:02}m {:02}s", h, m, s),
+        (d, h, m, _) => format!("{}d {:02}h {:02}m", d, h, m),
+    }
+}
+
+/// Builds the statistics selected in `display`, in display order.
+pub fn stat_items(
+    display: &DisplaySettings,
+    thresholds: &Thresholds,
+    status: &HostStatus,
+) -> Vec<StatItem> {
+    let t = thresholds;
+    let mut items = Vec::new();
+    let mut push = |kind, text: String, tooltip: String, severity| {
+        items.push(StatItem {
+            kind,
+            text,
+            tooltip,
+            severity,
+        })
+    };
+    let latency = |v: f64| Severity::of(v, t.latency_warn, t.latency_bad, false);
+    let jitter = |v: f64| Severity::of(v, t.jitter_warn, t.jitter_bad, false);
+
+    if display.show_mean {
+        push(
+            StatKind::Mean,
+            format!("{}: {:4.1}", tr!("M"), status.mean),
+            tr!("Mean RTT"),
+            latency(status.mean),
+        );
+    }
+    if display.show_median {
+        push(
+            StatKind::Median,
+            format!("{}: {:4.1}", tr!("Med"), status.median),
+            tr!("Median RTT"),
+            latency(status.median),
+        );
+    }
+    if display.show_rtp_jitter {
+        push(
+            StatKind::Jitter,
+            format!("{}: {:4.1}", tr!("J"), status.rtp_jitter),
+            tr!("RTP Jitter"),
+            jitter(status.rtp_jitter),
+        );
+    }
+    if display.show_rtp_mean_jitter {
+        push(
+            StatKind::MeanJitter,
+            format!("{}: {:4.1}", tr!("Jm"), status.rtp_jitter_mean),
+            tr!("Mean Jitter"),
+            jitter(status.rtp_jitter_mean),
+        );
+    }
+    if display.show_rtp_median_jitter {
+        push(
+            StatKind::MedianJitter,
+            format!("{}: {:4.1}", tr!("Jmed"), status.rtp_jitter_median),
+            tr!("Median Jitter"),
+            jitter(status.rtp_jitter_median),
+        );
+    }
+    if display.show_mos {
+        push(
+            StatKind::Mos,
+            format!("{}: {:3.1}", tr!("MOS"), status.mos),
+            tr!("Voice Quality (MOS)"),
+            Severity::of(status.mos, 4.0, 3.6, true),
+        );
+    }
+    if display.show_availability {
+        push(
+            StatKind::Availability,
+            format!("{}: {:3.0}%", tr!("Av"), status.availability),
+            tr!("Availability"),
+            Severity::of(status.availability, 99.0, 95.0, true),
+        );
+    }
+    if display.show_outliers {
+        push(
+            StatKind::Outliers,
+            format!("{}: {}", tr!("Out"), status.outliers),
+            tr!("Outliers (Lags)"),
+            if status.outliers > 3 {
+                Severity::Warn
+            } else {
+                Severity::Normal
+            },
+        );
+    }
+    if display.show_streak {
+        let streak_type = if status.streak_success {
+            tr!("S")
+        } else {
+            tr!("F")
+        };
+        let severity = if !status.streak_success && status.streak > 3 {
+            Severity::Bad
+        } else if !status.streak_success && status.streak > 1 {
+            Severity::Warn
+        } else {
+            Severity::Normal
+        };
+        push(
+            StatKind::Streak,
+            format!("{}: {}{}", tr!("Str"), streak_type, status.streak),
+            tr!("Streak"),
+            severity,
+        );
+    }
+    if display.show_uptime
+        && let Some((up, secs)) = status.state_duration(chrono::Utc::now().timestamp() as u64)
+    {
+        let duration = format_duration(secs);
+        let (text, severity) = if up {
+            (tr!("up {duration}"), Severity::Normal)
+        } else {
+            (tr!("down {duration}"), Severity::Bad)
+        };
+        push(
+            StatKind::Uptime,
+            text.replace("{duration}", &duration),
+            tr!("Time the host has been continuously up or down"),
+            severity,
+        );
+    }
+    if display.show_stddev {
+        push(
+            StatKind::StdDev,
+            format!("{}: {:4.1}", tr!("SD"), status.stddev),
+            tr!("Standard Deviation"),
+            Severity::Normal,
+        );
+    }
+    if display.show_p95 {
+        push(
+            StatKind::P95,
+            format!("95%: {:4.1}", status.p95),
+            tr!("95th Percentile"),
+            latency(status.p95),
+        );
+    }
+    if display.show_min_max {
+        push(
+            StatKind::MinMax,
+            format!(
+                "{}: {:1.0}-{:1.0}",
+                tr!("m/M"),
+                status.min_rtt,
+                status.max_rtt
+            ),
+            tr!("Min / Max RTT"),
+            Severity::Normal,
+        );
+    }
+    if display.show_loss {
+        let loss = loss_pct(status);
+        push(
+            StatKind::Loss,
+            format!("{}: {}/{} {:.1}%", tr!("L"), status.lost, status.sent, loss),
+            if status.unconfirmed_losses > 0 {
+                tr!("Packet Loss; {count} of the losses were answered by a confirmation ping")
+                    .replace("{count}", &status.unconfirmed_losses.to_string())
+            } else {
+                tr!("Packet Loss")
+            },
+            Severity::of(loss, t.loss_warn, t.loss_bad, false),
+        );
+    }
+    if display.show_ewma
+        && let Some(ewma) = status.ewma
+    {
+        // A degrading trend is worth a look before the thresholds are reached
+        let severity = if status.trend >= TREND_MIN_CHANGE {
+            latency(ewma).max(Severity::Warn)
+        } else {
+            latency(ewma)
+        };
+        push(
+            StatKind::Ewma,
+            format!("{}: {:4.1}{}", tr!("EW"), ewma, trend_arrow(status.trend)),
+            tr!(
+                "Moving average of RTT weighted to recent replies; ↑ the last 30 replies are over 10% slower than the 30 before them, ↓ faster"
+            ),
+            severity,
+        );
+    }
+    if display.show_loss_bursts {
+        push(
+            StatKind::LossBursts,
+            format!(
+                "{}: {}×{} {}: {:.1}",
+                tr!("Bu"),
+                status.loss_bursts,
+                status.max_loss_burst,
+                tr!("BR"),
+                status.burst_ratio
+            ),
+            tr!(
+                "Loss bursts in the history × the longest one, and the burst ratio (1.0 = random loss, higher = bursty, which hurts voice calls more)"
+            ),
+            Severity::of(status.burst_ratio, BURST_RATIO_WARN, BURST_RATIO_BAD, false),
+        );
+    }
+    // Shown once a host with SNMP enabled was polled
+    if status.snmp.polled_at.is_some() {
+        let snmp = &status.snmp;
+        let increase = snmp.increase.unwrap_or_default();
+        let text = match (&snmp.error, snmp.increase) {
+            (Some(_), None) => format!("{}: ?", tr!("IfErr")),
+            (_, None) => format!("{}: -", tr!("IfErr")),
+            (_, Some(increase)) => format!(
+                "{}: +{} {}: +{}",
+                tr!("IfErr"),
+                increase.errors(),
+                tr!("Disc"),
+                increase.discards()
+            ),
+        };
+        let mut tooltip = tr!(
+            "Interface errors and discards since the previous SNMP poll: in {in_errors}/{in_discards}, out {out_errors}/{out_discards}"
+        )
+        .replace("{in_errors}", &increase.in_errors.to_string())
+        .replace("{in_discards}", &increase.in_discards.to_string())
+        .replace("{out_errors}", &increase.out_errors.to_string())
+        .replace("{out_discards}", &increase.out_discards.to_string());
+        if let Some(e) = &snmp.error {
+            tooltip = format!("{}\n{}: {}", tooltip, tr!("SNMP poll failed"), e);
+        }
+        let severity = if snmp.error.is_some() || increase.errors() > 0 {
+            Severity::Warn
+        } else {
+            Severity::Normal
+        };
+        push(StatKind::Snmp, text, tooltip, severity);
+    }
+    items
+}
+
+/// Joins the statistics into one line, as shown after the host label.
+pub fn stats_line(items: &[StatItem]) -> String {
+    items
+        .iter()
+        .map(|item| item.text.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Positions of the annotations on a plot of bars at `slots` (ascending timestamps,
+/// one per bar): each annotation sits on the left edge of the first bar at or after it.
+///
+/// Annotations older than the first bar are left out; newer ones than the last bar
+/// sit on its right edge.
+pub fn annotation_positions<'a>(
+    annotations: &'a [Annotation],
+    slots: &[u64],
+) -> Vec<(f64, &'a Annotation)> {
+    let Some(&first) = slots.first() else {
+        return Vec::new();
+    };
+    annotations
+        .iter()
+        .filter(|a| a.timestamp >= first)
+        .map(|a| {
+            let i = slots.partition_point(|&t| t < a.timestamp);
+            (i as f64 - 0.5, a)
+        })
+        .collect()
+}
+
+/// RFC 3550 jitter after each reply as (timestamp, ms), computed like
+/// `HostStatus::rtp_jitter` but restarted at the first of `samples`.
+pub fn jitter_series<'a>(samples: impl IntoIterator<Item = &'a Sample>) -> Vec<[f64; 2]> {
+    let mut series = Vec::new();
+    let mut previous: Option<f64> = None;
+    let mut jitter: Option<f64> = None;
+    for sample in samples {
+        let Some(rtt) = sample.rtt else {
+            continue;
+        };
+        if let Some(prev) = previous {
+            let d = (rtt - prev).abs();
+            let j = match jitter {
+                Some(j) => j + (d - j) / RTP_JITTER_SMOOTHING_DIVISOR,
+                None => d,
+            };
+            jitter = Some(j);
+            series.push([sample.timestamp as f64, j]);
+        }
+        previous = Some(rtt);
+    }
+    series
+}
+
+/// A span without measurements: the host was stopped, its interface was down or
+/// the application was not running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pause {
+    /// Timestamp of the last sample before the pause.
+    pub start: u64,
+    /// Timestamp of the first sample after it, or the current time for a pause
+    /// that still lasts.
+    pub end: u64,
+    /// Position of the first sample after the pause among the samples.
+    pub index: usize,
+}
+
+/// Finds the pauses in `samples` taken every `interval_sec`: gaps too long for a
+/// missed probe, and a pause lasting until `now` after the last sample.
+///
+/// Samples imported at a longer step than the ping interval (e.g. Smokeping's
+/// 5 minutes) are judged by their usual (median) gap instead.
+pub fn pauses<'a>(
+    samples: impl IntoIterator<Item = &'a Sample>,
+    interval_sec: u64,
+    now: u64,
+) -> Vec<Pause> {
+    let times: Vec<u64> = samples.into_iter().map(|s| s.timestamp).collect();
+    let mut gaps: Vec<u64> = times
+        .windows(2)
+        .map(|w| w[1].saturating_sub(w[0]))
+        .collect();
+    gaps.sort_unstable();
+    let usual = gaps
+        .get(gaps.len() / 2)
+        .copied()
+        .unwrap_or(0)
+        .max(interval_sec);
+    let threshold = usual * PAUSE_GAP_INTERVALS + PAUSE_GAP_SLACK_SEC;
+
+    let mut pauses: Vec<Pause> = times
+        .windows(2)
+        .enumerate()
+        .filter(|(_, w)| w[1].saturating_sub(w[0]) > threshold)
+        .map(|(i, w)| Pause {
+            start: w[0],
+            end: w[1],
+            index: i + 1,
+        })
+        .collect();
+    if let Some(&last) = times.last()
+        && now.saturating_sub(last) > threshold
+    {
+        pauses.push(Pause {
+            start: last,
+            end: now,
+            index: times.len(),
+        });
+    }
+    pauses
+}
+
+/// Text of a pause for the hover text of the plots.
+pub fn pause_text(pause: &Pause) -> String {
+    let time = |ts: u64| {
+        chrono::DateTime::from_timestamp(ts as i64, 0)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_default()
+    };
+    tr!("Not measured from {start} to {end}")
+        .replace("{start}", &time(pause.start))
+        .replace("{end}", &time(pause.end))
+}
+
+/// Replies per RTT bucket of `bucket_ms`, from 0 up to the slowest reply; bucket
+/// `i` counts RTTs in `[i * bucket_ms, (i + 1) * bucket_ms)`.
+///
+/// At most MAX_HISTOGRAM_BUCKETS buckets are made, the last one taking all
+/// slower replies.
+pub fn histogram<'a>(samples: impl IntoIterator<Item = &'a Sample>, bucket_ms: f64) -> Vec<u32> {
+    let mut counts = Vec::new();
+    if bucket_ms <= 0.0 {
+        return counts;
+    }
+    for rtt in samples.into_iter().filter_map(|s| s.rtt) {
+        let i = ((rtt / bucket_ms).floor().max(0.0) as usize).min(MAX_HISTOGRAM_BUCKETS - 1);
+        if counts.len() <= i {
+            counts.resize(i + 1, 0);
+        }
+        counts[i] += 1;
+    }
+    counts
+}
+
+/// Rows of a heatmap column: one per RTT bucket, a slower-than-all bucket, and losses.
+pub const HEATMAP_ROWS: usize = HEATMAP_RTT_BUCKETS_MS.len() + 2;
+
+/// Samples of one heatmap column counted per row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapColumn {
+    /// Timestamp of the first sample in the column.
+    pub start: u64,
+    /// Replies per RTT bucket from the fastest, then the lost probes.
+    pub counts: [u32; HEATMAP_ROWS],
+}
+
+impl HeatmapColumn {
+    pub fn total(&self) -> u32 {
+        self.counts.iter().sum()
+    }
+}
+
+/// Counts `samples` into columns of `per_column` consecutive samples, like the
+/// smoke of Smokeping: how often each RTT bucket was hit and how many were lost.
+pub fn heatmap_columns(samples: &[Sample], per_column: usize) -> Vec<HeatmapColumn> {
+    samples
+        .chunks(per_column.max(1))
+        .map(|chunk| {
+            let mut counts = [0; HEATMAP_ROWS];
+            for sample in chunk.iter().filter(|s| !s.is_no_data()) {
+                let row = match sample.rtt {
+                    Some(rtt) => HEATMAP_RTT_BUCKETS_MS.partition_point(|&edge| edge < rtt),
+                    None => HEATMAP_ROWS - 1,
+                };
+                counts[row] += 1;
+            }
+            HeatmapColumn {
+                start: chunk[0].timestamp,
+                counts,
+            }
+        })
+        .collect()
+}
+
+/// Number of host cards of `card_width` with `spacing` between them that fit in
+/// `width`, at least one.
+pub fn grid_columns(width: f32, card_width: f32, spacing: f32) -> usize {
+    (((width + spacing) / (card_width + spacing)).floor() as usize).max(1)
+}
+
+/// Heights of a sparkline of the last `count` samples, from 0 to 1 for the highest
+/// RTT (or `min_top` if higher, so a quiet link stays flat); `None` for lost probes.
+pub fn sparkline(history: &VecDeque<Sample>, count: usize, min_top: f64) -> Vec<Option<f32>> {
+    let recent = history.iter().skip(history.len().saturating_sub(count));
+    let top = recent
+        .clone()
+        .filter_map(|s| s.rtt)
+        .fold(min_top, f64::max)
+        .max(f64::EPSILON);
+    recent
+        .map(|s| s.rtt.map(|rtt| (rtt / top) as f32))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::QoePreset;
+
+    #[test]
+    fn test_severity_of() {
+        assert_eq!(Severity::of(100.0, 150.0, 300.0, false), Severity::Normal);
+        assert_eq!(Severity::of(200.0, 150.0, 300.0, false), Severity::Warn);
+        assert_eq!(Severity::of(301.0, 150.0, 300.0, false), Severity::Bad);
+        assert_eq!(Severity::of(3.8, 4.0, 3.6, true), Severity::Warn);
+        assert_eq!(
+            Severity::of(f64::NAN, 150.0, 300.0, false),
+            Severity::Normal
+        );
+    }
+
+    #[test]
+    fn test_stat_items_follow_display_and_thresholds() {
+        let display = DisplaySettings {
+            show_mean: true,
+            show_median: false,
+            show_rtp_jitter: false,
+            show_rtp_mean_jitter: false,
+            show_rtp_median_jitter: false,
+            show_mos: false,
+            show_availability: false,
+            show_outliers: false,
+            show_streak: false,
+            show_stddev: false,
+            show_p95: false,
+            show_min_max: false,
+            show_loss: true,
+            ..DisplaySettings::default()
+        };
+        let status = HostStatus {
+            mean: 80.0,
+            sent: 100,
+            lost: 3,
+            ..HostStatus::default()
+        };
+
+        let items = stat_items(&display, &Thresholds::default(), &status);
+        let kinds: Vec<_> = items.iter().map(|i| i.kind).collect();
+        assert_eq!(kinds, vec![StatKind::Mean, StatKind::Loss]);
+        assert_eq!(items[0].severity, Severity::Normal);
+        assert_eq!(items[1].severity, Severity::Warn);
+        assert_eq!(stats_line(&items), "M: 80.0, L: 3/100 3.0%");
+
+        let gaming = QoePreset::Gaming.thresholds();
+        let items = stat_items(&display, &gaming, &status);
+        assert_eq!(items[0].severity, Severity::Warn);
+        assert_eq!(items[1].severity, Severity::Bad);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(65), "1m 05s");
+        assert_eq!(format_duration(754), "12m 34s");
+        assert_eq!(format_duration(3_725), "1h 02m 05s");
+        assert_eq!(format_duration(3 * 86400 + 4 * 3600 + 59), "3d 04h 00m");
+    }
+
+    #[test]
+    fn test_uptime_stat() {
+        let display = DisplaySettings {
+            show_mean: false,
+            show_median: false,
+            show_rtp_jitter: false,
+            show_mos: false,
+            show_loss: false,
+            show_uptime: true,
+            ..DisplaySettings::default()
+        };
+        let mut status = HostStatus::default();
+        assert!(stat_items(&display, &Thresholds::default(), &status).is_empty());
+
+        let now = chrono::Utc::now().timestamp() as u64;
+        status.state_since = Some(now - 2 * 3600);
+        let items = stat_items(&display, &Thresholds::default(), &status);
+        assert_eq!(items[0].kind, StatKind::Uptime);
+        assert!(items[0].text.starts_with("up 2h"));
+        assert_eq!(items[0].severity, Severity::Normal);
+
+        status.incident_start = Some(now - 60);
+        status.state_since = Some(now - 90);
+        assert_eq!(status.state_duration(now), Some((false, 90)));
+        let items = stat_items(&display, &Thresholds::default(), &status);
+        assert!(items[0].text.starts_with("down 1m "));
+        assert_eq!(items[0].severity, Severity::Bad);
+    }
+
+    #[test]
+    fn test_ewma_stat_and_trend() {
+        assert_eq!(trend_arrow(0.25), "↑");
+        assert_eq!(trend_arrow(-0.1), "↓");
+        assert_eq!(trend_arrow(0.05), "");
+
+        let display = DisplaySettings {
+            show_mean: false,
+            show_median: false,
+            show_rtp_jitter: false,
+            show_mos: false,
+            show_loss: false,
+            show_ewma: true,
+            ..DisplaySettings::default()
+        };
+        let mut status = HostStatus::default();
+        for i in 0..60 {
+            status.add_sample(if i < 30 { 20.0 } else { 30.0 }, true);
+        }
+        assert!((status.trend - 0.5).abs() < 1e-9);
+        let items = stat_items(&display, &Thresholds::default(), &status);
+        assert_eq!(items[0].kind, StatKind::Ewma);
+        assert!(items[0].text.ends_with('↑'));
+        assert_eq!(items[0].severity, Severity::Warn);
+    }
+
+    #[test]
+    fn test_snmp_stat() {
+        let display = DisplaySettings {
+            show_mean: false,
+            show_median: false,
+            show_rtp_jitter: false,
+            show_mos: false,
+            show_loss: false,
+            ..DisplaySettings::default()
+        };
+        let mut status = HostStatus::default();
+        assert!(stat_items(&display, &Thresholds::default(), &status).is_empty());
+
+        let counters = |in_errors, in_discards| crate::model::SnmpCounters {
+            in_errors,
+            in_discards,
+            ..Default::default()
+        };
+        status.snmp.record(Ok(counters(5, 1)), 60);
+        let items = stat_items(&display, &Thresholds::default(), &status);
+        assert_eq!(items[0].kind, StatKind::Snmp);
+        assert_eq!(items[0].text, "IfErr: -");
+
+        status.snmp.record(Ok(counters(5, 4)), 120);
+        let items = stat_items(&display, &Thresholds::default(), &status);
+        assert_eq!(items[0].text, "IfErr: +0 Disc: +3");
+        assert_eq!(items[0].severity, Severity::Normal);
+
+        status.snmp.record(Ok(counters(7, 4)), 180);
+        let items = stat_items(&display, &Thresholds::default(), &status);
+        assert_eq!(items[0].text, "IfErr: +2 Disc: +0");
+        assert_eq!(items[0].severity, Severity::Warn);
+    }
+
+    #[test]
+    fn test_annotation_positions() {
+        let note = |timestamp: u64| Annotation {
+            timestamp,
+            text: String::new(),
+        };
+        let annotations = [note(5), note(10), note(25), note(100)];
+        let positions: Vec<f64> = annotation_positions(&annotations, &[10, 20, 30])
+            .iter()
+            .map(|(x, _)| *x)
+            .collect();
+        assert_eq!(positions, vec![-0.5, 1.5, 2.5]);
+        assert!(annotation_positions(&annotations, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_jitter_series() {
+        let sample = |timestamp: u64, rtt: Option<f64>| Sample {
+            timestamp,
+            rtt,
+            ..Default::default()
+        };
+        let samples = [
+            sample(1, Some(10.0)),
+            sample(2, None),
+            sample(3, Some(26.0)),
+            sample(4, Some(18.0)),
+        ];
+        // The first difference starts the jitter, later ones move it by 1/16
+        assert_eq!(jitter_series(&samples), vec![[3.0, 16.0], [4.0, 15.5]]);
+        assert!(jitter_series(&samples[..2]).is_empty());
+    }
+
+    #[test]
+    fn test_pauses() {
+        let sample = |timestamp: u64| Sample {
+            timestamp,
+            rtt: Some(1.0),
+            ..Default::default()
+        };
+        // Every 10 s, stopped for 15 minutes, then running until 30 s ago
+        let samples = [sample(100), sample(110), sample(1_010), sample(1_020)];
+        assert_eq!(
+            pauses(&samples, 10, 1_050),
+            vec![Pause {
+                start: 110,
+                end: 1_010,
+                index: 2
+            }]
+        );
+        let still_stopped = pauses(&samples, 10, 2_000);
+        assert_eq!(still_stopped.len(), 2);
+        assert_eq!(
+            still_stopped[1],
+            Pause {
+                start: 1_020,
+                end: 2_000,
+                index: 4
+            }
+        );
+        assert!(pauses(&samples[..0], 10, 2_000).is_empty());
+
+        // Imported every 5 minutes
+        let imported: Vec<Sample> = (0..10).map(|i| sample(i * 300)).collect();
+        assert!(pauses(&imported, 10, 2_800).is_empty());
+    }
+
+    #[test]
+    fn test_histogram() {
+        let samples: Vec<Sample> = [Some(1.0), Some(1.5), None, Some(6.9), Some(1e9)]
+            .into_iter()
+            .map(|rtt| Sample {
+                rtt,
+                ..Default::default()
+            })
+            .collect();
+        let counts = histogram(&samples, 2.0);
+        assert_eq!(counts.len(), MAX_HISTOGRAM_BUCKETS);
+        assert_eq!(&counts[..4], &[2, 0, 0, 1]);
+        assert_eq!(counts[MAX_HISTOGRAM_BUCKETS - 1], 1);
+        assert_eq!(histogram(&samples[..3], 2.0), vec![2]);
+        assert!(histogram(&samples, 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_heatmap_columns() {
+        let sample = |timestamp: u64, rtt: Option<f64>| Sample {
+            timestamp,
+            rtt,
+            ..Default::default()
+        };
+        let samples = [
+            sample(1, Some(3.0)),
+            sample(2, Some(5.0)),
+            sample(3, Some(7.0)),
+            sample(4, None),
+            sample(5, Some(900.0)),
+        ];
+        let columns = heatmap_columns(&samples, 3);
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns[0].start, 1);
+        assert_eq!(columns[0].counts, [2, 1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(columns[1].start, 4);
+        assert_eq!(columns[1].counts, [0, 0, 0, 0, 0, 0, 0, 1, 1]);
+        assert_eq!(columns[1].total(), 2);
+    }
+
+    #[test]
+    fn test_grid_columns_and_sparkline() {
+        assert_eq!(grid_columns(100.0, 200.0, 8.0), 1);
+        assert_eq!(grid_columns(408.0, 200.0, 8.0), 2);
+        assert_eq!(grid_columns(1000.0, 200.0, 8.0), 4);
+
+        let mut history = VecDeque::new();
+        for rtt in [Some(50.0), Some(10.0), None, Some(20.0)] {
+            history.push_back(Sample {
+                rtt,
+                ..Default::default()
+            });
+        }
+        assert_eq!(
+            sparkline(&history, 3, 40.0),
+            vec![Some(0.25), None, Some(0.5)]
+        );
+        assert_eq!(sparkline(&history, 10, 40.0)[0], Some(1.0));
+    }
+}
@@ -0,0 +1,165 @@
+//! Process-wide limits on probing, shared by the pinger and the network scan, so
+//! that hundreds of hosts or a large scan neither flood the network nor run out
+//! of sockets: a cap on probes in flight and a send rate cap per interface.
+
+use crate::constants::{PROBE_RATE_BURST_SEC, PROBE_RATE_MAX_DELAY_MS};
+use crate::logic::pinger::SharedState;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+static LIMITS: LazyLock<Mutex<ProbeLimits>> = LazyLock::new(|| Mutex::new(ProbeLimits::default()));
+
+/// Send rate of one interface as a token bucket.
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// Takes a token for a probe at `now` with `rate` probes per second.
+    ///
+    /// Returns the time the probe may be sent, at most [`PROBE_RATE_MAX_DELAY_MS`]
+    /// ahead; if the backlog is longer, nothing is taken and `Err` tells when to
+    /// ask again.
+    fn take(&mut self, rate: f64, now: Instant) -> Result<Instant, Instant> {
+        let capacity = (rate * PROBE_RATE_BURST_SEC).max(1.0);
+        let refill = now.saturating_duration_since(self.updated).as_secs_f64() * rate;
+        self.tokens = (self.tokens + refill).min(capacity);
+        self.updated = self.updated.max(now);
+        let wait = Duration::from_secs_f64(((1.0 - self.tokens) / rate).max(0.0));
+        let max_delay = Duration::from_millis(PROBE_RATE_MAX_DELAY_MS);
+        if wait > max_delay {
+            return Err(now + (wait - max_delay));
+        }
+        self.tokens -= 1.0;
+        Ok(now + wait)
+    }
+}
+
+#[derive(Debug, Default)]
+struct ProbeLimits {
+    /// Probes allowed in flight at once; 0 for no limit.
+    max_concurrent: u32,
+    slots: Option<Arc<Semaphore>>,
+    /// Probes per second allowed on each interface; 0 for no limit.
+    rate_per_sec: u32,
+    /// Token buckets by interface name; empty for the default route.
+    buckets: HashMap<String, TokenBucket>,
+}
+
+impl ProbeLimits {
+    fn configure(&mut self, max_concurrent: u32, rate_per_sec: u32) {
+        // Probes in flight keep the permits of the old semaphore until they finish
+        if max_concurrent != self.max_concurrent {
+            self.max_concurrent = max_concurrent;
+            self.slots =
+                (max_concurrent > 0).then(|| Arc::new(Semaphore::new(max_concurrent as usize)));
+        }
+        if rate_per_sec != self.rate_per_sec {
+            self.rate_per_sec = rate_per_sec;
+            self.buckets.clear();
+        }
+    }
+
+    fn take(&mut self, interface: &str, now: Instant) -> Result<Instant, Instant> {
+        if self.rate_per_sec == 0 {
+            return Ok(now);
+        }
+        let rate = self.rate_per_sec as f64;
+        self.buckets
+            .entry(interface.to_string())
+            .or_insert(TokenBucket {
+                tokens: (rate * PROBE_RATE_BURST_SEC).max(1.0),
+                updated: now,
+            })
+            .take(rate, now)
+    }
+}
+
+/// Applies the limits from the settings.
+pub fn follow_settings(state: &SharedState) {
+    let (max_concurrent, rate_per_sec) = {
+        let state_lock = state.lock().expect("Failed to lock state for probe limits");
+        (
+            state_lock.settings.max_concurrent_probes,
+            state_lock.settings.probe_rate_per_interface,
+        )
+    };
+    if let Ok(mut limits) = LIMITS.lock() {
+        limits.configure(max_concurrent, rate_per_sec);
+    }
+}
+
+/// Takes a send token of `interface` (empty for the default route) for a probe at
+/// `now`: `Ok` with the time the probe may be sent, or `Err` with the time to ask
+/// again when the interface is already booked up.
+pub fn take_token(interface: &str, now: Instant) -> Result<Instant, Instant> {
+    match LIMITS.lock() {
+        Ok(mut limits) => limits.take(interface, now),
+        Err(_) => Ok(now),
+    }
+}
+
+/// Waits until a probe may be sent on `interface`.
+pub async fn wait_for_token(interface: &str) {
+    loop {
+        match take_token(interface, Instant::now()) {
+            Ok(at) => {
+                tokio::time::sleep_until(at.into()).await;
+                return;
+            }
+            Err(retry_at) => tokio::time::sleep_until(retry_at.into()).await,
+        }
+    }
+}
+
+/// Waits for a free probe slot; the probe holds the returned permit while it runs.
+/// `None` when the number of probes in flight is not limited.
+pub async fn acquire_slot() -> Option<OwnedSemaphorePermit> {
+    let slots = LIMITS.lock().ok()?.slots.clone()?;
+    slots.acquire_owned().await.ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_is_capped_per_interface() {
+        let now = Instant::now();
+        let mut limits = ProbeLimits::default();
+        assert_eq!(limits.take("eth0", now), Ok(now));
+
+        limits.configure(0, 100);
+        // A burst of a tenth of a second goes at once, the next ones 10 ms apart
+        let sent: Vec<_> = (0..12).map(|_| limits.take("eth0", now).unwrap()).collect();
+        let ms = |at: Instant| (at - now).as_secs_f64() * 1000.0;
+        assert!(sent[..10].iter().all(|at| *at == now));
+        assert!((ms(sent[10]) - 10.0).abs() < 1e-3);
+        assert!((ms(sent[11]) - 20.0).abs() < 1e-3);
+        // Other interfaces have their own budget
+        assert_eq!(limits.take("wlan0", now), Ok(now));
+
+        // Beyond the longest delay the probe has to ask again later
+        while limits.take("eth0", now).is_ok() {}
+        let retry_at = limits.take("eth0", now).unwrap_err();
+        assert!(retry_at > now && ms(retry_at) < 20.0);
+        assert!(limits.take("eth0", now + Duration::from_secs(2)).is_ok());
+    }
+
+    #[test]
+    fn test_concurrency_follows_settings() {
+        let mut limits = ProbeLimits::default();
+        assert!(limits.slots.is_none());
+        limits.configure(2, 0);
+        let slots = limits.slots.clone().unwrap();
+        assert_eq!(slots.available_permits(), 2);
+        limits.configure(2, 0);
+        assert!(Arc::ptr_eq(&slots, limits.slots.as_ref().unwrap()));
+        limits.configure(0, 0);
+        assert!(limits.slots.is_none());
+    }
+}
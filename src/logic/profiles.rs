@@ -0,0 +1,298 @@
+//! Configuration profiles ("Home", "Office", "Customer X"), each with its own hosts
+//! and settings. The active profile is the application state; the others are kept
+//! as separate documents in the eframe storage.
+
+use crate::logic::storage;
+use crate::model::AppState;
+
+/// Prefix of the storage keys of the profile documents.
+const PROFILE_KEY_PREFIX: &str = "profile:";
+
+/// A change of profiles picked in the UI, applied where the storage is at hand.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProfileChange {
+    Switch(String),
+    Create(String),
+    Delete(String),
+}
+
+impl ProfileChange {
+    pub fn apply(
+        &self,
+        state: &mut AppState,
+        storage: &mut dyn eframe::Storage,
+    ) -> Result<(), String> {
+        match self {
+            ProfileChange::Switch(name) => switch(state, storage, name),
+            ProfileChange::Create(name) => create(state, storage, name),
+            ProfileChange::Delete(name) => delete(state, storage, name),
+        }
+    }
+}
+
+fn profile_key(name: &str) -> String {
+    format!("{}{}", PROFILE_KEY_PREFIX, name)
+}
+
+/// Stores the active profile and loads `name` in its place.
+///
+/// Probing keeps running: the pinger picks up the hosts of the new profile on its
+/// next round. A profile never stored starts with the current settings and no hosts.
+pub fn switch(
+    state: &mut AppState,
+    storage: &mut dyn eframe::Storage,
+    name: &str,
+) -> Result<(), String> {
+    if name == state.profile {
+        return Ok(());
+    }
+    if !state.profile_names().iter().any(|p| p == name) {
+        return Err(format!("no profile named \"{}\"", name));
+    }
+    let mut loaded = match storage.get_string(&profile_key(name)) {
+        Some(doc) if !doc.is_empty() => serde_json::from_str::<AppState>(&doc)
+            .map_err(|e| format!("profile \"{}\" is damaged: {}", name, e))?,
+        _ => AppState {
+            settings: state.settings.clone(),
+            ..Default::default()
+        },
+    };
+    let current = serde_json::to_string(&*state).map_err(|e| e.to_string())?;
+    storage.set_string(&profile_key(&state.profile), current);
+
+    // The list of profiles and the state of the running application carry over
+    loaded.profile = name.to_string();
+    loaded.profiles = state.profile_names();
+    loaded.profile_networks = std::mem::take(&mut state.profile_networks);
+    loaded.auto_profile = state.auto_profile;
+    let old = std::mem::replace(state, loaded);
+    carry_runtime(old, state);
+    Ok(())
+}
+
+/// Moves what is not stored with a profile from the state of the old profile to
+/// the new one: the state of the running application, and the statistics of the
+/// hosts both profiles have. The other hosts continue from the stored history.
+fn carry_runtime(old: AppState, state: &mut AppState) {
+    let AppState {
+        mut statuses,
+        network,
+        public_ip,
+        interfaces,
+        connectivity,
+        shared_list,
+        pending_screenshots,
+        pinger_heartbeat,
+        pinger_generation,
+        action_log,
+        stream_error,
+        dhcp_leases,
+        sleep_gaps,
+        headless,
+        attached_windows,
+        daemon_attached,
+        agent_error,
+        api_error,
+        mqtt_connected,
+        mqtt_error,
+        remote_agent_status,
+        ..
+    } = old;
+    for host in &state.hosts {
+        if let Some(status) = statuses.remove(&host.address) {
+            state.statuses.insert(host.address.clone(), status);
+        }
+    }
+    state.network = network;
+    state.public_ip = public_ip;
+    state.interfaces = interfaces;
+    state.connectivity = connectivity;
+    state.shared_list = shared_list;
+    state.pending_screenshots = pending_screenshots;
+    state.pinger_heartbeat = pinger_heartbeat;
+    state.pinger_generation = pinger_generation;
+    state.action_log = action_log;
+    state.stream_error = stream_error;
+    state.dhcp_leases = dhcp_leases;
+    state.sleep_gaps = sleep_gaps;
+    state.headless = headless;
+    state.attached_windows = attached_windows;
+    state.daemon_attached = daemon_attached;
+    state.agent_error = agent_error;
+    state.api_error = api_error;
+    state.mqtt_connected = mqtt_connected;
+    state.mqtt_error = mqtt_error;
+    state.remote_agent_status = remote_agent_status;
+    if state.settings.persistent_history || state.daemon_attached {
+        storage::restore_statuses(state);
+    }
+}
+
+/// Addresses whose stored history belongs to a host of any profile, deleted hosts
+/// included; `None` if a profile cannot be read, so that nothing is pruned.
+pub fn known_addresses(
+    state: &AppState,
+    storage: Option<&dyn eframe::Storage>,
+) -> Option<Vec<String>> {
+    let mut known: Vec<String> = Vec::new();
+    let mut add = |profile: &AppState| {
+        known.extend(profile.hosts.iter().map(|h| h.address.clone()));
+        known.extend(profile.deleted_hosts.iter().map(|d| d.host.address.clone()));
+    };
+    add(state);
+    for name in state
+        .profile_names()
+        .iter()
+        .filter(|p| **p != state.profile)
+    {
+        let doc = storage?.get_string(&profile_key(name)).unwrap_or_default();
+        if !doc.is_empty() {
+            add(&serde_json::from_str::<AppState>(&doc).ok()?);
+        }
+    }
+    Some(known)
+}
+
+/// Adds a profile named `name` and switches to it.
+pub fn create(
+    state: &mut AppState,
+    storage: &mut dyn eframe::Storage,
+    name: &str,
+) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("the profile needs a name".to_string());
+    }
+    let mut names = state.profile_names();
+    if names.iter().any(|p| p == name) {
+        return Err(format!("a profile named \"{}\" exists already", name));
+    }
+    names.push(name.to_string());
+    state.profiles = names;
+    // A stale document of a deleted profile with the same name is not revived
+    storage.set_string(&profile_key(name), String::new());
+    switch(state, storage, name)
+}
+
+/// Removes the profile `name` with its hosts and settings; the active one cannot be removed.
+pub fn delete(
+    state: &mut AppState,
+    storage: &mut dyn eframe::Storage,
+    name: &str,
+) -> Result<(), String> {
+    if name == state.profile {
+        return Err("the active profile cannot be deleted".to_string());
+    }
+    let mut names = state.profile_names();
+    names.retain(|p| p != name);
+    state.profiles = names;
+    state.profile_networks.remove(name);
+    // The storage cannot remove keys, an empty document counts as none
+    storage.set_string(&profile_key(name), String::new());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::HostInfo;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct MemoryStorage(HashMap<String, String>);
+
+    impl eframe::Storage for MemoryStorage {
+        fn get_string(&self, key: &str) -> Option<String> {
+            self.0.get(key).cloned()
+        }
+
+        fn set_string(&mut self, key: &str, value: String) {
+            self.0.insert(key.to_string(), value);
+        }
+
+        fn flush(&mut self) {}
+    }
+
+    fn host(address: &str) -> HostInfo {
+        HostInfo {
+            address: address.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_profiles_keep_their_hosts_and_settings() {
+        let mut storage = MemoryStorage::default();
+        let mut state = AppState::default();
+        state.hosts.push(host("192.168.1.1"));
+        state.settings.dns_refresh_sec = 123;
+        state.pinger_generation = 7;
+
+        create(&mut state, &mut storage, " Office ").unwrap();
+        assert_eq!(state.profile, "Office");
+        assert_eq!(state.profile_names(), vec!["", "Office"]);
+        assert!(state.hosts.is_empty());
+        // A new profile starts from the current settings
+        assert_eq!(state.settings.dns_refresh_sec, 123);
+        assert_eq!(state.pinger_generation, 7);
+
+        state.hosts.push(host("10.0.0.1"));
+        switch(&mut state, &mut storage, "").unwrap();
+        assert_eq!(state.hosts[0].address, "192.168.1.1");
+        assert_eq!(state.profile_names(), vec!["", "Office"]);
+
+        switch(&mut state, &mut storage, "Office").unwrap();
+        assert_eq!(state.hosts[0].address, "10.0.0.1");
+        assert!(switch(&mut state, &mut storage, "Missing").is_err());
+    }
+
+    #[test]
+    fn test_history_of_every_profile_is_known() {
+        let mut storage = MemoryStorage::default();
+        let mut state = AppState::default();
+        create(&mut state, &mut storage, "Office").unwrap();
+        state.hosts.push(host("10.0.0.1"));
+        state.hosts.push(host("192.168.1.2"));
+        switch(&mut state, &mut storage, "").unwrap();
+
+        state.hosts.push(host("192.168.1.1"));
+        state.hosts.push(host("192.168.1.2"));
+        state
+            .statuses
+            .entry("192.168.1.2".to_string())
+            .or_default()
+            .add_sample(5.0, true);
+        state.api_error = Some("port in use".to_string());
+        switch(&mut state, &mut storage, "Office").unwrap();
+        // Statistics of a host in both profiles and the application state carry over
+        assert_eq!(state.statuses["192.168.1.2"].sent, 1);
+        assert_eq!(state.api_error.as_deref(), Some("port in use"));
+
+        let mut known = known_addresses(&state, Some(&storage)).unwrap();
+        known.sort();
+        assert_eq!(
+            known,
+            vec!["10.0.0.1", "192.168.1.1", "192.168.1.2", "192.168.1.2"]
+        );
+        storage.0.insert(profile_key(""), "{ damaged".to_string());
+        assert!(known_addresses(&state, Some(&storage)).is_none());
+    }
+
+    #[test]
+    fn test_create_and_delete_checks() {
+        let mut storage = MemoryStorage::default();
+        let mut state = AppState::default();
+        assert!(create(&mut state, &mut storage, "  ").is_err());
+        assert!(delete(&mut state, &mut storage, "").is_err());
+
+        create(&mut state, &mut storage, "Home").unwrap();
+        state.hosts.push(host("10.0.0.1"));
+        switch(&mut state, &mut storage, "").unwrap();
+        delete(&mut state, &mut storage, "Home").unwrap();
+        assert_eq!(state.profile_names(), vec![""]);
+
+        // A new profile with the name of a deleted one starts empty
+        create(&mut state, &mut storage, "Home").unwrap();
+        assert!(state.hosts.is_empty());
+    }
+}
@@ -0,0 +1,555 @@
+//! Availability reports per host and calendar period from the persisted history,
+//! shown as a table and exported as HTML or PDF.
+
+use crate::constants::STATE_CONFIRMATION_STREAK;
+use crate::logic::applog;
+use crate::logic::email::html_escape;
+use crate::logic::presenter::{format_duration, format_time};
+use crate::logic::sla::period_of;
+use crate::logic::storage::{self, HistoryAggregate, HistoryRecord};
+use crate::model::{HostInfo, SlaWindow};
+use eframe::egui;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tr::tr;
+
+/// Periods a report can be broken down into.
+pub const REPORT_PERIODS: [SlaWindow; 3] = [SlaWindow::Day, SlaWindow::Week, SlaWindow::Month];
+
+/// Statistics of one host over one period.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportRow {
+    /// Start and end of the period as Unix timestamps.
+    pub start: u64,
+    pub end: u64,
+    /// Name of the host, or its address.
+    pub host: String,
+    pub address: String,
+    pub aggregate: HistoryAggregate,
+    /// Outages that started in the period.
+    pub outages: usize,
+    /// Seconds of the period spent in an outage.
+    pub downtime: u64,
+}
+
+/// Availability of all hosts over the last periods, oldest period first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub window: SlaWindow,
+    /// Unix timestamp the report was generated at.
+    pub generated: u64,
+    pub rows: Vec<ReportRow>,
+}
+
+/// Start and end of the `count` periods of `window` up to the one containing `now`.
+pub fn periods(window: SlaWindow, count: usize, now: u64) -> Vec<(u64, u64)> {
+    let mut periods = vec![period_of(window, now)];
+    while periods.len() < count.max(1) {
+        let (start, _) = periods[periods.len() - 1];
+        periods.push(period_of(window, start.saturating_sub(1)));
+    }
+    periods.reverse();
+    periods
+}
+
+/// Outages in the records (oldest first) as start and end: runs of lost probes long
+/// enough to confirm a state change, ending with the next reply or the last record.
+pub fn outages(records: &[HistoryRecord]) -> Vec<(u64, u64)> {
+    let mut outages = Vec::new();
+    let mut run: Option<(u64, u32)> = None;
+    for record in records {
+        match (record.rtt, &mut run) {
+            (None, Some((_, len))) => *len += 1,
+            (None, None) => run = Some((record.timestamp, 1)),
+            (Some(_), Some((start, len))) => {
+                if *len >= STATE_CONFIRMATION_STREAK {
+                    outages.push((*start, record.timestamp));
+                }
+                run = None;
+            }
+            (Some(_), None) => {}
+        }
+    }
+    if let (Some((start, len)), Some(last)) = (run, records.last())
+        && len >= STATE_CONFIRMATION_STREAK
+    {
+        outages.push((start, last.timestamp));
+    }
+    outages
+}
+
+/// The rows of one host for `periods`, from its records (oldest first).
+pub fn host_rows(
+    host: &str,
+    address: &str,
+    records: &[HistoryRecord],
+    periods: &[(u64, u64)],
+) -> Vec<ReportRow> {
+    let outages = outages(records);
+    periods
+        .iter()
+        .map(|&(start, end)| {
+            let from = records.partition_point(|r| r.timestamp < start);
+            let to = records.partition_point(|r| r.timestamp < end);
+            ReportRow {
+                start,
+                end,
+                host: host.to_string(),
+                address: address.to_string(),
+                aggregate: storage::aggregate(&records[from..to]),
+                outages: outages
+                    .iter()
+                    .filter(|(s, _)| (start..end).contains(s))
+                    .count(),
+                downtime: outages
+                    .iter()
+                    .map(|&(s, e)| e.min(end).saturating_sub(s.max(start)))
+                    .sum(),
+            }
+        })
+        .collect()
+}
+
+/// Reads the history of `hosts` and builds the report of the last `count` periods.
+///
+/// Reading the history takes a while, so the hosts are a copy taken from the
+/// state rather than the locked state itself.
+pub fn build(hosts: &[HostInfo], window: SlaWindow, count: usize, now: u64) -> Report {
+    let periods = periods(window, count, now);
+    let from = periods.first().map_or(now, |p| p.0);
+    let mut per_host: Vec<Vec<ReportRow>> = hosts
+        .iter()
+        .filter(|h| !h.passive)
+        .map(|h| {
+            let records = storage::query(&h.address, from).unwrap_or_default();
+            let name = if h.name.is_empty() {
+                &h.address
+            } else {
+                &h.name
+            };
+            host_rows(name, &h.address, &records, &periods)
+        })
+        .filter(|rows| rows.iter().any(|r| r.aggregate.sent > 0))
+        .collect();
+    // Grouped by period, hosts in list order
+    let mut rows = Vec::new();
+    for _ in &periods {
+        for host in per_host.iter_mut() {
+            rows.push(host.remove(0));
+        }
+    }
+    Report {
+        window,
+        generated: now,
+        rows,
+    }
+}
+
+/// Label of the period of a row, e.g. "2024-05-01 – 2024-06-01".
+pub fn period_label(row: &ReportRow) -> String {
+    let date = |ts: u64| format_time(ts).chars().take(10).collect::<String>();
+    if row.end - row.start <= 86_400 + 3_600 {
+        date(row.start)
+    } else {
+        format!("{} – {}", date(row.start), date(row.end))
+    }
+}
+
+/// Column headers of the report table.
+pub fn headers() -> [String; 8] {
+    [
+        tr!("Period"),
+        tr!("Host"),
+        tr!("Availability"),
+        tr!("Mean RTT"),
+        tr!("p95 RTT"),
+        tr!("Outages"),
+        tr!("Downtime"),
+        tr!("Probes"),
+    ]
+}
+
+/// The cells of a row, in the order of [`headers`].
+pub fn cells(row: &ReportRow) -> [String; 8] {
+    let a = &row.aggregate;
+    let answered = a.sent > a.lost;
+    [
+        period_label(row),
+        if row.host == row.address {
+            row.host.clone()
+        } else {
+            format!("{} ({})", row.host, row.address)
+        },
+        if a.sent > 0 {
+            format!("{:.3} %", a.availability)
+        } else {
+            "—".to_string()
+        },
+        if answered {
+            format!("{:.1} ms", a.mean)
+        } else {
+            "—".to_string()
+        },
+        if answered {
+            format!("{:.1} ms", a.p95)
+        } else {
+            "—".to_string()
+        },
+        row.outages.to_string(),
+        format_duration(row.downtime),
+        a.sent.to_string(),
+    ]
+}
+
+/// Title of the report, e.g. "Monthly availability report".
+pub fn title(report: &Report) -> String {
+    match report.window {
+        SlaWindow::Day => tr!("Daily availability report"),
+        SlaWindow::Week => tr!("Weekly availability report"),
+        _ => tr!("Monthly availability report"),
+    }
+}
+
+/// The report as a standalone HTML page, suitable for printing.
+pub fn html(report: &Report) -> String {
+    let title = html_escape(&title(report));
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>\n\
+         body {{ font-family: sans-serif; margin: 2em; }}\n\
+         table {{ border-collapse: collapse; }}\n\
+         th, td {{ border: 1px solid #999; padding: 4px 8px; }}\n\
+         th {{ background: #eee; }}\n\
+         td.num {{ text-align: right; }}\n\
+         </style>\n</head>\n<body>\n<h1>{title}</h1>\n<p>{}</p>\n<table>\n<tr>",
+        html_escape(&tr!("Generated {time}").replace("{time}", &format_time(report.generated)))
+    );
+    for header in headers() {
+        out.push_str(&format!("<th>{}</th>", html_escape(&header)));
+    }
+    out.push_str("</tr>\n");
+    for row in &report.rows {
+        out.push_str("<tr>");
+        for (i, cell) in cells(row).iter().enumerate() {
+            let class = if i >= 2 { " class=\"num\"" } else { "" };
+            out.push_str(&format!("<td{}>{}</td>", class, html_escape(cell)));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n</body>\n</html>\n");
+    out
+}
+
+/// The report as a fixed-width text table.
+pub fn text_table(report: &Report) -> Vec<String> {
+    let rows: Vec<[String; 8]> = report.rows.iter().map(cells).collect();
+    let headers = headers();
+    let widths: Vec<usize> = (0..headers.len())
+        .map(|i| {
+            rows.iter()
+                .map(|r| r[i].chars().count())
+                .chain([headers[i].chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, (cell, &w))| {
+                if i >= 2 {
+                    format!("{:>w$}", cell)
+                } else {
+                    format!("{:<w$}", cell)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    let mut lines = vec![line(&headers)];
+    lines.push("-".repeat(lines[0].chars().count()));
+    lines.extend(rows.iter().map(|r| line(r)));
+    lines
+}
+
+/// The monospace font of the PDF: egui's Hack, which covers Latin, Greek and
+/// Cyrillic. It is embedded whole.
+fn pdf_font() -> std::borrow::Cow<'static, [u8]> {
+    egui::FontDefinitions::default().font_data["Hack"]
+        .font
+        .clone()
+}
+
+/// Text as a PDF hex string of the glyph ids of `face`, the character codes of
+/// the embedded font; the glyphs are collected in `used` for [`to_unicode`].
+fn pdf_glyphs(face: &ttf_parser::Face, text: &str, used: &mut BTreeMap<u16, char>) -> Vec<u8> {
+    let mut out = vec![b'<'];
+    for c in text.chars() {
+        let glyph = face.glyph_index(c).map_or(0, |g| g.0);
+        if glyph != 0 {
+            used.entry(glyph).or_insert(c);
+        }
+        out.extend(format!("{:04X}", glyph).into_bytes());
+    }
+    out.push(b'>');
+    out
+}
+
+/// CMap from the glyph ids back to the characters, so that the text of the PDF
+/// can be searched and copied.
+fn to_unicode(used: &BTreeMap<u16, char>) -> Vec<u8> {
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+         1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+    );
+    let used: Vec<(&u16, &char)> = used.iter().collect();
+    // At most 100 entries per section
+    for section in used.chunks(100) {
+        cmap.push_str(&format!("{} beginbfchar\n", section.len()));
+        for (glyph, c) in section {
+            let text: String = c
+                .encode_utf16(&mut [0; 2])
+                .iter()
+                .map(|unit| format!("{:04X}", unit))
+                .collect();
+            cmap.push_str(&format!("<{:04X}> <{}>\n", glyph, text));
+        }
+        cmap.push_str("endbfchar\n");
+    }
+    cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend");
+    cmap.into_bytes()
+}
+
+/// A PDF stream object with `content` and the extra entries of its dictionary.
+fn pdf_stream(entries: &str, content: &[u8]) -> Vec<u8> {
+    let mut stream = format!("<< /Length {}{} >>\nstream\n", content.len(), entries).into_bytes();
+    stream.extend(content);
+    stream.extend(b"\nendstream");
+    stream
+}
+
+/// The report as a PDF document of landscape A4 pages with the table set in an
+/// embedded monospace font.
+pub fn pdf(report: &Report) -> Vec<u8> {
+    const LINES_PER_PAGE: usize = 48;
+    let font = pdf_font();
+    let face =
+        ttf_parser::Face::parse(&font, 0).expect("The bundled font is a valid TrueType font");
+    let units = f64::from(face.units_per_em());
+    let scaled = |value: i16| (f64::from(value) * 1000.0 / units).round() as i32;
+    let advance = face
+        .glyph_index('0')
+        .and_then(|g| face.glyph_hor_advance(g))
+        .unwrap_or(face.units_per_em() * 3 / 5);
+
+    let mut lines = vec![
+        title(report),
+        tr!("Generated {time}").replace("{time}", &format_time(report.generated)),
+        String::new(),
+    ];
+    lines.extend(text_table(report));
+    // Wide tables shrink to fit the page
+    let widest = lines.iter().map(|l| l.chars().count()).max().unwrap_or(1);
+    let em = f64::from(advance) / units;
+    let size = (770.0 / (widest as f64 * em)).clamp(4.0, 9.0);
+
+    let mut used = BTreeMap::new();
+    let pages: Vec<Vec<u8>> = lines
+        .chunks(LINES_PER_PAGE)
+        .map(|page| {
+            let mut content =
+                format!("BT /F1 {:.1} Tf 36 559 Td {:.1} TL\n", size, 10.0).into_bytes();
+            for line in page {
+                content.extend(pdf_glyphs(&face, line, &mut used));
+                content.extend(b" Tj T*\n");
+            }
+            content.extend(b"ET");
+            content
+        })
+        .collect();
+
+    // Objects 1-7 are the catalog, the page tree and the font; pages follow in pairs
+    let bbox = face.global_bounding_box();
+    let mut objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            (0..pages.len())
+                .map(|i| format!("{} 0 R", 8 + i * 2))
+                .collect::<Vec<_>>()
+                .join(" "),
+            pages.len()
+        )
+        .into_bytes(),
+        b"<< /Type /Font /Subtype /Type0 /BaseFont /Hack-Regular /Encoding /Identity-H \
+          /DescendantFonts [4 0 R] /ToUnicode 7 0 R >>"
+            .to_vec(),
+        format!(
+            "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /Hack-Regular \
+             /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+             /FontDescriptor 5 0 R /DW {} /CIDToGIDMap /Identity >>",
+            scaled(advance as i16)
+        )
+        .into_bytes(),
+        format!(
+            "<< /Type /FontDescriptor /FontName /Hack-Regular /Flags 33 \
+             /FontBBox [{} {} {} {}] /ItalicAngle 0 /Ascent {} /Descent {} \
+             /CapHeight {} /StemV 80 /FontFile2 6 0 R >>",
+            scaled(bbox.x_min),
+            scaled(bbox.y_min),
+            scaled(bbox.x_max),
+            scaled(bbox.y_max),
+            scaled(face.ascender()),
+            scaled(face.descender()),
+            scaled(face.capital_height().unwrap_or(face.ascender())),
+        )
+        .into_bytes(),
+        pdf_stream(&format!(" /Length1 {}", font.len()), &font),
+        pdf_stream("", &to_unicode(&used)),
+    ];
+    for (i, content) in pages.iter().enumerate() {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 842 595] \
+                 /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                9 + i * 2
+            )
+            .into_bytes(),
+        );
+        objects.push(pdf_stream("", content));
+    }
+
+    // The comment of high bytes marks the file as binary
+    let mut out = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.extend(format!("{} 0 obj\n", i + 1).into_bytes());
+        out.extend(object);
+        out.extend(b"\nendobj\n");
+    }
+    let xref = out.len();
+    out.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+    for offset in offsets {
+        out.extend(format!("{:010} 00000 n \n", offset).into_bytes());
+    }
+    out.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .into_bytes(),
+    );
+    out
+}
+
+/// Writes the report as `extension` ("html" or "pdf") into a new timestamped file under `dir`.
+pub fn save(report: &Report, extension: &str, dir: &Path) -> std::io::Result<PathBuf> {
+    let path = dir.join(format!(
+        "egui_pinger-report-{}.{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        extension
+    ));
+    let content = match extension {
+        "pdf" => pdf(report),
+        _ => html(report).into_bytes(),
+    };
+    std::fs::File::create(&path)?.write_all(&content)?;
+    applog::info("report", format!("Report saved to {}", path.display()));
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(timestamp: u64, rtt: Option<f32>) -> HistoryRecord {
+        HistoryRecord { timestamp, rtt }
+    }
+
+    #[test]
+    fn test_outages_need_a_confirmed_streak() {
+        let records: Vec<HistoryRecord> = [
+            Some(10.0),
+            None,
+            None,
+            Some(10.0),
+            None,
+            None,
+            None,
+            None,
+            Some(12.0),
+            None,
+            None,
+            None,
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, rtt)| record(100 + i as u64 * 10, rtt))
+        .collect();
+        // Two lost probes are no outage; an open outage ends with the last record
+        assert_eq!(outages(&records), vec![(140, 180), (190, 210)]);
+
+        let rows = host_rows("Router", "10.0.0.1", &records, &[(100, 150), (150, 250)]);
+        assert_eq!(rows[0].outages, 1);
+        assert_eq!(rows[0].downtime, 10);
+        assert_eq!(rows[0].aggregate.sent, 5);
+        assert_eq!(rows[1].outages, 1);
+        assert_eq!(rows[1].downtime, 30 + 20);
+        assert_eq!(rows[1].aggregate.sent, 7);
+    }
+
+    #[test]
+    fn test_report_exports() {
+        let (start, end) = period_of(SlaWindow::Month, 1_700_000_000);
+        assert_eq!(periods(SlaWindow::Month, 2, 1_700_000_000)[1], (start, end));
+        let records: Vec<HistoryRecord> = (0..100)
+            .map(|i| record(start + i * 60, (i % 10 != 0).then_some(20.0)))
+            .collect();
+        let report = Report {
+            window: SlaWindow::Month,
+            generated: end,
+            rows: host_rows("<Office>", "10.0.0.1", &records, &[(start, end)]),
+        };
+        assert_eq!(report.rows[0].aggregate.availability, 90.0);
+
+        let html = html(&report);
+        assert!(html.contains("<h1>Monthly availability report</h1>"));
+        assert!(html.contains("<td>&lt;Office&gt; (10.0.0.1)</td><td class=\"num\">90.000 %</td>"));
+
+        let pdf = pdf(&report);
+        assert!(pdf.starts_with(b"%PDF-1.4\n"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+        let text = String::from_utf8_lossy(&pdf);
+        // The text is in glyph ids, mapped back to characters for copying
+        let font = pdf_font();
+        let face = ttf_parser::Face::parse(&font, 0).unwrap();
+        let mut used = BTreeMap::new();
+        let glyphs = pdf_glyphs(&face, "<Office> (10.0.0.1)", &mut used);
+        let glyphs = std::str::from_utf8(&glyphs).unwrap();
+        assert!(text.contains(glyphs.trim_matches(['<', '>'])));
+        let cyrillic = face.glyph_index('Ж').unwrap().0;
+        pdf_glyphs(&face, "Ж", &mut used);
+        assert!(
+            String::from_utf8(to_unicode(&used))
+                .unwrap()
+                .contains(&format!("<{:04X}> <0416>", cyrillic))
+        );
+        // The cross-reference table points at the objects
+        let xref: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|t| t.lines().next())
+            .and_then(|n| n.parse().ok())
+            .unwrap();
+        assert!(pdf[xref..].starts_with(b"xref\n"));
+    }
+}
@@ -0,0 +1,205 @@
+use crate::model::{AppState, Connectivity};
+use tr::tr;
+
+/// What the rest of the monitored network looked like when a host stopped answering.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Evidence {
+    pub captive_portal: bool,
+    /// The host name did not resolve.
+    pub dns_failed: bool,
+    /// The host name resolved to an unexpected address.
+    pub dns_mismatch: bool,
+    /// Whether the first hop of the route answers, if the route is known.
+    pub gateway_up: Option<bool>,
+    /// Hop where the route breaks, as deduced from hop pings.
+    pub failure_point: Option<String>,
+    pub group: String,
+    /// Other active hosts in the same group, and how many of them are down.
+    pub group_total: usize,
+    pub group_down: usize,
+    /// All other active hosts, and how many of them are down.
+    pub others_total: usize,
+    pub others_down: usize,
+}
+
+/// Collects evidence about the network around `address` from the current state.
+pub fn survey(state: &AppState, address: &str, dns_failed: bool) -> Evidence {
+    let status = state.statuses.get(address);
+    let group = state
+        .hosts
+        .iter()
+        .find(|h| h.address == address)
+        .map(|h| h.group.trim().to_string())
+        .unwrap_or_default();
+
+    let mut evidence = Evidence {
+        captive_portal: matches!(state.connectivity, Connectivity::CaptivePortal { .. }),
+        dns_failed,
+        dns_mismatch: status.is_some_and(|s| s.dns_mismatch.is_some()),
+        gateway_up: status
+            .and_then(|s| s.traceroute_path.first())
+            .filter(|hop| hop.as_str() != address)
+            .and_then(|hop| state.statuses.get(hop))
+            .filter(|hop| hop.sent > 0)
+            .map(|hop| hop.alive),
+        failure_point: status.and_then(|s| s.failure_point.clone()),
+        group: group.clone(),
+        ..Default::default()
+    };
+
+    for h in &state.hosts {
+        if h.address == address || h.is_stopped || h.passive {
+            continue;
+        }
+        let Some(s) = state.statuses.get(&h.address).filter(|s| s.sent > 0) else {
+            continue;
+        };
+        let down = !s.alive;
+        evidence.others_total += 1;
+        evidence.others_down += down as usize;
+        if !group.is_empty() && h.group.trim() == group {
+            evidence.group_total += 1;
+            evidence.group_down += down as usize;
+        }
+    }
+    evidence
+}
+
+/// Applies the rules, most specific first, and returns a one-line hint about the likely cause.
+pub fn diagnose(e: &Evidence) -> Option<String> {
+    if e.captive_portal {
+        return Some(tr!("behind a captive portal — log in to the network"));
+    }
+    if e.dns_failed {
+        return Some(tr!(
+            "name does not resolve — likely a DNS problem, not the host"
+        ));
+    }
+    if e.dns_mismatch {
+        return Some(tr!("DNS returns an unexpected address"));
+    }
+    if e.others_total > 0 && e.others_down == e.others_total {
+        return Some(tr!(
+            "all hosts down — likely the local network or Internet connection"
+        ));
+    }
+    if e.gateway_up == Some(false) {
+        return Some(tr!("gateway not reachable — likely the local network"));
+    }
+    if e.group_total > 0 && e.group_down == e.group_total {
+        return Some(
+            tr!("all hosts in group {group} down — likely a shared link or site")
+                .replace("{group}", &e.group),
+        );
+    }
+    if let Some(fp) = e
+        .failure_point
+        .as_ref()
+        .filter(|fp| *fp != "Local Interface")
+    {
+        return Some(tr!("route breaks at {node}").replace("{node}", fp));
+    }
+    if e.group_down > 0 {
+        return Some(
+            tr!("{down} of {total} other hosts in the group also down")
+                .replace("{down}", &e.group_down.to_string())
+                .replace("{total}", &e.group_total.to_string()),
+        );
+    }
+    if e.gateway_up == Some(true) {
+        return Some(tr!(
+            "gateway reachable, only this host down — likely the device itself"
+        ));
+    }
+    if e.others_total > 0 && e.others_down == 0 {
+        return Some(tr!(
+            "other hosts up, only this host down — likely the device itself"
+        ));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{HostInfo, HostStatus};
+
+    fn status(alive: bool) -> HostStatus {
+        let mut s = HostStatus::default();
+        s.add_sample(if alive { 10.0 } else { f64::NAN }, alive);
+        s.alive = alive;
+        s
+    }
+
+    fn host(address: &str, group: &str) -> HostInfo {
+        HostInfo {
+            address: address.to_string(),
+            group: group.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_survey_counts_group_and_gateway() {
+        let mut state = AppState {
+            hosts: vec![
+                host("10.1.0.5", "site"),
+                host("10.1.0.6", "site"),
+                host("10.1.0.7", "site"),
+                host("8.8.8.8", ""),
+            ],
+            ..Default::default()
+        };
+        let mut target = status(false);
+        target.traceroute_path = vec!["192.168.1.1".to_string(), "10.1.0.5".to_string()];
+        state.statuses.insert("10.1.0.5".to_string(), target);
+        state.statuses.insert("10.1.0.6".to_string(), status(false));
+        state.statuses.insert("10.1.0.7".to_string(), status(true));
+        state.statuses.insert("8.8.8.8".to_string(), status(true));
+        state
+            .statuses
+            .insert("192.168.1.1".to_string(), status(true));
+
+        let e = survey(&state, "10.1.0.5", false);
+        assert_eq!(e.gateway_up, Some(true));
+        assert_eq!((e.group_down, e.group_total), (1, 2));
+        assert_eq!((e.others_down, e.others_total), (1, 3));
+        assert_eq!(
+            diagnose(&e).as_deref(),
+            Some("1 of 2 other hosts in the group also down")
+        );
+    }
+
+    #[test]
+    fn test_rules_priority() {
+        let alone = Evidence {
+            gateway_up: Some(true),
+            others_total: 3,
+            ..Default::default()
+        };
+        assert_eq!(
+            diagnose(&alone).as_deref(),
+            Some("gateway reachable, only this host down — likely the device itself")
+        );
+
+        let everything_down = Evidence {
+            gateway_up: Some(false),
+            others_total: 3,
+            others_down: 3,
+            ..Default::default()
+        };
+        assert!(
+            diagnose(&everything_down)
+                .unwrap()
+                .starts_with("all hosts down")
+        );
+
+        let dns = Evidence {
+            dns_failed: true,
+            ..everything_down
+        };
+        assert!(diagnose(&dns).unwrap().starts_with("name does not resolve"));
+
+        assert_eq!(diagnose(&Evidence::default()), None);
+    }
+}
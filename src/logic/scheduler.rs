@@ -0,0 +1,100 @@
+//! Probe deadlines of the hosts, kept in a priority queue so the pinger loop
+//! sleeps until the next one is due instead of polling.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::time::Instant;
+
+/// Deadlines of the next probe of each host, earliest first.
+///
+/// Rescheduling or removing a host leaves its old queue entry behind; entries
+/// that no longer match the host's deadline are skipped when they come up.
+#[derive(Debug, Default)]
+pub struct Schedule {
+    queue: BinaryHeap<Reverse<(Instant, String)>>,
+    deadlines: HashMap<String, Instant>,
+}
+
+impl Schedule {
+    /// Deadline of the next probe of `address`, if it is scheduled.
+    pub fn deadline(&self, address: &str) -> Option<Instant> {
+        self.deadlines.get(address).copied()
+    }
+
+    /// Schedules the next probe of `address` at `at`, replacing its deadline.
+    pub fn set(&mut self, address: &str, at: Instant) {
+        self.deadlines.insert(address.to_string(), at);
+        self.queue.push(Reverse((at, address.to_string())));
+    }
+
+    /// Takes `address` off the schedule.
+    pub fn remove(&mut self, address: &str) {
+        self.deadlines.remove(address);
+    }
+
+    /// Keeps only the addresses for which `keep` returns true.
+    pub fn retain(&mut self, mut keep: impl FnMut(&str) -> bool) {
+        self.deadlines.retain(|address, _| keep(address));
+    }
+
+    /// The earliest deadline, if any host is scheduled.
+    pub fn next_deadline(&mut self) -> Option<Instant> {
+        self.drop_stale();
+        self.queue.peek().map(|Reverse((at, _))| *at)
+    }
+
+    /// Takes the hosts due at `now` off the schedule, with their deadlines, earliest first.
+    pub fn pop_due(&mut self, now: Instant) -> Vec<(String, Instant)> {
+        let mut due = Vec::new();
+        while let Some(at) = self.next_deadline().filter(|at| *at <= now) {
+            if let Some(Reverse((_, address))) = self.queue.pop() {
+                self.deadlines.remove(&address);
+                due.push((address, at));
+            }
+        }
+        due
+    }
+
+    /// Pops queue entries of removed or rescheduled hosts off the top.
+    fn drop_stale(&mut self) {
+        while let Some(Reverse((at, address))) = self.queue.peek() {
+            if self.deadlines.get(address) == Some(at) {
+                return;
+            }
+            self.queue.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_pop_due_in_deadline_order() {
+        let now = Instant::now();
+        let secs = Duration::from_secs;
+        let mut schedule = Schedule::default();
+        schedule.set("a", now + secs(5));
+        schedule.set("b", now + secs(1));
+        schedule.set("c", now + secs(3));
+        schedule.set("d", now + secs(2));
+        // Rescheduled and removed hosts leave stale entries behind
+        schedule.set("a", now + secs(2));
+        schedule.remove("d");
+        schedule.retain(|address| address != "c");
+
+        assert_eq!(schedule.next_deadline(), Some(now + secs(1)));
+        assert!(schedule.pop_due(now).is_empty());
+        assert_eq!(
+            schedule.pop_due(now + secs(10)),
+            vec![
+                ("b".to_string(), now + secs(1)),
+                ("a".to_string(), now + secs(2))
+            ]
+        );
+        assert_eq!(schedule.next_deadline(), None);
+        assert_eq!(schedule.deadline("a"), None);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::logic::applog;
+use eframe::egui::ColorImage;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Directory where incident screenshots are saved.
+pub fn incidents_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("egui_pinger")
+        .join("incidents")
+}
+
+/// File name (without extension) of the screenshot of an incident change.
+pub fn file_stem(address: &str, timestamp: u64, is_break: bool) -> String {
+    let time = chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .unwrap_or_default()
+        .with_timezone(&chrono::Local)
+        .format("%Y%m%d-%H%M%S");
+    let host: String = address
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!(
+        "{}_{}_{}",
+        time,
+        host,
+        if is_break { "down" } else { "restored" }
+    )
+}
+
+/// Encodes a screenshot as PNG in the incidents directory without blocking the UI.
+pub fn save(image: Arc<ColorImage>, stem: String) {
+    std::thread::spawn(move || {
+        let dir = incidents_dir();
+        let path = dir.join(format!("{}.png", stem));
+        let [width, height] = image.size;
+        let pixels: Vec<u8> = image.pixels.iter().flat_map(|p| p.to_array()).collect();
+        let result = std::fs::create_dir_all(&dir)
+            .map_err(|e| e.to_string())
+            .and_then(|_| {
+                image::RgbaImage::from_raw(width as u32, height as u32, pixels)
+                    .ok_or_else(|| "invalid image size".to_string())
+            })
+            .and_then(|img| img.save(&path).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => applog::info(
+                "screenshot",
+                format!("Saved incident screenshot {}", path.display()),
+            ),
+            Err(e) => applog::warn(
+                "screenshot",
+                format!("Failed to save {}: {}", path.display(), e),
+            ),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_stem_is_safe() {
+        let stem = file_stem("https://example.com/a b", 0, true);
+        assert!(stem.ends_with("_https___example.com_a_b_down"));
+        assert!(!stem.contains(['/', ':', ' ']));
+    }
+}
@@ -0,0 +1,49 @@
+//! Cancellation of the pinger and its probes when the application quits.
+
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Tells the pinger loop and its probes to stop; clones share the state.
+#[derive(Debug, Clone)]
+pub struct CancellationToken(Arc<watch::Sender<bool>>);
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self(Arc::new(watch::channel(false).0))
+    }
+}
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Completes once the token is cancelled.
+    pub async fn cancelled(&self) {
+        let mut cancelled = self.0.subscribe();
+        // The sender lives as long as this token, so waiting cannot fail
+        let _ = cancelled.wait_for(|c| *c).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_clones_see_the_cancellation() {
+        let token = CancellationToken::default();
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+        let waiter = tokio::spawn(async move { clone.cancelled().await });
+        token.cancel();
+        waiter.await.unwrap();
+        assert!(token.is_cancelled());
+        // Waiting after the cancellation returns at once
+        token.cancelled().await;
+    }
+}
@@ -0,0 +1,232 @@
+//! Scripted probe results for exercising failure handling deterministically in tests.
+//!
+//! A [`Scenario`] replaces the network: it describes which failure a host shows
+//! during which part of a timeline and feeds the matching results into the
+//! state exactly as the probe tasks of the pinger would.
+
+use crate::logic::SharedState;
+use crate::logic::pinger::{ProbeResult, apply_probe_result};
+use crate::model::HostInfo;
+
+/// A class of failure a simulated host can show.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Failure {
+    /// The resolver does not answer.
+    DnsTimeout,
+    /// The name does not exist.
+    Nxdomain,
+    /// A router answers with ICMP destination unreachable.
+    IcmpUnreachable,
+    /// Echo requests are dropped without any answer.
+    SilentLoss,
+    /// The host answers, with latency rising linearly over the phase.
+    LatencyRamp { from_ms: f64, to_ms: f64 },
+}
+
+/// A failure from `start` until before `end` (seconds since the start of the scenario).
+#[derive(Debug, Clone, PartialEq)]
+struct Phase {
+    start: u64,
+    end: u64,
+    failure: Failure,
+}
+
+/// Timeline of failures of one host; outside of them it answers in `base_rtt_ms`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Scenario {
+    base_rtt_ms: f64,
+    interval_sec: u64,
+    phases: Vec<Phase>,
+}
+
+impl Scenario {
+    /// A healthy host probed every `interval_sec` seconds.
+    pub fn new(base_rtt_ms: f64, interval_sec: u64) -> Self {
+        Self {
+            base_rtt_ms,
+            interval_sec: interval_sec.max(1),
+            phases: Vec::new(),
+        }
+    }
+
+    /// Adds a failure from `start` until before `end`; later phases win on overlap.
+    pub fn with(mut self, start: u64, end: u64, failure: Failure) -> Self {
+        self.phases.push(Phase {
+            start,
+            end,
+            failure,
+        });
+        self
+    }
+
+    /// The result of a probe `offset` seconds into the scenario, which started at `start_ts`.
+    pub fn result_at(&self, host: &HostInfo, start_ts: u64, offset: u64) -> ProbeResult {
+        let mut result = ProbeResult::new(
+            &host.address,
+            true,
+            self.base_rtt_ms,
+            false,
+            Some(host.clone()),
+        );
+        result.timestamp = start_ts + offset;
+
+        let phase = self
+            .phases
+            .iter()
+            .rev()
+            .find(|p| (p.start..p.end).contains(&offset));
+        let Some(phase) = phase else {
+            return result;
+        };
+        let (dns_error, error) = match phase.failure {
+            Failure::DnsTimeout => (true, Some("DNS timeout")),
+            Failure::Nxdomain => (true, Some("NXDOMAIN")),
+            Failure::IcmpUnreachable => (false, Some("Destination unreachable")),
+            Failure::SilentLoss => (false, None),
+            Failure::LatencyRamp { from_ms, to_ms } => {
+                let progress = (offset - phase.start) as f64
+                    / (phase.end - phase.start).saturating_sub(1).max(1) as f64;
+                result.rtt_ms = from_ms + (to_ms - from_ms) * progress;
+                return result;
+            }
+        };
+        result.alive = false;
+        result.rtt_ms = f64::NAN;
+        result.dns_error = dns_error;
+        result.probe_error = error.map(str::to_string);
+        result
+    }
+
+    /// Applies the probes of the first `duration` seconds to `host` in `state`.
+    pub fn run(&self, state: &SharedState, host: &HostInfo, start_ts: u64, duration: u64) {
+        for offset in (0..duration).step_by(self.interval_sec as usize) {
+            apply_probe_result(state, &self.result_at(host, start_ts, offset));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{AlertRule, AppState, HostStatus, LogEntry, SampleOutcome};
+    use std::sync::{Arc, Mutex};
+
+    const START: u64 = 1_700_000_000;
+
+    fn setup(rules: Vec<AlertRule>) -> (SharedState, HostInfo) {
+        let host = HostInfo {
+            name: "Simulated".to_string(),
+            address: "sim.example".to_string(),
+            alert_rules: rules,
+            ..Default::default()
+        };
+        let mut state = AppState::default();
+        state.settings.desktop_notifications = false;
+        state.hosts.push(host.clone());
+        state
+            .statuses
+            .insert(host.address.clone(), HostStatus::default());
+        (Arc::new(Mutex::new(state)), host)
+    }
+
+    fn incidents(status: &HostStatus) -> Vec<(bool, u64)> {
+        status
+            .events
+            .iter()
+            .filter_map(|e| match e {
+                LogEntry::Incident {
+                    is_break,
+                    timestamp,
+                    ..
+                } => Some((*is_break, *timestamp)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_dns_failures_are_recorded_as_dns_errors() {
+        let (state, host) = setup(Vec::new());
+        Scenario::new(20.0, 1)
+            .with(10, 15, Failure::DnsTimeout)
+            .with(15, 20, Failure::Nxdomain)
+            .run(&state, &host, START, 17);
+
+        let sl = state.lock().unwrap();
+        let status = &sl.statuses[&host.address];
+        assert!(status.dns_error);
+        assert_eq!(status.probe_error.as_deref(), Some("NXDOMAIN"));
+        assert_eq!(status.lost, 7);
+        assert!(
+            status
+                .history
+                .iter()
+                .skip(10)
+                .all(|s| s.outcome == SampleOutcome::DnsError)
+        );
+        assert_eq!(incidents(status), vec![(true, START + 12)]);
+    }
+
+    #[test]
+    fn test_unreachable_and_silent_loss_open_and_close_incidents() {
+        let (state, host) = setup(vec![AlertRule::ConsecutiveFailures { count: 5 }]);
+        let scenario = Scenario::new(20.0, 1)
+            .with(5, 10, Failure::IcmpUnreachable)
+            .with(20, 22, Failure::SilentLoss);
+
+        scenario.run(&state, &host, START, 9);
+        {
+            let sl = state.lock().unwrap();
+            let status = &sl.statuses[&host.address];
+            assert!(!status.dns_error);
+            assert_eq!(
+                status.probe_error.as_deref(),
+                Some("Destination unreachable")
+            );
+            assert_eq!(
+                status.history.back().unwrap().outcome,
+                SampleOutcome::Timeout
+            );
+            assert!(status.active_alerts.is_empty());
+        }
+
+        // Restart the timeline on a fresh host to see the whole outage
+        let (state, host) = setup(vec![AlertRule::ConsecutiveFailures { count: 5 }]);
+        scenario.run(&state, &host, START, 30);
+        let sl = state.lock().unwrap();
+        let status = &sl.statuses[&host.address];
+        // Two lost pings are too few for an incident
+        assert_eq!(
+            incidents(status),
+            vec![(true, START + 7), (false, START + 10)]
+        );
+        let raised = status
+            .events
+            .iter()
+            .filter(|e| matches!(e, LogEntry::Alert { raised: true, .. }))
+            .count();
+        assert_eq!(raised, 1);
+        assert!(status.active_alerts.is_empty());
+    }
+
+    #[test]
+    fn test_latency_ramp_raises_latency_alert() {
+        let (state, host) = setup(vec![AlertRule::LatencyAbove { ms: 100.0 }]);
+        let scenario = Scenario::new(20.0, 1).with(
+            0,
+            60,
+            Failure::LatencyRamp {
+                from_ms: 20.0,
+                to_ms: 400.0,
+            },
+        );
+        assert_eq!(scenario.result_at(&host, START, 59).rtt_ms, 400.0);
+
+        scenario.run(&state, &host, START, 60);
+        let sl = state.lock().unwrap();
+        let status = &sl.statuses[&host.address];
+        assert_eq!(status.lost, 0);
+        assert_eq!(status.active_alerts.len(), 1);
+        assert!(incidents(status).is_empty());
+    }
+}
@@ -0,0 +1,290 @@
+//! Comparison of the persisted history with ISP service level targets.
+
+use crate::logic::presenter::{format_duration, format_time};
+use crate::logic::storage::{self, HistoryAggregate, HistoryRecord};
+use crate::model::{AppState, SlaMetric, SlaSettings, SlaTarget, SlaWindow};
+use chrono::{Datelike, TimeZone};
+use tr::tr;
+
+/// Consecutive periods in which a target was missed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// Unix timestamp of the start of the first missed period.
+    pub start: u64,
+    /// Unix timestamp of the end of the last missed period.
+    pub end: u64,
+    /// Worst value measured in one of the periods.
+    pub worst: f64,
+}
+
+impl Violation {
+    pub fn duration(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+/// A target with the measurements and violations over the checked time span.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TargetReport {
+    pub target: SlaTarget,
+    /// Name of the host, or its address.
+    pub host: String,
+    pub aggregate: HistoryAggregate,
+    pub violations: Vec<Violation>,
+}
+
+/// Start and end of the period of `window` that contains `ts`.
+pub fn period_of(window: SlaWindow, ts: u64) -> (u64, u64) {
+    let fixed = |len: u64| (ts - ts % len, ts - ts % len + len);
+    let local = chrono::Local
+        .timestamp_opt(ts as i64, 0)
+        .single()
+        .unwrap_or_default();
+    let midnight = |date: chrono::NaiveDate| {
+        date.and_hms_opt(0, 0, 0)
+            .and_then(|t| t.and_local_timezone(chrono::Local).earliest())
+            .map(|t| t.timestamp() as u64)
+            .unwrap_or(ts)
+    };
+    match window {
+        SlaWindow::FiveMinutes => fixed(300),
+        SlaWindow::Hour => fixed(3_600),
+        SlaWindow::Day => {
+            let date = local.date_naive();
+            (midnight(date), midnight(date + chrono::Days::new(1)))
+        }
+        SlaWindow::Week => {
+            let monday = local.date_naive()
+                - chrono::Days::new(local.weekday().num_days_from_monday() as u64);
+            (midnight(monday), midnight(monday + chrono::Days::new(7)))
+        }
+        SlaWindow::Month => {
+            let first = local.date_naive().with_day(1).unwrap_or_default();
+            (midnight(first), midnight(first + chrono::Months::new(1)))
+        }
+    }
+}
+
+/// The value of `metric` over `records`; `None` if it cannot be measured.
+fn measure(metric: SlaMetric, aggregate: &HistoryAggregate) -> Option<f64> {
+    match metric {
+        SlaMetric::MeanLatency => (aggregate.sent > aggregate.lost).then_some(aggregate.mean),
+        SlaMetric::Loss => {
+            (aggregate.sent > 0).then(|| aggregate.lost as f64 / aggregate.sent as f64 * 100.0)
+        }
+    }
+}
+
+/// Splits the records (oldest first) into the periods of the target and collects
+/// the missed ones, merging consecutive periods into one violation.
+pub fn evaluate(target: &SlaTarget, records: &[HistoryRecord]) -> Vec<Violation> {
+    let mut violations: Vec<Violation> = Vec::new();
+    let mut rest = records;
+    while let Some(first) = rest.first() {
+        let (start, end) = period_of(target.window, first.timestamp);
+        let len = rest
+            .iter()
+            .position(|r| r.timestamp >= end)
+            .unwrap_or(rest.len());
+        let (period, next) = rest.split_at(len);
+        rest = next;
+        let Some(value) = measure(target.metric, &storage::aggregate(period)) else {
+            continue;
+        };
+        if value <= target.limit {
+            continue;
+        }
+        match violations.last_mut() {
+            Some(last) if last.end == start => {
+                last.end = end;
+                last.worst = last.worst.max(value);
+            }
+            _ => violations.push(Violation {
+                start,
+                end,
+                worst: value,
+            }),
+        }
+    }
+    violations
+}
+
+/// Reads the history since `from` and checks every target of the contract.
+pub fn check(state: &AppState, sla: &SlaSettings, from: u64) -> Vec<TargetReport> {
+    sla.targets
+        .iter()
+        .filter(|t| !t.address.is_empty())
+        .map(|target| {
+            let mut records = storage::query(&target.address, from).unwrap_or_default();
+            let host_info = state.hosts.iter().find(|h| h.address == target.address);
+            // Expected downtime does not count against the provider
+            if let Some(h) = host_info {
+                records.retain(|r| !h.maintenance.contains_at(r.timestamp));
+            }
+            let host = host_info
+                .filter(|h| !h.name.is_empty())
+                .map_or_else(|| target.address.clone(), |h| h.name.clone());
+            TargetReport {
+                target: target.clone(),
+                host,
+                aggregate: storage::aggregate(&records),
+                violations: evaluate(target, &records),
+            }
+        })
+        .collect()
+}
+
+/// A pre-formatted complaint to the ISP listing every violation between `from` and `to`.
+pub fn complaint(sla: &SlaSettings, reports: &[TargetReport], from: u64, to: u64) -> String {
+    let isp = if sla.isp.trim().is_empty() {
+        tr!("Sir or Madam")
+    } else {
+        tr!("{isp} support").replace("{isp}", sla.isp.trim())
+    };
+    let mut text = tr!("Subject: Service level violations {from} – {to}")
+        .replace("{from}", &format_time(from))
+        .replace("{to}", &format_time(to));
+    text.push_str("\n\n");
+    text.push_str(&tr!("Dear {isp},").replace("{isp}", &isp));
+    text.push_str("\n\n");
+    let intro = if sla.contract.trim().is_empty() {
+        tr!(
+            "My continuous monitoring of my connection recorded the following violations of the agreed service levels between {from} and {to}:"
+        )
+    } else {
+        tr!("My continuous monitoring of the connection under contract {contract} recorded the following violations of the agreed service levels between {from} and {to}:")
+            .replace("{contract}", sla.contract.trim())
+    };
+    text.push_str(
+        &intro
+            .replace("{from}", &format_time(from))
+            .replace("{to}", &format_time(to)),
+    );
+    text.push_str("\n\n");
+
+    for report in reports.iter().filter(|r| !r.violations.is_empty()) {
+        let unit = report.target.metric.unit();
+        let total: u64 = report.violations.iter().map(Violation::duration).sum();
+        text.push_str(&format!(
+            "{} ({}): {}\n",
+            report.host,
+            report.target.address,
+            report.target.describe()
+        ));
+        text.push_str(&format!(
+            "  {}: {:.1} ms, {:.2} % {} ({} {})\n",
+            tr!("Measured over the whole period"),
+            report.aggregate.mean,
+            100.0 - report.aggregate.availability,
+            tr!("loss"),
+            report.aggregate.sent,
+            tr!("probes")
+        ));
+        text.push_str(&format!(
+            "  {}: {}, {}: {}\n",
+            tr!("Violations"),
+            report.violations.len(),
+            tr!("total duration"),
+            format_duration(total)
+        ));
+        for v in &report.violations {
+            text.push_str(&format!(
+                "  - {} – {} ({}): {:.2} {}\n",
+                format_time(v.start),
+                format_time(v.end),
+                format_duration(v.duration()),
+                v.worst,
+                unit
+            ));
+        }
+        text.push('\n');
+    }
+    if reports.iter().all(|r| r.violations.is_empty()) {
+        text.push_str(&tr!("No violations were recorded."));
+        text.push_str("\n\n");
+    }
+    text.push_str(&tr!(
+        "Please investigate these issues and let me know which compensation applies under the agreement. The raw measurements are available on request."
+    ));
+    text.push('\n');
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(
+        from: u64,
+        count: u64,
+        step: u64,
+        rtt: impl Fn(u64) -> Option<f32>,
+    ) -> Vec<HistoryRecord> {
+        (0..count)
+            .map(|i| HistoryRecord {
+                timestamp: from + i * step,
+                rtt: rtt(i),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_consecutive_periods_merge_into_one_violation() {
+        let start = 1_700_000_000 - 1_700_000_000 % 3_600;
+        // Three hours at one probe a minute; the second and third hour are slow
+        let data = records(start, 180, 60, |i| Some(if i >= 60 { 45.0 } else { 20.0 }));
+        let target = SlaTarget {
+            address: "10.0.0.1".to_string(),
+            metric: SlaMetric::MeanLatency,
+            limit: 30.0,
+            window: SlaWindow::Hour,
+        };
+        let violations = evaluate(&target, &data);
+        assert_eq!(
+            violations,
+            vec![Violation {
+                start: start + 3_600,
+                end: start + 3 * 3_600,
+                worst: 45.0
+            }]
+        );
+        assert_eq!(violations[0].duration(), 7_200);
+    }
+
+    #[test]
+    fn test_monthly_loss() {
+        let (start, end) = period_of(SlaWindow::Month, 1_700_000_000);
+        assert!(start <= 1_700_000_000 && 1_700_000_000 < end);
+        assert!((28 * 86_400..=31 * 86_400 + 3_600).contains(&(end - start)));
+
+        // 1 lost probe in 100: 1% > 0.5%
+        let data = records(start, 100, 60, |i| (i != 50).then_some(10.0));
+        let target = SlaTarget {
+            address: "10.0.0.1".to_string(),
+            metric: SlaMetric::Loss,
+            limit: 0.5,
+            window: SlaWindow::Month,
+        };
+        let violations = evaluate(&target, &data);
+        assert_eq!(violations.len(), 1);
+        assert_eq!((violations[0].start, violations[0].end), (start, end));
+        assert_eq!(violations[0].worst, 1.0);
+
+        let report = TargetReport {
+            target,
+            host: "Gateway".to_string(),
+            aggregate: storage::aggregate(&data),
+            violations,
+        };
+        let sla = SlaSettings {
+            isp: "ExampleNet".to_string(),
+            contract: "K-4711".to_string(),
+            targets: Vec::new(),
+        };
+        let text = complaint(&sla, &[report], start, end);
+        assert!(text.contains("Dear ExampleNet support,"));
+        assert!(text.contains("contract K-4711"));
+        assert!(text.contains("Gateway (10.0.0.1): Packet loss ≤ 0.5 % monthly"));
+        assert!(text.contains("Violations: 1"));
+    }
+}
@@ -0,0 +1,432 @@
+//! SNMP v2c polling of the error and discard counters of the interfaces of a host
+//! (IF-MIB), with the little BER encoding SNMP needs.
+
+use crate::constants::{
+    SNMP_MAX_REPETITIONS, SNMP_MAX_REQUESTS, SNMP_POLL_INTERVAL_SEC, SNMP_TIMEOUT_MS,
+};
+use crate::logic::{SharedState, applog, dns};
+use crate::model::{HostInfo, SnmpCounters, SnmpSettings};
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+/// IF-MIB ifTable columns polled: ifInDiscards, ifInErrors, ifOutDiscards, ifOutErrors.
+const IF_IN_DISCARDS: [u32; 10] = [1, 3, 6, 1, 2, 1, 2, 2, 1, 13];
+const IF_IN_ERRORS: [u32; 10] = [1, 3, 6, 1, 2, 1, 2, 2, 1, 14];
+const IF_OUT_DISCARDS: [u32; 10] = [1, 3, 6, 1, 2, 1, 2, 2, 1, 19];
+const IF_OUT_ERRORS: [u32; 10] = [1, 3, 6, 1, 2, 1, 2, 2, 1, 20];
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_NULL: u8 = 0x05;
+const TAG_OID: u8 = 0x06;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_COUNTER32: u8 = 0x41;
+const TAG_GAUGE32: u8 = 0x42;
+const TAG_COUNTER64: u8 = 0x46;
+const PDU_RESPONSE: u8 = 0xa2;
+const PDU_GET_BULK: u8 = 0xa5;
+
+/// Appends a BER tag, length and content.
+fn push_tlv(out: &mut Vec<u8>, tag: u8, content: &[u8]) {
+    out.push(tag);
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes: Vec<u8> = len
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|b| *b == 0)
+            .collect();
+        out.push(0x80 | bytes.len() as u8);
+        out.extend_from_slice(&bytes);
+    }
+    out.extend_from_slice(content);
+}
+
+/// Minimal two's complement encoding of an integer.
+fn encode_integer(value: i64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let mut start = 0;
+    while start < 7
+        && ((bytes[start] == 0 && bytes[start + 1] & 0x80 == 0)
+            || (bytes[start] == 0xff && bytes[start + 1] & 0x80 != 0))
+    {
+        start += 1;
+    }
+    bytes[start..].to_vec()
+}
+
+fn encode_oid(oid: &[u32]) -> Vec<u8> {
+    let mut out = vec![(oid[0] * 40 + oid[1]) as u8];
+    for &arc in &oid[2..] {
+        let mut chunk = vec![(arc & 0x7f) as u8];
+        let mut rest = arc >> 7;
+        while rest > 0 {
+            chunk.push(0x80 | (rest & 0x7f) as u8);
+            rest >>= 7;
+        }
+        out.extend(chunk.iter().rev());
+    }
+    out
+}
+
+fn decode_oid(data: &[u8]) -> Option<Vec<u32>> {
+    let (&first, rest) = data.split_first()?;
+    let mut oid = vec![(first / 40) as u32, (first % 40) as u32];
+    let mut arc: u32 = 0;
+    for &b in rest {
+        arc = arc.checked_mul(128)? | (b & 0x7f) as u32;
+        if b & 0x80 == 0 {
+            oid.push(arc);
+            arc = 0;
+        }
+    }
+    Some(oid)
+}
+
+/// Builds a v2c GetBulkRequest for the rows after `oids`.
+pub fn build_get_bulk(
+    request_id: i32,
+    community: &str,
+    oids: &[&[u32]],
+    max_repetitions: u32,
+) -> Vec<u8> {
+    let mut varbinds = Vec::new();
+    for oid in oids {
+        let mut varbind = Vec::new();
+        push_tlv(&mut varbind, TAG_OID, &encode_oid(oid));
+        push_tlv(&mut varbind, TAG_NULL, &[]);
+        push_tlv(&mut varbinds, TAG_SEQUENCE, &varbind);
+    }
+    let mut pdu = Vec::new();
+    push_tlv(&mut pdu, TAG_INTEGER, &encode_integer(request_id as i64));
+    // Non-repeaters
+    push_tlv(&mut pdu, TAG_INTEGER, &encode_integer(0));
+    push_tlv(
+        &mut pdu,
+        TAG_INTEGER,
+        &encode_integer(max_repetitions as i64),
+    );
+    push_tlv(&mut pdu, TAG_SEQUENCE, &varbinds);
+
+    let mut message = Vec::new();
+    // Version 1 is SNMP v2c
+    push_tlv(&mut message, TAG_INTEGER, &encode_integer(1));
+    push_tlv(&mut message, TAG_OCTET_STRING, community.as_bytes());
+    push_tlv(&mut message, PDU_GET_BULK, &pdu);
+    let mut out = Vec::new();
+    push_tlv(&mut out, TAG_SEQUENCE, &message);
+    out
+}
+
+/// Reads the TLV at the start of `data`, returning its tag, content and the rest.
+fn read_tlv(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, data) = data.split_first()?;
+    let (&first, mut data) = data.split_first()?;
+    let len = if first < 0x80 {
+        first as usize
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > 4 || data.len() < count {
+            return None;
+        }
+        let len = data[..count]
+            .iter()
+            .fold(0usize, |len, &b| (len << 8) | b as usize);
+        data = &data[count..];
+        len
+    };
+    (data.len() >= len).then(|| (tag, &data[..len], &data[len..]))
+}
+
+fn decode_unsigned(data: &[u8]) -> u64 {
+    data.iter().fold(0u64, |v, &b| (v << 8) | b as u64)
+}
+
+/// A variable of a response: its OID and its value, `None` for a value that is
+/// not a counter (e.g. endOfMibView).
+pub type VarBind = (Vec<u32>, Option<u64>);
+
+/// Parses a Response PDU to the request `request_id`.
+pub fn parse_response(data: &[u8], request_id: i32) -> Result<Vec<VarBind>, String> {
+    let malformed = || "malformed SNMP response".to_string();
+    let (tag, message, _) = read_tlv(data).ok_or_else(malformed)?;
+    if tag != TAG_SEQUENCE {
+        return Err(malformed());
+    }
+    let (_, _version, rest) = read_tlv(message).ok_or_else(malformed)?;
+    let (_, _community, rest) = read_tlv(rest).ok_or_else(malformed)?;
+    let (tag, pdu, _) = read_tlv(rest).ok_or_else(malformed)?;
+    if tag != PDU_RESPONSE {
+        return Err(malformed());
+    }
+    let (_, id, rest) = read_tlv(pdu).ok_or_else(malformed)?;
+    if decode_unsigned(id) as i32 != request_id {
+        return Err("response to another request".to_string());
+    }
+    let (_, error_status, rest) = read_tlv(rest).ok_or_else(malformed)?;
+    match decode_unsigned(error_status) {
+        0 => {}
+        // authorizationError or noAccess: mostly a wrong community
+        16 | 6 => return Err("access denied, check the community".to_string()),
+        status => return Err(format!("SNMP error {}", status)),
+    }
+    let (_, _error_index, rest) = read_tlv(rest).ok_or_else(malformed)?;
+    let (_, mut varbinds, _) = read_tlv(rest).ok_or_else(malformed)?;
+    let mut result = Vec::new();
+    while !varbinds.is_empty() {
+        let (_, varbind, rest) = read_tlv(varbinds).ok_or_else(malformed)?;
+        varbinds = rest;
+        let (tag, oid, value) = read_tlv(varbind).ok_or_else(malformed)?;
+        let (value_tag, value, _) = read_tlv(value).ok_or_else(malformed)?;
+        if tag != TAG_OID {
+            return Err(malformed());
+        }
+        let oid = decode_oid(oid).ok_or_else(malformed)?;
+        let value = match value_tag {
+            TAG_INTEGER | TAG_COUNTER32 | TAG_GAUGE32 | TAG_COUNTER64 => {
+                Some(decode_unsigned(value))
+            }
+            _ => None,
+        };
+        result.push((oid, value));
+    }
+    Ok(result)
+}
+
+/// Sends a request and waits for the response with the same id.
+async fn exchange(
+    socket: &tokio::net::UdpSocket,
+    request_id: i32,
+    request: &[u8],
+) -> Result<Vec<VarBind>, String> {
+    socket.send(request).await.map_err(|e| e.to_string())?;
+    let mut buf = vec![0u8; 65535];
+    let deadline = tokio::time::Instant::now() + Duration::from_millis(SNMP_TIMEOUT_MS);
+    loop {
+        match tokio::time::timeout_at(deadline, socket.recv(&mut buf)).await {
+            Err(_) => return Err("timeout".to_string()),
+            Ok(Err(e)) => return Err(e.to_string()),
+            Ok(Ok(n)) => match parse_response(&buf[..n], request_id) {
+                Err(e) if e == "response to another request" => continue,
+                result => return result,
+            },
+        }
+    }
+}
+
+/// Walks a column of the interface table, returning the values by ifIndex.
+async fn walk_column(
+    socket: &tokio::net::UdpSocket,
+    community: &str,
+    column: &[u32],
+) -> Result<Vec<(u32, u64)>, String> {
+    let mut rows = Vec::new();
+    let mut next = column.to_vec();
+    for _ in 0..SNMP_MAX_REQUESTS {
+        let request_id = rand::random::<i32>() & 0x7fff_ffff;
+        let request = build_get_bulk(request_id, community, &[&next], SNMP_MAX_REPETITIONS);
+        let varbinds = exchange(socket, request_id, &request).await?;
+        if varbinds.is_empty() {
+            return Ok(rows);
+        }
+        for (oid, value) in varbinds {
+            // Past the end of the column
+            let Some(&index) = oid.strip_prefix(column).and_then(|rest| rest.first()) else {
+                return Ok(rows);
+            };
+            let Some(value) = value else {
+                return Ok(rows);
+            };
+            rows.push((index, value));
+            next = oid;
+        }
+    }
+    Ok(rows)
+}
+
+/// Sums the counters of `rows` over the watched interfaces (all if empty).
+fn sum_rows(rows: &[(u32, u64)], interfaces: &[u32]) -> u64 {
+    rows.iter()
+        .filter(|(index, _)| interfaces.is_empty() || interfaces.contains(index))
+        .map(|(_, value)| value)
+        .sum()
+}
+
+/// Polls the error and discard counters of the interfaces of `address`.
+pub async fn poll(address: &str, settings: &SnmpSettings) -> Result<SnmpCounters, String> {
+    let address = HostInfo::split_source(address).0;
+    let ip = match dns::strip_brackets(address).parse::<IpAddr>() {
+        Ok(ip) => ip,
+        Err(_) => *dns::resolve(address)
+            .await
+            .first()
+            .ok_or_else(|| "cannot resolve host".to_string())?,
+    };
+    let local: SocketAddr = if ip.is_ipv6() {
+        "[::]:0".parse().expect("valid address")
+    } else {
+        "0.0.0.0:0".parse().expect("valid address")
+    };
+    let socket = tokio::net::UdpSocket::bind(local)
+        .await
+        .map_err(|e| e.to_string())?;
+    socket
+        .connect(SocketAddr::new(ip, settings.port))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let interfaces = settings.interface_indexes();
+    let mut sums = [0u64; 4];
+    for (sum, column) in
+        sums.iter_mut()
+            .zip([IF_IN_ERRORS, IF_OUT_ERRORS, IF_IN_DISCARDS, IF_OUT_DISCARDS])
+    {
+        let rows = walk_column(&socket, &settings.community, &column).await?;
+        if rows.is_empty() {
+            return Err("the host has no interface table".to_string());
+        }
+        *sum = sum_rows(&rows, &interfaces);
+    }
+    let [in_errors, out_errors, in_discards, out_discards] = sums;
+    Ok(SnmpCounters {
+        in_errors,
+        out_errors,
+        in_discards,
+        out_discards,
+    })
+}
+
+/// Polls the hosts with SNMP enabled if a poll is due, storing the results in
+/// their status. The status of hosts with SNMP disabled is cleared.
+pub fn spawn_poll_if_due(state: &SharedState, last_poll: &mut Option<Instant>, now: Instant) {
+    if last_poll
+        .is_some_and(|t| now.duration_since(t) < Duration::from_secs(SNMP_POLL_INTERVAL_SEC))
+    {
+        return;
+    }
+    *last_poll = Some(now);
+    let targets: Vec<(String, SnmpSettings)> = {
+        let mut state_lock = state.lock().expect("Failed to lock state for SNMP");
+        let state_lock = &mut *state_lock;
+        for host in state_lock.hosts.iter().filter(|h| !h.snmp.enabled) {
+            if let Some(status) = state_lock.statuses.get_mut(&host.address) {
+                status.snmp = Default::default();
+            }
+        }
+        state_lock
+            .hosts
+            .iter()
+            .filter(|h| h.snmp.enabled && h.is_icmp() && !h.is_stopped)
+            .map(|h| (h.address.clone(), h.snmp.clone()))
+            .collect()
+    };
+    for (address, settings) in targets {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let result = poll(&address, &settings).await;
+            if let Err(e) = &result {
+                applog::warn("snmp", format!("Polling {} failed: {}", address, e));
+            }
+            let now_ts = chrono::Utc::now().timestamp() as u64;
+            let mut state_lock = state.lock().expect("Failed to lock state for SNMP");
+            if let Some(status) = state_lock.statuses.get_mut(&address) {
+                status.snmp.record(result, now_ts);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_integer_and_oid() {
+        assert_eq!(encode_integer(0), vec![0]);
+        assert_eq!(encode_integer(127), vec![0x7f]);
+        assert_eq!(encode_integer(128), vec![0x00, 0x80]);
+        assert_eq!(encode_integer(-1), vec![0xff]);
+        assert_eq!(encode_integer(0x1234), vec![0x12, 0x34]);
+
+        let oid = [1, 3, 6, 1, 4, 1, 2021, 300];
+        let encoded = encode_oid(&oid);
+        assert_eq!(encoded, vec![0x2b, 6, 1, 4, 1, 0x8f, 0x65, 0x82, 0x2c]);
+        assert_eq!(decode_oid(&encoded), Some(oid.to_vec()));
+    }
+
+    #[test]
+    fn test_get_bulk_request() {
+        let request = build_get_bulk(1, "public", &[&IF_IN_ERRORS], 10);
+        let expected: Vec<u8> = vec![
+            0x30, 0x27, 0x02, 0x01, 0x01, 0x04, 0x06, b'p', b'u', b'b', b'l', b'i', b'c', 0xa5,
+            0x1a, 0x02, 0x01, 0x01, 0x02, 0x01, 0x00, 0x02, 0x01, 0x0a, 0x30, 0x0f, 0x30, 0x0d,
+            0x06, 0x09, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x02, 0x02, 0x01, 0x0e, 0x05, 0x00,
+        ];
+        assert_eq!(request, expected);
+    }
+
+    /// Builds a response like an agent would, with the given variables.
+    fn response(
+        request_id: i32,
+        error_status: i64,
+        varbinds: &[(Vec<u32>, u8, Vec<u8>)],
+    ) -> Vec<u8> {
+        let mut list = Vec::new();
+        for (oid, tag, value) in varbinds {
+            let mut varbind = Vec::new();
+            push_tlv(&mut varbind, TAG_OID, &encode_oid(oid));
+            push_tlv(&mut varbind, *tag, value);
+            push_tlv(&mut list, TAG_SEQUENCE, &varbind);
+        }
+        let mut pdu = Vec::new();
+        push_tlv(&mut pdu, TAG_INTEGER, &encode_integer(request_id as i64));
+        push_tlv(&mut pdu, TAG_INTEGER, &encode_integer(error_status));
+        push_tlv(&mut pdu, TAG_INTEGER, &encode_integer(0));
+        push_tlv(&mut pdu, TAG_SEQUENCE, &list);
+        let mut message = Vec::new();
+        push_tlv(&mut message, TAG_INTEGER, &encode_integer(1));
+        push_tlv(&mut message, TAG_OCTET_STRING, b"public");
+        push_tlv(&mut message, PDU_RESPONSE, &pdu);
+        let mut out = Vec::new();
+        push_tlv(&mut out, TAG_SEQUENCE, &message);
+        out
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let row = |index: u32| [&IF_IN_ERRORS[..], &[index]].concat();
+        let data = response(
+            7,
+            0,
+            &[
+                (row(1), TAG_COUNTER32, vec![0x01, 0x00]),
+                (row(2), TAG_COUNTER32, vec![0xff, 0xff, 0xff, 0xff]),
+                // endOfMibView
+                (IF_OUT_ERRORS.to_vec(), 0x82, vec![]),
+            ],
+        );
+        let varbinds = parse_response(&data, 7).unwrap();
+        assert_eq!(varbinds.len(), 3);
+        assert_eq!(varbinds[0], (row(1), Some(256)));
+        assert_eq!(varbinds[1], (row(2), Some(4_294_967_295)));
+        assert_eq!(varbinds[2].1, None);
+
+        assert!(parse_response(&data, 8).is_err());
+        assert!(parse_response(&data[..20], 7).is_err());
+        let denied = response(7, 16, &[]);
+        assert!(
+            parse_response(&denied, 7)
+                .unwrap_err()
+                .contains("community")
+        );
+    }
+
+    #[test]
+    fn test_sum_rows() {
+        let rows = [(1, 5), (2, 7), (10, 100)];
+        assert_eq!(sum_rows(&rows, &[]), 112);
+        assert_eq!(sum_rows(&rows, &[2, 10]), 107);
+    }
+}
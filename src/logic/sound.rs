@@ -0,0 +1,283 @@
+use crate::logic::applog;
+use crate::model::{SoundEvent, SoundSettings, SoundTheme};
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+use std::path::{Path, PathBuf};
+
+#[cfg(windows)]
+use crate::constants::CREATE_NO_WINDOW;
+
+/// Sample rate of synthesized sounds.
+const SAMPLE_RATE: u32 = 22_050;
+
+/// Waveform of a synthesized tone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Wave {
+    Sine,
+    Square,
+}
+
+/// Tones of a themed sound as (frequency in Hz, duration in ms); a frequency of 0 is a pause.
+fn tones(theme: SoundTheme, event: SoundEvent) -> (Wave, f32, &'static [(f32, u32)]) {
+    match (theme, event) {
+        (SoundTheme::Classic, SoundEvent::Down) => {
+            (Wave::Sine, 0.6, &[(880.0, 150), (0.0, 60), (660.0, 300)])
+        }
+        (SoundTheme::Classic, SoundEvent::Recovery) => {
+            (Wave::Sine, 0.6, &[(660.0, 150), (0.0, 60), (880.0, 300)])
+        }
+        (SoundTheme::Classic, SoundEvent::Degraded) => (Wave::Sine, 0.5, &[(740.0, 200)]),
+        (SoundTheme::Classic, SoundEvent::Escalation) => (
+            Wave::Sine,
+            0.7,
+            &[
+                (880.0, 120),
+                (0.0, 60),
+                (880.0, 120),
+                (0.0, 60),
+                (880.0, 120),
+                (0.0, 60),
+                (660.0, 400),
+            ],
+        ),
+        (SoundTheme::Soft, SoundEvent::Down) => (Wave::Sine, 0.25, &[(523.3, 250), (392.0, 450)]),
+        (SoundTheme::Soft, SoundEvent::Recovery) => {
+            (Wave::Sine, 0.25, &[(392.0, 250), (523.3, 450)])
+        }
+        (SoundTheme::Soft, SoundEvent::Degraded) => (Wave::Sine, 0.2, &[(440.0, 300)]),
+        (SoundTheme::Soft, SoundEvent::Escalation) => (
+            Wave::Sine,
+            0.3,
+            &[(523.3, 250), (392.0, 250), (523.3, 250), (392.0, 450)],
+        ),
+        (SoundTheme::Alarm, SoundEvent::Down) => (
+            Wave::Square,
+            0.4,
+            &[(960.0, 250), (640.0, 250), (960.0, 250), (640.0, 250)],
+        ),
+        (SoundTheme::Alarm, SoundEvent::Recovery) => {
+            (Wave::Square, 0.3, &[(640.0, 120), (960.0, 300)])
+        }
+        (SoundTheme::Alarm, SoundEvent::Degraded) => {
+            (Wave::Square, 0.3, &[(800.0, 150), (0.0, 100), (800.0, 150)])
+        }
+        (SoundTheme::Alarm, SoundEvent::Escalation) => (
+            Wave::Square,
+            0.5,
+            &[
+                (960.0, 200),
+                (640.0, 200),
+                (960.0, 200),
+                (640.0, 200),
+                (960.0, 200),
+                (640.0, 200),
+                (960.0, 200),
+                (640.0, 200),
+            ],
+        ),
+    }
+}
+
+/// Synthesizes the sound of an event as 16-bit mono samples.
+pub fn synthesize(theme: SoundTheme, event: SoundEvent) -> Vec<i16> {
+    let (wave, volume, tones) = tones(theme, event);
+    let mut samples = Vec::new();
+    for &(freq, ms) in tones {
+        let count = (SAMPLE_RATE * ms / 1000) as usize;
+        // Short fades avoid clicks at tone boundaries
+        let fade = (SAMPLE_RATE as usize / 200).min(count / 2).max(1);
+        for i in 0..count {
+            if freq <= 0.0 {
+                samples.push(0);
+                continue;
+            }
+            let phase = (i as f32 * freq / SAMPLE_RATE as f32).fract();
+            let value = match wave {
+                Wave::Sine => (phase * std::f32::consts::TAU).sin(),
+                Wave::Square => {
+                    if phase < 0.5 {
+                        1.0
+                    } else {
+                        -1.0
+                    }
+                }
+            };
+            let envelope = (i.min(count - 1 - i) as f32 / fade as f32).min(1.0);
+            samples.push((value * envelope * volume * i16::MAX as f32) as i16);
+        }
+    }
+    samples
+}
+
+/// Encodes 16-bit mono samples as a WAV file.
+pub fn wav_bytes(samples: &[i16]) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut out = Vec::with_capacity(44 + data_len as usize);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    out.extend_from_slice(&1u16.to_le_bytes()); // mono
+    out.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    out.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    out.extend_from_slice(&2u16.to_le_bytes());
+    out.extend_from_slice(&16u16.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    for s in samples {
+        out.extend_from_slice(&s.to_le_bytes());
+    }
+    out
+}
+
+/// Writes `bytes` into a new file of a random name in `dir`, failing rather than
+/// opening a file that is already there.
+fn write_new(dir: &Path, bytes: &[u8]) -> std::io::Result<PathBuf> {
+    use std::io::Write;
+    let path = dir.join(format!("egui_pinger-{:016x}.wav", rand::random::<u64>()));
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)?
+        .write_all(bytes)?;
+    Ok(path)
+}
+
+/// Returns the WAV file to play for an event and whether it is a one-off file
+/// the caller removes after playing.
+///
+/// Themed sounds are kept in the user's cache directory and rewritten when they
+/// differ from the synthesized sound, e.g. after an update. Without a cache
+/// directory the sound goes into a new one-off file in the temp directory.
+fn sound_file(settings: &SoundSettings, event: SoundEvent) -> std::io::Result<(PathBuf, bool)> {
+    let custom = settings.event(event).custom_file.trim();
+    if !custom.is_empty() {
+        return Ok((PathBuf::from(custom), false));
+    }
+    let bytes = wav_bytes(&synthesize(settings.theme, event));
+    let Some(dir) = dirs::cache_dir().map(|d| d.join("egui_pinger").join("sounds")) else {
+        return Ok((write_new(&std::env::temp_dir(), &bytes)?, true));
+    };
+    let path = dir.join(format!("{:?}-{:?}.wav", settings.theme, event));
+    if std::fs::read(&path).ok().as_deref() != Some(bytes.as_slice()) {
+        std::fs::create_dir_all(&dir)?;
+        // Replaced in one step, so a sound playing meanwhile is never cut short
+        let written = write_new(&dir, &bytes)?;
+        std::fs::rename(&written, &path).inspect_err(|_| {
+            let _ = std::fs::remove_file(&written);
+        })?;
+    }
+    Ok((path, false))
+}
+
+/// Plays a WAV file with the platform's command line player, blocking until it finishes.
+#[cfg(windows)]
+fn play_file(path: &Path) -> Result<(), String> {
+    let script = format!(
+        "(New-Object Media.SoundPlayer '{}').PlaySync()",
+        path.display().to_string().replace('\'', "''")
+    );
+    let status = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()
+        .map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("powershell exited with {}", status))
+    }
+}
+
+/// Plays a WAV file with the first available command line player, blocking until it finishes.
+#[cfg(not(windows))]
+fn play_file(path: &Path) -> Result<(), String> {
+    let players: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+        &[("afplay", &[])]
+    } else {
+        &[("paplay", &[]), ("pw-play", &[]), ("aplay", &["-q"])]
+    };
+    let mut last_error = "no audio player found".to_string();
+    for (player, args) in players {
+        match std::process::Command::new(player)
+            .args(*args)
+            .arg(path)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+        {
+            Ok(status) if status.success() => return Ok(()),
+            Ok(status) => last_error = format!("{} exited with {}", player, status),
+            Err(_) => continue,
+        }
+    }
+    Err(last_error)
+}
+
+/// Plays the sound of an event without blocking the caller.
+pub fn play(settings: &SoundSettings, event: SoundEvent) {
+    let settings = settings.clone();
+    std::thread::spawn(move || {
+        let result = sound_file(&settings, event)
+            .map_err(|e| e.to_string())
+            .and_then(|(path, one_off)| {
+                let played = play_file(&path);
+                if one_off {
+                    let _ = std::fs::remove_file(&path);
+                }
+                played
+            });
+        if let Err(e) = result {
+            applog::warn("sound", format!("Failed to play {:?} sound: {}", event, e));
+        }
+    });
+}
+
+/// Plays the sound of an event, unless sounds or the event are disabled.
+///
+/// Quiet hours are left to the caller, which also holds back the other alerts then.
+pub fn trigger(settings: &SoundSettings, event: SoundEvent) {
+    if !settings.enabled || !settings.event(event).enabled {
+        return;
+    }
+    play(settings, event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sound_files_get_new_names() {
+        let dir = std::env::temp_dir();
+        let first = write_new(&dir, b"one").unwrap();
+        let second = write_new(&dir, b"two").unwrap();
+        assert_ne!(first, second);
+        assert_eq!(std::fs::read(&first).unwrap(), b"one");
+        let _ = std::fs::remove_file(first);
+        let _ = std::fs::remove_file(second);
+    }
+
+    #[test]
+    fn test_themes_have_distinct_sounds() {
+        for theme in SoundTheme::ALL {
+            let down = synthesize(theme, SoundEvent::Down);
+            assert!(!down.is_empty());
+            assert_ne!(down, synthesize(theme, SoundEvent::Recovery));
+        }
+        assert_ne!(
+            synthesize(SoundTheme::Classic, SoundEvent::Down),
+            synthesize(SoundTheme::Alarm, SoundEvent::Down)
+        );
+    }
+
+    #[test]
+    fn test_wav_header() {
+        let wav = wav_bytes(&[0, 1, -1]);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(u32::from_le_bytes(wav[40..44].try_into().unwrap()), 6);
+        assert_eq!(wav.len(), 50);
+    }
+}
@@ -0,0 +1,511 @@
+//! Persistent long-term ping history.
+//!
+//! Every ping result is appended to a per-host ring buffer file of fixed-size
+//! records, so statistics survive restarts while disk usage stays bounded.
+
+use crate::constants::{HISTORY_CAPACITY_RECORDS, HISTORY_WINDOW_SIZE};
+use crate::logic::applog;
+use crate::model::status::calculate_percentile;
+use crate::model::{AppState, LogEntry};
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use tr::tr;
+
+const MAGIC: &[u8; 4] = b"EPRB";
+const VERSION: u32 = 1;
+/// magic, version, record size, capacity, head, count
+const HEADER_SIZE: u64 = 24;
+/// timestamp (u64) + RTT in ms (f32, NaN for a timeout)
+const RECORD_SIZE: u64 = 12;
+const FILE_EXTENSION: &str = "ring";
+
+/// A single stored ping result.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistoryRecord {
+    pub timestamp: u64,
+    /// Round-trip time in ms, or `None` for a timeout.
+    pub rtt: Option<f32>,
+}
+
+impl HistoryRecord {
+    fn to_bytes(self) -> [u8; RECORD_SIZE as usize] {
+        let mut buf = [0u8; RECORD_SIZE as usize];
+        buf[..8].copy_from_slice(&self.timestamp.to_le_bytes());
+        buf[8..].copy_from_slice(&self.rtt.unwrap_or(f32::NAN).to_le_bytes());
+        buf
+    }
+
+    fn from_bytes(buf: &[u8]) -> Self {
+        let timestamp = u64::from_le_bytes(buf[..8].try_into().unwrap_or_default());
+        let rtt = f32::from_le_bytes(buf[8..12].try_into().unwrap_or_default());
+        Self {
+            timestamp,
+            rtt: (!rtt.is_nan()).then_some(rtt),
+        }
+    }
+}
+
+/// Reads the capacity, head and count from the header at the current position of
+/// `file`; `None` if it is not a valid ring file.
+fn read_header(file: &mut File) -> Option<(u32, u32, u32)> {
+    let mut header = [0u8; HEADER_SIZE as usize];
+    file.read_exact(&mut header).ok()?;
+    if &header[..4] != MAGIC {
+        return None;
+    }
+    let field = |i: usize| u32::from_le_bytes(header[i..i + 4].try_into().unwrap_or_default());
+    let (version, record_size, cap, head, count) =
+        (field(4), field(8), field(12), field(16), field(20));
+    (version == VERSION
+        && record_size as u64 == RECORD_SIZE
+        && cap > 0
+        && head < cap
+        && count <= cap)
+        .then_some((cap, head, count))
+}
+
+/// A fixed-capacity ring buffer of records stored in a single file.
+pub struct RingFile {
+    file: File,
+    capacity: u32,
+    /// Physical index of the next record to write.
+    head: u32,
+    /// Number of valid records.
+    count: u32,
+}
+
+impl RingFile {
+    /// Opens an existing ring file or creates a new one with the given capacity.
+    ///
+    /// The capacity of an existing file is kept; a corrupt file is recreated.
+    pub fn open(path: &Path, capacity: u32) -> io::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        if let Some((capacity, head, count)) = read_header(&mut file) {
+            return Ok(Self {
+                file,
+                capacity,
+                head,
+                count,
+            });
+        }
+        if file.metadata()?.len() > 0 {
+            applog::warn(
+                "storage",
+                format!("Recreating corrupt history file {}", path.display()),
+            );
+        }
+
+        file.set_len(0)?;
+        let mut ring = Self {
+            file,
+            capacity: capacity.max(1),
+            head: 0,
+            count: 0,
+        };
+        ring.write_header()?;
+        Ok(ring)
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        let mut header = Vec::with_capacity(HEADER_SIZE as usize);
+        header.extend_from_slice(MAGIC);
+        for v in [
+            VERSION,
+            RECORD_SIZE as u32,
+            self.capacity,
+            self.head,
+            self.count,
+        ] {
+            header.extend_from_slice(&v.to_le_bytes());
+        }
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(&header)
+    }
+
+    /// Appends a record, overwriting the oldest one when the buffer is full.
+    pub fn append(&mut self, record: HistoryRecord) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start(
+            HEADER_SIZE + self.head as u64 * RECORD_SIZE,
+        ))?;
+        self.file.write_all(&record.to_bytes())?;
+        self.head = (self.head + 1) % self.capacity;
+        self.count = (self.count + 1).min(self.capacity);
+        self.write_header()
+    }
+
+    /// Number of stored records.
+    pub fn len(&self) -> usize {
+        self.count as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Physical index of the record at logical position `i` (0 = oldest).
+    fn physical(&self, i: u32) -> u32 {
+        (self.head + self.capacity - self.count + i) % self.capacity
+    }
+
+    fn read_at(&mut self, i: u32) -> io::Result<HistoryRecord> {
+        let mut buf = [0u8; RECORD_SIZE as usize];
+        self.file.seek(SeekFrom::Start(
+            HEADER_SIZE + self.physical(i) as u64 * RECORD_SIZE,
+        ))?;
+        self.file.read_exact(&mut buf)?;
+        Ok(HistoryRecord::from_bytes(&buf))
+    }
+
+    /// Reads all records with `timestamp >= from`, oldest first.
+    ///
+    /// Records are appended in chronological order, so the start is found by binary search.
+    /// The header is read again first, as the background daemon may have appended
+    /// records since the file was opened.
+    pub fn read_since(&mut self, from: u64) -> io::Result<Vec<HistoryRecord>> {
+        self.file.seek(SeekFrom::Start(0))?;
+        if let Some((capacity, head, count)) = read_header(&mut self.file) {
+            (self.capacity, self.head, self.count) = (capacity, head, count);
+        }
+        let (mut lo, mut hi) = (0u32, self.count);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.read_at(mid)?.timestamp < from {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+
+        let wanted = self.count - lo;
+        let mut buf = vec![0u8; (wanted as u64 * RECORD_SIZE) as usize];
+        // The requested range is contiguous on disk, or wraps around the end once
+        let start = self.physical(lo);
+        let first_len = wanted.min(self.capacity - start);
+        self.file
+            .seek(SeekFrom::Start(HEADER_SIZE + start as u64 * RECORD_SIZE))?;
+        let split = (first_len as u64 * RECORD_SIZE) as usize;
+        self.file.read_exact(&mut buf[..split])?;
+        if wanted > first_len {
+            self.file.seek(SeekFrom::Start(HEADER_SIZE))?;
+            self.file.read_exact(&mut buf[split..])?;
+        }
+        Ok(buf
+            .chunks_exact(RECORD_SIZE as usize)
+            .map(HistoryRecord::from_bytes)
+            .collect())
+    }
+}
+
+/// Aggregated statistics over a period of stored history.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct HistoryAggregate {
+    pub sent: usize,
+    pub lost: usize,
+    pub availability: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub p95: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Computes aggregate statistics over a set of records.
+pub fn aggregate(records: &[HistoryRecord]) -> HistoryAggregate {
+    let rtts: Vec<f64> = records
+        .iter()
+        .filter_map(|r| r.rtt)
+        .map(f64::from)
+        .collect();
+    let sent = records.len();
+    let lost = sent - rtts.len();
+    if rtts.is_empty() {
+        return HistoryAggregate {
+            sent,
+            lost,
+            ..Default::default()
+        };
+    }
+    HistoryAggregate {
+        sent,
+        lost,
+        availability: rtts.len() as f64 / sent as f64 * 100.0,
+        mean: rtts.iter().sum::<f64>() / rtts.len() as f64,
+        median: calculate_percentile(rtts.iter().copied(), 50.0),
+        p95: calculate_percentile(rtts.iter().copied(), 95.0),
+        min: rtts.iter().copied().fold(f64::INFINITY, f64::min),
+        max: rtts.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+    }
+}
+
+/// Ring files of all hosts in one directory.
+pub struct HistoryStore {
+    dir: PathBuf,
+    capacity: u32,
+    files: HashMap<String, RingFile>,
+}
+
+impl HistoryStore {
+    pub fn new(dir: PathBuf, capacity: u32) -> Self {
+        Self {
+            dir,
+            capacity,
+            files: HashMap::new(),
+        }
+    }
+
+    /// File name used for a host address.
+    fn file_name(address: &str) -> String {
+        let safe: String = address
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        // Checksum keeps names unique when different addresses sanitize the same way
+        format!(
+            "{}-{:08x}.{}",
+            safe,
+            crc32fast::hash(address.as_bytes()),
+            FILE_EXTENSION
+        )
+    }
+
+    fn ring(&mut self, address: &str) -> io::Result<&mut RingFile> {
+        if !self.files.contains_key(address) {
+            std::fs::create_dir_all(&self.dir)?;
+            let ring = RingFile::open(&self.dir.join(Self::file_name(address)), self.capacity)?;
+            self.files.insert(address.to_string(), ring);
+        }
+        Ok(self.files.get_mut(address).expect("inserted above"))
+    }
+
+    /// Appends a ping result for a host.
+    pub fn record(&mut self, address: &str, record: HistoryRecord) -> io::Result<()> {
+        self.ring(address)?.append(record)
+    }
+
+    /// Writes the open files through to the disk.
+    pub fn sync(&self) -> io::Result<()> {
+        self.files
+            .values()
+            .try_for_each(|ring| ring.file.sync_data())
+    }
+
+    /// Reads the records of a host since `from`. Hosts without a file have no history.
+    pub fn query(&mut self, address: &str, from: u64) -> io::Result<Vec<HistoryRecord>> {
+        if !self.files.contains_key(address) && !self.dir.join(Self::file_name(address)).exists() {
+            return Ok(Vec::new());
+        }
+        self.ring(address)?.read_since(from)
+    }
+
+    /// Deletes the files of hosts that are not in `known`.
+    pub fn prune(&mut self, known: &[&str]) -> io::Result<usize> {
+        let keep: HashSet<String> = known.iter().map(|a| Self::file_name(a)).collect();
+        self.files.retain(|a, _| known.contains(&a.as_str()));
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Ok(0);
+        };
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.ends_with(FILE_EXTENSION) && !keep.contains(&name) {
+                std::fs::remove_file(entry.path())?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Default directory of the history files.
+pub fn default_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("egui_pinger")
+        .join("history")
+}
+
+static STORE: LazyLock<Mutex<HistoryStore>> =
+    LazyLock::new(|| Mutex::new(HistoryStore::new(default_dir(), HISTORY_CAPACITY_RECORDS)));
+
+/// Appends a ping result to the global history store, logging failures.
+pub fn record(address: &str, timestamp: u64, rtt: Option<f32>) {
+    if let Ok(mut store) = STORE.lock()
+        && let Err(e) = store.record(address, HistoryRecord { timestamp, rtt })
+    {
+        applog::error(
+            "storage",
+            format!("Failed to record history of {}: {}", address, e),
+        );
+    }
+}
+
+/// Writes the global history store through to the disk, e.g. before quitting.
+pub fn sync() {
+    if let Ok(store) = STORE.lock()
+        && let Err(e) = store.sync()
+    {
+        applog::error("storage", format!("Failed to write the history: {}", e));
+    }
+}
+
+/// Reads the history of a host since `from` from the global store.
+pub fn query(address: &str, from: u64) -> io::Result<Vec<HistoryRecord>> {
+    STORE
+        .lock()
+        .map_err(|_| io::Error::other("history store poisoned"))?
+        .query(address, from)
+}
+
+/// Deletes history files of hosts that no longer exist.
+pub fn prune(known: &[&str]) {
+    if let Ok(mut store) = STORE.lock() {
+        match store.prune(known) {
+            Ok(0) => {}
+            Ok(n) => applog::info("storage", format!("Removed history of {} unknown hosts", n)),
+            Err(e) => applog::warn("storage", format!("Failed to prune history: {}", e)),
+        }
+    }
+}
+
+/// Replays the most recent stored samples of every host without samples into its
+/// status, so the plots and windowed statistics continue where the last session ended.
+pub fn restore_statuses(state: &mut AppState) {
+    let from = (chrono::Utc::now().timestamp() as u64).saturating_sub(86_400);
+    let AppState {
+        hosts, statuses, ..
+    } = state;
+    for host in hosts.iter() {
+        if statuses
+            .get(&host.address)
+            .is_some_and(|s| !s.history.is_empty())
+        {
+            continue;
+        }
+        let records = match query(&host.address, from) {
+            Ok(r) => r,
+            Err(e) => {
+                applog::warn(
+                    "storage",
+                    format!("Failed to read history of {}: {}", host.address, e),
+                );
+                continue;
+            }
+        };
+        if records.is_empty() {
+            continue;
+        }
+        let status = statuses.entry(host.address.clone()).or_default();
+        for r in &records {
+            status.rollups.add(r.timestamp, r.rtt.map(f64::from));
+        }
+        let recent = &records[records.len().saturating_sub(HISTORY_WINDOW_SIZE)..];
+        for r in recent {
+            let rtt = r.rtt.map(f64::from);
+            status.add_sample(rtt.unwrap_or(f64::NAN), rtt.is_some());
+            status.events.push_back(LogEntry::Ping {
+                timestamp: r.timestamp,
+                seq: status.sent,
+                rtt: r.rtt,
+                bytes: host.packet_size as u16,
+            });
+        }
+        status.events.push_back(LogEntry::Marker {
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            message: tr!("Restored {count} samples from history")
+                .replace("{count}", &recent.len().to_string()),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("egui_pinger_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn rec(timestamp: u64, rtt: Option<f32>) -> HistoryRecord {
+        HistoryRecord { timestamp, rtt }
+    }
+
+    #[test]
+    fn test_ring_wraps_and_persists() {
+        let dir = temp_dir("ring");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("host.ring");
+        {
+            let mut ring = RingFile::open(&path, 4).unwrap();
+            for ts in 1..=6 {
+                ring.append(rec(ts, if ts == 5 { None } else { Some(ts as f32) }))
+                    .unwrap();
+            }
+        }
+        // Reopening keeps the data and the original capacity
+        let mut ring = RingFile::open(&path, 100).unwrap();
+        assert_eq!(ring.len(), 4);
+        let all = ring.read_since(0).unwrap();
+        assert_eq!(
+            all,
+            vec![
+                rec(3, Some(3.0)),
+                rec(4, Some(4.0)),
+                rec(5, None),
+                rec(6, Some(6.0))
+            ]
+        );
+        assert_eq!(ring.read_since(5).unwrap().len(), 2);
+        assert!(ring.read_since(7).unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_store_query_and_prune() {
+        let dir = temp_dir("store");
+        let mut store = HistoryStore::new(dir.clone(), 16);
+        assert!(store.query("1.1.1.1", 0).unwrap().is_empty());
+        store.record("1.1.1.1", rec(10, Some(5.0))).unwrap();
+        store.record("8.8.8.8", rec(10, None)).unwrap();
+        assert_eq!(store.query("1.1.1.1", 0).unwrap(), vec![rec(10, Some(5.0))]);
+
+        assert_eq!(store.prune(&["1.1.1.1"]).unwrap(), 1);
+        let mut reopened = HistoryStore::new(dir.clone(), 16);
+        assert!(reopened.query("8.8.8.8", 0).unwrap().is_empty());
+        assert_eq!(reopened.query("1.1.1.1", 0).unwrap().len(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let records = [
+            rec(1, Some(10.0)),
+            rec(2, None),
+            rec(3, Some(30.0)),
+            rec(4, Some(20.0)),
+        ];
+        let agg = aggregate(&records);
+        assert_eq!((agg.sent, agg.lost), (4, 1));
+        assert_eq!(agg.availability, 75.0);
+        assert_eq!(agg.mean, 20.0);
+        assert_eq!(agg.median, 20.0);
+        assert_eq!((agg.min, agg.max), (10.0, 30.0));
+        assert_eq!(aggregate(&[]), HistoryAggregate::default());
+    }
+}
@@ -0,0 +1,102 @@
+//! Read-only statistics stream for remote viewers such as the terminal client.
+//!
+//! Listens on localhost only; remote machines reach it through SSH port forwarding
+//! (`ssh -L 7170:localhost:7170 home`). Every client receives a [`StatsSnapshot`]
+//! as one line of JSON per interval until it disconnects.
+
+use crate::constants::STREAM_INTERVAL_MS;
+use crate::logic::listener::{self, Listener};
+use crate::logic::{SharedState, applog};
+use crate::model::snapshot::StatsSnapshot;
+use std::io::Write;
+use std::net::{Ipv4Addr, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// A running stream listener, started for the port in its key.
+pub type StreamServer = Listener<u16>;
+
+/// Starts listening on `127.0.0.1:port`.
+pub fn start(state: SharedState, port: u16) -> std::io::Result<StreamServer> {
+    Listener::start(
+        "stream",
+        Ipv4Addr::LOCALHOST,
+        port,
+        port,
+        move |client, peer, stop| {
+            applog::info("stream", format!("Client {} connected", peer));
+            serve_client(state.clone(), client, stop);
+        },
+    )
+}
+
+/// Sends a snapshot per interval until the client goes away or the server stops.
+fn serve_client(state: SharedState, mut client: TcpStream, stop: Arc<AtomicBool>) {
+    let _ = client.set_write_timeout(Some(Duration::from_secs(5)));
+    while !stop.load(Ordering::Relaxed) {
+        let snapshot = {
+            let state_lock = state.lock().expect("Failed to lock state for stream");
+            StatsSnapshot::capture(&state_lock)
+        };
+        let Ok(mut line) = serde_json::to_string(&snapshot) else {
+            return;
+        };
+        line.push('\n');
+        if client.write_all(line.as_bytes()).is_err() {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(STREAM_INTERVAL_MS));
+    }
+}
+
+/// Starts, restarts or stops the server to follow the settings.
+pub fn follow_settings(state: &SharedState, server: &mut Option<StreamServer>) {
+    let settings = state
+        .lock()
+        .expect("Failed to lock state for stream")
+        .settings
+        .stream_api
+        .clone();
+    let wanted = settings.enabled.then_some(settings.port);
+    if let Some(error) = listener::follow("stream", server, wanted, settings.port, || {
+        start(state.clone(), settings.port)
+    }) {
+        state
+            .lock()
+            .expect("Failed to lock state for stream")
+            .stream_error = error;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{AppState, HostInfo, HostStatus};
+    use std::io::{BufRead, BufReader};
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_client_receives_snapshots() {
+        let mut app_state = AppState::default();
+        app_state.hosts.push(HostInfo {
+            name: "Router".to_string(),
+            address: "192.168.1.1".to_string(),
+            ..Default::default()
+        });
+        let mut status = HostStatus::default();
+        status.add_sample(4.0, true);
+        app_state.statuses.insert("192.168.1.1".to_string(), status);
+        let state = Arc::new(Mutex::new(app_state));
+
+        let server = start(state, 0).unwrap();
+        let port = server.local_port.unwrap();
+        let client = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        let mut line = String::new();
+        BufReader::new(client).read_line(&mut line).unwrap();
+        let snapshot: StatsSnapshot = serde_json::from_str(&line).unwrap();
+        assert_eq!(snapshot.hosts.len(), 1);
+        assert_eq!(snapshot.hosts[0].name, "Router");
+        assert!(snapshot.hosts[0].alive);
+    }
+}
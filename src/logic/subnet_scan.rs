@@ -0,0 +1,329 @@
+//! Discovery of the hosts of a local network by pinging every address of a range,
+//! or the neighbors in the ARP table of the system.
+
+use crate::constants::{SCAN_CONCURRENCY, SCAN_MAX_ADDRESSES, SCAN_TIMEOUT_MS};
+use crate::logic::{applog, dns, lan_discovery, probe_limit};
+use crate::model::{AppState, HostInfo, HostStatus, PingMode};
+use futures::StreamExt;
+use ping_async::{IcmpEchoRequestor, IcmpEchoStatus};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Parses an IPv4 range in CIDR notation (`192.168.1.0/24`) or a single address
+/// into the addresses to probe. The network and broadcast addresses of ranges
+/// larger than /31 are left out.
+pub fn parse_range(text: &str) -> Result<Vec<Ipv4Addr>, String> {
+    let text = text.trim();
+    let (address, prefix) = text.split_once('/').unwrap_or((text, "32"));
+    let address: Ipv4Addr = address
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid IPv4 address {}", address))?;
+    let prefix: u32 = prefix
+        .trim()
+        .parse()
+        .ok()
+        .filter(|p| *p <= 32)
+        .ok_or_else(|| format!("invalid prefix length {}", prefix))?;
+    let size = 1u64 << (32 - prefix);
+    if size > SCAN_MAX_ADDRESSES as u64 {
+        return Err(format!(
+            "the range has {} addresses, at most {} can be scanned",
+            size, SCAN_MAX_ADDRESSES
+        ));
+    }
+    let mask = if prefix == 0 {
+        0
+    } else {
+        u32::MAX << (32 - prefix)
+    };
+    let network = u32::from(address) & mask;
+    let (first, last) = if size > 2 {
+        (network + 1, network + size as u32 - 2)
+    } else {
+        (network, network + size as u32 - 1)
+    };
+    Ok((first..=last).map(Ipv4Addr::from).collect())
+}
+
+/// The /24 around the address this machine uses to reach the Internet, as a
+/// starting point for the scan. No packet is sent to find it.
+pub fn local_subnet() -> Option<String> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:9").ok()?;
+    match socket.local_addr().ok()?.ip() {
+        IpAddr::V4(ip) if !ip.is_unspecified() && !ip.is_loopback() => {
+            let [a, b, c, _] = ip.octets();
+            Some(format!("{}.{}.{}.0/24", a, b, c))
+        }
+        _ => None,
+    }
+}
+
+/// A host found by the scan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanResult {
+    pub ip: Ipv4Addr,
+    /// RTT of the ping, `None` for a neighbor that does not answer ping.
+    pub rtt_ms: Option<f64>,
+    /// Name from reverse DNS, mDNS or NetBIOS, if any.
+    pub name: Option<String>,
+    /// MAC address from the ARP table, for hosts on the local network.
+    pub mac: Option<String>,
+    pub vendor: Option<String>,
+}
+
+/// State of a scan shared with the UI.
+#[derive(Debug, Clone, Default)]
+pub struct ScanProgress {
+    pub total: usize,
+    pub probed: usize,
+    /// Answering hosts in the order of their addresses.
+    pub found: Vec<ScanResult>,
+    pub finished: bool,
+    pub error: Option<String>,
+}
+
+/// A sweep of an address range running on a background thread.
+pub struct SubnetScan {
+    pub range: String,
+    progress: Arc<Mutex<ScanProgress>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl SubnetScan {
+    /// Starts scanning `range` (see [`parse_range`]) on a background thread.
+    pub fn start(range: String) -> Result<Self, String> {
+        let addresses = parse_range(&range)?;
+        Ok(Self::spawn(range, addresses, false))
+    }
+
+    /// Starts probing the neighbors in the ARP table, including those that do
+    /// not answer ping.
+    pub fn start_neighbors() -> Self {
+        let addresses = lan_discovery::read_arp_table()
+            .into_iter()
+            .map(|entry| entry.ip)
+            .collect();
+        Self::spawn(String::new(), addresses, true)
+    }
+
+    fn spawn(range: String, addresses: Vec<Ipv4Addr>, keep_silent: bool) -> Self {
+        let progress = Arc::new(Mutex::new(ScanProgress {
+            total: addresses.len(),
+            ..Default::default()
+        }));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (thread_progress, thread_stop) = (progress.clone(), stop.clone());
+        applog::info(
+            "scan",
+            format!("Scanning {} addresses {}", addresses.len(), range),
+        );
+        std::thread::spawn(move || {
+            match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(rt) => rt.block_on(run(addresses, keep_silent, thread_progress, thread_stop)),
+                Err(e) => applog::error("scan", format!("Failed to start scan runtime: {}", e)),
+            }
+        });
+        Self {
+            range,
+            progress,
+            stop,
+        }
+    }
+
+    /// Returns a copy of the current progress.
+    pub fn progress(&self) -> ScanProgress {
+        self.progress.lock().map(|p| p.clone()).unwrap_or_default()
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for SubnetScan {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Pings one address and looks up the name of an answering host, or of any host
+/// if `keep_silent`.
+async fn probe(
+    ip: Ipv4Addr,
+    resolver: Option<SocketAddr>,
+    keep_silent: bool,
+) -> Option<ScanResult> {
+    probe_limit::wait_for_token("").await;
+    let _slot = probe_limit::acquire_slot().await;
+    let timeout = Some(Duration::from_millis(SCAN_TIMEOUT_MS));
+    let requestor = IcmpEchoRequestor::new(IpAddr::V4(ip), None, None, timeout).ok()?;
+    let rtt_ms = match requestor.send().await {
+        Ok(reply) if reply.status() == IcmpEchoStatus::Success => {
+            Some(reply.round_trip_time().as_secs_f64() * 1000.0)
+        }
+        _ => None,
+    };
+    if rtt_ms.is_none() && !keep_silent {
+        return None;
+    }
+    Some(ScanResult {
+        ip,
+        rtt_ms,
+        name: lan_discovery::device_name(ip, resolver).await,
+        mac: None,
+        vendor: None,
+    })
+}
+
+async fn run(
+    addresses: Vec<Ipv4Addr>,
+    keep_silent: bool,
+    progress: Arc<Mutex<ScanProgress>>,
+    stop: Arc<AtomicBool>,
+) {
+    // Without the right to send ICMP every probe would just time out
+    if let Some(&first) = addresses.first()
+        && let Err(e) = IcmpEchoRequestor::new(IpAddr::V4(first), None, None, None)
+    {
+        if let Ok(mut p) = progress.lock() {
+            p.finished = true;
+            p.error = Some(format!("Cannot send ICMP requests: {}", e));
+        }
+        return;
+    }
+    let resolver = dns::system_nameserver();
+    let mut probes = futures::stream::iter(addresses)
+        .take_while(|_| std::future::ready(!stop.load(Ordering::Relaxed)))
+        .map(|ip| probe(ip, resolver, keep_silent))
+        .buffer_unordered(SCAN_CONCURRENCY);
+    while let Some(result) = probes.next().await {
+        if let Ok(mut p) = progress.lock() {
+            p.probed += 1;
+            if let Some(result) = result {
+                let at = p.found.partition_point(|r| r.ip < result.ip);
+                p.found.insert(at, result);
+            }
+        }
+    }
+    // The sweep has filled the ARP table with the hosts on the local network
+    let neighbors = lan_discovery::read_arp_table();
+    let vendors = lan_discovery::VendorTable::load();
+    if let Ok(mut p) = progress.lock() {
+        for result in &mut p.found {
+            if let Some(entry) = neighbors.iter().find(|e| e.ip == result.ip) {
+                result.vendor = vendors.vendor(&entry.mac);
+                result.mac = Some(entry.mac.clone());
+            }
+        }
+        p.finished = true;
+        applog::info(
+            "scan",
+            format!("Scan found {} of {} addresses", p.found.len(), p.total),
+        );
+    }
+}
+
+/// Adds scan results to the host list, named by their looked up name, else their
+/// DHCP lease, else the vendor of their network card. Addresses already in the
+/// list are skipped. Returns the number of added hosts.
+pub fn add_hosts(state: &mut AppState, found: &[ScanResult]) -> usize {
+    let mut added = 0;
+    for result in found {
+        let address = result.ip.to_string();
+        if state.hosts.iter().any(|h| h.address == address) {
+            continue;
+        }
+        let name = result
+            .name
+            .clone()
+            .or_else(|| state.dhcp_leases.names.get(&address).cloned())
+            .or_else(|| result.vendor.clone())
+            .unwrap_or_default();
+        let mut host = HostInfo {
+            name,
+            address: address.clone(),
+            mode: PingMode::NotFast,
+            thresholds: state.settings.default_thresholds,
+            ..Default::default()
+        };
+        if host.is_local() {
+            host.mode = PingMode::Fast;
+        }
+        state.statuses.insert(address, HostStatus::default());
+        state.hosts.push(host);
+        added += 1;
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        let addresses = parse_range("192.168.1.77/24").unwrap();
+        assert_eq!(addresses.len(), 254);
+        assert_eq!(addresses[0], Ipv4Addr::new(192, 168, 1, 1));
+        assert_eq!(addresses[253], Ipv4Addr::new(192, 168, 1, 254));
+        assert_eq!(
+            parse_range("10.0.0.4/31").unwrap(),
+            vec![Ipv4Addr::new(10, 0, 0, 4), Ipv4Addr::new(10, 0, 0, 5)]
+        );
+        assert_eq!(
+            parse_range(" 10.0.0.9 ").unwrap(),
+            vec![Ipv4Addr::new(10, 0, 0, 9)]
+        );
+        assert!(parse_range("10.0.0.0/8").is_err());
+        assert!(parse_range("10.0.0.0/33").is_err());
+        assert!(parse_range("fe80::/64").is_err());
+    }
+
+    #[test]
+    fn test_add_hosts() {
+        let mut state = AppState::default();
+        state.settings.default_thresholds = crate::model::QoePreset::Gaming.thresholds();
+        state.hosts.push(HostInfo {
+            address: "192.168.1.1".to_string(),
+            ..Default::default()
+        });
+        state
+            .dhcp_leases
+            .names
+            .insert("192.168.1.30".to_string(), "phone".to_string());
+        let found = |last: u8, name: Option<&str>| ScanResult {
+            ip: Ipv4Addr::new(192, 168, 1, last),
+            rtt_ms: Some(1.0),
+            name: name.map(str::to_string),
+            mac: None,
+            vendor: None,
+        };
+        let mut camera = found(40, None);
+        camera.vendor = Some("Axis Communications AB".to_string());
+        let added = add_hosts(
+            &mut state,
+            &[
+                found(1, None),
+                found(20, Some("nas.lan")),
+                found(30, None),
+                camera,
+            ],
+        );
+        assert_eq!(added, 3);
+        let names: Vec<&str> = state.hosts.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["", "nas.lan", "phone", "Axis Communications AB"]
+        );
+        assert_eq!(state.hosts[1].mode, PingMode::Fast);
+        assert_eq!(state.hosts[1].thresholds, state.settings.default_thresholds);
+        assert!(state.statuses.contains_key("192.168.1.30"));
+    }
+}
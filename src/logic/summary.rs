@@ -0,0 +1,271 @@
+//! Totals over all hosts for the window title and the summary bar.
+
+use crate::constants::{INTERNET_DEGRADED_LOSS_PCT, PUBLIC_DNS_ADDRESSES, SUMMARY_LOSS_WINDOW_SEC};
+use crate::logic::budget;
+use crate::model::{AppState, Connectivity, HostInfo, HostStatus};
+use tr::tr;
+
+/// Verdict on the Internet connection from the public DNS hosts in the list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InternetHealth {
+    /// No public DNS host is monitored.
+    #[default]
+    Unknown,
+    Good,
+    /// Some public DNS hosts are down, lose packets or answer slowly.
+    Degraded,
+    /// All public DNS hosts are down.
+    Down,
+}
+
+impl InternetHealth {
+    pub fn label(&self) -> String {
+        match self {
+            InternetHealth::Unknown => tr!("unknown (no public DNS host monitored)"),
+            InternetHealth::Good => tr!("good"),
+            InternetHealth::Degraded => tr!("degraded"),
+            InternetHealth::Down => tr!("down"),
+        }
+    }
+}
+
+/// Live state of the whole host list.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Summary {
+    pub hosts: usize,
+    /// Monitored (not stopped) hosts with at least one probe that are up.
+    pub up: usize,
+    /// Monitored (not stopped) hosts with at least one probe that are down.
+    pub down: usize,
+    /// Probes per second needed by the active hosts.
+    pub probes_per_sec: f64,
+    /// Name of the up host with the highest last RTT, and the RTT (ms).
+    pub worst_rtt: Option<(String, f64)>,
+    /// Probes of the monitored hosts in the last [`SUMMARY_LOSS_WINDOW_SEC`].
+    pub recent_sent: usize,
+    pub recent_lost: usize,
+    pub internet: InternetHealth,
+    /// Result of the captive portal check.
+    pub connectivity: Connectivity,
+}
+
+/// Whether a host is one of the well-known public DNS resolvers.
+fn is_public_dns(host: &HostInfo, status: &HostStatus) -> bool {
+    PUBLIC_DNS_ADDRESSES.contains(&host.address.as_str())
+        || status
+            .resolved_ip
+            .as_deref()
+            .is_some_and(|ip| PUBLIC_DNS_ADDRESSES.contains(&ip))
+}
+
+/// Probes and lost probes of a host since `since` (Unix time).
+fn recent_loss(status: &HostStatus, since: u64) -> (usize, usize) {
+    let recent = status
+        .history
+        .iter()
+        .filter(|s| s.timestamp >= since && !s.is_no_data());
+    recent.fold((0, 0), |(sent, lost), s| {
+        (sent + 1, lost + usize::from(s.is_lost()))
+    })
+}
+
+impl Summary {
+    pub fn compute(state: &AppState) -> Self {
+        Self::compute_at(state, chrono::Utc::now().timestamp() as u64)
+    }
+
+    /// Totals at `now` (Unix time).
+    pub fn compute_at(state: &AppState, now: u64) -> Self {
+        let since = now.saturating_sub(SUMMARY_LOSS_WINDOW_SEC);
+        let mut summary = Self {
+            hosts: state.hosts.len(),
+            probes_per_sec: budget::probe_rate(state),
+            connectivity: state.connectivity.clone(),
+            ..Default::default()
+        };
+        // Up and down public DNS hosts, and their probes and losses
+        let (mut dns_up, mut dns_down, mut dns_slow) = (0, 0, 0);
+        let (mut dns_sent, mut dns_lost) = (0, 0);
+        let monitored = state
+            .hosts
+            .iter()
+            .filter(|h| !h.is_stopped)
+            .filter_map(|h| state.statuses.get(&h.address).map(|s| (h, s)))
+            .filter(|(_, s)| s.sent > 0);
+        for (host, status) in monitored {
+            let last_rtt = (status.latency > 0.0).then_some(status.latency);
+            if status.alive {
+                summary.up += 1;
+                if let Some(rtt) = last_rtt
+                    && summary
+                        .worst_rtt
+                        .as_ref()
+                        .is_none_or(|(_, worst)| rtt > *worst)
+                {
+                    let name = if host.name.is_empty() {
+                        &host.address
+                    } else {
+                        &host.name
+                    };
+                    summary.worst_rtt = Some((name.clone(), rtt));
+                }
+            } else {
+                summary.down += 1;
+            }
+            let (sent, lost) = recent_loss(status, since);
+            summary.recent_sent += sent;
+            summary.recent_lost += lost;
+
+            if is_public_dns(host, status) {
+                if !status.alive {
+                    dns_down += 1;
+                    continue;
+                }
+                dns_up += 1;
+                dns_sent += sent;
+                dns_lost += lost;
+                if last_rtt.is_some_and(|rtt| rtt > host.thresholds.latency_warn) {
+                    dns_slow += 1;
+                }
+            }
+        }
+        let dns_loss_pct = if dns_sent > 0 {
+            dns_lost as f64 * 100.0 / dns_sent as f64
+        } else {
+            0.0
+        };
+        summary.internet = match (dns_up, dns_down) {
+            (0, 0) => InternetHealth::Unknown,
+            (0, _) => InternetHealth::Down,
+            (_, 0) if dns_slow == 0 && dns_loss_pct <= INTERNET_DEGRADED_LOSS_PCT => {
+                InternetHealth::Good
+            }
+            _ => InternetHealth::Degraded,
+        };
+        summary
+    }
+
+    /// Loss of all monitored hosts in the last [`SUMMARY_LOSS_WINDOW_SEC`] in percent,
+    /// `None` without probes.
+    pub fn recent_loss_pct(&self) -> Option<f64> {
+        (self.recent_sent > 0).then(|| self.recent_lost as f64 * 100.0 / self.recent_sent as f64)
+    }
+
+    /// E.g. "egui_pinger — 24 hosts, 3 down, 11 probes/s".
+    pub fn title(&self) -> String {
+        tr!("egui_pinger — {hosts} hosts, {down} down, {rate} probes/s")
+            .replace("{hosts}", &self.hosts.to_string())
+            .replace("{down}", &self.down.to_string())
+            .replace("{rate}", &format!("{:.0}", self.probes_per_sec))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{HostInfo, HostStatus, PingMode, Sample};
+
+    #[test]
+    fn test_summary_counts_down_hosts() {
+        let mut state = AppState::default();
+        for (i, (stopped, sent, alive)) in [
+            (false, 5, true),
+            (false, 5, false),
+            (true, 5, false),
+            (false, 0, false),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let address = format!("10.0.0.{}", i);
+            state.hosts.push(HostInfo {
+                address: address.clone(),
+                mode: PingMode::VeryFast,
+                is_stopped: stopped,
+                ..Default::default()
+            });
+            state.statuses.insert(
+                address,
+                HostStatus {
+                    sent,
+                    alive,
+                    ..Default::default()
+                },
+            );
+        }
+        let summary = Summary::compute(&state);
+        assert_eq!(summary.hosts, 4);
+        assert_eq!(summary.down, 1);
+        assert_eq!(
+            summary.title(),
+            format!(
+                "egui_pinger — 4 hosts, 1 down, {:.0} probes/s",
+                3.0 / PingMode::VeryFast.interval_secs() as f64
+            )
+        );
+    }
+
+    #[test]
+    fn test_summary_bar_totals_and_internet_verdict() {
+        let now = 10_000;
+        let mut state = AppState::default();
+        // Address, name, alive, RTT of every sample, every how many a sample is lost
+        let hosts = [
+            ("192.168.1.1", "Router", true, 2.0, 0),
+            ("1.1.1.1", "", true, 12.0, 0),
+            ("8.8.8.8", "Google", true, 18.0, 10),
+            ("10.0.0.5", "Camera", false, 40.0, 0),
+        ];
+        for (address, name, alive, rtt, lost_every) in hosts {
+            state.hosts.push(HostInfo {
+                address: address.to_string(),
+                name: name.to_string(),
+                ..Default::default()
+            });
+            // Samples every 10 s over the last 10 minutes, half of them in the window
+            let history = (0..60u64)
+                .map(|i| Sample {
+                    timestamp: now - 595 + i * 10,
+                    rtt: (lost_every == 0 || (i + 1) % lost_every != 0).then_some(rtt),
+                    ..Default::default()
+                })
+                .collect();
+            state.statuses.insert(
+                address.to_string(),
+                HostStatus {
+                    sent: 60,
+                    alive,
+                    latency: rtt,
+                    history,
+                    ..Default::default()
+                },
+            );
+        }
+        let summary = Summary::compute_at(&state, now);
+        assert_eq!((summary.up, summary.down), (3, 1));
+        assert_eq!(summary.worst_rtt, Some(("Google".to_string(), 18.0)));
+        assert_eq!((summary.recent_sent, summary.recent_lost), (120, 3));
+        // 3 of 30 probes to 8.8.8.8 lost
+        assert_eq!(summary.internet, InternetHealth::Degraded);
+
+        state.statuses.get_mut("8.8.8.8").unwrap().history.clear();
+        assert_eq!(
+            Summary::compute_at(&state, now).internet,
+            InternetHealth::Good
+        );
+        for address in ["1.1.1.1", "8.8.8.8"] {
+            state.statuses.get_mut(address).unwrap().alive = false;
+        }
+        assert_eq!(
+            Summary::compute_at(&state, now).internet,
+            InternetHealth::Down
+        );
+        state
+            .hosts
+            .retain(|h| h.address.starts_with("192.") || h.address.starts_with("10."));
+        assert_eq!(
+            Summary::compute_at(&state, now).internet,
+            InternetHealth::Unknown
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//! W3C trace-context style tags that tie probe packets to log lines and exports.
+//!
+//! A tag has the form of a `traceparent` header, `00-<trace id>-<span id>-01`.
+//! The trace id is configured; the span id is derived from the probed address and
+//! the Unix time of the probe, so a captured packet, the log line of its result and
+//! the exported ping all carry the same tag.
+
+use crate::model::CorrelationSettings;
+use rand::RngExt;
+
+/// A random trace id.
+pub fn new_trace_id() -> String {
+    let mut rng = rand::rng();
+    loop {
+        let id = format!("{:032x}", rng.random::<u128>());
+        if CorrelationSettings::is_valid_trace_id(&id) {
+            return id;
+        }
+    }
+}
+
+/// Span id of the probe of `address` at `timestamp`.
+pub fn span_id(address: &str, timestamp: u64) -> String {
+    format!(
+        "{:08x}{:08x}",
+        crc32fast::hash(address.as_bytes()),
+        timestamp as u32
+    )
+}
+
+/// The `traceparent` tag of the probe of `address` at `timestamp`.
+pub fn traceparent(trace_id: &str, address: &str, timestamp: u64) -> String {
+    format!("00-{}-{}-01", trace_id, span_id(address, timestamp))
+}
+
+/// Writes `traceparent=<tag>` to the start of a probe payload, growing it if needed.
+pub fn embed(payload: &mut Vec<u8>, tag: &str) {
+    let text = format!("traceparent={}", tag);
+    if payload.len() < text.len() {
+        payload.resize(text.len(), 0);
+    }
+    payload[..text.len()].copy_from_slice(text.as_bytes());
+}
+
+/// Appends the tag of `timestamp` to a log line.
+pub fn tag_line(line: String, trace_id: Option<&str>, address: &str, timestamp: u64) -> String {
+    match trace_id {
+        Some(id) => format!(
+            "{} traceparent={}",
+            line,
+            traceparent(id, address, timestamp)
+        ),
+        None => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_ids() {
+        let id = new_trace_id();
+        let valid = CorrelationSettings::is_valid_trace_id;
+        assert!(valid(&id));
+        assert!(!valid(&"0".repeat(32)));
+        assert!(!valid("4BF92F3577B34DA6A3CE929D0E0E4736"));
+        assert!(!valid(&id[1..]));
+    }
+
+    #[test]
+    fn test_payload_and_log_line_carry_the_same_tag() {
+        let id = "4bf92f3577b34da6a3ce929d0e0e4736";
+        let tag = traceparent(id, "10.0.0.1", 1_700_000_000);
+        assert_eq!(
+            tag,
+            format!("00-{}-{:08x}6553f100-01", id, crc32fast::hash(b"10.0.0.1"))
+        );
+
+        let mut payload = vec![7u8; 100];
+        embed(&mut payload, &tag);
+        assert_eq!(payload.len(), 100);
+        assert!(payload.starts_with(format!("traceparent={}", tag).as_bytes()));
+        assert_eq!(payload[99], 7);
+
+        let mut short = vec![0u8; 16];
+        embed(&mut short, &tag);
+        assert_eq!(short.len(), "traceparent=".len() + 55);
+
+        let line = tag_line(
+            "[..] reply".to_string(),
+            Some(id),
+            "10.0.0.1",
+            1_700_000_000,
+        );
+        assert!(line.ends_with(&tag));
+        assert_eq!(tag_line("x".to_string(), None, "10.0.0.1", 0), "x");
+    }
+}
@@ -1,5 +1,6 @@
 #[cfg(windows)]
 use crate::constants::CREATE_NO_WINDOW;
+use crate::logic::applog;
 use regex::Regex;
 use std::time::Duration;
 use tokio::process::Command as TokioCommand;
@@ -27,7 +28,7 @@ pub async fn run_traceroute(address: &str) -> Vec<String> {
         match cmd.spawn() {
             Ok(c) => c,
             Err(e) => {
-                eprintln!("Failed to spawn tracert: {}", e);
+                applog::error("tracer", format!("Failed to spawn tracert: {}", e));
                 return Vec::new();
             }
         }
@@ -41,7 +42,7 @@ pub async fn run_traceroute(address: &str) -> Vec<String> {
         {
             Ok(c) => c,
             Err(e) => {
-                eprintln!("Failed to spawn traceroute: {}", e);
+                applog::error("tracer", format!("Failed to spawn traceroute: {}", e));
                 return Vec::new();
             }
         }
@@ -55,11 +56,14 @@ pub async fn run_traceroute(address: &str) -> Vec<String> {
             parse_traceroute_output(&stdout)
         }
         Ok(Err(e)) => {
-            eprintln!("Traceroute process error: {}", e);
+            applog::error("tracer", format!("Traceroute process error: {}", e));
             Vec::new()
         }
         Err(_) => {
-            eprintln!("Traceroute timed out after 30s for {}", address);
+            applog::warn(
+                "tracer",
+                format!("Traceroute timed out after 30s for {}", address),
+            );
             // child is consumed by wait_with_output, process will be killed on drop
             Vec::new()
         }
@@ -0,0 +1,206 @@
+//! Dashboard of a statistics snapshot, drawn with ratatui by the terminal client.
+
+use crate::model::snapshot::{HostSnapshot, StatsSnapshot};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Cell, Paragraph, Row, Table};
+use tr::tr;
+
+/// Result column and its color: the RTT of a live host, otherwise why it is not.
+fn result(host: &HostSnapshot) -> (String, Color) {
+    if host.is_stopped {
+        (tr!("STOPPED"), Color::DarkGray)
+    } else if host.dns_error {
+        (tr!("UNKNOWN NAME"), Color::Red)
+    } else if host.sent == 0 {
+        ("…".to_string(), Color::DarkGray)
+    } else if host.alive {
+        (format!("{:.1} {}", host.latency, tr!("ms")), Color::Green)
+    } else if let Some(node) = &host.failure_point {
+        (tr!("DOWN (at {node})").replace("{node}", node), Color::Red)
+    } else {
+        (tr!("DOWN"), Color::Red)
+    }
+}
+
+/// Header line: the time of the snapshot and the host counts.
+fn header(snapshot: &StatsSnapshot, paint: impl Fn(Style) -> Style) -> Line<'static> {
+    let down = snapshot
+        .hosts
+        .iter()
+        .filter(|h| !h.is_stopped && h.sent > 0 && !h.alive)
+        .count();
+    // No time before the first snapshot arrives
+    let time = chrono::DateTime::from_timestamp(snapshot.timestamp as i64, 0)
+        .filter(|_| snapshot.timestamp > 0)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default();
+    let mut spans = vec![
+        Span::styled(
+            "egui_pinger",
+            paint(Style::new().add_modifier(Modifier::BOLD)),
+        ),
+        Span::raw(format!(
+            "  {}  {}",
+            time,
+            tr!("{count} hosts").replace("{count}", &snapshot.hosts.len().to_string())
+        )),
+    ];
+    if down > 0 {
+        spans.push(Span::raw(", "));
+        spans.push(Span::styled(
+            tr!("{count} down").replace("{count}", &down.to_string()),
+            paint(Style::new().fg(Color::Red)),
+        ));
+    }
+    Line::from(spans)
+}
+
+/// Draws the dashboard: a header with the host counts, one row per host and
+/// `status` (e.g. a lost connection) at the bottom, or the keys when there is none.
+///
+/// Without `color` everything is drawn in the terminal's own colors (e.g. for
+/// `NO_COLOR`).
+pub fn draw(frame: &mut Frame, snapshot: &StatsSnapshot, status: Option<&str>, color: bool) {
+    let paint = |style: Style| if color { style } else { Style::new() };
+    let [top, table_area, bottom] = Layout::vertical([
+        Constraint::Length(2),
+        Constraint::Fill(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    frame.render_widget(Paragraph::new(header(snapshot, paint)), top);
+
+    let rows = snapshot.hosts.iter().map(|host| {
+        let (text, result_color) = result(host);
+        let loss = if host.sent == 0 {
+            0.0
+        } else {
+            host.lost as f64 / host.sent as f64 * 100.0
+        };
+        let number = |value: f64| Cell::new(Line::from(format!("{:.1}", value)).right_aligned());
+        Row::new([
+            Cell::new(host.name.clone()),
+            Cell::new(host.address.clone()),
+            Cell::new(text).style(paint(Style::new().fg(result_color))),
+            number(host.mean),
+            number(host.p95),
+            number(host.rtp_jitter),
+            Cell::new(Line::from(format!("{:.1}%", loss)).right_aligned()),
+            number(host.mos),
+        ])
+    });
+    let titles = [
+        tr!("Name"),
+        tr!("Address"),
+        tr!("Result"),
+        tr!("Mean"),
+        tr!("P95"),
+        tr!("Jitter"),
+        tr!("Loss"),
+        tr!("MOS"),
+    ];
+    let widths = [
+        Constraint::Fill(1),
+        Constraint::Max(24),
+        Constraint::Length(16),
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Length(7),
+        Constraint::Length(4),
+    ];
+    let table = Table::new(rows, widths).header(
+        Row::new(titles.into_iter().enumerate().map(|(i, title)| {
+            // Number columns have their titles aligned with the numbers
+            if i < 3 {
+                Cell::new(title)
+            } else {
+                Cell::new(Line::from(title).right_aligned())
+            }
+        }))
+        .style(paint(Style::new().add_modifier(Modifier::BOLD))),
+    );
+    frame.render_widget(table, table_area);
+
+    let footer = match status {
+        Some(status) => Span::styled(status.to_string(), paint(Style::new().fg(Color::Yellow))),
+        None => Span::styled(tr!("q: quit"), paint(Style::new().fg(Color::DarkGray))),
+    };
+    frame.render_widget(Paragraph::new(Line::from(footer)), bottom);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn host(name: &str, alive: bool) -> HostSnapshot {
+        HostSnapshot {
+            name: name.to_string(),
+            address: "10.0.0.1".to_string(),
+            is_stopped: false,
+            alive,
+            dns_error: false,
+            latency: 12.34,
+            mean: 10.0,
+            median: 10.0,
+            p95: 20.0,
+            rtp_jitter: 1.5,
+            mos: 4.3,
+            availability: 100.0,
+            sent: 100,
+            lost: if alive { 0 } else { 5 },
+            failure_point: None,
+            traceroute_path: Vec::new(),
+        }
+    }
+
+    /// Draws the dashboard into a 100×8 buffer and returns its lines.
+    fn render(snapshot: &StatsSnapshot, status: Option<&str>, color: bool) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(100, 8)).unwrap();
+        terminal
+            .draw(|frame| draw(frame, snapshot, status, color))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        if !color {
+            assert!(buffer.content.iter().all(|c| c.fg == Color::Reset));
+        }
+        buffer
+            .content
+            .chunks(100)
+            .map(|row| row.iter().map(|c| c.symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_dashboard() {
+        let snapshot = StatsSnapshot {
+            timestamp: 1_700_000_000,
+            hosts: vec![
+                host("Router", true),
+                host("A very long name of the office printer", false),
+            ],
+            trace_id: None,
+        };
+        let lines = render(&snapshot, None, false);
+        assert!(lines[0].contains("2 hosts, 1 down"));
+        assert!(lines[2].starts_with("Name"));
+        assert!(lines[3].starts_with("Router "));
+        assert!(lines[3].contains("12.3 ms"));
+        assert!(lines[4].starts_with("A very long name"));
+        assert!(lines[4].contains("DOWN"));
+        assert!(lines[4].contains("5.0%"));
+        assert!(lines[7].starts_with("q: quit"));
+
+        let lines = render(&snapshot, Some("Connection lost"), true);
+        assert!(lines[7].starts_with("Connection lost"));
+    }
+}
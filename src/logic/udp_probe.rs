@@ -0,0 +1,163 @@
+//! UDP probes of `udp://host:port` addresses, for game servers and VoIP endpoints
+//! whose traffic is treated differently from ICMP and TCP on the way.
+
+use crate::constants::UDP_PROBE_TIMEOUT_MS;
+use crate::logic::dns;
+use std::io::ErrorKind;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+/// Target of a UDP probe, written as `udp://host:port`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UdpProbeTarget {
+    pub host: String,
+    pub port: u16,
+}
+
+/// Parses a `udp://host:port` address. IPv6 hosts are written in brackets.
+pub fn parse_target(address: &str) -> Option<UdpProbeTarget> {
+    let rest = address
+        .get(..6)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("udp://"))
+        .map(|_| &address[6..])?;
+    let (host, port) = rest.trim_end_matches('/').rsplit_once(':')?;
+    // Without brackets the port of an IPv6 address cannot be told apart
+    let bracketed = host.starts_with('[') && host.ends_with(']');
+    let host = dns::strip_brackets(host);
+    if host.is_empty() || host.contains(':') && (!bracketed || host.parse::<IpAddr>().is_err()) {
+        return None;
+    }
+    Some(UdpProbeTarget {
+        host: host.to_string(),
+        port: port.parse().ok().filter(|p| *p != 0)?,
+    })
+}
+
+/// Decodes the payload text of a UDP probe: `\xNN` is a byte in hex, `\n`, `\r`,
+/// `\t`, `\0` and `\\` are the usual characters, other characters are taken as is.
+pub fn parse_payload(text: &str) -> Result<Vec<u8>, String> {
+    let mut payload = Vec::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0u8; 4];
+            payload.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                let byte = u8::from_str_radix(&hex, 16)
+                    .ok()
+                    .filter(|_| hex.len() == 2)
+                    .ok_or_else(|| format!("invalid escape \\x{}", hex))?;
+                payload.push(byte);
+            }
+            Some('n') => payload.push(b'\n'),
+            Some('r') => payload.push(b'\r'),
+            Some('t') => payload.push(b'\t'),
+            Some('0') => payload.push(0),
+            Some('\\') => payload.push(b'\\'),
+            Some(other) => return Err(format!("invalid escape \\{}", other)),
+            None => return Err("payload ends with \\".to_string()),
+        }
+    }
+    Ok(payload)
+}
+
+/// Sends `payload` to the target and returns the time in ms until the reply.
+///
+/// An ICMP port unreachable counts as an answer too: the host is reachable, only
+/// nothing listens on the port. A service that ignores the datagram cannot be
+/// told from a lost one and times out.
+pub async fn probe(target: &UdpProbeTarget, payload: &[u8]) -> Result<f64, String> {
+    let ip = match target.host.parse::<IpAddr>() {
+        Ok(ip) => ip,
+        Err(_) => *dns::resolve(&target.host)
+            .await
+            .first()
+            .ok_or_else(|| "cannot resolve host".to_string())?,
+    };
+    let local: SocketAddr = if ip.is_ipv6() {
+        "[::]:0".parse().expect("valid address")
+    } else {
+        "0.0.0.0:0".parse().expect("valid address")
+    };
+    let socket = tokio::net::UdpSocket::bind(local)
+        .await
+        .map_err(|e| e.to_string())?;
+    socket
+        .connect(SocketAddr::new(ip, target.port))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let start = Instant::now();
+    socket.send(payload).await.map_err(|e| e.to_string())?;
+    let mut buf = [0u8; 2048];
+    let timeout = Duration::from_millis(UDP_PROBE_TIMEOUT_MS);
+    match tokio::time::timeout(timeout, socket.recv(&mut buf)).await {
+        Err(_) => Err("no answer".to_string()),
+        Ok(Ok(_)) => Ok(start.elapsed().as_secs_f64() * 1000.0),
+        // The connected socket reports an ICMP port unreachable as a refused connection
+        Ok(Err(e)) if e.kind() == ErrorKind::ConnectionRefused => {
+            Ok(start.elapsed().as_secs_f64() * 1000.0)
+        }
+        Ok(Err(e)) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(
+            parse_target("udp://game.example.com:27015"),
+            Some(UdpProbeTarget {
+                host: "game.example.com".to_string(),
+                port: 27015
+            })
+        );
+        assert_eq!(
+            parse_target("UDP://[2001:db8::1]:5060/"),
+            Some(UdpProbeTarget {
+                host: "2001:db8::1".to_string(),
+                port: 5060
+            })
+        );
+        assert_eq!(parse_target("udp://10.0.0.1"), None);
+        assert_eq!(parse_target("udp://10.0.0.1:0"), None);
+        assert_eq!(parse_target("udp://2001:db8::1:53"), None);
+        assert_eq!(parse_target("dns://10.0.0.1:53"), None);
+    }
+
+    #[test]
+    fn test_parse_payload() {
+        assert_eq!(
+            parse_payload(r"\xFF\xff\xFF\xFFTSource Engine Query\0").unwrap(),
+            b"\xff\xff\xff\xffTSource Engine Query\0".to_vec()
+        );
+        assert_eq!(parse_payload(r"ping\r\n").unwrap(), b"ping\r\n".to_vec());
+        assert_eq!(parse_payload("").unwrap(), Vec::<u8>::new());
+        assert!(parse_payload(r"\x4").is_err());
+        assert!(parse_payload(r"\q").is_err());
+        assert!(parse_payload("end\\").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_probe_gets_reply() {
+        let server = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let port = server.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 64];
+            let (n, from) = server.recv_from(&mut buf).await.unwrap();
+            server.send_to(&buf[..n], from).await.unwrap();
+        });
+        let target = UdpProbeTarget {
+            host: "127.0.0.1".to_string(),
+            port,
+        };
+        assert!(probe(&target, b"hello").await.is_ok());
+    }
+}
@@ -0,0 +1,309 @@
+use crate::constants::LATEST_RELEASE_URL;
+use crate::logic::{applog, http};
+use crate::model::ProxySettings;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Information about a published release.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReleaseInfo {
+    /// Version without the leading `v` (e.g. "0.7.0").
+    pub version: String,
+    /// Release notes in Markdown, as written on GitHub.
+    pub changelog: String,
+    /// Web page of the release.
+    pub page_url: String,
+    /// Download link of the build for the current platform, if one is published.
+    pub download_url: Option<String>,
+}
+
+/// Outcome of an update check.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpdateCheck {
+    UpToDate,
+    Available(ReleaseInfo),
+    Failed(String),
+}
+
+/// Parses a version like "v1.2.3" or "1.2" into comparable numbers.
+pub fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = version
+        .trim()
+        .trim_start_matches(['v', 'V'])
+        .split(['.', '-', '+'])
+        .map(|p| p.parse::<u64>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().and_then(|p| p.ok()).unwrap_or(0);
+    let patch = parts.next().and_then(|p| p.ok()).unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+/// Returns `true` if `latest` is a newer version than `current`.
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(l), Some(c)) => l > c,
+        _ => false,
+    }
+}
+
+/// Returns `true` if a release asset name looks like a build for this platform.
+fn is_platform_asset(name: &str) -> bool {
+    let os_marker = match std::env::consts::OS {
+        "windows" => "windows",
+        "macos" => "apple",
+        _ => "linux",
+    };
+    name.contains(std::env::consts::ARCH) && name.contains(os_marker) && !name.contains("mos")
+}
+
+/// Extracts release information from a GitHub "latest release" API response.
+pub fn parse_release(json: &serde_json::Value) -> Option<ReleaseInfo> {
+    let tag = json.get("tag_name")?.as_str()?;
+    let download_url = json
+        .get("assets")
+        .and_then(|a| a.as_array())
+        .and_then(|assets| {
+            assets.iter().find_map(|asset| {
+                let name = asset.get("name")?.as_str()?;
+                if is_platform_asset(name) {
+                    asset
+                        .get("browser_download_url")?
+                        .as_str()
+                        .map(str::to_string)
+                } else {
+                    None
+                }
+            })
+        });
+
+    Some(ReleaseInfo {
+        version: tag.trim_start_matches(['v', 'V']).to_string(),
+        changelog: json
+            .get("body")
+            .and_then(|b| b.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        page_url: json
+            .get("html_url")
+            .and_then(|u| u.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        download_url,
+    })
+}
+
+/// Queries GitHub for the latest release and compares it with the running version.
+pub fn check_latest_release(proxy: &ProxySettings) -> UpdateCheck {
+    let response = match http::agent(proxy, LATEST_RELEASE_URL)
+        .get(LATEST_RELEASE_URL)
+        .call()
+    {
+        Ok(r) => r,
+        Err(e) => return UpdateCheck::Failed(e.to_string()),
+    };
+    let body = match response.into_string() {
+        Ok(b) => b,
+        Err(e) => return UpdateCheck::Failed(e.to_string()),
+    };
+    let json: serde_json::Value = match serde_json::from_str(&body) {
+        Ok(j) => j,
+        Err(e) => return UpdateCheck::Failed(e.to_string()),
+    };
+    match parse_release(&json) {
+        Some(release) if is_newer(&release.version, env!("CARGO_PKG_VERSION")) => {
+            UpdateCheck::Available(release)
+        }
+        Some(_) => UpdateCheck::UpToDate,
+        None => UpdateCheck::Failed("Unexpected response from GitHub".to_string()),
+    }
+}
+
+/// Downloads a release asset into `dir`, returning the path of the saved file.
+pub fn download_release(url: &str, dir: &Path, proxy: &ProxySettings) -> Result<PathBuf, String> {
+    let file_name = url
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty() && *name != "..")
+        .unwrap_or("egui_pinger-update");
+    let response = http::agent(proxy, url)
+        .get(url)
+        .call()
+        .map_err(|e| e.to_string())?;
+    let path =
+        save_download(&mut response.into_reader(), dir, file_name).map_err(|e| e.to_string())?;
+    applog::info("updates", format!("Downloaded {}", path.display()));
+    Ok(path)
+}
+
+/// Writes a download into a new file of a random name in `dir` and renames it to
+/// `file_name` once complete, so that a broken download leaves no file behind
+/// and never overwrites one through a link planted under the final name.
+fn save_download(
+    reader: &mut dyn std::io::Read,
+    dir: &Path,
+    file_name: &str,
+) -> std::io::Result<PathBuf> {
+    let part = dir.join(format!(".egui_pinger-{:016x}.part", rand::random::<u64>()));
+    let copied = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&part)
+        .and_then(|mut file| std::io::copy(reader, &mut file));
+    let path = dir.join(file_name);
+    if let Err(e) = copied.and_then(|_| std::fs::rename(&part, &path)) {
+        let _ = std::fs::remove_file(&part);
+        return Err(e);
+    }
+    Ok(path)
+}
+
+/// Runs update checks and downloads in a background thread, polled by the UI.
+#[derive(Default)]
+pub struct UpdateChecker {
+    pending_check: Arc<Mutex<Option<UpdateCheck>>>,
+    pending_download: Arc<Mutex<Option<Result<PathBuf, String>>>>,
+    pub is_checking: bool,
+    pub is_downloading: bool,
+    /// Result of the last completed check.
+    pub result: Option<UpdateCheck>,
+    /// Result of the last completed download.
+    pub download_result: Option<Result<PathBuf, String>>,
+    /// Whether the result should be shown even if no update is available.
+    pub manual: bool,
+}
+
+impl UpdateChecker {
+    /// Starts a background check unless one is already running.
+    pub fn start_check(&mut self, manual: bool, proxy: ProxySettings) {
+        if self.is_checking {
+            return;
+        }
+        self.is_checking = true;
+        self.manual = manual;
+        let slot = self.pending_check.clone();
+        std::thread::spawn(move || {
+            let result = check_latest_release(&proxy);
+            if let UpdateCheck::Failed(e) = &result {
+                applog::warn("updates", format!("Update check failed: {}", e));
+            }
+            if let Ok(mut slot) = slot.lock() {
+                *slot = Some(result);
+            }
+        });
+    }
+
+    /// Starts a background download of the given URL into `dir`.
+    pub fn start_download(&mut self, url: String, dir: PathBuf, proxy: ProxySettings) {
+        if self.is_downloading {
+            return;
+        }
+        self.is_downloading = true;
+        self.download_result = None;
+        let slot = self.pending_download.clone();
+        std::thread::spawn(move || {
+            let result = download_release(&url, &dir, &proxy);
+            if let Ok(mut slot) = slot.lock() {
+                *slot = Some(result);
+            }
+        });
+    }
+
+    /// Collects results of finished background work. Returns `true` if a check completed.
+    pub fn poll(&mut self) -> bool {
+        let mut completed = false;
+        if self.is_checking
+            && let Ok(mut slot) = self.pending_check.lock()
+            && let Some(result) = slot.take()
+        {
+            self.result = Some(result);
+            self.is_checking = false;
+            completed = true;
+        }
+        if self.is_downloading
+            && let Ok(mut slot) = self.pending_download.lock()
+            && let Some(result) = slot.take()
+        {
+            self.download_result = Some(result);
+            self.is_downloading = false;
+        }
+        completed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_comparison() {
+        assert_eq!(parse_version("v0.6.2"), Some((0, 6, 2)));
+        assert_eq!(parse_version("1.2"), Some((1, 2, 0)));
+        assert_eq!(parse_version("garbage"), None);
+        assert!(is_newer("v0.7.0", "0.6.2"));
+        assert!(is_newer("0.6.10", "0.6.9"));
+        assert!(!is_newer("0.6.2", "0.6.2"));
+        assert!(!is_newer("0.5.9", "0.6.2"));
+    }
+
+    #[test]
+    fn test_broken_download_leaves_no_file() {
+        struct Broken(bool);
+        impl std::io::Read for Broken {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if std::mem::replace(&mut self.0, true) {
+                    return Err(std::io::Error::other("connection reset"));
+                }
+                buf[..4].copy_from_slice(b"part");
+                Ok(4)
+            }
+        }
+        let dir = std::env::temp_dir().join(format!("egui_pinger-dl-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert!(save_download(&mut Broken(false), &dir, "app.tar.gz").is_err());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        let path = save_download(&mut &b"complete"[..], &dir, "app.tar.gz").unwrap();
+        assert_eq!(path, dir.join("app.tar.gz"));
+        assert_eq!(std::fs::read(&path).unwrap(), b"complete");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_release() {
+        let asset_name = format!(
+            "egui_pinger_{}-{}-0.7.0.tar.gz",
+            std::env::consts::ARCH,
+            match std::env::consts::OS {
+                "windows" => "pc-windows-msvc",
+                "macos" => "apple-darwin",
+                _ => "unknown-linux-gnu",
+            }
+        );
+        let json = serde_json::json!({
+            "tag_name": "v0.7.0",
+            "body": "* New feature",
+            "html_url": "https://github.com/vlisivka/egui_pinger/releases/tag/v0.7.0",
+            "assets": [
+                { "name": "mos_x86_64-other.tar.gz", "browser_download_url": "https://example.invalid/mos" },
+                { "name": asset_name, "browser_download_url": "https://example.invalid/app" }
+            ]
+        });
+        let release = parse_release(&json).unwrap();
+        assert_eq!(release.version, "0.7.0");
+        assert_eq!(release.changelog, "* New feature");
+        assert_eq!(
+            release.download_url.as_deref(),
+            Some("https://example.invalid/app")
+        );
+    }
+
+    #[test]
+    fn test_parse_release_without_tag() {
+        assert_eq!(
+            parse_release(&serde_json::json!({ "message": "Not Found" })),
+            None
+        );
+    }
+}
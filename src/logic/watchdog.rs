@@ -0,0 +1,269 @@
+use crate::constants::{PINGER_STALL_TIMEOUT_SEC, WATCHDOG_NOTICE_SEC};
+use crate::logic::shutdown::CancellationToken;
+use crate::logic::{SharedState, applog, pinger_task};
+use std::sync::PoisonError;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tr::tr;
+
+/// Starts a pinger loop of the given generation on its own thread and runtime.
+fn spawn_pinger(state: SharedState, generation: u64, cancel: CancellationToken) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        match tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(rt) => rt.block_on(pinger_task(state, generation, cancel)),
+            Err(e) => applog::error("pinger", format!("Failed to start runtime: {}", e)),
+        }
+    })
+}
+
+/// Extracts the message of a panic payload.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Last restart of the pinger, shown as a warning for a while.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestartNotice {
+    pub reason: String,
+    pub at: chrono::DateTime<chrono::Local>,
+    /// Restarts since the application started.
+    pub count: u32,
+}
+
+/// Starts a pinger loop of a generation, stopped by the token.
+type Spawner = fn(SharedState, u64, CancellationToken) -> JoinHandle<()>;
+
+/// Restarts the pinger when its thread ends (e.g. after a panic) or its loop
+/// stops sending heartbeats.
+pub struct Watchdog {
+    state: SharedState,
+    spawn: Spawner,
+    handle: Option<JoinHandle<()>>,
+    /// Cancelled when the application quits; no restarts after that.
+    cancel: CancellationToken,
+    generation: u64,
+    started: Instant,
+    restarts: u32,
+    last_restart: Option<(Instant, RestartNotice)>,
+}
+
+impl Watchdog {
+    /// Starts the pinger and watches it.
+    pub fn start(state: SharedState) -> Self {
+        Self::with_spawner(state, spawn_pinger)
+    }
+
+    fn with_spawner(state: SharedState, spawn: Spawner) -> Self {
+        let generation = state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pinger_generation;
+        let cancel = CancellationToken::default();
+        Self {
+            handle: Some(spawn(state.clone(), generation, cancel.clone())),
+            state,
+            spawn,
+            cancel,
+            generation,
+            started: Instant::now(),
+            restarts: 0,
+            last_restart: None,
+        }
+    }
+
+    /// Restarts the pinger if needed; call regularly from the UI.
+    pub fn check(&mut self) {
+        self.check_at(Instant::now());
+    }
+
+    fn check_at(&mut self, now: Instant) {
+        if self.cancel.is_cancelled() {
+            return;
+        }
+        // A panic while holding the lock would otherwise take the UI down as well
+        if self.state.is_poisoned() {
+            applog::warn("watchdog", "State lock was poisoned by a panic, recovering");
+            self.state.clear_poison();
+        }
+
+        let timeout = Duration::from_secs(PINGER_STALL_TIMEOUT_SEC);
+        // Give a replacement that fails right away time before trying again
+        let cooling_down = self
+            .last_restart
+            .as_ref()
+            .is_some_and(|(at, _)| now.duration_since(*at) < timeout);
+
+        let finished = self.handle.as_ref().is_none_or(|h| h.is_finished());
+        let reason = if finished {
+            if cooling_down {
+                return;
+            }
+            match self.handle.take().map(JoinHandle::join) {
+                Some(Err(payload)) => tr!("the pinger panicked: {message}")
+                    .replace("{message}", &panic_message(payload.as_ref())),
+                _ => tr!("the pinger stopped"),
+            }
+        } else {
+            let heartbeat = self
+                .state
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .pinger_heartbeat;
+            let last_alive = heartbeat.map_or(self.started, |h| h.max(self.started));
+            if now.duration_since(last_alive) <= timeout {
+                return;
+            }
+            tr!("the pinger did not respond for {secs} s").replace(
+                "{secs}",
+                &now.duration_since(last_alive).as_secs().to_string(),
+            )
+        };
+
+        applog::error("watchdog", format!("Restarting pinger: {}", reason));
+        self.generation += 1;
+        {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            state.pinger_generation = self.generation;
+            state.pinger_heartbeat = None;
+        }
+        self.handle = Some((self.spawn)(
+            self.state.clone(),
+            self.generation,
+            self.cancel.clone(),
+        ));
+        self.started = now;
+        self.restarts += 1;
+        self.last_restart = Some((
+            now,
+            RestartNotice {
+                reason,
+                at: chrono::Local::now(),
+                count: self.restarts,
+            },
+        ));
+    }
+
+    /// Stops the pinger for good: cancels its loop and probes and waits up to
+    /// `timeout` for it to apply the last results. Returns whether it stopped in time.
+    pub fn shutdown(&mut self, timeout: Duration) -> bool {
+        self.cancel.cancel();
+        let Some(handle) = self.handle.take() else {
+            return true;
+        };
+        let deadline = Instant::now() + timeout;
+        while !handle.is_finished() {
+            if Instant::now() >= deadline {
+                applog::warn("watchdog", "The pinger did not stop in time");
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        if handle.join().is_err() {
+            applog::warn("watchdog", "The pinger panicked while stopping");
+        }
+        true
+    }
+
+    /// The last restart, while it is recent enough to be shown.
+    pub fn notice(&self) -> Option<&RestartNotice> {
+        self.last_restart
+            .as_ref()
+            .filter(|(at, _)| at.elapsed() < Duration::from_secs(WATCHDOG_NOTICE_SEC))
+            .map(|(_, notice)| notice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::AppState;
+    use std::sync::{Arc, Mutex};
+
+    /// Panics in the first generation and beats until replaced in the later ones.
+    fn flaky_pinger(state: SharedState, generation: u64, _: CancellationToken) -> JoinHandle<()> {
+        std::thread::spawn(move || {
+            if generation == 0 {
+                panic!("boom");
+            }
+            loop {
+                let mut s = state.lock().unwrap();
+                if s.pinger_generation != generation {
+                    return;
+                }
+                s.pinger_heartbeat = Some(Instant::now());
+                drop(s);
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        })
+    }
+
+    /// Never beats, like a loop stuck in a blocking call.
+    fn stuck_pinger(_: SharedState, _: u64, _: CancellationToken) -> JoinHandle<()> {
+        std::thread::spawn(|| std::thread::sleep(Duration::from_millis(200)))
+    }
+
+    #[test]
+    fn test_restarts_after_panic() {
+        let state: SharedState = Arc::new(Mutex::new(AppState::default()));
+        let mut dog = Watchdog::with_spawner(state.clone(), flaky_pinger);
+        while !dog.handle.as_ref().unwrap().is_finished() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        dog.check();
+
+        let notice = dog.notice().unwrap();
+        assert_eq!(notice.reason, "the pinger panicked: boom");
+        assert_eq!(notice.count, 1);
+        assert_eq!(state.lock().unwrap().pinger_generation, 1);
+
+        // The replacement is healthy
+        std::thread::sleep(Duration::from_millis(50));
+        dog.check();
+        assert_eq!(dog.restarts, 1);
+        state.lock().unwrap().pinger_generation = 99;
+    }
+
+    #[test]
+    fn test_restarts_stalled_loop() {
+        let state: SharedState = Arc::new(Mutex::new(AppState::default()));
+        let mut dog = Watchdog::with_spawner(state.clone(), stuck_pinger);
+        let start = dog.started;
+        dog.check_at(start + Duration::from_secs(PINGER_STALL_TIMEOUT_SEC));
+        assert!(dog.notice().is_none());
+
+        dog.check_at(start + Duration::from_secs(PINGER_STALL_TIMEOUT_SEC + 1));
+        let notice = dog.notice().unwrap();
+        assert!(notice.reason.starts_with("the pinger did not respond"));
+        assert_eq!(state.lock().unwrap().pinger_generation, 1);
+    }
+
+    /// Beats until cancelled.
+    fn stoppable_pinger(state: SharedState, _: u64, cancel: CancellationToken) -> JoinHandle<()> {
+        std::thread::spawn(move || {
+            while !cancel.is_cancelled() {
+                state.lock().unwrap().pinger_heartbeat = Some(Instant::now());
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        })
+    }
+
+    #[test]
+    fn test_shutdown_stops_pinger_for_good() {
+        let state: SharedState = Arc::new(Mutex::new(AppState::default()));
+        let mut dog = Watchdog::with_spawner(state.clone(), stoppable_pinger);
+        assert!(dog.shutdown(Duration::from_secs(5)));
+        assert!(dog.handle.is_none());
+
+        // The stopped pinger is not restarted
+        dog.check_at(Instant::now() + Duration::from_secs(PINGER_STALL_TIMEOUT_SEC + 1));
+        assert!(dog.handle.is_none() && dog.notice().is_none());
+        assert!(dog.shutdown(Duration::from_secs(1)));
+    }
+}
@@ -0,0 +1,202 @@
+//! HTTP webhooks (Slack, Discord, Telegram, ...) notified on host state transitions.
+
+use crate::logic::{applog, http};
+use crate::model::{ProxySettings, WebhookTarget};
+use tr::tr;
+
+/// Host state transition reported to the webhook targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    Down,
+    Up,
+    /// An alert rule fired.
+    Degraded,
+    /// The host is still down after the escalation time.
+    Escalation,
+}
+
+impl WebhookEvent {
+    /// Value of the `{event}` placeholder.
+    pub fn name(self) -> &'static str {
+        match self {
+            WebhookEvent::Down => "down",
+            WebhookEvent::Up => "up",
+            WebhookEvent::Degraded => "degraded",
+            WebhookEvent::Escalation => "escalation",
+        }
+    }
+}
+
+/// A state transition to report to the webhook targets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookMessage {
+    pub event: WebhookEvent,
+    pub host: String,
+    pub address: String,
+    /// Human-readable summary, e.g. "Gateway is down".
+    pub message: String,
+    /// Unix timestamp of the transition.
+    pub timestamp: u64,
+    pub loss_pct: f64,
+    /// Mean RTT in ms.
+    pub rtt_ms: f64,
+}
+
+/// Summary of a transition; `detail` is the downtime or the alert message.
+pub fn describe(event: WebhookEvent, host: &str, detail: &str) -> String {
+    match event {
+        WebhookEvent::Down => tr!("{host} is down"),
+        WebhookEvent::Up => tr!("{host} is up again after {detail}"),
+        WebhookEvent::Degraded => tr!("{host}: {detail}"),
+        WebhookEvent::Escalation => tr!("{host} is still down after {detail}"),
+    }
+    .replace("{host}", host)
+    .replace("{detail}", detail)
+}
+
+/// Escapes a value for use inside a JSON string.
+fn json_escape(value: &str) -> String {
+    let quoted = serde_json::Value::String(value.to_string()).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Value of the placeholder `name` for a message, `None` for an unknown one.
+fn placeholder(name: &str, msg: &WebhookMessage) -> Option<String> {
+    Some(match name {
+        "event" => msg.event.name().to_string(),
+        "host" => json_escape(&msg.host),
+        "address" => json_escape(&msg.address),
+        "message" => json_escape(&msg.message),
+        "time" => chrono::DateTime::from_timestamp(msg.timestamp as i64, 0)
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_default(),
+        "timestamp" => msg.timestamp.to_string(),
+        "loss" => format!("{:.1}", msg.loss_pct),
+        "rtt" => format!("{:.1}", msg.rtt_ms),
+        _ => return None,
+    })
+}
+
+/// Fills the `{placeholders}` of a template with JSON-escaped values.
+///
+/// Available: `{event}`, `{host}`, `{address}`, `{message}`, `{time}` (RFC 3339),
+/// `{timestamp}`, `{loss}` and `{rtt}`. The template is read once, so values are
+/// never searched for placeholders; other braces are kept as they are.
+pub fn render(template: &str, msg: &WebhookMessage) -> String {
+    let mut body = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        body.push_str(&rest[..open]);
+        let tail = &rest[open..];
+        match tail
+            .find('}')
+            .and_then(|close| Some((placeholder(&tail[1..close], msg)?, close)))
+        {
+            Some((value, close)) => {
+                body.push_str(&value);
+                rest = &tail[close + 1..];
+            }
+            None => {
+                body.push('{');
+                rest = &tail[1..];
+            }
+        }
+    }
+    body.push_str(rest);
+    body
+}
+
+/// POSTs a JSON body, returning the HTTP status.
+pub fn post(proxy: &ProxySettings, url: &str, body: &str) -> Result<u16, String> {
+    match http::agent(proxy, url)
+        .post(url)
+        .set("Content-Type", "application/json")
+        .send_string(body)
+    {
+        Ok(response) => Ok(response.status()),
+        Err(ureq::Error::Status(code, _)) => Err(format!("HTTP {}", code)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Sends the messages to every enabled target on a background thread.
+pub fn send(proxy: ProxySettings, targets: Vec<WebhookTarget>, messages: Vec<WebhookMessage>) {
+    let targets: Vec<_> = targets
+        .into_iter()
+        .filter(|t| t.enabled && !t.url.trim().is_empty())
+        .collect();
+    if targets.is_empty() || messages.is_empty() {
+        return;
+    }
+    std::thread::spawn(move || {
+        for msg in &messages {
+            for target in &targets {
+                if let Err(e) = post(&proxy, target.url.trim(), &render(&target.template, msg)) {
+                    applog::warn(
+                        "webhook",
+                        format!("Failed to notify {}: {}", target.display_name(), e),
+                    );
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::presenter::format_duration;
+    use crate::model::WebhookPreset;
+
+    fn message() -> WebhookMessage {
+        WebhookMessage {
+            event: WebhookEvent::Down,
+            host: "Rack \"3\"".to_string(),
+            address: "10.0.0.3".to_string(),
+            message: describe(WebhookEvent::Down, "Rack \"3\"", ""),
+            timestamp: 0,
+            loss_pct: 50.0,
+            rtt_ms: 12.34,
+        }
+    }
+
+    #[test]
+    fn test_render_escapes_values() {
+        let body = render(WebhookPreset::Generic.template(), &message());
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["event"], "down");
+        assert_eq!(json["host"], "Rack \"3\"");
+        assert_eq!(json["message"], "Rack \"3\" is down");
+        assert_eq!(json["time"], "1970-01-01T00:00:00+00:00");
+        assert_eq!(json["loss"], 50.0);
+        assert_eq!(json["rtt"], 12.3);
+
+        for preset in WebhookPreset::ALL {
+            let body = render(preset.template(), &message());
+            assert!(serde_json::from_str::<serde_json::Value>(&body).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_render_substitutes_once() {
+        let msg = WebhookMessage {
+            host: "{address}".to_string(),
+            ..message()
+        };
+        assert_eq!(
+            render(
+                r#"{"h": "{host}", "a": "{address}", "x": "{unknown}"}"#,
+                &msg
+            ),
+            r#"{"h": "{address}", "a": "10.0.0.3", "x": "{unknown}"}"#
+        );
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(
+            describe(WebhookEvent::Up, "GW", &format_duration(3_725)),
+            "GW is up again after 1h 02m 05s"
+        );
+    }
+}
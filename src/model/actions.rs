@@ -0,0 +1,41 @@
+use tr::tr;
+
+/// State change of a host that runs its action command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionEvent {
+    /// An incident opened.
+    Down,
+    /// The host answers again after an incident.
+    Up,
+}
+
+impl ActionEvent {
+    pub fn label(&self) -> String {
+        match self {
+            ActionEvent::Down => tr!("on down"),
+            ActionEvent::Up => tr!("on up"),
+        }
+    }
+
+    /// Value of the `EGUI_PINGER_EVENT` environment variable.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActionEvent::Down => "down",
+            ActionEvent::Up => "up",
+        }
+    }
+}
+
+/// An executed action command, kept in the action log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionRecord {
+    /// Unix timestamp when the command was started.
+    pub timestamp: u64,
+    pub address: String,
+    pub event: ActionEvent,
+    pub command: String,
+    /// Exit code, or why the command could not run to completion.
+    pub result: Result<i32, String>,
+    /// Run time in ms.
+    pub duration_ms: u64,
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use tr::tr;
+
+/// A condition on a host's measurements that raises an alert when met.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AlertRule {
+    /// Median latency of the last `window_min` minutes is more than `increase_pct`
+    /// percent higher than the median of the preceding window of the same length.
+    ///
+    /// Detects slow degradation (overheating modem, filling queue) before any
+    /// absolute threshold is crossed.
+    LatencyTrend { increase_pct: f64, window_min: u32 },
+    /// Packet loss over the statistics window exceeds `pct` percent.
+    LossAbove { pct: f64 },
+    /// At least `count` pings in a row have failed.
+    ConsecutiveFailures { count: u32 },
+    /// Median latency over the statistics window exceeds `ms`.
+    LatencyAbove { ms: f64 },
+    /// Estimated voice quality (MOS) drops below `mos`.
+    MosBelow { mos: f64 },
+}
+
+impl AlertRule {
+    /// Human-readable description of the rule.
+    pub fn label(&self) -> String {
+        match self {
+            AlertRule::LatencyTrend {
+                increase_pct,
+                window_min,
+            } => tr!("Median latency up by more than {pct}% over {min} min")
+                .replace("{pct}", &format!("{:.0}", increase_pct))
+                .replace("{min}", &window_min.to_string()),
+            AlertRule::LossAbove { pct } => {
+                tr!("Packet loss above {pct}%").replace("{pct}", &format!("{:.1}", pct))
+            }
+            AlertRule::ConsecutiveFailures { count } => {
+                tr!("{count} consecutive failures").replace("{count}", &count.to_string())
+            }
+            AlertRule::LatencyAbove { ms } => {
+                tr!("Median latency above {ms} ms").replace("{ms}", &format!("{:.0}", ms))
+            }
+            AlertRule::MosBelow { mos } => {
+                tr!("MOS below {mos}").replace("{mos}", &format!("{:.1}", mos))
+            }
+        }
+    }
+}
+
+/// An alert that is currently raised for a host.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveAlert {
+    pub rule: AlertRule,
+    /// Details of the violation at the time it was detected.
+    pub message: String,
+    /// Unix timestamp when the alert was raised.
+    pub since: u64,
+}
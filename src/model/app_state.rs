@@ -1,6 +1,11 @@
-use super::status::{HostInfo, HostStatus, LogFilter};
+use super::actions::ActionRecord;
+use super::settings::AppSettings;
+use super::snapshot::HostSnapshot;
+use super::status::{DisplaySettings, HostInfo, HostStatus, LogEntry, LogFilter, PingMode};
+use crate::constants::{MAX_RETAINED_EVENTS_PER_HOST, MAX_SLEEP_GAPS, SLEEP_RESUME_GRACE_SEC};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use tr::tr;
 
 #[derive(Default, Serialize, Deserialize)]
 pub struct AppState {
@@ -8,4 +13,594 @@ pub struct AppState {
     pub statuses: HashMap<String, HostStatus>,
     #[serde(default)]
     pub log_filter: LogFilter,
+    #[serde(default, deserialize_with = "AppSettings::deserialize_migrated")]
+    pub settings: AppSettings,
+    /// Hosts removed by the user whose history is retained until `purge_at`.
+    #[serde(default)]
+    pub deleted_hosts: Vec<DeletedHost>,
+    /// Groups whose alerts are silenced, with the Unix timestamp the silence ends.
+    #[serde(default)]
+    pub silenced_groups: HashMap<String, u64>,
+    /// Notes on the timeline ("rebooted router"), oldest first.
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+    /// Typical latency and loss of each host in the previous session, by address.
+    #[serde(default)]
+    pub baselines: HashMap<String, SessionBaseline>,
+    /// Name of the active configuration profile; empty for the default one.
+    #[serde(default)]
+    pub profile: String,
+    /// Names of the configuration profiles, the active one included.
+    #[serde(default)]
+    pub profiles: Vec<String>,
+    /// Networks on which profiles are activated automatically, by profile name.
+    #[serde(default)]
+    pub profile_networks: HashMap<String, ProfileNetwork>,
+    /// Switch to the profile of the detected network automatically.
+    #[serde(default)]
+    pub auto_profile: bool,
+    /// The network the machine is on, from the last detection.
+    #[serde(skip)]
+    pub network: Option<NetworkInfo>,
+    /// Public IP addresses from the last lookup.
+    #[serde(skip)]
+    pub public_ip: PublicIpStatus,
+    /// Link state of the network interfaces by name, from the last scan.
+    #[serde(skip)]
+    pub interfaces: HashMap<String, bool>,
+    /// Result of the last captive portal check.
+    #[serde(skip)]
+    pub connectivity: Connectivity,
+    /// State of the synchronization with the shared host list file.
+    #[serde(skip)]
+    pub shared_list: SharedListStatus,
+    /// File names (without extension) of incident screenshots the UI still has to take.
+    #[serde(skip)]
+    pub pending_screenshots: VecDeque<String>,
+    /// Last time the pinger loop ran, checked by the watchdog.
+    #[serde(skip)]
+    pub pinger_heartbeat: Option<std::time::Instant>,
+    /// Generation of the pinger loop that should run; older loops exit.
+    #[serde(skip)]
+    pub pinger_generation: u64,
+    /// On down / on up commands run since the start, oldest first.
+    #[serde(skip)]
+    pub action_log: VecDeque<ActionRecord>,
+    /// Reason the statistics stream could not be started.
+    #[serde(skip)]
+    pub stream_error: Option<String>,
+    /// Names from the DHCP lease file and the state of reading it.
+    #[serde(skip)]
+    pub dhcp_leases: DhcpLeaseStatus,
+    /// Times (start, end) the machine was asleep since the start, oldest first.
+    #[serde(skip)]
+    pub sleep_gaps: VecDeque<(u64, u64)>,
+    /// Set in the background daemon, which has no window to take incident screenshots of.
+    #[serde(skip)]
+    pub headless: bool,
+    /// Windows attached to the background daemon; while there are any, they show
+    /// the notifications and play the sounds instead of the daemon.
+    #[serde(skip)]
+    pub attached_windows: u32,
+    /// Whether this window shows the results of the background daemon instead of
+    /// pinging itself.
+    #[serde(skip)]
+    pub daemon_attached: bool,
+    /// Reason the remote agent could not be started.
+    #[serde(skip)]
+    pub agent_error: Option<String>,
+    /// Reason the JSON API could not be started.
+    #[serde(skip)]
+    pub api_error: Option<String>,
+    /// Whether the MQTT publisher is connected to the broker, and why it is not.
+    #[serde(skip)]
+    pub mqtt_connected: bool,
+    #[serde(skip)]
+    pub mqtt_error: Option<String>,
+    /// State of the links to the remote agents by their name.
+    #[serde(skip)]
+    pub remote_agent_status: HashMap<String, RemoteAgentStatus>,
+}
+
+/// Last synchronization with the shared host list file.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SharedListStatus {
+    /// File the state refers to; a different configured path forces a reload.
+    pub path: String,
+    /// Modification time of the file when it was last merged.
+    pub modified: Option<std::time::SystemTime>,
+    /// Number of hosts in the file when it was last merged.
+    pub hosts: usize,
+    /// Reason the last attempt to read the file failed.
+    pub error: Option<String>,
+}
+
+/// Host names read from the DHCP lease file.
+#[derive(Debug, Clone, Default)]
+pub struct DhcpLeaseStatus {
+    /// File the names come from; a different configured path forces a reload.
+    pub path: String,
+    /// Modification time of the file when it was last read.
+    pub modified: Option<std::time::SystemTime>,
+    /// IP address -> host name of the leases that have a name.
+    pub names: HashMap<String, String>,
+    /// Reason the last attempt to read the file failed.
+    pub error: Option<String>,
+}
+
+/// State of the link to a remote agent.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum RemoteAgentStatus {
+    #[default]
+    Connecting,
+    Connected,
+    /// The last attempt failed for this reason; it is retried later.
+    Failed(String),
+}
+
+/// Global Internet connectivity as seen by the captive portal check.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Connectivity {
+    /// Not checked yet, the check is disabled or its answer proved nothing.
+    #[default]
+    Unknown,
+    /// The check endpoint answered as expected.
+    Online,
+    /// The check request was intercepted, e.g. by a hotel or airport Wi-Fi login page.
+    CaptivePortal { login_url: Option<String> },
+    /// The check endpoint could not be reached at all.
+    Offline,
+}
+
+/// The network the machine is connected to, as far as it could be detected.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NetworkInfo {
+    pub gateway: Option<std::net::IpAddr>,
+    /// Lowercase, colon-separated MAC address of the default gateway.
+    pub gateway_mac: Option<String>,
+    /// Name of the Wi-Fi network, if connected over Wi-Fi.
+    pub ssid: Option<String>,
+    /// Public IP address, only looked up when a profile needs it.
+    pub public_ip: Option<std::net::IpAddr>,
+}
+
+/// Public IP addresses of the machine, as told by the public IP services.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PublicIpStatus {
+    pub v4: Option<std::net::IpAddr>,
+    pub v6: Option<std::net::IpAddr>,
+    /// Unix timestamp of the last lookup.
+    pub checked_at: Option<u64>,
+    /// Why the last lookup failed with every service.
+    pub error: Option<String>,
+}
+
+/// The network a profile is activated on. Every field that is set must match;
+/// a network without any field set matches nothing.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ProfileNetwork {
+    #[serde(default)]
+    pub gateway_mac: String,
+    #[serde(default)]
+    pub ssid: String,
+    #[serde(default)]
+    pub public_ip: String,
+}
+
+impl ProfileNetwork {
+    pub fn is_empty(&self) -> bool {
+        self.gateway_mac.trim().is_empty()
+            && self.ssid.trim().is_empty()
+            && self.public_ip.trim().is_empty()
+    }
+
+    /// Whether `network` is this one.
+    pub fn matches(&self, network: &NetworkInfo) -> bool {
+        let field = |wanted: &str, actual: Option<String>| {
+            let wanted = wanted.trim();
+            wanted.is_empty() || actual.is_some_and(|a| a == wanted)
+        };
+        // Detected addresses are lowercase and colon-separated
+        let mac = self
+            .gateway_mac
+            .trim()
+            .to_ascii_lowercase()
+            .replace('-', ":");
+        !self.is_empty()
+            && field(&mac, network.gateway_mac.clone())
+            && field(&self.ssid, network.ssid.clone())
+            && field(&self.public_ip, network.public_ip.map(|ip| ip.to_string()))
+    }
+
+    /// The network as detected, for "use the current network".
+    pub fn from_info(network: &NetworkInfo) -> Self {
+        Self {
+            gateway_mac: network.gateway_mac.clone().unwrap_or_default(),
+            ssid: network.ssid.clone().unwrap_or_default(),
+            public_ip: String::new(),
+        }
+    }
+}
+
+/// A deleted host together with the history retained for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedHost {
+    pub host: HostInfo,
+    /// Unix timestamp of the deletion.
+    pub deleted_at: u64,
+    /// Unix timestamp after which the retained data is purged.
+    pub purge_at: u64,
+    /// Statistics at the moment of deletion.
+    pub snapshot: HostSnapshot,
+    /// Most recent events of the host (up to MAX_RETAINED_EVENTS_PER_HOST).
+    pub events: VecDeque<LogEntry>,
+}
+
+/// A note at a point in time, drawn as a vertical marker on the history plots.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    /// Unix timestamp the note refers to.
+    pub timestamp: u64,
+    pub text: String,
+}
+
+/// Latency and loss of a host over a session, compared with the next session.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SessionBaseline {
+    /// Unix timestamp the baseline was taken at.
+    pub timestamp: u64,
+    /// Median RTT in ms.
+    pub median: f64,
+    /// Share of lost probes in percent.
+    pub loss_pct: f64,
+    /// Probes the values are based on.
+    pub samples: u32,
+}
+
+impl AppState {
+    /// The first profile whose network is the detected one.
+    pub fn matching_profile(&self) -> Option<String> {
+        let network = self.network.as_ref()?;
+        self.profile_names().into_iter().find(|name| {
+            self.profile_networks
+                .get(name)
+                .is_some_and(|n| n.matches(network))
+        })
+    }
+
+    /// Names of the configuration profiles; just the active one until another is created.
+    pub fn profile_names(&self) -> Vec<String> {
+        if self.profiles.contains(&self.profile) {
+            self.profiles.clone()
+        } else {
+            vec![self.profile.clone()]
+        }
+    }
+
+    /// Adds a note to the timeline, keeping the notes ordered by time.
+    pub fn annotate(&mut self, timestamp: u64, text: &str) {
+        let pos = self
+            .annotations
+            .partition_point(|a| a.timestamp <= timestamp);
+        self.annotations.insert(
+            pos,
+            Annotation {
+                timestamp,
+                text: text.trim().to_string(),
+            },
+        );
+    }
+
+    /// Adds a host with the settings of new hosts; `None` if its address is
+    /// already monitored.
+    pub fn add_host(&mut self, name: &str, address: &str) -> Option<&HostInfo> {
+        let address = HostInfo::normalize_address(address);
+        if self.hosts.iter().any(|h| h.address == address) {
+            return None;
+        }
+        self.statuses.insert(address.clone(), HostStatus::default());
+        let mut host_info = HostInfo {
+            name: name.trim().to_string(),
+            address,
+            thresholds: self.settings.default_thresholds,
+            mode: PingMode::NotFast,
+            display: DisplaySettings::default(),
+            packet_size: 16,
+            random_padding: false,
+            log_to_file: false,
+            log_file_path: String::new(),
+            is_stopped: false,
+            ..Default::default()
+        };
+        if host_info.is_local() {
+            host_info.mode = PingMode::Fast;
+        }
+        self.hosts.push(host_info);
+        self.hosts.last()
+    }
+
+    /// Removes a host from monitoring.
+    ///
+    /// If `retain_days` is set, the host's configuration, final statistics and
+    /// recent events are kept in `deleted_hosts` so they can be restored or
+    /// reviewed until the retention period expires.
+    pub fn delete_host(&mut self, address: &str, retain_days: Option<u32>, now: u64) {
+        let Some(pos) = self.hosts.iter().position(|h| h.address == address) else {
+            return;
+        };
+        let host = self.hosts.remove(pos);
+        let status = self.statuses.remove(address).unwrap_or_default();
+
+        if let Some(days) = retain_days {
+            let start = status
+                .events
+                .len()
+                .saturating_sub(MAX_RETAINED_EVENTS_PER_HOST);
+            self.deleted_hosts.retain(|d| d.host.address != address);
+            self.deleted_hosts.push(DeletedHost {
+                snapshot: HostSnapshot::capture(&host, &status),
+                events: status.events.range(start..).cloned().collect(),
+                host,
+                deleted_at: now,
+                purge_at: now + days as u64 * 86_400,
+            });
+        }
+    }
+
+    /// Moves a deleted host back into the monitored list, including its retained events.
+    ///
+    /// Returns `false` if a host with the same address is already monitored.
+    pub fn restore_deleted_host(&mut self, address: &str) -> bool {
+        if self.hosts.iter().any(|h| h.address == address) {
+            return false;
+        }
+        let Some(pos) = self
+            .deleted_hosts
+            .iter()
+            .position(|d| d.host.address == address)
+        else {
+            return false;
+        };
+        let deleted = self.deleted_hosts.remove(pos);
+        let status = self.statuses.entry(address.to_string()).or_default();
+        status.is_trace_hop = false;
+        for ev in deleted.events.into_iter().rev() {
+            status.events.push_front(ev);
+        }
+        status.trim_events();
+        self.hosts.push(deleted.host);
+        true
+    }
+
+    /// Permanently removes the retained data of a deleted host.
+    pub fn purge_deleted_host(&mut self, address: &str) {
+        self.deleted_hosts.retain(|d| d.host.address != address);
+    }
+
+    /// Purges all deleted hosts whose retention period has expired.
+    pub fn purge_expired_deleted_hosts(&mut self, now: u64) {
+        self.deleted_hosts.retain(|d| d.purge_at > now);
+    }
+
+    /// End of the silence of a group, if its alerts are silenced at `now`.
+    pub fn silenced_until(&self, group: &str, now: u64) -> Option<u64> {
+        self.silenced_groups
+            .get(group)
+            .copied()
+            .filter(|&until| until > now)
+    }
+
+    /// Whether the alerts of the host with `address` are silenced through its group.
+    pub fn is_host_silenced(&self, address: &str, now: u64) -> bool {
+        self.hosts
+            .iter()
+            .find(|h| h.address == address)
+            .is_some_and(|h| !h.group.is_empty() && self.silenced_until(&h.group, now).is_some())
+    }
+
+    /// Whether the alerts of the host with `address` fall into the global or its own quiet hours.
+    pub fn is_host_quiet(&self, address: &str, now: u64) -> bool {
+        self.settings.quiet_schedule.contains_at(now)
+            || self
+                .hosts
+                .iter()
+                .find(|h| h.address == address)
+                .is_some_and(|h| h.quiet_schedule.contains_at(now))
+    }
+
+    /// Whether `now` falls into a maintenance window of the host with `address`.
+    pub fn is_host_in_maintenance(&self, address: &str, now: u64) -> bool {
+        self.hosts
+            .iter()
+            .find(|h| h.address == address)
+            .is_some_and(|h| h.maintenance.contains_at(now))
+    }
+
+    /// Records that the machine was asleep from `start` to `end` and notes it on the timeline.
+    pub fn record_sleep_gap(&mut self, start: u64, end: u64) {
+        self.sleep_gaps.push_back((start, end));
+        if self.sleep_gaps.len() > MAX_SLEEP_GAPS {
+            self.sleep_gaps.pop_front();
+        }
+        let minutes = (end.saturating_sub(start) as f64 / 60.0).ceil() as u64;
+        self.annotate(
+            start,
+            &tr!("Computer asleep for {minutes} min").replace("{minutes}", &minutes.to_string()),
+        );
+    }
+
+    /// Whether a probe finished at `timestamp` tells nothing because the machine was
+    /// asleep, or had just woken up and was not back on the network yet.
+    pub fn is_sleep_gap(&self, timestamp: u64) -> bool {
+        self.sleep_gaps
+            .iter()
+            .any(|&(start, end)| (start..=end + SLEEP_RESUME_GRACE_SEC).contains(&timestamp))
+    }
+
+    /// Silences the alerts of a group until `until`, or lifts the silence if `None`.
+    ///
+    /// The change is recorded in the event log of each member.
+    pub fn set_group_silence(&mut self, group: &str, until: Option<u64>, now: u64) {
+        let message = match until {
+            Some(until) => {
+                self.silenced_groups.insert(group.to_string(), until);
+                let end = chrono::DateTime::from_timestamp(until as i64, 0)
+                    .map(|t| t.with_timezone(&chrono::Local).format("%H:%M").to_string())
+                    .unwrap_or_default();
+                tr!("Alerts of group {group} silenced until {end}")
+                    .replace("{group}", group)
+                    .replace("{end}", &end)
+            }
+            None => {
+                self.silenced_groups.remove(group);
+                tr!("Alerts of group {group} no longer silenced").replace("{group}", group)
+            }
+        };
+        for host in self.hosts.iter().filter(|h| h.group == group) {
+            if let Some(status) = self.statuses.get_mut(&host.address) {
+                status.events.push_back(LogEntry::Marker {
+                    timestamp: now,
+                    message: message.clone(),
+                });
+                status.trim_events();
+            }
+        }
+    }
+
+    /// Forgets silences that have ended.
+    pub fn purge_expired_silences(&mut self, now: u64) {
+        self.silenced_groups.retain(|_, until| *until > now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DisplaySettings, PingMode};
+
+    fn state_with_host(address: &str) -> AppState {
+        let mut state = AppState::default();
+        state.hosts.push(HostInfo {
+            name: "Test".to_string(),
+            address: address.to_string(),
+            mode: PingMode::Fast,
+            display: DisplaySettings::default(),
+            packet_size: 16,
+            random_padding: false,
+            log_to_file: false,
+            log_file_path: String::new(),
+            is_stopped: false,
+            ..Default::default()
+        });
+        let mut status = HostStatus::default();
+        status.add_sample(10.0, true);
+        status.events.push_back(LogEntry::Marker {
+            timestamp: 1,
+            message: "evidence".to_string(),
+        });
+        state.statuses.insert(address.to_string(), status);
+        state
+    }
+
+    #[test]
+    fn test_delete_without_retention() {
+        let mut state = state_with_host("1.1.1.1");
+        state.delete_host("1.1.1.1", None, 1000);
+        assert!(state.hosts.is_empty());
+        assert!(state.statuses.is_empty());
+        assert!(state.deleted_hosts.is_empty());
+    }
+
+    #[test]
+    fn test_delete_with_retention_and_restore() {
+        let mut state = state_with_host("1.1.1.1");
+        state.delete_host("1.1.1.1", Some(7), 1000);
+        assert!(state.hosts.is_empty());
+        assert_eq!(state.deleted_hosts.len(), 1);
+        assert_eq!(state.deleted_hosts[0].purge_at, 1000 + 7 * 86_400);
+        assert_eq!(state.deleted_hosts[0].snapshot.sent, 1);
+
+        assert!(state.restore_deleted_host("1.1.1.1"));
+        assert_eq!(state.hosts.len(), 1);
+        assert!(state.deleted_hosts.is_empty());
+        assert_eq!(state.statuses["1.1.1.1"].events.len(), 1);
+    }
+
+    #[test]
+    fn test_restore_refused_when_address_is_monitored_again() {
+        let mut state = state_with_host("1.1.1.1");
+        state.delete_host("1.1.1.1", Some(7), 1000);
+        state.hosts.push(state.deleted_hosts[0].host.clone());
+        assert!(!state.restore_deleted_host("1.1.1.1"));
+        assert_eq!(state.deleted_hosts.len(), 1);
+    }
+
+    #[test]
+    fn test_purge_expired_and_purge_now() {
+        let mut state = state_with_host("1.1.1.1");
+        state.hosts.push(HostInfo {
+            address: "2.2.2.2".to_string(),
+            ..state.hosts[0].clone()
+        });
+        state.delete_host("1.1.1.1", Some(1), 0);
+        state.delete_host("2.2.2.2", Some(30), 0);
+
+        state.purge_expired_deleted_hosts(86_400);
+        assert_eq!(state.deleted_hosts.len(), 1);
+        assert_eq!(state.deleted_hosts[0].host.address, "2.2.2.2");
+
+        state.purge_deleted_host("2.2.2.2");
+        assert!(state.deleted_hosts.is_empty());
+    }
+
+    #[test]
+    fn test_group_silence_expires() {
+        let mut state = state_with_host("1.1.1.1");
+        state.hosts[0].group = "rack-3".to_string();
+        assert!(!state.is_host_silenced("1.1.1.1", 100));
+
+        state.set_group_silence("rack-3", Some(100 + 7_200), 100);
+        assert_eq!(state.silenced_until("rack-3", 100), Some(7_300));
+        assert!(state.is_host_silenced("1.1.1.1", 7_299));
+        assert!(!state.is_host_silenced("1.1.1.1", 7_300));
+        assert!(matches!(
+            state.statuses["1.1.1.1"].events.back(),
+            Some(LogEntry::Marker { message, .. }) if message.starts_with("Alerts of group rack-3 silenced")
+        ));
+
+        state.purge_expired_silences(7_300);
+        assert!(state.silenced_groups.is_empty());
+
+        state.set_group_silence("rack-3", Some(200), 100);
+        state.set_group_silence("rack-3", None, 150);
+        assert!(!state.is_host_silenced("1.1.1.1", 150));
+    }
+
+    #[test]
+    fn test_matching_profile() {
+        let mut state = AppState {
+            profiles: vec![String::new(), "Office".to_string(), "Lab".to_string()],
+            ..Default::default()
+        };
+        let network = |ssid: &str| ProfileNetwork {
+            ssid: ssid.to_string(),
+            ..Default::default()
+        };
+        state
+            .profile_networks
+            .insert("Office".to_string(), network("Corp"));
+        state
+            .profile_networks
+            .insert("Lab".to_string(), network("Corp"));
+        assert_eq!(state.matching_profile(), None);
+
+        state.network = Some(NetworkInfo {
+            ssid: Some("Corp".to_string()),
+            ..Default::default()
+        });
+        // The first profile in the list wins
+        assert_eq!(state.matching_profile().as_deref(), Some("Office"));
+        state.network = Some(NetworkInfo::default());
+        assert_eq!(state.matching_profile(), None);
+    }
 }
@@ -0,0 +1,102 @@
+//! Outages of a host as discrete events, kept after the streak counters move on.
+
+use crate::constants::MAX_OUTAGES_PER_HOST;
+use std::collections::VecDeque;
+
+/// A finished outage: from the first lost probe to the first answer after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutageEvent {
+    /// Unix timestamp of the first lost probe.
+    pub start: u64,
+    /// Unix timestamp of the first answer after the outage.
+    pub end: u64,
+    /// Probes lost during the outage.
+    pub lost: u32,
+}
+
+impl OutageEvent {
+    pub fn duration(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+/// The outages of a host, oldest first, and the one in progress.
+#[derive(Debug, Clone, Default)]
+pub struct OutageLog {
+    current: Option<(u64, u32)>,
+    finished: VecDeque<OutageEvent>,
+}
+
+impl OutageLog {
+    /// Starts an outage at `start` that already lost `lost` probes.
+    pub fn begin(&mut self, start: u64, lost: u32) {
+        self.current = Some((start, lost));
+    }
+
+    /// Counts a probe lost during the outage in progress.
+    pub fn add_lost(&mut self) {
+        if let Some((_, lost)) = &mut self.current {
+            *lost += 1;
+        }
+    }
+
+    /// Ends the outage in progress at `end` and returns it.
+    pub fn end(&mut self, end: u64) -> Option<OutageEvent> {
+        let (start, lost) = self.current.take()?;
+        let event = OutageEvent { start, end, lost };
+        self.finished.push_back(event);
+        while self.finished.len() > MAX_OUTAGES_PER_HOST {
+            self.finished.pop_front();
+        }
+        Some(event)
+    }
+
+    /// Start and lost probes of the outage in progress.
+    pub fn current(&self) -> Option<(u64, u32)> {
+        self.current
+    }
+
+    /// Finished outages, oldest first.
+    pub fn finished(&self) -> &VecDeque<OutageEvent> {
+        &self.finished
+    }
+
+    pub fn clear(&mut self) {
+        self.current = None;
+        self.finished.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outage_log() {
+        let mut log = OutageLog::default();
+        log.add_lost();
+        assert_eq!(log.end(10), None);
+
+        log.begin(100, 3);
+        log.add_lost();
+        assert_eq!(log.current(), Some((100, 4)));
+        let event = log.end(105).unwrap();
+        assert_eq!(
+            event,
+            OutageEvent {
+                start: 100,
+                end: 105,
+                lost: 4
+            }
+        );
+        assert_eq!(event.duration(), 5);
+        assert_eq!(log.current(), None);
+
+        for i in 0..MAX_OUTAGES_PER_HOST as u64 {
+            log.begin(200 + i * 10, 3);
+            log.end(205 + i * 10);
+        }
+        assert_eq!(log.finished().len(), MAX_OUTAGES_PER_HOST);
+        assert_eq!(log.finished()[0].start, 200);
+    }
+}
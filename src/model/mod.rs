@@ -1,5 +1,42 @@
+pub mod actions;
+pub mod alerts;
 pub mod app_state;
+pub mod events;
+pub mod rollup;
+pub mod schedule;
+pub mod settings;
+pub mod sla;
+pub mod snapshot;
+pub mod snmp;
+pub mod stats;
 pub mod status;
+pub mod theme;
+pub mod thresholds;
+pub mod window;
 
-pub use app_state::AppState;
-pub use status::{DisplaySettings, HostInfo, HostStatus, LogEntry, PingMode};
+pub use actions::{ActionEvent, ActionRecord};
+pub use alerts::{ActiveAlert, AlertRule};
+pub use app_state::{
+    Annotation, AppState, Connectivity, DeletedHost, DhcpLeaseStatus, NetworkInfo, ProfileNetwork,
+    PublicIpStatus, RemoteAgentStatus, SessionBaseline, SharedListStatus,
+};
+pub use events::{OutageEvent, OutageLog};
+pub use rollup::{RollupStats, Rollups};
+pub use schedule::{QuietRange, QuietSchedule};
+pub use settings::{
+    AgentSettings, ApiSettings, AppSettings, CorrelationSettings, DashboardSettings,
+    DhcpNamesSettings, EmailSettings, EventSound, HostLayout, HostSort, MqttSettings, PlotSettings,
+    ProxyMode, ProxySettings, RecentFile, RecentFileKind, RemoteAgent, SmtpSecurity, SoundEvent,
+    SoundSettings, SoundTheme, StreamApiSettings, WebhookPreset, WebhookTarget,
+};
+pub use sla::{SlaMetric, SlaSettings, SlaTarget, SlaWindow};
+pub use snapshot::StatsSnapshot;
+pub use snmp::{SnmpCounters, SnmpSettings, SnmpStatus};
+pub use stats::SortedWindow;
+pub use status::{
+    AddressFamily, DisplaySettings, DualStackStatus, HostInfo, HostStatus, HttpMethod, LogEntry,
+    PingMode, Sample, SampleOutcome,
+};
+pub use theme::{ColorTheme, StatusColors};
+pub use thresholds::{QoePreset, Thresholds};
+pub use window::WindowStats;
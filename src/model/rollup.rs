@@ -0,0 +1,322 @@
+use super::status::calculate_percentile;
+use crate::constants::{MAX_HOUR_ROLLUPS, MAX_MINUTE_ROLLUPS};
+use std::collections::VecDeque;
+
+/// Statistics of all samples within one closed time bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RollupStats {
+    /// Unix timestamp of the start of the bucket.
+    pub start: u64,
+    pub sent: u32,
+    pub lost: u32,
+    /// RTT statistics in ms over successful samples (0.0 if there were none).
+    pub min: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub p95: f64,
+    pub max: f64,
+}
+
+impl RollupStats {
+    /// Packet loss in percent.
+    pub fn loss_pct(&self) -> f64 {
+        if self.sent == 0 {
+            0.0
+        } else {
+            self.lost as f64 / self.sent as f64 * 100.0
+        }
+    }
+
+    /// Merges consecutive buckets into one.
+    ///
+    /// Counts, min, max and mean are exact; median and P95 are approximated by the
+    /// sample-weighted median and P95 of the bucket values.
+    pub fn combine(buckets: &[RollupStats]) -> RollupStats {
+        let Some(first) = buckets.first() else {
+            return RollupStats::default();
+        };
+        let mut out = RollupStats {
+            start: first.start,
+            ..Default::default()
+        };
+        let mut medians = Vec::new();
+        let mut p95s = Vec::new();
+        let mut received_total = 0u64;
+        let mut sum = 0.0;
+        for b in buckets {
+            out.sent += b.sent;
+            out.lost += b.lost;
+            let received = b.sent - b.lost;
+            if received == 0 {
+                continue;
+            }
+            out.min = if received_total == 0 {
+                b.min
+            } else {
+                out.min.min(b.min)
+            };
+            out.max = out.max.max(b.max);
+            sum += b.mean * received as f64;
+            received_total += received as u64;
+            medians.extend(std::iter::repeat_n(b.median, received as usize));
+            p95s.extend(std::iter::repeat_n(b.p95, received as usize));
+        }
+        if received_total > 0 {
+            out.mean = sum / received_total as f64;
+            out.median = calculate_percentile(medians, 50.0);
+            out.p95 = calculate_percentile(p95s, 95.0);
+        }
+        out
+    }
+}
+
+/// Samples of the bucket that is still being filled.
+#[derive(Debug, Clone, Default)]
+struct OpenBucket {
+    start: u64,
+    sent: u32,
+    rtts: Vec<f64>,
+}
+
+impl OpenBucket {
+    fn close(&self) -> RollupStats {
+        let mut stats = RollupStats {
+            start: self.start,
+            sent: self.sent,
+            lost: self.sent - self.rtts.len() as u32,
+            ..Default::default()
+        };
+        if !self.rtts.is_empty() {
+            stats.min = self.rtts.iter().copied().fold(f64::INFINITY, f64::min);
+            stats.max = self.rtts.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            stats.mean = self.rtts.iter().sum::<f64>() / self.rtts.len() as f64;
+            stats.median = calculate_percentile(self.rtts.iter().copied(), 50.0);
+            stats.p95 = calculate_percentile(self.rtts.iter().copied(), 95.0);
+        }
+        stats
+    }
+}
+
+/// Rolls samples up into fixed-length buckets, keeping a bounded number of closed ones.
+#[derive(Debug, Clone)]
+struct Series {
+    length: u64,
+    max_buckets: usize,
+    open: Option<OpenBucket>,
+    closed: VecDeque<RollupStats>,
+}
+
+impl Series {
+    fn new(length: u64, max_buckets: usize) -> Self {
+        Self {
+            length,
+            max_buckets,
+            open: None,
+            closed: VecDeque::new(),
+        }
+    }
+
+    fn add(&mut self, timestamp: u64, rtt: Option<f64>) {
+        let start = timestamp - timestamp % self.length;
+        match &mut self.open {
+            Some(bucket) if bucket.start == start => {}
+            // Late samples from a closed bucket are counted in the current one
+            Some(bucket) if start < bucket.start => {}
+            _ => {
+                if let Some(bucket) = self.open.take() {
+                    self.closed.push_back(bucket.close());
+                    while self.closed.len() > self.max_buckets {
+                        self.closed.pop_front();
+                    }
+                }
+                self.open = Some(OpenBucket {
+                    start,
+                    ..Default::default()
+                });
+            }
+        }
+        let bucket = self.open.as_mut().expect("bucket opened above");
+        bucket.sent += 1;
+        if let Some(rtt) = rtt {
+            bucket.rtts.push(rtt);
+        }
+    }
+}
+
+/// Per-minute and per-hour statistics maintained as samples arrive.
+///
+/// Long-window views and reports read the closed buckets instead of
+/// reprocessing the raw sample stream.
+#[derive(Debug, Clone)]
+pub struct Rollups {
+    minutes: Series,
+    hours: Series,
+}
+
+impl Default for Rollups {
+    fn default() -> Self {
+        Self {
+            minutes: Series::new(60, MAX_MINUTE_ROLLUPS),
+            hours: Series::new(3_600, MAX_HOUR_ROLLUPS),
+        }
+    }
+}
+
+impl Rollups {
+    /// Adds a sample; `rtt` is `None` for a lost packet.
+    pub fn add(&mut self, timestamp: u64, rtt: Option<f64>) {
+        self.minutes.add(timestamp, rtt);
+        self.hours.add(timestamp, rtt);
+    }
+
+    /// Closed per-minute buckets, oldest first.
+    pub fn minutes(&self) -> &VecDeque<RollupStats> {
+        &self.minutes.closed
+    }
+
+    /// Closed per-hour buckets, oldest first.
+    pub fn hours(&self) -> &VecDeque<RollupStats> {
+        &self.hours.closed
+    }
+
+    /// Statistics of the minute and hour currently being filled.
+    pub fn current(&self) -> (Option<RollupStats>, Option<RollupStats>) {
+        (
+            self.minutes.open.as_ref().map(OpenBucket::close),
+            self.hours.open.as_ref().map(OpenBucket::close),
+        )
+    }
+
+    /// Combined statistics of all closed buckets starting at or after `from`,
+    /// using hours where they are complete and minutes for the rest.
+    pub fn summary_since(&self, from: u64) -> RollupStats {
+        let hours: Vec<RollupStats> = self
+            .hours()
+            .iter()
+            .filter(|b| b.start >= from)
+            .copied()
+            .collect();
+        // Minutes cover the partial hour before the first and after the last complete hour
+        let covered = hours
+            .first()
+            .zip(hours.last())
+            .map(|(first, last)| first.start..last.start + 3_600);
+        let mut buckets: Vec<RollupStats> = self
+            .minutes()
+            .iter()
+            .filter(|b| b.start >= from && !covered.as_ref().is_some_and(|c| c.contains(&b.start)))
+            .copied()
+            .collect();
+        buckets.extend(hours);
+        buckets.sort_by_key(|b| b.start);
+        RollupStats::combine(&buckets)
+    }
+
+    /// Closed buckets for a long-term plot of at most `max_bars` bars, oldest first.
+    ///
+    /// Uses minutes while they still cover all collected data and hours after that,
+    /// merging consecutive buckets so that the whole range fits.
+    pub fn long_term(&self, max_bars: usize) -> Vec<RollupStats> {
+        let minutes_cover_all = match (self.minutes().front(), self.hours().front()) {
+            (Some(m), Some(h)) => m.start <= h.start,
+            (_, None) => true,
+            (None, Some(_)) => false,
+        };
+        let buckets = if minutes_cover_all {
+            self.minutes()
+        } else {
+            self.hours()
+        };
+        let per_bar = buckets.len().div_ceil(max_bars.max(1)).max(1);
+        let buckets: Vec<RollupStats> = buckets.iter().copied().collect();
+        // Align merged groups to the newest bucket so the last bar is always complete
+        let mut bars: Vec<RollupStats> =
+            buckets.rchunks(per_bar).map(RollupStats::combine).collect();
+        bars.reverse();
+        bars
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minute_buckets_close_on_boundary() {
+        let mut r = Rollups::default();
+        for (ts, rtt) in [
+            (60, Some(10.0)),
+            (75, Some(30.0)),
+            (90, None),
+            (119, Some(20.0)),
+        ] {
+            r.add(ts, rtt);
+        }
+        assert!(r.minutes().is_empty());
+        r.add(120, Some(5.0));
+
+        let m = r.minutes()[0];
+        assert_eq!(m.start, 60);
+        assert_eq!((m.sent, m.lost), (4, 1));
+        assert_eq!((m.min, m.mean, m.median, m.max), (10.0, 20.0, 20.0, 30.0));
+        assert_eq!(m.loss_pct(), 25.0);
+        assert_eq!(r.current().0.map(|c| c.sent), Some(1));
+        assert!(r.hours().is_empty());
+    }
+
+    #[test]
+    fn test_summary_combines_hours_and_minutes() {
+        let mut r = Rollups::default();
+        // One full hour at 10 ms, then ten minutes at 20 ms with one loss per minute
+        for ts in (0..3_600).step_by(10) {
+            r.add(ts, Some(10.0));
+        }
+        for ts in (3_600..4_260).step_by(10) {
+            r.add(ts, if ts % 60 == 0 { None } else { Some(20.0) });
+        }
+        assert_eq!(r.hours().len(), 1);
+        assert_eq!(r.minutes().len(), 70);
+
+        let s = r.summary_since(0);
+        assert_eq!(s.sent, 360 + 60);
+        assert_eq!(s.lost, 10);
+        assert_eq!((s.min, s.max), (10.0, 20.0));
+        assert!((s.mean - (360.0 * 10.0 + 50.0 * 20.0) / 410.0).abs() < 1e-9);
+        assert_eq!(s.median, 10.0);
+        assert_eq!(RollupStats::combine(&[]), RollupStats::default());
+    }
+
+    #[test]
+    fn test_long_term_switches_to_hours() {
+        let mut r = Rollups::default();
+        for minute in 0..10u64 {
+            r.add(minute * 60, Some(minute as f64));
+        }
+        let bars = r.long_term(4);
+        // Nine closed minutes merged by three
+        assert_eq!(bars.len(), 3);
+        assert_eq!((bars[0].start, bars[0].sent), (0, 3));
+        assert_eq!((bars[2].min, bars[2].max), (6.0, 8.0));
+
+        // Two days of data: minutes no longer reach back to the first hour
+        for minute in 10..(2 * MAX_MINUTE_ROLLUPS as u64) {
+            r.add(minute * 60, None);
+        }
+        let bars = r.long_term(300);
+        assert_eq!(bars.len(), r.hours().len());
+        assert_eq!(bars[0].start, 0);
+    }
+
+    #[test]
+    fn test_bucket_count_is_bounded() {
+        let mut r = Rollups::default();
+        for minute in 0..(MAX_MINUTE_ROLLUPS as u64 + 10) {
+            r.add(minute * 60, Some(1.0));
+        }
+        assert_eq!(r.minutes().len(), MAX_MINUTE_ROLLUPS);
+    }
+}
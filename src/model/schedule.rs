@@ -0,0 +1,135 @@
+use chrono::{Datelike, Timelike};
+use serde::{Deserialize, Serialize};
+use tr::tr;
+
+/// Short names of the weekdays, Monday first.
+pub fn weekday_labels() -> [String; 7] {
+    [
+        tr!("Mo"),
+        tr!("Tu"),
+        tr!("We"),
+        tr!("Th"),
+        tr!("Fr"),
+        tr!("Sa"),
+        tr!("Su"),
+    ]
+}
+
+/// A time range on selected weekdays, in minutes since local midnight.
+///
+/// A range that wraps around midnight (e.g. Fr 22:00-07:00) continues into the
+/// morning of the following day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuietRange {
+    /// Days on which the range starts, Monday first.
+    #[serde(default = "all_days")]
+    pub days: [bool; 7],
+    #[serde(default)]
+    pub start_min: u16,
+    #[serde(default)]
+    pub end_min: u16,
+}
+
+impl Default for QuietRange {
+    fn default() -> Self {
+        Self {
+            days: all_days(),
+            start_min: 22 * 60,
+            end_min: 7 * 60,
+        }
+    }
+}
+
+fn all_days() -> [bool; 7] {
+    [true; 7]
+}
+
+impl QuietRange {
+    /// Whether `minute` of `weekday` (0 = Monday) falls into the range.
+    pub fn contains(&self, weekday: usize, minute: u16) -> bool {
+        let (start, end) = (self.start_min, self.end_min);
+        if start < end {
+            self.days[weekday % 7] && (start..end).contains(&minute)
+        } else if start > end {
+            (self.days[weekday % 7] && minute >= start)
+                || (self.days[(weekday + 6) % 7] && minute < end)
+        } else {
+            false
+        }
+    }
+}
+
+/// Weekly schedule during which alerts are not delivered; statistics still accumulate.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct QuietSchedule {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub ranges: Vec<QuietRange>,
+}
+
+impl QuietSchedule {
+    /// Whether `minute` of `weekday` (0 = Monday) is quiet.
+    pub fn contains(&self, weekday: usize, minute: u16) -> bool {
+        self.enabled && self.ranges.iter().any(|r| r.contains(weekday, minute))
+    }
+
+    /// Whether the Unix timestamp `ts` is quiet in local time.
+    pub fn contains_at(&self, ts: u64) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let Some(time) = chrono::DateTime::from_timestamp(ts as i64, 0) else {
+            return false;
+        };
+        let local = time.with_timezone(&chrono::Local);
+        self.contains(
+            local.weekday().num_days_from_monday() as usize,
+            (local.hour() * 60 + local.minute()) as u16,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_wraps_into_next_day() {
+        // Friday night until Saturday morning
+        let mut days = [false; 7];
+        days[4] = true;
+        let range = QuietRange {
+            days,
+            start_min: 22 * 60,
+            end_min: 7 * 60,
+        };
+        assert!(range.contains(4, 23 * 60));
+        assert!(range.contains(5, 3 * 60));
+        assert!(!range.contains(5, 23 * 60));
+        assert!(!range.contains(4, 3 * 60));
+        assert!(!range.contains(5, 7 * 60));
+
+        // Sunday night wraps into Monday
+        days = [false; 7];
+        days[6] = true;
+        let range = QuietRange { days, ..range };
+        assert!(range.contains(0, 6 * 60));
+    }
+
+    #[test]
+    fn test_schedule() {
+        let mut schedule = QuietSchedule {
+            enabled: false,
+            ranges: vec![QuietRange {
+                start_min: 12 * 60,
+                end_min: 13 * 60,
+                ..Default::default()
+            }],
+        };
+        assert!(!schedule.contains(2, 12 * 60 + 30));
+        schedule.enabled = true;
+        assert!(schedule.contains(2, 12 * 60 + 30));
+        assert!(!schedule.contains(2, 13 * 60));
+    }
+}
@@ -0,0 +1,1084 @@
+use super::schedule::{QuietRange, QuietSchedule};
+use super::sla::SlaSettings;
+use super::theme::{ColorTheme, StatusColors};
+use super::thresholds::Thresholds;
+use crate::constants::{
+    CAPTIVE_PORTAL_CHECK_URL, DEFAULT_AGENT_PORT, DEFAULT_API_PORT, DEFAULT_DAEMON_PORT,
+    DEFAULT_DASHBOARD_SCROLL_SEC, DEFAULT_DASHBOARD_ZOOM, DEFAULT_DELETED_HOST_RETENTION_DAYS,
+    DEFAULT_DHCP_LEASE_FILE, DEFAULT_DNS_REFRESH_SEC, DEFAULT_EMAIL_DOWN_MIN,
+    DEFAULT_JITTER_BUFFER_MS, DEFAULT_MAX_CONCURRENT_PROBES, DEFAULT_MQTT_INTERVAL_SEC,
+    DEFAULT_MQTT_PORT, DEFAULT_MQTT_TLS_PORT, DEFAULT_PROBE_BUDGET_PER_SEC,
+    DEFAULT_PROBE_RATE_PER_INTERFACE, DEFAULT_PUBLIC_IP_SERVICES, DEFAULT_SOUND_ESCALATION_MIN,
+    DEFAULT_STREAM_PORT, HISTORY_WINDOW_SIZE, MAX_RECENT_FILES,
+};
+use serde::{Deserialize, Serialize};
+use tr::tr;
+
+/// Application-wide settings, persisted together with the host list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppSettings {
+    /// Language code of the user interface, e.g. "uk"; empty follows the system locale.
+    #[serde(default)]
+    pub language: String,
+    /// Keep the history of deleted hosts for a while instead of discarding it at once.
+    #[serde(default = "default_true")]
+    pub retain_deleted_hosts: bool,
+    /// Number of days the history of a deleted host is kept before it is purged.
+    #[serde(default = "default_retention_days")]
+    pub deleted_host_retention_days: u32,
+    /// Periodically query GitHub for new releases. Disable for offline deployments.
+    #[serde(default = "default_true")]
+    pub check_for_updates: bool,
+    /// Unix timestamp of the last automatic update check.
+    #[serde(default)]
+    pub last_update_check: u64,
+    /// Proxy used for outgoing HTTP(S) requests.
+    #[serde(default)]
+    pub proxy: ProxySettings,
+    /// Periodically check whether the network is behind a captive portal.
+    #[serde(default = "default_true")]
+    pub captive_portal_check: bool,
+    /// Endpoint expected to answer with HTTP 204 when the Internet is reachable.
+    #[serde(default = "default_captive_portal_url")]
+    pub captive_portal_url: String,
+    /// Periodically look up the public IP addresses and note their changes on the timeline.
+    #[serde(default)]
+    pub public_ip_check: bool,
+    /// Services telling the public IP, one per line: HTTPS URLs answering with the
+    /// address in plain text, or `stun:host:port` STUN servers.
+    #[serde(default = "default_public_ip_services")]
+    pub public_ip_services: String,
+    /// Size of the simulated codec jitter buffer in the log window (ms).
+    #[serde(default = "default_jitter_buffer_ms")]
+    pub jitter_buffer_ms: u32,
+    /// Record every ping result on disk so history survives restarts.
+    #[serde(default)]
+    pub persistent_history: bool,
+    /// Show a desktop notification when an alert is raised.
+    #[serde(default = "default_true")]
+    pub desktop_notifications: bool,
+    /// Show the number of hosts that are down in a badge on the window icon.
+    #[serde(default = "default_true")]
+    pub taskbar_badge: bool,
+    /// Point out hosts that became slower or lossier than in the previous session.
+    #[serde(default = "default_true")]
+    pub compare_sessions: bool,
+    /// Sounds played on incidents and alerts.
+    #[serde(default)]
+    pub sounds: SoundSettings,
+    /// Save a screenshot of the main window when an incident opens or closes.
+    #[serde(default)]
+    pub incident_screenshots: bool,
+    /// Kiosk mode: hosts and settings cannot be changed until unlocked.
+    #[serde(default)]
+    pub kiosk_locked: bool,
+    /// Salted hash of the kiosk unlock password; empty unlocks without a password.
+    #[serde(default)]
+    pub kiosk_password: String,
+    /// Host list file shared by a team (e.g. on a network drive or in a git checkout).
+    /// Its hosts are merged read-only into the local list. Empty disables team mode.
+    #[serde(default)]
+    pub shared_host_list: String,
+    /// Probes per second the configured hosts may need before a warning is shown; 0 disables it.
+    #[serde(default = "default_probe_budget")]
+    pub probe_budget_per_sec: u32,
+    /// Probes allowed in flight at once, network scans included; 0 for no limit.
+    #[serde(default = "default_max_concurrent_probes")]
+    pub max_concurrent_probes: u32,
+    /// Probes per second allowed on each network interface; 0 for no limit.
+    #[serde(default = "default_probe_rate_per_interface")]
+    pub probe_rate_per_interface: u32,
+    /// Host names are resolved again after this many seconds; 0 resolves them only once.
+    #[serde(default = "default_dns_refresh")]
+    pub dns_refresh_sec: u64,
+    /// Size and density of the history plot in each host row.
+    #[serde(default)]
+    pub plot: PlotSettings,
+    /// HTTP endpoints notified when a host goes down, recovers or raises an alert.
+    #[serde(default)]
+    pub webhooks: Vec<WebhookTarget>,
+    /// Email sent when a host stays down for a while.
+    #[serde(default)]
+    pub email: EmailSettings,
+    /// Weekly times during which no host delivers alerts.
+    #[serde(default)]
+    pub quiet_schedule: QuietSchedule,
+    /// Trace id tagging probes, log lines and exports.
+    #[serde(default)]
+    pub correlation: CorrelationSettings,
+    /// Service levels promised by the ISP, compared with the persisted history.
+    #[serde(default)]
+    pub sla: SlaSettings,
+    /// Recently used files, most recent first (File → Recent files).
+    #[serde(default)]
+    pub recent_files: Vec<RecentFile>,
+    /// Statistics stream for the terminal client.
+    #[serde(default)]
+    pub stream_api: StreamApiSettings,
+    /// Localhost port of the background daemon (egui_pinger_daemon).
+    #[serde(default = "default_daemon_port")]
+    pub daemon_port: u16,
+    /// Serving the results of this instance to remote viewers.
+    #[serde(default)]
+    pub agent: AgentSettings,
+    /// Remote agents whose hosts are shown alongside the local ones.
+    #[serde(default)]
+    pub remote_agents: Vec<RemoteAgent>,
+    /// JSON API over HTTP for scripts and home automation.
+    #[serde(default)]
+    pub api: ApiSettings,
+    /// Host states and latency published to an MQTT broker.
+    #[serde(default)]
+    pub mqtt: MqttSettings,
+    /// Host names taken from the DHCP server's lease file.
+    #[serde(default)]
+    pub dhcp_names: DhcpNamesSettings,
+    /// Order of the hosts within their group in the host list.
+    #[serde(default)]
+    pub host_sort: HostSort,
+    /// Reverse the order of `host_sort`.
+    #[serde(default)]
+    pub host_sort_reversed: bool,
+    #[serde(default)]
+    pub host_layout: HostLayout,
+    /// The first-run setup was offered, so it is not offered again.
+    #[serde(default)]
+    pub setup_offered: bool,
+    /// Quality thresholds given to new hosts; also color what belongs to no host.
+    #[serde(default)]
+    pub default_thresholds: Thresholds,
+    /// Colors of the latency ranges and of lost probes.
+    #[serde(default)]
+    pub color_theme: ColorTheme,
+    /// Colors of the custom color theme.
+    #[serde(default)]
+    pub custom_colors: StatusColors,
+    /// Wall dashboard shown on a TV.
+    #[serde(default)]
+    pub dashboard: DashboardSettings,
+}
+
+impl AppSettings {
+    /// Deserializes the settings and moves the settings of older versions to their
+    /// current place.
+    pub fn deserialize_migrated<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let mut settings = Self::deserialize(deserializer)?;
+        // The daily sound quiet hours became a range of the weekly quiet schedule
+        if let Some(quiet) = settings.sounds.legacy_quiet_hours.take()
+            && quiet.enabled
+            && quiet.start_min != quiet.end_min
+        {
+            settings.quiet_schedule.enabled = true;
+            settings.quiet_schedule.ranges.push(QuietRange {
+                start_min: quiet.start_min,
+                end_min: quiet.end_min,
+                ..Default::default()
+            });
+        }
+        Ok(settings)
+    }
+
+    /// Puts `file` at the top of the recent files, dropping an older entry of the same
+    /// file and the oldest beyond MAX_RECENT_FILES.
+    pub fn remember_file(&mut self, file: RecentFile) {
+        self.recent_files
+            .retain(|f| !(f.kind == file.kind && f.path == file.path));
+        self.recent_files.insert(0, file);
+        self.recent_files.truncate(MAX_RECENT_FILES);
+    }
+}
+
+/// What a recent file was used as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecentFileKind {
+    /// A ping log imported or followed into a host.
+    Capture,
+    /// A shared host list file.
+    HostList,
+}
+
+/// A file opened from the menu bar's recent files list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentFile {
+    pub path: String,
+    pub kind: RecentFileKind,
+    /// Host a capture was imported into.
+    #[serde(default)]
+    pub address: String,
+}
+
+/// Event that can play a sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SoundEvent {
+    /// A host went down (an incident opened).
+    Down,
+    /// A host is reachable again (an incident closed).
+    Recovery,
+    /// An alert rule was raised, e.g. high loss or latency.
+    Degraded,
+    /// A host is still down after the escalation delay.
+    Escalation,
+}
+
+impl SoundEvent {
+    pub const ALL: [SoundEvent; 4] = [
+        SoundEvent::Down,
+        SoundEvent::Recovery,
+        SoundEvent::Degraded,
+        SoundEvent::Escalation,
+    ];
+
+    pub fn label(&self) -> String {
+        match self {
+            SoundEvent::Down => tr!("Host down"),
+            SoundEvent::Recovery => tr!("Recovery"),
+            SoundEvent::Degraded => tr!("Degraded (alert raised)"),
+            SoundEvent::Escalation => tr!("Escalation (still down)"),
+        }
+    }
+}
+
+/// Order of the host list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HostSort {
+    /// The order set by drag and drop.
+    #[default]
+    Manual,
+    Name,
+    /// Lowest last RTT first.
+    Latency,
+    /// Lowest loss first.
+    Loss,
+    /// Best MOS first.
+    Mos,
+    /// Down hosts first, then up ones, then stopped and not yet probed ones.
+    Status,
+}
+
+impl HostSort {
+    pub const ALL: [HostSort; 6] = [
+        HostSort::Manual,
+        HostSort::Name,
+        HostSort::Latency,
+        HostSort::Loss,
+        HostSort::Mos,
+        HostSort::Status,
+    ];
+
+    pub fn label(&self) -> String {
+        match self {
+            HostSort::Manual => tr!("Manual"),
+            HostSort::Name => tr!("Name"),
+            HostSort::Latency => tr!("Latency"),
+            HostSort::Loss => tr!("Loss"),
+            HostSort::Mos => tr!("MOS"),
+            HostSort::Status => tr!("Status"),
+        }
+    }
+}
+
+/// Arrangement of the hosts in the main window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HostLayout {
+    /// One row per host with all statistics.
+    #[default]
+    List,
+    /// Cards with name, status and a sparkline in as many columns as fit.
+    Grid,
+}
+
+/// Built-in set of synthesized sounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SoundTheme {
+    /// Short beeps.
+    #[default]
+    Classic,
+    /// Quiet chimes for shared offices.
+    Soft,
+    /// Loud sirens for NOC screens.
+    Alarm,
+}
+
+impl SoundTheme {
+    pub const ALL: [SoundTheme; 3] = [SoundTheme::Classic, SoundTheme::Soft, SoundTheme::Alarm];
+
+    pub fn label(&self) -> String {
+        match self {
+            SoundTheme::Classic => tr!("Classic"),
+            SoundTheme::Soft => tr!("Soft"),
+            SoundTheme::Alarm => tr!("Alarm"),
+        }
+    }
+}
+
+/// Sound configuration of a single event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventSound {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// WAV file played instead of the theme sound; empty uses the theme.
+    #[serde(default)]
+    pub custom_file: String,
+}
+
+impl Default for EventSound {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            custom_file: String::new(),
+        }
+    }
+}
+
+/// Daily quiet hours of the sound settings of older versions, in minutes since local
+/// midnight. They are moved into [`AppSettings::quiet_schedule`] on load.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct LegacyQuietHours {
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default = "default_quiet_start")]
+    start_min: u16,
+    #[serde(default = "default_quiet_end")]
+    end_min: u16,
+}
+
+/// Sounds played on incidents and alerts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SoundSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub theme: SoundTheme,
+    #[serde(default)]
+    pub down: EventSound,
+    #[serde(default)]
+    pub recovery: EventSound,
+    #[serde(default)]
+    pub degraded: EventSound,
+    #[serde(default)]
+    pub escalation: EventSound,
+    /// Minutes a host must stay down before the escalation sound plays; 0 disables it.
+    #[serde(default = "default_escalation_min")]
+    pub escalation_min: u32,
+    #[serde(default, rename = "quiet_hours", skip_serializing)]
+    legacy_quiet_hours: Option<LegacyQuietHours>,
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            theme: SoundTheme::default(),
+            down: EventSound::default(),
+            recovery: EventSound::default(),
+            degraded: EventSound::default(),
+            escalation: EventSound::default(),
+            escalation_min: default_escalation_min(),
+            legacy_quiet_hours: None,
+        }
+    }
+}
+
+impl SoundSettings {
+    pub fn event(&self, event: SoundEvent) -> &EventSound {
+        match event {
+            SoundEvent::Down => &self.down,
+            SoundEvent::Recovery => &self.recovery,
+            SoundEvent::Degraded => &self.degraded,
+            SoundEvent::Escalation => &self.escalation,
+        }
+    }
+
+    pub fn event_mut(&mut self, event: SoundEvent) -> &mut EventSound {
+        match event {
+            SoundEvent::Down => &mut self.down,
+            SoundEvent::Recovery => &mut self.recovery,
+            SoundEvent::Degraded => &mut self.degraded,
+            SoundEvent::Escalation => &mut self.escalation,
+        }
+    }
+}
+
+/// How outgoing HTTP(S) requests reach the Internet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ProxyMode {
+    /// Use the proxy configured in the environment or the operating system.
+    #[default]
+    System,
+    /// Connect directly, ignoring any system proxy.
+    Direct,
+    /// Use the proxy configured below.
+    Manual,
+}
+
+/// An HTTP endpoint that receives a JSON POST on host state transitions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookTarget {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub url: String,
+    /// JSON body with `{placeholders}` filled in by `logic::webhook::render`.
+    #[serde(default = "default_webhook_template")]
+    pub template: String,
+}
+
+impl Default for WebhookTarget {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            name: String::new(),
+            url: String::new(),
+            template: default_webhook_template(),
+        }
+    }
+}
+
+impl WebhookTarget {
+    /// Name shown in the settings and in the log; the URL if no name is set.
+    pub fn display_name(&self) -> &str {
+        if self.name.is_empty() {
+            &self.url
+        } else {
+            &self.name
+        }
+    }
+}
+
+fn default_webhook_template() -> String {
+    WebhookPreset::Generic.template().to_string()
+}
+
+/// Body templates for common chat services.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookPreset {
+    Slack,
+    Discord,
+    Telegram,
+    Generic,
+}
+
+impl WebhookPreset {
+    pub const ALL: [WebhookPreset; 4] = [
+        WebhookPreset::Slack,
+        WebhookPreset::Discord,
+        WebhookPreset::Telegram,
+        WebhookPreset::Generic,
+    ];
+
+    pub fn label(&self) -> String {
+        match self {
+            WebhookPreset::Slack => "Slack".to_string(),
+            WebhookPreset::Discord => "Discord".to_string(),
+            WebhookPreset::Telegram => "Telegram".to_string(),
+            WebhookPreset::Generic => tr!("Generic JSON"),
+        }
+    }
+
+    /// Body template; Telegram also needs the chat ID filled in.
+    pub fn template(&self) -> &'static str {
+        match self {
+            WebhookPreset::Slack => r#"{"text": "{message}"}"#,
+            WebhookPreset::Discord => r#"{"content": "{message}"}"#,
+            WebhookPreset::Telegram => r#"{"chat_id": "CHAT_ID", "text": "{message}"}"#,
+            WebhookPreset::Generic => {
+                r#"{"event": "{event}", "host": "{host}", "address": "{address}", "message": "{message}", "time": "{time}", "loss": {loss}, "rtt": {rtt}}"#
+            }
+        }
+    }
+}
+
+/// Dimensions of the history plot in the host rows.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlotSettings {
+    /// Width in points.
+    #[serde(default = "default_plot_width")]
+    pub width: f32,
+    /// Height in points.
+    #[serde(default = "default_plot_height")]
+    pub height: f32,
+    /// Number of most recent samples (or buckets of the long-term plot) shown.
+    #[serde(default = "default_plot_samples")]
+    pub samples: usize,
+    /// Width of a bar relative to its slot; below 1.0 leaves gaps between bars.
+    #[serde(default = "default_plot_bar_width")]
+    pub bar_width: f64,
+    /// Minutes of wall-clock time shown by every mini-chart, so hosts pinged at
+    /// different intervals line up; 0 shows the last `samples` of each host instead.
+    #[serde(default)]
+    pub time_span_min: u32,
+}
+
+impl Default for PlotSettings {
+    fn default() -> Self {
+        Self {
+            width: default_plot_width(),
+            height: default_plot_height(),
+            samples: default_plot_samples(),
+            bar_width: default_plot_bar_width(),
+            time_span_min: 0,
+        }
+    }
+}
+
+fn default_plot_width() -> f32 {
+    300.0
+}
+
+fn default_plot_height() -> f32 {
+    30.0
+}
+
+fn default_plot_samples() -> usize {
+    HISTORY_WINDOW_SIZE
+}
+
+fn default_plot_bar_width() -> f64 {
+    1.0
+}
+
+/// How the connection to the SMTP server is secured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with `STARTTLS` (usually port 587).
+    #[default]
+    StartTls,
+    /// TLS from the start (usually port 465).
+    Tls,
+    /// Unencrypted, e.g. for a relay on the local network (usually port 25).
+    None,
+}
+
+impl SmtpSecurity {
+    pub const ALL: [SmtpSecurity; 3] = [
+        SmtpSecurity::StartTls,
+        SmtpSecurity::Tls,
+        SmtpSecurity::None,
+    ];
+
+    pub fn label(&self) -> String {
+        match self {
+            SmtpSecurity::StartTls => "STARTTLS".to_string(),
+            SmtpSecurity::Tls => "TLS".to_string(),
+            SmtpSecurity::None => tr!("None"),
+        }
+    }
+
+    pub fn default_port(&self) -> u16 {
+        match self {
+            SmtpSecurity::StartTls => 587,
+            SmtpSecurity::Tls => 465,
+            SmtpSecurity::None => 25,
+        }
+    }
+}
+
+/// SMTP server and recipients of the alert emails.
+///
+/// The password is not part of the configuration; it is kept in the keyring of the
+/// operating system under [`EmailSettings::keyring_account`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmailSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub server: String,
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    /// Login name; empty sends without authentication.
+    #[serde(default)]
+    pub username: String,
+    /// Sender address; the login name if empty.
+    #[serde(default)]
+    pub from: String,
+    /// Comma-separated recipient addresses.
+    #[serde(default)]
+    pub recipients: String,
+    /// An email is sent once a host has been down this long (minutes).
+    #[serde(default = "default_email_down_min")]
+    pub down_min: u32,
+}
+
+impl Default for EmailSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            server: String::new(),
+            port: default_smtp_port(),
+            security: SmtpSecurity::default(),
+            username: String::new(),
+            from: String::new(),
+            recipients: String::new(),
+            down_min: DEFAULT_EMAIL_DOWN_MIN,
+        }
+    }
+}
+
+impl EmailSettings {
+    /// Account name of the password in the keyring.
+    pub fn keyring_account(&self) -> String {
+        format!("smtp:{}@{}", self.username, self.server)
+    }
+
+    /// The recipient addresses, without blanks.
+    pub fn recipient_list(&self) -> Vec<String> {
+        self.recipients
+            .split([',', ';'])
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Sender address: `from`, or the login name.
+    pub fn sender(&self) -> &str {
+        if self.from.trim().is_empty() {
+            self.username.trim()
+        } else {
+            self.from.trim()
+        }
+    }
+}
+
+fn default_smtp_port() -> u16 {
+    SmtpSecurity::default().default_port()
+}
+
+fn default_email_down_min() -> u32 {
+    DEFAULT_EMAIL_DOWN_MIN
+}
+
+/// W3C trace-context style tagging of probes for correlation with packet captures
+/// and the logs of other tools.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct CorrelationSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 32 lowercase hex digits, see `logic::trace_context`.
+    #[serde(default)]
+    pub trace_id: String,
+}
+
+impl CorrelationSettings {
+    /// The trace id, if tagging is enabled and the id is valid.
+    pub fn active_trace_id(&self) -> Option<&str> {
+        (self.enabled && Self::is_valid_trace_id(&self.trace_id)).then_some(self.trace_id.as_str())
+    }
+
+    /// Whether `id` is 32 lowercase hex digits, not all zero (as required by W3C trace context).
+    pub fn is_valid_trace_id(id: &str) -> bool {
+        id.len() == 32
+            && id.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+            && id.bytes().any(|b| b != b'0')
+    }
+}
+
+/// Read-only statistics stream on localhost, viewed remotely through SSH port forwarding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamApiSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_stream_port")]
+    pub port: u16,
+}
+
+impl Default for StreamApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_STREAM_PORT,
+        }
+    }
+}
+
+/// Results of this instance served on the network to other egui_pinger windows.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_agent_port")]
+    pub port: u16,
+    /// Shared secret the viewers prove they know.
+    #[serde(default)]
+    pub token: String,
+}
+
+impl Default for AgentSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_AGENT_PORT,
+            token: String::new(),
+        }
+    }
+}
+
+/// JSON API over HTTP with the statistics, the history and adding and removing hosts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_api_port")]
+    pub port: u16,
+    /// Listen on all interfaces instead of localhost only.
+    #[serde(default)]
+    pub listen_all: bool,
+    /// Bearer token every request has to carry; empty for none.
+    #[serde(default)]
+    pub token: String,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_API_PORT,
+            listen_all: false,
+            token: String::new(),
+        }
+    }
+}
+
+/// Publishing of the host states and latency to an MQTT broker, e.g. for Home Assistant.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MqttSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Host name or address of the broker.
+    #[serde(default)]
+    pub broker: String,
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+    /// Connect with TLS, verified against the public certificate authorities.
+    #[serde(default)]
+    pub tls: bool,
+    /// Login name; empty connects without authentication. Needs `tls`.
+    #[serde(default)]
+    pub username: String,
+    /// Topics are `<prefix>/status` and `<prefix>/<host>/state`.
+    #[serde(default = "default_mqtt_topic_prefix")]
+    pub topic_prefix: String,
+    /// Publish Home Assistant discovery payloads so the hosts appear as devices.
+    #[serde(default = "default_true")]
+    pub discovery: bool,
+    #[serde(default = "default_mqtt_discovery_prefix")]
+    pub discovery_prefix: String,
+    /// Seconds between publications of the metrics; up/down changes go out at once.
+    #[serde(default = "default_mqtt_interval_sec")]
+    pub interval_sec: u32,
+}
+
+impl Default for MqttSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            broker: String::new(),
+            port: DEFAULT_MQTT_PORT,
+            tls: false,
+            username: String::new(),
+            topic_prefix: default_mqtt_topic_prefix(),
+            discovery: true,
+            discovery_prefix: default_mqtt_discovery_prefix(),
+            interval_sec: DEFAULT_MQTT_INTERVAL_SEC,
+        }
+    }
+}
+
+impl MqttSettings {
+    /// Account name of the password in the keyring.
+    pub fn keyring_account(&self) -> String {
+        format!("mqtt:{}@{}", self.username, self.broker)
+    }
+
+    /// Usual port of a broker with or without TLS.
+    pub fn default_port(tls: bool) -> u16 {
+        if tls {
+            DEFAULT_MQTT_TLS_PORT
+        } else {
+            DEFAULT_MQTT_PORT
+        }
+    }
+}
+
+/// Another egui_pinger whose hosts are shown alongside the local ones.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct RemoteAgent {
+    /// Name the hosts of the agent are tagged with, e.g. "office".
+    pub name: String,
+    /// `host:port` of the agent.
+    pub address: String,
+    #[serde(default)]
+    pub token: String,
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Full-screen view without editing controls for a wall display.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DashboardSettings {
+    /// Zoom factor of the user interface, to read it from a distance.
+    #[serde(default = "default_dashboard_zoom")]
+    pub zoom: f32,
+    /// Seconds each group is shown before scrolling to the next; 0 does not scroll.
+    #[serde(default = "default_dashboard_scroll_sec")]
+    pub scroll_sec: u32,
+}
+
+impl Default for DashboardSettings {
+    fn default() -> Self {
+        Self {
+            zoom: DEFAULT_DASHBOARD_ZOOM,
+            scroll_sec: DEFAULT_DASHBOARD_SCROLL_SEC,
+        }
+    }
+}
+
+/// Labels hosts with the names devices gave the DHCP server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DhcpNamesSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Lease file of dnsmasq, ISC dhcpd or Kea.
+    #[serde(default = "default_dhcp_lease_file")]
+    pub lease_file: String,
+}
+
+impl Default for DhcpNamesSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lease_file: default_dhcp_lease_file(),
+        }
+    }
+}
+
+/// Proxy configuration for HTTP-based features (update checks, HTTP probes, webhooks).
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ProxySettings {
+    #[serde(default)]
+    pub mode: ProxyMode,
+    /// Proxy address for `Manual` mode, e.g. `http://proxy.corp:3128` or `socks5://host:1080`.
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub username: String,
+    /// Stored in plain text together with the rest of the configuration.
+    #[serde(default)]
+    pub password: String,
+    /// Comma-separated hosts or domain suffixes that bypass the manual proxy.
+    #[serde(default)]
+    pub no_proxy: String,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_dashboard_zoom() -> f32 {
+    DEFAULT_DASHBOARD_ZOOM
+}
+
+fn default_dashboard_scroll_sec() -> u32 {
+    DEFAULT_DASHBOARD_SCROLL_SEC
+}
+
+fn default_stream_port() -> u16 {
+    DEFAULT_STREAM_PORT
+}
+
+fn default_daemon_port() -> u16 {
+    DEFAULT_DAEMON_PORT
+}
+
+fn default_agent_port() -> u16 {
+    DEFAULT_AGENT_PORT
+}
+
+fn default_api_port() -> u16 {
+    DEFAULT_API_PORT
+}
+
+fn default_mqtt_port() -> u16 {
+    DEFAULT_MQTT_PORT
+}
+
+fn default_mqtt_topic_prefix() -> String {
+    "egui_pinger".to_string()
+}
+
+fn default_mqtt_discovery_prefix() -> String {
+    "homeassistant".to_string()
+}
+
+fn default_mqtt_interval_sec() -> u32 {
+    DEFAULT_MQTT_INTERVAL_SEC
+}
+
+fn default_dhcp_lease_file() -> String {
+    DEFAULT_DHCP_LEASE_FILE.to_string()
+}
+
+fn default_quiet_start() -> u16 {
+    22 * 60
+}
+
+fn default_quiet_end() -> u16 {
+    7 * 60
+}
+
+fn default_escalation_min() -> u32 {
+    DEFAULT_SOUND_ESCALATION_MIN
+}
+
+fn default_captive_portal_url() -> String {
+    CAPTIVE_PORTAL_CHECK_URL.to_string()
+}
+
+fn default_public_ip_services() -> String {
+    DEFAULT_PUBLIC_IP_SERVICES.join("\n")
+}
+
+fn default_jitter_buffer_ms() -> u32 {
+    DEFAULT_JITTER_BUFFER_MS
+}
+
+fn default_probe_budget() -> u32 {
+    DEFAULT_PROBE_BUDGET_PER_SEC
+}
+
+fn default_max_concurrent_probes() -> u32 {
+    DEFAULT_MAX_CONCURRENT_PROBES
+}
+
+fn default_probe_rate_per_interface() -> u32 {
+    DEFAULT_PROBE_RATE_PER_INTERFACE
+}
+
+fn default_dns_refresh() -> u64 {
+    DEFAULT_DNS_REFRESH_SEC
+}
+
+fn default_retention_days() -> u32 {
+    DEFAULT_DELETED_HOST_RETENTION_DAYS
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            language: String::new(),
+            retain_deleted_hosts: true,
+            deleted_host_retention_days: DEFAULT_DELETED_HOST_RETENTION_DAYS,
+            check_for_updates: true,
+            last_update_check: 0,
+            proxy: ProxySettings::default(),
+            captive_portal_check: true,
+            captive_portal_url: default_captive_portal_url(),
+            public_ip_check: false,
+            public_ip_services: default_public_ip_services(),
+            jitter_buffer_ms: DEFAULT_JITTER_BUFFER_MS,
+            persistent_history: false,
+            desktop_notifications: true,
+            taskbar_badge: true,
+            compare_sessions: true,
+            sounds: SoundSettings::default(),
+            incident_screenshots: false,
+            kiosk_locked: false,
+            kiosk_password: String::new(),
+            shared_host_list: String::new(),
+            probe_budget_per_sec: DEFAULT_PROBE_BUDGET_PER_SEC,
+            max_concurrent_probes: DEFAULT_MAX_CONCURRENT_PROBES,
+            probe_rate_per_interface: DEFAULT_PROBE_RATE_PER_INTERFACE,
+            dns_refresh_sec: DEFAULT_DNS_REFRESH_SEC,
+            plot: PlotSettings::default(),
+            webhooks: Vec::new(),
+            email: EmailSettings::default(),
+            quiet_schedule: QuietSchedule::default(),
+            correlation: CorrelationSettings::default(),
+            sla: SlaSettings::default(),
+            recent_files: Vec::new(),
+            stream_api: StreamApiSettings::default(),
+            daemon_port: DEFAULT_DAEMON_PORT,
+            agent: AgentSettings::default(),
+            remote_agents: Vec::new(),
+            api: ApiSettings::default(),
+            mqtt: MqttSettings::default(),
+            dhcp_names: DhcpNamesSettings::default(),
+            host_sort: HostSort::default(),
+            host_sort_reversed: false,
+            host_layout: HostLayout::default(),
+            setup_offered: false,
+            default_thresholds: Thresholds::default(),
+            color_theme: ColorTheme::default(),
+            custom_colors: StatusColors::default(),
+            dashboard: DashboardSettings::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_files() {
+        let mut settings = AppSettings::default();
+        let capture = |path: &str| RecentFile {
+            path: path.to_string(),
+            kind: RecentFileKind::Capture,
+            address: "10.0.0.1".to_string(),
+        };
+        for i in 0..MAX_RECENT_FILES + 2 {
+            settings.remember_file(capture(&format!("/tmp/{}.log", i)));
+        }
+        assert_eq!(settings.recent_files.len(), MAX_RECENT_FILES);
+
+        // Reopening moves a file to the top; the same path as host list is a separate entry
+        settings.remember_file(capture("/tmp/5.log"));
+        settings.remember_file(RecentFile {
+            kind: RecentFileKind::HostList,
+            ..capture("/tmp/5.log")
+        });
+        let paths: Vec<_> = settings
+            .recent_files
+            .iter()
+            .map(|f| (f.path.as_str(), f.kind))
+            .collect();
+        assert_eq!(paths[0], ("/tmp/5.log", RecentFileKind::HostList));
+        assert_eq!(paths[1], ("/tmp/5.log", RecentFileKind::Capture));
+        assert_eq!(paths.iter().filter(|(p, _)| *p == "/tmp/5.log").count(), 2);
+        assert_eq!(settings.recent_files.len(), MAX_RECENT_FILES);
+    }
+
+    #[test]
+    fn test_sound_quiet_hours_move_into_schedule() {
+        let load = |json: &str| {
+            AppSettings::deserialize_migrated(&mut serde_json::Deserializer::from_str(json))
+                .unwrap()
+        };
+        let settings =
+            load(r#"{"sounds":{"quiet_hours":{"enabled":true,"start_min":1320,"end_min":420}}}"#);
+        assert!(settings.quiet_schedule.enabled);
+        assert_eq!(
+            settings.quiet_schedule.ranges,
+            vec![QuietRange {
+                days: [true; 7],
+                start_min: 1320,
+                end_min: 420,
+            }]
+        );
+        assert!(settings.quiet_schedule.contains(3, 23 * 60));
+        let saved = serde_json::to_string(&settings).unwrap();
+        assert!(!saved.contains("quiet_hours"));
+
+        // Disabled quiet hours are dropped
+        let settings = load(r#"{"sounds":{"quiet_hours":{"enabled":false}}}"#);
+        assert_eq!(settings.quiet_schedule, QuietSchedule::default());
+    }
+}
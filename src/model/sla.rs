@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use tr::tr;
+
+/// Measured value a service level target limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SlaMetric {
+    /// Mean RTT of the answered probes, in ms.
+    #[default]
+    MeanLatency,
+    /// Share of lost probes, in percent.
+    Loss,
+}
+
+impl SlaMetric {
+    pub const ALL: [SlaMetric; 2] = [SlaMetric::MeanLatency, SlaMetric::Loss];
+
+    pub fn label(&self) -> String {
+        match self {
+            SlaMetric::MeanLatency => tr!("Mean latency"),
+            SlaMetric::Loss => tr!("Packet loss"),
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            SlaMetric::MeanLatency => "ms",
+            SlaMetric::Loss => "%",
+        }
+    }
+}
+
+/// Period over which a target is evaluated; each period is a separate measurement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SlaWindow {
+    FiveMinutes,
+    #[default]
+    Hour,
+    /// A local calendar day.
+    Day,
+    /// A local calendar week, starting on Monday.
+    Week,
+    /// A local calendar month.
+    Month,
+}
+
+impl SlaWindow {
+    pub const ALL: [SlaWindow; 5] = [
+        SlaWindow::FiveMinutes,
+        SlaWindow::Hour,
+        SlaWindow::Day,
+        SlaWindow::Week,
+        SlaWindow::Month,
+    ];
+
+    pub fn label(&self) -> String {
+        match self {
+            SlaWindow::FiveMinutes => tr!("per 5 minutes"),
+            SlaWindow::Hour => tr!("hourly"),
+            SlaWindow::Day => tr!("daily"),
+            SlaWindow::Week => tr!("weekly"),
+            SlaWindow::Month => tr!("monthly"),
+        }
+    }
+}
+
+/// A contractual limit, e.g. "≤ 30 ms to the first hop, hourly".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlaTarget {
+    /// Address of the measured host.
+    #[serde(default)]
+    pub address: String,
+    #[serde(default)]
+    pub metric: SlaMetric,
+    /// Highest value that still meets the target.
+    #[serde(default)]
+    pub limit: f64,
+    #[serde(default)]
+    pub window: SlaWindow,
+}
+
+impl Default for SlaTarget {
+    fn default() -> Self {
+        Self {
+            address: String::new(),
+            metric: SlaMetric::MeanLatency,
+            limit: 30.0,
+            window: SlaWindow::Hour,
+        }
+    }
+}
+
+impl SlaTarget {
+    /// E.g. "Mean latency ≤ 30.0 ms hourly".
+    pub fn describe(&self) -> String {
+        format!(
+            "{} ≤ {:.1} {} {}",
+            self.metric.label(),
+            self.limit,
+            self.metric.unit(),
+            self.window.label()
+        )
+    }
+}
+
+/// The ISP contract the history is compared against.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct SlaSettings {
+    /// Name of the provider, used in the complaint summary.
+    #[serde(default)]
+    pub isp: String,
+    /// Contract or customer number, used in the complaint summary.
+    #[serde(default)]
+    pub contract: String,
+    #[serde(default)]
+    pub targets: Vec<SlaTarget>,
+}
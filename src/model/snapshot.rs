@@ -0,0 +1,85 @@
+use super::app_state::AppState;
+use super::status::{HostInfo, HostStatus};
+use serde::{Deserialize, Serialize};
+
+/// Point-in-time statistics of a single monitored host.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostSnapshot {
+    pub name: String,
+    pub address: String,
+    pub is_stopped: bool,
+    pub alive: bool,
+    pub dns_error: bool,
+    pub latency: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub p95: f64,
+    pub rtp_jitter: f64,
+    pub mos: f64,
+    pub availability: f64,
+    pub sent: u32,
+    pub lost: u32,
+    pub failure_point: Option<String>,
+    pub traceroute_path: Vec<String>,
+}
+
+/// Point-in-time statistics of all monitored hosts.
+///
+/// Snapshots are plain serializable data, decoupled from the live `HostStatus`
+/// so they can be written to disk or attached to bug reports.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatsSnapshot {
+    pub timestamp: u64,
+    pub hosts: Vec<HostSnapshot>,
+    /// Correlation trace id active when the snapshot was taken.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+}
+
+impl HostSnapshot {
+    /// Captures the current statistics of a single host.
+    pub fn capture(host: &HostInfo, s: &HostStatus) -> Self {
+        Self {
+            name: host.name.clone(),
+            address: host.address.clone(),
+            is_stopped: host.is_stopped,
+            alive: s.alive,
+            dns_error: s.dns_error,
+            latency: s.latency,
+            mean: s.mean,
+            median: s.median,
+            p95: s.p95,
+            rtp_jitter: s.rtp_jitter,
+            mos: s.mos,
+            availability: s.availability,
+            sent: s.sent,
+            lost: s.lost,
+            failure_point: s.failure_point.clone(),
+            traceroute_path: s.traceroute_path.clone(),
+        }
+    }
+}
+
+impl StatsSnapshot {
+    /// Captures the current statistics of every configured host.
+    pub fn capture(state: &AppState) -> Self {
+        let default_status = HostStatus::default();
+        let hosts = state
+            .hosts
+            .iter()
+            .map(|h| {
+                HostSnapshot::capture(h, state.statuses.get(&h.address).unwrap_or(&default_status))
+            })
+            .collect();
+
+        Self {
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            hosts,
+            trace_id: state
+                .settings
+                .correlation
+                .active_trace_id()
+                .map(str::to_string),
+        }
+    }
+}
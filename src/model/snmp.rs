@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+
+/// SNMP v2c polling of the interface counters of a host, e.g. a router, to see
+/// whether loss goes along with interface errors.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnmpSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Community string; sent in plain text as SNMP v2c does.
+    #[serde(default = "default_community")]
+    pub community: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// Comma-separated interface indexes (ifIndex) to watch; empty watches all.
+    #[serde(default)]
+    pub interfaces: String,
+}
+
+fn default_community() -> String {
+    "public".to_string()
+}
+
+fn default_port() -> u16 {
+    161
+}
+
+impl Default for SnmpSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            community: default_community(),
+            port: default_port(),
+            interfaces: String::new(),
+        }
+    }
+}
+
+impl SnmpSettings {
+    /// The watched interface indexes; empty for all. Invalid entries are skipped.
+    pub fn interface_indexes(&self) -> Vec<u32> {
+        self.interfaces
+            .split(',')
+            .filter_map(|i| i.trim().parse().ok())
+            .collect()
+    }
+}
+
+/// Error and discard counters summed over the watched interfaces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SnmpCounters {
+    pub in_errors: u64,
+    pub out_errors: u64,
+    pub in_discards: u64,
+    pub out_discards: u64,
+}
+
+impl SnmpCounters {
+    /// Increase of the counters since `earlier`. The 32-bit counters of IF-MIB
+    /// wrap around; a counter that went down is taken as wrapped once.
+    pub fn increase_since(&self, earlier: &SnmpCounters) -> SnmpCounters {
+        let delta = |now: u64, before: u64| {
+            if now >= before {
+                now - before
+            } else {
+                (now + (1 << 32)).saturating_sub(before)
+            }
+        };
+        SnmpCounters {
+            in_errors: delta(self.in_errors, earlier.in_errors),
+            out_errors: delta(self.out_errors, earlier.out_errors),
+            in_discards: delta(self.in_discards, earlier.in_discards),
+            out_discards: delta(self.out_discards, earlier.out_discards),
+        }
+    }
+
+    pub fn errors(&self) -> u64 {
+        self.in_errors + self.out_errors
+    }
+
+    pub fn discards(&self) -> u64 {
+        self.in_discards + self.out_discards
+    }
+}
+
+/// Result of the SNMP polling of a host.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SnmpStatus {
+    /// Counters of the last successful poll.
+    pub counters: Option<SnmpCounters>,
+    /// Increase between the last two successful polls.
+    pub increase: Option<SnmpCounters>,
+    /// Timestamp of the last poll.
+    pub polled_at: Option<u64>,
+    /// Why the last poll failed.
+    pub error: Option<String>,
+}
+
+impl SnmpStatus {
+    /// Records the outcome of a poll at `now`.
+    pub fn record(&mut self, result: Result<SnmpCounters, String>, now: u64) {
+        self.polled_at = Some(now);
+        match result {
+            Ok(counters) => {
+                self.increase = self.counters.map(|before| counters.increase_since(&before));
+                self.counters = Some(counters);
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snmp_status_records_increase() {
+        let counters = |in_errors, out_discards| SnmpCounters {
+            in_errors,
+            out_discards,
+            ..Default::default()
+        };
+        let mut status = SnmpStatus::default();
+        status.record(Ok(counters(10, 4_294_967_290)), 60);
+        assert_eq!(status.increase, None);
+
+        status.record(Ok(counters(13, 5)), 120);
+        let increase = status.increase.unwrap();
+        assert_eq!((increase.errors(), increase.discards()), (3, 11));
+
+        status.record(Err("timeout".to_string()), 180);
+        assert_eq!(status.error.as_deref(), Some("timeout"));
+        assert_eq!(status.counters, Some(counters(13, 5)));
+    }
+
+    #[test]
+    fn test_interface_indexes() {
+        let settings = SnmpSettings {
+            interfaces: " 2, 10,x,".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(settings.interface_indexes(), vec![2, 10]);
+        assert!(SnmpSettings::default().interface_indexes().is_empty());
+    }
+}
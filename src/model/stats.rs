@@ -0,0 +1,99 @@
+//! Order statistics of a sliding window, updated as values enter and leave it so
+//! that percentiles need no sort of the whole window on every sample.
+
+/// Values of a window kept in ascending order.
+///
+/// Adding or removing a value is a binary search and a shift of the values after
+/// it; percentiles are then read directly and match
+/// [`super::status::calculate_percentile`] over the same values exactly.
+#[derive(Debug, Clone, Default)]
+pub struct SortedWindow {
+    sorted: Vec<f64>,
+}
+
+impl SortedWindow {
+    pub fn insert(&mut self, value: f64) {
+        let at = self.sorted.partition_point(|v| v.total_cmp(&value).is_lt());
+        self.sorted.insert(at, value);
+    }
+
+    /// Removes one occurrence of `value`; false if the window has none.
+    pub fn remove(&mut self, value: f64) -> bool {
+        match self.sorted.binary_search_by(|v| v.total_cmp(&value)) {
+            Ok(at) => {
+                self.sorted.remove(at);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sorted.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sorted.is_empty()
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.sorted.first().copied()
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.sorted.last().copied()
+    }
+
+    /// Percentile (0–100) with linear interpolation, 0 for an empty window.
+    pub fn percentile(&self, percentile: f64) -> f64 {
+        percentile_of_sorted(&self.sorted, percentile)
+    }
+}
+
+/// Percentile (0–100) of ascending `sorted` values with linear interpolation
+/// between the closest ranks; 0 when there are no values.
+pub fn percentile_of_sorted(sorted: &[f64], percentile: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let pos = (percentile / 100.0) * (sorted.len() - 1) as f64;
+    let base = pos.floor() as usize;
+    let fract = pos - base as f64;
+    if base + 1 < sorted.len() {
+        sorted[base] + fract * (sorted[base + 1] - sorted[base])
+    } else {
+        sorted[base]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::status::calculate_percentile;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_sorted_window_matches_sorting() {
+        let values = [12.0, 3.5, 40.0, 3.5, 7.25, 99.0, 0.5, 12.0, 18.0, 5.0, 61.0];
+        let mut window = SortedWindow::default();
+        let mut recent = VecDeque::new();
+        for value in values.iter().cycle().take(50) {
+            window.insert(*value);
+            recent.push_back(*value);
+            if recent.len() > 6 {
+                let old = recent.pop_front().unwrap();
+                assert!(window.remove(old));
+            }
+            for p in [0.0, 25.0, 50.0, 95.0, 100.0] {
+                assert_eq!(
+                    window.percentile(p),
+                    calculate_percentile(recent.iter().copied(), p)
+                );
+            }
+            assert_eq!(window.min(), recent.iter().copied().reduce(f64::min));
+            assert_eq!(window.max(), recent.iter().copied().reduce(f64::max));
+        }
+        assert!(!window.remove(1000.0));
+        assert_eq!(window.len(), 6);
+    }
+}
@@ -1,7 +1,20 @@
-use crate::constants::{HISTORY_WINDOW_SIZE, MAX_EVENTS_PER_HOST, RTP_JITTER_SMOOTHING_DIVISOR};
+use super::alerts::{ActiveAlert, AlertRule};
+use super::events::OutageLog;
+use super::rollup::Rollups;
+use super::schedule::QuietSchedule;
+use super::snmp::{SnmpSettings, SnmpStatus};
+use super::stats::percentile_of_sorted;
+use super::thresholds::Thresholds;
+use super::window::WindowStats;
+use crate::constants::{
+    EWMA_ALPHA, HISTORY_WINDOW_SIZE, MAX_EVENTS_PER_HOST, RTP_JITTER_SMOOTHING_DIVISOR,
+    STATE_CONFIRMATION_STREAK, TREND_WINDOW,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashSet, VecDeque};
 use std::io::Write;
+use std::net::IpAddr;
+use std::time::Duration;
 use tr::tr;
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -16,6 +29,30 @@ pub enum PingMode {
 }
 
 impl PingMode {
+    /// All modes, from the shortest interval to the longest.
+    pub const ALL: [PingMode; 7] = [
+        PingMode::VeryFast,
+        PingMode::Fast,
+        PingMode::NotFast,
+        PingMode::Normal,
+        PingMode::NotSlow,
+        PingMode::Slow,
+        PingMode::VerySlow,
+    ];
+
+    /// Nominal interval between pings in seconds (without jitter).
+    pub fn interval_secs(&self) -> u64 {
+        match self {
+            PingMode::VeryFast => 1,
+            PingMode::Fast => 2,
+            PingMode::NotFast => 5,
+            PingMode::Normal => 10,
+            PingMode::NotSlow => 30,
+            PingMode::Slow => 60,
+            PingMode::VerySlow => 300,
+        }
+    }
+
     pub fn label(&self) -> String {
         match self {
             PingMode::VeryFast => tr!("Very fast (1s)"),
@@ -29,6 +66,23 @@ impl PingMode {
     }
 }
 
+/// Request method of an HTTP(S) probe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HttpMethod {
+    #[default]
+    Get,
+    Head,
+}
+
+impl HttpMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HttpMethod::Get => "GET",
+            HttpMethod::Head => "HEAD",
+        }
+    }
+}
+
 /// Represents a single event in a host's history log.
 ///
 /// Log entries are used for both persistent logging to files and displaying
@@ -71,9 +125,18 @@ pub enum LogEntry {
         streak: u32,    // number of packets in current state
         downtime_sec: Option<u64>,
         node: Option<String>,
+        /// Likely cause of a loss, from the root-cause rules.
+        #[serde(default)]
+        hint: Option<String>,
     },
     /// Custom message marker
     Marker { timestamp: u64, message: String },
+    /// Alert rule raised or cleared
+    Alert {
+        timestamp: u64,
+        raised: bool, // true = raised, false = cleared
+        message: String,
+    },
 }
 
 impl LogEntry {
@@ -189,9 +252,14 @@ impl LogEntry {
                 streak,
                 downtime_sec,
                 node,
+                hint,
                 ..
             } => {
                 if *is_break {
+                    let hint = hint
+                        .as_ref()
+                        .map(|h| format!(". {}: {}", tr!("Hint"), h))
+                        .unwrap_or_default();
                     if let Some(n) = node {
                         let translated_node = if n == "Local Interface" {
                             tr!("Local Interface").to_string()
@@ -199,17 +267,19 @@ impl LogEntry {
                             n.clone()
                         };
                         format!(
-                            "! [{}] {} ({})",
+                            "! [{}] {} ({}){}",
                             ts,
                             tr!("Connectivity lost at {node}").replace("{node}", &translated_node),
-                            tr!("{n} requests without answer").replace("{n}", &streak.to_string())
+                            tr!("{n} requests without answer").replace("{n}", &streak.to_string()),
+                            hint
                         )
                     } else {
                         format!(
-                            "! [{}] {} ({})",
+                            "! [{}] {} ({}){}",
                             ts,
                             tr!("Connectivity lost"),
-                            tr!("{n} requests without answer").replace("{n}", &streak.to_string())
+                            tr!("{n} requests without answer").replace("{n}", &streak.to_string()),
+                            hint
                         )
                     }
                 } else {
@@ -235,6 +305,16 @@ impl LogEntry {
                 };
                 format!("=== {}: {} ===", message, dts)
             }
+            LogEntry::Alert {
+                raised, message, ..
+            } => {
+                let what = if *raised {
+                    tr!("Alert raised")
+                } else {
+                    tr!("Alert cleared")
+                };
+                format!("! [{}] {}: {}", ts, what, message)
+            }
         }
     }
 
@@ -245,6 +325,7 @@ impl LogEntry {
             LogEntry::RouteUpdate { timestamp, .. } => *timestamp,
             LogEntry::Incident { timestamp, .. } => *timestamp,
             LogEntry::Marker { timestamp, .. } => *timestamp,
+            LogEntry::Alert { timestamp, .. } => *timestamp,
         }
     }
 }
@@ -283,6 +364,24 @@ pub struct DisplaySettings {
     pub show_min_max: bool,
     #[serde(default = "default_true")]
     pub show_loss: bool,
+    /// Exponentially weighted moving average of the RTT with a trend arrow.
+    #[serde(default = "default_false")]
+    pub show_ewma: bool,
+    /// Number and longest run of consecutive losses, and the burst ratio.
+    #[serde(default = "default_false")]
+    pub show_loss_bursts: bool,
+    /// How long the host has been continuously up or down.
+    #[serde(default = "default_false")]
+    pub show_uptime: bool,
+    /// Background bar showing how close the host is to its alert thresholds.
+    #[serde(default = "default_true")]
+    pub show_threshold_bar: bool,
+    /// Plot per-minute or per-hour statistics instead of the last samples.
+    #[serde(default = "default_false")]
+    pub long_term_plot: bool,
+    /// Plot the recent samples as a heatmap of RTT buckets instead of bars.
+    #[serde(default = "default_false")]
+    pub heatmap_plot: bool,
 }
 
 fn default_true() -> bool {
@@ -311,6 +410,12 @@ impl Default for DisplaySettings {
             show_p95: false,
             show_min_max: false,
             show_loss: true,
+            show_ewma: false,
+            show_loss_bursts: false,
+            show_uptime: false,
+            show_threshold_bar: true,
+            long_term_plot: false,
+            heatmap_plot: false,
         }
     }
 }
@@ -345,20 +450,148 @@ impl Default for LogFilter {
 pub struct HostInfo {
     pub name: String,
     pub address: String,
+    /// Name of the collapsible group the host is shown in; empty for ungrouped hosts.
+    #[serde(default)]
+    pub group: String,
+    /// Free-text notes shown when hovering the host, e.g. where the device is.
+    #[serde(default)]
+    pub notes: String,
+    /// Labels such as "prod" or "vpn"; unlike the group a host can have several.
+    #[serde(default)]
+    pub tags: Vec<String>,
     #[serde(default = "default_ping_mode")]
     pub mode: PingMode,
+    /// Ping every second while the host is down, then return to `mode`.
+    #[serde(default)]
+    pub adaptive_interval: bool,
     #[serde(default)]
     pub display: DisplaySettings,
     #[serde(default = "default_packet_size")]
     pub packet_size: usize,
     #[serde(default = "default_false")]
     pub random_padding: bool,
+    /// IP TTL / hop limit of the probes; `None` keeps the system default.
+    #[serde(default)]
+    pub ttl: Option<u8>,
+    /// How long to wait for an ICMP reply before the probe counts as lost, in ms;
+    /// `None` keeps the default of one second.
+    #[serde(default)]
+    pub timeout_ms: Option<u32>,
+    /// Quick extra pings sent when a scheduled ping goes unanswered; if one of them
+    /// is answered, the loss is still counted but does not make the host down.
+    #[serde(default)]
+    pub confirm_probes: u8,
+    /// DSCP marking of the probes (0 = best effort, 46 = EF).
+    #[serde(default)]
+    pub dscp: u8,
     #[serde(default = "default_false")]
     pub log_to_file: bool,
     #[serde(default)]
     pub log_file_path: String,
     #[serde(default = "default_false")]
     pub is_stopped: bool,
+    #[serde(default)]
+    pub alert_rules: Vec<AlertRule>,
+    /// Expected DNS answer: an IP address or a regular expression matched against
+    /// the resolved addresses. Empty disables the check. Ignored for IP addresses.
+    #[serde(default)]
+    pub expected_dns_answer: String,
+    /// Statistics come from an imported or followed log file; the host is not pinged.
+    #[serde(default)]
+    pub passive: bool,
+    /// Request method used when the address is an HTTP(S) URL.
+    #[serde(default)]
+    pub http_method: HttpMethod,
+    /// Status codes counted as alive for HTTP(S) probes, e.g. "200,204" or "200-299".
+    /// Empty accepts 200-399.
+    #[serde(default)]
+    pub http_expected_status: String,
+    /// Datagram sent to a `udp://host:port` address, with `\xNN` escapes for
+    /// binary bytes. Empty sends an empty datagram.
+    #[serde(default)]
+    pub udp_payload: String,
+    /// SNMP polling of the interface error counters, for routers
+    #[serde(default)]
+    pub snmp: SnmpSettings,
+    /// Shell command run instead of pinging; exit code 0 means up and the first
+    /// number printed is the latency in ms. Empty pings the address.
+    #[serde(default)]
+    pub probe_command: String,
+    /// Shell command run when an incident opens; empty runs nothing.
+    #[serde(default)]
+    pub on_down_command: String,
+    /// Shell command run when the host answers again after an incident.
+    #[serde(default)]
+    pub on_up_command: String,
+    /// Comes from the shared host list file and is managed there, not locally.
+    #[serde(default)]
+    pub shared: bool,
+    /// Also ping the IPv4 and the IPv6 address of the host name separately.
+    #[serde(default)]
+    pub dual_stack: bool,
+    /// Values from which latency, jitter and loss are shown as degraded or bad.
+    #[serde(default)]
+    pub thresholds: Thresholds,
+    /// Weekly times during which this host delivers no alerts, besides the global ones.
+    #[serde(default)]
+    pub quiet_schedule: QuietSchedule,
+    /// Weekly windows of expected downtime, e.g. a backup reboot: being down then is
+    /// shown neutrally, left out of availability and the SLA check, and not alerted.
+    #[serde(default)]
+    pub maintenance: QuietSchedule,
+    /// Network interface the host is reached through, e.g. `wlan0`; while it is down
+    /// the probes pause instead of counting as lost. Empty for any interface.
+    #[serde(default)]
+    pub interface: String,
+    /// Address of the host this one is compared with, e.g. the same destination
+    /// without the VPN, to show the latency and loss it adds. Empty for none.
+    #[serde(default)]
+    pub paired_with: String,
+    /// Name of the remote agent that measures this host; empty for local hosts.
+    #[serde(default)]
+    pub agent: String,
+}
+
+impl Default for HostInfo {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            address: String::new(),
+            group: String::new(),
+            notes: String::new(),
+            tags: Vec::new(),
+            mode: default_ping_mode(),
+            display: DisplaySettings::default(),
+            packet_size: default_packet_size(),
+            random_padding: false,
+            ttl: None,
+            timeout_ms: None,
+            confirm_probes: 0,
+            dscp: 0,
+            log_to_file: false,
+            log_file_path: String::new(),
+            is_stopped: false,
+            alert_rules: Vec::new(),
+            expected_dns_answer: String::new(),
+            passive: false,
+            adaptive_interval: false,
+            http_method: HttpMethod::default(),
+            http_expected_status: String::new(),
+            udp_payload: String::new(),
+            snmp: SnmpSettings::default(),
+            probe_command: String::new(),
+            on_down_command: String::new(),
+            on_up_command: String::new(),
+            shared: false,
+            dual_stack: false,
+            thresholds: Thresholds::default(),
+            quiet_schedule: QuietSchedule::default(),
+            maintenance: QuietSchedule::default(),
+            interface: String::new(),
+            paired_with: String::new(),
+            agent: String::new(),
+        }
+    }
 }
 
 fn default_ping_mode() -> PingMode {
@@ -370,6 +603,14 @@ fn default_packet_size() -> usize {
 }
 
 impl HostInfo {
+    /// Clears the shell commands, for hosts from a source that must not run
+    /// commands on this machine.
+    pub fn clear_commands(&mut self) {
+        self.probe_command.clear();
+        self.on_down_command.clear();
+        self.on_up_command.clear();
+    }
+
     /// Appends formatted log lines to the host's log file (if logging is enabled).
     pub fn append_to_log(&self, lines: &[String]) {
         if !self.log_to_file || self.log_file_path.is_empty() {
@@ -386,8 +627,100 @@ impl HostInfo {
         }
     }
 
+    /// Normalizes a user-entered address: host names are case-insensitive, URL paths are not.
+    pub fn normalize_address(input: &str) -> String {
+        let address = input.trim();
+        if address.contains("://") {
+            address.to_string()
+        } else {
+            address.to_lowercase()
+        }
+    }
+
+    /// A copy of the host with another name and address, for adding similar hosts.
+    ///
+    /// What belongs to the original alone is not copied: its log file, the link to
+    /// an imported log and the membership in the shared host list.
+    pub fn duplicate(&self, name: String, address: String) -> HostInfo {
+        HostInfo {
+            name,
+            address,
+            log_to_file: false,
+            log_file_path: String::new(),
+            passive: false,
+            shared: false,
+            ..self.clone()
+        }
+    }
+
+    /// Splits a ping address of the form `destination@source` into the destination
+    /// and the interface name or local IP address the pings are sent from, so the
+    /// same destination can be watched over several links as separate hosts.
+    /// URLs are returned whole.
+    pub fn split_source(address: &str) -> (&str, Option<&str>) {
+        if address.contains("://") {
+            return (address, None);
+        }
+        match address.rsplit_once('@') {
+            Some((target, source)) if !target.is_empty() && !source.is_empty() => {
+                (target, Some(source))
+            }
+            _ => (address, None),
+        }
+    }
+
+    /// The destination pinged, without the `@source` of the address.
+    pub fn target(&self) -> &str {
+        Self::split_source(&self.address).0
+    }
+
+    /// Interface the host is bound to: the one set in the settings, otherwise the
+    /// interface name of an `@source` address. Empty for any interface.
+    pub fn bound_interface(&self) -> &str {
+        match self.interface.trim() {
+            "" => match Self::split_source(&self.address).1 {
+                Some(source) if source.trim_matches(['[', ']']).parse::<IpAddr>().is_err() => {
+                    source
+                }
+                _ => "",
+            },
+            name => name,
+        }
+    }
+
+    /// Reply timeout of the host's pings, `None` for the default.
+    pub fn icmp_timeout(&self) -> Option<Duration> {
+        self.timeout_ms.map(|ms| Duration::from_millis(ms as u64))
+    }
+
+    /// Returns `true` if the address is an HTTP(S) URL probed with requests instead of pings.
+    pub fn is_http(&self) -> bool {
+        let address = self.address.to_ascii_lowercase();
+        address.starts_with("http://") || address.starts_with("https://")
+    }
+
+    /// Returns `true` if the address is a `dns://resolver/name` lookup timed instead of pinged.
+    pub fn is_dns(&self) -> bool {
+        self.address.to_ascii_lowercase().starts_with("dns://")
+    }
+
+    /// Returns `true` if the address is a `udp://host:port` probed with a datagram.
+    pub fn is_udp(&self) -> bool {
+        self.address.to_ascii_lowercase().starts_with("udp://")
+    }
+
+    /// Returns `true` if the host is checked by running its probe command.
+    pub fn is_exec(&self) -> bool {
+        !self.probe_command.trim().is_empty()
+    }
+
+    /// Returns `true` if the host is monitored with ICMP echo requests.
+    pub fn is_icmp(&self) -> bool {
+        !self.is_exec() && !self.is_http() && !self.is_dns() && !self.is_udp()
+    }
+
     pub fn is_local(&self) -> bool {
-        if let Ok(ip) = self.address.parse::<std::net::IpAddr>() {
+        if let Ok(ip) = self.target().parse::<std::net::IpAddr>() {
             match ip {
                 std::net::IpAddr::V4(v4) => {
                     // RFC 1918: 10.0.0.0/8, 172.16.0.0/12, 192.168.0.0/16
@@ -407,6 +740,98 @@ impl HostInfo {
     }
 }
 
+/// Outcome of a single probe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SampleOutcome {
+    /// The host answered in time
+    #[default]
+    Reply,
+    /// No answer within the timeout
+    Timeout,
+    /// The host name could not be resolved, so nothing was sent
+    DnsError,
+    /// No answer, but the local machine was asleep, so the probe tells nothing
+    NoData,
+}
+
+/// IP address family pinged separately for dual-stack hosts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AddressFamily {
+    V4,
+    V6,
+}
+
+impl AddressFamily {
+    pub const ALL: [AddressFamily; 2] = [AddressFamily::V4, AddressFamily::V6];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AddressFamily::V4 => "IPv4",
+            AddressFamily::V6 => "IPv6",
+        }
+    }
+
+    pub fn matches(&self, ip: &std::net::IpAddr) -> bool {
+        match self {
+            AddressFamily::V4 => ip.is_ipv4(),
+            AddressFamily::V6 => ip.is_ipv6(),
+        }
+    }
+}
+
+/// Separate statistics of the IPv4 and IPv6 address of a dual-stack host.
+#[derive(Debug, Clone, Default)]
+pub struct DualStackStatus {
+    pub v4: HostStatus,
+    pub v6: HostStatus,
+}
+
+impl DualStackStatus {
+    pub fn get(&self, family: AddressFamily) -> &HostStatus {
+        match family {
+            AddressFamily::V4 => &self.v4,
+            AddressFamily::V6 => &self.v6,
+        }
+    }
+
+    pub fn get_mut(&mut self, family: AddressFamily) -> &mut HostStatus {
+        match family {
+            AddressFamily::V4 => &mut self.v4,
+            AddressFamily::V6 => &mut self.v6,
+        }
+    }
+}
+
+/// One probe result in the RTT history.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Sample {
+    /// Unix time in seconds
+    pub timestamp: u64,
+    /// Sequence number of the probe, counted from 1
+    pub seq: u32,
+    pub outcome: SampleOutcome,
+    /// RTT in milliseconds, only for replies
+    pub rtt: Option<f64>,
+    /// Taken during a maintenance window of the host, so not counted in availability
+    pub maintenance: bool,
+}
+
+impl Sample {
+    pub fn is_lost(&self) -> bool {
+        self.rtt.is_none() && !self.is_no_data()
+    }
+
+    /// Whether the probe went unanswered because the local machine was asleep.
+    pub fn is_no_data(&self) -> bool {
+        self.outcome == SampleOutcome::NoData
+    }
+
+    /// RTT in milliseconds, NaN for a lost probe.
+    pub fn rtt_or_nan(&self) -> f64 {
+        self.rtt.unwrap_or(f64::NAN)
+    }
+}
+
 /// Current monitoring status and statistics for a single host.
 ///
 /// This struct holds all real-time and historical data for a target, including
@@ -419,9 +844,9 @@ pub struct HostStatus {
     /// Last RTT in milliseconds
     #[serde(skip, default)]
     pub latency: f64,
-    /// RTT history (sliding window, last HISTORY_WINDOW_SIZE samples; NaN = loss)
+    /// Probe history (sliding window, last HISTORY_WINDOW_SIZE samples)
     #[serde(skip, default)]
-    pub history: VecDeque<f64>,
+    pub history: VecDeque<Sample>,
     /// Mean of latency
     #[serde(skip, default)]
     pub mean: f64,
@@ -461,6 +886,26 @@ pub struct HostStatus {
     /// Number of packets with RTT > mean + 3*stddev
     #[serde(skip, default)]
     pub outliers: u32,
+    /// Exponentially weighted moving average of RTT, `None` before the first reply
+    #[serde(skip, default)]
+    pub ewma: Option<f64>,
+    /// Relative change of the mean RTT of the last replies against the replies
+    /// before them (0.1 = 10% slower), 0.0 until enough replies are collected
+    #[serde(skip, default)]
+    pub trend: f64,
+    /// Number of runs of consecutive lost packets in history
+    #[serde(skip, default)]
+    pub loss_bursts: u32,
+    /// Longest run of consecutive lost packets in history
+    #[serde(skip, default)]
+    pub max_loss_burst: u32,
+    /// Mean length of the loss runs relative to random loss of the same rate
+    /// (1.0 = random, higher = bursty)
+    #[serde(skip, default)]
+    pub burst_ratio: f64,
+    /// Running statistics of `history`, kept up to date as samples come and go
+    #[serde(skip, default)]
+    pub window: WindowStats,
     /// Current success/fail streak count
     #[serde(skip, default)]
     pub streak: u32,
@@ -473,6 +918,9 @@ pub struct HostStatus {
     /// Number of responses not received
     #[serde(skip, default)]
     pub lost: u32,
+    /// Losses among `lost` that a confirmation ping overruled
+    #[serde(skip, default)]
+    pub unconfirmed_losses: u32,
 
     // --- Traceroute & Unified Pool Fields ---
     /// The discovered sequence of IP addresses to reach this host
@@ -527,6 +975,19 @@ pub struct HostStatus {
     #[serde(skip, default)]
     pub incident_start: Option<u64>,
 
+    /// Timestamp since which the host is up, or since which it is down once
+    /// the outage is confirmed
+    #[serde(skip, default)]
+    pub state_since: Option<u64>,
+
+    /// The current incident lasted long enough to play the escalation sound
+    #[serde(skip, default)]
+    pub incident_escalated: bool,
+
+    /// An alert email was sent for the current incident
+    #[serde(skip, default)]
+    pub incident_emailed: bool,
+
     /// Counter of pings since last statistics entry in log
     #[serde(skip, default)]
     pub log_pings_since_stats: u32,
@@ -534,17 +995,101 @@ pub struct HostStatus {
     /// Flag indicating if DNS resolution failed
     #[serde(skip, default)]
     pub dns_error: bool,
+
+    /// Description of the last DNS answer that did not match the expected one
+    #[serde(skip, default)]
+    pub dns_mismatch: Option<String>,
+
+    /// Address the host name currently resolves to (unset for IP addresses)
+    #[serde(skip, default)]
+    pub resolved_ip: Option<String>,
+
+    /// Previous address and time of the last change of the resolved address
+    #[serde(skip, default)]
+    pub address_change: Option<(String, u64)>,
+
+    /// Status code of the last HTTP(S) probe response
+    #[serde(skip, default)]
+    pub http_status: Option<u16>,
+
+    /// Reason the last HTTP(S) or DNS probe failed
+    #[serde(skip, default)]
+    pub probe_error: Option<String>,
+
+    /// Alerts currently raised for this host
+    #[serde(skip, default)]
+    pub active_alerts: Vec<ActiveAlert>,
+
+    /// Per-minute and per-hour statistics
+    #[serde(skip, default)]
+    pub rollups: Rollups,
+
+    /// Per-family statistics of a dual-stack host
+    #[serde(skip, default)]
+    pub dual_stack: Option<Box<DualStackStatus>>,
+
+    /// The interface the host is reached through is down, so probes are paused
+    #[serde(skip, default)]
+    pub interface_down: bool,
+
+    /// Outages of this session with start, end and lost probes
+    #[serde(skip, default)]
+    pub outages: OutageLog,
+
+    /// Name the device gave the DHCP server, if DHCP names are enabled
+    #[serde(skip, default)]
+    pub dhcp_name: Option<String>,
+
+    /// Interface error counters of the host polled over SNMP
+    #[serde(skip, default)]
+    pub snmp: SnmpStatus,
 }
 
 impl HostStatus {
-    /// Adds a new RTT sample and updates statistics.
+    /// Adds a new RTT sample taken now and updates statistics; NaN RTT means loss.
     pub fn add_sample(&mut self, rtt_ms: f64, alive: bool) {
+        let rtt = Some(rtt_ms).filter(|r| alive && !r.is_nan());
+        self.record_sample(Sample {
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            seq: self.sent + 1,
+            outcome: if rtt.is_some() {
+                SampleOutcome::Reply
+            } else {
+                SampleOutcome::Timeout
+            },
+            rtt,
+            maintenance: false,
+        });
+    }
+
+    /// Adds a probe result to the history and updates statistics.
+    ///
+    /// A sample taken while the machine was asleep only marks the history; it
+    /// counts neither as sent nor as lost.
+    pub fn record_sample(&mut self, sample: Sample) {
+        self.record(sample, !sample.is_lost());
+    }
+
+    /// Adds a lost probe that a confirmation ping overruled: it counts in the loss
+    /// statistics, but the host stays up and its success streak goes on.
+    pub fn record_unconfirmed_loss(&mut self, sample: Sample) {
+        self.unconfirmed_losses += 1;
+        self.record(sample, true);
+    }
+
+    fn record(&mut self, sample: Sample, up: bool) {
+        if sample.is_no_data() {
+            self.push_history(sample);
+            return;
+        }
         self.sent += 1;
-        self.alive = alive;
+        self.alive = up;
         self.last_updated = Some(std::time::Instant::now());
 
-        if rtt_ms.is_nan() {
+        if sample.is_lost() {
             self.lost += 1;
+        }
+        if !up {
             if !self.streak_success {
                 self.streak += 1;
             } else {
@@ -558,59 +1103,37 @@ impl HostStatus {
             self.streak_success = true;
         }
 
-        self.latency = rtt_ms;
-
-        // Add to history (sliding window)
-        self.history.push_back(rtt_ms);
-        if self.history.len() > HISTORY_WINDOW_SIZE {
-            self.history.pop_front();
+        self.latency = sample.rtt_or_nan();
+        if let Some(rtt) = sample.rtt {
+            self.ewma = Some(
+                self.ewma
+                    .map_or(rtt, |ewma| ewma + EWMA_ALPHA * (rtt - ewma)),
+            );
         }
 
-        // Availability is calculated as a sliding window (unlike total Packet Loss)
-        let total_window = self.history.len();
-        if total_window > 0 {
-            let lost_in_window = self.history.iter().filter(|rtt| rtt.is_nan()).count();
-            self.availability =
-                (total_window - lost_in_window) as f64 / total_window as f64 * 100.0;
-        } else {
-            self.availability = 100.0;
-        }
+        self.push_history(sample);
 
-        let valid_data: Vec<f64> = self
-            .history
-            .iter()
-            .copied()
-            .filter(|v| !v.is_nan())
-            .collect();
+        // Availability is calculated as a sliding window (unlike total Packet Loss),
+        // leaving out maintenance windows and the times the machine was asleep
+        self.availability = self.window.availability();
+        (self.loss_bursts, self.max_loss_burst, self.burst_ratio) = self.window.loss_bursts();
 
-        if valid_data.is_empty() {
+        if self.window.replies() == 0 {
             self.mean = 0.0;
             self.median = 0.0;
             return;
         }
-
-        if valid_data.len() < 2 {
-            self.mean = valid_data[0];
-            self.median = valid_data[0];
-            self.min_rtt = valid_data[0];
-            self.max_rtt = valid_data[0];
-            self.stddev = 0.0;
-            self.p95 = valid_data[0];
-            self.outliers = 0;
-            self.mos = calculate_mos(self.mean, self.rtp_jitter, 100.0 - self.availability);
-            return;
-        }
-
-        // Arithmetic mean
-        self.mean = valid_data.iter().sum::<f64>() / valid_data.len() as f64;
+        self.mean = self.window.mean();
+        self.stddev = self.window.stddev();
+        self.min_rtt = self.window.min();
+        self.max_rtt = self.window.max();
+        self.outliers = self.window.outliers();
 
         // Calculate RTP Jitter (RFC 3550)
         // J = J + (|D| - J) / 16
         // We calculate D as the difference in RTT between current and previous packet.
-        if valid_data.len() >= 2 {
-            let last_idx = valid_data.len() - 1;
-            let current_rtt = valid_data[last_idx];
-            let prev_rtt = valid_data[last_idx - 1];
+        let previous_rtt = self.history.iter().rev().skip(1).find_map(|s| s.rtt);
+        if let (Some(current_rtt), Some(prev_rtt)) = (sample.rtt, previous_rtt) {
             let d = (current_rtt - prev_rtt).abs();
 
             if self.rtp_jitter_history.is_empty() {
@@ -621,53 +1144,87 @@ impl HostStatus {
             }
 
             self.rtp_jitter_history.push_back(self.rtp_jitter);
-            if self.rtp_jitter_history.len() > HISTORY_WINDOW_SIZE {
-                self.rtp_jitter_history.pop_front();
+            self.window.push_jitter(self.rtp_jitter);
+            if self.rtp_jitter_history.len() > HISTORY_WINDOW_SIZE
+                && let Some(old) = self.rtp_jitter_history.pop_front()
+            {
+                self.window.evict_jitter(old);
             }
         }
 
-        // Calculate statistics for RTT
-        self.median = calculate_percentile(valid_data.iter().copied(), 50.0);
-        self.p95 = calculate_percentile(valid_data.iter().copied(), 95.0);
-        self.min_rtt = valid_data.iter().copied().fold(f64::INFINITY, f64::min);
-        self.max_rtt = valid_data.iter().copied().fold(f64::NEG_INFINITY, f64::max);
-
-        let variance = valid_data
-            .iter()
-            .map(|&v| {
-                let diff = v - self.mean;
-                diff * diff
-            })
-            .sum::<f64>()
-            / valid_data.len() as f64;
-        self.stddev = variance.sqrt();
+        self.median = self.window.percentile(50.0);
+        self.p95 = self.window.percentile(95.0);
 
         // Calculate statistics for RTP Jitter history
         if !self.rtp_jitter_history.is_empty() {
-            self.rtp_jitter_mean =
-                self.rtp_jitter_history.iter().sum::<f64>() / self.rtp_jitter_history.len() as f64;
-            self.rtp_jitter_median =
-                calculate_percentile(self.rtp_jitter_history.iter().copied(), 50.0);
+            self.rtp_jitter_mean = self.window.jitter_mean();
+            self.rtp_jitter_median = self.window.jitter_median();
         }
 
-        // Calculate Outliers
-        let threshold = self.mean + 3.0 * self.stddev;
-        if self.stddev > 0.1 {
-            self.outliers = valid_data.iter().filter(|&&v| v > threshold).count() as u32;
+        // Trend: the last replies against the ones before them
+        let recent: Vec<f64> = self
+            .history
+            .iter()
+            .rev()
+            .filter_map(|s| s.rtt)
+            .take(2 * TREND_WINDOW)
+            .collect();
+        self.trend = if recent.len() == 2 * TREND_WINDOW {
+            let mean = |window: &[f64]| window.iter().sum::<f64>() / window.len() as f64;
+            let previous_mean = mean(&recent[TREND_WINDOW..]);
+            if previous_mean > 0.0 {
+                mean(&recent[..TREND_WINDOW]) / previous_mean - 1.0
+            } else {
+                0.0
+            }
         } else {
-            self.outliers = 0;
-        }
+            0.0
+        };
 
         // Calculate MOS
         let loss_pct = 100.0 - self.availability;
         self.mos = calculate_mos(self.mean, self.rtp_jitter, loss_pct);
     }
 
+    /// Adds a sample to the end of the history, dropping the oldest beyond the window.
+    fn push_history(&mut self, sample: Sample) {
+        self.history.push_back(sample);
+        self.window.push(&sample);
+        if self.history.len() > HISTORY_WINDOW_SIZE
+            && let Some(old) = self.history.pop_front()
+        {
+            self.window.evict(&old);
+        }
+    }
+
+    /// Whether the last probe fell into a maintenance window of the host.
+    pub fn in_maintenance(&self) -> bool {
+        self.history.back().is_some_and(|s| s.maintenance)
+    }
+
+    /// Whether one more lost probe confirms the host down and opens an incident.
+    pub fn next_loss_opens_incident(&self) -> bool {
+        let losses = if self.streak_success {
+            1
+        } else {
+            self.streak + 1
+        };
+        self.incident_start.is_none() && losses == STATE_CONFIRMATION_STREAK
+    }
+
+    /// Whether the host is up and for how many seconds it has been in that
+    /// state at `now`; `None` before the state is known.
+    pub fn state_duration(&self, now: u64) -> Option<(bool, u64)> {
+        self.state_since
+            .map(|since| (self.incident_start.is_none(), now.saturating_sub(since)))
+    }
+
     /// Resets all statistics fields, typically used when stopping the host pinging.
     pub fn reset_statistics(&mut self) {
         self.alive = false;
         self.latency = f64::NAN;
         self.history.clear();
+        self.window = WindowStats::default();
         self.mean = 0.0;
         self.rtp_jitter = 0.0;
         self.rtp_jitter_history.clear();
@@ -681,14 +1238,31 @@ impl HostStatus {
         self.mos = 0.0;
         self.availability = 0.0;
         self.outliers = 0;
+        self.ewma = None;
+        self.trend = 0.0;
+        self.loss_bursts = 0;
+        self.max_loss_burst = 0;
+        self.burst_ratio = 0.0;
         self.streak = 0;
         self.streak_success = false;
         self.sent = 0;
         self.lost = 0;
+        self.unconfirmed_losses = 0;
         self.prev_alive = None;
         self.incident_start = None;
+        self.state_since = None;
+        self.incident_escalated = false;
+        self.incident_emailed = false;
         self.log_pings_since_stats = 0;
         self.dns_error = false;
+        self.dns_mismatch = None;
+        self.address_change = None;
+        self.http_status = None;
+        self.probe_error = None;
+        self.active_alerts.clear();
+        self.rollups.clear();
+        self.outages.clear();
+        self.dual_stack = None;
         self.events.clear();
         // Do not reset traceroute_path, tracking states for traceroute
     }
@@ -723,21 +1297,46 @@ pub fn calculate_mos(rtt: f64, jitter: f64, loss_pct: f64) -> f64 {
     1.0 + 0.035 * r + 0.000007 * r * (r - 60.0) * (100.0 - r)
 }
 
+/// Counts the runs of consecutive losses in a sequence of probe results (true =
+/// lost) and returns their number, the longest run and the burst ratio.
+///
+/// The burst ratio divides the mean run length by `1 / (1 - p)`, the mean run
+/// length of random (Bernoulli) loss with the same loss rate `p`: 1.0 means
+/// random loss, higher values mean the losses come in bursts. It is 0.0 without
+/// loss and the run length itself when every probe is lost.
+pub fn calculate_loss_bursts(lost: impl IntoIterator<Item = bool>) -> (u32, u32, f64) {
+    let (mut total, mut lost_count, mut bursts, mut longest, mut run) =
+        (0u32, 0u32, 0u32, 0u32, 0u32);
+    for is_lost in lost {
+        total += 1;
+        if is_lost {
+            lost_count += 1;
+            run += 1;
+            if run == 1 {
+                bursts += 1;
+            }
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    let ratio = if bursts == 0 {
+        0.0
+    } else if lost_count == total {
+        lost_count as f64
+    } else {
+        let mean_run = lost_count as f64 / bursts as f64;
+        let loss_rate = lost_count as f64 / total as f64;
+        mean_run * (1.0 - loss_rate)
+    };
+    (bursts, longest, ratio)
+}
+
 /// Calculates a percentile from a sequence of data.
 pub fn calculate_percentile(data: impl IntoIterator<Item = f64>, percentile: f64) -> f64 {
     let mut sorted: Vec<f64> = data.into_iter().collect();
-    if sorted.is_empty() {
-        return 0.0;
-    }
     sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-    let pos = (percentile / 100.0) * (sorted.len() - 1) as f64;
-    let base = pos.floor() as usize;
-    let fract = pos - base as f64;
-    if base + 1 < sorted.len() {
-        sorted[base] + fract * (sorted[base + 1] - sorted[base])
-    } else {
-        sorted[base]
-    }
+    percentile_of_sorted(&sorted, percentile)
 }
 
 #[cfg(test)]
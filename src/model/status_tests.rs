@@ -15,6 +15,49 @@ fn test_add_sample_stats() {
     assert!(!status.streak_success); // Last was NaN
 }
 
+#[test]
+fn test_record_sample_keeps_outcome() {
+    let mut status = HostStatus::default();
+    status.add_sample(10.0, true);
+    status.record_sample(Sample {
+        timestamp: 1_700_000_000,
+        seq: 2,
+        outcome: SampleOutcome::DnsError,
+        rtt: None,
+        maintenance: false,
+    });
+
+    assert_eq!(status.history[0].seq, 1);
+    assert_eq!(status.history[0].outcome, SampleOutcome::Reply);
+    assert_eq!(status.history[1].outcome, SampleOutcome::DnsError);
+    assert_eq!(status.history[1].timestamp, 1_700_000_000);
+    assert!(!status.alive);
+    assert!(status.latency.is_nan());
+    assert_eq!(status.availability, 50.0);
+}
+
+#[test]
+fn test_maintenance_samples_left_out_of_availability() {
+    let mut status = HostStatus::default();
+    status.add_sample(10.0, true);
+    status.add_sample(f64::NAN, false);
+    for seq in 3..=5 {
+        status.record_sample(Sample {
+            timestamp: 1_700_000_000,
+            seq,
+            outcome: SampleOutcome::Timeout,
+            rtt: None,
+            maintenance: true,
+        });
+    }
+    assert!(status.in_maintenance());
+    assert_eq!(status.availability, 50.0);
+
+    status.add_sample(10.0, true);
+    assert!(!status.in_maintenance());
+    assert!((status.availability - 2.0 / 3.0 * 100.0).abs() < 1e-9);
+}
+
 #[test]
 fn test_calculate_percentile() {
     let data = [1.0, 2.0, 3.0, 4.0, 5.0];
@@ -73,6 +116,22 @@ fn test_streaks() {
     assert!(status.streak_success);
 }
 
+#[test]
+fn test_next_loss_opens_incident() {
+    let mut status = HostStatus::default();
+    status.add_sample(10.0, true);
+    assert!(!status.next_loss_opens_incident());
+    for _ in 1..STATE_CONFIRMATION_STREAK {
+        status.add_sample(f64::NAN, false);
+    }
+    assert!(status.next_loss_opens_incident());
+
+    // Once down, further losses continue the incident
+    status.add_sample(f64::NAN, false);
+    status.incident_start = Some(1);
+    assert!(!status.next_loss_opens_incident());
+}
+
 #[test]
 fn test_outliers_detection() {
     let mut status = HostStatus::default();
@@ -121,8 +180,8 @@ fn test_history_limit() {
         status.add_sample(i as f64, true);
     }
     assert_eq!(status.history.len(), 300);
-    assert_eq!(status.history[0], 100.0);
-    assert_eq!(status.history[299], 399.0);
+    assert_eq!(status.history[0].rtt, Some(100.0));
+    assert_eq!(status.history[299].rtt, Some(399.0));
 }
 
 #[test]
@@ -137,6 +196,7 @@ fn test_hostinfo_is_local() {
         log_to_file: false,
         log_file_path: String::new(),
         is_stopped: false,
+        ..Default::default()
     };
     assert!(h.is_local(), "127.0.0.1 should be local");
 
@@ -205,6 +265,7 @@ fn test_hostinfo_defaults() {
         log_to_file: false,
         log_file_path: String::new(),
         is_stopped: false,
+        ..Default::default()
     };
     assert_eq!(h.mode, PingMode::Fast);
     assert_eq!(h.packet_size, 16);
@@ -243,8 +304,8 @@ fn test_large_rtt_history_and_statistics() {
     }
 
     assert_eq!(status.history.len(), 300);
-    assert_eq!(*status.history.front().unwrap(), 201.0);
-    assert_eq!(*status.history.back().unwrap(), 500.0);
+    assert_eq!(status.history.front().unwrap().rtt, Some(201.0));
+    assert_eq!(status.history.back().unwrap().rtt, Some(500.0));
 
     assert_eq!(status.min_rtt, 201.0);
     assert_eq!(status.max_rtt, 500.0);
@@ -392,6 +453,7 @@ fn test_hostinfo_serde_roundtrip() {
         log_to_file: false,
         log_file_path: String::new(),
         is_stopped: false,
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&host).unwrap();
@@ -432,6 +494,7 @@ fn test_appstate_serde_roundtrip() {
         log_to_file: false,
         log_file_path: String::new(),
         is_stopped: false,
+        ..Default::default()
     });
     state.hosts.push(HostInfo {
         name: "Router".to_string(),
@@ -443,6 +506,7 @@ fn test_appstate_serde_roundtrip() {
         log_to_file: false,
         log_file_path: String::new(),
         is_stopped: false,
+        ..Default::default()
     });
 
     let json = serde_json::to_string_pretty(&state).unwrap();
@@ -534,6 +598,12 @@ fn test_log_entry_statistics_formatting() {
         show_p95: true,
         show_min_max: true,
         show_loss: true,
+        show_ewma: true,
+        show_loss_bursts: true,
+        show_uptime: true,
+        show_threshold_bar: true,
+        long_term_plot: false,
+        heatmap_plot: false,
     };
 
     let formatted_all = entry.format("127.0.0.1", Some(&display));
@@ -695,3 +765,104 @@ fn test_reset_statistics() {
     assert_eq!(status.mean, 0.0);
     assert_eq!(status.rtp_jitter, 0.0);
 }
+
+#[test]
+fn test_duplicate_host() {
+    let original = HostInfo {
+        name: "web1".to_string(),
+        address: "10.0.0.11".to_string(),
+        mode: PingMode::Slow,
+        packet_size: 1400,
+        tags: vec!["prod".to_string()],
+        log_to_file: true,
+        log_file_path: "/tmp/web1.log".to_string(),
+        shared: true,
+        ..Default::default()
+    };
+    let copy = original.duplicate("web2".to_string(), "10.0.0.12".to_string());
+    assert_eq!(copy.address, "10.0.0.12");
+    assert_eq!(copy.mode, PingMode::Slow);
+    assert_eq!(copy.packet_size, 1400);
+    assert_eq!(copy.tags, original.tags);
+    assert!(!copy.log_to_file && copy.log_file_path.is_empty() && !copy.shared);
+}
+
+#[test]
+fn test_split_source() {
+    assert_eq!(HostInfo::split_source("8.8.8.8"), ("8.8.8.8", None));
+    assert_eq!(
+        HostInfo::split_source("8.8.8.8@wlan0"),
+        ("8.8.8.8", Some("wlan0"))
+    );
+    assert_eq!(
+        HostInfo::split_source("[2001:db8::1]@192.168.1.5"),
+        ("[2001:db8::1]", Some("192.168.1.5"))
+    );
+    // A URL may carry user info; a lone @ is no binding
+    assert_eq!(
+        HostInfo::split_source("https://user@example.com/"),
+        ("https://user@example.com/", None)
+    );
+    assert_eq!(HostInfo::split_source("host@"), ("host@", None));
+}
+
+#[test]
+fn test_bound_interface() {
+    let host = |address: &str, interface: &str| HostInfo {
+        address: address.to_string(),
+        interface: interface.to_string(),
+        ..Default::default()
+    };
+    assert_eq!(host("8.8.8.8", "").bound_interface(), "");
+    assert_eq!(host("8.8.8.8@wlan0", "").bound_interface(), "wlan0");
+    assert_eq!(host("8.8.8.8@wlan0", " eth0 ").bound_interface(), "eth0");
+    // A local address is no interface name
+    assert_eq!(host("8.8.8.8@192.168.1.5", "").bound_interface(), "");
+    assert_eq!(host("[2001:db8::1]@[fe80::1]", "").bound_interface(), "");
+}
+
+#[test]
+fn test_calculate_loss_bursts() {
+    let pattern = |text: &str| text.chars().map(|c| c == 'x').collect::<Vec<_>>();
+
+    assert_eq!(calculate_loss_bursts(pattern("....")), (0, 0, 0.0));
+
+    // Spread out: 2 losses in 10 as 2 runs of 1, ratio 1 * (1 - 0.2)
+    let (bursts, longest, ratio) = calculate_loss_bursts(pattern("..x....x.."));
+    assert_eq!((bursts, longest), (2, 1));
+    assert!((ratio - 0.8).abs() < 1e-9);
+
+    // The same loss rate in one run is bursty: 2 * 0.8
+    let (bursts, longest, ratio) = calculate_loss_bursts(pattern("...xx....."));
+    assert_eq!((bursts, longest), (1, 2));
+    assert!((ratio - 1.6).abs() < 1e-9);
+
+    assert_eq!(calculate_loss_bursts(pattern("xxx")), (1, 3, 3.0));
+}
+
+#[test]
+fn test_record_sample_tracks_loss_bursts() {
+    let mut status = HostStatus::default();
+    for i in 0..20 {
+        status.add_sample(10.0, !(5..8).contains(&i) && i != 12);
+    }
+    assert_eq!(status.loss_bursts, 2);
+    assert_eq!(status.max_loss_burst, 3);
+    assert!(status.burst_ratio > 1.0);
+
+    status.reset_statistics();
+    assert_eq!(status.loss_bursts, 0);
+    assert_eq!(status.max_loss_burst, 0);
+}
+
+#[test]
+fn test_ewma() {
+    let mut status = HostStatus::default();
+    assert_eq!(status.ewma, None);
+    status.add_sample(10.0, true);
+    assert_eq!(status.ewma, Some(10.0));
+    status.add_sample(f64::NAN, false);
+    assert_eq!(status.ewma, Some(10.0));
+    status.add_sample(20.0, true);
+    assert!((status.ewma.unwrap() - 11.0).abs() < 1e-9);
+}
@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use tr::tr;
+
+/// Colors of the latency ranges and of lost probes, as sRGB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusColors {
+    /// Latency below the warn threshold
+    pub ok: [u8; 3],
+    /// Latency above the warn threshold, degraded statistics
+    pub warn: [u8; 3],
+    /// Latency above the bad threshold
+    pub bad: [u8; 3],
+    /// Lost probes, hosts down and bad statistics
+    pub loss: [u8; 3],
+}
+
+impl Default for StatusColors {
+    fn default() -> Self {
+        ColorTheme::OkabeIto.preset_colors(false)
+    }
+}
+
+/// Color scheme of the status colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ColorTheme {
+    /// Okabe-Ito palette, distinguishable with all common kinds of color blindness.
+    #[default]
+    OkabeIto,
+    /// Green, yellow and red like a traffic light.
+    Classic,
+    /// Shades of grey, for printing and for displays without color.
+    Monochrome,
+    /// Colors chosen by the user, the same in the light and the dark theme.
+    Custom,
+}
+
+impl ColorTheme {
+    pub const ALL: [ColorTheme; 4] = [
+        ColorTheme::OkabeIto,
+        ColorTheme::Classic,
+        ColorTheme::Monochrome,
+        ColorTheme::Custom,
+    ];
+
+    pub fn label(&self) -> String {
+        match self {
+            ColorTheme::OkabeIto => tr!("Color-blind safe (Okabe-Ito)"),
+            ColorTheme::Classic => tr!("Classic (green/yellow/red)"),
+            ColorTheme::Monochrome => tr!("Monochrome"),
+            ColorTheme::Custom => tr!("Custom"),
+        }
+    }
+
+    /// Colors of the theme for a light or dark background; `custom` for the
+    /// custom theme.
+    pub fn colors(&self, is_dark: bool, custom: &StatusColors) -> StatusColors {
+        match self {
+            ColorTheme::Custom => *custom,
+            preset => preset.preset_colors(is_dark),
+        }
+    }
+
+    fn preset_colors(&self, is_dark: bool) -> StatusColors {
+        let (ok, warn, bad, loss) = match (self, is_dark) {
+            // The original colors of the application
+            (ColorTheme::OkabeIto | ColorTheme::Custom, false) => {
+                ([0, 114, 178], [230, 159, 0], [204, 121, 167], [213, 94, 0])
+            }
+            (ColorTheme::OkabeIto | ColorTheme::Custom, true) => (
+                [86, 180, 233],
+                [240, 228, 66],
+                [204, 121, 167],
+                [213, 94, 0],
+            ),
+            (ColorTheme::Classic, false) => {
+                ([0, 140, 0], [190, 150, 0], [220, 110, 0], [200, 0, 0])
+            }
+            (ColorTheme::Classic, true) => {
+                ([80, 200, 80], [230, 210, 40], [255, 140, 40], [255, 70, 70])
+            }
+            (ColorTheme::Monochrome, false) => (
+                [40, 40, 40],
+                [100, 100, 100],
+                [140, 140, 140],
+                [180, 180, 180],
+            ),
+            (ColorTheme::Monochrome, true) => (
+                [220, 220, 220],
+                [170, 170, 170],
+                [130, 130, 130],
+                [90, 90, 90],
+            ),
+        };
+        StatusColors {
+            ok,
+            warn,
+            bad,
+            loss,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_colors() {
+        let custom = StatusColors {
+            ok: [1, 2, 3],
+            ..Default::default()
+        };
+        assert_eq!(ColorTheme::Custom.colors(true, &custom), custom);
+        for theme in [
+            ColorTheme::OkabeIto,
+            ColorTheme::Classic,
+            ColorTheme::Monochrome,
+        ] {
+            for is_dark in [false, true] {
+                let c = theme.colors(is_dark, &custom);
+                let all = [c.ok, c.warn, c.bad, c.loss];
+                for (i, a) in all.iter().enumerate() {
+                    assert!(all[i + 1..].iter().all(|b| a != b), "{:?}", theme);
+                }
+            }
+        }
+        // The default keeps the colors the application always had
+        assert_eq!(
+            ColorTheme::default().colors(true, &custom).ok,
+            [86, 180, 233]
+        );
+    }
+}
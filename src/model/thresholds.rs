@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use tr::tr;
+
+/// Values from which a host's statistics are shown as degraded (warn) or bad.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Thresholds {
+    /// RTT in ms
+    pub latency_warn: f64,
+    pub latency_bad: f64,
+    /// RTP jitter in ms
+    pub jitter_warn: f64,
+    pub jitter_bad: f64,
+    /// Packet loss in percent
+    pub loss_warn: f64,
+    pub loss_bad: f64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        QoePreset::Voip.thresholds()
+    }
+}
+
+impl Thresholds {
+    /// The preset these thresholds are equal to, if any.
+    pub fn preset(&self) -> Option<QoePreset> {
+        QoePreset::ALL.into_iter().find(|p| p.thresholds() == *self)
+    }
+}
+
+/// Threshold presets for common kinds of traffic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QoePreset {
+    Voip,
+    VideoConferencing,
+    Gaming,
+    WebBrowsing,
+    BulkTransfer,
+}
+
+impl QoePreset {
+    pub const ALL: [QoePreset; 5] = [
+        QoePreset::Voip,
+        QoePreset::VideoConferencing,
+        QoePreset::Gaming,
+        QoePreset::WebBrowsing,
+        QoePreset::BulkTransfer,
+    ];
+
+    pub fn label(&self) -> String {
+        match self {
+            QoePreset::Voip => tr!("VoIP"),
+            QoePreset::VideoConferencing => tr!("Video conferencing"),
+            QoePreset::Gaming => tr!("Gaming"),
+            QoePreset::WebBrowsing => tr!("Web browsing"),
+            QoePreset::BulkTransfer => tr!("Bulk transfer"),
+        }
+    }
+
+    /// Warn/bad values of the preset.
+    ///
+    /// VoIP follows ITU-T G.114 and the original fixed colors of the host row;
+    /// the others are tighter (gaming) or looser (web, bulk) around it.
+    pub fn thresholds(&self) -> Thresholds {
+        let (latency_warn, latency_bad, jitter_warn, jitter_bad, loss_warn, loss_bad) = match self {
+            QoePreset::Voip => (150.0, 300.0, 20.0, 30.0, 1.0, 3.0),
+            QoePreset::VideoConferencing => (200.0, 400.0, 30.0, 50.0, 1.0, 3.0),
+            QoePreset::Gaming => (50.0, 100.0, 10.0, 20.0, 0.5, 2.0),
+            QoePreset::WebBrowsing => (300.0, 1000.0, 50.0, 100.0, 2.0, 5.0),
+            QoePreset::BulkTransfer => (500.0, 2000.0, 100.0, 300.0, 2.0, 5.0),
+        };
+        Thresholds {
+            latency_warn,
+            latency_bad,
+            jitter_warn,
+            jitter_bad,
+            loss_warn,
+            loss_bad,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_are_distinct_and_ordered() {
+        for preset in QoePreset::ALL {
+            let t = preset.thresholds();
+            assert_eq!(t.preset(), Some(preset));
+            assert!(t.latency_warn < t.latency_bad);
+            assert!(t.jitter_warn < t.jitter_bad);
+            assert!(t.loss_warn < t.loss_bad);
+        }
+        let custom = Thresholds {
+            latency_bad: 700.0,
+            ..Thresholds::default()
+        };
+        assert_eq!(custom.preset(), None);
+        assert_eq!(Thresholds::default().preset(), Some(QoePreset::Voip));
+    }
+}
@@ -0,0 +1,292 @@
+use super::stats::SortedWindow;
+use super::status::Sample;
+use std::collections::VecDeque;
+
+/// Statistics of the samples in the history window, updated as samples enter
+/// and leave the window instead of being recomputed over all of it.
+#[derive(Debug, Clone, Default)]
+pub struct WindowStats {
+    /// Samples counted in availability: neither in maintenance nor asleep.
+    counted: usize,
+    /// Lost samples among the counted ones.
+    lost: usize,
+    /// Replies in the window.
+    replies: usize,
+    /// Running mean of the RTTs of the replies (Welford).
+    mean: f64,
+    /// Sum of the squared deviations of the RTTs from the mean (Welford).
+    m2: f64,
+    /// Round-trip times of the replies in ascending order.
+    rtts: SortedWindow,
+    /// Lengths of the runs of lost samples in the window, oldest first.
+    bursts: VecDeque<u32>,
+    /// Whether the last counted sample was lost, i.e. the last burst goes on.
+    last_lost: bool,
+    /// Whether each reply in the window was an outlier when it came, oldest first.
+    outlier_flags: VecDeque<bool>,
+    /// Replies in the window that were outliers, i.e. the `true` flags.
+    outliers: usize,
+    /// Sum of the values of the RTP jitter history.
+    jitter_sum: f64,
+    /// Values of the RTP jitter history in ascending order.
+    jitters: SortedWindow,
+}
+
+/// Whether a sample counts in availability and loss statistics.
+fn is_counted(sample: &Sample) -> bool {
+    !sample.maintenance && !sample.is_no_data()
+}
+
+impl WindowStats {
+    /// Takes in a sample added to the end of the window.
+    pub fn push(&mut self, sample: &Sample) {
+        if is_counted(sample) {
+            self.counted += 1;
+            let lost = sample.is_lost();
+            if lost {
+                self.lost += 1;
+                match self.bursts.back_mut() {
+                    Some(run) if self.last_lost => *run += 1,
+                    _ => self.bursts.push_back(1),
+                }
+            }
+            self.last_lost = lost;
+        }
+        if let Some(rtt) = sample.rtt {
+            // Replies more than three standard deviations above the mean so far
+            let stddev = self.stddev();
+            let outlier = stddev > 0.1 && rtt > self.mean + 3.0 * stddev;
+            self.outlier_flags.push_back(outlier);
+            self.outliers += usize::from(outlier);
+
+            self.replies += 1;
+            let delta = rtt - self.mean;
+            self.mean += delta / self.replies as f64;
+            self.m2 += delta * (rtt - self.mean);
+            self.rtts.insert(rtt);
+            self.settle();
+        }
+    }
+
+    /// Takes out a sample dropped from the front of the window.
+    pub fn evict(&mut self, sample: &Sample) {
+        if is_counted(sample) {
+            self.counted -= 1;
+            if sample.is_lost() {
+                self.lost -= 1;
+                if let Some(run) = self.bursts.front_mut() {
+                    *run -= 1;
+                    if *run == 0 {
+                        self.bursts.pop_front();
+                    }
+                }
+            }
+            if self.counted == 0 {
+                self.last_lost = false;
+            }
+        }
+        if let Some(rtt) = sample.rtt {
+            if self.outlier_flags.pop_front() == Some(true) {
+                self.outliers -= 1;
+            }
+            self.replies -= 1;
+            if self.replies == 0 {
+                (self.mean, self.m2) = (0.0, 0.0);
+            } else {
+                let delta = rtt - self.mean;
+                self.mean -= delta / self.replies as f64;
+                self.m2 = (self.m2 - delta * (rtt - self.mean)).max(0.0);
+            }
+            self.rtts.remove(rtt);
+            self.settle();
+        }
+    }
+
+    /// Equal replies have no spread; this also clears the rounding errors left by
+    /// replies that came and went.
+    fn settle(&mut self) {
+        if let (Some(min), Some(max)) = (self.rtts.min(), self.rtts.max())
+            && min == max
+        {
+            (self.mean, self.m2) = (min, 0.0);
+        }
+    }
+
+    pub fn push_jitter(&mut self, jitter: f64) {
+        self.jitter_sum += jitter;
+        self.jitters.insert(jitter);
+    }
+
+    pub fn evict_jitter(&mut self, jitter: f64) {
+        self.jitter_sum -= jitter;
+        self.jitters.remove(jitter);
+    }
+
+    /// Mean of the RTP jitter history, 0 when it is empty.
+    pub fn jitter_mean(&self) -> f64 {
+        if self.jitters.is_empty() {
+            0.0
+        } else {
+            self.jitter_sum / self.jitters.len() as f64
+        }
+    }
+
+    pub fn jitter_median(&self) -> f64 {
+        self.jitters.percentile(50.0)
+    }
+
+    /// Availability in percent, 100 without counted samples.
+    pub fn availability(&self) -> f64 {
+        if self.counted == 0 {
+            100.0
+        } else {
+            (self.counted - self.lost) as f64 / self.counted as f64 * 100.0
+        }
+    }
+
+    pub fn replies(&self) -> usize {
+        self.replies
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Population standard deviation of the replies.
+    pub fn stddev(&self) -> f64 {
+        if self.replies == 0 {
+            0.0
+        } else {
+            (self.m2 / self.replies as f64).sqrt()
+        }
+    }
+
+    /// Percentile (0–100) of the round-trip times, 0 without replies.
+    pub fn percentile(&self, percentile: f64) -> f64 {
+        self.rtts.percentile(percentile)
+    }
+
+    pub fn min(&self) -> f64 {
+        self.rtts.min().unwrap_or(f64::INFINITY)
+    }
+
+    pub fn max(&self) -> f64 {
+        self.rtts.max().unwrap_or(f64::NEG_INFINITY)
+    }
+
+    pub fn outliers(&self) -> u32 {
+        self.outliers as u32
+    }
+
+    /// Number of loss bursts, the longest one and the burst ratio, like
+    /// [`super::status::calculate_loss_bursts`] over the counted samples.
+    pub fn loss_bursts(&self) -> (u32, u32, f64) {
+        let bursts = self.bursts.len() as u32;
+        let longest = self.bursts.iter().copied().max().unwrap_or(0);
+        let ratio = if bursts == 0 {
+            0.0
+        } else if self.lost == self.counted {
+            self.lost as f64
+        } else {
+            let mean_run = self.lost as f64 / bursts as f64;
+            let loss_rate = self.lost as f64 / self.counted as f64;
+            mean_run * (1.0 - loss_rate)
+        };
+        (bursts, longest, ratio)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::status::{calculate_loss_bursts, calculate_percentile};
+
+    fn sample(rtt: Option<f64>) -> Sample {
+        Sample {
+            rtt,
+            outcome: if rtt.is_some() {
+                crate::model::SampleOutcome::Reply
+            } else {
+                crate::model::SampleOutcome::Timeout
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_window_matches_full_recomputation() {
+        let rtts = [
+            Some(10.0),
+            None,
+            None,
+            Some(30.0),
+            Some(12.5),
+            None,
+            Some(80.0),
+            Some(11.0),
+            None,
+            None,
+            None,
+            Some(9.0),
+        ];
+        let mut window = WindowStats::default();
+        let mut history = VecDeque::new();
+        for (i, rtt) in rtts.iter().cycle().take(60).enumerate() {
+            let s = sample(*rtt);
+            history.push_back(s);
+            window.push(&s);
+            if history.len() > 7 {
+                let old = history.pop_front().unwrap();
+                window.evict(&old);
+            }
+
+            let replies: Vec<f64> = history.iter().filter_map(|s| s.rtt).collect();
+            let mean = replies.iter().sum::<f64>() / replies.len() as f64;
+            let variance =
+                replies.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / replies.len() as f64;
+            assert!((window.mean() - mean).abs() < 1e-9, "step {}", i);
+            assert!(
+                (window.stddev() - variance.sqrt()).abs() < 1e-6,
+                "step {}",
+                i
+            );
+            assert_eq!(
+                window.min(),
+                replies.iter().copied().fold(f64::INFINITY, f64::min)
+            );
+            assert_eq!(
+                window.max(),
+                replies.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+            );
+            assert_eq!(
+                window.percentile(95.0),
+                calculate_percentile(replies.iter().copied(), 95.0)
+            );
+            let lost = history.iter().filter(|s| s.is_lost()).count();
+            let availability = (history.len() - lost) as f64 / history.len() as f64 * 100.0;
+            assert!((window.availability() - availability).abs() < 1e-9);
+            assert_eq!(
+                window.loss_bursts(),
+                calculate_loss_bursts(history.iter().map(|s| s.is_lost())),
+                "step {}",
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn test_equal_replies_have_no_spread() {
+        let mut window = WindowStats::default();
+        let mut history = VecDeque::new();
+        for rtt in [1.1, 7.3, 2.9, 10.0, 10.0, 10.0] {
+            let s = sample(Some(rtt));
+            history.push_back(s);
+            window.push(&s);
+            if history.len() > 3 {
+                let old = history.pop_front().unwrap();
+                window.evict(&old);
+            }
+        }
+        assert_eq!((window.mean(), window.stddev()), (10.0, 0.0));
+    }
+}
@@ -0,0 +1,62 @@
+use crate::model::AppState;
+use eframe::egui;
+use eframe::egui::RichText;
+use tr::tr;
+
+/// Renders the window listing the on down / on up commands that were run.
+pub fn render_action_log_window(ctx: &egui::Context, state: &mut AppState, open: &mut bool) {
+    egui::Window::new(tr!("Action Log"))
+        .open(open)
+        .resizable(true)
+        .default_width(600.0)
+        .show(ctx, |ui| {
+            if state.action_log.is_empty() {
+                ui.label(tr!("No actions have run yet."));
+                return;
+            }
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("action_log_grid")
+                    .striped(true)
+                    .num_columns(5)
+                    .show(ui, |ui| {
+                        ui.strong(tr!("Time"));
+                        ui.strong(tr!("Host"));
+                        ui.strong(tr!("Event"));
+                        ui.strong(tr!("Command"));
+                        ui.strong(tr!("Result"));
+                        ui.end_row();
+
+                        for record in state.action_log.iter().rev() {
+                            let time = chrono::DateTime::from_timestamp(record.timestamp as i64, 0)
+                                .map(|t| {
+                                    t.with_timezone(&chrono::Local)
+                                        .format("%Y-%m-%d %H:%M:%S")
+                                        .to_string()
+                                })
+                                .unwrap_or_default();
+                            ui.label(time);
+                            ui.label(&record.address);
+                            ui.label(record.event.label());
+                            ui.label(RichText::new(&record.command).monospace());
+                            match &record.result {
+                                Ok(0) => ui.label(
+                                    tr!("OK in {ms} ms")
+                                        .replace("{ms}", &record.duration_ms.to_string()),
+                                ),
+                                Ok(code) => ui.colored_label(
+                                    egui::Color32::from_rgb(213, 94, 0),
+                                    tr!("exit code {code}").replace("{code}", &code.to_string()),
+                                ),
+                                Err(e) => ui.colored_label(egui::Color32::from_rgb(213, 94, 0), e),
+                            };
+                            ui.end_row();
+                        }
+                    });
+            });
+
+            if ui.button(tr!("Clear")).clicked() {
+                state.action_log.clear();
+            }
+        });
+}
@@ -0,0 +1,41 @@
+use crate::model::AppState;
+use eframe::egui;
+use eframe::egui::{Color32, RichText};
+
+/// Shows a banner listing all alerts currently raised, one line per alert.
+///
+/// Alerts of hosts in silenced groups are left out.
+pub fn render_alert_banner(ui: &mut egui::Ui, state: &AppState) {
+    let now = chrono::Utc::now().timestamp() as u64;
+    let lines: Vec<String> = state
+        .hosts
+        .iter()
+        .filter(|h| !state.is_host_silenced(&h.address, now))
+        .filter_map(|h| state.statuses.get(&h.address).map(|s| (h, s)))
+        .flat_map(|(h, s)| {
+            let host = if h.name.is_empty() {
+                h.address.clone()
+            } else {
+                h.name.clone()
+            };
+            s.active_alerts
+                .iter()
+                .map(move |a| format!("⚠ {}: {}", host, a.message))
+        })
+        .collect();
+    if lines.is_empty() {
+        return;
+    }
+
+    let color = Color32::from_rgb(213, 94, 0);
+    egui::Frame::new()
+        .stroke(egui::Stroke::new(1.0, color))
+        .inner_margin(6.0)
+        .corner_radius(4.0)
+        .show(ui, |ui| {
+            for line in lines {
+                ui.colored_label(color, RichText::new(line).strong());
+            }
+        });
+    ui.add_space(4.0);
+}
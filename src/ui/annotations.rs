@@ -0,0 +1,68 @@
+use crate::logic::presenter::format_time;
+use crate::model::AppState;
+use eframe::egui;
+use eframe::egui::RichText;
+use tr::tr;
+
+/// Renders the window adding and removing notes on the timeline.
+pub fn render_annotations_window(ctx: &egui::Context, state: &mut AppState, open: &mut bool) {
+    let draft_id = egui::Id::new("annotation_draft");
+    let (mut text, mut minutes_ago): (String, u32) =
+        ctx.data_mut(|d| d.get_temp(draft_id)).unwrap_or_default();
+    egui::Window::new(tr!("Annotations"))
+        .open(open)
+        .resizable(true)
+        .default_width(450.0)
+        .show(ctx, |ui| {
+            ui.label(
+                RichText::new(tr!(
+                    "Notes such as \"rebooted router\" are drawn as vertical markers on the history plots and included in the statistics export."
+                ))
+                .small(),
+            );
+            ui.horizontal(|ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut text)
+                        .hint_text(tr!("Rebooted router"))
+                        .desired_width(220.0),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut minutes_ago)
+                        .range(0..=10_080)
+                        .suffix(tr!(" min ago")),
+                );
+                let submitted =
+                    response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if (ui.button(tr!("Add")).clicked() || submitted) && !text.trim().is_empty() {
+                    let now = chrono::Utc::now().timestamp() as u64;
+                    state.annotate(now.saturating_sub(minutes_ago as u64 * 60), &text);
+                    text.clear();
+                    minutes_ago = 0;
+                }
+            });
+
+            ui.separator();
+            if state.annotations.is_empty() {
+                ui.label(tr!("No annotations yet."));
+                return;
+            }
+            let mut remove = None;
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for (i, note) in state.annotations.iter().enumerate().rev() {
+                        ui.horizontal(|ui| {
+                            if ui.button("🗑").on_hover_text(tr!("Remove")).clicked() {
+                                remove = Some(i);
+                            }
+                            ui.label(RichText::new(format_time(note.timestamp)).monospace());
+                            ui.label(&note.text);
+                        });
+                    }
+                });
+            if let Some(i) = remove {
+                state.annotations.remove(i);
+            }
+        });
+    ctx.data_mut(|d| d.insert_temp(draft_id, (text, minutes_ago)));
+}
@@ -0,0 +1,183 @@
+use crate::app::PingVisuals;
+use crate::constants::{BUFFERBLOAT_IDLE_SEC, BUFFERBLOAT_LOAD_SEC};
+use crate::logic::diagnostics::{BufferbloatCheck, BufferbloatPhase, BufferbloatResult, LoadKind};
+use crate::model::ProxySettings;
+use eframe::egui;
+use eframe::egui::{Color32, RichText};
+use std::time::Duration;
+use tr::tr;
+
+/// The bufferbloat window of a host: the chosen load and the running or
+/// finished check.
+pub struct BufferbloatState {
+    pub target: String,
+    pub load: LoadKind,
+    pub check: Option<BufferbloatCheck>,
+}
+
+impl BufferbloatState {
+    pub fn new(target: String) -> Self {
+        Self {
+            target,
+            load: LoadKind::default_for_platform(),
+            check: None,
+        }
+    }
+}
+
+/// Median of the answered probes so far, for the live readout.
+fn median(rtts: &[Option<f64>]) -> String {
+    let mut replies: Vec<f64> = rtts.iter().flatten().copied().collect();
+    if replies.is_empty() {
+        return "—".to_string();
+    }
+    replies.sort_by(f64::total_cmp);
+    format!("{:.1} ms", replies[replies.len() / 2])
+}
+
+/// Color of a grade: A and B are fine, C is noticeable, D and F hurt.
+fn grade_color(visuals: &PingVisuals, grade: char) -> Color32 {
+    let [r, g, b] = match grade {
+        'A' | 'B' => visuals.colors.ok,
+        'C' => visuals.colors.warn,
+        _ => visuals.colors.loss,
+    };
+    Color32::from_rgb(r, g, b)
+}
+
+fn render_result(ui: &mut egui::Ui, visuals: &PingVisuals, result: &BufferbloatResult) {
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new(result.grade.to_string())
+                .size(40.0)
+                .strong()
+                .color(grade_color(visuals, result.grade)),
+        );
+        ui.vertical(|ui| {
+            let increase = result.increase_ms().map_or_else(
+                || tr!("no answer under load"),
+                |ms| format!("+{:.1} ms", ms),
+            );
+            ui.strong(tr!("Latency under load: {increase}").replace("{increase}", &increase));
+            ui.label(
+                tr!("Idle: {idle} ms, {loss}% loss")
+                    .replace("{idle}", &format!("{:.1}", result.idle_ms))
+                    .replace("{loss}", &format!("{:.0}", result.idle_loss_pct)),
+            );
+            let loaded = result
+                .loaded_ms
+                .map_or_else(|| "—".to_string(), |ms| format!("{:.1}", ms));
+            ui.label(
+                tr!("Loaded: {loaded} ms, {loss}% loss")
+                    .replace("{loaded}", &loaded)
+                    .replace("{loss}", &format!("{:.0}", result.loaded_loss_pct)),
+            );
+        });
+    });
+}
+
+/// Renders the bufferbloat check window: latency idle versus under load and the
+/// grade of the increase.
+pub fn render_bufferbloat_window(
+    ctx: &egui::Context,
+    visuals: &PingVisuals,
+    state: &mut Option<BufferbloatState>,
+    proxy: &ProxySettings,
+) {
+    let Some(bb) = state.as_mut() else {
+        return;
+    };
+    let progress = bb.check.as_ref().map(|c| c.progress());
+    let running = progress
+        .as_ref()
+        .is_some_and(|p| p.phase != BufferbloatPhase::Done);
+    let mut open = true;
+
+    egui::Window::new(format!("{} - {}", tr!("Bufferbloat"), bb.target))
+        .open(&mut open)
+        .resizable(false)
+        .default_width(380.0)
+        .show(ctx, |ui| {
+            ui.label(tr!(
+                "Measures the latency while the connection is idle and again while it is saturated. A large increase means oversized buffers, felt as lag in calls and games whenever someone uploads or downloads."
+            ));
+            ui.add_space(4.0);
+            ui.add_enabled_ui(!running, |ui| {
+                egui::ComboBox::from_label(tr!("Load"))
+                    .selected_text(bb.load.label())
+                    .show_ui(ui, |ui| {
+                        for kind in LoadKind::ALL {
+                            ui.selectable_value(&mut bb.load, kind, kind.label());
+                        }
+                    });
+            });
+            ui.label(
+                RichText::new(
+                    tr!("The check takes {total} seconds and saturates the connection for {load} of them.")
+                        .replace(
+                            "{total}",
+                            &(BUFFERBLOAT_IDLE_SEC + BUFFERBLOAT_LOAD_SEC).to_string(),
+                        )
+                        .replace("{load}", &BUFFERBLOAT_LOAD_SEC.to_string()),
+                )
+                .small(),
+            );
+            ui.add_space(4.0);
+            if running {
+                if ui.button(tr!("Stop")).clicked() {
+                    bb.check = None;
+                }
+            } else if ui.button(tr!("Start")).clicked() {
+                bb.check = Some(BufferbloatCheck::start(
+                    bb.target.clone(),
+                    bb.load,
+                    proxy.clone(),
+                ));
+            }
+
+            let Some(p) = &progress else {
+                return;
+            };
+            ui.separator();
+            if let Some(e) = &p.error {
+                ui.colored_label(Color32::from_rgb(213, 94, 0), e);
+                return;
+            }
+            match p.phase {
+                BufferbloatPhase::Idle => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(tr!("Measuring idle latency..."));
+                    });
+                }
+                BufferbloatPhase::Loaded => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(tr!("Measuring latency under load..."));
+                    });
+                }
+                BufferbloatPhase::Done => {}
+            }
+            if let Some(result) = &p.result {
+                render_result(ui, visuals, result);
+            } else {
+                ui.label(format!("{}: {}", tr!("Idle"), median(&p.idle)));
+                if p.phase == BufferbloatPhase::Loaded {
+                    ui.label(format!("{}: {}", tr!("Loaded"), median(&p.loaded)));
+                }
+            }
+            if let Some(e) = &p.load_error {
+                ui.colored_label(
+                    Color32::from_rgb(213, 94, 0),
+                    tr!("The connection may not have been saturated: {error}")
+                        .replace("{error}", e),
+                );
+            }
+        });
+
+    if !open {
+        *state = None;
+    } else if running {
+        ctx.request_repaint_after(Duration::from_millis(250));
+    }
+}
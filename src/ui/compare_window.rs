@@ -0,0 +1,132 @@
+use crate::app::PingVisuals;
+use crate::model::AppState;
+use crate::ui::plot_window::time_of;
+use eframe::egui;
+use eframe::egui::Color32;
+use egui_plot::{Legend, Line, MarkerShape, Plot, PlotPoint, Points};
+use tr::tr;
+
+/// Series colors, distinguishable with color vision deficiencies (Okabe-Ito).
+const SERIES_COLORS: [Color32; 7] = [
+    Color32::from_rgb(0, 114, 178),
+    Color32::from_rgb(230, 159, 0),
+    Color32::from_rgb(0, 158, 115),
+    Color32::from_rgb(204, 121, 167),
+    Color32::from_rgb(86, 180, 233),
+    Color32::from_rgb(213, 94, 0),
+    Color32::from_rgb(240, 228, 66),
+];
+
+/// Renders the window plotting the RTT of the selected hosts on one time axis.
+///
+/// `selected` holds the addresses of the compared hosts, in the order they were picked.
+pub fn render_compare_window(
+    ctx: &egui::Context,
+    visuals: &PingVisuals,
+    state: &AppState,
+    selected: &mut Vec<String>,
+    open: &mut bool,
+) {
+    let label_of = |address: &str| {
+        state
+            .hosts
+            .iter()
+            .find(|h| h.address == address)
+            .filter(|h| !h.name.is_empty())
+            .map_or_else(|| address.to_string(), |h| h.name.clone())
+    };
+    // Hosts deleted since they were picked
+    selected.retain(|a| state.statuses.contains_key(a));
+
+    egui::Window::new(tr!("Compare Hosts"))
+        .open(open)
+        .resizable(true)
+        .default_size([900.0, 450.0])
+        .show(ctx, |ui| {
+            egui::SidePanel::left("compare_hosts")
+                .resizable(true)
+                .default_width(180.0)
+                .show_inside(ui, |ui| {
+                    ui.label(egui::RichText::new(tr!("Hosts to compare:")).strong());
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        for host in &state.hosts {
+                            let mut on = selected.contains(&host.address);
+                            let text = if host.name.is_empty() {
+                                host.address.clone()
+                            } else {
+                                format!("{} ({})", host.name, host.address)
+                            };
+                            if ui.checkbox(&mut on, text).changed() {
+                                if on {
+                                    selected.push(host.address.clone());
+                                } else {
+                                    selected.retain(|a| *a != host.address);
+                                }
+                            }
+                        }
+                    });
+                });
+
+            if selected.is_empty() {
+                ui.centered_and_justified(|ui| {
+                    ui.label(tr!("Select hosts on the left to plot their RTT together."));
+                });
+                return;
+            }
+            ui.label(
+                egui::RichText::new(tr!(
+                    "Scroll or drag to zoom and pan, double-click to reset. Crosses mark lost probes."
+                ))
+                .small(),
+            );
+            Plot::new("compare_plot")
+                .legend(Legend::default())
+                .x_axis_formatter(|mark, _| time_of(mark.value))
+                .y_axis_label(tr!("RTT, ms"))
+                .label_formatter(|name, value: &PlotPoint| {
+                    let time = time_of(value.x);
+                    if name.is_empty() {
+                        format!("{}\n{:.1} {}", time, value.y, tr!("ms"))
+                    } else {
+                        format!("{}\n{}\n{:.1} {}", name, time, value.y, tr!("ms"))
+                    }
+                })
+                .include_y(0.0)
+                .show(ui, |plot_ui| {
+                    for (i, address) in selected.iter().enumerate() {
+                        let Some(status) = state.statuses.get(address) else {
+                            continue;
+                        };
+                        let color = SERIES_COLORS[i % SERIES_COLORS.len()];
+                        let name = label_of(address);
+                        let rtt: Vec<[f64; 2]> = status
+                            .history
+                            .iter()
+                            .filter_map(|s| s.rtt.map(|r| [s.timestamp as f64, r]))
+                            .collect();
+                        let losses: Vec<[f64; 2]> = status
+                            .history
+                            .iter()
+                            .filter(|s| s.is_lost())
+                            .map(|s| [s.timestamp as f64, 0.0])
+                            .collect();
+                        plot_ui.line(Line::new(name.clone(), rtt).color(color).width(1.5));
+                        if !losses.is_empty() {
+                            plot_ui.points(
+                                Points::new(name, losses)
+                                    .shape(MarkerShape::Cross)
+                                    .radius(4.0)
+                                    .color(color),
+                            );
+                        }
+                    }
+                    for note in &state.annotations {
+                        plot_ui.vline(
+                            egui_plot::VLine::new("", note.timestamp as f64)
+                                .color(visuals.annotation_color())
+                                .width(1.0),
+                        );
+                    }
+                });
+        });
+}
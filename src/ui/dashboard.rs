@@ -0,0 +1,74 @@
+use crate::logic::keep_awake::KeepAwake;
+use crate::model::DashboardSettings;
+use eframe::egui;
+use std::time::Instant;
+use tr::tr;
+
+/// State of the wall dashboard while it is shown.
+pub struct DashboardState {
+    /// Zoom factor to restore when leaving.
+    pub previous_zoom: f32,
+    /// Group scrolled to last.
+    pub group: usize,
+    pub last_scroll: Instant,
+    _keep_awake: KeepAwake,
+}
+
+impl DashboardState {
+    /// Enters the dashboard: full screen, zoomed in, with the screen kept awake.
+    pub fn enter(ctx: &egui::Context, settings: &DashboardSettings) -> Self {
+        let previous_zoom = ctx.zoom_factor();
+        ctx.set_zoom_factor(settings.zoom);
+        ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(true));
+        Self {
+            previous_zoom,
+            group: 0,
+            last_scroll: Instant::now(),
+            _keep_awake: KeepAwake::start(),
+        }
+    }
+
+    /// Leaves the dashboard, restoring the window and the zoom.
+    pub fn leave(self, ctx: &egui::Context) {
+        ctx.set_zoom_factor(self.previous_zoom);
+        ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(false));
+    }
+
+    /// The group to scroll to now, if one is due: every `scroll_sec` the next of
+    /// `groups`, starting over after the last.
+    pub fn scroll_target(&mut self, groups: usize, scroll_sec: u32) -> Option<usize> {
+        if scroll_sec == 0 || groups < 2 || self.last_scroll.elapsed().as_secs() < scroll_sec as u64
+        {
+            return None;
+        }
+        self.last_scroll = Instant::now();
+        self.group = (self.group + 1) % groups;
+        Some(self.group)
+    }
+}
+
+/// Renders the dashboard section of the settings window.
+pub fn render_dashboard_section(ui: &mut egui::Ui, settings: &mut DashboardSettings) {
+    ui.horizontal(|ui| {
+        ui.label(tr!("Zoom:"));
+        ui.add(
+            egui::DragValue::new(&mut settings.zoom)
+                .range(1.0..=4.0)
+                .speed(0.05)
+                .suffix("×"),
+        );
+        ui.label(tr!("Next group every"));
+        ui.add(
+            egui::DragValue::new(&mut settings.scroll_sec)
+                .range(0..=600)
+                .suffix(tr!(" s")),
+        )
+        .on_hover_text(tr!("0 does not scroll"));
+    });
+    ui.label(
+        egui::RichText::new(tr!(
+            "View → Wall dashboard or the --kiosk option shows the hosts full screen without editing controls, keeps the screen awake and scrolls through the groups. Esc leaves it."
+        ))
+        .small(),
+    );
+}
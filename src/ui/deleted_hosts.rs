@@ -0,0 +1,91 @@
+use crate::logic::presenter::format_time;
+use crate::model::AppState;
+use eframe::egui;
+use eframe::egui::RichText;
+use tr::tr;
+
+/// Renders the window listing deleted hosts whose history is still retained.
+pub fn render_deleted_hosts_window(ctx: &egui::Context, state: &mut AppState, open: &mut bool) {
+    let now = chrono::Utc::now().timestamp() as u64;
+    state.purge_expired_deleted_hosts(now);
+
+    let mut restore = None;
+    let mut purge = None;
+    let mut purge_all = false;
+
+    egui::Window::new(tr!("Deleted Hosts"))
+        .open(open)
+        .resizable(true)
+        .default_width(500.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.checkbox(
+                    &mut state.settings.retain_deleted_hosts,
+                    tr!("Keep history of deleted hosts for"),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut state.settings.deleted_host_retention_days)
+                        .range(1..=3650)
+                        .suffix(tr!(" days")),
+                );
+            });
+            ui.separator();
+
+            if state.deleted_hosts.is_empty() {
+                ui.label(tr!("No deleted hosts."));
+                return;
+            }
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("deleted_hosts_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong(tr!("Host"));
+                        ui.strong(tr!("Deleted"));
+                        ui.strong(tr!("Purge"));
+                        ui.strong(tr!("Availability"));
+                        ui.label("");
+                        ui.end_row();
+
+                        for d in &state.deleted_hosts {
+                            ui.label(format!("{} ({})", d.host.name, d.host.address));
+                            ui.label(format_time(d.deleted_at));
+                            ui.label(format_time(d.purge_at));
+                            ui.label(format!(
+                                "{:.1}% ({}/{})",
+                                d.snapshot.availability,
+                                d.snapshot.sent - d.snapshot.lost.min(d.snapshot.sent),
+                                d.snapshot.sent
+                            ));
+                            ui.horizontal(|ui| {
+                                if ui.button(tr!("Restore")).clicked() {
+                                    restore = Some(d.host.address.clone());
+                                }
+                                if ui.button(tr!("Purge now")).clicked() {
+                                    purge = Some(d.host.address.clone());
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+            });
+
+            ui.add_space(8.0);
+            if ui
+                .button(RichText::new(tr!("Purge all")).strong())
+                .clicked()
+            {
+                purge_all = true;
+            }
+        });
+
+    if let Some(address) = restore {
+        state.restore_deleted_host(&address);
+    }
+    if let Some(address) = purge {
+        state.purge_deleted_host(&address);
+    }
+    if purge_all {
+        state.deleted_hosts.clear();
+    }
+}
@@ -0,0 +1,101 @@
+use crate::model::{AppState, HostInfo, HostStatus};
+use eframe::egui;
+use tr::tr;
+
+/// A host being duplicated: the original and the name and address of the copy.
+pub struct DuplicateHostState {
+    pub source: String,
+    pub name: String,
+    pub address: String,
+}
+
+impl DuplicateHostState {
+    pub fn new(source: &HostInfo) -> Self {
+        Self {
+            source: source.address.clone(),
+            name: String::new(),
+            address: String::new(),
+        }
+    }
+}
+
+/// Renders the dialog asking for the address of a copy of a host. The copy is
+/// added after the original and opened in the host settings.
+pub fn render_duplicate_host_window(
+    ctx: &egui::Context,
+    duplicating: &mut Option<DuplicateHostState>,
+    state: &mut AppState,
+    editing_host: &mut Option<String>,
+) {
+    let Some(dup) = duplicating.as_mut() else {
+        return;
+    };
+    let Some(source_idx) = state.hosts.iter().position(|h| h.address == dup.source) else {
+        *duplicating = None;
+        return;
+    };
+
+    let address = HostInfo::normalize_address(&dup.address);
+    let taken = state.hosts.iter().any(|h| h.address == address);
+    let mut open = true;
+    let mut done = false;
+    let mut cancelled = false;
+    egui::Window::new(tr!("Duplicate Host"))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(
+                tr!("New host with the settings of {host}")
+                    .replace("{host}", &state.hosts[source_idx].address),
+            );
+            ui.add_space(4.0);
+            egui::Grid::new("duplicate_host")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label(format!("{}:", tr!("Name")));
+                    ui.text_edit_singleline(&mut dup.name);
+                    ui.end_row();
+                    ui.label(format!("{}:", tr!("Address")));
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut dup.address)
+                            .hint_text(tr!("Host address or URL")),
+                    );
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        done = !address.is_empty() && !taken;
+                    }
+                    ui.end_row();
+                });
+            if taken {
+                ui.colored_label(
+                    egui::Color32::RED,
+                    tr!("This address is already in the list"),
+                );
+            }
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(!address.is_empty() && !taken, egui::Button::new(tr!("Add")))
+                    .clicked()
+                {
+                    done = true;
+                }
+                if ui.button(tr!("Cancel")).clicked() {
+                    cancelled = true;
+                }
+            });
+        });
+
+    if done {
+        let copy = state.hosts[source_idx].duplicate(dup.name.trim().to_string(), address.clone());
+        state
+            .statuses
+            .insert(address.clone(), HostStatus::default());
+        state.hosts.insert(source_idx + 1, copy);
+        *editing_host = Some(address);
+        open = false;
+    }
+    if !open || cancelled {
+        *duplicating = None;
+    }
+}
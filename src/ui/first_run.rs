@@ -0,0 +1,44 @@
+use crate::logic::{SharedState, netconfig};
+use eframe::egui;
+use eframe::egui::RichText;
+use tr::tr;
+
+/// Renders the welcome window offering to fill an empty host list with the
+/// gateway, the DNS servers of the system and public resolvers.
+pub fn render_first_run_window(ctx: &egui::Context, state: &SharedState, open: &mut bool) {
+    let mut answered = false;
+    egui::Window::new(tr!("Welcome"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label(tr!(
+                "The host list is empty. Add the hosts that tell where a connection problem is?"
+            ));
+            ui.add_space(4.0);
+            ui.label(
+                RichText::new(tr!(
+                    "The default gateway and the DNS servers of this computer are detected and added together with 1.1.1.1 and 8.8.8.8. Local hosts are pinged more often."
+                ))
+                .small(),
+            );
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button(tr!("Set me up")).clicked() {
+                    netconfig::spawn_first_run_setup(state);
+                    answered = true;
+                }
+                if ui.button(tr!("No thanks")).clicked() {
+                    answered = true;
+                }
+            });
+        });
+    if answered {
+        state
+            .lock()
+            .expect("State mutex poisoned")
+            .settings
+            .setup_offered = true;
+        *open = false;
+    }
+}
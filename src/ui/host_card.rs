@@ -0,0 +1,131 @@
+use crate::constants::{GRID_CARD_WIDTH, GRID_SPARKLINE_SAMPLES};
+use crate::logic::presenter;
+use crate::model::{HostInfo, HostStatus};
+use eframe::egui;
+use eframe::egui::{RichText, Sense, Stroke, pos2, vec2};
+use tr::tr;
+
+/// Height (points) of the sparkline of a host card.
+const SPARKLINE_HEIGHT: f32 = 28.0;
+
+/// Renders a host as a card of the grid layout: status color, name, latency and a
+/// sparkline of the recent samples. Clicking it opens the detailed plot.
+pub fn render_host_card(
+    ui: &mut egui::Ui,
+    visuals: &crate::app::PingVisuals,
+    host_info: &HostInfo,
+    status: &HostStatus,
+    viewing_plot: &mut Option<String>,
+) {
+    let color = visuals.status_color(
+        host_info.is_stopped || status.interface_down || (!status.alive && status.in_maintenance()),
+        status.alive,
+        status.latency,
+        &host_info.thresholds,
+    );
+    let result = if host_info.is_stopped {
+        tr!("STOPPED")
+    } else if status.sent == 0 {
+        "…".to_string()
+    } else if status.alive {
+        format!("{:.0} {}", status.latency, tr!("ms"))
+    } else if status.in_maintenance() {
+        tr!("MAINTENANCE")
+    } else {
+        tr!("DOWN")
+    };
+    let name = if host_info.name.is_empty() {
+        &host_info.address
+    } else {
+        &host_info.name
+    };
+
+    let frame = egui::Frame::group(ui.style())
+        .stroke(Stroke::new(1.5, color))
+        .inner_margin(6.0);
+    let response = frame
+        .show(ui, |ui| {
+            let inner_width = GRID_CARD_WIDTH - 12.0;
+            ui.set_width(inner_width);
+            ui.horizontal(|ui| {
+                ui.colored_label(color, RichText::new("●").strong());
+                ui.add(
+                    egui::Label::new(RichText::new(name).strong())
+                        .truncate()
+                        .selectable(false),
+                );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.colored_label(color, RichText::new(result).monospace().strong());
+                });
+            });
+
+            let (rect, _) =
+                ui.allocate_exact_size(vec2(inner_width, SPARKLINE_HEIGHT), Sense::hover());
+            let heights = presenter::sparkline(
+                &status.history,
+                GRID_SPARKLINE_SAMPLES,
+                host_info.thresholds.latency_warn,
+            );
+            let painter = ui.painter_at(rect);
+            let step = rect.width() / GRID_SPARKLINE_SAMPLES.max(2) as f32;
+            let x_of = |i: usize| rect.right() - (heights.len() - 1 - i) as f32 * step;
+            let mut line = Vec::new();
+            for (i, height) in heights.iter().enumerate() {
+                match height {
+                    Some(h) => line.push(pos2(x_of(i), rect.bottom() - h * rect.height())),
+                    None => {
+                        let segment = std::mem::take(&mut line);
+                        if segment.len() > 1 {
+                            painter.line(segment, Stroke::new(1.5, color));
+                        }
+                        painter.line_segment(
+                            [pos2(x_of(i), rect.top()), pos2(x_of(i), rect.bottom())],
+                            Stroke::new(
+                                1.0,
+                                visuals.host_latency_color(f64::NAN, &host_info.thresholds),
+                            ),
+                        );
+                    }
+                }
+            }
+            if line.len() > 1 {
+                painter.line(line, Stroke::new(1.5, color));
+            }
+        })
+        .response
+        .interact(Sense::click());
+
+    let mut hover = vec![host_info.address.clone()];
+    if !host_info.notes.trim().is_empty() {
+        hover.push(host_info.notes.trim().to_string());
+    }
+    if status.sent > 0 {
+        hover.push(presenter::stats_line(&presenter::stat_items(
+            &host_info.display,
+            &host_info.thresholds,
+            status,
+        )));
+    }
+    if response.on_hover_text(hover.join("\n")).clicked() {
+        *viewing_plot = Some(host_info.address.clone());
+    }
+}
+
+/// Renders hosts as cards in as many columns as fit the visible width.
+pub fn render_host_grid<'a>(
+    ui: &mut egui::Ui,
+    visuals: &crate::app::PingVisuals,
+    hosts: impl Iterator<Item = (&'a HostInfo, &'a HostStatus)>,
+    viewing_plot: &mut Option<String>,
+) {
+    let spacing = ui.spacing().item_spacing.x;
+    let columns = presenter::grid_columns(ui.clip_rect().width(), GRID_CARD_WIDTH, spacing);
+    let hosts: Vec<_> = hosts.collect();
+    for row in hosts.chunks(columns) {
+        ui.horizontal(|ui| {
+            for (host_info, status) in row {
+                render_host_card(ui, visuals, host_info, status, viewing_plot);
+            }
+        });
+    }
+}
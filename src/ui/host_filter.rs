@@ -0,0 +1,61 @@
+use crate::model::{HostLayout, HostSort};
+use eframe::egui;
+use tr::tr;
+
+/// Renders the search box, the tag choice, the sort order and the layout choice
+/// above the host list.
+pub fn render_host_filter_bar(
+    ui: &mut egui::Ui,
+    filter: &mut String,
+    tags: &[String],
+    tag_filter: &mut Option<String>,
+    sort: &mut HostSort,
+    reversed: &mut bool,
+    layout: &mut HostLayout,
+) {
+    ui.horizontal(|ui| {
+        ui.label("🔍");
+        ui.add(
+            egui::TextEdit::singleline(filter)
+                .hint_text(tr!("Filter by name, address, group or tag"))
+                .desired_width(220.0),
+        );
+        if !filter.is_empty() && ui.small_button("✖").on_hover_text(tr!("Clear")).clicked() {
+            filter.clear();
+        }
+        if !tags.is_empty() {
+            egui::ComboBox::from_id_salt("tag_filter")
+                .selected_text(tag_filter.clone().unwrap_or_else(|| tr!("All tags")))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(tag_filter, None, tr!("All tags"));
+                    for tag in tags {
+                        ui.selectable_value(tag_filter, Some(tag.clone()), tag);
+                    }
+                });
+        }
+        ui.separator();
+        ui.label(tr!("Sort:"));
+        egui::ComboBox::from_id_salt("host_sort")
+            .selected_text(sort.label())
+            .show_ui(ui, |ui| {
+                for option in HostSort::ALL {
+                    ui.selectable_value(sort, option, option.label());
+                }
+            });
+        if *sort != HostSort::Manual {
+            let arrow = if *reversed { "⬇" } else { "⬆" };
+            if ui
+                .button(arrow)
+                .on_hover_text(tr!("Reverse the order"))
+                .clicked()
+            {
+                *reversed = !*reversed;
+            }
+        }
+        ui.separator();
+        ui.selectable_value(layout, HostLayout::List, "☰")
+            .on_hover_text(tr!("List with all statistics"));
+        ui.selectable_value(layout, HostLayout::Grid, "▦")
+            .on_hover_text(tr!("Grid of cards, for many hosts"));
+    });
+}
@@ -0,0 +1,162 @@
+use crate::app::PingVisuals;
+use crate::logic::presenter::format_duration;
+use crate::model::{HostInfo, HostStatus};
+use eframe::egui::RichText;
+use tr::tr;
+
+/// Silence durations offered in the context menu of a group header, in minutes.
+pub const SILENCE_DURATIONS_MIN: [u64; 6] = [30, 60, 120, 240, 480, 1440];
+
+/// Label of a silence duration in the group context menu.
+pub fn silence_label(minutes: u64) -> String {
+    if minutes < 60 {
+        tr!("{n} min").replace("{n}", &minutes.to_string())
+    } else {
+        tr!("{n} h").replace("{n}", &(minutes / 60).to_string())
+    }
+}
+
+/// Aggregate status of the hosts in a group.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GroupSummary {
+    pub hosts: usize,
+    /// Monitored (not stopped) hosts that are down.
+    pub down: usize,
+    /// Name of the worst host and its latency (`None` if it is down).
+    pub worst: Option<(String, Option<f64>)>,
+    /// Average packet loss over monitored hosts, in percent.
+    pub avg_loss: f64,
+}
+
+impl GroupSummary {
+    /// Summarizes a group: a down host is worse than any live one, otherwise
+    /// the host with the highest latency is the worst. Stopped hosts are ignored.
+    pub fn compute<'a>(members: impl IntoIterator<Item = (&'a HostInfo, &'a HostStatus)>) -> Self {
+        let mut summary = Self::default();
+        let mut losses = Vec::new();
+        for (host, status) in members {
+            summary.hosts += 1;
+            if host.is_stopped || status.sent == 0 {
+                continue;
+            }
+            losses.push(status.lost as f64 / status.sent as f64 * 100.0);
+            let latency = status.alive.then_some(status.latency);
+            if latency.is_none() {
+                summary.down += 1;
+            }
+            let worse = match &summary.worst {
+                None => true,
+                Some((_, None)) => false,
+                Some((_, Some(worst))) => latency.is_none_or(|l| l > *worst),
+            };
+            if worse {
+                let name = if host.name.is_empty() {
+                    host.address.clone()
+                } else {
+                    host.name.clone()
+                };
+                summary.worst = Some((name, latency));
+            }
+        }
+        if !losses.is_empty() {
+            summary.avg_loss = losses.iter().sum::<f64>() / losses.len() as f64;
+        }
+        summary
+    }
+
+    /// Header text of a collapsible group, colored by the state of its worst host.
+    ///
+    /// `silenced_for` is the remaining time of an alert silence in seconds.
+    pub fn header(
+        &self,
+        group: &str,
+        visuals: &PingVisuals,
+        silenced_for: Option<u64>,
+    ) -> RichText {
+        let mut text = format!("{} ({})", group, self.hosts);
+        if let Some(secs) = silenced_for {
+            text.push_str(&format!("  🔕 {}", format_duration(secs)));
+        }
+        let color = match &self.worst {
+            Some((name, latency)) => {
+                let state = match latency {
+                    Some(ms) => format!("{:.0} {}", ms, tr!("ms")),
+                    None => tr!("DOWN"),
+                };
+                text.push_str(&format!(
+                    "  ·  {}: {} {}  ·  {}: {:.1}%",
+                    tr!("worst"),
+                    name,
+                    state,
+                    tr!("avg loss"),
+                    self.avg_loss
+                ));
+                if self.down > 1 {
+                    text.push_str(&format!(
+                        "  ·  {}",
+                        tr!("{count} down").replace("{count}", &self.down.to_string())
+                    ));
+                }
+                visuals.status_color(
+                    false,
+                    latency.is_some(),
+                    latency.unwrap_or(f64::NAN),
+                    &visuals.thresholds,
+                )
+            }
+            None => visuals.status_color(true, false, f64::NAN, &visuals.thresholds),
+        };
+        RichText::new(text).strong().monospace().color(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(name: &str, samples: &[Option<f64>], stopped: bool) -> (HostInfo, HostStatus) {
+        let host = HostInfo {
+            name: name.to_string(),
+            address: name.to_lowercase(),
+            is_stopped: stopped,
+            ..Default::default()
+        };
+        let mut status = HostStatus::default();
+        for s in samples {
+            status.add_sample(s.unwrap_or(f64::NAN), s.is_some());
+        }
+        (host, status)
+    }
+
+    #[test]
+    fn test_down_host_is_worst() {
+        let members = [
+            member("A", &[Some(10.0), Some(12.0)], false),
+            member("B", &[Some(50.0), None], false),
+            member("C", &[Some(80.0)], false),
+            member("D", &[None, None], true),
+        ];
+        let summary = GroupSummary::compute(members.iter().map(|(h, s)| (h, s)));
+        assert_eq!(summary.hosts, 4);
+        assert_eq!(summary.down, 1);
+        assert_eq!(summary.worst, Some(("B".to_string(), None)));
+        assert!((summary.avg_loss - 50.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_silence_label() {
+        assert_eq!(silence_label(30), "30 min");
+        assert_eq!(silence_label(120), "2 h");
+    }
+
+    #[test]
+    fn test_highest_latency_is_worst() {
+        let members = [
+            member("A", &[Some(10.0)], false),
+            member("B", &[Some(80.0)], false),
+        ];
+        let summary = GroupSummary::compute(members.iter().map(|(h, s)| (h, s)));
+        assert_eq!(summary.worst, Some(("B".to_string(), Some(80.0))));
+        assert_eq!(summary.avg_loss, 0.0);
+    }
+}
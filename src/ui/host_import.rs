@@ -0,0 +1,199 @@
+use crate::logic::host_import::{self, ImportPlan, Resolution};
+use crate::model::AppState;
+use eframe::egui;
+use eframe::egui::RichText;
+use tr::tr;
+
+/// State of the host import window between frames.
+#[derive(Default)]
+pub struct HostImportState {
+    /// Host list file to import.
+    pub path: String,
+    /// Loaded import waiting for the conflicts to be resolved.
+    pub plan: Option<ImportPlan>,
+    /// What the last import changed, or why the file could not be loaded.
+    pub summary: Vec<String>,
+}
+
+/// Short form of a setting value for the conflict table.
+fn value_text(value: &serde_json::Value) -> String {
+    let text = match value {
+        serde_json::Value::String(s) => format!("\"{}\"", s),
+        other => other.to_string(),
+    };
+    if text.chars().count() > 40 {
+        format!("{}…", text.chars().take(39).collect::<String>())
+    } else {
+        text
+    }
+}
+
+/// Renders the window importing hosts from a file, resolving conflicts with
+/// existing hosts before anything is changed.
+pub fn render_host_import_window(
+    ctx: &egui::Context,
+    import: &mut HostImportState,
+    state: &mut AppState,
+    open: &mut bool,
+) {
+    egui::Window::new(tr!("Import Hosts"))
+        .open(open)
+        .resizable(true)
+        .default_width(600.0)
+        .show(ctx, |ui| {
+            ui.label(
+                RichText::new(tr!(
+                    "A JSON host list or plain text with one \"address [name]\" per line. New hosts are added at the end in the order of the file; hosts that already exist with different settings are listed below to choose what to keep."
+                ))
+                .small(),
+            );
+            ui.horizontal(|ui| {
+                ui.label(tr!("File:"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut import.path)
+                        .hint_text("/path/to/hosts.json")
+                        .desired_width(320.0),
+                );
+                if ui
+                    .add_enabled(!import.path.trim().is_empty(), egui::Button::new(tr!("Load")))
+                    .clicked()
+                {
+                    let result = std::fs::read_to_string(import.path.trim())
+                        .map_err(|e| e.to_string())
+                        .and_then(|text| host_import::plan_import(state, &text));
+                    match result {
+                        Ok(plan) => {
+                            import.plan = Some(plan);
+                            import.summary.clear();
+                        }
+                        Err(e) => {
+                            import.plan = None;
+                            import.summary = vec![format!("{}: {}", tr!("Error"), e)];
+                        }
+                    }
+                }
+            });
+
+            let mut apply = false;
+            let mut cancel = false;
+            if let Some(plan) = &mut import.plan {
+                ui.separator();
+                ui.label(
+                    tr!("{added} new, {conflicts} conflicting, {unchanged} unchanged")
+                        .replace("{added}", &plan.added.len().to_string())
+                        .replace("{conflicts}", &plan.conflicts.len().to_string())
+                        .replace("{unchanged}", &plan.unchanged.to_string()),
+                );
+                if !plan.conflicts.is_empty() {
+                    ui.horizontal(|ui| {
+                        if ui.button(tr!("Keep all local")).clicked() {
+                            for conflict in &mut plan.conflicts {
+                                conflict.resolution = Resolution::KeepLocal;
+                            }
+                        }
+                        if ui.button(tr!("Take all imported")).clicked() {
+                            for conflict in &mut plan.conflicts {
+                                conflict.resolution = Resolution::TakeImported;
+                            }
+                        }
+                    });
+                }
+                egui::ScrollArea::vertical()
+                    .max_height(350.0)
+                    .show(ui, |ui| {
+                        for conflict in &mut plan.conflicts {
+                            ui.group(|ui| {
+                                ui.horizontal(|ui| {
+                                    ui.strong(format!("{} ({})", conflict.name, conflict.address));
+                                    ui.radio_value(
+                                        &mut conflict.resolution,
+                                        Resolution::KeepLocal,
+                                        tr!("Keep local"),
+                                    );
+                                    ui.radio_value(
+                                        &mut conflict.resolution,
+                                        Resolution::TakeImported,
+                                        tr!("Take imported"),
+                                    );
+                                    ui.radio_value(
+                                        &mut conflict.resolution,
+                                        Resolution::Merge,
+                                        tr!("Merge"),
+                                    );
+                                });
+                                let merging = conflict.resolution == Resolution::Merge;
+                                egui::Grid::new(("import_conflict", &conflict.address))
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        ui.strong(tr!("Setting"));
+                                        ui.strong(tr!("Local"));
+                                        ui.strong(tr!("Imported"));
+                                        ui.end_row();
+                                        for field in &mut conflict.fields {
+                                            ui.label(&field.field);
+                                            let take = match conflict.resolution {
+                                                Resolution::KeepLocal => false,
+                                                Resolution::TakeImported => true,
+                                                Resolution::Merge => field.take_imported,
+                                            };
+                                            let mut local = !take;
+                                            if ui
+                                                .add_enabled(
+                                                    merging,
+                                                    egui::RadioButton::new(
+                                                        local,
+                                                        value_text(&field.local),
+                                                    ),
+                                                )
+                                                .clicked()
+                                            {
+                                                local = true;
+                                            }
+                                            if ui
+                                                .add_enabled(
+                                                    merging,
+                                                    egui::RadioButton::new(
+                                                        !local,
+                                                        value_text(&field.imported),
+                                                    ),
+                                                )
+                                                .clicked()
+                                            {
+                                                local = false;
+                                            }
+                                            if merging {
+                                                field.take_imported = !local;
+                                            }
+                                            ui.end_row();
+                                        }
+                                    });
+                            });
+                        }
+                        if !plan.added.is_empty() {
+                            ui.label(RichText::new(tr!("New hosts:")).strong());
+                            for host in &plan.added {
+                                ui.label(format!("+ {} {}", host.address, host.name));
+                            }
+                        }
+                    });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    apply = ui.button(tr!("Apply")).clicked();
+                    cancel = ui.button(tr!("Cancel")).clicked();
+                });
+            }
+            if apply && let Some(plan) = import.plan.take() {
+                import.summary = host_import::apply_import(state, plan).lines();
+            }
+            if cancel {
+                import.plan = None;
+            }
+
+            if !import.summary.is_empty() {
+                ui.separator();
+                for line in &import.summary {
+                    ui.label(line);
+                }
+            }
+        });
+}
@@ -1,8 +1,13 @@
-use crate::constants::RTT_WARNING_THRESHOLD_MS;
-use crate::model::{HostInfo, HostStatus};
+use crate::constants::{
+    ADDRESS_CHANGE_HIGHLIGHT_SEC, HISTORY_WINDOW_SIZE, THRESHOLD_WARNING_FRACTION,
+};
+use crate::logic::alerts;
+use crate::logic::presenter::{self, StatItem};
+use crate::model::{AddressFamily, Annotation, HostInfo, HostStatus, SampleOutcome, Thresholds};
+use crate::ui::plots;
 use eframe::egui;
 use eframe::egui::{Color32, RichText};
-use egui_plot::{Bar, BarChart, HLine, Plot};
+use egui_plot::{Bar, BarChart, HLine, Plot, Polygon, VLine};
 use tr::tr;
 
 /// Renders a single host row with controls and a mini-chart.
@@ -12,32 +17,64 @@ pub fn render_host_row(
     visuals: &crate::app::PingVisuals,
     host_info: &HostInfo,
     status: &HostStatus,
+    annotations: &[Annotation],
     idx: usize,
     deleting_host: &mut Option<String>,
     editing_host: &mut Option<String>,
     viewing_route: &mut Option<String>,
     viewing_log: &mut Option<String>,
+    viewing_plot: &mut Option<String>,
     toggled_stop: &mut Option<usize>,
     moved: &mut Option<(usize, usize)>,
+    tag_filter: &mut Option<String>,
+    reorderable: bool,
+    behind_portal: bool,
+    locked: bool,
 ) {
-    let color = visuals.status_color(host_info.is_stopped, status.alive, status.latency);
+    let color = visuals.status_color(
+        host_info.is_stopped || status.interface_down || (!status.alive && status.in_maintenance()),
+        status.alive,
+        status.latency,
+        &host_info.thresholds,
+    );
 
     let mut parts = Vec::new();
     if host_info.display.show_name {
-        parts.push(host_info.name.clone());
+        match &status.dhcp_name {
+            Some(dhcp_name) if host_info.name.is_empty() => parts.push(format!("({})", dhcp_name)),
+            _ => parts.push(host_info.name.clone()),
+        }
     }
     if host_info.display.show_address {
         parts.push(host_info.address.clone());
+        if let Some(ip) = &status.resolved_ip {
+            // Recently changed addresses are marked until the change is old news
+            let changed = status.address_change.as_ref().is_some_and(|(_, ts)| {
+                (chrono::Utc::now().timestamp() as u64).saturating_sub(*ts)
+                    < ADDRESS_CHANGE_HIGHLIGHT_SEC
+            });
+            parts.push(format!("[{}{}]", ip, if changed { " ↻" } else { "" }));
+        }
     }
     parts.push("→".to_string());
 
     if host_info.display.show_latency {
         if host_info.is_stopped {
             parts.push(tr!("STOPPED").to_string());
+        } else if status.interface_down {
+            parts.push(tr!("INTERFACE DOWN").to_string());
+        } else if status.dns_mismatch.is_some() {
+            parts.push(tr!("WRONG DNS ANSWER").to_string());
         } else if status.dns_error {
             parts.push(tr!("UNKNOWN NAME").to_string());
         } else if status.alive {
             parts.push(format!("{:4.0}{}", status.latency, tr!("ms")));
+        } else if status.in_maintenance() {
+            parts.push(tr!("MAINTENANCE").to_string());
+        } else if behind_portal {
+            parts.push(tr!("CAPTIVE PORTAL").to_string());
+        } else if let Some(code) = status.http_status {
+            parts.push(format!("HTTP {}", code));
         } else {
             let down_text = if let Some(ref fp) = status.failure_point {
                 let translated_fp = if fp == "Local Interface" {
@@ -53,275 +90,188 @@ pub fn render_host_row(
         }
     }
 
-    struct StatDisplay {
-        text: String,
-        tooltip: String,
-        color: Option<Color32>,
-    }
-    let mut stats = Vec::new();
-
-    if !host_info.is_stopped {
-        let loss_pct =
-            (status.lost as f64 / if status.sent == 0 { 1 } else { status.sent } as f64) * 100.0;
-
-        if host_info.display.show_mean {
-            stats.push(StatDisplay {
-                text: format!("{}: {:4.1}", tr!("M"), status.mean),
-                tooltip: tr!("Mean RTT").to_string(),
-                color: visuals.value_color(status.mean, 150.0, 300.0, false),
-            });
-        }
-        if host_info.display.show_median {
-            stats.push(StatDisplay {
-                text: format!("{}: {:4.1}", tr!("Med"), status.median),
-                tooltip: tr!("Median RTT").to_string(),
-                color: visuals.value_color(status.median, 150.0, 300.0, false),
-            });
-        }
-        if host_info.display.show_rtp_jitter {
-            stats.push(StatDisplay {
-                text: format!("{}: {:4.1}", tr!("J"), status.rtp_jitter),
-                tooltip: tr!("RTP Jitter").to_string(),
-                color: visuals.value_color(status.rtp_jitter, 20.0, 30.0, false),
-            });
-        }
-        if host_info.display.show_rtp_mean_jitter {
-            stats.push(StatDisplay {
-                text: format!("{}: {:4.1}", tr!("Jm"), status.rtp_jitter_mean),
-                tooltip: tr!("Mean Jitter").to_string(),
-                color: visuals.value_color(status.rtp_jitter_mean, 20.0, 30.0, false),
-            });
-        }
-        if host_info.display.show_rtp_median_jitter {
-            stats.push(StatDisplay {
-                text: format!("{}: {:4.1}", tr!("Jmed"), status.rtp_jitter_median),
-                tooltip: tr!("Median Jitter").to_string(),
-                color: visuals.value_color(status.rtp_jitter_median, 20.0, 30.0, false),
-            });
-        }
-        if host_info.display.show_mos {
-            stats.push(StatDisplay {
-                text: format!("{}: {:3.1}", tr!("MOS"), status.mos),
-                tooltip: tr!("Voice Quality (MOS)").to_string(),
-                color: visuals.value_color(status.mos, 4.0, 3.6, true),
-            });
-        }
-        if host_info.display.show_availability {
-            stats.push(StatDisplay {
-                text: format!("{}: {:3.0}%", tr!("Av"), status.availability),
-                tooltip: tr!("Availability").to_string(),
-                color: visuals.value_color(status.availability, 99.0, 95.0, true),
-            });
-        }
-        if host_info.display.show_outliers {
-            stats.push(StatDisplay {
-                text: format!("{}: {}", tr!("Out"), status.outliers),
-                tooltip: tr!("Outliers (Lags)").to_string(),
-                color: if status.outliers > 3 {
-                    Some(Color32::from_rgb(230, 159, 0))
-                } else {
-                    None
-                },
-            });
-        }
-        if host_info.display.show_streak {
-            let streak_type = if status.streak_success {
-                tr!("S")
-            } else {
-                tr!("F")
-            };
-            let c = if !status.streak_success && status.streak > 3 {
-                Some(Color32::from_rgb(213, 94, 0))
-            } else if !status.streak_success && status.streak > 1 {
-                Some(if visuals.is_dark {
-                    Color32::from_rgb(240, 228, 66)
-                } else {
-                    Color32::from_rgb(230, 159, 0)
-                })
-            } else {
-                None
-            };
-            stats.push(StatDisplay {
-                text: format!("{}: {}{}", tr!("Str"), streak_type, status.streak),
-                tooltip: tr!("Streak").to_string(),
-                color: c,
-            });
-        }
-        if host_info.display.show_stddev {
-            stats.push(StatDisplay {
-                text: format!("{}: {:4.1}", tr!("SD"), status.stddev),
-                tooltip: tr!("Standard Deviation").to_string(),
-                color: None,
-            });
-        }
-        if host_info.display.show_p95 {
-            stats.push(StatDisplay {
-                text: format!("95%: {:4.1}", status.p95),
-                tooltip: tr!("95th Percentile").to_string(),
-                color: visuals.value_color(status.p95, 150.0, 300.0, false),
-            });
-        }
-        if host_info.display.show_min_max {
-            stats.push(StatDisplay {
-                text: format!(
-                    "{}: {:1.0}-{:1.0}",
-                    tr!("m/M"),
-                    status.min_rtt,
-                    status.max_rtt
-                ),
-                tooltip: tr!("Min / Max RTT").to_string(),
-                color: None,
-            });
-        }
-        if host_info.display.show_loss {
-            stats.push(StatDisplay {
-                text: format!(
-                    "{}: {}/{} {:.1}%",
-                    tr!("L"),
-                    status.lost,
-                    status.sent,
-                    loss_pct
-                ),
-                tooltip: tr!("Packet Loss").to_string(),
-                color: visuals.value_color(loss_pct, 1.0, 3.0, false),
-            });
-        }
-    }
+    let stats = if host_info.is_stopped {
+        Vec::new()
+    } else {
+        presenter::stat_items(&host_info.display, &host_info.thresholds, status)
+    };
 
     let row_id = egui::Id::new("host_row").with(&host_info.address);
+    // Reserved below the row content so the threshold bar is drawn behind it
+    let threshold_bar = ui.painter().add(egui::Shape::Noop);
     let (inner_res, dropped_payload) = ui.dnd_drop_zone::<usize, ()>(egui::Frame::NONE, |ui| {
         ui.horizontal(|ui| {
-            // Drag handle
-            let handle_id = row_id.with("handle");
-            let handle_res = ui.dnd_drag_source(handle_id, idx, |ui| {
-                ui.label(RichText::new(" ☰ ").monospace().strong());
-            });
-            if handle_res.response.hovered() {
-                ui.ctx().set_cursor_icon(egui::CursorIcon::Grab);
-            }
+            // In kiosk mode only the read-only route view is available
+            if locked {
+                if ui.button("📍").clicked() {
+                    *viewing_route = Some(host_info.address.clone());
+                }
+            } else {
+                // Drag handle, only in the manual order
+                if reorderable {
+                    let handle_id = row_id.with("handle");
+                    let handle_res = ui.dnd_drag_source(handle_id, idx, |ui| {
+                        ui.label(RichText::new(" ☰ ").monospace().strong());
+                    });
+                    if handle_res.response.hovered() {
+                        ui.ctx().set_cursor_icon(egui::CursorIcon::Grab);
+                    }
+                } else {
+                    ui.label(RichText::new(" ☰ ").monospace().weak())
+                        .on_hover_text(tr!("Sort by Manual and clear the filter to reorder"));
+                }
 
-            // Host control buttons (positioned left for layout stability)
-            if ui.button("x").clicked() {
-                *deleting_host = Some(host_info.address.clone());
-            }
-            if ui.button("⚙").clicked() {
-                *editing_host = Some(host_info.address.clone());
-            }
-            if ui.button("📍").clicked() {
-                *viewing_route = Some(host_info.address.clone());
-            }
-            if ui.button("📋").on_hover_text(tr!("View Log")).clicked() {
-                *viewing_log = Some(host_info.address.clone());
+                // Host control buttons (positioned left for layout stability)
+                if ui
+                    .add_enabled(!host_info.shared, egui::Button::new("x"))
+                    .on_disabled_hover_text(tr!("Managed by the shared host list"))
+                    .clicked()
+                {
+                    *deleting_host = Some(host_info.address.clone());
+                }
+                if ui.button("⚙").clicked() {
+                    *editing_host = Some(host_info.address.clone());
+                }
+                if ui.button("📍").clicked() {
+                    *viewing_route = Some(host_info.address.clone());
+                }
+                if ui.button("📋").on_hover_text(tr!("View Log")).clicked() {
+                    *viewing_log = Some(host_info.address.clone());
+                }
+
+                let stop_icon = if host_info.is_stopped { "▶" } else { "⏹" };
+                let stop_tooltip = if host_info.is_stopped {
+                    tr!("Start monitoring")
+                } else {
+                    tr!("Stop monitoring")
+                };
+                if ui.button(stop_icon).on_hover_text(stop_tooltip).clicked() {
+                    *toggled_stop = Some(idx);
+                }
             }
 
-            let stop_icon = if host_info.is_stopped { "▶" } else { "⏹" };
-            let stop_tooltip = if host_info.is_stopped {
-                tr!("Start monitoring")
+            let interval_sec = host_info.mode.interval_secs();
+            let clicked = if host_info.display.heatmap_plot {
+                plots::render_heatmap(
+                    ui,
+                    visuals,
+                    status,
+                    annotations,
+                    interval_sec,
+                    &host_info.thresholds,
+                )
             } else {
-                tr!("Stop monitoring")
+                render_history_plot(
+                    ui,
+                    visuals,
+                    &host_info.address,
+                    status,
+                    annotations,
+                    interval_sec,
+                    host_info.display.long_term_plot,
+                    &host_info.thresholds,
+                )
             };
-            if ui.button(stop_icon).on_hover_text(stop_tooltip).clicked() {
-                *toggled_stop = Some(idx);
+            if clicked {
+                *viewing_plot = Some(host_info.address.clone());
             }
 
-            // Chart: thin bars — blue (<150ms), yellow/orange (>150ms),
-            // and vermilion (timeout) colors
-            let chart = BarChart::new(
-                String::new(),
-                status
-                    .history
+            if !status.active_alerts.is_empty() {
+                let details: Vec<String> = status
+                    .active_alerts
                     .iter()
-                    .enumerate()
-                    .map(|(i, &rtt)| {
-                        // For timeouts, display bar at warning threshold height
-                        let height = if rtt.is_nan() {
-                            RTT_WARNING_THRESHOLD_MS
-                        } else {
-                            rtt
-                        };
-                        let fill = visuals.latency_color(rtt);
-
-                        Bar::new(i as f64, height).width(1.0).fill(fill)
-                    })
-                    .collect(),
-            )
-            .allow_hover(false); // Disable built-in bar tooltips
-
-            // Ping history chart.
-            // To fill 300 bars of width 1.0 without gaps:
-            // 1. Set X bounds from -0.5 to 299.5 (300 units total).
-            // 2. Remove horizontal padding (margin_fraction).
-            let plot_res = Plot::new(format!("plot_{}", &host_info.address))
-                .height(30.0)
-                .width(300.0)
-                .show_axes(false)
-                .show_grid(false)
-                .show_x(false) // Disable built-in tooltip system
-                .show_y(false)
-                .allow_zoom(false)
-                .allow_drag(false)
-                .allow_scroll(false)
-                .set_margin_fraction(egui::Vec2::new(0.0, 0.05))
-                .include_x(-0.5)
-                .include_x(299.5)
-                .include_y(0.0)
-                .include_y(RTT_WARNING_THRESHOLD_MS)
-                .show(ui, |plot_ui: &mut egui_plot::PlotUi| {
-                    plot_ui.hline(
-                        HLine::new("", RTT_WARNING_THRESHOLD_MS)
-                            .color(visuals.limit_line_color())
-                            .width(1.0),
-                    );
-                    plot_ui.bar_chart(chart);
-                });
-
-            plot_res.response.on_hover_ui(|ui| {
-                if let Some(hover_pos) = ui.ctx().pointer_hover_pos() {
-                    let pos = plot_res.transform.value_from_position(hover_pos);
-                    let i = pos.x.round() as i32;
-                    if i >= 0 && i < status.history.len() as i32 {
-                        let rtt = status.history[i as usize];
-                        let text = if rtt.is_nan() {
-                            tr!("Timeout").to_string()
-                        } else {
-                            format!("{:.1} {}", rtt, tr!("ms"))
-                        };
-                        ui.horizontal(|ui| {
-                            ui.add_space(4.0);
-                            ui.label(text);
-                            ui.add_space(4.0);
-                        });
-                    }
-                }
-            });
+                    .map(|a| a.message.clone())
+                    .collect();
+                let [r, g, b] = visuals.colors.warn;
+                ui.colored_label(
+                    Color32::from_rgb(r, g, b),
+                    RichText::new("⚠").monospace().strong(),
+                )
+                .on_hover_text(details.join("\n"));
+            }
 
             // Label with host name, address, and current latency
-            ui.colored_label(
+            let label = ui.colored_label(
                 color,
                 RichText::new(format!("{}  ", parts.join(" ")))
                     .monospace()
                     .strong(),
             );
+            let mut hover = Vec::new();
+            if !host_info.notes.trim().is_empty() {
+                hover.push(host_info.notes.trim().to_string());
+            }
+            if let Some(reason) = status.dns_mismatch.as_ref().or(status.probe_error.as_ref()) {
+                hover.push(reason.clone());
+            }
+            if let Some(dhcp_name) = &status.dhcp_name {
+                hover.push(tr!("DHCP name: {name}").replace("{name}", dhcp_name));
+            }
+            if let Some((previous, ts)) = &status.address_change {
+                let time = chrono::DateTime::from_timestamp(*ts as i64, 0)
+                    .map(|t| {
+                        t.with_timezone(&chrono::Local)
+                            .format("%Y-%m-%d %H:%M:%S")
+                            .to_string()
+                    })
+                    .unwrap_or_default();
+                hover.push(
+                    tr!("Address changed from {old} at {time}")
+                        .replace("{old}", previous)
+                        .replace("{time}", &time),
+                );
+            }
+            if !hover.is_empty() {
+                label.on_hover_text(hover.join("\n"));
+            }
 
-            ui.horizontal(|ui| {
-                ui.spacing_mut().item_spacing.x = 0.0;
-                for (i, stat) in stats.iter().enumerate() {
-                    let c = stat.color.unwrap_or(color);
-                    ui.colored_label(c, RichText::new(&stat.text).monospace().strong())
-                        .on_hover_text(&stat.tooltip);
-
-                    if i < stats.len() - 1 {
-                        ui.colored_label(color, RichText::new(", ").monospace().strong());
-                    }
+            // Clicking a tag shows only the hosts with it, clicking it again all
+            for tag in &host_info.tags {
+                let selected = tag_filter.as_deref() == Some(tag.as_str());
+                if crate::ui::tags::tag_chip(ui, tag, selected).clicked() {
+                    *tag_filter = (!selected).then(|| tag.clone());
                 }
-            });
+            }
+
+            render_stats(ui, visuals, color, &stats);
         });
     });
 
     let response = inner_res.response;
 
+    if host_info.display.show_threshold_bar
+        && !host_info.is_stopped
+        && let Some((rule, proximity)) = alerts::closest_threshold(host_info, status)
+        && proximity > 0.0
+    {
+        let alpha = if visuals.is_dark { 40 } else { 60 };
+        let bar_color = if proximity >= 1.0 {
+            let [r, g, b] = visuals.colors.loss;
+            Color32::from_rgba_unmultiplied(r, g, b, alpha)
+        } else if proximity >= THRESHOLD_WARNING_FRACTION {
+            let [r, g, b] = visuals.colors.warn;
+            Color32::from_rgba_unmultiplied(r, g, b, alpha)
+        } else {
+            Color32::from_rgba_unmultiplied(128, 128, 128, alpha / 2)
+        };
+        let mut rect = response.rect;
+        rect.set_width(rect.width() * proximity.min(1.0) as f32);
+        ui.painter().set(
+            threshold_bar,
+            egui::Shape::rect_filled(rect, 2.0, bar_color),
+        );
+        response.clone().on_hover_text(
+            tr!("{rule}: {pct}% of the threshold")
+                .replace("{rule}", &rule.label())
+                .replace("{pct}", &format!("{:.0}", proximity * 100.0)),
+        );
+    }
+
     // If another row was dropped onto this row
-    if let Some(from_idx) = dropped_payload {
+    if let Some(from_idx) = dropped_payload
+        && !locked
+        && reorderable
+    {
         *moved = Some((*from_idx, idx));
     }
 
@@ -330,4 +280,380 @@ pub fn render_host_row(
         ui.painter()
             .rect_filled(response.rect, 2.0, Color32::from_white_alpha(30));
     }
+
+    if host_info.dual_stack
+        && !host_info.is_stopped
+        && let Some(dual_stack) = &status.dual_stack
+    {
+        for family in AddressFamily::ALL {
+            render_family_row(
+                ui,
+                visuals,
+                host_info,
+                family,
+                dual_stack.get(family),
+                annotations,
+            );
+        }
+    }
+}
+
+/// Renders the IPv4 or IPv6 sub-row of a dual-stack host.
+fn render_family_row(
+    ui: &mut egui::Ui,
+    visuals: &crate::app::PingVisuals,
+    host_info: &HostInfo,
+    family: AddressFamily,
+    status: &HostStatus,
+    annotations: &[Annotation],
+) {
+    let color = visuals.status_color(false, status.alive, status.latency, &host_info.thresholds);
+    let result = if status.sent == 0 {
+        "…".to_string()
+    } else if status.dns_error {
+        tr!("NO ADDRESS")
+    } else if status.alive {
+        format!("{:4.0}{}", status.latency, tr!("ms"))
+    } else {
+        tr!("DOWN")
+    };
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("   ↳ ").monospace());
+        // The detailed plot shows the combined history of the host
+        let interval_sec = host_info.mode.interval_secs();
+        if host_info.display.heatmap_plot {
+            plots::render_heatmap(
+                ui,
+                visuals,
+                status,
+                annotations,
+                interval_sec,
+                &host_info.thresholds,
+            );
+        } else {
+            render_history_plot(
+                ui,
+                visuals,
+                &format!("{}#{}", host_info.address, family.label()),
+                status,
+                annotations,
+                interval_sec,
+                host_info.display.long_term_plot,
+                &host_info.thresholds,
+            );
+        }
+        ui.colored_label(
+            color,
+            RichText::new(format!(
+                "{} {} → {}  ",
+                family.label(),
+                status.resolved_ip.as_deref().unwrap_or("-"),
+                result
+            ))
+            .monospace()
+            .strong(),
+        );
+        render_stats(
+            ui,
+            visuals,
+            color,
+            &presenter::stat_items(&host_info.display, &host_info.thresholds, status),
+        );
+    });
+}
+
+/// Renders statistics separated by commas, in `color` unless a statistic has its own.
+fn render_stats(
+    ui: &mut egui::Ui,
+    visuals: &crate::app::PingVisuals,
+    color: Color32,
+    stats: &[StatItem],
+) {
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        for (i, stat) in stats.iter().enumerate() {
+            let c = visuals.severity_color(stat.severity).unwrap_or(color);
+            ui.colored_label(c, RichText::new(&stat.text).monospace().strong())
+                .on_hover_text(&stat.tooltip);
+
+            if i < stats.len() - 1 {
+                ui.colored_label(color, RichText::new(", ").monospace().strong());
+            }
+        }
+    });
+}
+
+/// Renders the mini-chart of the last samples, or of minute/hour buckets if `long_term`,
+/// with the annotations as vertical markers and pauses in measuring (with samples
+/// every `interval_sec`) as grey bands. With a shared time axis in the plot settings
+/// the samples of the last minutes are placed by their age instead of their index.
+/// Bars are colored and the limit line drawn by the host's `thresholds`.
+/// Returns whether it was clicked.
+#[allow(clippy::too_many_arguments)]
+fn render_history_plot(
+    ui: &mut egui::Ui,
+    visuals: &crate::app::PingVisuals,
+    id: &str,
+    status: &HostStatus,
+    annotations: &[Annotation],
+    interval_sec: u64,
+    long_term: bool,
+    thresholds: &Thresholds,
+) -> bool {
+    // Per-minute or per-hour buckets instead of raw samples
+    let plot = &visuals.plot;
+    let samples = plot.samples.clamp(1, HISTORY_WINDOW_SIZE);
+    let long_term = long_term.then(|| status.rollups.long_term(samples));
+    let now = chrono::Utc::now().timestamp() as u64;
+    // Seconds shown on the shared time axis, where x is the age of a sample
+    let span =
+        (long_term.is_none() && plot.time_span_min > 0).then_some(plot.time_span_min as f64 * 60.0);
+    // Only the most recent samples are shown
+    let skip = match span {
+        Some(span) => status
+            .history
+            .partition_point(|s| (now.saturating_sub(s.timestamp) as f64) > span),
+        None => status.history.len().saturating_sub(samples),
+    };
+    // Position of a sample by its index among the shown ones or its timestamp
+    let x_of = |i: usize, timestamp: u64| match span {
+        Some(_) => timestamp as f64 - now as f64,
+        None => i as f64,
+    };
+    let bar_width = match span {
+        Some(_) => interval_sec.max(1) as f64 * plot.bar_width,
+        None => plot.bar_width,
+    };
+
+    // Chart: thin bars — blue (below the warn threshold), yellow/orange (above it),
+    // purple (above the bad one) and vermilion (timeout) colors
+    let limit = thresholds.latency_warn;
+    let bars: Vec<Bar> = match &long_term {
+        // Average RTT per bucket; buckets with loss use the loss colors
+        Some(buckets) => buckets
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                let (height, fill) = if b.sent == b.lost {
+                    (limit, visuals.latency_color(f64::NAN))
+                } else {
+                    (
+                        b.mean,
+                        visuals
+                            .value_color(
+                                b.loss_pct(),
+                                thresholds.loss_warn,
+                                thresholds.loss_bad,
+                                false,
+                            )
+                            .unwrap_or_else(|| visuals.host_latency_color(b.mean, thresholds)),
+                    )
+                };
+                Bar::new(i as f64, height).width(plot.bar_width).fill(fill)
+            })
+            .collect(),
+        None => status
+            .history
+            .iter()
+            .skip(skip)
+            .enumerate()
+            .map(|(i, sample)| {
+                let rtt = sample.rtt_or_nan();
+                // For timeouts, display bar at warning threshold height
+                let height = if rtt.is_nan() { limit } else { rtt };
+                // While the machine was asleep, like the pauses in measuring
+                let fill = if sample.is_no_data() {
+                    visuals.pause_color()
+                } else {
+                    visuals.host_latency_color(rtt, thresholds)
+                };
+
+                Bar::new(x_of(i, sample.timestamp), height)
+                    .width(bar_width)
+                    .fill(fill)
+            })
+            .collect(),
+    };
+    let chart = BarChart::new(String::new(), bars).allow_hover(false); // Disable built-in bar tooltips
+    let markers: Vec<(f64, &Annotation)> = match (&long_term, span) {
+        (Some(buckets), _) => {
+            let slots: Vec<u64> = buckets.iter().map(|b| b.start).collect();
+            presenter::annotation_positions(annotations, &slots)
+        }
+        (None, Some(span)) => annotations
+            .iter()
+            .map(|a| (x_of(0, a.timestamp), a))
+            .filter(|(x, _)| *x >= -span && *x <= 0.0)
+            .collect(),
+        (None, None) => {
+            let slots: Vec<u64> = status
+                .history
+                .iter()
+                .skip(skip)
+                .map(|s| s.timestamp)
+                .collect();
+            presenter::annotation_positions(annotations, &slots)
+        }
+    };
+    // Buckets without samples already show as gaps
+    let pauses = if long_term.is_some() {
+        Vec::new()
+    } else {
+        presenter::pauses(status.history.iter().skip(skip), interval_sec, now)
+    };
+    // Extent of a pause band on the x axis
+    let pause_range = |pause: &presenter::Pause| match span {
+        Some(_) => (x_of(0, pause.start), x_of(0, pause.end)),
+        None => {
+            let x = pause.index as f64 - 0.5;
+            (x - 0.4, x + 0.4)
+        }
+    };
+    // Hover distance on the time axis: a few points, but at least half a bar
+    let hover_slack = span.map_or(0.0, |span| {
+        (span * 3.0 / plot.width as f64).max(bar_width / 2.0)
+    });
+
+    // Ping history chart.
+    // To fill N bars of width 1.0 without gaps:
+    // 1. Set X bounds from -0.5 to N - 0.5 (N units total).
+    // 2. Remove horizontal padding (margin_fraction).
+    // On the time axis the bounds are the span up to now instead.
+    let (x_min, x_max) = match span {
+        Some(span) => (-span, 0.0),
+        None => (-0.5, samples as f64 - 0.5),
+    };
+    let plot_res = Plot::new(format!("plot_{}", id))
+        .height(plot.height)
+        .width(plot.width)
+        .show_axes(false)
+        .show_grid(false)
+        .show_x(false) // Disable built-in tooltip system
+        .show_y(false)
+        .allow_zoom(false)
+        .allow_drag(false)
+        .allow_scroll(false)
+        .set_margin_fraction(egui::Vec2::new(0.0, 0.05))
+        .include_x(x_min)
+        .include_x(x_max)
+        .include_y(0.0)
+        .include_y(limit)
+        .show(ui, |plot_ui: &mut egui_plot::PlotUi| {
+            plot_ui.hline(
+                HLine::new("", limit)
+                    .color(visuals.limit_line_color())
+                    .width(1.0),
+            );
+            plot_ui.bar_chart(chart);
+            // A band between the bars before and after a pause
+            let top = plot_ui.plot_bounds().max()[1];
+            for pause in &pauses {
+                let (left, right) = pause_range(pause);
+                plot_ui.polygon(
+                    Polygon::new(
+                        "",
+                        vec![[left, 0.0], [right, 0.0], [right, top], [left, top]],
+                    )
+                    .fill_color(visuals.pause_color())
+                    .stroke(egui::Stroke::NONE),
+                );
+            }
+            for (x, _) in &markers {
+                plot_ui.vline(
+                    VLine::new("", *x)
+                        .color(visuals.annotation_color())
+                        .width(1.5),
+                );
+            }
+        });
+
+    let clicked = plot_res.response.clicked();
+    plot_res.response.on_hover_ui(|ui| {
+        if let Some(hover_pos) = ui.ctx().pointer_hover_pos() {
+            let pos = plot_res.transform.value_from_position(hover_pos);
+            let i = pos.x.round() as i32;
+            // Notes on the left edge of the hovered bar, or near the pointer in time
+            let near_marker = |x: f64| match span {
+                Some(_) => (x - pos.x).abs() <= hover_slack,
+                None => (x + 0.5).round() as i32 == i,
+            };
+            for (_, note) in markers.iter().filter(|(x, _)| near_marker(*x)) {
+                ui.horizontal(|ui| {
+                    ui.add_space(4.0);
+                    ui.colored_label(visuals.annotation_color(), format!("📌 {}", note.text));
+                    ui.add_space(4.0);
+                });
+            }
+            let near_pause = |pause: &presenter::Pause| match span {
+                Some(_) => {
+                    let (left, right) = pause_range(pause);
+                    pos.x >= left - hover_slack && pos.x <= right + hover_slack
+                }
+                None => pause.index as i32 == i,
+            };
+            for pause in pauses.iter().filter(|p| near_pause(p)) {
+                ui.horizontal(|ui| {
+                    ui.add_space(4.0);
+                    ui.label(format!("⏸ {}", presenter::pause_text(pause)));
+                    ui.add_space(4.0);
+                });
+            }
+            // The nearest sample in time on the time axis
+            let hovered = match span {
+                Some(_) => status
+                    .history
+                    .iter()
+                    .skip(skip)
+                    .min_by(|a, b| {
+                        let distance = |t: u64| (x_of(0, t) - pos.x).abs();
+                        distance(a.timestamp).total_cmp(&distance(b.timestamp))
+                    })
+                    .filter(|s| (x_of(0, s.timestamp) - pos.x).abs() <= hover_slack),
+                None => usize::try_from(i)
+                    .ok()
+                    .and_then(|i| status.history.get(skip + i)),
+            };
+            if let Some(buckets) = &long_term {
+                if let Some(b) = usize::try_from(i).ok().and_then(|i| buckets.get(i)) {
+                    let start = presenter::format_time(b.start);
+                    let rtt = if b.sent == b.lost {
+                        tr!("Timeout")
+                    } else {
+                        format!("{:.1} / {:.1} / {:.1} {}", b.min, b.mean, b.max, tr!("ms"))
+                    };
+                    let text = tr!("{start}  min/avg/max {rtt}, loss {loss}%")
+                        .replace("{start}", &start)
+                        .replace("{rtt}", &rtt)
+                        .replace("{loss}", &format!("{:.1}", b.loss_pct()));
+                    ui.horizontal(|ui| {
+                        ui.add_space(4.0);
+                        ui.label(text);
+                        ui.add_space(4.0);
+                    });
+                }
+            } else if let Some(sample) = hovered {
+                let result = match (sample.outcome, sample.rtt) {
+                    (SampleOutcome::DnsError, _) => tr!("DNS error"),
+                    (SampleOutcome::NoData, _) => tr!("No data (computer asleep)"),
+                    (_, Some(rtt)) => format!("{:.1} {}", rtt, tr!("ms")),
+                    (_, None) => tr!("Timeout"),
+                };
+                let time = chrono::DateTime::from_timestamp(sample.timestamp as i64, 0)
+                    .map(|t| {
+                        t.with_timezone(&chrono::Local)
+                            .format("%H:%M:%S")
+                            .to_string()
+                    })
+                    .unwrap_or_default();
+                let text = format!("#{} {}  {}", sample.seq, time, result);
+                ui.horizontal(|ui| {
+                    ui.add_space(4.0);
+                    ui.label(text);
+                    ui.add_space(4.0);
+                });
+            }
+        }
+        ui.label(egui::RichText::new(tr!("Click for the detailed plot")).small());
+    });
+    clicked
 }
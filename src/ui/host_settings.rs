@@ -1,14 +1,33 @@
-use crate::model::{HostInfo, PingMode};
+use crate::constants::{
+    DSCP_PRESETS, MAX_CONFIRM_PROBES, MAX_PROBE_TIMEOUT_MS, MIN_PROBE_TIMEOUT_MS,
+};
+use crate::logic::diagnostics::MtuDiscovery;
+use crate::logic::host_order;
+use crate::logic::live_trace::LiveTrace;
+use crate::model::{AlertRule, HostInfo, HttpMethod, PingMode, QoePreset, Thresholds};
+use crate::ui::bufferbloat::BufferbloatState;
+use crate::ui::duplicate_host::DuplicateHostState;
+use crate::ui::quiet_hours::render_schedule_editor;
 use eframe::egui;
 use tr::tr;
 
 /// Renders the host settings window for adding or editing targets.
 ///
 /// Returns `true` if the help button was clicked inside the window.
+/// The "Trace" button starts a live trace of the host in `trace`, the
+/// "Bufferbloat" button opens the bufferbloat check in `bufferbloat`, "Find MTU"
+/// starts a path MTU discovery in `mtu`, the "Duplicate" button asks for the address of a copy in `duplicating`;
+/// `interfaces` are the names of the network interfaces offered for the host.
+#[allow(clippy::too_many_arguments)]
 pub fn render_host_settings_window(
     ctx: &egui::Context,
     hosts: &mut [HostInfo],
     editing_host: &mut Option<String>,
+    trace: &mut Option<LiveTrace>,
+    bufferbloat: &mut Option<BufferbloatState>,
+    mtu: &mut Option<MtuDiscovery>,
+    duplicating: &mut Option<DuplicateHostState>,
+    interfaces: &[String],
 ) -> bool {
     let mut help_requested = false;
 
@@ -16,23 +35,119 @@ pub fn render_host_settings_window(
         return false;
     };
 
+    // Hosts this one can be compared with, as (address, label)
+    let others: Vec<(String, String)> = hosts
+        .iter()
+        .filter(|o| o.address != *addr)
+        .map(|o| {
+            let label = if o.name.is_empty() {
+                o.address.clone()
+            } else {
+                format!("{} ({})", o.name, o.address)
+            };
+            (o.address.clone(), label)
+        })
+        .collect();
+
     let Some(h) = hosts.iter_mut().find(|h| h.address == *addr) else {
         *editing_host = None;
         return false;
     };
 
     let mut is_open = true;
+    // Thresholds to copy to every host of the group once the window is drawn
+    let mut apply_to_group: Option<(String, Thresholds)> = None;
     let window_res = egui::Window::new(tr!("Host Settings"))
         .open(&mut is_open)
         .resizable(false)
         .show(ctx, |ui| {
-            ui.heading(format!("{}: {}", tr!("Host"), h.address));
+            ui.horizontal(|ui| {
+                ui.heading(format!("{}: {}", tr!("Host"), h.address));
+                if h.is_icmp()
+                    && ui
+                        .button(tr!("Trace"))
+                        .on_hover_text(tr!(
+                            "Shows live latency and loss of every hop on the route to this host"
+                        ))
+                        .clicked()
+                {
+                    *trace = Some(LiveTrace::start(h.target().to_string()));
+                }
+                if h.is_icmp()
+                    && ui
+                        .button(tr!("Bufferbloat"))
+                        .on_hover_text(tr!(
+                            "Compares the latency to this host on an idle and on a saturated connection"
+                        ))
+                        .clicked()
+                {
+                    *bufferbloat = Some(BufferbloatState::new(h.target().to_string()));
+                }
+                if h.is_icmp()
+                    && ui
+                        .button(tr!("Find MTU"))
+                        .on_hover_text(tr!(
+                            "Finds the largest packet that reaches this host unfragmented, e.g. to debug a VPN"
+                        ))
+                        .clicked()
+                {
+                    *mtu = Some(MtuDiscovery::start(h.target().to_string()));
+                }
+                if ui
+                    .button(tr!("Duplicate"))
+                    .on_hover_text(tr!("Adds a host with the same settings and another address"))
+                    .clicked()
+                {
+                    *duplicating = Some(DuplicateHostState::new(h));
+                }
+            });
             ui.add_space(8.0);
+            if h.shared {
+                ui.label(tr!(
+                    "This host is managed by the shared host list. Edit the file to change it."
+                ));
+                ui.add_space(8.0);
+                ui.disable();
+            }
 
             ui.horizontal(|ui| {
                 ui.label(format!("{}:", tr!("Name")));
                 ui.text_edit_singleline(&mut h.name);
             });
+            ui.horizontal(|ui| {
+                ui.label(format!("{}:", tr!("Group")));
+                ui.add(egui::TextEdit::singleline(&mut h.group).hint_text(tr!("none")))
+                    .on_hover_text(tr!("Hosts with the same group are shown under a collapsible header"));
+            });
+            ui.horizontal(|ui| {
+                ui.label(format!("{}:", tr!("Tags")));
+                // The text is kept while typing so a trailing comma is not lost
+                let id = ui.id().with(("tags", &h.address));
+                let mut text = ui
+                    .data_mut(|d| d.get_temp::<String>(id))
+                    .unwrap_or_else(|| h.tags.join(", "));
+                let response = ui
+                    .add(egui::TextEdit::singleline(&mut text).hint_text("prod, vpn"))
+                    .on_hover_text(tr!(
+                        "Comma-separated labels shown as colored chips; a host can have several"
+                    ));
+                if response.changed() {
+                    h.tags = host_order::parse_tags(&text);
+                }
+                if response.has_focus() {
+                    ui.data_mut(|d| d.insert_temp(id, text));
+                } else {
+                    ui.data_mut(|d| d.remove::<String>(id));
+                }
+            });
+            ui.label(format!("{}:", tr!("Notes")));
+            ui.add(
+                egui::TextEdit::multiline(&mut h.notes)
+                    .hint_text(tr!("e.g. rack 3, switch port 12, call Bob if down"))
+                    .desired_rows(2)
+                    .desired_width(f32::INFINITY),
+            )
+            .on_hover_text(tr!("Shown when hovering the host in the list"));
 
             ui.add_space(8.0);
             ui.horizontal(|ui| {
@@ -40,34 +155,417 @@ pub fn render_host_settings_window(
                 egui::ComboBox::from_id_salt(format!("combo_{}", &h.address))
                     .selected_text(h.mode.label())
                     .show_ui(ui, |ui| {
-                        for mode in [
-                            PingMode::VeryFast,
-                            PingMode::Fast,
-                            PingMode::NotFast,
-                            PingMode::Normal,
-                            PingMode::NotSlow,
-                            PingMode::Slow,
-                            PingMode::VerySlow,
-                        ] {
+                        for mode in PingMode::ALL {
                             ui.selectable_value(&mut h.mode, mode, mode.label());
                         }
                     });
             });
+            ui.checkbox(&mut h.adaptive_interval, tr!("Adaptive (1 s while down)"))
+                .on_hover_text(tr!(
+                    "Pings every second while the host fails, then returns to the interval above"
+                ));
+            ui.checkbox(&mut h.passive, tr!("Imported log only (do not ping)"))
+                .on_hover_text(tr!(
+                    "Statistics come from a log file imported or followed in System Tools"
+                ));
 
             ui.add_space(8.0);
-            ui.label(tr!("VPN & Privacy:"));
             ui.horizontal(|ui| {
-                ui.label(tr!("Packet Size:"));
+                ui.label(tr!("Probe command:"));
                 ui.add(
-                    egui::DragValue::new(&mut h.packet_size)
-                        .range(16..=1400)
-                        .suffix(tr!(" bytes")),
-                );
+                    egui::TextEdit::singleline(&mut h.probe_command)
+                        .hint_text(tr!("none (ping the address)"))
+                        .desired_width(240.0),
+                )
+                .on_hover_text(tr!(
+                    "Shell command run instead of pinging. Exit code 0 means up; the first number it prints is the latency in ms (otherwise its run time is used). The address is passed in EGUI_PINGER_ADDRESS."
+                ));
             });
-            ui.checkbox(&mut h.random_padding, tr!("Random Padding"))
+
+            if h.is_exec() {
+                // The command replaces all address-based probes
+            } else if h.is_http() {
+                ui.add_space(8.0);
+                ui.label(tr!("HTTP(S) probe:"));
+                ui.horizontal(|ui| {
+                    ui.label(tr!("Method:"));
+                    for method in [HttpMethod::Get, HttpMethod::Head] {
+                        ui.selectable_value(&mut h.http_method, method, method.as_str());
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label(tr!("Alive status codes:"));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut h.http_expected_status)
+                            .hint_text("200-399")
+                            .desired_width(160.0),
+                    )
+                    .on_hover_text(tr!(
+                        "Comma-separated codes, ranges or classes, e.g. 200,204 or 2xx. Latency is the time to the first byte of the response."
+                    ));
+                });
+            } else if h.is_dns() {
+                ui.add_space(8.0);
+                ui.label(tr!("DNS probe:"));
+                match crate::logic::dns::parse_probe_target(&h.address) {
+                    Some(target) => {
+                        let resolver = target
+                            .resolver
+                            .map(|r| r.to_string())
+                            .unwrap_or_else(|| tr!("system resolver"));
+                        ui.label(
+                            tr!("Latency is the time {resolver} takes to resolve {name}.")
+                                .replace("{resolver}", &resolver)
+                                .replace("{name}", &target.name),
+                        );
+                    }
+                    None => {
+                        ui.colored_label(
+                            egui::Color32::RED,
+                            tr!("Invalid address, expected dns://resolver/name"),
+                        );
+                    }
+                }
+            } else if h.is_udp() {
+                ui.add_space(8.0);
+                ui.label(tr!("UDP probe:"));
+                if crate::logic::udp_probe::parse_target(&h.address).is_none() {
+                    ui.colored_label(
+                        egui::Color32::RED,
+                        tr!("Invalid address, expected udp://host:port"),
+                    );
+                }
+                ui.horizontal(|ui| {
+                    ui.label(tr!("Payload:"));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut h.udp_payload)
+                            .hint_text(tr!("empty datagram"))
+                            .desired_width(240.0),
+                    )
+                    .on_hover_text(tr!(
+                        "Datagram the service answers, e.g. its status query; \\xNN is a byte in hex. Latency is the time to the reply or to the ICMP port unreachable of a closed port. A service that ignores the datagram counts as lost."
+                    ));
+                });
+                if let Err(e) = crate::logic::udp_probe::parse_payload(&h.udp_payload) {
+                    ui.colored_label(egui::Color32::RED, e);
+                }
+            } else {
+                ui.add_space(8.0);
+                ui.label(tr!("VPN & Privacy:"));
+                ui.horizontal(|ui| {
+                    ui.label(tr!("Packet Size:"));
+                    ui.add(
+                        egui::DragValue::new(&mut h.packet_size)
+                            .range(16..=1400)
+                            .suffix(tr!(" bytes")),
+                    );
+                });
+                ui.checkbox(&mut h.random_padding, tr!("Random Padding"))
+                    .on_hover_text(tr!(
+                        "Adds 0-25% random extra data to each packet to mask traffic patterns"
+                    ));
+                ui.horizontal(|ui| {
+                    let mut custom_timeout = h.timeout_ms.is_some();
+                    if ui
+                        .checkbox(&mut custom_timeout, tr!("Timeout:"))
+                        .on_hover_text(tr!(
+                            "How long to wait for a reply before the ping counts as lost, e.g. 500 ms on a LAN or 3000 ms over satellite. Default: 1000 ms"
+                        ))
+                        .changed()
+                    {
+                        h.timeout_ms = custom_timeout.then_some(1_000);
+                    }
+                    if let Some(timeout) = h.timeout_ms.as_mut() {
+                        ui.add(
+                            egui::DragValue::new(timeout)
+                                .range(MIN_PROBE_TIMEOUT_MS..=MAX_PROBE_TIMEOUT_MS)
+                                .suffix(tr!(" ms")),
+                        );
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label(tr!("Confirmation pings:"));
+                    ui.add(egui::DragValue::new(&mut h.confirm_probes).range(0..=MAX_CONFIRM_PROBES))
+                        .on_hover_text(tr!(
+                            "Quick extra pings sent when a ping goes unanswered. If one of them is answered, the loss is still counted, but the host is not marked down, so a single dropped packet causes no false alarm. 0 sends none."
+                        ));
+                });
+                ui.horizontal(|ui| {
+                    let mut custom_ttl = h.ttl.is_some();
+                    if ui
+                        .checkbox(&mut custom_ttl, tr!("TTL:"))
+                        .on_hover_text(tr!("Hop limit of the probes instead of the system default"))
+                        .changed()
+                    {
+                        h.ttl = custom_ttl.then_some(64);
+                    }
+                    if let Some(ttl) = h.ttl.as_mut() {
+                        ui.add(egui::DragValue::new(ttl).range(1..=255));
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label(tr!("DSCP:"));
+                    let selected = DSCP_PRESETS
+                        .iter()
+                        .find(|(_, v)| *v == h.dscp)
+                        .map_or_else(|| h.dscp.to_string(), |(name, v)| format!("{} ({})", name, v));
+                    egui::ComboBox::from_id_salt(format!("dscp_{}", &h.address))
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            for (name, value) in DSCP_PRESETS {
+                                ui.selectable_value(&mut h.dscp, value, format!("{} ({})", name, value));
+                            }
+                        });
+                    ui.add(egui::DragValue::new(&mut h.dscp).range(0..=63))
+                        .on_hover_text(tr!(
+                            "Marks the probes for QoS, e.g. EF (46) for VoIP-class traffic. Not supported on Windows."
+                        ));
+                });
+
+                if crate::logic::dns::strip_brackets(h.target())
+                    .parse::<std::net::IpAddr>()
+                    .is_err()
+                {
+                    ui.add_space(8.0);
+                    ui.label(tr!("DNS:"));
+                    let edited = ui
+                        .horizontal(|ui| {
+                            ui.label(tr!("Expected answer:"));
+                            ui.add(
+                                egui::TextEdit::singleline(&mut h.expected_dns_answer)
+                                    .hint_text("203.0.113.10 / ^10\\.")
+                                    .desired_width(160.0),
+                            )
+                            .on_hover_text(tr!(
+                                "IP address or regular expression the resolved addresses must contain. A mismatch counts as a failed ping."
+                            ))
+                            .changed()
+                        })
+                        .inner;
+                    // The pattern is only compiled again when it was edited
+                    let invalid_id = egui::Id::new(("expected_dns_answer_invalid", &h.address));
+                    let invalid = match ui.data(|d| d.get_temp::<bool>(invalid_id)) {
+                        Some(invalid) if !edited => invalid,
+                        _ => {
+                            let expected = h.expected_dns_answer.trim();
+                            let invalid = !expected.is_empty()
+                                && crate::logic::dns::ExpectedAnswer::parse(expected).is_err();
+                            ui.data_mut(|d| d.insert_temp(invalid_id, invalid));
+                            invalid
+                        }
+                    };
+                    if invalid {
+                        ui.colored_label(egui::Color32::RED, tr!("Invalid regular expression"));
+                    }
+                    ui.checkbox(&mut h.dual_stack, tr!("Dual-stack (ping IPv4 and IPv6)"))
+                        .on_hover_text(tr!(
+                            "Also pings the IPv4 and the IPv6 address of the name in parallel and shows their statistics in two sub-rows"
+                        ));
+                }
+
+                ui.add_space(8.0);
+                ui.checkbox(&mut h.snmp.enabled, tr!("SNMP interface counters"))
+                    .on_hover_text(tr!(
+                        "Polls the error and discard counters of the interfaces of this host, e.g. a router, every minute over SNMP v2c and shows their increase next to the ping statistics"
+                    ));
+                if h.snmp.enabled {
+                    ui.horizontal(|ui| {
+                        ui.label(tr!("Community:"));
+                        ui.add(
+                            egui::TextEdit::singleline(&mut h.snmp.community)
+                                .password(true)
+                                .desired_width(100.0),
+                        );
+                        ui.label(tr!("Port:"));
+                        ui.add(egui::DragValue::new(&mut h.snmp.port).range(1..=65535));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(tr!("Interfaces:"));
+                        ui.add(
+                            egui::TextEdit::singleline(&mut h.snmp.interfaces)
+                                .hint_text(tr!("all"))
+                                .desired_width(120.0),
+                        )
+                        .on_hover_text(tr!(
+                            "Comma-separated interface indexes (ifIndex) to sum, e.g. the WAN port. Empty sums all interfaces."
+                        ));
+                    });
+                }
+            }
+
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                ui.label(tr!("Interface:"));
+                // Shown while the field is empty, so the interface of the address
+                let hint = match h.bound_interface() {
+                    "" => tr!("any"),
+                    source => source.to_string(),
+                };
+                ui.add(
+                    egui::TextEdit::singleline(&mut h.interface)
+                        .hint_text(hint)
+                        .desired_width(120.0),
+                )
                 .on_hover_text(tr!(
-                    "Adds 0-25% random extra data to each packet to mask traffic patterns"
+                    "Network interface the host is reached through, e.g. wlan0; empty takes the interface of an address@interface. While it is down the probes pause and the host shows INTERFACE DOWN instead of losses."
                 ));
+                egui::ComboBox::from_id_salt(format!("interface_{}", &h.address))
+                    .selected_text("")
+                    .width(20.0)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut h.interface, String::new(), tr!("any"));
+                        for name in interfaces {
+                            ui.selectable_value(&mut h.interface, name.clone(), name);
+                        }
+                    });
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr!("Compare with:"));
+                let selected = others
+                    .iter()
+                    .find(|(address, _)| *address == h.paired_with)
+                    .map_or_else(|| tr!("none"), |(_, label)| label.clone());
+                egui::ComboBox::from_id_salt(format!("paired_{}", &h.address))
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut h.paired_with, String::new(), tr!("none"));
+                        for (address, label) in &others {
+                            ui.selectable_value(&mut h.paired_with, address.clone(), label);
+                        }
+                    })
+                    .response
+                    .on_hover_text(tr!(
+                        "Shows the latency and loss this host adds over the other one in View → Paired hosts, e.g. 8.8.8.8@tun0 over 8.8.8.8@eth0 for the overhead of a VPN tunnel"
+                    ));
+            });
+
+            ui.add_space(8.0);
+            egui::CollapsingHeader::new(tr!("Maintenance windows"))
+                .default_open(h.maintenance.enabled)
+                .show(ui, |ui| {
+                    ui.label(
+                        egui::RichText::new(tr!(
+                            "Expected downtime, e.g. a weekly backup reboot: the host is shown as MAINTENANCE in a neutral color, losses are left out of availability and the SLA check, and no alerts are sent."
+                        ))
+                        .weak(),
+                    );
+                    render_schedule_editor(ui, "maintenance", &mut h.maintenance);
+                });
+
+            ui.add_space(8.0);
+            ui.label(tr!("Quality thresholds:"));
+            ui.horizontal(|ui| {
+                render_threshold_preset(ui, format!("qoe_{}", &h.address), &mut h.thresholds);
+                if !h.group.is_empty()
+                    && ui
+                        .button(tr!("Apply to group"))
+                        .on_hover_text(tr!("Copies these thresholds to all hosts of the group"))
+                        .clicked()
+                {
+                    apply_to_group = Some((h.group.clone(), h.thresholds));
+                }
+            });
+            render_threshold_values(ui, &mut h.thresholds);
+
+            ui.add_space(8.0);
+            ui.label(tr!("Actions:"));
+            let env_hint = tr!(
+                "Shell command run on the state change. EGUI_PINGER_EVENT, EGUI_PINGER_NAME, EGUI_PINGER_ADDRESS, EGUI_PINGER_LOSS, EGUI_PINGER_RTT and EGUI_PINGER_DOWNTIME describe it."
+            );
+            for (label, command) in [
+                (tr!("On down:"), &mut h.on_down_command),
+                (tr!("On up:"), &mut h.on_up_command),
+            ] {
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    ui.add(
+                        egui::TextEdit::singleline(command)
+                            .hint_text(tr!("none"))
+                            .desired_width(240.0),
+                    )
+                    .on_hover_text(&env_hint);
+                });
+            }
+
+            ui.add_space(8.0);
+            ui.label(tr!("Alerts:"));
+            let mut remove_rule = None;
+            for (i, rule) in h.alert_rules.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    match rule {
+                        AlertRule::LatencyTrend {
+                            increase_pct,
+                            window_min,
+                        } => {
+                            ui.label(tr!("Median latency up by more than"));
+                            ui.add(
+                                egui::DragValue::new(increase_pct)
+                                    .range(5.0..=1000.0)
+                                    .suffix("%"),
+                            );
+                            ui.label(tr!("over"));
+                            ui.add(
+                                egui::DragValue::new(window_min)
+                                    .range(1..=1440)
+                                    .suffix(tr!(" min")),
+                            );
+                        }
+                        AlertRule::LossAbove { pct } => {
+                            ui.label(tr!("Packet loss above"));
+                            ui.add(egui::DragValue::new(pct).range(0.1..=100.0).suffix("%"));
+                        }
+                        AlertRule::ConsecutiveFailures { count } => {
+                            ui.label(tr!("Consecutive failures:"));
+                            ui.add(egui::DragValue::new(count).range(1..=1000));
+                        }
+                        AlertRule::LatencyAbove { ms } => {
+                            ui.label(tr!("Median latency above"));
+                            ui.add(
+                                egui::DragValue::new(ms)
+                                    .range(1.0..=10000.0)
+                                    .suffix(tr!(" ms")),
+                            );
+                        }
+                        AlertRule::MosBelow { mos } => {
+                            ui.label(tr!("MOS below"));
+                            ui.add(egui::DragValue::new(mos).range(1.0..=4.5).speed(0.05));
+                        }
+                    }
+                    if ui.button("x").on_hover_text(tr!("Remove rule")).clicked() {
+                        remove_rule = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = remove_rule {
+                h.alert_rules.remove(i);
+            }
+            ui.horizontal_wrapped(|ui| {
+                if ui.button(tr!("+ Loss")).clicked() {
+                    h.alert_rules.push(AlertRule::LossAbove { pct: 5.0 });
+                }
+                if ui.button(tr!("+ Failures")).clicked() {
+                    h.alert_rules
+                        .push(AlertRule::ConsecutiveFailures { count: 5 });
+                }
+                if ui.button(tr!("+ Latency")).clicked() {
+                    h.alert_rules.push(AlertRule::LatencyAbove { ms: 150.0 });
+                }
+                if ui.button(tr!("+ MOS")).clicked() {
+                    h.alert_rules.push(AlertRule::MosBelow { mos: 3.5 });
+                }
+            });
+            if ui
+                .button(tr!("+ Latency trend alert"))
+                .on_hover_text(tr!(
+                    "Alert when the median latency grows steadily, before absolute thresholds are crossed"
+                ))
+                .clicked()
+            {
+                h.alert_rules.push(AlertRule::LatencyTrend {
+                    increase_pct: 50.0,
+                    window_min: 10,
+                });
+            }
 
             ui.add_space(8.0);
             ui.horizontal(|ui| {
@@ -104,6 +602,8 @@ pub fn render_host_settings_window(
                 .on_hover_text(tr!("Count of extremely delayed packets (lags)"));
             ui.checkbox(&mut h.display.show_streak, tr!("Streak"))
                 .on_hover_text(tr!("Current consecutive successes or failures"));
+            ui.checkbox(&mut h.display.show_uptime, tr!("Up/down time"))
+                .on_hover_text(tr!("How long the host has been continuously up or down"));
             ui.checkbox(&mut h.display.show_stddev, tr!("StdDev"))
                 .on_hover_text(tr!("Standard Deviation (spread of latency values)"));
             ui.checkbox(&mut h.display.show_p95, tr!("95th Percentile"))
@@ -112,6 +612,26 @@ pub fn render_host_settings_window(
                 .on_hover_text(tr!("Absolute best and worst latency in history"));
             ui.checkbox(&mut h.display.show_loss, tr!("Packet Loss"))
                 .on_hover_text(tr!("Count and percentage of dropped packets"));
+            ui.checkbox(&mut h.display.show_ewma, tr!("EWMA and trend"))
+                .on_hover_text(tr!(
+                    "Moving average of RTT weighted to recent replies, with ↑ when latency is getting worse and ↓ when it improves"
+                ));
+            ui.checkbox(&mut h.display.show_loss_bursts, tr!("Loss bursts"))
+                .on_hover_text(tr!(
+                    "Number and longest run of consecutive losses, and how bursty the loss is compared to random loss"
+                ));
+            ui.checkbox(&mut h.display.show_threshold_bar, tr!("Threshold bar"))
+                .on_hover_text(tr!(
+                    "Background bar showing how close the host is to its nearest alert threshold; amber from 80%"
+                ));
+            ui.checkbox(&mut h.display.long_term_plot, tr!("Long-term plot"))
+                .on_hover_text(tr!(
+                    "Plot average RTT and loss per minute, or per hour once more than a day is collected"
+                ));
+            ui.checkbox(&mut h.display.heatmap_plot, tr!("Heatmap plot"))
+                .on_hover_text(tr!(
+                    "Plot the recent samples as a heatmap like Smokeping: columns of 5 samples, rows of RTT ranges with lost probes on top, brighter for more samples"
+                ));
 
             ui.add_space(12.0);
             ui.button(tr!("Close")).clicked()
@@ -123,6 +643,12 @@ pub fn render_host_settings_window(
         is_open = false;
     }
 
+    if let Some((group, thresholds)) = apply_to_group {
+        for host in hosts.iter_mut().filter(|host| host.group == group) {
+            host.thresholds = thresholds;
+        }
+    }
+
     // Close window if requested
     if !is_open {
         *editing_host = None;
@@ -130,3 +656,50 @@ pub fn render_host_settings_window(
 
     help_requested
 }
+
+/// Renders the preset choice of quality thresholds, in a horizontal layout.
+pub fn render_threshold_preset(ui: &mut egui::Ui, id_salt: String, thresholds: &mut Thresholds) {
+    ui.label(tr!("Preset:"));
+    let active = thresholds
+        .preset()
+        .map_or_else(|| tr!("Custom"), |p| p.label());
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(active)
+        .show_ui(ui, |ui| {
+            for preset in QoePreset::ALL {
+                if ui
+                    .selectable_label(thresholds.preset() == Some(preset), preset.label())
+                    .clicked()
+                {
+                    *thresholds = preset.thresholds();
+                }
+            }
+        })
+        .response
+        .on_hover_text(tr!(
+            "Sets the warn and bad values of latency, jitter and loss for a kind of traffic"
+        ));
+}
+
+/// Renders the warn and bad values of latency, jitter and loss, one row each.
+pub fn render_threshold_values(ui: &mut egui::Ui, thresholds: &mut Thresholds) {
+    let t = thresholds;
+    for (label, warn, bad, unit) in [
+        (
+            tr!("Latency"),
+            &mut t.latency_warn,
+            &mut t.latency_bad,
+            "ms",
+        ),
+        (tr!("Jitter"), &mut t.jitter_warn, &mut t.jitter_bad, "ms"),
+        (tr!("Loss"), &mut t.loss_warn, &mut t.loss_bad, "%"),
+    ] {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", label));
+            ui.label(tr!("warn"));
+            ui.add(egui::DragValue::new(warn).range(0.0..=10000.0).suffix(unit));
+            ui.label(tr!("bad"));
+            ui.add(egui::DragValue::new(bad).range(0.0..=10000.0).suffix(unit));
+        });
+    }
+}
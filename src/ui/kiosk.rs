@@ -0,0 +1,123 @@
+use crate::logic::{SharedState, kiosk};
+use eframe::egui;
+use tr::tr;
+
+/// Renders the kiosk section of the settings window with the "Lock" button.
+///
+/// A click puts the password into `lock_request`, for [`lock`] once the state is released.
+pub fn render_lock_section(ui: &mut egui::Ui, lock_request: &mut Option<String>) {
+    let id = egui::Id::new("kiosk_new_password");
+    let mut password: String = ui.data_mut(|d| d.get_temp(id).unwrap_or_default());
+    ui.horizontal(|ui| {
+        ui.label(tr!("Unlock password:"));
+        ui.add(
+            egui::TextEdit::singleline(&mut password)
+                .password(true)
+                .hint_text(tr!("optional"))
+                .desired_width(160.0),
+        );
+        if ui
+            .button(tr!("Lock"))
+            .on_hover_text(tr!(
+                "Hides adding, editing and deleting hosts and all settings, keeping the live view. For wall displays in shared spaces."
+            ))
+            .clicked()
+        {
+            *lock_request = Some(std::mem::take(&mut password));
+        }
+    });
+    ui.data_mut(|d| d.insert_temp(id, password));
+}
+
+/// Hashes the password on a worker thread, as PBKDF2 takes a while, and then locks.
+pub fn lock(ctx: &egui::Context, state: &SharedState, password: String) {
+    let (ctx, state) = (ctx.clone(), state.clone());
+    std::thread::spawn(move || {
+        let hash = kiosk::hash_password(&password);
+        let mut state = state.lock().expect("State mutex poisoned");
+        state.settings.kiosk_password = hash;
+        state.settings.kiosk_locked = true;
+        ctx.request_repaint();
+    });
+}
+
+/// Input of the unlock window.
+#[derive(Clone, Default)]
+struct UnlockForm {
+    password: String,
+    failed: bool,
+    /// A password is being checked on a worker thread.
+    checking: bool,
+}
+
+/// Renders the window asking for the kiosk password.
+///
+/// The password is checked on a worker thread, which unlocks the state if it matches.
+pub fn render_unlock_window(ctx: &egui::Context, state: &SharedState, open: &mut bool) {
+    let id = egui::Id::new("kiosk_unlock");
+    let result_id = egui::Id::new("kiosk_unlock_result");
+    let mut form: UnlockForm = ctx.data_mut(|d| d.get_temp(id).unwrap_or_default());
+    // A result arriving after the window was closed is dropped
+    if let Some(matched) = ctx.data_mut(|d| d.remove_temp::<bool>(result_id))
+        && form.checking
+    {
+        form.checking = false;
+        form.failed = !matched;
+    }
+    let mut still_open = *open;
+    egui::Window::new(tr!("Unlock"))
+        .open(&mut still_open)
+        .resizable(false)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            let response = ui.add_enabled(
+                !form.checking,
+                egui::TextEdit::singleline(&mut form.password)
+                    .password(true)
+                    .hint_text(tr!("Password")),
+            );
+            response.request_focus();
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            ui.horizontal(|ui| {
+                let clicked = ui
+                    .add_enabled(!form.checking, egui::Button::new(tr!("Unlock")))
+                    .clicked();
+                if form.checking {
+                    ui.spinner();
+                } else if clicked || submitted {
+                    form.checking = true;
+                    form.failed = false;
+                    let stored = state
+                        .lock()
+                        .expect("State mutex poisoned")
+                        .settings
+                        .kiosk_password
+                        .clone();
+                    let password = std::mem::take(&mut form.password);
+                    let (ctx, state) = (ctx.clone(), state.clone());
+                    std::thread::spawn(move || {
+                        let matched = kiosk::verify_password(&stored, &password);
+                        if matched {
+                            state
+                                .lock()
+                                .expect("State mutex poisoned")
+                                .settings
+                                .kiosk_locked = false;
+                        }
+                        ctx.data_mut(|d| d.insert_temp(result_id, matched));
+                        ctx.request_repaint();
+                    });
+                }
+            });
+            if form.failed {
+                ui.colored_label(egui::Color32::RED, tr!("Wrong password"));
+            }
+        });
+    if !still_open {
+        *open = false;
+    }
+    if !*open {
+        form = UnlockForm::default();
+    }
+    ctx.data_mut(|d| d.insert_temp(id, form));
+}
@@ -1,11 +1,86 @@
 use crate::app::PingVisuals;
-use crate::constants::MAX_UI_EVENTS;
-use crate::model::{AppState, LogEntry};
+use crate::constants::{JITTER_SIMULATION_WINDOW_SEC, MAX_UI_EVENTS};
+use crate::logic::presenter::format_duration;
+use crate::logic::presenter::format_time;
+use crate::logic::storage::{self, HistoryAggregate};
+use crate::logic::{export, jitter_buffer};
+use crate::model::{AppState, LogEntry, OutageLog};
 use eframe::egui;
 use eframe::egui::{Color32, RichText};
 use std::io::Write;
 use tr::tr;
 
+/// Periods offered for long-term history aggregates: (label, seconds).
+fn history_periods() -> [(String, u64); 3] {
+    [
+        (tr!("Last hour"), 3_600),
+        (tr!("Last 24h"), 86_400),
+        (tr!("Last 7 days"), 7 * 86_400),
+    ]
+}
+
+/// Shows aggregates of the on-disk history for the selected period.
+///
+/// Results are cached in egui memory and recomputed at most every 10 seconds.
+fn render_history_aggregates(ui: &mut egui::Ui, addr: &str) {
+    let period_id = egui::Id::new(("history_period", addr));
+    let cache_id = egui::Id::new(("history_aggregate", addr));
+    let periods = history_periods();
+
+    let mut period = ui.data(|d| d.get_temp::<u64>(period_id)).unwrap_or(86_400);
+    ui.horizontal(|ui| {
+        for (label, secs) in &periods {
+            ui.selectable_value(&mut period, *secs, label);
+        }
+    });
+    ui.data_mut(|d| d.insert_temp(period_id, period));
+
+    let now = chrono::Utc::now().timestamp() as u64;
+    let cached = ui.data(|d| d.get_temp::<(u64, u64, Option<HistoryAggregate>)>(cache_id));
+    let aggregate = match cached {
+        Some((p, computed_at, agg)) if p == period && now.saturating_sub(computed_at) < 10 => agg,
+        _ => {
+            let agg = storage::query(addr, now.saturating_sub(period))
+                .ok()
+                .map(|records| storage::aggregate(&records));
+            ui.data_mut(|d| d.insert_temp(cache_id, (period, now, agg)));
+            agg
+        }
+    };
+
+    match aggregate {
+        Some(a) if a.sent > 0 => {
+            ui.label(format!(
+                "{}: {:.2}% ({} {}, {} {})",
+                tr!("Availability"),
+                a.availability,
+                a.sent,
+                tr!("sent"),
+                a.lost,
+                tr!("lost")
+            ));
+            ui.label(format!(
+                "{}: {:.1} / {:.1} / {:.1} {}   {}: {:.1} … {:.1} {}",
+                tr!("Mean / median / P95"),
+                a.mean,
+                a.median,
+                a.p95,
+                tr!("ms"),
+                tr!("Range"),
+                a.min,
+                a.max,
+                tr!("ms")
+            ));
+        }
+        Some(_) => {
+            ui.label(tr!("No history recorded for this period."));
+        }
+        None => {
+            ui.label(tr!("History could not be read."));
+        }
+    }
+}
+
 /// Returns the appropriate display color for a log entry based on its type.
 pub fn log_entry_color(entry: &LogEntry, visuals: &PingVisuals) -> Color32 {
     match entry {
@@ -17,16 +92,107 @@ pub fn log_entry_color(entry: &LogEntry, visuals: &PingVisuals) -> Color32 {
         LogEntry::Statistics { .. } => Color32::from_rgb(0, 158, 115),              // Bluish green
         LogEntry::RouteUpdate { .. } => Color32::from_rgb(0, 114, 178),             // Blue
         LogEntry::Marker { .. } => Color32::from_rgb(204, 121, 167), // Reddish purple
+        LogEntry::Alert { raised: true, .. } => Color32::from_rgb(230, 159, 0), // Orange
+        LogEntry::Alert { raised: false, .. } => Color32::from_rgb(0, 158, 115), // Bluish green
         _ => visuals.latency_color(0.1),                             // Normal ping
     }
 }
 
+/// Active tab in the log window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogTab {
+    #[default]
+    Log,
+    Events,
+}
+
+/// Persistent state of the log window.
+#[derive(Debug, Default)]
+pub struct LogViewerState {
+    pub tab: LogTab,
+    /// Result of the last outage export (path or error message).
+    pub export_status: String,
+}
+
+/// Writes the outages of `addr` as CSV into `dir` and returns the file path.
+fn save_outages(addr: &str, log: &OutageLog, dir: &std::path::Path) -> std::io::Result<String> {
+    let path = dir.join(format!(
+        "{}-outages-{}.csv",
+        addr.replace(['.', ':', '/', '[', ']'], "_"),
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::write(&path, export::host_outages_csv(addr, log))?;
+    Ok(path.display().to_string())
+}
+
+/// Renders the outages of a host, newest first, with the one in progress on top.
+fn render_events_tab(ui: &mut egui::Ui, log: &OutageLog, addr: &str, viewer: &mut LogViewerState) {
+    let now = chrono::Utc::now().timestamp() as u64;
+    if let Some((start, lost)) = log.current() {
+        ui.colored_label(
+            Color32::RED,
+            tr!("Down since {time} ({duration}), {lost} probes lost")
+                .replace("{time}", &format_time(start))
+                .replace("{duration}", &format_duration(now.saturating_sub(start)))
+                .replace("{lost}", &lost.to_string()),
+        );
+    }
+    let events = log.finished();
+    let total: u64 = events.iter().map(|e| e.duration()).sum();
+    ui.horizontal(|ui| {
+        ui.label(
+            tr!("{count} outages, {duration} in total")
+                .replace("{count}", &events.len().to_string())
+                .replace("{duration}", &format_duration(total)),
+        );
+        if ui
+            .add_enabled(!events.is_empty(), egui::Button::new(tr!("Export CSV")))
+            .clicked()
+        {
+            let dir = dirs::home_dir().unwrap_or_else(std::env::temp_dir);
+            viewer.export_status = match save_outages(addr, log, &dir) {
+                Ok(path) => format!("{}: {}", tr!("Saved to"), path),
+                Err(e) => format!("{}: {}", tr!("Error"), e),
+            };
+        }
+    });
+    if !viewer.export_status.is_empty() {
+        ui.label(RichText::new(&viewer.export_status).monospace());
+    }
+    ui.separator();
+
+    if events.is_empty() {
+        ui.label(tr!("No outages recorded in this session."));
+        return;
+    }
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("outage_events")
+            .striped(true)
+            .num_columns(4)
+            .show(ui, |ui| {
+                ui.strong(tr!("Start"));
+                ui.strong(tr!("End"));
+                ui.strong(tr!("Duration"));
+                ui.strong(tr!("Lost"));
+                ui.end_row();
+                for event in events.iter().rev() {
+                    ui.label(format_time(event.start));
+                    ui.label(format_time(event.end));
+                    ui.label(format_duration(event.duration()));
+                    ui.label(event.lost.to_string());
+                    ui.end_row();
+                }
+            });
+    });
+}
+
 /// Renders the log viewer window for a specific host.
 pub fn render_log_window(
     ctx: &egui::Context,
     visuals: &PingVisuals,
     state: &mut AppState,
     viewing_log: &mut Option<String>,
+    viewer: &mut LogViewerState,
 ) {
     let Some(ref addr) = viewing_log.clone() else {
         return;
@@ -52,6 +218,18 @@ pub fn render_log_window(
         .default_width(600.0)
         .default_height(400.0)
         .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut viewer.tab, LogTab::Log, tr!("Log"));
+                ui.selectable_value(&mut viewer.tab, LogTab::Events, tr!("Events"));
+            });
+            ui.separator();
+            if viewer.tab == LogTab::Events {
+                if let Some(status) = state.statuses.get(addr) {
+                    render_events_tab(ui, &status.outages, addr, viewer);
+                }
+                return;
+            }
+
             // 1. Logging Controls
             ui.horizontal(|ui| {
                 // Get data and handle checkbox (ends borrow of h early)
@@ -114,7 +292,79 @@ pub fn render_log_window(
 
             ui.separator();
 
-            // 3. The Log View
+            // 3. Jitter buffer simulation
+            egui::CollapsingHeader::new(tr!("Jitter buffer simulation"))
+                .id_salt("jitter_buffer_sim")
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(tr!("Buffer size:"));
+                        ui.add(
+                            egui::DragValue::new(&mut state.settings.jitter_buffer_ms)
+                                .range(10..=500)
+                                .suffix(tr!(" ms")),
+                        );
+                    });
+                    if let Some(status) = state.statuses.get(addr) {
+                        let now = chrono::Utc::now().timestamp() as u64;
+                        let r = jitter_buffer::simulate(
+                            &status.events,
+                            now.saturating_sub(JITTER_SIMULATION_WINDOW_SEC),
+                            state.settings.jitter_buffer_ms as f64,
+                        );
+                        if r.packets == 0 {
+                            ui.label(tr!("No pings recorded in the last 5 minutes."));
+                        } else {
+                            ui.label(
+                                tr!("You would have heard {glitches} glitches in the last 5 minutes ({late} of {packets} packets late, {lost} lost).")
+                                    .replace("{glitches}", &r.glitches.to_string())
+                                    .replace("{late}", &r.late.to_string())
+                                    .replace("{packets}", &r.packets.to_string())
+                                    .replace("{lost}", &r.lost.to_string()),
+                            )
+                            .on_hover_text(tr!(
+                                "Replays the recorded pings through a fixed playout buffer, assuming one-way delay is half the RTT. Each run of consecutive late or lost packets counts as one glitch."
+                            ));
+                        }
+                    }
+                });
+
+            // 4. Rolled-up statistics of this session
+            if let Some(status) = state.statuses.get(addr) {
+                egui::CollapsingHeader::new(tr!("Recent statistics"))
+                    .id_salt("rollup_stats")
+                    .show(ui, |ui| {
+                        let now = chrono::Utc::now().timestamp() as u64;
+                        for (label, secs) in [(tr!("Last hour"), 3_600), (tr!("Last 24h"), 86_400)] {
+                            let s = status.rollups.summary_since(now.saturating_sub(secs));
+                            if s.sent == 0 {
+                                ui.label(format!("{}: {}", label, tr!("no complete minutes yet")));
+                            } else {
+                                ui.label(format!(
+                                    "{}: {} {:.2}%, {} {:.1} / {:.1} / {:.1} {}",
+                                    label,
+                                    tr!("loss"),
+                                    s.loss_pct(),
+                                    tr!("min / median / P95"),
+                                    s.min,
+                                    s.median,
+                                    s.p95,
+                                    tr!("ms")
+                                ));
+                            }
+                        }
+                    });
+            }
+
+            // 5. Long-term history from disk
+            if state.settings.persistent_history {
+                egui::CollapsingHeader::new(tr!("Long-term history"))
+                    .id_salt("long_term_history")
+                    .show(ui, |ui| render_history_aggregates(ui, addr));
+            }
+
+            ui.separator();
+
+            // 6. The Log View
             if let Some(status) = state.statuses.get(addr) {
                 let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
                 let total_events = status.events.len();
@@ -162,7 +412,9 @@ pub fn render_log_window(
                             }
                             LogEntry::Statistics { .. } => state.log_filter.show_stats,
                             LogEntry::RouteUpdate { .. } => state.log_filter.show_route,
-                            LogEntry::Incident { .. } => state.log_filter.show_incidents,
+                            LogEntry::Incident { .. } | LogEntry::Alert { .. } => {
+                                state.log_filter.show_incidents
+                            }
                             LogEntry::Marker { .. } => true,
                         })
                         .collect();
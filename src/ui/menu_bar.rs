@@ -0,0 +1,175 @@
+use crate::model::{RecentFile, RecentFileKind};
+use crate::ui::profiles::profile_label;
+use crate::ui::system_tools::ToolsTab;
+use eframe::egui;
+use tr::tr;
+
+/// Action picked in the menu bar, carried out by the application.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MenuAction {
+    OpenTools(ToolsTab),
+    OpenRecent(RecentFile),
+    ImportHosts,
+    ScanNetwork,
+    ClearRecent,
+    Settings,
+    Profiles,
+    SwitchProfile(String),
+    QuietHours,
+    Annotations,
+    Compare,
+    Pairs,
+    Dashboard,
+    DeletedHosts,
+    ActionLog,
+    Help,
+    CheckUpdates,
+    Quit,
+}
+
+/// Menu entry of a recent file: the file name, with the host for captures.
+fn recent_label(file: &RecentFile) -> String {
+    let name = std::path::Path::new(&file.path)
+        .file_name()
+        .map_or_else(|| file.path.clone(), |n| n.to_string_lossy().into_owned());
+    match file.kind {
+        RecentFileKind::Capture => format!("{} → {}", name, file.address),
+        RecentFileKind::HostList => format!("{} ({})", name, tr!("shared host list")),
+    }
+}
+
+/// Renders the File/View/Tools/Help menu bar with the profile selector and returns
+/// the picked action.
+pub fn render_menu_bar(
+    ui: &mut egui::Ui,
+    recent: &[RecentFile],
+    has_deleted: bool,
+    has_actions: bool,
+    profiles: &[String],
+    active_profile: &str,
+) -> Option<MenuAction> {
+    let mut action = None;
+    let mut profile_action = None;
+    let mut pick = |ui: &mut egui::Ui, enabled: bool, label: String, picked: MenuAction| {
+        if ui.add_enabled(enabled, egui::Button::new(label)).clicked() {
+            action = Some(picked);
+            ui.close();
+        }
+    };
+
+    egui::MenuBar::new().ui(ui, |ui| {
+        ui.menu_button(tr!("File"), |ui| {
+            pick(
+                ui,
+                true,
+                tr!("Import log…"),
+                MenuAction::OpenTools(ToolsTab::Import),
+            );
+            pick(ui, true, tr!("Import hosts…"), MenuAction::ImportHosts);
+            pick(ui, true, tr!("Scan network…"), MenuAction::ScanNetwork);
+            pick(ui, true, tr!("Profiles…"), MenuAction::Profiles);
+            ui.add_enabled_ui(!recent.is_empty(), |ui| {
+                ui.menu_button(tr!("Recent files"), |ui| {
+                    for file in recent {
+                        pick(
+                            ui,
+                            true,
+                            recent_label(file),
+                            MenuAction::OpenRecent(file.clone()),
+                        );
+                    }
+                    ui.separator();
+                    pick(ui, true, tr!("Clear list"), MenuAction::ClearRecent);
+                });
+            });
+            ui.separator();
+            pick(
+                ui,
+                true,
+                tr!("Export statistics…"),
+                MenuAction::OpenTools(ToolsTab::Export),
+            );
+            pick(
+                ui,
+                true,
+                tr!("Availability report…"),
+                MenuAction::OpenTools(ToolsTab::Report),
+            );
+            pick(
+                ui,
+                true,
+                tr!("Diagnostic bundle…"),
+                MenuAction::OpenTools(ToolsTab::BugReport),
+            );
+            ui.separator();
+            pick(ui, true, tr!("Quit"), MenuAction::Quit);
+        });
+
+        ui.menu_button(tr!("View"), |ui| {
+            pick(
+                ui,
+                has_deleted,
+                tr!("Deleted Hosts"),
+                MenuAction::DeletedHosts,
+            );
+            pick(ui, has_actions, tr!("Action Log"), MenuAction::ActionLog);
+            pick(ui, true, tr!("Quiet hours"), MenuAction::QuietHours);
+            pick(ui, true, tr!("Annotations…"), MenuAction::Annotations);
+            ui.separator();
+            pick(ui, true, tr!("Compare hosts…"), MenuAction::Compare);
+            pick(ui, true, tr!("Paired hosts…"), MenuAction::Pairs);
+            pick(ui, true, tr!("Wall dashboard"), MenuAction::Dashboard);
+        });
+
+        ui.menu_button(tr!("Tools"), |ui| {
+            pick(
+                ui,
+                true,
+                tr!("System commands"),
+                MenuAction::OpenTools(ToolsTab::Commands),
+            );
+            pick(
+                ui,
+                true,
+                tr!("ISP SLA"),
+                MenuAction::OpenTools(ToolsTab::Sla),
+            );
+            ui.separator();
+            pick(ui, true, tr!("Settings…"), MenuAction::Settings);
+        });
+
+        ui.menu_button(tr!("Help"), |ui| {
+            pick(ui, true, tr!("Help"), MenuAction::Help);
+            pick(
+                ui,
+                true,
+                tr!("Network guide"),
+                MenuAction::OpenTools(ToolsTab::Guide),
+            );
+            ui.separator();
+            pick(ui, true, tr!("Check for updates"), MenuAction::CheckUpdates);
+        });
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            egui::ComboBox::from_id_salt("profile_select")
+                .selected_text(profile_label(active_profile))
+                .show_ui(ui, |ui| {
+                    for name in profiles {
+                        if ui
+                            .selectable_label(name == active_profile, profile_label(name))
+                            .clicked()
+                            && name != active_profile
+                        {
+                            profile_action = Some(MenuAction::SwitchProfile(name.clone()));
+                        }
+                    }
+                    ui.separator();
+                    if ui.button(tr!("Profiles…")).clicked() {
+                        profile_action = Some(MenuAction::Profiles);
+                    }
+                });
+            ui.label(tr!("Profile:"));
+        });
+    });
+    action.or(profile_action)
+}
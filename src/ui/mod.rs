@@ -1,6 +1,38 @@
+pub mod action_log;
+pub mod alert_banner;
+pub mod annotations;
+pub mod bufferbloat;
+pub mod compare_window;
+pub mod dashboard;
+pub mod deleted_hosts;
+pub mod duplicate_host;
+pub mod first_run;
 pub mod help;
+pub mod host_card;
+pub mod host_filter;
+pub mod host_group;
+pub mod host_import;
 pub mod host_row;
 pub mod host_settings;
+pub mod kiosk;
 pub mod log_viewer;
+pub mod menu_bar;
+pub mod mtu;
+pub mod pair_window;
+pub mod plot_window;
+pub mod plots;
+pub mod probe_budget;
+pub mod profiles;
+pub mod public_ip;
+pub mod quiet_hours;
 pub mod route_viewer;
+pub mod settings;
+pub mod sound_settings;
+pub mod subnet_scan;
+pub mod summary_bar;
 pub mod system_tools;
+pub mod tags;
+pub mod toast;
+pub mod trace_view;
+pub mod update_dialog;
+pub mod watchdog;
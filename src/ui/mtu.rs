@@ -0,0 +1,102 @@
+use crate::app::PingVisuals;
+use crate::constants::MTU_MAX;
+use crate::logic::diagnostics::MtuDiscovery;
+use eframe::egui;
+use eframe::egui::{Color32, RichText};
+use std::time::Duration;
+use tr::tr;
+
+/// Renders the path MTU window with the tried packet sizes and the result.
+pub fn render_mtu_window(
+    ctx: &egui::Context,
+    visuals: &PingVisuals,
+    discovery: &mut Option<MtuDiscovery>,
+) {
+    let Some(running) = discovery.as_ref() else {
+        return;
+    };
+    let target = running.target.clone();
+    let progress = running.progress();
+    let mut open = true;
+    let mut restart = false;
+    let [r, g, b] = visuals.colors.ok;
+    let fits_color = Color32::from_rgb(r, g, b);
+    let [r, g, b] = visuals.colors.loss;
+    let too_large_color = Color32::from_rgb(r, g, b);
+
+    egui::Window::new(format!("{} - {}", tr!("Path MTU"), target))
+        .open(&mut open)
+        .resizable(false)
+        .default_width(340.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr!(
+                    "Largest packet that reaches the host without being fragmented"
+                ));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if progress.finished && ui.button("🔄").on_hover_text(tr!("Restart")).clicked()
+                    {
+                        restart = true;
+                    }
+                });
+            });
+            ui.separator();
+            if let Some(e) = &progress.error {
+                ui.colored_label(Color32::from_rgb(213, 94, 0), e);
+                return;
+            }
+            if progress.finished {
+                match progress.mtu {
+                    Some(mtu) => {
+                        ui.label(
+                            RichText::new(format!("MTU {}", mtu)).size(24.0).strong(),
+                        );
+                        ui.label(
+                            tr!("Largest ping payload: {payload} bytes")
+                                .replace("{payload}", &(mtu - progress.overhead).to_string()),
+                        );
+                        if mtu < MTU_MAX {
+                            // The IP header with a 20 byte TCP header instead of 8 bytes of ICMP
+                            let mss = mtu - (progress.overhead - 8 + 20);
+                            ui.label(
+                                tr!("The path carries less than a full Ethernet frame, typical of a VPN or PPPoE link. If large transfers stall, set the MTU of the tunnel to {mtu} or clamp the TCP MSS to {mss}.")
+                                    .replace("{mtu}", &mtu.to_string())
+                                    .replace("{mss}", &mss.to_string()),
+                            );
+                        }
+                    }
+                    None => {
+                        ui.colored_label(
+                            too_large_color,
+                            tr!("Not even the smallest packet got an answer"),
+                        );
+                    }
+                }
+            } else {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(tr!("Searching..."));
+                });
+            }
+            ui.add_space(4.0);
+            egui::Grid::new("mtu_tries").striped(true).show(ui, |ui| {
+                for (size, fits) in &progress.tried {
+                    ui.monospace(size.to_string());
+                    if *fits {
+                        ui.colored_label(fits_color, tr!("fits"));
+                    } else {
+                        ui.colored_label(too_large_color, tr!("too large or lost"));
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+
+    if !open {
+        *discovery = None;
+    } else if restart {
+        *discovery = Some(MtuDiscovery::start(target));
+    } else if !progress.finished {
+        ctx.request_repaint_after(Duration::from_millis(250));
+    }
+}
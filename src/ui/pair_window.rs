@@ -0,0 +1,111 @@
+use crate::app::PingVisuals;
+use crate::logic::pairs;
+use crate::model::AppState;
+use crate::ui::plot_window::time_of;
+use eframe::egui;
+use egui_plot::{HLine, Line, Plot, PlotPoint};
+use tr::tr;
+
+/// Renders the window with the latency and loss each paired host has over the
+/// host it is compared with, e.g. a destination through a VPN over the same one direct.
+pub fn render_pair_window(
+    ctx: &egui::Context,
+    visuals: &PingVisuals,
+    state: &AppState,
+    open: &mut bool,
+) {
+    let label_of = |address: &str| {
+        state
+            .hosts
+            .iter()
+            .find(|h| h.address == address)
+            .filter(|h| !h.name.is_empty())
+            .map_or_else(|| address.to_string(), |h| h.name.clone())
+    };
+    let pairs = pairs::pairs(&state.hosts);
+
+    egui::Window::new(tr!("Paired Hosts"))
+        .open(open)
+        .resizable(true)
+        .default_size([600.0, 400.0])
+        .show(ctx, |ui| {
+            if pairs.is_empty() {
+                ui.label(tr!(
+                    "Pick a host to compare with in the host settings (Compare with) to see the latency and loss one host adds over the other, e.g. the same destination through and around a VPN tunnel."
+                ));
+                return;
+            }
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (address, other) in &pairs {
+                    let (Some(a), Some(b)) = (state.statuses.get(address), state.statuses.get(other))
+                    else {
+                        continue;
+                    };
+                    ui.heading(format!("{} − {}", label_of(address), label_of(other)));
+                    let Some(delta) = pairs::compare(a, b) else {
+                        ui.label(tr!("Waiting for probes of both hosts…"));
+                        ui.separator();
+                        continue;
+                    };
+                    let thresholds = state
+                        .hosts
+                        .iter()
+                        .find(|h| h.address == *address)
+                        .map(|h| h.thresholds)
+                        .unwrap_or_default();
+                    let ms = |value: f64| format!("{:+.1} {}", value, tr!("ms"));
+                    egui::Grid::new(("pair", address))
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            ui.label(tr!("Latency overhead:")).on_hover_text(tr!(
+                                "Median difference of the replies both hosts received at about the same time"
+                            ));
+                            ui.label(ms(delta.latency_ms));
+                            ui.end_row();
+                            ui.label(tr!("95th percentile:"));
+                            ui.label(ms(delta.p95_ms));
+                            ui.end_row();
+                            ui.label(tr!("Jitter:"));
+                            ui.label(ms(delta.jitter_ms));
+                            ui.end_row();
+                            ui.label(tr!("Loss:"));
+                            let loss = format!("{:+.1} {}", delta.loss_pct, tr!("pp"));
+                            match visuals.value_color(
+                                delta.loss_pct,
+                                thresholds.loss_warn,
+                                thresholds.loss_bad,
+                                false,
+                            ) {
+                                Some(color) => ui.colored_label(color, loss),
+                                None => ui.label(loss),
+                            }
+                            .on_hover_text(tr!("Difference of the loss in percentage points"));
+                            ui.end_row();
+                            ui.label(tr!("Matched replies:"));
+                            ui.label(delta.matched.to_string());
+                            ui.end_row();
+                        });
+
+                    let series = pairs::delta_series(a, b);
+                    if !series.is_empty() {
+                        Plot::new(("pair_plot", address))
+                            .height(120.0)
+                            .x_axis_formatter(|mark, _| time_of(mark.value))
+                            .y_axis_label(tr!("Δ RTT, ms"))
+                            .label_formatter(|_, value: &PlotPoint| {
+                                format!("{}\n{:+.1} {}", time_of(value.x), value.y, tr!("ms"))
+                            })
+                            .show(ui, |plot_ui| {
+                                plot_ui.hline(
+                                    HLine::new("", 0.0)
+                                        .color(visuals.limit_line_color())
+                                        .width(1.0),
+                                );
+                                plot_ui.line(Line::new(tr!("Latency overhead"), series).width(1.5));
+                            });
+                    }
+                    ui.separator();
+                }
+            });
+        });
+}
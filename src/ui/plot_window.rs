@@ -0,0 +1,227 @@
+use crate::app::PingVisuals;
+use crate::constants::DEFAULT_HISTOGRAM_BUCKET_MS;
+use crate::logic::presenter;
+use crate::model::AppState;
+use crate::ui::plots;
+use eframe::egui;
+use eframe::egui::Color32;
+use egui_plot::{
+    HLine, Legend, Line, LineStyle, MarkerShape, Plot, PlotPoint, Points, Polygon, Text, VLine,
+};
+use tr::tr;
+
+/// Formats plot x values (Unix timestamps) as local time of day.
+pub fn time_of(x: f64) -> String {
+    chrono::DateTime::from_timestamp(x.round() as i64, 0)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+/// Renders the large plot of the recent samples of a host: RTT with loss markers,
+/// mean/median/P95 overlays and annotations, and the jitter below it.
+pub fn render_plot_window(
+    ctx: &egui::Context,
+    visuals: &PingVisuals,
+    state: &AppState,
+    viewing_plot: &mut Option<String>,
+) {
+    let Some(addr) = viewing_plot.clone() else {
+        return;
+    };
+    let Some(status) = state.statuses.get(&addr) else {
+        *viewing_plot = None;
+        return;
+    };
+    let host = state.hosts.iter().find(|h| h.address == addr);
+    let title = host
+        .filter(|h| !h.name.is_empty())
+        .map_or_else(|| addr.clone(), |h| format!("{} ({})", h.name, addr));
+    let pauses = presenter::pauses(
+        &status.history,
+        host.map_or(0, |h| h.mode.interval_secs()),
+        chrono::Utc::now().timestamp() as u64,
+    );
+
+    // Overlays shown: mean, median, P95, jitter
+    let overlays_id = egui::Id::new("plot_window_overlays");
+    let mut shown: [bool; 4] = ctx
+        .data_mut(|d| d.get_temp(overlays_id))
+        .unwrap_or([true; 4]);
+    // Histogram instead of the timeline, and its bucket width
+    let histogram_id = egui::Id::new("plot_window_histogram");
+    let (mut histogram, mut bucket_ms): (bool, f64) = ctx
+        .data_mut(|d| d.get_temp(histogram_id))
+        .unwrap_or((false, DEFAULT_HISTOGRAM_BUCKET_MS));
+
+    let mut open = true;
+    egui::Window::new(format!("{} - {}", tr!("Plot"), title))
+        .id(egui::Id::new("plot_window").with(&addr))
+        .open(&mut open)
+        .resizable(true)
+        .default_size([800.0, 450.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut histogram, false, tr!("Timeline"));
+                ui.selectable_value(&mut histogram, true, tr!("Histogram"));
+                ui.separator();
+                if histogram {
+                    ui.label(tr!("Bucket width:"));
+                    ui.add(
+                        egui::DragValue::new(&mut bucket_ms)
+                            .range(0.1..=100.0)
+                            .speed(0.1)
+                            .suffix(tr!(" ms")),
+                    );
+                    return;
+                }
+                ui.checkbox(&mut shown[0], tr!("Mean"));
+                ui.checkbox(&mut shown[1], tr!("Median"));
+                ui.checkbox(&mut shown[2], tr!("P95"));
+                ui.checkbox(&mut shown[3], tr!("Jitter"));
+                ui.label(
+                    egui::RichText::new(tr!(
+                        "Scroll or drag to zoom and pan, double-click to reset"
+                    ))
+                    .small(),
+                );
+            });
+
+            if histogram {
+                let thresholds = host.map_or(visuals.thresholds, |h| h.thresholds);
+                plots::render_histogram(ui, visuals, &addr, status, bucket_ms, &thresholds);
+                return;
+            }
+
+            // The line is broken at the pauses instead of bridging them
+            let mut rtt: Vec<Vec<[f64; 2]>> = vec![Vec::new()];
+            let mut breaks = pauses.iter().map(|p| p.index).peekable();
+            for (i, sample) in status.history.iter().enumerate() {
+                if breaks.next_if_eq(&i).is_some() {
+                    rtt.push(Vec::new());
+                }
+                if let (Some(r), Some(segment)) = (sample.rtt, rtt.last_mut()) {
+                    segment.push([sample.timestamp as f64, r]);
+                }
+            }
+            let losses: Vec<[f64; 2]> = status
+                .history
+                .iter()
+                .filter(|s| s.is_lost())
+                .map(|s| [s.timestamp as f64, 0.0])
+                .collect();
+            let top = status.max_rtt.max(1.0);
+            let first = status.history.front().map_or(0, |s| s.timestamp);
+            let notes: Vec<_> = state
+                .annotations
+                .iter()
+                .filter(|a| a.timestamp >= first)
+                .collect();
+
+            let jitter_height = if shown[3] {
+                ui.available_height() * 0.3
+            } else {
+                0.0
+            };
+            let label = |name: &str, value: &PlotPoint| {
+                let time = time_of(value.x);
+                if name.is_empty() {
+                    format!("{}\n{:.1} {}", time, value.y, tr!("ms"))
+                } else {
+                    format!("{}\n{}\n{:.1} {}", name, time, value.y, tr!("ms"))
+                }
+            };
+            Plot::new(("plot_detail", &addr))
+                .height(ui.available_height() - jitter_height)
+                .legend(Legend::default())
+                .link_axis("plot_detail_x", [true, false])
+                .link_cursor("plot_detail_x", [true, false])
+                .x_axis_formatter(|mark, _| time_of(mark.value))
+                .y_axis_label(tr!("RTT, ms"))
+                .label_formatter(label)
+                .include_y(0.0)
+                .show(ui, |plot_ui| {
+                    for pause in &pauses {
+                        let (start, end) = (pause.start as f64, pause.end as f64);
+                        plot_ui.polygon(
+                            Polygon::new(
+                                tr!("Not measured"),
+                                vec![[start, 0.0], [end, 0.0], [end, top], [start, top]],
+                            )
+                            .fill_color(visuals.pause_color())
+                            .stroke(egui::Stroke::NONE),
+                        );
+                    }
+                    for segment in rtt {
+                        plot_ui.line(
+                            Line::new(tr!("RTT"), segment)
+                                .color(visuals.latency_color(0.0))
+                                .width(1.5),
+                        );
+                    }
+                    plot_ui.points(
+                        Points::new(tr!("Loss"), losses)
+                            .shape(MarkerShape::Cross)
+                            .radius(4.0)
+                            .color(visuals.latency_color(f64::NAN)),
+                    );
+                    let overlays = [
+                        (shown[0], tr!("Mean"), status.mean, LineStyle::Solid),
+                        (
+                            shown[1],
+                            tr!("Median"),
+                            status.median,
+                            LineStyle::dashed_dense(),
+                        ),
+                        (shown[2], tr!("P95"), status.p95, LineStyle::dashed_loose()),
+                    ];
+                    for (on, name, value, style) in overlays {
+                        if on && status.sent > status.lost {
+                            plot_ui.hline(HLine::new(name, value).style(style).width(1.0));
+                        }
+                    }
+                    for note in &notes {
+                        let x = note.timestamp as f64;
+                        plot_ui.vline(
+                            VLine::new("", x)
+                                .color(visuals.annotation_color())
+                                .width(1.5),
+                        );
+                        plot_ui.text(
+                            Text::new("", PlotPoint::new(x, top), format!("📌 {}", note.text))
+                                .color(visuals.annotation_color())
+                                .anchor(egui::Align2::LEFT_TOP),
+                        );
+                    }
+                });
+
+            if shown[3] {
+                let jitter = presenter::jitter_series(&status.history);
+                Plot::new(("plot_detail_jitter", &addr))
+                    .height(ui.available_height())
+                    .link_axis("plot_detail_x", [true, false])
+                    .link_cursor("plot_detail_x", [true, false])
+                    .x_axis_formatter(|mark, _| time_of(mark.value))
+                    .y_axis_label(tr!("Jitter, ms"))
+                    .label_formatter(label)
+                    .include_y(0.0)
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(
+                            Line::new(tr!("Jitter"), jitter)
+                                .color(Color32::from_rgb(204, 121, 167))
+                                .width(1.5),
+                        );
+                    });
+            }
+        });
+    ctx.data_mut(|d| {
+        d.insert_temp(overlays_id, shown);
+        d.insert_temp(histogram_id, (histogram, bucket_ms));
+    });
+    if !open {
+        *viewing_plot = None;
+    }
+}
@@ -0,0 +1,208 @@
+//! Alternative renderers of a host's history next to the bar mini-chart.
+
+use crate::app::PingVisuals;
+use crate::constants::{
+    HEATMAP_RTT_BUCKETS_MS, HEATMAP_SAMPLES_PER_COLUMN, HISTORY_WINDOW_SIZE, MAX_HISTOGRAM_BUCKETS,
+};
+use crate::logic::presenter::{self, HEATMAP_ROWS};
+use crate::model::{Annotation, HostStatus, Sample, Thresholds};
+use eframe::egui;
+use eframe::egui::{Color32, Rect, Sense, Stroke, pos2, vec2};
+use egui_plot::{Bar, BarChart, Legend, LineStyle, Plot, VLine};
+use tr::tr;
+
+/// Label of a heatmap row, from the fastest bucket to the lost probes.
+fn row_label(row: usize) -> String {
+    match row {
+        r if r < HEATMAP_RTT_BUCKETS_MS.len() => {
+            format!("≤ {} {}", HEATMAP_RTT_BUCKETS_MS[r], tr!("ms"))
+        }
+        r if r == HEATMAP_ROWS - 1 => tr!("Lost"),
+        _ => format!(
+            "> {} {}",
+            HEATMAP_RTT_BUCKETS_MS[HEATMAP_RTT_BUCKETS_MS.len() - 1],
+            tr!("ms")
+        ),
+    }
+}
+
+/// Color of a heatmap row at full intensity.
+fn row_color(visuals: &PingVisuals, row: usize, thresholds: &Thresholds) -> Color32 {
+    let rtt = match row {
+        r if r == HEATMAP_ROWS - 1 => f64::NAN,
+        r if r < HEATMAP_RTT_BUCKETS_MS.len() => HEATMAP_RTT_BUCKETS_MS[r],
+        _ => f64::INFINITY,
+    };
+    visuals.host_latency_color(rtt, thresholds)
+}
+
+/// Renders the last samples as a heatmap of the size of the mini-chart: columns of
+/// consecutive samples, rows of RTT buckets with lost probes on top, and the share
+/// of the column's samples in a bucket as the intensity of its color. Pauses in
+/// measuring (with samples every `interval_sec`) are grey bands. Rows are colored
+/// by the host's `thresholds`.
+///
+/// Returns whether it was clicked.
+pub fn render_heatmap(
+    ui: &mut egui::Ui,
+    visuals: &PingVisuals,
+    status: &HostStatus,
+    annotations: &[Annotation],
+    interval_sec: u64,
+    thresholds: &Thresholds,
+) -> bool {
+    let plot = &visuals.plot;
+    let samples = plot.samples.clamp(1, HISTORY_WINDOW_SIZE);
+    let mut skip = status.history.len().saturating_sub(samples);
+    // Columns start at fixed sequence numbers so they do not shift with every new sample
+    if let Some(first) = status.history.get(skip) {
+        let offset = (first.seq as usize).saturating_sub(1) % HEATMAP_SAMPLES_PER_COLUMN;
+        skip += (HEATMAP_SAMPLES_PER_COLUMN - offset) % HEATMAP_SAMPLES_PER_COLUMN;
+    }
+    let recent: Vec<Sample> = status.history.iter().skip(skip).cloned().collect();
+    let columns = presenter::heatmap_columns(&recent, HEATMAP_SAMPLES_PER_COLUMN);
+    let slots: Vec<u64> = columns.iter().map(|c| c.start).collect();
+    let markers = presenter::annotation_positions(annotations, &slots);
+    let pauses = presenter::pauses(&recent, interval_sec, chrono::Utc::now().timestamp() as u64);
+
+    let (rect, response) = ui.allocate_exact_size(vec2(plot.width, plot.height), Sense::click());
+    let column_width = rect.width() / samples.div_ceil(HEATMAP_SAMPLES_PER_COLUMN) as f32;
+    let row_height = rect.height() / HEATMAP_ROWS as f32;
+    let painter = ui.painter_at(rect);
+    for (i, column) in columns.iter().enumerate() {
+        let total = column.total().max(1) as f32;
+        let left = rect.left() + i as f32 * column_width;
+        for (row, &count) in column.counts.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let bottom = rect.bottom() - row as f32 * row_height;
+            let cell = Rect::from_min_max(
+                pos2(left, bottom - row_height),
+                pos2(left + column_width, bottom),
+            );
+            let share = count as f32 / total;
+            painter.rect_filled(
+                cell,
+                0.0,
+                row_color(visuals, row, thresholds).gamma_multiply(0.25 + 0.75 * share),
+            );
+        }
+    }
+    let pause_x = |index: usize| {
+        rect.left() + index as f32 / HEATMAP_SAMPLES_PER_COLUMN as f32 * column_width
+    };
+    for pause in &pauses {
+        let x = pause_x(pause.index);
+        painter.rect_filled(
+            Rect::from_min_max(pos2(x - 2.0, rect.top()), pos2(x + 2.0, rect.bottom())),
+            0.0,
+            visuals.pause_color(),
+        );
+    }
+    for (x, _) in &markers {
+        let x = rect.left() + (*x as f32 + 0.5) * column_width;
+        painter.line_segment(
+            [pos2(x, rect.top()), pos2(x, rect.bottom())],
+            Stroke::new(1.5, visuals.annotation_color()),
+        );
+    }
+
+    let clicked = response.clicked();
+    let hover_x = response.hover_pos().map(|pos| pos.x);
+    response.on_hover_ui(|ui| {
+        let Some(hover_x) = hover_x else {
+            return;
+        };
+        let i = ((hover_x - rect.left()) / column_width).floor() as usize;
+        for pause in pauses
+            .iter()
+            .filter(|p| (pause_x(p.index) - hover_x).abs() < 4.0)
+        {
+            ui.label(format!("⏸ {}", presenter::pause_text(pause)));
+        }
+        for (_, note) in markers
+            .iter()
+            .filter(|(x, _)| (x + 0.5).round() as usize == i)
+        {
+            ui.colored_label(visuals.annotation_color(), format!("📌 {}", note.text));
+        }
+        if let Some(column) = columns.get(i) {
+            let time = chrono::DateTime::from_timestamp(column.start as i64, 0)
+                .map(|t| {
+                    t.with_timezone(&chrono::Local)
+                        .format("%H:%M:%S")
+                        .to_string()
+                })
+                .unwrap_or_default();
+            ui.label(
+                tr!("{count} samples from {time}")
+                    .replace("{count}", &column.total().to_string())
+                    .replace("{time}", &time),
+            );
+            for (row, &count) in column.counts.iter().enumerate().rev() {
+                if count > 0 {
+                    ui.colored_label(
+                        row_color(visuals, row, thresholds),
+                        format!("{}: {}", row_label(row), count),
+                    );
+                }
+            }
+        }
+        ui.label(egui::RichText::new(tr!("Click for the detailed plot")).small());
+    });
+    clicked
+}
+
+/// Renders the distribution of the RTTs in the history in buckets of `bucket_ms`,
+/// with the median and P95 marked, e.g. to reveal the two peaks of Wi-Fi that
+/// wakes from power saving.
+pub fn render_histogram(
+    ui: &mut egui::Ui,
+    visuals: &PingVisuals,
+    id: &str,
+    status: &HostStatus,
+    bucket_ms: f64,
+    thresholds: &Thresholds,
+) {
+    let counts = presenter::histogram(&status.history, bucket_ms);
+    let bars: Vec<Bar> = counts
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count > 0)
+        .map(|(i, &count)| {
+            let start = i as f64 * bucket_ms;
+            let name = if i == MAX_HISTOGRAM_BUCKETS - 1 {
+                format!("≥ {:.1} {}", start, tr!("ms"))
+            } else {
+                format!("{:.1}–{:.1} {}", start, start + bucket_ms, tr!("ms"))
+            };
+            Bar::new(start + bucket_ms / 2.0, count as f64)
+                .width(bucket_ms)
+                .fill(visuals.host_latency_color(start, thresholds))
+                .name(name)
+        })
+        .collect();
+    let replies = status.sent.saturating_sub(status.lost);
+    Plot::new(("plot_histogram", id))
+        .legend(Legend::default())
+        .x_axis_label(tr!("RTT, ms"))
+        .y_axis_label(tr!("Replies"))
+        .include_x(0.0)
+        .include_y(0.0)
+        .show(ui, |plot_ui| {
+            plot_ui.bar_chart(BarChart::new(tr!("Replies"), bars));
+            if replies > 0 {
+                plot_ui.vline(
+                    VLine::new(tr!("Median"), status.median)
+                        .style(LineStyle::dashed_dense())
+                        .width(1.5),
+                );
+                plot_ui.vline(
+                    VLine::new(tr!("P95"), status.p95)
+                        .style(LineStyle::dashed_loose())
+                        .width(1.5),
+                );
+            }
+        });
+}
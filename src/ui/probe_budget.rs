@@ -0,0 +1,53 @@
+use crate::logic::budget::BudgetWarning;
+use crate::model::PingMode;
+use eframe::egui;
+use eframe::egui::{Color32, RichText};
+use tr::tr;
+
+/// Shows a banner when the hosts need more probes per second than the budget allows.
+///
+/// Returns the suggested mode if the user chose to apply it (only offered when `can_apply`).
+pub fn render_probe_budget_banner(
+    ui: &mut egui::Ui,
+    warning: Option<&BudgetWarning>,
+    can_apply: bool,
+) -> Option<PingMode> {
+    let warning = warning?;
+    let mut apply = None;
+
+    let color = Color32::from_rgb(230, 159, 0);
+    egui::Frame::new()
+        .stroke(egui::Stroke::new(1.0, color))
+        .inner_margin(6.0)
+        .corner_radius(4.0)
+        .show(ui, |ui| {
+            ui.colored_label(
+                color,
+                RichText::new(
+                    tr!("Probe budget exceeded: {required} probes/s needed, {budget} allowed. Ping intervals will drift.")
+                        .replace("{required}", &format!("{:.1}", warning.required))
+                        .replace("{budget}", &format!("{:.0}", warning.budget)),
+                )
+                .strong(),
+            );
+            ui.horizontal_wrapped(|ui| match warning.suggested_mode {
+                Some(mode) => {
+                    ui.label(
+                        tr!("Suggestion: set {count} hosts to {mode}.")
+                            .replace("{count}", &warning.affected_hosts.to_string())
+                            .replace("{mode}", &mode.label()),
+                    );
+                    if can_apply && ui.button(tr!("Apply")).clicked() {
+                        apply = Some(mode);
+                    }
+                }
+                None => {
+                    ui.label(tr!(
+                        "Stop or remove some hosts, or raise the probe budget in Settings."
+                    ));
+                }
+            });
+        });
+    ui.add_space(4.0);
+    apply
+}
@@ -0,0 +1,209 @@
+use crate::logic::profiles::ProfileChange;
+use crate::model::{AppState, NetworkInfo, ProfileNetwork};
+use eframe::egui;
+use eframe::egui::RichText;
+use tr::tr;
+
+/// State of the profiles window.
+#[derive(Default)]
+pub struct ProfilesState {
+    pub open: bool,
+    pub new_name: String,
+    /// Profile whose deletion waits for confirmation.
+    pub deleting: Option<String>,
+    /// Change picked in the window or the menu bar, applied by the application.
+    pub pending: Option<ProfileChange>,
+    /// Why the last change failed.
+    pub error: Option<String>,
+    /// Profile of the detected network at the last automatic switch.
+    pub auto_matched: Option<String>,
+}
+
+impl ProfilesState {
+    /// Switches to the profile of the detected network when it changes. A profile
+    /// picked by hand stays until the machine moves to another known network.
+    pub fn follow_network(&mut self, state: &AppState) {
+        if !state.auto_profile {
+            self.auto_matched = None;
+            return;
+        }
+        let matched = state.matching_profile();
+        if matched == self.auto_matched {
+            return;
+        }
+        self.auto_matched = matched.clone();
+        if let Some(name) = matched.filter(|name| *name != state.profile)
+            && self.pending.is_none()
+        {
+            self.pending = Some(ProfileChange::Switch(name));
+        }
+    }
+}
+
+/// One line describing a detected network.
+fn describe_network(network: &NetworkInfo) -> String {
+    let mut parts = Vec::new();
+    if let Some(ssid) = &network.ssid {
+        parts.push(format!("Wi-Fi \"{}\"", ssid));
+    }
+    match (&network.gateway, &network.gateway_mac) {
+        (Some(ip), Some(mac)) => parts.push(format!("{} {} ({})", tr!("gateway"), ip, mac)),
+        (Some(ip), None) => parts.push(format!("{} {}", tr!("gateway"), ip)),
+        _ => {}
+    }
+    if let Some(ip) = &network.public_ip {
+        parts.push(format!("{} {}", tr!("public IP"), ip));
+    }
+    if parts.is_empty() {
+        tr!("no network detected")
+    } else {
+        parts.join(", ")
+    }
+}
+
+/// Display name of a profile; the default profile has an empty name.
+pub fn profile_label(name: &str) -> String {
+    if name.is_empty() {
+        tr!("Default")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Renders the window creating, switching and deleting configuration profiles.
+pub fn render_profiles_window(
+    ctx: &egui::Context,
+    profiles: &mut ProfilesState,
+    state: &mut AppState,
+) {
+    let names = state.profile_names();
+    let active = state.profile.clone();
+    let mut open = profiles.open;
+    egui::Window::new(tr!("Profiles"))
+        .open(&mut open)
+        .resizable(false)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            ui.label(
+                RichText::new(tr!(
+                    "Each profile has its own host list and settings, e.g. for home, the office or a customer network."
+                ))
+                .small(),
+            );
+            ui.separator();
+            for name in &names {
+                ui.horizontal(|ui| {
+                    let is_active = *name == active;
+                    if ui
+                        .selectable_label(is_active, profile_label(name))
+                        .clicked()
+                        && !is_active
+                    {
+                        profiles.pending = Some(ProfileChange::Switch(name.clone()));
+                    }
+                    if is_active {
+                        return;
+                    }
+                    if profiles.deleting.as_ref() == Some(name) {
+                        ui.label(tr!("Delete with its hosts?"));
+                        if ui.button(tr!("Yes")).clicked() {
+                            profiles.pending = Some(ProfileChange::Delete(name.clone()));
+                            profiles.deleting = None;
+                        }
+                        if ui.button(tr!("No")).clicked() {
+                            profiles.deleting = None;
+                        }
+                    } else if ui.button("🗑").on_hover_text(tr!("Delete")).clicked() {
+                        profiles.deleting = Some(name.clone());
+                    }
+                });
+                if state.auto_profile {
+                    let network = state.profile_networks.entry(name.clone()).or_default();
+                    render_network_editor(ui, name, network, state.network.as_ref());
+                }
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut profiles.new_name)
+                        .hint_text(tr!("Office"))
+                        .desired_width(160.0),
+                );
+                let submitted =
+                    response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if (ui.button(tr!("Create")).clicked() || submitted)
+                    && !profiles.new_name.trim().is_empty()
+                {
+                    profiles.pending = Some(ProfileChange::Create(profiles.new_name.clone()));
+                    profiles.new_name.clear();
+                }
+            });
+            ui.label(
+                RichText::new(tr!(
+                    "A new profile starts with the current settings and no hosts."
+                ))
+                .small()
+                .weak(),
+            );
+            ui.separator();
+            ui.checkbox(&mut state.auto_profile, tr!("Switch automatically by network"))
+                .on_hover_text(tr!(
+                    "Activates the profile of the network the machine is on, recognized by the MAC address of the gateway, the Wi-Fi name or the public IP. A profile picked by hand stays until the network changes."
+                ));
+            if state.auto_profile {
+                let current = state
+                    .network
+                    .as_ref()
+                    .map_or_else(|| tr!("detecting…"), describe_network);
+                ui.label(
+                    RichText::new(format!("{}: {}", tr!("Current network"), current)).small(),
+                );
+            }
+            if let Some(e) = &profiles.error {
+                ui.colored_label(egui::Color32::from_rgb(213, 94, 0), e);
+            }
+        });
+    profiles.open = open;
+    state.profile_networks.retain(|_, n| !n.is_empty());
+}
+
+/// Renders the fields of the network a profile is activated on.
+fn render_network_editor(
+    ui: &mut egui::Ui,
+    name: &str,
+    network: &mut ProfileNetwork,
+    current: Option<&NetworkInfo>,
+) {
+    ui.push_id(name, |ui| {
+        ui.indent("network", |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr!("Gateway MAC:"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut network.gateway_mac)
+                        .hint_text("00:11:22:33:44:55")
+                        .desired_width(130.0),
+                );
+                ui.label(tr!("Wi-Fi:"));
+                ui.add(egui::TextEdit::singleline(&mut network.ssid).desired_width(100.0));
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr!("Public IP:"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut network.public_ip)
+                        .hint_text("203.0.113.7")
+                        .desired_width(130.0),
+                );
+                if let Some(current) = current
+                    && ui
+                        .button(tr!("Use current network"))
+                        .on_hover_text(tr!(
+                            "Fills in the gateway MAC and the Wi-Fi name detected now"
+                        ))
+                        .clicked()
+                {
+                    *network = ProfileNetwork::from_info(current);
+                }
+            });
+        });
+    });
+}
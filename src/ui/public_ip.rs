@@ -0,0 +1,37 @@
+use crate::logic::presenter::format_time;
+use crate::model::PublicIpStatus;
+use eframe::egui;
+use eframe::egui::RichText;
+use tr::tr;
+
+/// Renders the line with the public IP addresses of the machine. A click on an
+/// address copies it.
+pub fn render_public_ip_panel(ui: &mut egui::Ui, status: &PublicIpStatus) {
+    ui.horizontal_wrapped(|ui| {
+        ui.label(tr!("Public IP:"));
+        if status.checked_at.is_none() {
+            ui.weak(tr!("looking up…"));
+            return;
+        }
+        for ip in [status.v4, status.v6].into_iter().flatten() {
+            let text = ip.to_string();
+            if ui
+                .add(
+                    egui::Label::new(RichText::new(&text).monospace().strong())
+                        .sense(egui::Sense::click()),
+                )
+                .on_hover_text(tr!("Click to copy"))
+                .clicked()
+            {
+                ui.ctx().copy_text(text);
+            }
+        }
+        if let Some(e) = &status.error {
+            ui.colored_label(egui::Color32::from_rgb(213, 94, 0), "⚠")
+                .on_hover_text(tr!("The last lookup failed: {error}").replace("{error}", e));
+        }
+        if let Some(ts) = status.checked_at {
+            ui.weak(tr!("checked {time}").replace("{time}", &format_time(ts)));
+        }
+    });
+}
@@ -0,0 +1,100 @@
+use crate::model::schedule::weekday_labels;
+use crate::model::{AppState, QuietRange, QuietSchedule};
+use crate::ui::sound_settings::time_of_day;
+use eframe::egui;
+use eframe::egui::RichText;
+use tr::tr;
+
+/// Renders the editor of a weekly quiet schedule.
+pub fn render_schedule_editor(ui: &mut egui::Ui, id_salt: &str, schedule: &mut QuietSchedule) {
+    ui.push_id(id_salt, |ui| {
+        ui.checkbox(&mut schedule.enabled, tr!("Enabled"));
+        ui.add_enabled_ui(schedule.enabled, |ui| {
+            let days = weekday_labels();
+            let mut remove = None;
+            for (i, range) in schedule.ranges.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    for (day, label) in days.iter().enumerate() {
+                        ui.toggle_value(&mut range.days[day], label);
+                    }
+                    ui.separator();
+                    ui.push_id(i, |ui| {
+                        ui.add(time_of_day(&mut range.start_min));
+                        ui.label("–");
+                        ui.add(time_of_day(&mut range.end_min));
+                    });
+                    if ui.button("🗑").on_hover_text(tr!("Remove")).clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = remove {
+                schedule.ranges.remove(i);
+            }
+            if ui.button(tr!("Add time range")).clicked() {
+                schedule.ranges.push(QuietRange::default());
+            }
+        });
+    });
+}
+
+/// Renders the window editing the global quiet hours and those of single hosts.
+pub fn render_quiet_hours_window(ctx: &egui::Context, state: &mut AppState, open: &mut bool) {
+    let selected_id = egui::Id::new("quiet_hours_host");
+    let mut selected: Option<String> = ctx.data_mut(|d| d.get_temp(selected_id)).flatten();
+    egui::Window::new(tr!("Quiet Hours"))
+        .open(open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(
+                RichText::new(tr!(
+                    "No notifications, sounds, webhooks or emails are sent during quiet hours; statistics and the event log are still recorded. A range ending before it starts continues into the next day."
+                ))
+                .small(),
+            );
+
+            ui.separator();
+            ui.strong(tr!("All hosts"));
+            render_schedule_editor(ui, "global", &mut state.settings.quiet_schedule);
+
+            ui.separator();
+            ui.strong(tr!("Single host"));
+            let name_of = |address: &str| {
+                state
+                    .hosts
+                    .iter()
+                    .find(|h| h.address == address)
+                    .map(|h| {
+                        if h.name.is_empty() {
+                            h.address.clone()
+                        } else {
+                            format!("{} ({})", h.name, h.address)
+                        }
+                    })
+                    .unwrap_or_default()
+            };
+            egui::ComboBox::from_id_salt("quiet_hours_host_select")
+                .selected_text(
+                    selected
+                        .as_deref()
+                        .map(name_of)
+                        .unwrap_or_else(|| tr!("Select a host")),
+                )
+                .show_ui(ui, |ui| {
+                    for host in &state.hosts {
+                        let mut label = name_of(&host.address);
+                        if host.quiet_schedule.enabled {
+                            label = format!("🌙 {}", label);
+                        }
+                        ui.selectable_value(&mut selected, Some(host.address.clone()), label);
+                    }
+                });
+            if let Some(host) = selected
+                .as_ref()
+                .and_then(|a| state.hosts.iter_mut().find(|h| &h.address == a))
+            {
+                render_schedule_editor(ui, "host", &mut host.quiet_schedule);
+            }
+        });
+    ctx.data_mut(|d| d.insert_temp(selected_id, selected));
+}
@@ -0,0 +1,918 @@
+use crate::constants::{DEFAULT_PLOT_TIME_SPAN_MIN, HISTORY_CAPACITY_RECORDS, HISTORY_WINDOW_SIZE};
+use crate::logic::email::DownReport;
+use crate::logic::updates::UpdateChecker;
+use crate::logic::{email, http, i18n, keyring, storage, trace_context, webhook};
+use crate::model::{
+    AgentSettings, ApiSettings, AppSettings, ColorTheme, DhcpLeaseStatus, EmailSettings,
+    MqttSettings, PlotSettings, ProxyMode, ProxySettings, RecentFile, RecentFileKind, RemoteAgent,
+    RemoteAgentStatus, SharedListStatus, SmtpSecurity, WebhookPreset, WebhookTarget,
+};
+use eframe::egui;
+use eframe::egui::RichText;
+use std::collections::HashMap;
+use tr::tr;
+
+/// Renders the application-wide settings window.
+#[allow(clippy::too_many_arguments)]
+pub fn render_settings_window(
+    ctx: &egui::Context,
+    settings: &mut AppSettings,
+    shared_list: &SharedListStatus,
+    dhcp_leases: &DhcpLeaseStatus,
+    stream_error: Option<&str>,
+    daemon_attached: bool,
+    agent_error: Option<&str>,
+    agent_status: &HashMap<String, RemoteAgentStatus>,
+    api_error: Option<&str>,
+    mqtt_status: (bool, Option<&str>),
+    updates: &mut UpdateChecker,
+    quiet_hours_open: &mut bool,
+    kiosk_lock: &mut Option<String>,
+    open: &mut bool,
+) {
+    egui::Window::new(tr!("Settings"))
+        .open(open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.strong(tr!("Language"));
+            let language_name = |code: &str| {
+                i18n::LANGUAGES
+                    .iter()
+                    .find(|(c, _)| *c == code)
+                    .map_or_else(|| tr!("System default"), |(_, name)| name.to_string())
+            };
+            let previous = settings.language.clone();
+            egui::ComboBox::from_id_salt("language")
+                .selected_text(language_name(&settings.language))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut settings.language, String::new(), tr!("System default"));
+                    for (code, name) in i18n::LANGUAGES {
+                        ui.selectable_value(&mut settings.language, code.to_string(), name);
+                    }
+                });
+            if settings.language != previous {
+                i18n::set_language(&settings.language);
+            }
+
+            ui.separator();
+            ui.strong(tr!("Updates"));
+            ui.checkbox(
+                &mut settings.check_for_updates,
+                tr!("Check for updates automatically"),
+            )
+            .on_hover_text(tr!(
+                "Queries GitHub once a day. Disable this for offline deployments."
+            ));
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(!updates.is_checking, egui::Button::new(tr!("Check now")))
+                    .clicked()
+                {
+                    updates.start_check(true, settings.proxy.clone());
+                }
+                if updates.is_checking {
+                    ui.spinner();
+                }
+            });
+
+            ui.separator();
+            ui.strong(tr!("Alerts"));
+            ui.checkbox(
+                &mut settings.desktop_notifications,
+                tr!("Desktop notifications"),
+            )
+            .on_hover_text(tr!(
+                "Show a system notification when an alert rule of a host is triggered"
+            ));
+            ui.checkbox(
+                &mut settings.taskbar_badge,
+                tr!("Show the number of down hosts on the taskbar icon"),
+            )
+            .on_hover_text(tr!(
+                "Draws the count on the window icon, where the window manager shows it (Windows, X11). Not on macOS or Wayland."
+            ));
+            ui.checkbox(
+                &mut settings.compare_sessions,
+                tr!("Compare with the previous session after startup"),
+            )
+            .on_hover_text(tr!(
+                "A few minutes after the start, point out hosts whose latency or loss is clearly worse than in the previous session"
+            ));
+            ui.checkbox(
+                &mut settings.incident_screenshots,
+                tr!("Save a screenshot when an incident opens or closes"),
+            )
+            .on_hover_text(
+                tr!("PNG images of the main window are saved in {dir}").replace(
+                    "{dir}",
+                    &crate::logic::screenshot::incidents_dir()
+                        .display()
+                        .to_string(),
+                ),
+            );
+
+            ui.separator();
+            ui.strong(tr!("Quality thresholds of new hosts"));
+            ui.horizontal(|ui| {
+                crate::ui::host_settings::render_threshold_preset(
+                    ui,
+                    "default_qoe".to_string(),
+                    &mut settings.default_thresholds,
+                );
+            })
+            .response
+            .on_hover_text(tr!(
+                "Latency above the warn value is colored yellow, above the bad value purple; each host can change them in its settings"
+            ));
+            crate::ui::host_settings::render_threshold_values(ui, &mut settings.default_thresholds);
+
+            ui.separator();
+            ui.strong(tr!("Sounds"));
+            crate::ui::sound_settings::render_sound_section(ui, &mut settings.sounds);
+            ui.horizontal(|ui| {
+                if ui.button(tr!("Quiet hours…")).clicked() {
+                    *quiet_hours_open = true;
+                }
+                if settings.quiet_schedule.enabled {
+                    ui.label("🌙");
+                }
+            })
+            .response
+            .on_hover_text(tr!(
+                "Weekly times during which all or single hosts deliver no alerts"
+            ));
+
+            ui.separator();
+            ui.strong(tr!("Webhooks"));
+            render_webhooks_section(ui, &mut settings.webhooks, &settings.proxy);
+
+            ui.separator();
+            ui.strong(tr!("Email alerts"));
+            render_email_section(ui, &mut settings.email);
+
+            ui.separator();
+            ui.strong(tr!("Correlation"));
+            ui.checkbox(
+                &mut settings.correlation.enabled,
+                tr!("Tag probes, log lines and exports with a trace id"),
+            )
+            .on_hover_text(tr!(
+                "Each probe gets a W3C traceparent tag (00-<trace id>-<span id>-01) in its ICMP payload, its log line and the exported pings, to find it in packet captures and router logs. Payloads cannot be tagged on Windows."
+            ));
+            ui.add_enabled_ui(settings.correlation.enabled, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(tr!("Trace id:"));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut settings.correlation.trace_id)
+                            .font(egui::TextStyle::Monospace)
+                            .desired_width(280.0),
+                    );
+                    if ui.button(tr!("New")).clicked() {
+                        settings.correlation.trace_id = trace_context::new_trace_id();
+                    }
+                });
+                if settings.correlation.enabled
+                    && settings.correlation.active_trace_id().is_none()
+                {
+                    ui.colored_label(
+                        egui::Color32::RED,
+                        tr!("The trace id must be 32 lowercase hex digits, not all zero"),
+                    );
+                }
+            });
+
+            ui.separator();
+            ui.strong(tr!("History"));
+            ui.checkbox(
+                &mut settings.persistent_history,
+                tr!("Keep long-term history on disk"),
+            )
+            .on_hover_text(
+                tr!("Records every ping so statistics survive restarts. Up to {size} MB per host in {dir}")
+                    .replace(
+                        "{size}",
+                        &(HISTORY_CAPACITY_RECORDS as u64 * 12 / 1_000_000).to_string(),
+                    )
+                    .replace("{dir}", &storage::default_dir().display().to_string()),
+            );
+
+            ui.separator();
+            ui.strong(tr!("Colors"));
+            render_color_theme(ui, settings, ctx.style().visuals.dark_mode);
+
+            ui.separator();
+            ui.strong(tr!("History plot"));
+            let plot = &mut settings.plot;
+            ui.horizontal(|ui| {
+                ui.label(tr!("Size:"));
+                ui.add(egui::DragValue::new(&mut plot.width).range(60.0..=2000.0).suffix(" pt"));
+                ui.label("×");
+                ui.add(egui::DragValue::new(&mut plot.height).range(10.0..=300.0).suffix(" pt"));
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr!("Samples shown:"));
+                ui.add(egui::DragValue::new(&mut plot.samples).range(10..=HISTORY_WINDOW_SIZE))
+                    .on_hover_text(tr!("Most recent samples, or minute/hour buckets of the long-term plot"));
+                ui.label(tr!("Bar width:"));
+                ui.add(egui::Slider::new(&mut plot.bar_width, 0.2..=1.0))
+                    .on_hover_text(tr!("Below 1.0 leaves gaps between the bars"));
+            });
+            ui.horizontal(|ui| {
+                let mut same_time = plot.time_span_min > 0;
+                if ui
+                    .checkbox(&mut same_time, tr!("Same time axis for all hosts"))
+                    .on_hover_text(tr!(
+                        "Places the samples by the time they were taken, so hosts pinged at different intervals line up"
+                    ))
+                    .changed()
+                {
+                    plot.time_span_min = if same_time { DEFAULT_PLOT_TIME_SPAN_MIN } else { 0 };
+                }
+                if same_time {
+                    ui.add(egui::DragValue::new(&mut plot.time_span_min).range(1..=60).suffix(tr!(" min")));
+                }
+            });
+            if *plot != PlotSettings::default() && ui.button(tr!("Reset plot size")).clicked() {
+                *plot = PlotSettings::default();
+            }
+
+            ui.separator();
+            ui.strong(tr!("Connectivity"));
+            ui.checkbox(
+                &mut settings.captive_portal_check,
+                tr!("Detect captive portals"),
+            )
+            .on_hover_text(tr!(
+                "Requests a URL that returns HTTP 204 every minute. Any other answer means the network intercepts traffic, e.g. a Wi-Fi login page."
+            ));
+            ui.add_enabled_ui(settings.captive_portal_check, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(tr!("Check URL:"));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut settings.captive_portal_url)
+                            .desired_width(300.0),
+                    );
+                });
+            });
+
+            ui.checkbox(&mut settings.public_ip_check, tr!("Show the public IP"))
+                .on_hover_text(tr!(
+                    "Looks up the public IPv4 and IPv6 address every minute and notes every change on the timeline, e.g. when a PPPoE session reconnects."
+                ));
+            ui.add_enabled_ui(settings.public_ip_check, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(tr!("Services:"));
+                    ui.add(
+                        egui::TextEdit::multiline(&mut settings.public_ip_services)
+                            .desired_rows(3)
+                            .desired_width(300.0),
+                    )
+                    .on_hover_text(tr!(
+                        "One per line: an HTTPS URL answering with the address in plain text, or stun:host:port for a STUN server. The first IPv4 and the first IPv6 answer are used."
+                    ));
+                });
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr!("Probe budget:"));
+                ui.add(
+                    egui::DragValue::new(&mut settings.probe_budget_per_sec)
+                        .range(0..=10_000)
+                        .suffix(tr!(" probes/s")),
+                )
+                .on_hover_text(tr!(
+                    "Warn when the hosts need more probes per second than this, and suggest longer intervals. 0 disables the warning."
+                ));
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr!("Probes in flight at most:"));
+                ui.add(egui::DragValue::new(&mut settings.max_concurrent_probes).range(0..=10_000))
+                    .on_hover_text(tr!(
+                        "Probes waiting for an answer at the same time, network scans included; further probes wait for their turn. 0 for no limit."
+                    ));
+                ui.label(tr!("Rate per interface:"));
+                ui.add(
+                    egui::DragValue::new(&mut settings.probe_rate_per_interface)
+                        .range(0..=100_000)
+                        .suffix(tr!(" probes/s")),
+                )
+                .on_hover_text(tr!(
+                    "Probes sent per second on each network interface at most, so many hosts or a scan do not flood the network. 0 for no limit."
+                ));
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr!("Resolve host names again every:"));
+                ui.add(
+                    egui::DragValue::new(&mut settings.dns_refresh_sec)
+                        .range(0..=86_400)
+                        .suffix(tr!(" s")),
+                )
+                .on_hover_text(tr!(
+                    "Host names are looked up again after this time so that DNS changes are followed. 0 resolves them only once."
+                ));
+            });
+
+            ui.separator();
+            ui.strong(tr!("Kiosk mode"));
+            crate::ui::kiosk::render_lock_section(ui, kiosk_lock);
+
+            ui.separator();
+            ui.strong(tr!("Wall dashboard"));
+            crate::ui::dashboard::render_dashboard_section(ui, &mut settings.dashboard);
+
+            ui.separator();
+            ui.strong(tr!("Team"));
+            ui.horizontal(|ui| {
+                ui.label(tr!("Shared host list:"));
+                let response = ui
+                    .add(
+                        egui::TextEdit::singleline(&mut settings.shared_host_list)
+                            .hint_text("/mnt/team/hosts.txt")
+                            .desired_width(300.0),
+                    )
+                    .on_hover_text(tr!(
+                        "A file with one 'address name' per line, or a JSON host list. Its hosts are added read-only and follow changes to the file; statistics stay local."
+                    ));
+                if response.lost_focus() && !settings.shared_host_list.trim().is_empty() {
+                    let path = settings.shared_host_list.trim().to_string();
+                    settings.remember_file(RecentFile {
+                        path,
+                        kind: RecentFileKind::HostList,
+                        address: String::new(),
+                    });
+                }
+            });
+            if let Some(e) = &shared_list.error {
+                ui.colored_label(egui::Color32::RED, format!("{}: {}", tr!("Error"), e));
+            } else if shared_list.modified.is_some() {
+                ui.label(
+                    tr!("{count} shared hosts").replace("{count}", &shared_list.hosts.to_string()),
+                );
+            }
+
+            ui.separator();
+            ui.strong(tr!("DHCP names"));
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut settings.dhcp_names.enabled, tr!("Lease file:"));
+                ui.add_enabled(
+                    settings.dhcp_names.enabled,
+                    egui::TextEdit::singleline(&mut settings.dhcp_names.lease_file)
+                        .desired_width(300.0),
+                );
+            })
+            .response
+            .on_hover_text(tr!(
+                "Shows the names devices gave the DHCP server next to hosts without a name of their own. Reads dnsmasq (OpenWrt: /tmp/dhcp.leases), ISC dhcpd and Kea lease files, e.g. from the router over a mounted share."
+            ));
+            if settings.dhcp_names.enabled {
+                if let Some(e) = &dhcp_leases.error {
+                    ui.colored_label(egui::Color32::RED, format!("{}: {}", tr!("Error"), e));
+                } else if dhcp_leases.modified.is_some() {
+                    ui.label(
+                        tr!("{count} named leases")
+                            .replace("{count}", &dhcp_leases.names.len().to_string()),
+                    );
+                }
+            }
+
+            ui.separator();
+            ui.strong(tr!("Remote viewing"));
+            ui.horizontal(|ui| {
+                ui.checkbox(
+                    &mut settings.stream_api.enabled,
+                    tr!("Statistics stream on localhost port"),
+                );
+                ui.add(egui::DragValue::new(&mut settings.stream_api.port).range(1..=65_535));
+            })
+            .response
+            .on_hover_text(
+                tr!("For the terminal client egui_pinger_tui. From another machine, forward the port first: ssh -L {port}:localhost:{port} <this machine>")
+                    .replace("{port}", &settings.stream_api.port.to_string()),
+            );
+            if let Some(e) = stream_error {
+                ui.colored_label(egui::Color32::RED, format!("{}: {}", tr!("Error"), e));
+            }
+            ui.horizontal(|ui| {
+                ui.label(tr!("Background daemon on localhost port"));
+                ui.add(egui::DragValue::new(&mut settings.daemon_port).range(1..=65_535));
+                if daemon_attached {
+                    ui.label(RichText::new(tr!("attached")).color(egui::Color32::GREEN));
+                }
+            })
+            .response
+            .on_hover_text(tr!(
+                "egui_pinger_daemon keeps pinging and recording the history while this window is closed. A window started while it runs shows its results instead of pinging itself. The port takes effect when both are started again."
+            ));
+
+            ui.separator();
+            ui.strong(tr!("Remote agents"));
+            render_agents_section(
+                ui,
+                &mut settings.agent,
+                &mut settings.remote_agents,
+                agent_error,
+                agent_status,
+            );
+
+            ui.separator();
+            ui.strong(tr!("HTTP API"));
+            render_api_section(ui, &mut settings.api, api_error);
+
+            ui.separator();
+            ui.strong(tr!("MQTT"));
+            render_mqtt_section(ui, &mut settings.mqtt, mqtt_status);
+
+            ui.separator();
+            ui.strong(tr!("Proxy"));
+            let proxy = &mut settings.proxy;
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut proxy.mode, ProxyMode::System, tr!("System"));
+                ui.radio_value(&mut proxy.mode, ProxyMode::Direct, tr!("No proxy"));
+                ui.radio_value(&mut proxy.mode, ProxyMode::Manual, tr!("Manual"));
+            });
+            match proxy.mode {
+                ProxyMode::System => {
+                    // Detection may spawn a process, so it is done once per session
+                    let id = egui::Id::new("detected_system_proxy");
+                    let detected = ui.data_mut(|d| {
+                        d.get_temp_mut_or_insert_with(id, || {
+                            http::system_proxy().map(|(url, _)| http::redact(&url))
+                        })
+                        .clone()
+                    });
+                    let detected = detected.unwrap_or_else(|| tr!("none detected"));
+                    ui.label(format!("{}: {}", tr!("Detected proxy"), detected));
+                }
+                ProxyMode::Direct => {}
+                ProxyMode::Manual => {
+                    egui::Grid::new("proxy_grid").num_columns(2).show(ui, |ui| {
+                        ui.label(tr!("Address:"));
+                        ui.add(
+                            egui::TextEdit::singleline(&mut proxy.url)
+                                .hint_text("http://proxy.example.com:3128"),
+                        )
+                        .on_hover_text(tr!("Supported schemes: http, socks4, socks5"));
+                        ui.end_row();
+                        ui.label(tr!("Username:"));
+                        ui.text_edit_singleline(&mut proxy.username);
+                        ui.end_row();
+                        ui.label(tr!("Password:"));
+                        ui.add(egui::TextEdit::singleline(&mut proxy.password).password(true))
+                            .on_hover_text(tr!("Stored unencrypted in the configuration file."));
+                        ui.end_row();
+                        ui.label(tr!("Bypass for:"));
+                        ui.add(
+                            egui::TextEdit::singleline(&mut proxy.no_proxy)
+                                .hint_text("localhost, .corp.example.com"),
+                        );
+                        ui.end_row();
+                    });
+                }
+            }
+        });
+}
+
+/// Renders the list of webhook targets with their URL and body template.
+fn render_webhooks_section(
+    ui: &mut egui::Ui,
+    webhooks: &mut Vec<WebhookTarget>,
+    proxy: &ProxySettings,
+) {
+    ui.label(
+        RichText::new(tr!(
+            "POSTed on down, recovery, escalation and raised alerts. Placeholders: {event} {host} {address} {message} {time} {timestamp} {loss} {rtt}"
+        ))
+        .small(),
+    );
+    let mut remove = None;
+    for (i, target) in webhooks.iter_mut().enumerate() {
+        ui.push_id(("webhook", i), |ui| {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut target.enabled, "");
+                ui.add(
+                    egui::TextEdit::singleline(&mut target.name)
+                        .hint_text(tr!("Name"))
+                        .desired_width(100.0),
+                );
+                ui.add(
+                    egui::TextEdit::singleline(&mut target.url)
+                        .hint_text("https://hooks.slack.com/services/...")
+                        .desired_width(260.0),
+                );
+                if ui.button("🗑").on_hover_text(tr!("Remove")).clicked() {
+                    remove = Some(i);
+                }
+            });
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("webhook_preset")
+                    .selected_text(tr!("Template"))
+                    .show_ui(ui, |ui| {
+                        for preset in WebhookPreset::ALL {
+                            if ui.selectable_label(false, preset.label()).clicked() {
+                                target.template = preset.template().to_string();
+                            }
+                        }
+                    });
+                if ui
+                    .button(tr!("Send test"))
+                    .on_hover_text(tr!("Failures are written to the application log"))
+                    .clicked()
+                {
+                    let host = tr!("Test host");
+                    webhook::send(
+                        proxy.clone(),
+                        vec![WebhookTarget {
+                            enabled: true,
+                            ..target.clone()
+                        }],
+                        vec![webhook::WebhookMessage {
+                            event: webhook::WebhookEvent::Down,
+                            message: webhook::describe(webhook::WebhookEvent::Down, &host, ""),
+                            host,
+                            address: "192.0.2.1".to_string(),
+                            timestamp: chrono::Utc::now().timestamp() as u64,
+                            loss_pct: 100.0,
+                            rtt_ms: 0.0,
+                        }],
+                    );
+                }
+            });
+            ui.add(
+                egui::TextEdit::multiline(&mut target.template)
+                    .code_editor()
+                    .desired_rows(2)
+                    .desired_width(f32::INFINITY),
+            );
+        });
+    }
+    if let Some(i) = remove {
+        webhooks.remove(i);
+    }
+    if ui.button(tr!("Add webhook")).clicked() {
+        webhooks.push(WebhookTarget::default());
+    }
+}
+
+/// Renders serving this instance as an agent and the list of remote agents shown here.
+fn render_agents_section(
+    ui: &mut egui::Ui,
+    agent: &mut AgentSettings,
+    remote_agents: &mut Vec<RemoteAgent>,
+    agent_error: Option<&str>,
+    agent_status: &HashMap<String, RemoteAgentStatus>,
+) {
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut agent.enabled, tr!("Serve the results on port"));
+        ui.add(egui::DragValue::new(&mut agent.port).range(1..=65_535));
+        ui.add(
+            egui::TextEdit::singleline(&mut agent.token)
+                .password(true)
+                .hint_text(tr!("Token"))
+                .desired_width(120.0),
+        );
+        if ui.button(tr!("Generate")).clicked() {
+            agent.token = format!("{:032x}", rand::random::<u128>());
+            ui.ctx().copy_text(agent.token.clone());
+        }
+    })
+    .response
+    .on_hover_text(tr!(
+        "Other egui_pinger windows with the token show the hosts of this one. The results are not encrypted; over the internet use a VPN or an SSH tunnel. Generate copies the new token."
+    ));
+    if agent.enabled && agent.token.is_empty() {
+        ui.colored_label(
+            egui::Color32::YELLOW,
+            tr!("Viewers are refused until a token is set"),
+        );
+    }
+    if let Some(e) = agent_error {
+        ui.colored_label(egui::Color32::RED, format!("{}: {}", tr!("Error"), e));
+    }
+
+    let mut remove = None;
+    for (i, remote) in remote_agents.iter_mut().enumerate() {
+        ui.push_id(("remote_agent", i), |ui| {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut remote.enabled, "");
+                ui.add(
+                    egui::TextEdit::singleline(&mut remote.name)
+                        .hint_text(tr!("Name"))
+                        .desired_width(80.0),
+                );
+                ui.add(
+                    egui::TextEdit::singleline(&mut remote.address)
+                        .hint_text("office.example.com:7172")
+                        .desired_width(160.0),
+                );
+                ui.add(
+                    egui::TextEdit::singleline(&mut remote.token)
+                        .password(true)
+                        .hint_text(tr!("Token"))
+                        .desired_width(100.0),
+                );
+                if ui.button("🗑").on_hover_text(tr!("Remove")).clicked() {
+                    remove = Some(i);
+                }
+                match agent_status.get(&remote.name).filter(|_| remote.enabled) {
+                    Some(RemoteAgentStatus::Connected) => {
+                        ui.colored_label(egui::Color32::GREEN, tr!("connected"));
+                    }
+                    Some(RemoteAgentStatus::Connecting) => {
+                        ui.label(tr!("connecting…"));
+                    }
+                    Some(RemoteAgentStatus::Failed(e)) => {
+                        ui.colored_label(egui::Color32::RED, tr!("failed"))
+                            .on_hover_text(e);
+                    }
+                    None => {}
+                }
+            });
+        });
+    }
+    if let Some(i) = remove {
+        remote_agents.remove(i);
+    }
+    if ui
+        .button(tr!("Add agent"))
+        .on_hover_text(tr!(
+            "Its hosts are shown here tagged with its name and are not pinged from this machine"
+        ))
+        .clicked()
+    {
+        remote_agents.push(RemoteAgent::default());
+    }
+}
+
+fn render_api_section(ui: &mut egui::Ui, api: &mut ApiSettings, api_error: Option<&str>) {
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut api.enabled, tr!("Answer JSON requests on port"));
+        ui.add(egui::DragValue::new(&mut api.port).range(1..=65_535));
+        ui.add(
+            egui::TextEdit::singleline(&mut api.token)
+                .password(true)
+                .hint_text(tr!("Token"))
+                .desired_width(120.0),
+        );
+        if ui.button(tr!("Generate")).clicked() {
+            api.token = format!("{:032x}", rand::random::<u128>());
+            ui.ctx().copy_text(api.token.clone());
+        }
+    })
+    .response
+    .on_hover_text(tr!(
+        "Scripts and home automation can read the statistics and history at /api/status and /api/hosts and, with a token set, add or remove hosts. With a token, requests need the header \"Authorization: Bearer <token>\" and hosts are added as application/json."
+    ));
+    ui.checkbox(
+        &mut api.listen_all,
+        tr!("Accept requests from other machines"),
+    );
+    if api.enabled && api.listen_all && api.token.is_empty() {
+        ui.colored_label(
+            egui::Color32::YELLOW,
+            tr!("Set a token to accept requests from other machines"),
+        );
+    }
+    if let Some(e) = api_error {
+        ui.colored_label(egui::Color32::RED, format!("{}: {}", tr!("Error"), e));
+    }
+}
+
+/// Renders the broker, its login and the topics of the MQTT publisher.
+fn render_mqtt_section(
+    ui: &mut egui::Ui,
+    mqtt: &mut MqttSettings,
+    (connected, error): (bool, Option<&str>),
+) {
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut mqtt.enabled, tr!("Publish to broker"));
+        ui.add(
+            egui::TextEdit::singleline(&mut mqtt.broker)
+                .hint_text("homeassistant.local")
+                .desired_width(160.0),
+        );
+        ui.label(tr!("Port"));
+        ui.add(egui::DragValue::new(&mut mqtt.port).range(1..=65_535));
+        if ui.checkbox(&mut mqtt.tls, "TLS").changed()
+            && mqtt.port == MqttSettings::default_port(!mqtt.tls)
+        {
+            mqtt.port = MqttSettings::default_port(mqtt.tls);
+        }
+        if mqtt.enabled && connected {
+            ui.label(RichText::new(tr!("connected")).color(egui::Color32::GREEN));
+        }
+    })
+    .response
+    .on_hover_text(tr!(
+        "Every host gets a retained JSON state at <prefix>/<address>/state, published at once when it goes up or down and otherwise at the interval below."
+    ));
+    if let Some(e) = error {
+        ui.colored_label(egui::Color32::RED, format!("{}: {}", tr!("Error"), e));
+    }
+    ui.horizontal(|ui| {
+        ui.label(tr!("User"));
+        ui.add(egui::TextEdit::singleline(&mut mqtt.username).desired_width(100.0));
+        if !mqtt.username.trim().is_empty() {
+            ui.label(tr!("Password"));
+            render_keyring_password(ui, "mqtt", &mqtt.keyring_account());
+        }
+    });
+    if !mqtt.tls && !mqtt.username.trim().is_empty() {
+        ui.colored_label(egui::Color32::YELLOW, tr!("Turn on TLS to log in"));
+    }
+    ui.horizontal(|ui| {
+        ui.label(tr!("Topic prefix"));
+        ui.add(egui::TextEdit::singleline(&mut mqtt.topic_prefix).desired_width(100.0));
+        ui.label(tr!("Metrics every"));
+        ui.add(
+            egui::DragValue::new(&mut mqtt.interval_sec)
+                .range(1..=3_600)
+                .suffix(" s"),
+        );
+    });
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut mqtt.discovery, tr!("Home Assistant discovery under"))
+            .on_hover_text(tr!(
+                "Every host appears as a device with its connectivity, latency and packet loss"
+            ));
+        ui.add_enabled(
+            mqtt.discovery,
+            egui::TextEdit::singleline(&mut mqtt.discovery_prefix).desired_width(100.0),
+        );
+    });
+}
+
+/// Renders the SMTP server, the recipients and the password kept in the keyring.
+fn render_email_section(ui: &mut egui::Ui, email_settings: &mut EmailSettings) {
+    ui.checkbox(
+        &mut email_settings.enabled,
+        tr!("Send an email when a host stays down"),
+    );
+    ui.add_enabled_ui(email_settings.enabled, |ui| {
+        egui::Grid::new("email_grid").num_columns(2).show(ui, |ui| {
+            ui.label(tr!("Down for (min):"));
+            ui.add(egui::DragValue::new(&mut email_settings.down_min).range(1..=1440));
+            ui.end_row();
+
+            ui.label(tr!("SMTP server:"));
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut email_settings.server)
+                        .hint_text("smtp.example.com")
+                        .desired_width(180.0),
+                );
+                ui.add(egui::DragValue::new(&mut email_settings.port).range(1..=65535));
+                let previous = email_settings.security;
+                egui::ComboBox::from_id_salt("smtp_security")
+                    .selected_text(email_settings.security.label())
+                    .show_ui(ui, |ui| {
+                        for security in SmtpSecurity::ALL {
+                            ui.selectable_value(
+                                &mut email_settings.security,
+                                security,
+                                security.label(),
+                            );
+                        }
+                    });
+                if email_settings.security != previous
+                    && email_settings.port == previous.default_port()
+                {
+                    email_settings.port = email_settings.security.default_port();
+                }
+            });
+            ui.end_row();
+
+            ui.label(tr!("Username:"));
+            ui.add(
+                egui::TextEdit::singleline(&mut email_settings.username)
+                    .hint_text(tr!("empty: no authentication")),
+            );
+            ui.end_row();
+            if email_settings.security == SmtpSecurity::None
+                && !email_settings.username.trim().is_empty()
+            {
+                ui.label("");
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    tr!("Choose STARTTLS or TLS to log in"),
+                );
+                ui.end_row();
+            }
+
+            ui.label(tr!("Password:"));
+            render_keyring_password(ui, "smtp", &email_settings.keyring_account());
+            ui.end_row();
+
+            ui.label(tr!("From:"));
+            ui.add(
+                egui::TextEdit::singleline(&mut email_settings.from)
+                    .hint_text(email_settings.username.clone()),
+            );
+            ui.end_row();
+
+            ui.label(tr!("Recipients:"));
+            ui.add(
+                egui::TextEdit::singleline(&mut email_settings.recipients)
+                    .hint_text("ops@example.com, me@example.com"),
+            );
+            ui.end_row();
+        });
+        if ui
+            .button(tr!("Send test email"))
+            .on_hover_text(tr!("The result is written to the application log"))
+            .clicked()
+        {
+            let now = chrono::Utc::now().timestamp() as u64;
+            email::spawn(
+                email_settings.clone(),
+                DownReport {
+                    host: tr!("Test host"),
+                    address: "192.0.2.1".to_string(),
+                    down_since: now.saturating_sub(email_settings.down_min as u64 * 60),
+                    timestamp: now,
+                    loss_pct: 50.0,
+                    rtt_ms: 20.0,
+                    samples: (0..60)
+                        .map(|i| (i % 20 < 15).then_some(10.0 + (i % 7) as f64 * 5.0))
+                        .collect(),
+                },
+            );
+        }
+    });
+}
+
+/// Password field that saves to the keyring instead of the configuration.
+fn render_keyring_password(ui: &mut egui::Ui, id_salt: &str, account: &str) {
+    let id = egui::Id::new(("keyring_password", id_salt));
+    let (mut password, mut outcome): (String, Option<Result<String, String>>) =
+        ui.data_mut(|d| d.get_temp(id).unwrap_or_default());
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut password)
+                .password(true)
+                .desired_width(140.0),
+        );
+        if ui
+            .button(tr!("Save to keyring"))
+            .on_hover_text(tr!(
+                "The password is kept by the operating system, not in the configuration file"
+            ))
+            .clicked()
+        {
+            outcome = Some(keyring::store(account, &password).map(|_| tr!("Password saved")));
+            password.clear();
+        }
+        if ui.button(tr!("Forget")).clicked() {
+            outcome = Some(keyring::delete(account).map(|_| tr!("Password removed")));
+        }
+        match &outcome {
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            Some(Err(e)) => {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+            None => {}
+        }
+    });
+    ui.data_mut(|d| d.insert_temp(id, (password, outcome)));
+}
+
+/// Renders the choice of the color theme and the buttons to change its colors;
+/// changing a color of a preset switches to a custom theme starting from it.
+fn render_color_theme(ui: &mut egui::Ui, settings: &mut AppSettings, is_dark: bool) {
+    ui.horizontal(|ui| {
+        ui.label(tr!("Theme:"));
+        egui::ComboBox::from_id_salt("color_theme")
+            .selected_text(settings.color_theme.label())
+            .show_ui(ui, |ui| {
+                for theme in ColorTheme::ALL {
+                    ui.selectable_value(&mut settings.color_theme, theme, theme.label());
+                }
+            });
+    });
+    let mut colors = settings
+        .color_theme
+        .colors(is_dark, &settings.custom_colors);
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        for (label, color) in [
+            (tr!("OK"), &mut colors.ok),
+            (tr!("Warn"), &mut colors.warn),
+            (tr!("Bad"), &mut colors.bad),
+            (tr!("Lost"), &mut colors.loss),
+        ] {
+            changed |= egui::color_picker::color_edit_button_srgb(ui, color).changed();
+            ui.label(label);
+        }
+    })
+    .response
+    .on_hover_text(tr!(
+        "Latency below the warn threshold, above it, above the bad threshold, and lost probes"
+    ));
+    if changed {
+        settings.custom_colors = colors;
+        settings.color_theme = ColorTheme::Custom;
+    }
+}
@@ -0,0 +1,65 @@
+use crate::logic::sound;
+use crate::model::{SoundEvent, SoundSettings, SoundTheme};
+use eframe::egui;
+use tr::tr;
+
+/// Drag value editing a time of day stored as minutes since midnight, shown as HH:MM.
+pub fn time_of_day(minutes: &mut u16) -> egui::DragValue<'_> {
+    egui::DragValue::new(minutes)
+        .range(0..=23 * 60 + 59)
+        .speed(5.0)
+        .custom_formatter(|m, _| format!("{:02}:{:02}", m as u32 / 60, m as u32 % 60))
+        .custom_parser(|text| {
+            let (h, m) = text.trim().split_once(':')?;
+            let (h, m) = (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?);
+            (h < 24 && m < 60).then_some((h * 60 + m) as f64)
+        })
+}
+
+/// Renders the sound section of the settings window.
+pub fn render_sound_section(ui: &mut egui::Ui, sounds: &mut SoundSettings) {
+    ui.checkbox(&mut sounds.enabled, tr!("Play sounds"));
+    ui.add_enabled_ui(sounds.enabled, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(tr!("Theme:"));
+            egui::ComboBox::from_id_salt("sound_theme")
+                .selected_text(sounds.theme.label())
+                .show_ui(ui, |ui| {
+                    for theme in SoundTheme::ALL {
+                        ui.selectable_value(&mut sounds.theme, theme, theme.label());
+                    }
+                });
+        });
+
+        egui::Grid::new("sound_events")
+            .num_columns(3)
+            .show(ui, |ui| {
+                for event in SoundEvent::ALL {
+                    let entry = sounds.event_mut(event);
+                    ui.checkbox(&mut entry.enabled, event.label());
+                    ui.add(
+                        egui::TextEdit::singleline(&mut entry.custom_file)
+                            .hint_text(tr!("theme sound"))
+                            .desired_width(200.0),
+                    )
+                    .on_hover_text(tr!("Path of a WAV file played instead of the theme sound"));
+                    if ui.button("▶").on_hover_text(tr!("Test")).clicked() {
+                        sound::play(sounds, event);
+                    }
+                    ui.end_row();
+                }
+            });
+
+        ui.horizontal(|ui| {
+            ui.label(tr!("Escalate after:"));
+            ui.add(
+                egui::DragValue::new(&mut sounds.escalation_min)
+                    .range(0..=1440)
+                    .suffix(tr!(" min")),
+            )
+            .on_hover_text(tr!(
+                "Play the escalation sound once when a host is still down after this time. 0 disables it."
+            ));
+        });
+    });
+}
@@ -0,0 +1,181 @@
+use crate::logic::lan_discovery;
+use crate::logic::subnet_scan::{self, SubnetScan};
+use crate::model::AppState;
+use eframe::egui;
+use eframe::egui::RichText;
+use std::collections::HashSet;
+use std::net::Ipv4Addr;
+use tr::tr;
+
+/// State of the network scan window between frames.
+#[derive(Default)]
+pub struct SubnetScanState {
+    /// Range to scan, prefilled with the local /24 when the window opens.
+    pub range: String,
+    pub scan: Option<SubnetScan>,
+    /// Found hosts chosen to be added.
+    pub selected: HashSet<Ipv4Addr>,
+    /// Why the scan could not start, or what the last "Add" did.
+    pub message: Option<String>,
+}
+
+/// Renders the window scanning an address range for answering hosts and adding
+/// the chosen ones to the host list.
+pub fn render_subnet_scan_window(
+    ctx: &egui::Context,
+    scan_state: &mut SubnetScanState,
+    state: &mut AppState,
+    open: &mut bool,
+) {
+    let progress = scan_state.scan.as_ref().map(SubnetScan::progress);
+    let running = progress.as_ref().is_some_and(|p| !p.finished);
+
+    egui::Window::new(tr!("Scan Network"))
+        .open(open)
+        .resizable(true)
+        .default_width(480.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr!("Range:"));
+                ui.add_enabled(
+                    !running,
+                    egui::TextEdit::singleline(&mut scan_state.range)
+                        .hint_text("192.168.1.0/24")
+                        .desired_width(160.0),
+                );
+                if running {
+                    if ui.button(tr!("Stop")).clicked()
+                        && let Some(scan) = &scan_state.scan
+                    {
+                        scan.stop();
+                    }
+                    ui.spinner();
+                } else {
+                    if ui.button(tr!("Scan")).clicked() {
+                        scan_state.selected.clear();
+                        scan_state.message = None;
+                        match SubnetScan::start(scan_state.range.trim().to_string()) {
+                            Ok(scan) => scan_state.scan = Some(scan),
+                            Err(e) => {
+                                scan_state.scan = None;
+                                scan_state.message = Some(format!("{}: {}", tr!("Error"), e));
+                            }
+                        }
+                    }
+                    if ui
+                        .button(tr!("Known neighbors"))
+                        .on_hover_text(tr!(
+                            "Devices in the ARP table of this computer, also those that do not answer ping"
+                        ))
+                        .clicked()
+                    {
+                        scan_state.selected.clear();
+                        scan_state.message = None;
+                        scan_state.scan = Some(SubnetScan::start_neighbors());
+                    }
+                }
+            });
+            ui.label(
+                RichText::new(tr!(
+                    "Pings every address of an IPv4 range (at most a /20) and looks up the names of the hosts that answer by reverse DNS, mDNS or NetBIOS. Hosts that do not answer ping are only found among the known neighbors."
+                ))
+                .small(),
+            );
+
+            if let Some(progress) = &progress {
+                ui.separator();
+                if let Some(error) = &progress.error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+                ui.label(
+                    tr!("{probed} of {total} addresses probed, {found} hosts answered")
+                        .replace("{probed}", &progress.probed.to_string())
+                        .replace("{total}", &progress.total.to_string())
+                        .replace("{found}", &progress.found.len().to_string()),
+                );
+                egui::ScrollArea::vertical()
+                    .max_height(350.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("scan_results").striped(true).show(ui, |ui| {
+                            for result in &progress.found {
+                                let address = result.ip.to_string();
+                                let known = state.hosts.iter().any(|h| h.address == address);
+                                let mut checked = scan_state.selected.contains(&result.ip);
+                                let name = result
+                                    .name
+                                    .clone()
+                                    .or_else(|| state.dhcp_leases.names.get(&address).cloned())
+                                    .unwrap_or_default();
+                                if ui
+                                    .add_enabled(!known, egui::Checkbox::new(&mut checked, &address))
+                                    .on_disabled_hover_text(tr!("Already in the host list"))
+                                    .changed()
+                                {
+                                    if checked {
+                                        scan_state.selected.insert(result.ip);
+                                    } else {
+                                        scan_state.selected.remove(&result.ip);
+                                    }
+                                }
+                                ui.label(name);
+                                let vendor = match (&result.vendor, &result.mac) {
+                                    (Some(vendor), _) => vendor.clone(),
+                                    (None, Some(mac)) if lan_discovery::is_private_mac(mac) => {
+                                        tr!("private MAC address")
+                                    }
+                                    _ => String::new(),
+                                };
+                                let vendor_label = ui.label(vendor);
+                                if let Some(mac) = &result.mac {
+                                    vendor_label.on_hover_text(mac);
+                                }
+                                match result.rtt_ms {
+                                    Some(rtt) => ui.label(format!("{:.1} {}", rtt, tr!("ms"))),
+                                    None => ui.weak(tr!("no ping")),
+                                };
+                                ui.end_row();
+                            }
+                        });
+                    });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button(tr!("Select all")).clicked() {
+                        scan_state.selected = progress
+                            .found
+                            .iter()
+                            .map(|r| r.ip)
+                            .filter(|ip| !state.hosts.iter().any(|h| h.address == ip.to_string()))
+                            .collect();
+                    }
+                    if ui
+                        .add_enabled(
+                            !scan_state.selected.is_empty(),
+                            egui::Button::new(tr!("Add selected")),
+                        )
+                        .clicked()
+                    {
+                        let chosen: Vec<_> = progress
+                            .found
+                            .iter()
+                            .filter(|r| scan_state.selected.contains(&r.ip))
+                            .cloned()
+                            .collect();
+                        let added = subnet_scan::add_hosts(state, &chosen);
+                        scan_state.selected.clear();
+                        scan_state.message = Some(
+                            tr!("{count} hosts added").replace("{count}", &added.to_string()),
+                        );
+                    }
+                });
+            }
+            if let Some(message) = &scan_state.message {
+                ui.label(message);
+            }
+        });
+    if !*open && let Some(scan) = scan_state.scan.take() {
+        scan.stop();
+    }
+    if running {
+        ctx.request_repaint_after(std::time::Duration::from_millis(250));
+    }
+}
@@ -0,0 +1,97 @@
+use crate::app::PingVisuals;
+use crate::constants::SUMMARY_LOSS_WINDOW_SEC;
+use crate::logic::presenter::Severity;
+use crate::logic::summary::{InternetHealth, Summary};
+use crate::model::Connectivity;
+use eframe::egui;
+use eframe::egui::RichText;
+use tr::tr;
+
+/// Renders the line of totals above the host list: hosts up and down, the worst
+/// current latency, the loss of the last minutes, the Internet verdict and a
+/// captive portal in the way.
+pub fn render_summary_bar(ui: &mut egui::Ui, visuals: &PingVisuals, summary: &Summary) {
+    if summary.hosts == 0 {
+        return;
+    }
+    ui.horizontal_wrapped(|ui| {
+        let internet_color = match summary.internet {
+            InternetHealth::Unknown => ui.visuals().weak_text_color(),
+            InternetHealth::Good => visuals.latency_color(0.0),
+            InternetHealth::Degraded => visuals
+                .severity_color(Severity::Warn)
+                .unwrap_or(ui.visuals().text_color()),
+            InternetHealth::Down => visuals.latency_color(f64::NAN),
+        };
+        ui.label(tr!("Internet:"));
+        ui.colored_label(
+            internet_color,
+            RichText::new(summary.internet.label()).strong(),
+        )
+        .on_hover_text(tr!(
+            "Judged by the public DNS resolvers in the list (e.g. 1.1.1.1, 8.8.8.8, 9.9.9.9): all down, some down or slow, or all fine"
+        ));
+        if let Connectivity::CaptivePortal { login_url } = &summary.connectivity {
+            ui.colored_label(
+                visuals
+                    .severity_color(Severity::Warn)
+                    .unwrap_or(ui.visuals().text_color()),
+                RichText::new(tr!("behind a captive portal")).strong(),
+            )
+            .on_hover_text(tr!("Log in to the network to get Internet access"));
+            if let Some(url) = login_url {
+                ui.hyperlink_to(tr!("Open login page"), url);
+            }
+        }
+        ui.separator();
+
+        ui.label(
+            tr!("{up} up").replace("{up}", &summary.up.to_string()),
+        );
+        let down = tr!("{down} down").replace("{down}", &summary.down.to_string());
+        if summary.down > 0 {
+            ui.colored_label(visuals.latency_color(f64::NAN), RichText::new(down).strong());
+        } else {
+            ui.label(down);
+        }
+        ui.separator();
+
+        ui.label(tr!("Worst latency:"));
+        match &summary.worst_rtt {
+            Some((name, rtt)) => {
+                ui.colored_label(
+                    visuals.latency_color(*rtt),
+                    format!("{:.1} {}", rtt, tr!("ms")),
+                );
+                ui.label(format!("({})", name));
+            }
+            None => {
+                ui.label("-");
+            }
+        }
+        ui.separator();
+
+        ui.label(
+            tr!("Loss, {min} min:")
+                .replace("{min}", &(SUMMARY_LOSS_WINDOW_SEC / 60).to_string()),
+        );
+        match summary.recent_loss_pct() {
+            Some(pct) => {
+                let text = format!("{:.1}%", pct);
+                match visuals.value_color(pct, 1.0, 3.0, false) {
+                    Some(color) => ui.colored_label(color, text),
+                    None => ui.label(text),
+                }
+                .on_hover_text(
+                    tr!("{lost} of {sent} probes of all monitored hosts")
+                        .replace("{lost}", &summary.recent_lost.to_string())
+                        .replace("{sent}", &summary.recent_sent.to_string()),
+                );
+            }
+            None => {
+                ui.label("-");
+            }
+        }
+    });
+    ui.separator();
+}
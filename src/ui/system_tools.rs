@@ -5,6 +5,17 @@ use std::os::windows::process::CommandExt;
 use std::sync::{Arc, Mutex};
 use tr::tr;
 
+use crate::logic::SharedState;
+use crate::logic::bundle::create_diagnostic_bundle;
+use crate::logic::export::export_statistics;
+use crate::logic::import::{self, ImportFormat, LogFollower};
+use crate::logic::presenter::{format_duration, format_time};
+use crate::logic::report::{self, REPORT_PERIODS, Report};
+use crate::logic::sla::{self, TargetReport};
+use crate::model::{
+    HostInfo, HostStatus, RecentFile, RecentFileKind, SlaMetric, SlaTarget, SlaWindow,
+};
+
 #[cfg(windows)]
 use crate::constants::CREATE_NO_WINDOW;
 
@@ -51,6 +62,11 @@ pub enum ToolsTab {
     #[default]
     Guide,
     Commands,
+    BugReport,
+    Import,
+    Export,
+    Sla,
+    Report,
 }
 
 /// Persistent state for the System Tools window.
@@ -59,6 +75,37 @@ pub struct SystemToolsState {
     pub output: String,
     pub is_running: bool,
     pub selected_tab: ToolsTab,
+    /// Replace host names and addresses with pseudonyms in the diagnostic bundle.
+    pub anonymize_bundle: bool,
+    /// Result of the last bundle creation (path or error message).
+    pub bundle_status: String,
+    /// Replace host names and addresses with pseudonyms in exported statistics.
+    pub anonymize_export: bool,
+    /// Result of the last statistics export (path or error message).
+    pub export_status: String,
+    /// Log file to import or follow.
+    pub import_path: String,
+    pub import_format: ImportFormat,
+    /// Address of the host receiving imported pings; created if it does not exist.
+    pub import_address: String,
+    /// Result of the last import.
+    pub import_status: String,
+    /// Log files being followed.
+    pub followers: Vec<LogFollower>,
+    /// Days of history compared with the ISP service levels.
+    pub sla_days: u64,
+    /// Result of the last SLA check: checked time span and one report per target.
+    pub sla_reports: Option<(u64, u64, Vec<TargetReport>)>,
+    /// Complaint text generated from the last SLA check, editable before copying.
+    pub sla_complaint: String,
+    /// Period the availability report is broken down into.
+    pub report_window: SlaWindow,
+    /// Number of periods in the availability report, up to the current one.
+    pub report_periods: usize,
+    /// Last generated availability report.
+    pub report: Option<Report>,
+    /// Result of the last report export (path or error message).
+    pub report_status: String,
     /// Shared buffer: background thread writes result here, UI polls it.
     pending_result: Arc<Mutex<Option<String>>>,
     /// Cached command list (built once).
@@ -72,6 +119,22 @@ impl Default for SystemToolsState {
             output: String::new(),
             is_running: false,
             selected_tab: ToolsTab::default(),
+            anonymize_bundle: true,
+            bundle_status: String::new(),
+            anonymize_export: true,
+            export_status: String::new(),
+            import_path: String::new(),
+            import_format: ImportFormat::default(),
+            import_address: String::new(),
+            import_status: String::new(),
+            followers: Vec::new(),
+            sla_days: 30,
+            sla_reports: None,
+            sla_complaint: String::new(),
+            report_window: SlaWindow::Month,
+            report_periods: 1,
+            report: None,
+            report_status: String::new(),
             pending_result: Arc::new(Mutex::new(None)),
             commands: get_commands(),
         }
@@ -408,7 +471,12 @@ fn run_command_background(cmd: String, args: Vec<String>, result_slot: Arc<Mutex
 // --- UI rendering ---
 
 /// Renders the System Tools window containing diagnostic utilities.
-pub fn ui_system_tools_window(ctx: &egui::Context, open: &mut bool, state: &mut SystemToolsState) {
+pub fn ui_system_tools_window(
+    ctx: &egui::Context,
+    open: &mut bool,
+    state: &mut SystemToolsState,
+    app_state: &SharedState,
+) {
     // Poll for background command result
     if state.is_running
         && let Ok(mut slot) = state.pending_result.lock()
@@ -466,6 +534,15 @@ pub fn ui_system_tools_window(ctx: &egui::Context, open: &mut bool, state: &mut
                     ToolsTab::Commands,
                     tr!("Command Output"),
                 );
+                ui.selectable_value(
+                    &mut state.selected_tab,
+                    ToolsTab::BugReport,
+                    tr!("Bug Report"),
+                );
+                ui.selectable_value(&mut state.selected_tab, ToolsTab::Import, tr!("Import Log"));
+                ui.selectable_value(&mut state.selected_tab, ToolsTab::Export, tr!("Export"));
+                ui.selectable_value(&mut state.selected_tab, ToolsTab::Sla, tr!("ISP SLA"));
+                ui.selectable_value(&mut state.selected_tab, ToolsTab::Report, tr!("Report"));
             });
 
             ui.separator();
@@ -473,6 +550,11 @@ pub fn ui_system_tools_window(ctx: &egui::Context, open: &mut bool, state: &mut
             match state.selected_tab {
                 ToolsTab::Guide => render_guide(ui),
                 ToolsTab::Commands => render_commands_tab(ui, state),
+                ToolsTab::BugReport => render_bug_report_tab(ui, state, app_state),
+                ToolsTab::Import => render_import_tab(ui, state, app_state),
+                ToolsTab::Export => render_export_tab(ui, state, app_state),
+                ToolsTab::Sla => render_sla_tab(ui, state, app_state),
+                ToolsTab::Report => render_report_tab(ui, state, app_state),
             }
         });
 
@@ -524,6 +606,502 @@ fn render_commands_tab(ui: &mut egui::Ui, state: &mut SystemToolsState) {
         });
 }
 
+/// Renders the "Bug Report" tab with the diagnostic bundle export.
+fn render_bug_report_tab(ui: &mut egui::Ui, state: &mut SystemToolsState, app_state: &SharedState) {
+    ui.label(
+        RichText::new(tr!(
+            "Creates a ZIP archive with recent logs, the current configuration, a statistics snapshot and environment information. Attach it to your bug report."
+        ))
+        .weak(),
+    );
+    ui.add_space(4.0);
+    ui.checkbox(
+        &mut state.anonymize_bundle,
+        tr!("Anonymize host names and addresses"),
+    )
+    .on_hover_text(tr!(
+        "Also leaves out the application log and the notes, tags, commands and SNMP communities of the hosts."
+    ));
+    ui.add_space(4.0);
+
+    if ui.button(tr!("Create diagnostic bundle")).clicked() {
+        let dir = dirs::home_dir().unwrap_or_else(std::env::temp_dir);
+        let result = {
+            let state_lock = app_state.lock().expect("State mutex poisoned");
+            create_diagnostic_bundle(&state_lock, state.anonymize_bundle, &dir)
+        };
+        state.bundle_status = match result {
+            Ok(path) => format!("{}: {}", tr!("Saved to"), path.display()),
+            Err(e) => format!("{}: {}", tr!("Error"), e),
+        };
+    }
+
+    if !state.bundle_status.is_empty() {
+        ui.add_space(4.0);
+        ui.label(RichText::new(&state.bundle_status).monospace());
+    }
+}
+
+/// Renders the "Export" tab with CSV/JSON statistics export.
+fn render_export_tab(ui: &mut egui::Ui, state: &mut SystemToolsState, app_state: &SharedState) {
+    ui.label(
+        RichText::new(tr!(
+            "Exports per-host statistics (CSV and JSON) and the recent ping results (CSV) into a new folder in your home directory."
+        ))
+        .weak(),
+    );
+    ui.add_space(4.0);
+    ui.checkbox(
+        &mut state.anonymize_export,
+        tr!("Anonymize host names and addresses"),
+    )
+    .on_hover_text(tr!(
+        "Each host and traceroute hop gets the same pseudonym in every exported file, so the data can be shared publicly without revealing your network"
+    ));
+    ui.add_space(4.0);
+
+    if ui.button(tr!("Export statistics")).clicked() {
+        let dir = dirs::home_dir().unwrap_or_else(std::env::temp_dir);
+        let result = {
+            let state_lock = app_state.lock().expect("State mutex poisoned");
+            export_statistics(&state_lock, state.anonymize_export, &dir)
+        };
+        state.export_status = match result {
+            Ok(path) => format!("{}: {}", tr!("Saved to"), path.display()),
+            Err(e) => format!("{}: {}", tr!("Error"), e),
+        };
+    }
+
+    if !state.export_status.is_empty() {
+        ui.add_space(4.0);
+        ui.label(RichText::new(&state.export_status).monospace());
+    }
+}
+
+/// Renders the "ISP SLA" tab: contractual targets, the violation log and the complaint summary.
+fn render_sla_tab(ui: &mut egui::Ui, state: &mut SystemToolsState, app_state: &SharedState) {
+    let mut state_lock = app_state.lock().expect("State mutex poisoned");
+    let app = &mut *state_lock;
+    ui.label(
+        RichText::new(tr!(
+            "Enter the service levels promised by your ISP. They are compared with the persisted history, period by period."
+        ))
+        .weak(),
+    );
+    if !app.settings.persistent_history {
+        ui.colored_label(
+            egui::Color32::from_rgb(213, 94, 0),
+            tr!("Enable \"Keep long-term history on disk\" in the settings to record the history to compare."),
+        );
+    }
+    ui.add_space(4.0);
+
+    let hosts = &app.hosts;
+    let sla_settings = &mut app.settings.sla;
+    egui::Grid::new("sla_contract")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label(tr!("ISP:"));
+            ui.text_edit_singleline(&mut sla_settings.isp);
+            ui.end_row();
+            ui.label(tr!("Contract number:"));
+            ui.text_edit_singleline(&mut sla_settings.contract);
+            ui.end_row();
+        });
+
+    let mut remove = None;
+    for (i, target) in sla_settings.targets.iter_mut().enumerate() {
+        ui.push_id(("sla_target", i), |ui| {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("host")
+                    .selected_text(if target.address.is_empty() {
+                        tr!("Select a host")
+                    } else {
+                        target.address.clone()
+                    })
+                    .show_ui(ui, |ui| {
+                        for host in hosts {
+                            let label = if host.name.is_empty() {
+                                host.address.clone()
+                            } else {
+                                format!("{} ({})", host.name, host.address)
+                            };
+                            ui.selectable_value(&mut target.address, host.address.clone(), label);
+                        }
+                    });
+                egui::ComboBox::from_id_salt("metric")
+                    .selected_text(target.metric.label())
+                    .show_ui(ui, |ui| {
+                        for metric in SlaMetric::ALL {
+                            ui.selectable_value(&mut target.metric, metric, metric.label());
+                        }
+                    });
+                ui.label("≤");
+                ui.add(
+                    egui::DragValue::new(&mut target.limit)
+                        .range(0.0..=10_000.0)
+                        .speed(0.1)
+                        .suffix(format!(" {}", target.metric.unit())),
+                );
+                egui::ComboBox::from_id_salt("window")
+                    .selected_text(target.window.label())
+                    .show_ui(ui, |ui| {
+                        for window in SlaWindow::ALL {
+                            ui.selectable_value(&mut target.window, window, window.label());
+                        }
+                    });
+                if ui.button("🗑").on_hover_text(tr!("Remove")).clicked() {
+                    remove = Some(i);
+                }
+            });
+        });
+    }
+    if let Some(i) = remove {
+        sla_settings.targets.remove(i);
+    }
+    let mut run_check = false;
+    ui.horizontal(|ui| {
+        if ui.button(tr!("Add target")).clicked() {
+            sla_settings.targets.push(SlaTarget::default());
+        }
+        ui.separator();
+        ui.label(tr!("Check the last"));
+        ui.add(
+            egui::DragValue::new(&mut state.sla_days)
+                .range(1..=400)
+                .suffix(tr!(" days")),
+        );
+        run_check = ui.button(tr!("Check")).clicked();
+    });
+    if run_check {
+        let to = chrono::Utc::now().timestamp() as u64;
+        let from = to.saturating_sub(state.sla_days * 86_400);
+        let reports = sla::check(app, &app.settings.sla, from);
+        state.sla_complaint = sla::complaint(&app.settings.sla, &reports, from, to);
+        state.sla_reports = Some((from, to, reports));
+    }
+
+    let Some((_, _, reports)) = &state.sla_reports else {
+        return;
+    };
+    ui.separator();
+    ui.strong(tr!("Violation log"));
+    egui::ScrollArea::vertical()
+        .id_salt("sla_violations")
+        .max_height(160.0)
+        .show(ui, |ui| {
+            egui::Grid::new("sla_violation_grid")
+                .striped(true)
+                .num_columns(4)
+                .show(ui, |ui| {
+                    ui.strong(tr!("Target"));
+                    ui.strong(tr!("From"));
+                    ui.strong(tr!("Duration"));
+                    ui.strong(tr!("Worst value"));
+                    ui.end_row();
+                    for report in reports {
+                        for v in &report.violations {
+                            ui.label(format!("{}: {}", report.host, report.target.describe()));
+                            ui.label(format_time(v.start));
+                            ui.label(format_duration(v.duration()));
+                            ui.label(format!("{:.2} {}", v.worst, report.target.metric.unit()));
+                            ui.end_row();
+                        }
+                    }
+                });
+            if reports.iter().all(|r| r.violations.is_empty()) {
+                ui.label(tr!("No violations were recorded."));
+            }
+        });
+
+    ui.separator();
+    ui.horizontal(|ui| {
+        ui.strong(tr!("Complaint summary"));
+        if ui.button(tr!("Copy")).clicked() {
+            ui.ctx().copy_text(state.sla_complaint.clone());
+        }
+    });
+    egui::ScrollArea::vertical()
+        .id_salt("sla_complaint")
+        .max_height(200.0)
+        .show(ui, |ui| {
+            ui.add(
+                egui::TextEdit::multiline(&mut state.sla_complaint)
+                    .desired_width(f32::INFINITY)
+                    .font(egui::TextStyle::Monospace),
+            );
+        });
+}
+
+/// Renders the "Report" tab: availability per host and period from the persisted history,
+/// with HTML and PDF export.
+fn render_report_tab(ui: &mut egui::Ui, state: &mut SystemToolsState, app_state: &SharedState) {
+    ui.label(
+        RichText::new(tr!(
+            "Availability, mean and p95 RTT, outages and downtime per host and period, computed from the persisted history."
+        ))
+        .weak(),
+    );
+    if !app_state
+        .lock()
+        .expect("State mutex poisoned")
+        .settings
+        .persistent_history
+    {
+        ui.colored_label(
+            egui::Color32::from_rgb(213, 94, 0),
+            tr!("Enable \"Keep long-term history on disk\" in the settings to record the history to report on."),
+        );
+    }
+    ui.add_space(4.0);
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt("report_window")
+            .selected_text(state.report_window.label())
+            .show_ui(ui, |ui| {
+                for window in REPORT_PERIODS {
+                    ui.selectable_value(&mut state.report_window, window, window.label());
+                }
+            });
+        ui.label(tr!("Periods:"));
+        ui.add(egui::DragValue::new(&mut state.report_periods).range(1..=31))
+            .on_hover_text(tr!("Number of periods up to the current one"));
+        if ui.button(tr!("Generate")).clicked() {
+            let now = chrono::Utc::now().timestamp() as u64;
+            let hosts = app_state
+                .lock()
+                .expect("State mutex poisoned")
+                .hosts
+                .clone();
+            state.report = Some(report::build(
+                &hosts,
+                state.report_window,
+                state.report_periods,
+                now,
+            ));
+            state.report_status.clear();
+        }
+    });
+
+    let Some(generated) = &state.report else {
+        return;
+    };
+    ui.separator();
+    if generated.rows.is_empty() {
+        ui.label(tr!("No history recorded in this time span."));
+        return;
+    }
+    egui::ScrollArea::both()
+        .id_salt("report_table")
+        .max_height(260.0)
+        .show(ui, |ui| {
+            egui::Grid::new("report_grid")
+                .striped(true)
+                .num_columns(8)
+                .show(ui, |ui| {
+                    for header in report::headers() {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+                    for row in &generated.rows {
+                        for cell in report::cells(row) {
+                            ui.label(cell);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+    ui.add_space(4.0);
+    ui.horizontal(|ui| {
+        let mut export = None;
+        if ui.button(tr!("Export HTML")).clicked() {
+            export = Some("html");
+        }
+        if ui
+            .button(tr!("Export PDF"))
+            .on_hover_text(tr!(
+                "The PDF uses a standard font covering Latin letters only; export HTML and print it from a browser for other scripts"
+            ))
+            .clicked()
+        {
+            export = Some("pdf");
+        }
+        if ui.button(tr!("Copy")).clicked() {
+            ui.ctx()
+                .copy_text(report::text_table(generated).join("\n"));
+        }
+        if let Some(extension) = export {
+            let dir = dirs::home_dir().unwrap_or_else(std::env::temp_dir);
+            state.report_status = match report::save(generated, extension, &dir) {
+                Ok(path) => format!("{}: {}", tr!("Saved to"), path.display()),
+                Err(e) => format!("{}: {}", tr!("Error"), e),
+            };
+        }
+    });
+    if !state.report_status.is_empty() {
+        ui.add_space(4.0);
+        ui.label(RichText::new(&state.report_status).monospace());
+    }
+}
+
+/// Adds a host that is fed only from an imported log, unless it already exists.
+fn ensure_import_host(app_state: &SharedState, address: &str, path: &std::path::Path) {
+    let mut state_lock = app_state.lock().expect("State mutex poisoned");
+    if state_lock.hosts.iter().any(|h| h.address == address) {
+        return;
+    }
+    state_lock
+        .statuses
+        .insert(address.to_string(), HostStatus::default());
+    let thresholds = state_lock.settings.default_thresholds;
+    state_lock.hosts.push(HostInfo {
+        name: path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        address: address.to_string(),
+        passive: true,
+        thresholds,
+        ..Default::default()
+    });
+}
+
+/// Renders the "Import Log" tab: one-off import and live following of external ping logs.
+/// Adds an imported or followed log to the recent files of the File menu.
+fn remember_capture(app_state: &SharedState, path: &str, address: &str) {
+    let mut state = app_state.lock().expect("State mutex poisoned");
+    state.settings.remember_file(RecentFile {
+        path: path.to_string(),
+        kind: RecentFileKind::Capture,
+        address: address.to_string(),
+    });
+}
+
+fn render_import_tab(ui: &mut egui::Ui, state: &mut SystemToolsState, app_state: &SharedState) {
+    ui.label(
+        RichText::new(tr!(
+            "Reads the output of ping, fping or Smokeping (rrdtool fetch) and adds it to the statistics of a host. Following a file reads new lines as they are written, like tail -f."
+        ))
+        .weak(),
+    );
+    ui.add_space(4.0);
+
+    egui::Grid::new("import_grid")
+        .num_columns(2)
+        .spacing([8.0, 4.0])
+        .show(ui, |ui| {
+            ui.label(tr!("Log file:"));
+            ui.add(
+                egui::TextEdit::singleline(&mut state.import_path)
+                    .hint_text("/var/log/ping.log")
+                    .desired_width(350.0),
+            );
+            ui.end_row();
+
+            ui.label(tr!("Format:"));
+            egui::ComboBox::from_id_salt("import_format_combo")
+                .selected_text(state.import_format.label())
+                .show_ui(ui, |ui| {
+                    for format in ImportFormat::ALL {
+                        ui.selectable_value(&mut state.import_format, format, format.label());
+                    }
+                });
+            ui.end_row();
+
+            ui.label(tr!("Host address:"));
+            ui.add(
+                egui::TextEdit::singleline(&mut state.import_address)
+                    .hint_text("8.8.8.8")
+                    .desired_width(350.0),
+            );
+            ui.end_row();
+        });
+
+    let path = std::path::PathBuf::from(state.import_path.trim());
+    let address = HostInfo::normalize_address(&state.import_address);
+    let ready = !state.import_path.trim().is_empty() && !address.is_empty();
+
+    ui.add_space(4.0);
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(ready, egui::Button::new(tr!("Import")))
+            .clicked()
+        {
+            state.import_status = match import::read_file(&path, state.import_format) {
+                Ok(samples) => {
+                    ensure_import_host(app_state, &address, &path);
+                    remember_capture(app_state, state.import_path.trim(), &address);
+                    let count = import::add_samples(app_state, &address, &samples);
+                    tr!("Imported {count} pings into {host}")
+                        .replace("{count}", &count.to_string())
+                        .replace("{host}", &address)
+                }
+                Err(e) => format!("{}: {}", tr!("Error"), e),
+            };
+        }
+        if ui
+            .add_enabled(ready, egui::Button::new(tr!("Follow")))
+            .on_hover_text(tr!("The host is not pinged while its log is followed"))
+            .clicked()
+        {
+            ensure_import_host(app_state, &address, &path);
+            remember_capture(app_state, state.import_path.trim(), &address);
+            if let Some(h) = app_state
+                .lock()
+                .expect("State mutex poisoned")
+                .hosts
+                .iter_mut()
+                .find(|h| h.address == address)
+            {
+                h.passive = true;
+            }
+            state.followers.push(LogFollower::start(
+                app_state.clone(),
+                path.clone(),
+                address.clone(),
+                state.import_format,
+            ));
+            state.import_status.clear();
+        }
+    });
+
+    if !state.import_status.is_empty() {
+        ui.add_space(4.0);
+        ui.label(RichText::new(&state.import_status).monospace());
+    }
+
+    if !state.followers.is_empty() {
+        ui.add_space(8.0);
+        ui.strong(tr!("Followed logs"));
+        let mut stopped = None;
+        for (i, follower) in state.followers.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new(format!(
+                        "{} → {}",
+                        follower.path.display(),
+                        follower.address
+                    ))
+                    .monospace(),
+                );
+                if follower.is_running() {
+                    if ui.button(tr!("Stop")).clicked() {
+                        stopped = Some(i);
+                    }
+                } else {
+                    ui.label(RichText::new(tr!("stopped (see application log)")).weak());
+                    if ui.button(tr!("Remove")).clicked() {
+                        stopped = Some(i);
+                    }
+                }
+            });
+        }
+        if let Some(i) = stopped {
+            state.followers.remove(i);
+        }
+    }
+}
+
 /// Renders the troubleshooting guide tab.
 fn render_guide(ui: &mut egui::Ui) {
     egui::ScrollArea::vertical().show(ui, |ui| {
@@ -559,6 +1137,7 @@ fn render_guide(ui: &mut egui::Ui) {
         ui.label(tr!("If you can ping 8.8.8.8 in the main window but cannot open websites:"));
         ui.label(tr!("  • Run 'DNS Configuration' — verify your DNS servers are correctly set. Common public DNS: 8.8.8.8, 1.1.1.1, 9.9.9.9."));
         ui.label(tr!("  • Run 'DNS Lookup (google.com)' — if it fails or returns wrong IPs, your DNS server may be down or misconfigured."));
+        ui.label(tr!("  • Add a host like dns://1.1.1.1/example.com in the main window to track how long your DNS server takes to answer, next to the ping latency."));
         if cfg!(target_os = "windows") {
             ui.label(tr!("  • Run 'DNS Cache' — check for stale entries. You can flush the cache with: ipconfig /flushdns (requires admin)."));
         }
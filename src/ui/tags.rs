@@ -0,0 +1,38 @@
+use eframe::egui;
+use eframe::egui::{Color32, RichText};
+
+/// Colors of the tag chips, from the color-blind safe palette of the status colors.
+const TAG_COLORS: [Color32; 7] = [
+    Color32::from_rgb(0, 114, 178),
+    Color32::from_rgb(230, 159, 0),
+    Color32::from_rgb(0, 158, 115),
+    Color32::from_rgb(204, 121, 167),
+    Color32::from_rgb(86, 180, 233),
+    Color32::from_rgb(213, 94, 0),
+    Color32::from_rgb(240, 228, 66),
+];
+
+/// Color of a tag; the same tag gets the same color on every row and every run.
+pub fn tag_color(tag: &str) -> Color32 {
+    let hash = tag
+        .bytes()
+        .fold(2166136261u32, |h, b| (h ^ b as u32).wrapping_mul(16777619));
+    TAG_COLORS[hash as usize % TAG_COLORS.len()]
+}
+
+/// Renders a tag as a small colored chip; `selected` outlines it.
+pub fn tag_chip(ui: &mut egui::Ui, tag: &str, selected: bool) -> egui::Response {
+    let text = RichText::new(tag).small().color(Color32::BLACK);
+    let stroke = if selected {
+        egui::Stroke::new(1.5, ui.visuals().strong_text_color())
+    } else {
+        egui::Stroke::NONE
+    };
+    ui.add(
+        egui::Button::new(text)
+            .fill(tag_color(tag))
+            .stroke(stroke)
+            .corner_radius(8.0)
+            .small(),
+    )
+}
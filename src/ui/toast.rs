@@ -0,0 +1,29 @@
+use eframe::egui;
+use eframe::egui::{Color32, RichText};
+
+/// Shows `lines` in a small box in the bottom right corner of the window.
+///
+/// Returns whether the box was closed.
+pub fn render_toast(ctx: &egui::Context, lines: &[String]) -> bool {
+    let mut closed = false;
+    egui::Area::new(egui::Id::new("toast"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-12.0, -12.0])
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            let color = Color32::from_rgb(230, 159, 0);
+            egui::Frame::popup(ui.style())
+                .stroke(egui::Stroke::new(1.0, color))
+                .show(ui, |ui| {
+                    ui.set_max_width(420.0);
+                    ui.horizontal(|ui| {
+                        ui.vertical(|ui| {
+                            for line in lines {
+                                ui.label(RichText::new(format!("📈 {}", line)).color(color));
+                            }
+                        });
+                        closed = ui.small_button("✖").clicked();
+                    });
+                });
+        });
+    closed
+}
@@ -0,0 +1,122 @@
+use crate::app::PingVisuals;
+use crate::logic::live_trace::{self, LiveTrace};
+use eframe::egui;
+use eframe::egui::{Color32, RichText};
+use std::time::Duration;
+use tr::tr;
+
+/// Formats an optional RTT in ms for the hop table.
+fn ms(value: Option<f64>) -> String {
+    value
+        .map(|v| format!("{:.1}", v))
+        .unwrap_or_else(|| "—".to_string())
+}
+
+/// Renders the live trace window with per-hop latency and loss, updating every round.
+pub fn render_trace_window(
+    ctx: &egui::Context,
+    visuals: &PingVisuals,
+    trace: &mut Option<LiveTrace>,
+) {
+    let Some(running) = trace.as_ref() else {
+        return;
+    };
+    let target = running.target.clone();
+    let progress = running.progress();
+    let mut open = true;
+    let mut restart = false;
+
+    egui::Window::new(format!("{} - {}", tr!("Trace"), target))
+        .open(&mut open)
+        .resizable(true)
+        .default_width(520.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                match progress.distance {
+                    Some(d) => ui.label(
+                        tr!("Target reached in {hops} hops").replace("{hops}", &d.to_string()),
+                    ),
+                    None if progress.discovering => ui.label(tr!("Discovering route...")),
+                    None => ui.label(tr!("Target does not answer TTL-limited probes")),
+                };
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("🔄").on_hover_text(tr!("Restart")).clicked() {
+                        restart = true;
+                    }
+                });
+            });
+            if let Some(e) = &progress.error {
+                ui.colored_label(Color32::from_rgb(213, 94, 0), e);
+                return;
+            }
+            if progress.discovering {
+                ui.spinner();
+                return;
+            }
+
+            let worst = live_trace::worst_hop(&progress.hops);
+            ui.separator();
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("live_trace_hops")
+                    .striped(true)
+                    .num_columns(8)
+                    .show(ui, |ui| {
+                        for header in [
+                            "#",
+                            &tr!("Address"),
+                            &tr!("Loss"),
+                            &tr!("Sent"),
+                            &tr!("Last"),
+                            &tr!("Avg"),
+                            &tr!("Best"),
+                            &tr!("Worst"),
+                        ] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+
+                        for (i, hop) in progress.hops.iter().enumerate() {
+                            ui.label(format!("{}.", i + 1));
+                            let address = ui.monospace(&hop.address);
+                            if let Some((_, added)) = worst.filter(|(w, _)| *w == i) {
+                                address.on_hover_text(
+                                    tr!(
+                                        "Adds {ms} ms on average; the largest increase on the route"
+                                    )
+                                    .replace("{ms}", &format!("{:.1}", added)),
+                                );
+                            }
+                            let loss = hop.loss_pct();
+                            let loss_text = RichText::new(format!("{:.1}%", loss));
+                            ui.label(if loss > 0.0 {
+                                loss_text.color(Color32::from_rgb(213, 94, 0))
+                            } else {
+                                loss_text
+                            });
+                            ui.label(hop.sent.to_string());
+                            ui.label(ms(hop.last));
+                            let avg = RichText::new(ms(hop.avg()));
+                            ui.label(match hop.avg() {
+                                Some(a) if worst.is_some_and(|(w, _)| w == i) => {
+                                    avg.color(visuals.latency_color(a)).strong()
+                                }
+                                Some(a) => avg.color(visuals.latency_color(a)),
+                                None => avg,
+                            });
+                            let received = hop.sent > hop.lost;
+                            ui.label(ms(received.then_some(hop.best)));
+                            ui.label(ms(received.then_some(hop.worst)));
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+
+    if !open {
+        *trace = None;
+    } else if restart {
+        *trace = Some(LiveTrace::start(target));
+    } else {
+        ctx.request_repaint_after(Duration::from_millis(250));
+    }
+}
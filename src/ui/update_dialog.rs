@@ -0,0 +1,89 @@
+use crate::logic::updates::{UpdateCheck, UpdateChecker};
+use crate::model::ProxySettings;
+use eframe::egui;
+use eframe::egui::RichText;
+use tr::tr;
+
+/// Renders the result of an update check: changelog and download links of a new release.
+pub fn render_update_dialog(
+    ctx: &egui::Context,
+    updates: &mut UpdateChecker,
+    proxy: &ProxySettings,
+) {
+    let Some(result) = updates.result.clone() else {
+        return;
+    };
+
+    let mut open = true;
+    let mut close_clicked = false;
+    egui::Window::new(tr!("Software Update"))
+        .open(&mut open)
+        .resizable(true)
+        .default_width(450.0)
+        .show(ctx, |ui| {
+            match &result {
+                UpdateCheck::UpToDate => {
+                    ui.label(
+                        tr!("You are using the latest version ({version}).")
+                            .replace("{version}", env!("CARGO_PKG_VERSION")),
+                    );
+                }
+                UpdateCheck::Failed(e) => {
+                    ui.label(format!("{}: {}", tr!("Update check failed"), e));
+                }
+                UpdateCheck::Available(release) => {
+                    ui.heading(
+                        tr!("Version {new} is available (you have {current})")
+                            .replace("{new}", &release.version)
+                            .replace("{current}", env!("CARGO_PKG_VERSION")),
+                    );
+                    ui.add_space(8.0);
+                    ui.strong(tr!("What's new:"));
+                    egui::ScrollArea::vertical()
+                        .max_height(250.0)
+                        .show(ui, |ui| {
+                            ui.label(RichText::new(&release.changelog).monospace());
+                        });
+                    ui.add_space(8.0);
+                    if !release.page_url.is_empty() {
+                        ui.hyperlink_to(tr!("Open release page"), &release.page_url);
+                    }
+                    if let Some(url) = &release.download_url {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(
+                                    !updates.is_downloading,
+                                    egui::Button::new(tr!("Download")),
+                                )
+                                .clicked()
+                            {
+                                let dir = dirs::download_dir()
+                                    .or_else(dirs::home_dir)
+                                    .unwrap_or_else(std::env::temp_dir);
+                                updates.start_download(url.clone(), dir, proxy.clone());
+                            }
+                            if updates.is_downloading {
+                                ui.spinner();
+                            }
+                        });
+                        match &updates.download_result {
+                            Some(Ok(path)) => {
+                                ui.label(format!("{}: {}", tr!("Saved to"), path.display()));
+                            }
+                            Some(Err(e)) => {
+                                ui.label(format!("{}: {}", tr!("Error"), e));
+                            }
+                            None => {}
+                        }
+                    }
+                }
+            }
+            ui.add_space(8.0);
+            close_clicked = ui.button(tr!("Close")).clicked();
+        });
+
+    if !open || close_clicked {
+        updates.result = None;
+        updates.download_result = None;
+    }
+}
@@ -0,0 +1,38 @@
+use crate::logic::watchdog::RestartNotice;
+use eframe::egui;
+use eframe::egui::{Color32, RichText};
+use tr::tr;
+
+/// Shows a warning after the watchdog had to restart the background pinger.
+pub fn render_watchdog_banner(ui: &mut egui::Ui, notice: Option<&RestartNotice>) {
+    let Some(notice) = notice else {
+        return;
+    };
+
+    let color = Color32::from_rgb(213, 94, 0);
+    egui::Frame::new()
+        .stroke(egui::Stroke::new(1.0, color))
+        .inner_margin(6.0)
+        .corner_radius(4.0)
+        .show(ui, |ui| {
+            ui.colored_label(
+                color,
+                RichText::new(
+                    tr!("Monitoring was restarted at {time}: {reason}.")
+                        .replace("{time}", &notice.at.format("%H:%M:%S").to_string())
+                        .replace("{reason}", &notice.reason),
+                )
+                .strong(),
+            );
+            let mut details = tr!("Statistics may have a gap around this time.");
+            if notice.count > 1 {
+                details.push(' ');
+                details.push_str(
+                    &tr!("Restarts since start: {count}.")
+                        .replace("{count}", &notice.count.to_string()),
+                );
+            }
+            ui.label(details);
+        });
+    ui.add_space(4.0);
+}
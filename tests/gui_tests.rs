@@ -22,6 +22,7 @@ fn make_state_with_host(name: &str, address: &str, mode: PingMode) -> (Arc<Mutex
             log_to_file: false,
             log_file_path: String::new(),
             is_stopped: false,
+            ..Default::default()
         });
         s.statuses
             .insert(address.to_string(), HostStatus::default());
@@ -54,12 +55,14 @@ fn make_state_with_active_host(name: &str, address: &str, rtt: f64) -> Arc<Mutex
                 show_p95: true,
                 show_min_max: true,
                 show_loss: true,
+                ..Default::default()
             },
             packet_size: 64,
             random_padding: true,
             log_to_file: false,
             log_file_path: String::new(),
             is_stopped: false,
+            ..Default::default()
         });
         let status = HostStatus {
             alive: true,
@@ -145,6 +148,7 @@ fn test_status_display_updates() {
             log_to_file: false,
             log_file_path: String::new(),
             is_stopped: false,
+            ..Default::default()
         });
         let status = HostStatus {
             alive: true,
@@ -177,6 +181,7 @@ fn test_stop_start_flow() {
             log_to_file: false,
             log_file_path: String::new(),
             is_stopped: false,
+            ..Default::default()
         });
         let status = HostStatus {
             sent: 10,
@@ -415,6 +420,27 @@ fn test_cancel_deletion() {
     assert_eq!(state.lock().unwrap().hosts.len(), 1);
 }
 
+#[test]
+fn test_deleted_host_history_is_retained() {
+    let (state, _) = make_state_with_host("Test", "1.2.3.4", PingMode::Fast);
+
+    let mut app = EguiPinger::from_state(state.clone());
+    let mut harness = Harness::new(|ctx| app.ui_layout(ctx));
+    harness.set_size(egui::vec2(1200.0, 800.0));
+    harness.run();
+
+    harness.get_by_label("x").click();
+    harness.run();
+
+    harness.get_by_label(&tr!("Delete")).click();
+    harness.run();
+
+    let s = state.lock().unwrap();
+    assert!(s.hosts.is_empty());
+    assert_eq!(s.deleted_hosts.len(), 1);
+    assert_eq!(s.deleted_hosts[0].host.address, "1.2.3.4");
+}
+
 // === Multiple hosts ===
 
 #[test]
@@ -689,10 +715,12 @@ fn test_system_tools_window_opens() {
     let state = Arc::new(Mutex::new(AppState::default()));
     let mut app = EguiPinger::from_state(state);
 
-    // 1. Click the 🔧 button to open System Tools
+    // 1. Open System Tools from Tools → System commands
     {
         let mut harness = Harness::new(|ctx| app.ui_layout(ctx));
-        harness.get_by_label("🔧").click();
+        harness.get_by_label(&tr!("Tools")).click();
+        harness.run();
+        harness.get_by_label(&tr!("System commands")).click();
         harness.run();
     }
 